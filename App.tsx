@@ -14,10 +14,27 @@ import {
   HistoryMessageRecord,
   NativeAppConfig,
   PayloadTemplate,
+  RetainedSnapshotImportResult,
+  PinnedMessageRecord,
+  ExportSettings,
+  SessionParameters,
+  ServerRedirect,
   ConnectionTopicDocument,
   TopicCatalogFile,
   TopicCatalogItem,
   TopicDirection,
+  StorageStatus,
+  PublishAckPayload,
+  SubscriptionResultPayload,
+  SubscribeOptions,
+  SubscriptionPreset,
+  SubscriptionPresetsPayload,
+  HistoryPauseStatus,
+  HistoryRepair,
+  PeekEndedPayload,
+  CredentialExpiry,
+  ConnectionDuplicate,
+  UserProperty,
 } from './types';
 import {
   DEFAULT_PROFILE,
@@ -27,7 +44,7 @@ import {
   STORAGE_THEME_KEY,
   getRandomColor,
 } from './constants';
-import { invokeCommand, isTauriRuntime, listenEvent } from './services/tauriBridge';
+import { invokeCommand, isTauriRuntime, listenEvent, runJob } from './services/tauriBridge';
 import { SUPPORTED_LANGUAGES, type SupportedLanguage } from './i18n';
 import ConnectionModal from './components/ConnectionModal';
 import SettingsModal from './components/SettingsModal';
@@ -35,7 +52,7 @@ import ConnectionItem from './components/ConnectionItem';
 import TopicWorkbench from './components/TopicWorkbench';
 import MessageLog from './components/MessageLog';
 import SimpleInputModal from './components/SimpleInputModal';
-import HistoryExportModal, { HistoryExportRequest } from './components/HistoryExportModal';
+import HistoryExportModal, { HistoryExportFormat, HistoryExportRequest } from './components/HistoryExportModal';
 import AboutModal from './components/AboutModal';
 
 interface ImportPayload {
@@ -57,11 +74,13 @@ interface MqttStatusEvent {
   connectionId: string;
   status: ConnectionStatus | string;
   lastError?: string;
+  session?: SessionParameters;
+  redirect?: ServerRedirect;
 }
 
 interface MqttBatchEvent {
   connectionId: string;
-  messages: Array<{ topic: string; payload: string; qos: number; retain: boolean; direction?: 'in' | 'out'; timestamp: number }>;
+  messages: Array<{ topic: string; payload: string; qos: number; retain: boolean; direction?: 'in' | 'out'; timestamp: number; userProperties?: UserProperty[]; isBinary?: boolean }>;
 }
 
 type ThemeMode = 'light' | 'dark';
@@ -117,9 +136,12 @@ const TOPIC_AI_SOURCE_MAX_CHARS = 24000;
 const ZIP_EOCD_SIGNATURE = 0x06054b50;
 const ZIP_CENTRAL_DIRECTORY_SIGNATURE = 0x02014b50;
 const ZIP_LOCAL_FILE_HEADER_SIGNATURE = 0x04034b50;
+const PEEK_MAX_MESSAGES = 10;
+const PEEK_DURATION_SECS = 30;
 
 const normalizeQos = (qos: number): 0 | 1 | 2 => (qos === 1 || qos === 2 ? qos : 0);
-const normalizeStatus = (status: string): ConnectionStatus => (['disconnected', 'connecting', 'connected', 'error'].includes(status) ? (status as ConnectionStatus) : 'error');
+
+const normalizeStatus = (status: string): ConnectionStatus => (['disconnected', 'connecting', 'connected', 'error', 'takenOver'].includes(status) ? (status as ConnectionStatus) : 'error');
 const normalizeDirection = (value: unknown): TopicDirection =>
   value === 'publish' || value === 'subscribe' || value === 'both' ? value : 'publish';
 
@@ -543,6 +565,8 @@ const toViewMessages = (items: HistoryMessageRecord[]): Message[] =>
     retain: item.retain,
     direction: item.direction,
     timestamp: item.timestamp,
+    userProperties: item.userProperties,
+    isBinary: item.isBinary,
   }));
 
 const trimMessageWindow = (messages: Message[], keepFrom: 'start' | 'end') => {
@@ -600,6 +624,8 @@ const App: React.FC = () => {
   const [theme, setTheme] = useState<ThemeMode>(() => getInitialTheme());
   const themeGuardRef = useRef(false);
   const [publisherTemplates, setPublisherTemplates] = useState<PayloadTemplate[]>([]);
+  const [storageStatus, setStorageStatus] = useState<StorageStatus | null>(null);
+  const [exportSettings, setExportSettings] = useState<ExportSettings>({});
   const [connectionTopicDocs, setConnectionTopicDocs] = useState<Record<string, ConnectionTopicDocument>>({});
   const [isConfigLoaded, setIsConfigLoaded] = useState(false);
   const [startupPhase, setStartupPhase] = useState<StartupPhase>('boot');
@@ -609,12 +635,13 @@ const App: React.FC = () => {
   const [confirmDialog, setConfirmDialog] = useState<ConfirmDialogState | null>(null);
   const [activities, setActivities] = useState<ActivityNotice[]>([]);
   const [logMessages, setLogMessages] = useState<Message[]>([]);
+  const [pinnedMessages, setPinnedMessages] = useState<PinnedMessageRecord[]>([]);
   const [oldestCursor, setOldestCursor] = useState<{ timestamp: number; id: number } | null>(null);
   const [hasMoreOlder, setHasMoreOlder] = useState(false);
   const [isLoadingOlder, setIsLoadingOlder] = useState(false);
   const [isExportModalOpen, setIsExportModalOpen] = useState(false);
   const [lastExportPath, setLastExportPath] = useState('');
-  const [lastExportFormat, setLastExportFormat] = useState<'ndjson' | 'csv'>('ndjson');
+  const [lastExportFormat, setLastExportFormat] = useState<HistoryExportFormat>('ndjson');
   const [isExportingHistory, setIsExportingHistory] = useState(false);
   const [topicImportTargetId, setTopicImportTargetId] = useState<string | null>(null);
   const [topicAiImportTargetId, setTopicAiImportTargetId] = useState<string | null>(null);
@@ -623,6 +650,7 @@ const App: React.FC = () => {
 
   const fileInputRef = useRef<HTMLInputElement>(null);
   const topicFileInputRef = useRef<HTMLInputElement>(null);
+  const snapshotFileInputRef = useRef<HTMLInputElement>(null);
   const topicAiFileInputRef = useRef<HTMLInputElement>(null);
   const lastSavedConfigRef = useRef('');
   const toastTimersRef = useRef<number[]>([]);
@@ -762,6 +790,33 @@ const App: React.FC = () => {
     }
   };
 
+  const loadPinnedMessages = async (connectionId: string) => {
+    if (!isTauriRuntime()) {
+      setPinnedMessages([]);
+      return;
+    }
+    try {
+      const rows = await invokeCommand<PinnedMessageRecord[]>('history_query_pinned', { connectionId });
+      if (activeIdRef.current === connectionId) {
+        setPinnedMessages(rows);
+      }
+    } catch (error) {
+      console.error('Failed to load pinned messages', error);
+      setPinnedMessages([]);
+    }
+  };
+
+  const togglePinnedMessage = async (historyId: number, pin: boolean) => {
+    const connectionId = activeIdRef.current;
+    if (!connectionId) return;
+    try {
+      await invokeCommand<void>(pin ? 'history_pin' : 'history_unpin', { connectionId, messageId: historyId });
+      await loadPinnedMessages(connectionId);
+    } catch (error) {
+      pushToast(error instanceof Error ? error.message : t('messageLog.pinFailed'), 'error');
+    }
+  };
+
   const loadOlderHistory = async () => {
     if (!activeIdRef.current || !oldestCursor || isLoadingOlder) {
       return;
@@ -858,6 +913,7 @@ const App: React.FC = () => {
         setAiConfig({ ...DEFAULT_AI_CONFIG, ...(loaded.aiConfig || {}) });
         setAiPrompts(normalizeAiPrompts(loaded.aiPrompts));
         setPublisherTemplates(Array.isArray(loaded.publisherTemplates) ? loaded.publisherTemplates : []);
+        setExportSettings({ exportDirectory: loaded.exportDirectory, exportFilenameTemplate: loaded.exportFilenameTemplate, exportTimeZone: loaded.exportTimeZone });
         setConnectionTopicDocs(
           normalizeTopicDocumentMap(loaded.connectionTopicDocs, loadedConnectionIds)
         );
@@ -935,14 +991,17 @@ const App: React.FC = () => {
     }
     if (!activeId) {
       setLogMessages([]);
+      setPinnedMessages([]);
       setOldestCursor(null);
       setHasMoreOlder(false);
       return;
     }
     setLogMessages([]);
+    setPinnedMessages([]);
     setOldestCursor(null);
     setHasMoreOlder(false);
     void loadLatestHistory(activeId);
+    void loadPinnedMessages(activeId);
   }, [activeId, isConfigLoaded]);
 
   useEffect(() => {
@@ -988,8 +1047,10 @@ const App: React.FC = () => {
       language: currentLanguage,
       theme,
       activeConnectionId: activeId || undefined,
-      publisherTemplates,
       connectionTopicDocs,
+      exportDirectory: exportSettings.exportDirectory?.trim() || undefined,
+      exportFilenameTemplate: exportSettings.exportFilenameTemplate?.trim() || undefined,
+      exportTimeZone: exportSettings.exportTimeZone?.trim() || undefined,
     };
 
     const serializedConfig = JSON.stringify(config);
@@ -1019,8 +1080,8 @@ const App: React.FC = () => {
     currentLanguage,
     theme,
     activeId,
-    publisherTemplates,
     connectionTopicDocs,
+    exportSettings,
   ]);
 
   useEffect(() => {
@@ -1040,11 +1101,119 @@ const App: React.FC = () => {
         setConnections((prev) => {
           const conn = prev[payload.connectionId];
           if (!conn) return prev;
-          return { ...prev, [payload.connectionId]: { ...conn, status: normalizeStatus(payload.status), lastError: payload.lastError } };
+          // The session subscribes to the profile's presets itself after CONNACK.
+          const presets = payload.status === 'connected'
+            ? (conn.profile.defaultSubscriptions ?? []).filter((preset) => !conn.subscriptions.some((sub) => sub.topic === preset.topic))
+            : [];
+          const subscriptions = presets.length
+            ? [...conn.subscriptions, ...presets.map((preset) => ({ topic: preset.topic, qos: preset.qos, color: getRandomColor(), muted: false }))]
+            : conn.subscriptions;
+          return { ...prev, [payload.connectionId]: { ...conn, subscriptions, status: normalizeStatus(payload.status), lastError: payload.lastError, session: payload.session ?? conn.session, redirect: payload.redirect ?? (payload.status === 'connected' ? conn.redirect : undefined) } };
         });
       });
       registerUnlistener(statusUnlisten);
 
+      const templatesUnlisten = await listenEvent<PayloadTemplate[]>('publisher-templates-updated', (payload) => {
+        setPublisherTemplates(payload);
+      });
+      registerUnlistener(templatesUnlisten);
+
+      const presetsUnlisten = await listenEvent<SubscriptionPresetsPayload>('subscription-presets-updated', (payload) => {
+        setConnections((prev) => {
+          const conn = prev[payload.connectionId];
+          if (!conn) return prev;
+          return { ...prev, [payload.connectionId]: { ...conn, profile: { ...conn.profile, defaultSubscriptions: payload.subscriptions } } };
+        });
+      });
+      registerUnlistener(presetsUnlisten);
+
+      const peekUnlisten = await listenEvent<PeekEndedPayload>('mqtt-peek-ended', (payload) => {
+        pushToast(t('app.peekEnded', { topic: payload.topic, count: payload.received }), 'info');
+      });
+      registerUnlistener(peekUnlisten);
+
+      const credentialUnlisten = await listenEvent<CredentialExpiry[]>('credential-expiry', (expiries) => {
+        for (const expiry of expiries) {
+          if (expiry.error) {
+            pushToast(t('app.credentialUnreadable', { identity: expiry.identityName, error: expiry.error }), 'error');
+          } else if (expiry.expired) {
+            pushToast(t('app.credentialExpired', { identity: expiry.identityName }), 'error');
+          } else {
+            pushToast(t('app.credentialExpiring', { identity: expiry.identityName, days: expiry.daysLeft ?? 0 }), 'info');
+          }
+        }
+      });
+      registerUnlistener(credentialUnlisten);
+
+      const applyHistoryPause = (status: HistoryPauseStatus) => {
+        setConnections((prev) => {
+          const conn = prev[status.connectionId];
+          if (!conn) return prev;
+          return { ...prev, [status.connectionId]: { ...conn, historyPaused: status.paused ? status : undefined } };
+        });
+      };
+      const historyPauseUnlisten = await listenEvent<HistoryPauseStatus>('history-pause-changed', applyHistoryPause);
+      registerUnlistener(historyPauseUnlisten);
+      if (isTauriRuntime()) {
+        invokeCommand<HistoryPauseStatus[]>('history_paused_list')
+          .then((statuses) => {
+            if (!disposed) statuses.forEach(applyHistoryPause);
+          })
+          .catch(() => {});
+      }
+
+      const publishAckUnlisten = await listenEvent<PublishAckPayload>('mqtt-publish-ack', (payload) => {
+        if (payload.success) return;
+        pushToast(
+          t('app.publishRejected', {
+            topic: payload.topic,
+            reason: payload.reasonString ? `${payload.reason}: ${payload.reasonString}` : payload.reason,
+          }),
+          'error'
+        );
+      });
+      registerUnlistener(publishAckUnlisten);
+
+      const subscriptionResultUnlisten = await listenEvent<SubscriptionResultPayload>('mqtt-subscription-result', (payload) => {
+        if (payload.success) return;
+        const reason = payload.reasonString ? `${payload.reason}: ${payload.reasonString}` : payload.reason;
+        if (payload.kind === 'unsubscribe') {
+          pushToast(t('app.unsubscribeRejected', { topic: payload.topic, reason }), 'error');
+          return;
+        }
+        pushToast(t('app.subscribeRejected', { topic: payload.topic, reason }), 'error');
+        setConnections((prev) => {
+          const conn = prev[payload.connectionId];
+          if (!conn) return prev;
+          return {
+            ...prev,
+            [payload.connectionId]: { ...conn, subscriptions: conn.subscriptions.filter((sub) => sub.topic !== payload.topic) },
+          };
+        });
+      });
+      registerUnlistener(subscriptionResultUnlisten);
+
+      const storageUnlisten = await listenEvent<StorageStatus>('history-storage-status', setStorageStatus);
+      registerUnlistener(storageUnlisten);
+      if (isTauriRuntime()) {
+        invokeCommand<StorageStatus>('storage_guard_status')
+          .then((status) => {
+            if (!disposed) setStorageStatus(status);
+          })
+          .catch(() => {});
+      }
+
+      // Fetched rather than listened for: the scan runs on the first history call, which can
+      // come before this listener exists.
+      if (isTauriRuntime()) {
+        invokeCommand<HistoryRepair[]>('history_repairs')
+          .then((repairs) => {
+            if (disposed) return;
+            repairs.forEach((repair) => pushToast(t('app.historyRepaired', { file: repair.fileName, movedTo: repair.movedTo }), 'error'));
+          })
+          .catch(() => {});
+      }
+
       const batchUnlisten = await listenEvent<MqttBatchEvent>('mqtt-message-batch', (payload) => {
         const msgs: Message[] = payload.messages.map((m) => ({
           id: crypto.randomUUID(),
@@ -1054,6 +1223,8 @@ const App: React.FC = () => {
           retain: m.retain,
           direction: m.direction || 'in',
           timestamp: m.timestamp || Date.now(),
+          userProperties: m.userProperties,
+          isBinary: m.isBinary,
         }));
         setConnections((prev) => appendMessages(prev, payload.connectionId, msgs));
         if (activeIdRef.current === payload.connectionId) {
@@ -1116,21 +1287,7 @@ const App: React.FC = () => {
       }
 
       e.preventDefault();
-      const sourceProfile = sourceConnection.profile;
-      const newId = crypto.randomUUID();
-      handleSaveProfile({
-        ...sourceProfile,
-        id: newId,
-        name: `${sourceProfile.name} (${t('app.copySuffix')})`,
-        clientId: `nexus-${Math.random().toString(16).substring(2, 10)}`,
-      });
-      const sourceDoc = connectionTopicDocs[sourceProfile.id];
-      if (sourceDoc) {
-        setConnectionTopicDocs((prev) => ({
-          ...prev,
-          [newId]: cloneTopicDocument(sourceDoc),
-        }));
-      }
+      void cloneConnection(null, sourceConnection.profile);
     };
 
     window.addEventListener('keydown', handleKeyDown);
@@ -1166,9 +1323,12 @@ const App: React.FC = () => {
       return;
     }
 
+    const isProduction = activeConnection.profile.environment === 'prod';
     const shouldClear = await askConfirm({
       title: t('common.clear'),
-      message: t('messageLog.confirmClear'),
+      message: isProduction
+        ? `${t('app.productionWarning', { name: activeConnection.profile.name })} ${t('messageLog.confirmClear')}`
+        : t('messageLog.confirmClear'),
       tone: 'danger',
       confirmLabel: t('common.clear'),
     });
@@ -1183,7 +1343,11 @@ const App: React.FC = () => {
 
     if (isTauriRuntime()) {
       try {
-        await invokeCommand<void>('history_clear', { connectionId: activeConnection.profile.id });
+        await invokeCommand<void>('history_clear', {
+          connectionId: activeConnection.profile.id,
+          confirmation: isProduction ? activeConnection.profile.name : undefined,
+        });
+        setPinnedMessages([]);
       } catch (error) {
         finishActivity(
           activityId,
@@ -1211,7 +1375,7 @@ const App: React.FC = () => {
     finishActivity(activityId, 'success', t('app.activity.done'));
   };
 
-  const browseExportPath = async (format: 'ndjson' | 'csv'): Promise<string | null> => {
+  const browseExportPath = async (format: HistoryExportFormat): Promise<string | null> => {
     if (!activeConnection || !isTauriRuntime()) {
       return null;
     }
@@ -1239,7 +1403,7 @@ const App: React.FC = () => {
     );
 
     try {
-      const result = await invokeCommand<HistoryExportResult>('history_export', {
+      const result = await runJob<HistoryExportResult>('history_export', {
         connectionId: activeConnection.profile.id,
         format: request.format,
         fromTs: request.fromTs,
@@ -1263,6 +1427,60 @@ const App: React.FC = () => {
     }
   };
 
+  const exportRetainedSnapshot = async () => {
+    if (!activeConnection || !isTauriRuntime()) {
+      return;
+    }
+    try {
+      const path = await invokeCommand<string | null>('retained_snapshot_export', {
+        connectionId: activeConnection.profile.id,
+      });
+      if (path) {
+        pushToast(t('messageLog.snapshotExportSuccess', { path }), 'success');
+      }
+    } catch (error) {
+      pushToast(error instanceof Error ? error.message : t('messageLog.snapshotFailed'), 'error');
+    }
+  };
+
+  const importRetainedSnapshot = (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    const target = activeConnection;
+    if (!file || !target) {
+      if (snapshotFileInputRef.current) snapshotFileInputRef.current.value = '';
+      return;
+    }
+
+    const reader = new FileReader();
+    reader.onload = async (event) => {
+      try {
+        const content = event.target?.result;
+        if (typeof content !== 'string') return;
+
+        const shouldContinue = await askConfirm({
+          title: t('common.confirm'),
+          message: t('messageLog.snapshotImportConfirm', { name: target.profile.name }),
+          tone: 'danger',
+          confirmLabel: t('common.import'),
+        });
+        if (!shouldContinue) return;
+
+        const result = await runJob<RetainedSnapshotImportResult>('retained_snapshot_import', {
+          connectionId: target.profile.id,
+          pathOrContent: content,
+          confirmation: target.profile.environment === 'prod' ? target.profile.name : undefined,
+        });
+        pushToast(t('messageLog.snapshotImportSuccess', { count: result.published }), 'success');
+      } catch (error) {
+        pushToast(error instanceof Error ? error.message : t('messageLog.snapshotFailed'), 'error');
+      } finally {
+        if (snapshotFileInputRef.current) snapshotFileInputRef.current.value = '';
+      }
+    };
+
+    reader.readAsText(file);
+  };
+
   const exportConfig = async () => {
     const data: ImportPayload = {
       magic: APP_CONFIG_MAGIC,
@@ -1639,7 +1857,12 @@ const App: React.FC = () => {
         if (data.language && SUPPORTED_LANGUAGES.includes(data.language as SupportedLanguage)) {
           void i18n.changeLanguage(data.language as SupportedLanguage);
         }
-        if (Array.isArray(data.publisherTemplates)) setPublisherTemplates(data.publisherTemplates);
+        if (Array.isArray(data.publisherTemplates)) {
+          void invokeCommand<PayloadTemplate[]>('templates_import', {
+            templates: data.publisherTemplates,
+            replace: true,
+          }).catch((error) => pushToast(error instanceof Error ? error.message : String(error), 'error'));
+        }
         if (data.connectionTopicDocs && !(data.connections && Array.isArray(data.connections))) {
           setConnectionTopicDocs(
             normalizeTopicDocumentMap(data.connectionTopicDocs, new Set(Object.keys(connections)))
@@ -1699,9 +1922,13 @@ const App: React.FC = () => {
 
   const deleteConnection = async (e: React.MouseEvent | null, id: string) => {
     if (e) e.stopPropagation();
+    const deletedProfile = connections[id]?.profile;
+    const isProduction = deletedProfile?.environment === 'prod';
     const shouldDelete = await askConfirm({
       title: t('app.context.delete'),
-      message: t('app.confirmDeleteConnection'),
+      message: isProduction
+        ? `${t('app.productionWarning', { name: deletedProfile.name })} ${t('app.confirmDeleteConnection')}`
+        : t('app.confirmDeleteConnection'),
       tone: 'danger',
       confirmLabel: t('common.delete'),
     });
@@ -1720,7 +1947,10 @@ const App: React.FC = () => {
 
     if (isTauriRuntime()) {
       try {
-        await invokeCommand<void>('history_delete_connection', { connectionId: id });
+        await invokeCommand<void>('history_delete_connection', {
+          connectionId: id,
+          confirmation: isProduction ? deletedProfile.name : undefined,
+        });
       } catch (error) {
         finishActivity(
           activityId,
@@ -1752,8 +1982,27 @@ const App: React.FC = () => {
     finishActivity(activityId, 'success', t('app.activity.done'));
   };
 
-  const cloneConnection = (e: React.MouseEvent | null, profile: ConnectionProfile) => {
+  const cloneConnection = async (e: React.MouseEvent | null, profile: ConnectionProfile) => {
     if (e) e.stopPropagation();
+    if (isTauriRuntime()) {
+      try {
+        const duplicate = await invokeCommand<ConnectionDuplicate>('connection_duplicate', {
+          connectionId: profile.id,
+          overrides: { name: `${profile.name} (${t('app.copySuffix')})` },
+        });
+        handleSaveProfile(duplicate.profile);
+        const document = duplicate.topicDocument;
+        if (document) {
+          setConnectionTopicDocs((prev) => ({
+            ...prev,
+            [duplicate.profile.id]: cloneTopicDocument(document),
+          }));
+        }
+        return;
+      } catch {
+        // not saved yet; copy it in the UI below
+      }
+    }
     const newId = crypto.randomUUID();
     handleSaveProfile({
       ...profile,
@@ -1798,12 +2047,12 @@ const App: React.FC = () => {
     setConnections((prev) => ({ ...prev, [id]: { ...prev[id], status: 'disconnected', subscriptions: [] } }));
   };
 
-  const subscribe = async (id: string, topic: string, qos: 0 | 1 | 2, color?: string) => {
+  const subscribe = async (id: string, topic: string, qos: 0 | 1 | 2, color?: string, options?: SubscribeOptions) => {
     const conn = connections[id];
     if (!conn || conn.status !== 'connected') return;
 
     try {
-      await invokeCommand<void>('mqtt_subscribe', { connectionId: id, topic, qos });
+      await invokeCommand<void>('mqtt_subscribe', { connectionId: id, topic, qos, options });
       setConnections((prev) => {
         const current = prev[id];
         if (!current || current.subscriptions.some((sub) => sub.topic === topic)) return prev;
@@ -1811,7 +2060,7 @@ const App: React.FC = () => {
           ...prev,
           [id]: {
             ...current,
-            subscriptions: [...current.subscriptions, { topic, qos, color: color || getRandomColor(), muted: false }],
+            subscriptions: [...current.subscriptions, { topic, qos, color: color || getRandomColor(), muted: false, options }],
           },
         };
       });
@@ -1820,6 +2069,43 @@ const App: React.FC = () => {
     }
   };
 
+  const saveDefaultSubscriptions = async (id: string) => {
+    const conn = connections[id];
+    if (!conn) return;
+    const previous = conn.profile.defaultSubscriptions ?? [];
+    const subscriptions: SubscriptionPreset[] = conn.subscriptions.map((sub) => ({
+      topic: sub.topic,
+      qos: sub.qos,
+      options: sub.options ?? previous.find((preset) => preset.topic === sub.topic)?.options,
+    }));
+    try {
+      await invokeCommand<SubscriptionPreset[]>('subscription_presets_save', { connectionId: id, subscriptions });
+      pushToast(t('app.defaultSubscriptionsSaved', { count: subscriptions.length }), 'success');
+    } catch (err) {
+      pushToast(err instanceof Error ? err.message : String(err), 'error');
+    }
+  };
+
+  const toggleHistoryPause = async (id: string) => {
+    const conn = connections[id];
+    if (!conn) return;
+    try {
+      const status = await invokeCommand<HistoryPauseStatus>(conn.historyPaused ? 'history_resume' : 'history_pause', { connectionId: id });
+      if (!status.paused) pushToast(t('app.historyResumed', { count: status.skippedMessages }), 'success');
+    } catch (err) {
+      pushToast(err instanceof Error ? err.message : String(err), 'error');
+    }
+  };
+
+  const peek = async (id: string, topic: string, qos: 0 | 1 | 2) => {
+    try {
+      await invokeCommand<void>('mqtt_peek', { connectionId: id, topic, qos, maxMessages: PEEK_MAX_MESSAGES, durationSecs: PEEK_DURATION_SECS });
+      pushToast(t('app.peekStarted', { topic }), 'info');
+    } catch (err) {
+      pushToast(err instanceof Error ? err.message : String(err), 'error');
+    }
+  };
+
   const unsubscribe = async (id: string, topic: string) => {
     const conn = connections[id];
     if (!conn || conn.status !== 'connected') return;
@@ -1843,9 +2129,25 @@ const App: React.FC = () => {
   const publish = async (id: string, topic: string, payload: string, qos: 0 | 1 | 2, retain: boolean) => {
     const conn = connections[id];
     if (!conn || conn.status !== 'connected') return;
+    const isProduction = conn.profile.environment === 'prod';
+    if (isProduction) {
+      const shouldPublish = await askConfirm({
+        title: t('publisher.publishMessage'),
+        message: t('app.confirmProductionPublish', { name: conn.profile.name }),
+        tone: 'danger',
+      });
+      if (!shouldPublish) return;
+    }
 
     try {
-      await invokeCommand<void>('mqtt_publish', { connectionId: id, topic, payload, qos, retain });
+      await invokeCommand<number>('mqtt_publish', {
+        connectionId: id,
+        topic,
+        payload,
+        qos,
+        retain,
+        confirmation: isProduction ? conn.profile.name : undefined,
+      });
       const outgoing: Message = {
         id: crypto.randomUUID(),
         topic,
@@ -1987,7 +2289,7 @@ const App: React.FC = () => {
           <button
             className="w-full text-left px-4 py-2 hover:bg-zinc-50 dark:hover:bg-zinc-700 text-sm text-zinc-700 dark:text-zinc-300 hover:text-indigo-600 dark:hover:text-indigo-400 flex items-center gap-2"
             onClick={() => {
-              cloneConnection(null, connections[contextMenu.id].profile);
+              void cloneConnection(null, connections[contextMenu.id].profile);
               setContextMenu(null);
             }}
           >
@@ -2071,7 +2373,7 @@ const App: React.FC = () => {
                       }}
                       onDelete={(e) => { void deleteConnection(e, c.profile.id); }}
                       onEdit={(e) => { e.stopPropagation(); setEditingProfile(c.profile); setIsModalOpen(true); }}
-                      onClone={(e) => cloneConnection(e, c.profile)}
+                      onClone={(e) => { void cloneConnection(e, c.profile); }}
                       onContextMenu={(e) => { e.preventDefault(); e.stopPropagation(); setContextMenu({ x: e.clientX, y: e.clientY, id: c.profile.id }); }}
                     />
                   ))}
@@ -2140,18 +2442,84 @@ const App: React.FC = () => {
                 </div>
               </div>
               <div className="flex items-center gap-3">
+                {isTauriRuntime() && (
+                  <button
+                    onClick={() => { void saveDefaultSubscriptions(activeConnection.profile.id); }}
+                    title={t('app.saveDefaultSubscriptions')}
+                    className="p-2 rounded-lg text-zinc-500 hover:text-indigo-600 hover:bg-zinc-100 dark:text-zinc-400 dark:hover:text-indigo-400 dark:hover:bg-zinc-800"
+                  >
+                    <i className="fas fa-bookmark"></i>
+                  </button>
+                )}
+                {isTauriRuntime() && (
+                  <button
+                    onClick={() => { void toggleHistoryPause(activeConnection.profile.id); }}
+                    title={t(activeConnection.historyPaused ? 'app.resumeHistory' : 'app.pauseHistory')}
+                    className={`p-2 rounded-lg hover:bg-zinc-100 dark:hover:bg-zinc-800 ${activeConnection.historyPaused ? 'text-amber-600 dark:text-amber-400' : 'text-zinc-500 hover:text-indigo-600 dark:text-zinc-400 dark:hover:text-indigo-400'}`}
+                  >
+                    <i className={`fas ${activeConnection.historyPaused ? 'fa-play-circle' : 'fa-pause-circle'}`}></i>
+                  </button>
+                )}
                 <button onClick={() => { if (activeConnection.status === 'connected') { void disconnect(activeConnection.profile.id); } else { void connect(activeConnection.profile.id); } }} className={`px-6 py-2 rounded-lg font-bold text-white transition-all shadow-md active:scale-95 min-w-[120px] text-sm flex items-center justify-center gap-2 ${activeConnection.status === 'connected' ? 'bg-rose-500 hover:bg-rose-600 shadow-rose-200 dark:shadow-rose-500/20' : 'bg-emerald-500 hover:bg-emerald-600 shadow-emerald-200 dark:shadow-emerald-500/20'}`}>
                   {activeConnection.status === 'connected' ? <><i className="fas fa-plug"></i> {t('common.disconnect')}</> : <><i className="fas fa-power-off"></i> {t('common.connect')}</>}
                 </button>
               </div>
             </header>
 
+            {storageStatus?.low && (
+              <div className="bg-red-50 dark:bg-rose-500/15 text-red-700 dark:text-rose-400 px-4 lg:px-5 py-2 text-xs border-b border-red-100 dark:border-rose-500/30 flex items-center gap-2">
+                <i className="fas fa-hdd"></i>
+                <span className="font-semibold">
+                  {t(storageStatus.writesPaused ? 'app.storagePaused' : 'app.storagePruning', {
+                    free: `${((storageStatus.availableBytes ?? 0) / (1024 * 1024)).toFixed(0)} MB`,
+                  })}
+                </span>
+              </div>
+            )}
+
+            {activeConnection.historyPaused && (
+              <div className="bg-amber-50 dark:bg-amber-500/15 text-amber-700 dark:text-amber-300 px-4 lg:px-5 py-2 text-xs border-b border-amber-100 dark:border-amber-500/30 flex items-center gap-2">
+                <i className="fas fa-pause-circle"></i>
+                <span>{t('app.historyPaused')}</span>
+              </div>
+            )}
+
             {activeConnection.lastError && (
               <div className="bg-red-50 dark:bg-rose-500/15 text-red-700 dark:text-rose-400 px-4 lg:px-5 py-2 text-xs border-b border-red-100 dark:border-rose-500/30 flex items-center gap-2 animate-in slide-in-from-top-2">
                 <i className="fas fa-exclamation-circle"></i> <span className="font-semibold">{t('app.connectionError')}</span> {activeConnection.lastError}
               </div>
             )}
 
+            {activeConnection.redirect && (
+              <div className="bg-amber-50 dark:bg-amber-500/15 text-amber-700 dark:text-amber-300 px-4 lg:px-5 py-2 text-xs border-b border-amber-100 dark:border-amber-500/30 flex items-center gap-2">
+                <i className="fas fa-directions"></i>
+                <span>
+                  {t(activeConnection.redirect.permanent ? 'app.serverMoved' : 'app.serverRedirect', {
+                    target: activeConnection.redirect.port
+                      ? `${activeConnection.redirect.host}:${activeConnection.redirect.port}`
+                      : activeConnection.redirect.host,
+                  })}
+                </span>
+                {activeConnection.redirect.following && <span className="font-semibold">{t('app.followingRedirect')}</span>}
+              </div>
+            )}
+
+            {activeConnection.status === 'connected' && (activeConnection.session?.clientIdAssigned || activeConnection.session?.keepAliveOverridden) && (
+              <div className="bg-sky-50 dark:bg-sky-500/15 text-sky-700 dark:text-sky-300 px-4 lg:px-5 py-2 text-xs border-b border-sky-100 dark:border-sky-500/30 flex items-center gap-3">
+                <i className="fas fa-info-circle"></i>
+                {activeConnection.session.clientIdAssigned && (
+                  <span>{t('app.assignedClientId')} <span className="font-mono">{activeConnection.session.clientId}</span></span>
+                )}
+                {activeConnection.session.keepAliveOverridden && (
+                  <span>
+                    {activeConnection.session.keepAliveSecs === 0
+                      ? t('app.serverKeepAliveDisabled')
+                      : t('app.serverKeepAlive', { seconds: activeConnection.session.keepAliveSecs })}
+                  </span>
+                )}
+              </div>
+            )}
+
             <main className="flex-1 p-2 lg:p-2.5 overflow-hidden">
               <div className="grid grid-cols-1 lg:grid-cols-5 gap-4 h-full">
                 <div className="lg:col-span-2 h-full min-h-[500px]">
@@ -2173,6 +2541,7 @@ const App: React.FC = () => {
                     onUnsubscribe={(topic) => {
                       void unsubscribe(activeConnection.profile.id, topic);
                     }}
+                    onPeek={isTauriRuntime() ? (topic, qos) => { void peek(activeConnection.profile.id, topic, qos); } : undefined}
                     onToggleMute={(topic) => toggleMute(activeConnection.profile.id, topic)}
                     onGeneratePayload={generatePayload}
                     onNotify={(message, tone: NoticeTone = 'info') => pushToast(message, tone)}
@@ -2192,6 +2561,14 @@ const App: React.FC = () => {
                     onLoadOlder={() => loadOlderHistory()}
                     onClear={() => { void clearActiveHistory(); }}
                     onExport={() => setIsExportModalOpen(true)}
+                    pinned={pinnedMessages}
+                    onTogglePin={isTauriRuntime() ? togglePinnedMessage : undefined}
+                    onSnapshotExport={isTauriRuntime() ? exportRetainedSnapshot : undefined}
+                    onSnapshotImport={
+                      isTauriRuntime() && activeConnection.status === 'connected'
+                        ? () => snapshotFileInputRef.current?.click()
+                        : undefined
+                    }
                   />
                 </div>
               </div>
@@ -2243,6 +2620,8 @@ const App: React.FC = () => {
           aiConfig={aiConfig}
           aiPrompts={aiPrompts}
           configFilePath={configPaths?.configFile}
+          exportSettings={exportSettings}
+          onExportSettingsChange={setExportSettings}
           onLanguageChange={(language) => {
             if (SUPPORTED_LANGUAGES.includes(language)) {
               void i18n.changeLanguage(language);
@@ -2288,6 +2667,13 @@ const App: React.FC = () => {
         />
 
         <input type="file" ref={fileInputRef} onChange={importConfig} className="hidden" accept=".json" />
+        <input
+          type="file"
+          ref={snapshotFileInputRef}
+          onChange={importRetainedSnapshot}
+          className="hidden"
+          accept=".json"
+        />
         <input
           type="file"
           ref={topicFileInputRef}
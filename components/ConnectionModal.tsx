@@ -22,6 +22,16 @@ const getProtocolDefaults = (protocol: ConnectionProfile['protocol']) => ({
   ssl: protocol === 'mqtts' || protocol === 'wss',
 });
 
+// "key=value, key2=value2"; entries without a key are dropped.
+const parseLabels = (text: string): Record<string, string> => {
+  const labels: Record<string, string> = {};
+  for (const entry of text.split(',')) {
+    const [key, ...rest] = entry.split('=');
+    if (key.trim()) labels[key.trim()] = rest.join('=').trim();
+  }
+  return labels;
+};
+
 const ConnectionModal: React.FC<ConnectionModalProps> = ({
   isOpen,
   onClose,
@@ -34,6 +44,7 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
 }) => {
   const { t } = useTranslation();
   const [profile, setProfile] = useState<ConnectionProfile>(DEFAULT_PROFILE);
+  const [labelsText, setLabelsText] = useState('');
   const isHttps = typeof window !== 'undefined' && window.location.protocol === 'https:';
 
   useEffect(() => {
@@ -41,6 +52,7 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
       return;
     }
 
+    setLabelsText(Object.entries(initialProfile?.labels ?? {}).map(([key, value]) => `${key}=${value}`).join(', '));
     if (initialProfile) {
       const normalizedProtocol = initialProfile.protocol || 'mqtt';
       const defaults = getProtocolDefaults(normalizedProtocol);
@@ -70,6 +82,16 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
     const checked = (e.target as HTMLInputElement).checked;
 
     setProfile((prev) => {
+      if (name === 'idleMinutes' || name === 'idleIgnoreInbound') {
+        const current = prev.idleDisconnect ?? { minutes: 0, ignoreInbound: false };
+        return {
+          ...prev,
+          idleDisconnect: name === 'idleMinutes'
+            ? { ...current, minutes: Math.max(0, Math.floor(Number(value) || 0)) }
+            : { ...current, ignoreInbound: checked },
+        };
+      }
+
       if (type === 'checkbox') {
         return { ...prev, [name]: checked };
       }
@@ -78,6 +100,10 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
         return { ...prev, port: Number(value) };
       }
 
+      if (name === 'environment') {
+        return { ...prev, environment: (value || undefined) as ConnectionProfile['environment'] };
+      }
+
       if (name === 'protocolVersion') {
         return { ...prev, protocolVersion: Number(value) as 3 | 4 | 5 };
       }
@@ -239,6 +265,23 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
                     </select>
                   </div>
                 </div>
+                {(profile.protocol === 'mqtts' || profile.protocol === 'wss') && (
+                  <div className="grid grid-cols-3 gap-2 mt-2">
+                    {(['caCertPath', 'clientCertPath', 'clientKeyPath'] as const).map((field) => (
+                      <div key={field}>
+                        <label className="text-[10px] font-bold text-zinc-400 dark:text-zinc-500 uppercase tracking-wider mb-1 block">{t(`connectionModal.${field}`)}</label>
+                        <input
+                          type="text"
+                          name={field}
+                          value={profile[field] || ''}
+                          onChange={handleLinkChange}
+                          className="w-full border border-zinc-300 dark:border-zinc-700 rounded-md px-2 py-1.5 font-mono text-xs bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100"
+                          placeholder={t('connectionModal.placeholders.certPath')}
+                        />
+                      </div>
+                    ))}
+                  </div>
+                )}
                 {profile.protocol === 'ws' && isHttps && (
                   <div className="mt-2 text-xs text-amber-600 dark:text-amber-400 flex items-center gap-1 bg-amber-50 dark:bg-amber-500/15 p-2 rounded border border-amber-100 dark:border-amber-500/30">
                     <i className="fas fa-exclamation-triangle"></i> {t('connectionModal.httpsRequiresWss')}
@@ -317,6 +360,16 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
                       <i className="fas fa-random"></i>
                     </button>
                   </div>
+                  <label className="mt-2 flex items-center gap-2 text-xs text-zinc-600 dark:text-zinc-300">
+                    <input
+                      type="checkbox"
+                      name="uniqueClientId"
+                      checked={profile.uniqueClientId ?? false}
+                      onChange={handleLinkChange}
+                      className="w-4 h-4 text-indigo-600 rounded focus:ring-indigo-500"
+                    />
+                    {t('connectionModal.uniqueClientId')}
+                  </label>
                 </div>
               </div>
             </div>
@@ -351,6 +404,30 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
                     />
                     <datalist id="groups-list">{existingGroups.map((g) => <option key={g} value={g} />)}</datalist>
                   </div>
+                  <div>
+                    <label className="block text-sm font-bold text-zinc-700 dark:text-zinc-200 mb-1">{t('connectionModal.environment')}</label>
+                    <select
+                      name="environment"
+                      value={profile.environment || ''}
+                      onChange={handleLinkChange}
+                      className="w-full px-3 py-2 border border-indigo-200 dark:border-indigo-500/30 rounded-lg focus:ring-2 focus:ring-indigo-500 bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100"
+                    >
+                      <option value="">{t('connectionModal.environments.none')}</option>
+                      <option value="dev">{t('connectionModal.environments.dev')}</option>
+                      <option value="staging">{t('connectionModal.environments.staging')}</option>
+                      <option value="prod">{t('connectionModal.environments.prod')}</option>
+                    </select>
+                  </div>
+                  <div>
+                    <label className="block text-sm font-bold text-zinc-700 dark:text-zinc-200 mb-1">{t('connectionModal.labels')}</label>
+                    <input
+                      type="text"
+                      value={labelsText}
+                      onChange={(e) => setLabelsText(e.target.value)}
+                      className="w-full px-3 py-2 border border-indigo-200 dark:border-indigo-500/30 rounded-lg focus:ring-2 focus:ring-indigo-500 bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 font-mono text-sm"
+                      placeholder={t('connectionModal.placeholders.labels')}
+                    />
+                  </div>
                   <div>
                     <label className="block text-sm font-bold text-zinc-700 dark:text-zinc-200 mb-2">{t('connectionModal.colorTag')}</label>
                     <div className="flex gap-2 flex-wrap bg-white dark:bg-zinc-800 p-3 rounded-lg border border-indigo-100 dark:border-indigo-500/20">
@@ -375,6 +452,40 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
                     />
                     <span className="text-sm font-medium text-zinc-700 dark:text-zinc-200">{t('connectionModal.cleanSession')}</span>
                   </div>
+                  <div className="flex items-center gap-3 bg-white dark:bg-zinc-800 p-3 rounded-lg border border-indigo-100 dark:border-indigo-500/20">
+                    <input
+                      type="checkbox"
+                      name="replayUnackedPublishes"
+                      checked={profile.replayUnackedPublishes ?? false}
+                      onChange={handleLinkChange}
+                      className="w-5 h-5 text-indigo-600 rounded focus:ring-indigo-500"
+                    />
+                    <span className="text-sm font-medium text-zinc-700 dark:text-zinc-200">{t('connectionModal.replayUnackedPublishes')}</span>
+                  </div>
+                  <div className="bg-white dark:bg-zinc-800 p-3 rounded-lg border border-indigo-100 dark:border-indigo-500/20 space-y-2">
+                    <div className="flex items-center gap-3">
+                      <span className="text-sm font-medium text-zinc-700 dark:text-zinc-200 flex-1">{t('connectionModal.idleDisconnect')}</span>
+                      <input
+                        type="number"
+                        min={0}
+                        name="idleMinutes"
+                        value={profile.idleDisconnect?.minutes ?? 0}
+                        onChange={handleLinkChange}
+                        className="w-20 px-2 py-1 border border-indigo-200 dark:border-indigo-500/30 rounded-lg focus:ring-2 focus:ring-indigo-500 bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 text-sm"
+                      />
+                    </div>
+                    <label className="flex items-center gap-3">
+                      <input
+                        type="checkbox"
+                        name="idleIgnoreInbound"
+                        checked={profile.idleDisconnect?.ignoreInbound ?? false}
+                        disabled={!profile.idleDisconnect?.minutes}
+                        onChange={handleLinkChange}
+                        className="w-5 h-5 text-indigo-600 rounded focus:ring-indigo-500"
+                      />
+                      <span className="text-sm text-zinc-600 dark:text-zinc-300">{t('connectionModal.idleIgnoreInbound')}</span>
+                    </label>
+                  </div>
                 </div>
               </div>
             </div>
@@ -386,7 +497,7 @@ const ConnectionModal: React.FC<ConnectionModalProps> = ({
             {t('common.cancel')}
           </button>
           <button
-            onClick={() => onSave(profile)}
+            onClick={() => onSave({ ...profile, labels: parseLabels(labelsText) })}
             className="px-6 py-2 bg-indigo-600 hover:bg-indigo-700 text-white rounded-lg shadow-lg shadow-indigo-200 dark:shadow-indigo-500/20 font-bold transition-transform active:scale-95"
           >
             {initialProfile ? t('connectionModal.saveChanges') : t('connectionModal.createConnection')}
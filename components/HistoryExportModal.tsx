@@ -1,7 +1,7 @@
 import React, { useEffect, useMemo, useState } from 'react';
 import { useTranslation } from 'react-i18next';
 
-export type HistoryExportFormat = 'ndjson' | 'csv';
+export type HistoryExportFormat = 'ndjson' | 'csv' | 'xlsx';
 export type HistoryRangePreset = '1h' | '6h' | '12h' | '24h' | '7d' | 'all' | 'custom';
 
 export interface HistoryExportRequest {
@@ -153,6 +153,7 @@ const HistoryExportModal: React.FC<HistoryExportModalProps> = ({
               >
                 <option value="ndjson">NDJSON</option>
                 <option value="csv">CSV</option>
+                <option value="xlsx">Excel (XLSX)</option>
               </select>
             </div>
             <div className="md:col-span-2">
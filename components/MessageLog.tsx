@@ -1,6 +1,6 @@
 import React, { useRef, useEffect, useState } from 'react';
 import { useTranslation } from 'react-i18next';
-import { Message, Subscription } from '../types';
+import { Message, PinnedMessageRecord, Subscription } from '../types';
 
 interface MessageLogProps {
   messages: Message[];
@@ -10,6 +10,10 @@ interface MessageLogProps {
   onLoadOlder: () => Promise<void> | void;
   onClear: () => Promise<void> | void;
   onExport: () => Promise<void> | void;
+  onSnapshotExport?: () => Promise<void> | void;
+  onSnapshotImport?: () => Promise<void> | void;
+  pinned?: PinnedMessageRecord[];
+  onTogglePin?: (historyId: number, pin: boolean) => Promise<void> | void;
 }
 
 const MessageLog: React.FC<MessageLogProps> = ({
@@ -20,6 +24,10 @@ const MessageLog: React.FC<MessageLogProps> = ({
   onLoadOlder,
   onClear,
   onExport,
+  onSnapshotExport,
+  onSnapshotImport,
+  pinned = [],
+  onTogglePin,
 }) => {
   const { t } = useTranslation();
   const bottomRef = useRef<HTMLDivElement>(null);
@@ -71,6 +79,8 @@ const MessageLog: React.FC<MessageLogProps> = ({
     return msg.topic.toLowerCase().includes(term) || msg.payload.toLowerCase().includes(term);
   });
 
+  const pinnedIds = new Set(pinned.map((record) => record.id));
+
   const getTopicColor = (topic: string) => {
     const sub = findMatchingSubscription(topic);
     return sub ? sub.color : 'bg-zinc-500';
@@ -157,9 +167,43 @@ const MessageLog: React.FC<MessageLogProps> = ({
           <button onClick={onExport} className="text-zinc-400 dark:text-zinc-500 hover:text-indigo-600 dark:hover:text-indigo-400 text-xs font-medium transition-colors flex items-center gap-1 px-2 py-1 rounded hover:bg-indigo-50 dark:hover:bg-indigo-500/15">
             <i className="fas fa-file-export"></i> <span className="hidden sm:inline">{t('common.export')}</span>
           </button>
+          {onSnapshotExport && (
+            <button onClick={onSnapshotExport} title={t('messageLog.snapshotExportTitle')} className="text-zinc-400 dark:text-zinc-500 hover:text-indigo-600 dark:hover:text-indigo-400 text-xs font-medium transition-colors flex items-center gap-1 px-2 py-1 rounded hover:bg-indigo-50 dark:hover:bg-indigo-500/15">
+              <i className="fas fa-camera"></i> <span className="hidden sm:inline">{t('messageLog.snapshot')}</span>
+            </button>
+          )}
+          {onSnapshotImport && (
+            <button onClick={onSnapshotImport} title={t('messageLog.snapshotImportTitle')} className="text-zinc-400 dark:text-zinc-500 hover:text-indigo-600 dark:hover:text-indigo-400 text-xs font-medium transition-colors flex items-center gap-1 px-2 py-1 rounded hover:bg-indigo-50 dark:hover:bg-indigo-500/15">
+              <i className="fas fa-seedling"></i> <span className="hidden sm:inline">{t('messageLog.seed')}</span>
+            </button>
+          )}
         </div>
       </div>
 
+      {pinned.length > 0 && (
+        <div className="px-2.5 py-2 border-b border-amber-200/70 dark:border-amber-500/20 bg-amber-50/60 dark:bg-amber-500/5 space-y-1 max-h-40 overflow-y-auto custom-scrollbar">
+          {pinned.map((record) => (
+            <div key={record.id} className="flex items-center gap-2 text-[11px] group">
+              <i className="fas fa-thumbtack text-amber-500 text-[10px]"></i>
+              <span className="font-mono text-zinc-400 dark:text-zinc-500 tabular-nums">{formatTime(record.timestamp)}</span>
+              <span className="font-mono font-semibold text-zinc-700 dark:text-zinc-200 truncate max-w-[40%]">{record.topic}</span>
+              <span className="font-mono text-zinc-500 dark:text-zinc-400 truncate flex-1" title={record.payload}>
+                {record.note || record.payload}
+              </span>
+              {onTogglePin && (
+                <button
+                  onClick={() => { void onTogglePin(record.id, false); }}
+                  className="text-zinc-400 hover:text-red-500 opacity-0 group-hover:opacity-100 transition-opacity"
+                  title={t('messageLog.unpin')}
+                >
+                  <i className="fas fa-times"></i>
+                </button>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+
       <div
         ref={listRef}
         onScroll={(event) => { void handleListScroll(event); }}
@@ -201,11 +245,30 @@ const MessageLog: React.FC<MessageLogProps> = ({
                   <div className="flex gap-2 text-[10px] text-zinc-500 dark:text-zinc-400 font-medium mt-0.5">
                     <span className="bg-zinc-100 dark:bg-zinc-800 px-1.5 py-0.5 rounded border border-zinc-200 dark:border-zinc-700">QoS {msg.qos}</span>
                     {msg.retain && <span className="text-orange-600 dark:text-orange-400 bg-orange-50 dark:bg-orange-500/10 px-1.5 py-0.5 rounded border border-orange-200/50 dark:border-orange-500/20">{t('messageLog.retain')}</span>}
+                    {msg.isBinary && <span className="bg-zinc-100 dark:bg-zinc-800 px-1.5 py-0.5 rounded border border-zinc-200 dark:border-zinc-700" title={t('messageLog.binaryTitle')}>{t('messageLog.binary')}</span>}
+                    {onTogglePin && msg.historyId !== undefined && (
+                      <button
+                        onClick={() => { void onTogglePin(msg.historyId as number, !pinnedIds.has(msg.historyId as number)); }}
+                        className={`px-1 transition-colors ${pinnedIds.has(msg.historyId) ? 'text-amber-500' : 'text-zinc-300 dark:text-zinc-600 hover:text-amber-500'}`}
+                        title={pinnedIds.has(msg.historyId) ? t('messageLog.unpin') : t('messageLog.pin')}
+                      >
+                        <i className="fas fa-thumbtack"></i>
+                      </button>
+                    )}
                   </div>
                 </div>
                 <pre className="text-xs leading-relaxed text-zinc-700 dark:text-zinc-300 font-mono whitespace-pre-wrap break-words max-h-64 overflow-y-auto custom-scrollbar bg-zinc-50/80 dark:bg-zinc-950/30 p-2.5 rounded border border-zinc-100 dark:border-zinc-800/80 shadow-inner dark:shadow-none selection:bg-indigo-200 dark:selection:bg-indigo-500/30">
                   {msg.payload}
                 </pre>
+                {msg.userProperties && msg.userProperties.length > 0 && (
+                  <div className="flex flex-wrap gap-1 mt-2 text-[10px] font-mono text-zinc-500 dark:text-zinc-400">
+                    {msg.userProperties.map((property, index) => (
+                      <span key={index} className="bg-zinc-100 dark:bg-zinc-800 px-1.5 py-0.5 rounded border border-zinc-200 dark:border-zinc-700">
+                        {property.key}: {property.value}
+                      </span>
+                    ))}
+                  </div>
+                )}
               </div>
             </div>
           );
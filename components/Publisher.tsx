@@ -1,15 +1,15 @@
 import React, { useState, useEffect, useRef } from 'react';
 import { useTranslation } from 'react-i18next';
-import { PayloadTemplate, PublishHistoryItem } from '../types';
+import { invokeCommand, listenEvent } from '../services/tauriBridge';
+import { PayloadTemplate, PayloadTemplateDraft, PublishHistoryItem } from '../types';
 
 interface PublisherProps {
   onPublish: (topic: string, payload: string, qos: 0 | 1 | 2, retain: boolean) => void;
   onGeneratePayload: (topic: string, description: string) => Promise<string>;
   isConnected: boolean;
   onNotify?: (message: string, tone?: 'info' | 'success' | 'error') => void;
-  templates: PayloadTemplate[];
+  connectionId?: string;
   history: PublishHistoryItem[];
-  onTemplatesChange: (templates: PayloadTemplate[]) => void;
   onHistoryChange: (history: PublishHistoryItem[]) => void;
 }
 
@@ -18,9 +18,8 @@ const Publisher: React.FC<PublisherProps> = ({
   onGeneratePayload,
   isConnected,
   onNotify,
-  templates,
+  connectionId,
   history,
-  onTemplatesChange,
   onHistoryChange,
 }) => {
   const { t } = useTranslation();
@@ -31,6 +30,7 @@ const Publisher: React.FC<PublisherProps> = ({
 
   const [isGenerating, setIsGenerating] = useState(false);
 
+  const [templates, setTemplates] = useState<PayloadTemplate[]>([]);
   const [showSaveTemplate, setShowSaveTemplate] = useState(false);
   const [newTemplateName, setNewTemplateName] = useState('');
 
@@ -54,6 +54,36 @@ const Publisher: React.FC<PublisherProps> = ({
     return () => document.removeEventListener('mousedown', handleClickOutside);
   }, []);
 
+  useEffect(() => {
+    let disposed = false;
+    let unlisten: (() => void) | undefined;
+
+    const refresh = () => {
+      void invokeCommand<PayloadTemplate[]>('templates_list', { connectionId })
+        .then((list) => {
+          if (!disposed) setTemplates(list);
+        })
+        .catch((error) => console.error('Failed to load templates', error));
+    };
+
+    refresh();
+    void listenEvent<PayloadTemplate[]>('publisher-templates-updated', refresh).then((u) => {
+      if (disposed) u();
+      else unlisten = u;
+    });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [connectionId]);
+
+  const notifyError = (error: unknown) => {
+    if (onNotify) {
+      onNotify(error instanceof Error ? error.message : String(error), 'error');
+    }
+  };
+
   const handlePublish = () => {
     onPublish(topic, payload, qos, retain);
     addToHistory();
@@ -97,22 +127,20 @@ const Publisher: React.FC<PublisherProps> = ({
     }
   };
 
-  const saveTemplate = (e: React.FormEvent) => {
+  const saveTemplate = async (e: React.FormEvent) => {
     e.preventDefault();
     if (!newTemplateName) {
       return;
     }
 
-    const newTemplate: PayloadTemplate = {
-      id: crypto.randomUUID(),
-      name: newTemplateName,
-      topic,
-      payload,
-    };
-
-    onTemplatesChange([...templates, newTemplate]);
-    setNewTemplateName('');
-    setShowSaveTemplate(false);
+    const draft: PayloadTemplateDraft = { name: newTemplateName, topic, payload, connectionId };
+    try {
+      await invokeCommand<PayloadTemplate>('template_create', { draft });
+      setNewTemplateName('');
+      setShowSaveTemplate(false);
+    } catch (error) {
+      notifyError(error);
+    }
   };
 
   const loadTemplate = (template: PayloadTemplate) => {
@@ -122,7 +150,7 @@ const Publisher: React.FC<PublisherProps> = ({
 
   const deleteTemplate = (id: string, e: React.MouseEvent) => {
     e.stopPropagation();
-    onTemplatesChange(templates.filter((t) => t.id !== id));
+    void invokeCommand<void>('template_delete', { id }).catch(notifyError);
   };
 
   const filteredHistory = history.filter((h) => h.topic.toLowerCase().includes(topic.toLowerCase()));
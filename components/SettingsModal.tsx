@@ -1,12 +1,24 @@
 import React, { useState, useEffect, useMemo, useRef } from 'react';
 import { useTranslation } from 'react-i18next';
-import { BrokerConfig, AuthIdentity, AiConfig, AiPromptsConfig } from '../types';
+import { BrokerConfig, AuthIdentity, AiConfig, AiPromptsConfig, ExportSettings } from '../types';
 import { SUPPORTED_LANGUAGES, type SupportedLanguage } from '../i18n';
 import { DEFAULT_AI_PROMPTS, DEFAULT_BROKER, DEFAULT_IDENTITY } from '../constants';
 import foxEmblemLight from '../assets/fox-emblem-light.svg';
 import foxEmblem from '../assets/fox-emblem.svg';
 import { openExternalUrl } from '../services/externalLink';
 
+// `datetime-local` inputs work in local time without a zone.
+const toLocalDateTimeInput = (ms?: number) => {
+  if (ms === undefined || ms === null) return '';
+  const date = new Date(ms);
+  return new Date(ms - date.getTimezoneOffset() * 60_000).toISOString().slice(0, 16);
+};
+
+const fromLocalDateTimeInput = (value: string) => {
+  const ms = new Date(value).getTime();
+  return value && Number.isFinite(ms) ? ms : undefined;
+};
+
 interface SettingsModalProps {
   isOpen: boolean;
   onClose: () => void;
@@ -24,6 +36,8 @@ interface SettingsModalProps {
   aiConfig: AiConfig;
   aiPrompts: AiPromptsConfig;
   configFilePath?: string;
+  exportSettings: ExportSettings;
+  onExportSettingsChange: (settings: ExportSettings) => void;
   onLanguageChange: (language: SupportedLanguage) => void;
   onThemeChange: (theme: 'light' | 'dark') => void;
   onOpenConfigDir: () => void;
@@ -100,6 +114,8 @@ const SettingsModal: React.FC<SettingsModalProps> = ({
   aiConfig,
   aiPrompts,
   configFilePath,
+  exportSettings,
+  onExportSettingsChange,
   onLanguageChange,
   onThemeChange,
   onOpenConfigDir,
@@ -389,6 +405,36 @@ const SettingsModal: React.FC<SettingsModalProps> = ({
                       <i className="fas fa-copy mr-2"></i>{t('common.copy')}
                     </button>
                   </div>
+                  <div className="mt-5 grid grid-cols-1 md:grid-cols-2 gap-4">
+                    <div>
+                      <label className="text-xs font-bold text-zinc-500 dark:text-zinc-400 uppercase tracking-wider mb-1 block">{t('settingsModal.exportDirectory')}</label>
+                      <input
+                        value={exportSettings.exportDirectory ?? ''}
+                        onChange={(event) => onExportSettingsChange({ ...exportSettings, exportDirectory: event.target.value })}
+                        placeholder={t('settingsModal.exportDirectoryPlaceholder')}
+                        className="w-full border border-zinc-300 dark:border-zinc-700 rounded-lg px-3 py-2 bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 text-sm font-mono focus:ring-2 focus:ring-indigo-500"
+                      />
+                    </div>
+                    <div>
+                      <label className="text-xs font-bold text-zinc-500 dark:text-zinc-400 uppercase tracking-wider mb-1 block">{t('settingsModal.exportFilenameTemplate')}</label>
+                      <input
+                        value={exportSettings.exportFilenameTemplate ?? ''}
+                        onChange={(event) => onExportSettingsChange({ ...exportSettings, exportFilenameTemplate: event.target.value })}
+                        placeholder="{connectionId}-history-{timestamp}.{ext}"
+                        className="w-full border border-zinc-300 dark:border-zinc-700 rounded-lg px-3 py-2 bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 text-sm font-mono focus:ring-2 focus:ring-indigo-500"
+                      />
+                    </div>
+                    <div>
+                      <label className="text-xs font-bold text-zinc-500 dark:text-zinc-400 uppercase tracking-wider mb-1 block">{t('settingsModal.exportTimeZone')}</label>
+                      <input
+                        value={exportSettings.exportTimeZone ?? ''}
+                        onChange={(event) => onExportSettingsChange({ ...exportSettings, exportTimeZone: event.target.value })}
+                        placeholder={t('settingsModal.exportTimeZonePlaceholder')}
+                        className="w-full border border-zinc-300 dark:border-zinc-700 rounded-lg px-3 py-2 bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 text-sm font-mono focus:ring-2 focus:ring-indigo-500"
+                      />
+                    </div>
+                  </div>
+                  <p className="mt-2 text-xs text-zinc-500 dark:text-zinc-400">{t('settingsModal.exportFilenameHint')}</p>
                 </div>
 
                 <div className="rounded-xl border border-zinc-200 dark:border-zinc-700 bg-white dark:bg-zinc-900 p-5 shadow-sm dark:shadow-black/20">
@@ -984,6 +1030,11 @@ const SettingsModal: React.FC<SettingsModalProps> = ({
                     <div><label className="text-sm font-bold text-zinc-600 dark:text-zinc-300 block mb-1">{t('settingsModal.username')}</label><input type="text" className="w-full border border-zinc-300 dark:border-zinc-700 p-2 rounded-lg bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100" value={editingIdentity.username || ''} onChange={(e) => setEditingIdentity((i) => ({ ...i, username: e.target.value }))} /></div>
                     <div><label className="text-sm font-bold text-zinc-600 dark:text-zinc-300 block mb-1">{t('settingsModal.password')}</label><input type="text" className="w-full border border-zinc-300 dark:border-zinc-700 p-2 rounded-lg bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100" value={editingIdentity.password || ''} onChange={(e) => setEditingIdentity((i) => ({ ...i, password: e.target.value }))} /></div>
                     <div><label className="text-sm font-bold text-zinc-600 dark:text-zinc-300 block mb-1">{t('settingsModal.staticClientId')}</label><input type="text" className="w-full border border-zinc-300 dark:border-zinc-700 p-2 rounded-lg bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 font-mono text-sm" value={editingIdentity.clientId || ''} onChange={(e) => setEditingIdentity((i) => ({ ...i, clientId: e.target.value }))} placeholder={t('settingsModal.placeholders.staticClientId')} /></div>
+                    <div><label className="text-sm font-bold text-zinc-600 dark:text-zinc-300 block mb-1">{t('settingsModal.clientCertPath')}</label><input type="text" className="w-full border border-zinc-300 dark:border-zinc-700 p-2 rounded-lg bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 font-mono text-sm" value={editingIdentity.clientCertPath || ''} onChange={(e) => setEditingIdentity((i) => ({ ...i, clientCertPath: e.target.value || undefined }))} placeholder={t('settingsModal.placeholders.clientCertPath')} /></div>
+                    <div className="grid grid-cols-2 gap-4">
+                      <div><label className="text-sm font-bold text-zinc-600 dark:text-zinc-300 block mb-1">{t('settingsModal.tokenExpiresAt')}</label><input type="datetime-local" className="w-full border border-zinc-300 dark:border-zinc-700 p-2 rounded-lg bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 text-sm" value={toLocalDateTimeInput(editingIdentity.tokenExpiresAt)} onChange={(e) => setEditingIdentity((i) => ({ ...i, tokenExpiresAt: fromLocalDateTimeInput(e.target.value) }))} /></div>
+                      <div><label className="text-sm font-bold text-zinc-600 dark:text-zinc-300 block mb-1">{t('settingsModal.expiryWarningDays')}</label><input type="number" min={0} className="w-full border border-zinc-300 dark:border-zinc-700 p-2 rounded-lg bg-white dark:bg-zinc-800 text-zinc-900 dark:text-zinc-100 text-sm" value={editingIdentity.expiryWarningDays ?? ''} onChange={(e) => setEditingIdentity((i) => ({ ...i, expiryWarningDays: e.target.value === '' ? undefined : Math.max(0, Math.round(Number(e.target.value))) }))} placeholder="14" /></div>
+                    </div>
 
                     <div className="pt-4 flex justify-end gap-2 border-t border-zinc-200 dark:border-zinc-700 mt-4">
                       <button onClick={() => setIsEditingIdentity(false)} className="px-4 py-2 text-zinc-500 dark:text-zinc-400 hover:bg-zinc-200 dark:hover:bg-zinc-700 rounded-lg">{t('common.cancel')}</button>
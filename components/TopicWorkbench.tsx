@@ -23,6 +23,7 @@ interface TopicWorkbenchProps {
   isConnectionConnected: (connectionId: string) => boolean;
   onSubscribe: (topic: string, qos: 0 | 1 | 2) => void;
   onUnsubscribe: (topic: string) => void;
+  onPeek?: (topic: string, qos: 0 | 1 | 2) => void;
   onToggleMute: (topic: string) => void;
   onGeneratePayload: (topic: string, description: string) => Promise<string>;
   onNotify?: (message: string, tone?: 'info' | 'success' | 'error') => void;
@@ -150,6 +151,7 @@ const TopicWorkbench: React.FC<TopicWorkbenchProps> = ({
   isConnectionConnected,
   onSubscribe,
   onUnsubscribe,
+  onPeek,
   onToggleMute,
   onGeneratePayload,
   onNotify,
@@ -1113,6 +1115,18 @@ const TopicWorkbench: React.FC<TopicWorkbenchProps> = ({
                 >
                   <i className={`fas ${isSubscribed ? 'fa-eye-slash' : 'fa-rss'}`} aria-hidden></i>
                 </button>
+                {onPeek && !isSubscribed && (
+                  <button
+                    onClick={() => {
+                      if (activeTopic?.topic.trim()) onPeek(activeTopic.topic, activeTopic.qos);
+                    }}
+                    disabled={!canSubscribe || !isConnected}
+                    title={t('topicWorkbench.peek')}
+                    className="px-2 py-1.5 text-xs rounded bg-teal-600 hover:bg-teal-700 text-white disabled:bg-zinc-300 dark:disabled:bg-zinc-700 inline-flex items-center justify-center min-w-[2.25rem]"
+                  >
+                    <i className="fas fa-binoculars" aria-hidden></i>
+                  </button>
+                )}
               </div>
 
               {subscriptions.length > 0 && (
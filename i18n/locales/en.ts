@@ -41,6 +41,29 @@
     tip: 'Tip: Use Ctrl+C / Ctrl+V to clone connections',
     createFirstConnection: 'Create First Connection',
     connectionError: 'Connection Error:',
+    serverRedirect: 'Broker asked to use another server: {{target}}',
+    serverMoved: 'Broker moved to {{target}}',
+    followingRedirect: 'Reconnecting there…',
+    publishRejected: 'Broker rejected publish to {{topic}}: {{reason}}',
+    subscribeRejected: 'Broker rejected subscription to {{topic}}: {{reason}}',
+    unsubscribeRejected: 'Broker rejected unsubscribe from {{topic}}: {{reason}}',
+    saveDefaultSubscriptions: 'Subscribe to the current topics on every connect',
+    defaultSubscriptionsSaved: 'Saved {{count}} default subscriptions',
+    pauseHistory: 'Pause history recording (live messages keep showing)',
+    resumeHistory: 'Resume history recording',
+    historyPaused: 'History recording is paused for this connection. Live messages are not being saved.',
+    historyResumed: 'History recording resumed ({{count}} messages were not saved)',
+    historyRepaired: 'History database {{file}} was damaged and has been replaced with an empty one. The old file was kept as {{movedTo}}.',
+    peekStarted: 'Peeking at {{topic}}',
+    peekEnded: 'Peek at {{topic}} ended after {{count}} messages',
+    credentialExpired: 'Credential of {{identity}} has expired',
+    credentialExpiring: 'Credential of {{identity}} expires in {{days}} days',
+    credentialUnreadable: 'Cannot read credential expiry of {{identity}}: {{error}}',
+    storagePaused: 'Disk almost full ({{free}} free). History recording is paused.',
+    storagePruning: 'Disk almost full ({{free}} free). Oldest history is being removed to make room.',
+    assignedClientId: 'Broker-assigned client ID:',
+    serverKeepAlive: 'Broker set keep-alive to {{seconds}}s',
+    serverKeepAliveDisabled: 'Broker disabled keep-alive pings',
     openConfigDir: 'Open Config Folder',
     configFilePath: 'Config File Path',
     pathUnavailable: 'Path unavailable',
@@ -66,6 +89,8 @@
       parseFailed: 'Failed to parse configuration file.',
     },
     confirmDeleteConnection: 'Delete this connection instance?',
+    confirmProductionPublish: 'Publish to the production connection "{{name}}"?',
+    productionWarning: '"{{name}}" is a production connection.',
     deleteHistoryFailed: 'Failed to delete local history for this connection.',
     activity: {
       clearHistory: 'Clearing history',
@@ -115,6 +140,9 @@
     port: 'PORT',
     path: 'PATH',
     mqttVersion: 'MQTT Version',
+    caCertPath: 'CA Certificate',
+    clientCertPath: 'Client Certificate',
+    clientKeyPath: 'Client Key',
     httpsRequiresWss: 'HTTPS requires wss://',
     username: 'USERNAME',
     password: 'PASSWORD',
@@ -122,8 +150,20 @@
     instanceDetails: 'Instance Details',
     displayName: 'Display Name',
     groupFolder: 'Group / Folder',
+    labels: 'Labels',
+    environment: 'Environment',
+    environments: {
+      none: 'Not set',
+      dev: 'Development',
+      staging: 'Staging',
+      prod: 'Production (confirm publishes and history deletes)',
+    },
     colorTag: 'Color Tag',
     cleanSession: 'Clean Session',
+    replayUnackedPublishes: 'Republish unacknowledged QoS 1/2 messages after reconnecting',
+    idleDisconnect: 'Disconnect after idle minutes (0 = never)',
+    idleIgnoreInbound: 'Incoming messages don\'t count as activity',
+    uniqueClientId: 'Append a random suffix on each connect (avoids session takeover from synced configs)',
     saveChanges: 'Save Changes',
     createConnection: 'Create Connection',
     placeholders: {
@@ -131,6 +171,8 @@
       pathTcp: 'Optional for TCP',
       displayName: 'My Device 1',
       group: 'e.g. General',
+      labels: 'environment=prod, site=berlin',
+      certPath: '/path/to/file.pem (optional)',
       tcpMountPath: 'Not used for TCP',
       wsMountPath: '/mqtt',
     },
@@ -158,6 +200,12 @@
     generalDescription: 'Configure language, theme, and native config file location.',
     interfaceSection: 'Interface',
     storageSection: 'Storage',
+    exportDirectory: 'Default export directory',
+    exportDirectoryPlaceholder: 'App data exports folder',
+    exportFilenameTemplate: 'Export file name',
+    exportFilenameHint: 'Used when exporting history without choosing a file. Placeholders: {connection}, {connectionId}, {from}, {to}, {timestamp}, {ext}.',
+    exportTimeZone: 'Export time zone',
+    exportTimeZonePlaceholder: 'UTC, local or +02:00 (blank: epoch only)',
     backupSection: 'Backup & Migration',
     backupDescription: 'Import or export configuration data only, excluding message history.',
     aboutSection: 'About',
@@ -214,11 +262,15 @@
     username: 'Username',
     password: 'Password',
     staticClientId: 'Static Client ID (Optional)',
+    clientCertPath: 'Client Certificate (Optional, for expiry tracking)',
+    tokenExpiresAt: 'Token Expires At',
+    expiryWarningDays: 'Warn Days Before Expiry',
     saveIdentity: 'Save Identity',
     placeholders: {
       productionCluster: 'e.g. Production Cluster',
       adminUser: 'e.g. Admin User',
       staticClientId: 'Leave empty for auto-generated per link',
+      clientCertPath: '/path/to/client.crt',
       none: '<none>',
       auto: '<auto>',
       tcpMountPath: 'Not used for TCP',
@@ -276,6 +328,7 @@
     formatJson: 'Format JSON',
     publishTemplate: 'Publish Template',
     publishExample: 'Publish Example',
+    peek: 'Peek: subscribe until 10 messages or 30 seconds, then unsubscribe',
     contextMenu: {
       subscribe: 'Subscribe',
       unsubscribe: 'Unsubscribe',
@@ -358,6 +411,19 @@
   },
   messageLog: {
     title: 'Messages',
+    snapshot: 'Snapshot',
+    snapshotExportTitle: 'Save the last retained value of every topic as a JSON snapshot',
+    snapshotExportSuccess: 'Retained snapshot saved to {{path}}',
+    pin: 'Pin',
+    unpin: 'Unpin',
+    binary: 'Base64',
+    binaryTitle: 'Binary payload, shown as base64',
+    pinFailed: 'Failed to update pinned messages.',
+    seed: 'Seed',
+    snapshotImportTitle: 'Republish a retained snapshot to this broker',
+    snapshotImportConfirm: 'Publish every topic in this snapshot as a retained message on {{name}}?',
+    snapshotImportSuccess: 'Published {{count}} retained messages.',
+    snapshotFailed: 'Retained snapshot failed.',
     filterPlaceholder: 'Filter topic/payload...',
     resumeAutoScroll: 'Resume Auto-scroll',
     pauseAutoScroll: 'Pause Auto-scroll',
@@ -403,5 +469,5 @@
     delete: 'Delete',
   },
 };
-
-
+
+
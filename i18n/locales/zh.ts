@@ -41,6 +41,29 @@
     tip: '提示：使用 Ctrl+C / Ctrl+V 快速复制连接',
     createFirstConnection: '创建第一个连接',
     connectionError: '连接错误：',
+    serverRedirect: 'Broker 要求改用其他服务器：{{target}}',
+    serverMoved: 'Broker 已迁移到 {{target}}',
+    followingRedirect: '正在重新连接…',
+    publishRejected: 'Broker 拒绝了发往 {{topic}} 的消息：{{reason}}',
+    subscribeRejected: 'Broker 拒绝了对 {{topic}} 的订阅：{{reason}}',
+    unsubscribeRejected: 'Broker 拒绝了取消订阅 {{topic}}：{{reason}}',
+    saveDefaultSubscriptions: '每次连接时自动订阅当前主题',
+    defaultSubscriptionsSaved: '已保存 {{count}} 个默认订阅',
+    pauseHistory: '暂停记录历史（实时消息仍会显示）',
+    resumeHistory: '恢复记录历史',
+    historyPaused: '此连接已暂停记录历史，实时消息不会被保存。',
+    historyResumed: '已恢复记录历史（期间有 {{count}} 条消息未保存）',
+    historyRepaired: '历史数据库 {{file}} 已损坏，已替换为新的空数据库。原文件保存在 {{movedTo}}。',
+    peekStarted: '正在窥视 {{topic}}',
+    peekEnded: '{{topic}} 的窥视已结束，共收到 {{count}} 条消息',
+    credentialExpired: '{{identity}} 的凭据已过期',
+    credentialExpiring: '{{identity}} 的凭据将在 {{days}} 天后过期',
+    credentialUnreadable: '无法读取 {{identity}} 的凭据过期时间：{{error}}',
+    storagePaused: '磁盘空间即将耗尽（剩余 {{free}}），已暂停记录历史。',
+    storagePruning: '磁盘空间即将耗尽（剩余 {{free}}），正在删除最旧的历史记录以腾出空间。',
+    assignedClientId: '服务器分配的客户端 ID：',
+    serverKeepAlive: '服务器将保活时间设为 {{seconds}} 秒',
+    serverKeepAliveDisabled: '服务器已禁用保活心跳',
     openConfigDir: '打开配置目录',
     configFilePath: '配置文件路径',
     pathUnavailable: '路径不可用',
@@ -66,6 +89,8 @@
       parseFailed: '解析配置文件失败。',
     },
     confirmDeleteConnection: '确认删除该连接实例吗？',
+    confirmProductionPublish: '确认向生产连接“{{name}}”发布消息吗？',
+    productionWarning: '“{{name}}”是生产连接。',
     deleteHistoryFailed: '删除该连接本地历史失败。',
     activity: {
       clearHistory: '正在清空历史',
@@ -114,6 +139,9 @@
     port: '端口',
     path: '路径',
     mqttVersion: 'MQTT 版本',
+    caCertPath: 'CA 证书',
+    clientCertPath: '客户端证书',
+    clientKeyPath: '客户端私钥',
     httpsRequiresWss: 'HTTPS 环境需要使用 wss://',
     username: '用户名',
     password: '密码',
@@ -121,8 +149,20 @@
     instanceDetails: '实例详情',
     displayName: '显示名称',
     groupFolder: '分组 / 文件夹',
+    labels: '标签',
+    environment: '环境',
+    environments: {
+      none: '未设置',
+      dev: '开发',
+      staging: '预发布',
+      prod: '生产（发布和删除历史需确认）',
+    },
     colorTag: '颜色标签',
     cleanSession: '清理会话',
+    replayUnackedPublishes: '重连后重新发布未确认的 QoS 1/2 消息',
+    idleDisconnect: '空闲多少分钟后断开（0 表示不断开）',
+    idleIgnoreInbound: '收到的消息不算作活动',
+    uniqueClientId: '每次连接追加随机后缀（避免同步配置的多台设备互相顶替会话）',
     saveChanges: '保存修改',
     createConnection: '创建连接',
     placeholders: {
@@ -130,6 +170,8 @@
       pathTcp: 'TCP 可留空',
       displayName: '我的设备 1',
       group: '例如：通用',
+      labels: 'environment=prod, site=berlin',
+      certPath: '/path/to/file.pem（可选）',
       tcpMountPath: 'TCP 不使用该字段',
       wsMountPath: '/mqtt',
     },
@@ -156,6 +198,12 @@
     generalDescription: '集中管理语言、主题与本机配置文件位置。',
     interfaceSection: '界面',
     storageSection: '存储',
+    exportDirectory: '默认导出目录',
+    exportDirectoryPlaceholder: '应用数据中的 exports 目录',
+    exportFilenameTemplate: '导出文件名',
+    exportFilenameHint: '未选择文件导出历史记录时使用。占位符：{connection}、{connectionId}、{from}、{to}、{timestamp}、{ext}。',
+    exportTimeZone: '导出时区',
+    exportTimeZonePlaceholder: 'UTC、local 或 +08:00（留空则仅导出时间戳）',
     backupSection: '备份与迁移',
     backupDescription: '导入或导出配置类数据，不包含消息报文历史。',
     aboutSection: '关于',
@@ -210,11 +258,15 @@
     username: '用户名',
     password: '密码',
     staticClientId: '静态客户端 ID（可选）',
+    clientCertPath: '客户端证书（可选，用于跟踪过期）',
+    tokenExpiresAt: '令牌过期时间',
+    expiryWarningDays: '提前警告天数',
     saveIdentity: '保存身份',
     placeholders: {
       productionCluster: '例如：生产集群',
       adminUser: '例如：管理员',
       staticClientId: '留空则每条连接自动生成',
+      clientCertPath: '/path/to/client.crt',
       none: '<无>',
       auto: '<自动>',
       tcpMountPath: 'TCP 不使用该字段',
@@ -272,6 +324,7 @@
     formatJson: '格式化 JSON',
     publishTemplate: '发送模板',
     publishExample: '发送示例',
+    peek: '窥视：订阅至收到 10 条消息或 30 秒后自动取消订阅',
     contextMenu: {
       subscribe: '订阅',
       unsubscribe: '取消订阅',
@@ -354,6 +407,19 @@
   },
   messageLog: {
     title: '消息',
+    snapshot: '快照',
+    snapshotExportTitle: '将每个主题最新的保留消息保存为 JSON 快照',
+    snapshotExportSuccess: '保留消息快照已保存到 {{path}}',
+    pin: '置顶',
+    unpin: '取消置顶',
+    binary: 'Base64',
+    binaryTitle: '二进制负载，以 Base64 显示',
+    pinFailed: '更新置顶消息失败。',
+    seed: '回灌',
+    snapshotImportTitle: '将保留消息快照重新发布到当前 Broker',
+    snapshotImportConfirm: '确认将快照中的所有主题作为保留消息发布到 {{name}} 吗？',
+    snapshotImportSuccess: '已发布 {{count}} 条保留消息。',
+    snapshotFailed: '保留消息快照操作失败。',
     filterPlaceholder: '过滤主题/Payload...',
     resumeAutoScroll: '恢复自动滚动',
     pauseAutoScroll: '暂停自动滚动',
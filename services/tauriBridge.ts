@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import type { JobStatus } from '../types';
 
 const TAURI_RUNTIME_FLAG = '__TAURI_INTERNALS__';
 
@@ -27,3 +28,46 @@ export async function listenEvent<T>(
 
   return unlisten;
 }
+
+/**
+ * Starts a command that runs as a background job and resolves with the job's result once its
+ * final `job-progress` event arrives. Cancelled and failed jobs reject.
+ */
+export async function runJob<T>(
+  command: string,
+  args?: Record<string, unknown>,
+  onProgress?: (status: JobStatus<T>) => void
+): Promise<T> {
+  let jobId: string | null = null;
+  const finished = new Map<string, JobStatus<T>>();
+  let settle: (status: JobStatus<T>) => void = () => {};
+  const outcome = new Promise<T>((resolve, reject) => {
+    settle = (status) => {
+      if (status.state === 'completed') {
+        resolve(status.result as T);
+      } else {
+        reject(new Error(status.error ?? status.state));
+      }
+    };
+  });
+
+  const unlisten = await listenEvent<JobStatus<T>>('job-progress', (status) => {
+    if (jobId === null) {
+      // The job can finish before its id is known; keep the final event until then.
+      if (status.state !== 'running') finished.set(status.jobId, status);
+      return;
+    }
+    if (status.jobId !== jobId) return;
+    onProgress?.(status);
+    if (status.state !== 'running') settle(status);
+  });
+
+  try {
+    jobId = await invokeCommand<string>(command, args);
+    const early = finished.get(jobId);
+    if (early) settle(early);
+    return await outcome;
+  } finally {
+    unlisten();
+  }
+}
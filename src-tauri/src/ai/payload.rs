@@ -4,6 +4,7 @@ use rig::completion::Prompt;
 use rig::prelude::CompletionClient;
 use rig::providers::openai;
 
+#[tracing::instrument(skip(defaults, options, prompt_system, prompt_user), err)]
 pub async fn generate_payload(
     topic: &str,
     description: &str,
@@ -73,7 +74,7 @@ pub async fn generate_payload(
         .context("AI generation request failed")?;
 
     let normalized = normalize_response_to_json(&response)?;
-    Ok(serde_json::to_string_pretty(&normalized).context("failed to serialize AI JSON output")?)
+    serde_json::to_string_pretty(&normalized).context("failed to serialize AI JSON output")
 }
 
 fn merge_config(defaults: &AiConfig, options: &Option<AiConfig>) -> AiConfig {
@@ -0,0 +1,324 @@
+use super::{BrokerShared, ClientEntry, Outbound};
+use crate::mqtt::{now_millis, qos_to_u8, topic_matches};
+use anyhow::{Result, anyhow};
+use bytes::BytesMut;
+use rumqttc::mqttbytes::{Error as PacketError, check};
+use rumqttc::{
+    ConnAck, Connect, ConnectReturnCode, Packet, PubAck, PubComp, PubRec, PubRel, Publish, QoS,
+    SubAck, SubscribeReasonCode, UnsubAck,
+};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::mpsc;
+use tokio::time::{self, Duration, Instant};
+
+const MAX_PACKET_SIZE: usize = 1024 * 1024;
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+
+pub(super) async fn serve(stream: TcpStream, addr: SocketAddr, shared: Arc<BrokerShared>) {
+    let _ = stream.set_nodelay(true);
+    let (reader, writer) = stream.into_split();
+    let mut client = ClientConnection {
+        reader,
+        writer,
+        read_buf: BytesMut::with_capacity(4096),
+        write_buf: BytesMut::with_capacity(4096),
+        next_pkid: 0,
+        awaiting_pubrel: HashSet::new(),
+    };
+    let _ = client.run(addr, &shared).await;
+}
+
+struct ClientConnection {
+    reader: OwnedReadHalf,
+    writer: OwnedWriteHalf,
+    read_buf: BytesMut,
+    write_buf: BytesMut,
+    next_pkid: u16,
+    /// Inbound QoS 2 packet ids already routed and waiting for PUBREL, so a DUP retransmit
+    /// is acknowledged again without being delivered twice.
+    awaiting_pubrel: HashSet<u16>,
+}
+
+impl ClientConnection {
+    async fn run(&mut self, addr: SocketAddr, shared: &BrokerShared) -> Result<()> {
+        let connect = match time::timeout(
+            Duration::from_secs(CONNECT_TIMEOUT_SECS),
+            self.read_connect(),
+        )
+        .await
+        {
+            Ok(Ok(Some(connect))) => connect,
+            _ => return Ok(()),
+        };
+
+        let authorized = match &shared.credentials {
+            None => true,
+            Some((username, password)) => connect
+                .login
+                .as_ref()
+                .is_some_and(|login| &login.username == username && &login.password == password),
+        };
+        if !authorized {
+            self.write(Packet::ConnAck(ConnAck::new(
+                ConnectReturnCode::BadUserNamePassword,
+                false,
+            )))
+            .await?;
+            return Ok(());
+        }
+
+        let session_id = shared.next_session_id();
+        let client_id = if connect.client_id.is_empty() {
+            format!("nexus-embedded-{session_id}")
+        } else {
+            connect.client_id.clone()
+        };
+
+        // A second connection with the same client id takes over the session.
+        for entry in shared.clients.iter() {
+            if entry.client_id == client_id {
+                let _ = entry.outbound.send(Outbound::Close);
+            }
+        }
+
+        let (outbound_tx, mut outbound_rx) = mpsc::unbounded_channel::<Outbound>();
+        shared.clients.insert(
+            session_id,
+            ClientEntry {
+                client_id,
+                username: connect.login.as_ref().map(|login| login.username.clone()),
+                remote_addr: addr.to_string(),
+                connected_at: now_millis(),
+                subscriptions: Vec::new(),
+                messages_in: 0,
+                messages_out: 0,
+                outbound: outbound_tx,
+            },
+        );
+
+        self.write(Packet::ConnAck(ConnAck::new(
+            ConnectReturnCode::Success,
+            false,
+        )))
+        .await?;
+
+        let keep_alive = if connect.keep_alive == 0 {
+            Duration::from_secs(u32::MAX as u64)
+        } else {
+            Duration::from_millis(connect.keep_alive as u64 * 1500)
+        };
+        let mut shutdown_rx = shared.shutdown_rx.clone();
+        // Only packets from the client count as a sign of life, not publishes routed to it.
+        let mut deadline = Instant::now() + keep_alive;
+
+        let result = loop {
+            tokio::select! {
+                read = time::timeout_at(deadline, self.read_packet()) => {
+                    let packet = match read {
+                        Ok(Ok(packet)) => packet,
+                        Ok(Err(error)) => break Err(error),
+                        Err(_) => break Err(anyhow!("keep-alive timeout")),
+                    };
+                    deadline = Instant::now() + keep_alive;
+                    match self.handle_packet(packet, session_id, shared).await {
+                        Ok(true) => {}
+                        Ok(false) => break Ok(()),
+                        Err(error) => break Err(error),
+                    }
+                }
+                outbound = outbound_rx.recv() => {
+                    match outbound {
+                        Some(Outbound::Publish(mut publish)) => {
+                            if publish.qos != QoS::AtMostOnce {
+                                publish.pkid = self.next_pkid();
+                            }
+                            if let Err(error) = self.write(Packet::Publish(publish)).await {
+                                break Err(error);
+                            }
+                        }
+                        Some(Outbound::Close) | None => break Ok(()),
+                    }
+                }
+                _ = shutdown_rx.changed() => break Ok(()),
+            }
+        };
+
+        shared.clients.remove(&session_id);
+
+        // Only an ungraceful drop triggers the last will; DISCONNECT breaks with Ok.
+        if result.is_err() {
+            if let Some(will) = connect.last_will {
+                let mut publish = Publish::from_bytes(will.topic, will.qos, will.message);
+                publish.retain = will.retain;
+                shared.route(&publish);
+            }
+        }
+
+        result
+    }
+
+    async fn handle_packet(
+        &mut self,
+        packet: Packet,
+        session_id: u64,
+        shared: &BrokerShared,
+    ) -> Result<bool> {
+        match packet {
+            Packet::Publish(publish) => {
+                let pkid = publish.pkid;
+                let qos = publish.qos;
+                if qos != QoS::ExactlyOnce || self.awaiting_pubrel.insert(pkid) {
+                    if let Some(mut entry) = shared.clients.get_mut(&session_id) {
+                        entry.messages_in += 1;
+                    }
+                    shared.route(&publish);
+                }
+                match qos {
+                    QoS::AtMostOnce => {}
+                    QoS::AtLeastOnce => self.write(Packet::PubAck(PubAck::new(pkid))).await?,
+                    QoS::ExactlyOnce => self.write(Packet::PubRec(PubRec::new(pkid))).await?,
+                }
+            }
+            Packet::PubRel(pubrel) => {
+                self.awaiting_pubrel.remove(&pubrel.pkid);
+                self.write(Packet::PubComp(PubComp::new(pubrel.pkid)))
+                    .await?
+            }
+            Packet::PubRec(pubrec) => self.write(Packet::PubRel(PubRel::new(pubrec.pkid))).await?,
+            Packet::Subscribe(subscribe) => {
+                let codes = subscribe
+                    .filters
+                    .iter()
+                    .map(|filter| SubscribeReasonCode::Success(filter.qos))
+                    .collect::<Vec<_>>();
+                if let Some(mut entry) = shared.clients.get_mut(&session_id) {
+                    for filter in &subscribe.filters {
+                        entry
+                            .subscriptions
+                            .retain(|existing| existing.path != filter.path);
+                        entry.subscriptions.push(filter.clone());
+                    }
+                }
+                self.write(Packet::SubAck(SubAck::new(subscribe.pkid, codes)))
+                    .await?;
+
+                let retained = shared
+                    .retained
+                    .iter()
+                    .filter_map(|entry| {
+                        let filter = subscribe
+                            .filters
+                            .iter()
+                            .find(|filter| topic_matches(&filter.path, entry.key()))?;
+                        let mut publish = entry.value().clone();
+                        publish.qos = crate::mqtt::qos_from_u8(
+                            qos_to_u8(filter.qos).min(qos_to_u8(publish.qos)),
+                        );
+                        publish.retain = true;
+                        Some(publish)
+                    })
+                    .collect::<Vec<_>>();
+                for mut publish in retained {
+                    if publish.qos != QoS::AtMostOnce {
+                        publish.pkid = self.next_pkid();
+                    }
+                    self.write(Packet::Publish(publish)).await?;
+                }
+            }
+            Packet::Unsubscribe(unsubscribe) => {
+                if let Some(mut entry) = shared.clients.get_mut(&session_id) {
+                    entry
+                        .subscriptions
+                        .retain(|existing| !unsubscribe.topics.contains(&existing.path));
+                }
+                self.write(Packet::UnsubAck(UnsubAck::new(unsubscribe.pkid)))
+                    .await?;
+            }
+            Packet::PingReq => self.write(Packet::PingResp).await?,
+            Packet::Disconnect => return Ok(false),
+            Packet::Connect(_) => return Err(anyhow!("duplicate CONNECT packet")),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// Reads the CONNECT packet; `None` means an MQTT 5 client, already refused.
+    ///
+    /// The protocol level is checked on the raw frame because the 3.1.1 parser misreads the
+    /// MQTT 5 properties. The refusal is the 3.1.1 CONNACK with return code 0x01
+    /// (unacceptable protocol version), which MQTT 5 asks servers that don't speak it to send.
+    async fn read_connect(&mut self) -> Result<Option<Connect>> {
+        loop {
+            match check(self.read_buf.iter(), MAX_PACKET_SIZE) {
+                Ok(_) => break,
+                Err(PacketError::InsufficientBytes(required)) => self.fill(required).await?,
+                Err(error) => return Err(anyhow!("malformed packet: {error}")),
+            }
+        }
+        if connect_protocol_level(&self.read_buf) == Some(5) {
+            self.write(Packet::ConnAck(ConnAck::new(
+                ConnectReturnCode::RefusedProtocolVersion,
+                false,
+            )))
+            .await?;
+            return Ok(None);
+        }
+        match self.read_packet().await? {
+            Packet::Connect(connect) => Ok(Some(connect)),
+            _ => Err(anyhow!("expected a CONNECT packet")),
+        }
+    }
+
+    async fn read_packet(&mut self) -> Result<Packet> {
+        loop {
+            match Packet::read(&mut self.read_buf, MAX_PACKET_SIZE) {
+                Ok(packet) => return Ok(packet),
+                Err(PacketError::InsufficientBytes(required)) => self.fill(required).await?,
+                Err(error) => return Err(anyhow!("malformed packet: {error}")),
+            }
+        }
+    }
+
+    async fn fill(&mut self, required: usize) -> Result<()> {
+        self.read_buf.reserve(required);
+        let read = self.reader.read_buf(&mut self.read_buf).await?;
+        if read == 0 {
+            return Err(anyhow!("connection closed by client"));
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, packet: Packet) -> Result<()> {
+        self.write_buf.clear();
+        packet
+            .write(&mut self.write_buf, MAX_PACKET_SIZE)
+            .map_err(|error| anyhow!("failed to encode packet: {error}"))?;
+        self.writer.write_all(&self.write_buf).await?;
+        Ok(())
+    }
+
+    fn next_pkid(&mut self) -> u16 {
+        self.next_pkid = self.next_pkid.checked_add(1).unwrap_or(1);
+        self.next_pkid
+    }
+}
+
+/// Protocol level of the CONNECT framed at the start of `buf`: after the fixed header, the
+/// two-byte length and the four bytes of "MQTT".
+fn connect_protocol_level(buf: &[u8]) -> Option<u8> {
+    if buf.first()? >> 4 != 1 {
+        return None;
+    }
+    let length_bytes = buf
+        .get(1..)?
+        .iter()
+        .take(4)
+        .position(|byte| byte & 0x80 == 0)?
+        + 1;
+    buf.get(1 + length_bytes + 6).copied()
+}
@@ -0,0 +1,235 @@
+pub mod connection;
+
+use crate::crash;
+use crate::models::{EmbeddedBrokerClient, EmbeddedBrokerConfig, EmbeddedBrokerStatus};
+use crate::mqtt::{now_millis, qos_to_u8, topic_matches};
+use anyhow::{Context, Result, anyhow};
+use dashmap::DashMap;
+use rumqttc::{Publish, SubscribeFilter};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, mpsc, watch};
+use tokio::task::JoinHandle;
+
+const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1";
+
+#[derive(Clone, Default)]
+pub struct BrokerManager {
+    running: Arc<Mutex<Option<RunningBroker>>>,
+}
+
+struct RunningBroker {
+    config: EmbeddedBrokerConfig,
+    started_at: u64,
+    shared: Arc<BrokerShared>,
+    shutdown_tx: watch::Sender<bool>,
+    accept_task: JoinHandle<()>,
+}
+
+struct BrokerShared {
+    credentials: Option<(String, String)>,
+    clients: DashMap<u64, ClientEntry>,
+    retained: DashMap<String, Publish>,
+    next_session_id: AtomicU64,
+    shutdown_rx: watch::Receiver<bool>,
+}
+
+struct ClientEntry {
+    client_id: String,
+    username: Option<String>,
+    remote_addr: String,
+    connected_at: u64,
+    subscriptions: Vec<SubscribeFilter>,
+    messages_in: u64,
+    messages_out: u64,
+    outbound: mpsc::UnboundedSender<Outbound>,
+}
+
+enum Outbound {
+    Publish(Publish),
+    Close,
+}
+
+impl BrokerManager {
+    pub async fn start(&self, config: EmbeddedBrokerConfig) -> Result<EmbeddedBrokerStatus> {
+        let mut running = self.running.lock().await;
+        if let Some(existing) = running.as_ref() {
+            return Err(anyhow!(
+                "embedded broker is already running on port {}",
+                existing.config.port
+            ));
+        }
+
+        if config.port == 0 {
+            return Err(anyhow!("embedded broker port is required"));
+        }
+        let bind_address = config
+            .bind_address
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or(DEFAULT_BIND_ADDRESS)
+            .to_string();
+        let credentials = config
+            .username
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|username| {
+                (
+                    username.to_string(),
+                    config.password.clone().unwrap_or_default(),
+                )
+            });
+
+        let listener = TcpListener::bind((bind_address.as_str(), config.port))
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to bind embedded broker on {bind_address}:{}",
+                    config.port
+                )
+            })?;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let shared = Arc::new(BrokerShared {
+            credentials,
+            clients: DashMap::new(),
+            retained: DashMap::new(),
+            next_session_id: AtomicU64::new(1),
+            shutdown_rx: shutdown_rx.clone(),
+        });
+
+        let accept_shared = Arc::clone(&shared);
+        let accept_task = tokio::spawn(crash::monitored("broker-accept", async move {
+            let mut shutdown_rx = shutdown_rx;
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        match accepted {
+                            Ok((stream, addr)) => {
+                                tokio::spawn(crash::monitored("broker-connection", connection::serve(stream, addr, Arc::clone(&accept_shared))));
+                            }
+                            Err(error) => tracing::warn!("embedded broker accept failed: {error}"),
+                        }
+                    }
+                    _ = shutdown_rx.changed() => break,
+                }
+            }
+        }));
+
+        *running = Some(RunningBroker {
+            config: EmbeddedBrokerConfig {
+                bind_address: Some(bind_address),
+                ..config
+            },
+            started_at: now_millis(),
+            shared,
+            shutdown_tx,
+            accept_task,
+        });
+
+        Ok(status_of(running.as_ref()))
+    }
+
+    pub async fn stop(&self) -> Result<EmbeddedBrokerStatus> {
+        let mut running = self.running.lock().await;
+        if let Some(broker) = running.take() {
+            let _ = broker.shutdown_tx.send(true);
+            broker.accept_task.abort();
+            // Wait for the accept loop to drop its listener so the port can be reused right away.
+            let _ = broker.accept_task.await;
+            for entry in broker.shared.clients.iter() {
+                let _ = entry.outbound.send(Outbound::Close);
+            }
+            broker.shared.clients.clear();
+        }
+        Ok(status_of(None))
+    }
+
+    pub async fn status(&self) -> EmbeddedBrokerStatus {
+        let running = self.running.lock().await;
+        status_of(running.as_ref())
+    }
+}
+
+impl BrokerShared {
+    fn next_session_id(&self) -> u64 {
+        self.next_session_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn route(&self, publish: &Publish) {
+        if publish.retain {
+            if publish.payload.is_empty() {
+                self.retained.remove(&publish.topic);
+            } else {
+                self.retained.insert(publish.topic.clone(), publish.clone());
+            }
+        }
+
+        for mut entry in self.clients.iter_mut() {
+            let granted = entry
+                .subscriptions
+                .iter()
+                .filter(|filter| topic_matches(&filter.path, &publish.topic))
+                .map(|filter| qos_to_u8(filter.qos))
+                .max();
+            let Some(granted) = granted else {
+                continue;
+            };
+
+            let mut outgoing = publish.clone();
+            outgoing.qos = crate::mqtt::qos_from_u8(granted.min(qos_to_u8(publish.qos)));
+            outgoing.retain = false;
+            outgoing.dup = false;
+            if entry.outbound.send(Outbound::Publish(outgoing)).is_ok() {
+                entry.messages_out += 1;
+            }
+        }
+    }
+}
+
+fn status_of(running: Option<&RunningBroker>) -> EmbeddedBrokerStatus {
+    let Some(broker) = running else {
+        return EmbeddedBrokerStatus {
+            running: false,
+            port: None,
+            bind_address: None,
+            auth_enabled: false,
+            started_at: None,
+            retained_count: 0,
+            clients: Vec::new(),
+        };
+    };
+
+    let mut clients = broker
+        .shared
+        .clients
+        .iter()
+        .map(|entry| EmbeddedBrokerClient {
+            client_id: entry.client_id.clone(),
+            username: entry.username.clone(),
+            remote_addr: entry.remote_addr.clone(),
+            connected_at: entry.connected_at,
+            subscriptions: entry
+                .subscriptions
+                .iter()
+                .map(|filter| filter.path.clone())
+                .collect(),
+            messages_in: entry.messages_in,
+            messages_out: entry.messages_out,
+        })
+        .collect::<Vec<_>>();
+    clients.sort_by_key(|client| client.connected_at);
+
+    EmbeddedBrokerStatus {
+        running: true,
+        port: Some(broker.config.port),
+        bind_address: broker.config.bind_address.clone(),
+        auth_enabled: broker.shared.credentials.is_some(),
+        started_at: Some(broker.started_at),
+        retained_count: broker.shared.retained.len(),
+        clients,
+    }
+}
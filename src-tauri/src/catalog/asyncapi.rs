@@ -0,0 +1,491 @@
+use super::CATALOG_VERSION;
+use crate::models::{ConnectionTopicDocument, TopicCatalogItem, TopicDirection};
+use crate::mqtt::now_millis;
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Map, Value, json};
+
+const MAX_REF_DEPTH: usize = 16;
+const MQTT_BINDING_VERSION: &str = "0.2.0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.map(str::to_ascii_lowercase).as_deref() {
+            Some("yaml") | Some("yml") => Self::Yaml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// Converts an AsyncAPI 2.x or 3.x document (JSON or YAML) into catalog items.
+pub fn import(content: &str) -> Result<ConnectionTopicDocument> {
+    let root = parse_document(content)?;
+    let version = root
+        .get("asyncapi")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing `asyncapi` version field"))?;
+
+    let topics = if version.starts_with("2.") {
+        import_v2(&root)?
+    } else if version.starts_with("3.") {
+        import_v3(&root)?
+    } else {
+        return Err(anyhow!("unsupported AsyncAPI version: {version}"));
+    };
+
+    Ok(ConnectionTopicDocument {
+        version: CATALOG_VERSION.to_string(),
+        updated_at: now_millis(),
+        topics,
+    })
+}
+
+/// Renders a catalog as an AsyncAPI 3.0 document with MQTT bindings.
+pub fn export(
+    title: &str,
+    document: &ConnectionTopicDocument,
+    format: DocumentFormat,
+) -> Result<String> {
+    let mut channels = Map::new();
+    let mut operations = Map::new();
+
+    for (index, item) in document.topics.iter().enumerate() {
+        let channel_id = unique_key(&channels, &channel_key(item, index));
+        let (address, parameters) = to_address(&item.topic);
+        let message_id = "message".to_string();
+
+        let mut message = Map::new();
+        message.insert("name".into(), Value::String(item.name.clone()));
+        if let Some(content_type) = &item.content_type {
+            message.insert("contentType".into(), Value::String(content_type.clone()));
+        }
+        if let Some(schema) = &item.schema {
+            let payload = serde_json::from_str::<Value>(schema)
+                .or_else(|_| serde_yaml::from_str::<Value>(schema))
+                .unwrap_or_else(|_| json!({ "type": "string", "description": schema }));
+            message.insert("payload".into(), payload);
+        }
+        if let Some(example) = item
+            .payload_example
+            .as_ref()
+            .or(item.payload_template.as_ref())
+        {
+            let payload = serde_json::from_str::<Value>(example)
+                .unwrap_or_else(|_| Value::String(example.clone()));
+            message.insert("examples".into(), json!([{ "payload": payload }]));
+        }
+
+        let mut channel = Map::new();
+        channel.insert("address".into(), Value::String(address));
+        if !parameters.is_empty() {
+            channel.insert("parameters".into(), Value::Object(parameters));
+        }
+        if let Some(description) = &item.description {
+            channel.insert("description".into(), Value::String(description.clone()));
+        }
+        if !item.tags.is_empty() {
+            let tags = item
+                .tags
+                .iter()
+                .map(|tag| json!({ "name": tag }))
+                .collect::<Vec<_>>();
+            channel.insert("tags".into(), Value::Array(tags));
+        }
+        channel.insert(
+            "messages".into(),
+            json!({ message_id.clone(): Value::Object(message) }),
+        );
+        channels.insert(channel_id.clone(), Value::Object(channel));
+
+        let actions: &[&str] = match item.direction {
+            TopicDirection::Publish => &["send"],
+            TopicDirection::Subscribe => &["receive"],
+            TopicDirection::Both => &["send", "receive"],
+        };
+        for action in actions {
+            let operation_id = format!("{action}{}", upper_first(&channel_id));
+            operations.insert(
+                operation_id,
+                json!({
+                    "action": action,
+                    "channel": { "$ref": format!("#/channels/{}", escape_pointer(&channel_id)) },
+                    "messages": [{
+                        "$ref": format!(
+                            "#/channels/{}/messages/{message_id}",
+                            escape_pointer(&channel_id)
+                        )
+                    }],
+                    "bindings": {
+                        "mqtt": {
+                            "qos": item.qos,
+                            "retain": item.retain,
+                            "bindingVersion": MQTT_BINDING_VERSION,
+                        }
+                    }
+                }),
+            );
+        }
+    }
+
+    let root = json!({
+        "asyncapi": "3.0.0",
+        "info": {
+            "title": title,
+            "version": document.version,
+        },
+        "defaultContentType": "application/json",
+        "channels": channels,
+        "operations": operations,
+    });
+
+    match format {
+        DocumentFormat::Json => {
+            serde_json::to_string_pretty(&root).context("failed to serialize AsyncAPI JSON")
+        }
+        DocumentFormat::Yaml => {
+            serde_yaml::to_string(&root).context("failed to serialize AsyncAPI YAML")
+        }
+    }
+}
+
+fn parse_document(content: &str) -> Result<Value> {
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('{') {
+        return serde_json::from_str(trimmed).context("invalid AsyncAPI JSON document");
+    }
+    serde_yaml::from_str(content).context("invalid AsyncAPI YAML document")
+}
+
+fn import_v2(root: &Value) -> Result<Vec<TopicCatalogItem>> {
+    let channels = root
+        .get("channels")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("AsyncAPI document has no channels"))?;
+
+    let mut items = Vec::new();
+    for (name, channel) in channels {
+        let channel = resolve(root, channel)?;
+        let publish = channel
+            .get("publish")
+            .map(|op| resolve(root, op))
+            .transpose()?;
+        let subscribe = channel
+            .get("subscribe")
+            .map(|op| resolve(root, op))
+            .transpose()?;
+        let direction = match (publish.is_some(), subscribe.is_some()) {
+            (true, true) => TopicDirection::Both,
+            (false, true) => TopicDirection::Subscribe,
+            _ => TopicDirection::Publish,
+        };
+        let operation = publish.or(subscribe);
+        let message = operation
+            .and_then(|op| op.get("message"))
+            .map(|message| first_message(root, message))
+            .transpose()?
+            .flatten();
+        let bindings = operation.and_then(|op| op.pointer("/bindings/mqtt"));
+
+        items.push(build_item(
+            items.len(),
+            name,
+            channel,
+            operation,
+            message,
+            bindings,
+            direction,
+            root,
+        )?);
+    }
+    Ok(items)
+}
+
+fn import_v3(root: &Value) -> Result<Vec<TopicCatalogItem>> {
+    let channels = root
+        .get("channels")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("AsyncAPI document has no channels"))?;
+    let operations = root.get("operations").and_then(Value::as_object);
+
+    let mut items = Vec::new();
+    for (channel_id, channel) in channels {
+        let channel = resolve(root, channel)?;
+        // A null address marks a dynamic channel; its id is the closest thing to a topic.
+        let address = channel
+            .get("address")
+            .and_then(Value::as_str)
+            .unwrap_or(channel_id);
+        let pointer = format!("#/channels/{}", escape_pointer(channel_id));
+
+        let mut sends = false;
+        let mut receives = false;
+        let mut operation: Option<&Value> = None;
+        for op in operations.into_iter().flat_map(|ops| ops.values()) {
+            let op = resolve(root, op)?;
+            let targets = op
+                .pointer("/channel/$ref")
+                .and_then(Value::as_str)
+                .is_some_and(|reference| reference == pointer);
+            if !targets {
+                continue;
+            }
+            match op.get("action").and_then(Value::as_str) {
+                Some("send") => sends = true,
+                Some("receive") => receives = true,
+                _ => {}
+            }
+            operation.get_or_insert(op);
+        }
+        let direction = match (sends, receives) {
+            (true, true) => TopicDirection::Both,
+            (false, true) => TopicDirection::Subscribe,
+            _ => TopicDirection::Publish,
+        };
+
+        let message = match channel.get("messages").and_then(Value::as_object) {
+            Some(messages) => messages
+                .values()
+                .next()
+                .map(|message| resolve(root, message))
+                .transpose()?,
+            None => None,
+        };
+        let bindings = operation.and_then(|op| op.pointer("/bindings/mqtt"));
+
+        items.push(build_item(
+            items.len(),
+            address,
+            channel,
+            operation,
+            message,
+            bindings,
+            direction,
+            root,
+        )?);
+    }
+    Ok(items)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_item(
+    index: usize,
+    address: &str,
+    channel: &Value,
+    operation: Option<&Value>,
+    message: Option<&Value>,
+    bindings: Option<&Value>,
+    direction: TopicDirection,
+    root: &Value,
+) -> Result<TopicCatalogItem> {
+    let text = |value: Option<&Value>, key: &str| {
+        value
+            .and_then(|value| value.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    let topic = from_address(address);
+    let name = text(message, "title")
+        .or_else(|| text(message, "name"))
+        .or_else(|| text(operation, "summary"))
+        .unwrap_or_else(|| topic.clone());
+    let description = text(Some(channel), "description")
+        .or_else(|| text(operation, "description"))
+        .or_else(|| text(message, "description"));
+    let content_type = text(message, "contentType").or_else(|| {
+        root.get("defaultContentType")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    });
+
+    let schema = match message.and_then(|message| message.get("payload")) {
+        Some(payload) => Some(
+            serde_json::to_string_pretty(&inline_refs(root, payload, 0)?)
+                .context("failed to serialize payload schema")?,
+        ),
+        None => None,
+    };
+    let payload_example = message
+        .and_then(|message| message.get("examples"))
+        .and_then(Value::as_array)
+        .and_then(|examples| examples.first())
+        .and_then(|example| example.get("payload").or(Some(example)))
+        .map(|payload| match payload {
+            Value::String(text) => text.clone(),
+            other => serde_json::to_string_pretty(other).unwrap_or_default(),
+        });
+
+    let mut tags = Vec::new();
+    for source in [Some(channel), operation, message].into_iter().flatten() {
+        if let Some(Value::Array(entries)) = source.get("tags") {
+            for entry in entries {
+                if let Some(tag) = entry.get("name").and_then(Value::as_str) {
+                    if !tags.iter().any(|existing| existing == tag) {
+                        tags.push(tag.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    let qos = bindings
+        .and_then(|mqtt| mqtt.get("qos"))
+        .and_then(Value::as_u64)
+        .map(|qos| qos.min(2) as u8)
+        .unwrap_or(0);
+    let retain = bindings
+        .and_then(|mqtt| mqtt.get("retain"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    Ok(TopicCatalogItem {
+        id: format!("asyncapi-{}-{index}", now_millis()),
+        name,
+        topic,
+        direction,
+        qos,
+        retain,
+        content_type,
+        description,
+        tags,
+        payload_template: None,
+        payload_example,
+        schema,
+    })
+}
+
+/// v2 operations may carry `oneOf` message lists; the first variant represents the channel.
+fn first_message<'a>(root: &'a Value, message: &'a Value) -> Result<Option<&'a Value>> {
+    let message = resolve(root, message)?;
+    match message.get("oneOf").and_then(Value::as_array) {
+        Some(variants) => variants
+            .first()
+            .map(|variant| resolve(root, variant))
+            .transpose(),
+        None => Ok(Some(message)),
+    }
+}
+
+fn resolve<'a>(root: &'a Value, value: &'a Value) -> Result<&'a Value> {
+    let mut current = value;
+    for _ in 0..MAX_REF_DEPTH {
+        let Some(reference) = current.get("$ref").and_then(Value::as_str) else {
+            return Ok(current);
+        };
+        let pointer = reference
+            .strip_prefix('#')
+            .ok_or_else(|| anyhow!("external $ref is not supported: {reference}"))?;
+        current = root
+            .pointer(pointer)
+            .ok_or_else(|| anyhow!("unresolved $ref: {reference}"))?;
+    }
+    Err(anyhow!("$ref chain is too deep"))
+}
+
+fn inline_refs(root: &Value, value: &Value, depth: usize) -> Result<Value> {
+    if depth > MAX_REF_DEPTH {
+        return Err(anyhow!("schema $ref nesting is too deep"));
+    }
+    let value = resolve(root, value)?;
+    Ok(match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, child)| Ok((key.clone(), inline_refs(root, child, depth + 1)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|child| inline_refs(root, child, depth + 1))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Channel parameters (`{deviceId}`) become single-level MQTT wildcards.
+fn from_address(address: &str) -> String {
+    address
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with('{') && segment.ends_with('}') {
+                "+"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn to_address(topic: &str) -> (String, Map<String, Value>) {
+    let mut parameters = Map::new();
+    let address = topic
+        .split('/')
+        .map(|segment| {
+            if segment == "+" {
+                let name = format!("segment{}", parameters.len() + 1);
+                parameters.insert(name.clone(), json!({}));
+                format!("{{{name}}}")
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    (address, parameters)
+}
+
+fn channel_key(item: &TopicCatalogItem, index: usize) -> String {
+    let source = if item.name.trim().is_empty() {
+        &item.topic
+    } else {
+        &item.name
+    };
+    let mut key = String::new();
+    let mut upper = false;
+    for ch in source.chars() {
+        if ch.is_ascii_alphanumeric() {
+            if upper && !key.is_empty() {
+                key.push(ch.to_ascii_uppercase());
+            } else {
+                key.push(ch);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    if key.is_empty() {
+        format!("channel{}", index + 1)
+    } else {
+        key
+    }
+}
+
+fn unique_key(existing: &Map<String, Value>, candidate: &str) -> String {
+    if !existing.contains_key(candidate) {
+        return candidate.to_string();
+    }
+    (2..)
+        .map(|suffix| format!("{candidate}{suffix}"))
+        .find(|key| !existing.contains_key(key))
+        .unwrap_or_else(|| candidate.to_string())
+}
+
+fn upper_first(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,573 @@
+use crate::models::{ConnectionTopicDocument, TopicCatalogItem, TopicDirection};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "yield", "box", "try", "gen",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodegenLanguage {
+    TypeScript,
+    Rust,
+}
+
+impl CodegenLanguage {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.map(str::to_ascii_lowercase).as_deref() {
+            Some("rust") | Some("rs") => Self::Rust,
+            _ => Self::TypeScript,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::TypeScript => "ts",
+            Self::Rust => "rs",
+        }
+    }
+}
+
+/// Renders one source file with a constant per catalog topic and a type per JSON Schema:
+/// TypeScript interfaces, or Rust structs deriving serde's `Serialize` and `Deserialize`.
+///
+/// Covers the schema keywords catalogs use in practice (`type`, `properties`, `required`,
+/// `items` and string `enum`s); anything else becomes `unknown` or `serde_json::Value`.
+pub fn render(
+    title: &str,
+    document: &ConnectionTopicDocument,
+    language: CodegenLanguage,
+) -> String {
+    let mut items = document.topics.iter().collect::<Vec<_>>();
+    items.sort_by(|a, b| a.topic.cmp(&b.topic));
+
+    let mut generator = Generator::default();
+    let mut constants = Vec::new();
+    let mut constant_names = HashSet::new();
+    for item in items {
+        let base = item_name(item);
+        let constant = unique(
+            &mut constant_names,
+            format!("{}_TOPIC", screaming_snake(&base)),
+        );
+        let payload_type = match item.schema.as_deref().map(str::trim) {
+            Some(schema) if !schema.is_empty() => match serde_json::from_str::<Value>(schema) {
+                Ok(schema) => {
+                    let name = format!("{}Payload", pascal(&base));
+                    let ty = generator.ty(&schema, &name, item.description.as_deref());
+                    Some(ty)
+                }
+                Err(_) => None,
+            },
+            _ => None,
+        };
+        constants.push((constant, item, payload_type));
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated from the \"{title}\" topic catalog. Do not edit by hand."
+    );
+    let _ = writeln!(out);
+    match language {
+        CodegenLanguage::TypeScript => {
+            for (constant, item, payload_type) in &constants {
+                write_doc(
+                    &mut out,
+                    "",
+                    &topic_doc(item, payload_type.as_ref(), language),
+                    language,
+                );
+                let _ = writeln!(
+                    out,
+                    "export const {constant} = {};",
+                    string_literal(&item.topic, '\'')
+                );
+            }
+            for definition in &generator.definitions {
+                let _ = writeln!(out);
+                render_typescript(&mut out, definition);
+            }
+        }
+        CodegenLanguage::Rust => {
+            if !generator.definitions.is_empty() {
+                let _ = writeln!(out, "use serde::{{Deserialize, Serialize}};");
+                let _ = writeln!(out);
+            }
+            for (constant, item, payload_type) in &constants {
+                write_doc(
+                    &mut out,
+                    "",
+                    &topic_doc(item, payload_type.as_ref(), language),
+                    language,
+                );
+                let _ = writeln!(
+                    out,
+                    "pub const {constant}: &str = {};",
+                    string_literal(&item.topic, '"')
+                );
+            }
+            for definition in &generator.definitions {
+                let _ = writeln!(out);
+                render_rust(&mut out, definition);
+            }
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone)]
+enum Ty {
+    String,
+    Integer,
+    Number,
+    Bool,
+    Null,
+    Any,
+    Array(Box<Ty>),
+    Nullable(Box<Ty>),
+    Named(String),
+}
+
+enum Definition {
+    Struct {
+        name: String,
+        doc: Option<String>,
+        fields: Vec<Field>,
+    },
+    Enum {
+        name: String,
+        doc: Option<String>,
+        variants: Vec<String>,
+    },
+}
+
+struct Field {
+    key: String,
+    ty: Ty,
+    required: bool,
+    doc: Option<String>,
+}
+
+#[derive(Default)]
+struct Generator {
+    definitions: Vec<Definition>,
+    names: HashSet<String>,
+}
+
+impl Generator {
+    fn ty(&mut self, schema: &Value, name: &str, doc: Option<&str>) -> Ty {
+        let Value::Object(schema) = schema else {
+            return Ty::Any;
+        };
+        let doc = schema
+            .get("description")
+            .and_then(Value::as_str)
+            .or(doc)
+            .map(str::to_string);
+
+        if let Some(Value::Array(options)) = schema.get("enum") {
+            let variants = options
+                .iter()
+                .map(|option| option.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>();
+            if let Some(variants) = variants.filter(|variants| !variants.is_empty()) {
+                let name = unique(&mut self.names, name.to_string());
+                self.definitions.push(Definition::Enum {
+                    name: name.clone(),
+                    doc,
+                    variants,
+                });
+                return Ty::Named(name);
+            }
+            return Ty::Any;
+        }
+
+        let types = match schema.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ if schema.contains_key("properties") => vec!["object"],
+            _ => Vec::new(),
+        };
+        let nullable = types.contains(&"null");
+        let concrete = types
+            .iter()
+            .copied()
+            .filter(|name| *name != "null")
+            .collect::<Vec<_>>();
+        let ty = match concrete.as_slice() {
+            [] if nullable => return Ty::Null,
+            ["string"] => Ty::String,
+            ["integer"] => Ty::Integer,
+            ["number"] | ["integer", "number"] | ["number", "integer"] => Ty::Number,
+            ["boolean"] => Ty::Bool,
+            ["array"] => {
+                let item = schema.get("items").unwrap_or(&Value::Null);
+                Ty::Array(Box::new(self.ty(item, &format!("{name}Item"), None)))
+            }
+            ["object"] => match schema.get("properties") {
+                Some(Value::Object(properties)) if !properties.is_empty() => {
+                    self.object(schema, properties, name, doc)
+                }
+                _ => Ty::Any,
+            },
+            _ => Ty::Any,
+        };
+        if nullable {
+            Ty::Nullable(Box::new(ty))
+        } else {
+            ty
+        }
+    }
+
+    fn object(
+        &mut self,
+        schema: &Map<String, Value>,
+        properties: &Map<String, Value>,
+        name: &str,
+        doc: Option<String>,
+    ) -> Ty {
+        let name = unique(&mut self.names, name.to_string());
+        let required = match schema.get("required") {
+            Some(Value::Array(keys)) => keys.iter().filter_map(Value::as_str).collect(),
+            _ => HashSet::new(),
+        };
+        let fields = properties
+            .iter()
+            .map(|(key, property)| Field {
+                key: key.clone(),
+                ty: self.ty(property, &format!("{name}{}", pascal(key)), None),
+                required: required.contains(key.as_str()),
+                doc: property
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            })
+            .collect();
+        self.definitions.push(Definition::Struct {
+            name: name.clone(),
+            doc,
+            fields,
+        });
+        Ty::Named(name)
+    }
+}
+
+fn render_typescript(out: &mut String, definition: &Definition) {
+    match definition {
+        Definition::Struct { name, doc, fields } => {
+            write_doc(
+                out,
+                "",
+                doc.as_deref().unwrap_or_default(),
+                CodegenLanguage::TypeScript,
+            );
+            let _ = writeln!(out, "export interface {name} {{");
+            for field in fields {
+                write_doc(
+                    out,
+                    "  ",
+                    field.doc.as_deref().unwrap_or_default(),
+                    CodegenLanguage::TypeScript,
+                );
+                let key = if is_identifier(&field.key) {
+                    field.key.clone()
+                } else {
+                    string_literal(&field.key, '\'')
+                };
+                let optional = if field.required { "" } else { "?" };
+                let _ = writeln!(out, "  {key}{optional}: {};", typescript_type(&field.ty));
+            }
+            let _ = writeln!(out, "}}");
+        }
+        Definition::Enum {
+            name,
+            doc,
+            variants,
+        } => {
+            write_doc(
+                out,
+                "",
+                doc.as_deref().unwrap_or_default(),
+                CodegenLanguage::TypeScript,
+            );
+            let variants = variants
+                .iter()
+                .map(|variant| string_literal(variant, '\''))
+                .collect::<Vec<_>>();
+            let _ = writeln!(out, "export type {name} = {};", variants.join(" | "));
+        }
+    }
+}
+
+fn typescript_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "string".to_string(),
+        Ty::Integer | Ty::Number => "number".to_string(),
+        Ty::Bool => "boolean".to_string(),
+        Ty::Null => "null".to_string(),
+        Ty::Any => "unknown".to_string(),
+        Ty::Array(item) => match item.as_ref() {
+            Ty::Nullable(_) => format!("({})[]", typescript_type(item)),
+            _ => format!("{}[]", typescript_type(item)),
+        },
+        Ty::Nullable(inner) => format!("{} | null", typescript_type(inner)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn render_rust(out: &mut String, definition: &Definition) {
+    match definition {
+        Definition::Struct { name, doc, fields } => {
+            write_doc(
+                out,
+                "",
+                doc.as_deref().unwrap_or_default(),
+                CodegenLanguage::Rust,
+            );
+            let _ = writeln!(out, "#[derive(Debug, Clone, Serialize, Deserialize)]");
+            let _ = writeln!(out, "pub struct {name} {{");
+            let mut field_names = HashSet::new();
+            for field in fields {
+                write_doc(
+                    out,
+                    "    ",
+                    field.doc.as_deref().unwrap_or_default(),
+                    CodegenLanguage::Rust,
+                );
+                let mut field_name = snake(&field.key);
+                if field_name.is_empty() || field_name.starts_with(|ch: char| ch.is_ascii_digit()) {
+                    field_name = format!("field_{field_name}");
+                }
+                if RUST_KEYWORDS.contains(&field_name.as_str()) {
+                    field_name.push('_');
+                }
+                let field_name = unique(&mut field_names, field_name);
+                let mut attributes = Vec::new();
+                if field_name != field.key {
+                    attributes.push(format!("rename = {}", string_literal(&field.key, '"')));
+                }
+                let ty = match (&field.ty, field.required) {
+                    (Ty::Nullable(_), _) => rust_type(&field.ty),
+                    (ty, true) => rust_type(ty),
+                    (ty, false) => format!("Option<{}>", rust_type(ty)),
+                };
+                if !field.required || matches!(field.ty, Ty::Nullable(_)) {
+                    attributes.push("default".to_string());
+                    attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+                }
+                if !attributes.is_empty() {
+                    let _ = writeln!(out, "    #[serde({})]", attributes.join(", "));
+                }
+                let _ = writeln!(out, "    pub {field_name}: {ty},");
+            }
+            let _ = writeln!(out, "}}");
+        }
+        Definition::Enum {
+            name,
+            doc,
+            variants,
+        } => {
+            write_doc(
+                out,
+                "",
+                doc.as_deref().unwrap_or_default(),
+                CodegenLanguage::Rust,
+            );
+            let _ = writeln!(
+                out,
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]"
+            );
+            let _ = writeln!(out, "pub enum {name} {{");
+            let mut variant_names = HashSet::new();
+            for variant in variants {
+                let mut variant_name = pascal(variant);
+                if variant_name.is_empty()
+                    || variant_name.starts_with(|ch: char| ch.is_ascii_digit())
+                {
+                    variant_name = format!("Value{variant_name}");
+                }
+                let variant_name = unique(&mut variant_names, variant_name);
+                let _ = writeln!(
+                    out,
+                    "    #[serde(rename = {})]",
+                    string_literal(variant, '"')
+                );
+                let _ = writeln!(out, "    {variant_name},");
+            }
+            let _ = writeln!(out, "}}");
+        }
+    }
+}
+
+fn rust_type(ty: &Ty) -> String {
+    match ty {
+        Ty::String => "String".to_string(),
+        Ty::Integer => "i64".to_string(),
+        Ty::Number => "f64".to_string(),
+        Ty::Bool => "bool".to_string(),
+        Ty::Null => "()".to_string(),
+        Ty::Any => "serde_json::Value".to_string(),
+        Ty::Array(item) => format!("Vec<{}>", rust_type(item)),
+        Ty::Nullable(inner) => format!("Option<{}>", rust_type(inner)),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn topic_doc(
+    item: &TopicCatalogItem,
+    payload_type: Option<&Ty>,
+    language: CodegenLanguage,
+) -> String {
+    let mut lines = Vec::new();
+    if let Some(description) = item.description.as_deref().map(str::trim) {
+        if !description.is_empty() {
+            lines.push(description.to_string());
+        }
+    }
+    let direction = match item.direction {
+        TopicDirection::Publish => "publish",
+        TopicDirection::Subscribe => "subscribe",
+        TopicDirection::Both => "publish & subscribe",
+    };
+    let retain = if item.retain { ", retained" } else { "" };
+    lines.push(format!("{direction}, QoS {}{retain}", item.qos));
+    if let Some(ty) = payload_type {
+        let name = match language {
+            CodegenLanguage::TypeScript => typescript_type(ty),
+            CodegenLanguage::Rust => rust_type(ty),
+        };
+        lines.push(format!("Payload: `{name}`"));
+    }
+    lines.join("\n")
+}
+
+/// `/** ... */` in TypeScript, `///` lines in Rust.
+fn write_doc(out: &mut String, indent: &str, doc: &str, language: CodegenLanguage) {
+    let lines = doc
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>();
+    match (language, lines.as_slice()) {
+        (_, []) => {}
+        (CodegenLanguage::TypeScript, [line]) => {
+            let _ = writeln!(out, "{indent}/** {} */", line.replace("*/", "* /"));
+        }
+        (CodegenLanguage::TypeScript, lines) => {
+            let _ = writeln!(out, "{indent}/**");
+            for line in lines {
+                let _ = writeln!(out, "{indent} * {}", line.replace("*/", "* /"));
+            }
+            let _ = writeln!(out, "{indent} */");
+        }
+        (CodegenLanguage::Rust, lines) => {
+            for line in lines {
+                let _ = writeln!(out, "{indent}/// {line}");
+            }
+        }
+    }
+}
+
+/// The catalog entry's name, or its topic when it has none.
+fn item_name(item: &TopicCatalogItem) -> String {
+    let name = item.name.trim();
+    if words(name).is_empty() {
+        item.topic.clone()
+    } else {
+        name.to_string()
+    }
+}
+
+fn words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for ch in value.chars() {
+        if !ch.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if ch.is_ascii_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = ch.is_ascii_lowercase() || ch.is_ascii_digit();
+        current.push(ch.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn pascal(value: &str) -> String {
+    words(value)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn snake(value: &str) -> String {
+    words(value).join("_")
+}
+
+fn screaming_snake(value: &str) -> String {
+    let name = snake(value).to_ascii_uppercase();
+    if name.is_empty() || name.starts_with(|ch: char| ch.is_ascii_digit()) {
+        format!("T_{name}")
+    } else {
+        name
+    }
+}
+
+fn unique(taken: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut counter = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{name}{counter}");
+        counter += 1;
+    }
+    candidate
+}
+
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$')
+}
+
+fn string_literal(value: &str, quote: char) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push(quote);
+    for ch in value.chars() {
+        match ch {
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            ch if ch == quote => {
+                literal.push('\\');
+                literal.push(ch);
+            }
+            ch => literal.push(ch),
+        }
+    }
+    literal.push(quote);
+    literal
+}
@@ -0,0 +1,243 @@
+use crate::models::{
+    DiscoveredTopic, MessageDirection, MqttBatchItem, TopicCatalogItem, TopicDirection,
+    TopicDiscoveryDraft,
+};
+use crate::mqtt::now_millis;
+use dashmap::DashMap;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const DEFAULT_MAX_DRAFT_TOPICS: usize = 5000;
+const MAX_SAMPLE_BYTES: usize = 4096;
+const DISCOVERED_TAG: &str = "discovered";
+
+#[derive(Clone)]
+pub struct TopicDiscoveryManager {
+    drafts: Arc<DashMap<String, Draft>>,
+    max_topics: Arc<AtomicUsize>,
+}
+
+impl Default for TopicDiscoveryManager {
+    fn default() -> Self {
+        Self {
+            drafts: Arc::default(),
+            max_topics: Arc::new(AtomicUsize::new(DEFAULT_MAX_DRAFT_TOPICS)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Draft {
+    active: bool,
+    started_at: Option<u64>,
+    truncated: bool,
+    topics: HashMap<String, DiscoveredTopic>,
+}
+
+impl TopicDiscoveryManager {
+    pub fn set_max_topics(&self, max_topics: usize) {
+        self.max_topics.store(max_topics, Ordering::Relaxed);
+    }
+
+    /// Topic count and approximate retained bytes of a connection's draft.
+    pub fn usage(&self, connection_id: &str) -> (usize, u64) {
+        let Some(draft) = self.drafts.get(connection_id) else {
+            return (0, 0);
+        };
+        let bytes = draft
+            .topics
+            .values()
+            .map(|topic| (topic.topic.len() * 2 + topic.sample_payload.len()) as u64)
+            .sum();
+        (draft.topics.len(), bytes)
+    }
+
+    pub fn start(&self, connection_id: &str) -> TopicDiscoveryDraft {
+        let mut draft = self.drafts.entry(connection_id.to_string()).or_default();
+        if !draft.active {
+            draft.active = true;
+            draft.started_at = Some(now_millis());
+        }
+        snapshot(connection_id, &draft)
+    }
+
+    /// Stops recording but keeps the draft so it can still be reviewed and promoted.
+    pub fn stop(&self, connection_id: &str) -> TopicDiscoveryDraft {
+        let mut draft = self.drafts.entry(connection_id.to_string()).or_default();
+        draft.active = false;
+        snapshot(connection_id, &draft)
+    }
+
+    pub fn draft(&self, connection_id: &str) -> TopicDiscoveryDraft {
+        match self.drafts.get(connection_id) {
+            Some(draft) => snapshot(connection_id, &draft),
+            None => snapshot(connection_id, &Draft::default()),
+        }
+    }
+
+    pub fn discard(&self, connection_id: &str, topics: Option<&[String]>) -> TopicDiscoveryDraft {
+        let mut draft = self.drafts.entry(connection_id.to_string()).or_default();
+        match topics {
+            Some(topics) => {
+                for topic in topics {
+                    draft.topics.remove(topic);
+                }
+            }
+            None => draft.topics.clear(),
+        }
+        draft.truncated = false;
+        snapshot(connection_id, &draft)
+    }
+
+    /// Builds catalog items for the selected draft topics; callers discard them once saved.
+    pub fn catalog_items(&self, connection_id: &str, topics: &[String]) -> Vec<TopicCatalogItem> {
+        let Some(draft) = self.drafts.get(connection_id) else {
+            return Vec::new();
+        };
+        let promoted_at = now_millis();
+        topics
+            .iter()
+            .filter_map(|topic| draft.topics.get(topic).cloned())
+            .enumerate()
+            .map(|(index, discovered)| to_catalog_item(discovered, promoted_at, index))
+            .collect()
+    }
+
+    pub fn observe(&self, connection_id: &str, batch: &[MqttBatchItem]) {
+        let Some(mut draft) = self.drafts.get_mut(connection_id) else {
+            return;
+        };
+        if !draft.active {
+            return;
+        }
+
+        let max_topics = self.max_topics.load(Ordering::Relaxed);
+        for item in batch {
+            if !matches!(item.direction, MessageDirection::In) {
+                continue;
+            }
+            if let Some(existing) = draft.topics.get_mut(&item.topic) {
+                existing.message_count += 1;
+                existing.last_seen = item.timestamp;
+                existing.qos = item.qos;
+                existing.retain |= item.retain;
+                if !item.payload.is_empty() {
+                    existing.sample_payload = sample(&item.payload);
+                    existing.content_type = infer_content_type(&item.payload).to_string();
+                }
+                continue;
+            }
+            // Evict the least recently seen topics so a flood of new ones cannot grow the draft.
+            while draft.topics.len() >= max_topics {
+                let Some(stale) = draft
+                    .topics
+                    .values()
+                    .min_by_key(|topic| topic.last_seen)
+                    .map(|topic| topic.topic.clone())
+                else {
+                    break;
+                };
+                draft.topics.remove(&stale);
+                draft.truncated = true;
+            }
+            draft.topics.insert(
+                item.topic.clone(),
+                DiscoveredTopic {
+                    topic: item.topic.clone(),
+                    message_count: 1,
+                    first_seen: item.timestamp,
+                    last_seen: item.timestamp,
+                    qos: item.qos,
+                    retain: item.retain,
+                    content_type: infer_content_type(&item.payload).to_string(),
+                    sample_payload: sample(&item.payload),
+                },
+            );
+        }
+    }
+}
+
+fn snapshot(connection_id: &str, draft: &Draft) -> TopicDiscoveryDraft {
+    let mut topics = draft.topics.values().cloned().collect::<Vec<_>>();
+    topics.sort_by(|a, b| a.topic.cmp(&b.topic));
+    TopicDiscoveryDraft {
+        connection_id: connection_id.to_string(),
+        active: draft.active,
+        started_at: draft.started_at,
+        truncated: draft.truncated,
+        topics,
+    }
+}
+
+fn sample(payload: &str) -> String {
+    if payload.len() <= MAX_SAMPLE_BYTES {
+        return payload.to_string();
+    }
+    let mut end = MAX_SAMPLE_BYTES;
+    while !payload.is_char_boundary(end) {
+        end -= 1;
+    }
+    payload[..end].to_string()
+}
+
+fn infer_content_type(payload: &str) -> &'static str {
+    let trimmed = payload.trim();
+    match serde_json::from_str::<Value>(trimmed) {
+        Ok(Value::Object(_)) | Ok(Value::Array(_)) => "application/json",
+        _ if trimmed.starts_with('<') && trimmed.ends_with('>') => "application/xml",
+        _ => "text/plain",
+    }
+}
+
+fn to_catalog_item(
+    discovered: DiscoveredTopic,
+    promoted_at: u64,
+    index: usize,
+) -> TopicCatalogItem {
+    let schema = if discovered.content_type == "application/json" {
+        serde_json::from_str::<Value>(&discovered.sample_payload)
+            .ok()
+            .and_then(|value| serde_json::to_string_pretty(&infer_schema(&value)).ok())
+    } else {
+        None
+    };
+
+    TopicCatalogItem {
+        id: format!("discovered-{promoted_at}-{index}"),
+        name: discovered.topic.clone(),
+        topic: discovered.topic,
+        direction: TopicDirection::Subscribe,
+        qos: discovered.qos,
+        retain: discovered.retain,
+        content_type: Some(discovered.content_type),
+        description: None,
+        tags: vec![DISCOVERED_TAG.to_string()],
+        payload_template: None,
+        payload_example: Some(discovered.sample_payload),
+        schema,
+    }
+}
+
+/// Derives a minimal JSON Schema from one sample; reviewers are expected to refine it.
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "type": "null" }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(number) if number.is_i64() || number.is_u64() => json!({ "type": "integer" }),
+        Value::Number(_) => json!({ "type": "number" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => match items.first() {
+            Some(first) => json!({ "type": "array", "items": infer_schema(first) }),
+            None => json!({ "type": "array" }),
+        },
+        Value::Object(map) => {
+            let properties = map
+                .iter()
+                .map(|(key, child)| (key.clone(), infer_schema(child)))
+                .collect::<Map<_, _>>();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::models::{ConnectionTopicDocument, TopicCatalogItem, TopicDirection};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const ROOT_GROUP: &str = "(root)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsFormat {
+    Markdown,
+    Html,
+}
+
+impl DocsFormat {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.map(str::to_ascii_lowercase).as_deref() {
+            Some("html") | Some("htm") => Self::Html,
+            _ => Self::Markdown,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+pub fn render(title: &str, document: &ConnectionTopicDocument, format: DocsFormat) -> String {
+    match format {
+        DocsFormat::Markdown => render_markdown(title, document),
+        DocsFormat::Html => render_html(title, document),
+    }
+}
+
+/// Groups topics by their first level so related topics read together.
+fn grouped(document: &ConnectionTopicDocument) -> BTreeMap<&str, Vec<&TopicCatalogItem>> {
+    let mut groups: BTreeMap<&str, Vec<&TopicCatalogItem>> = BTreeMap::new();
+    for item in &document.topics {
+        let prefix = match item.topic.split_once('/') {
+            Some((first, _)) if !first.is_empty() => first,
+            _ => ROOT_GROUP,
+        };
+        groups.entry(prefix).or_default().push(item);
+    }
+    for items in groups.values_mut() {
+        items.sort_by(|a, b| a.topic.cmp(&b.topic));
+    }
+    groups
+}
+
+fn direction_label(direction: TopicDirection) -> &'static str {
+    match direction {
+        TopicDirection::Publish => "publish",
+        TopicDirection::Subscribe => "subscribe",
+        TopicDirection::Both => "publish & subscribe",
+    }
+}
+
+fn render_markdown(title: &str, document: &ConnectionTopicDocument) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {title}\n");
+    let _ = writeln!(
+        out,
+        "{} topics · catalog version {}\n",
+        document.topics.len(),
+        document.version
+    );
+
+    let groups = grouped(document);
+    for (prefix, items) in &groups {
+        let _ = writeln!(out, "## `{prefix}`\n");
+        let _ = writeln!(out, "| Topic | Name | Direction | QoS | Retain |");
+        let _ = writeln!(out, "| --- | --- | --- | --- | --- |");
+        for item in items {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {} |",
+                item.topic,
+                item.name.replace('|', "\\|"),
+                direction_label(item.direction),
+                item.qos,
+                if item.retain { "yes" } else { "no" }
+            );
+        }
+        out.push('\n');
+
+        for item in items {
+            let _ = writeln!(out, "### {}\n", item.name);
+            let _ = writeln!(out, "- **Topic:** `{}`", item.topic);
+            if let Some(content_type) = &item.content_type {
+                let _ = writeln!(out, "- **Content type:** {content_type}");
+            }
+            if !item.tags.is_empty() {
+                let _ = writeln!(out, "- **Tags:** {}", item.tags.join(", "));
+            }
+            out.push('\n');
+            if let Some(description) = &item.description {
+                let _ = writeln!(out, "{description}\n");
+            }
+            if let Some(schema) = &item.schema {
+                let _ = writeln!(out, "**Schema**\n\n```json\n{}\n```\n", schema.trim());
+            }
+            if let Some(example) = item
+                .payload_example
+                .as_ref()
+                .or(item.payload_template.as_ref())
+            {
+                let _ = writeln!(out, "**Example**\n\n```\n{}\n```\n", example.trim());
+            }
+        }
+    }
+    out
+}
+
+fn render_html(title: &str, document: &ConnectionTopicDocument) -> String {
+    let mut out = String::new();
+    let title = escape_html(title);
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>\n\
+         body{{font-family:system-ui,sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2933}}\n\
+         table{{border-collapse:collapse;width:100%;margin-bottom:1.5rem}}\n\
+         th,td{{border:1px solid #d9e2ec;padding:.4rem .6rem;text-align:left}}\n\
+         th{{background:#f0f4f8}}\n\
+         code,pre{{font-family:ui-monospace,monospace;background:#f0f4f8;border-radius:4px}}\n\
+         pre{{padding:.75rem;overflow:auto}}\n\
+         .tag{{display:inline-block;background:#e3f2fd;border-radius:999px;padding:0 .5rem;margin-right:.25rem;font-size:.85em}}\n\
+         section.topic{{border-top:1px solid #d9e2ec;padding-top:.5rem}}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{} topics &middot; catalog version {}</p>\n",
+        document.topics.len(),
+        escape_html(&document.version)
+    );
+
+    let groups = grouped(document);
+    let _ = writeln!(out, "<nav><ul>");
+    for prefix in groups.keys() {
+        let _ = writeln!(
+            out,
+            "<li><a href=\"#group-{0}\"><code>{0}</code></a></li>",
+            escape_html(prefix)
+        );
+    }
+    let _ = writeln!(out, "</ul></nav>");
+
+    for (prefix, items) in &groups {
+        let prefix = escape_html(prefix);
+        let _ = writeln!(out, "<h2 id=\"group-{prefix}\"><code>{prefix}</code></h2>");
+        let _ = writeln!(
+            out,
+            "<table><thead><tr><th>Topic</th><th>Name</th><th>Direction</th><th>QoS</th><th>Retain</th></tr></thead><tbody>"
+        );
+        for item in items {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&item.topic),
+                escape_html(&item.name),
+                direction_label(item.direction),
+                item.qos,
+                if item.retain { "yes" } else { "no" }
+            );
+        }
+        let _ = writeln!(out, "</tbody></table>");
+
+        for item in items {
+            let _ = writeln!(out, "<section class=\"topic\">");
+            let _ = writeln!(out, "<h3>{}</h3>", escape_html(&item.name));
+            let _ = writeln!(out, "<p><code>{}</code>", escape_html(&item.topic));
+            if let Some(content_type) = &item.content_type {
+                let _ = write!(out, " &middot; {}", escape_html(content_type));
+            }
+            let _ = writeln!(out, "</p>");
+            if !item.tags.is_empty() {
+                let tags = item
+                    .tags
+                    .iter()
+                    .map(|tag| format!("<span class=\"tag\">{}</span>", escape_html(tag)))
+                    .collect::<String>();
+                let _ = writeln!(out, "<p>{tags}</p>");
+            }
+            if let Some(description) = &item.description {
+                let _ = writeln!(out, "<p>{}</p>", escape_html(description));
+            }
+            if let Some(schema) = &item.schema {
+                let _ = writeln!(
+                    out,
+                    "<h4>Schema</h4>\n<pre>{}</pre>",
+                    escape_html(schema.trim())
+                );
+            }
+            if let Some(example) = item
+                .payload_example
+                .as_ref()
+                .or(item.payload_template.as_ref())
+            {
+                let _ = writeln!(
+                    out,
+                    "<h4>Example</h4>\n<pre>{}</pre>",
+                    escape_html(example.trim())
+                );
+            }
+            let _ = writeln!(out, "</section>");
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
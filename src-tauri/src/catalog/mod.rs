@@ -0,0 +1,195 @@
+pub mod asyncapi;
+pub mod codegen;
+pub mod discovery;
+pub mod docs;
+pub mod schema;
+
+use crate::config_store;
+use crate::models::{
+    ConnectionTopicDocument, TopicCatalogImportResult, TopicCatalogItem, TopicCatalogMergeStrategy,
+};
+use crate::mqtt::{is_valid_topic_filter, now_millis};
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use tauri::{AppHandle, Emitter};
+
+pub const CATALOG_VERSION: &str = "1";
+
+/// Reads a connection's saved catalog, returning an empty document when none exists yet.
+pub fn load(app: &AppHandle, connection_id: &str) -> Result<ConnectionTopicDocument> {
+    let config = config_store::load_config(app)?;
+    Ok(config
+        .connection_topic_docs
+        .get(connection_id)
+        .cloned()
+        .unwrap_or_else(|| ConnectionTopicDocument {
+            version: CATALOG_VERSION.to_string(),
+            updated_at: 0,
+            topics: Vec::new(),
+        }))
+}
+
+/// Human-readable title for exported catalogs, based on the connection name.
+pub fn title(app: &AppHandle, connection_id: &str) -> Result<String> {
+    let config = config_store::load_config(app)?;
+    Ok(config
+        .connections
+        .iter()
+        .find(|profile| profile.id == connection_id)
+        .map(|profile| format!("{} topics", profile.name))
+        .unwrap_or_else(|| format!("{connection_id} topics")))
+}
+
+/// Parses a catalog from a file path or inline content, validates it and merges it into the
+/// connection's saved catalog.
+pub fn import(
+    app: &AppHandle,
+    connection_id: &str,
+    path_or_content: &str,
+    strategy: TopicCatalogMergeStrategy,
+) -> Result<TopicCatalogImportResult> {
+    let content = read_source(path_or_content)?;
+    let incoming = parse_items(&content)?;
+    validate_items(&incoming)?;
+    merge_into(app, connection_id, incoming, strategy)
+}
+
+/// Writes validated items into the connection's catalog and notifies the UI.
+pub fn merge_into(
+    app: &AppHandle,
+    connection_id: &str,
+    incoming: Vec<TopicCatalogItem>,
+    strategy: TopicCatalogMergeStrategy,
+) -> Result<TopicCatalogImportResult> {
+    let mut config = config_store::load_config(app)?;
+    let mut document = config
+        .connection_topic_docs
+        .get(connection_id)
+        .cloned()
+        .unwrap_or_else(|| ConnectionTopicDocument {
+            version: CATALOG_VERSION.to_string(),
+            updated_at: 0,
+            topics: Vec::new(),
+        });
+
+    let (added, updated, removed) = match strategy {
+        TopicCatalogMergeStrategy::Replace => {
+            let removed = document.topics.len();
+            document.topics = dedupe_ids(incoming);
+            (document.topics.len(), 0, removed)
+        }
+        TopicCatalogMergeStrategy::MergeByTopic => merge_by_topic(&mut document.topics, incoming),
+    };
+    document.updated_at = now_millis();
+
+    config
+        .connection_topic_docs
+        .insert(connection_id.to_string(), document.clone());
+    config_store::save_config(app, &config)?;
+
+    let result = TopicCatalogImportResult {
+        connection_id: connection_id.to_string(),
+        document,
+        added,
+        updated,
+        removed,
+    };
+    let _ = app.emit("topic-catalog-updated", &result);
+    Ok(result)
+}
+
+fn read_source(path_or_content: &str) -> Result<String> {
+    let trimmed = path_or_content.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow!("catalog import source is empty"));
+    }
+    if trimmed.starts_with('{') || trimmed.starts_with('[') || trimmed.contains('\n') {
+        return Ok(path_or_content.to_string());
+    }
+    fs::read_to_string(trimmed).with_context(|| format!("failed to read catalog file: {trimmed}"))
+}
+
+/// Accepts an exported catalog document, a bare item array, or an AsyncAPI document.
+fn parse_items(content: &str) -> Result<Vec<TopicCatalogItem>> {
+    let value = match serde_json::from_str::<Value>(content) {
+        Ok(value) => value,
+        Err(_) => return Ok(asyncapi::import(content)?.topics),
+    };
+    if value.get("asyncapi").is_some() {
+        return Ok(asyncapi::import(content)?.topics);
+    }
+
+    let topics = match value {
+        Value::Array(_) => value,
+        Value::Object(mut map) => map
+            .remove("topics")
+            .ok_or_else(|| anyhow!("catalog document has no `topics` array"))?,
+        _ => return Err(anyhow!("catalog must be a JSON object or array")),
+    };
+    serde_json::from_value(topics).context("catalog topics do not match the expected shape")
+}
+
+fn validate_items(items: &[TopicCatalogItem]) -> Result<()> {
+    for (index, item) in items.iter().enumerate() {
+        let label = if item.name.trim().is_empty() {
+            format!("item {}", index + 1)
+        } else {
+            format!("'{}'", item.name)
+        };
+        if !is_valid_topic_filter(item.topic.trim()) {
+            return Err(anyhow!("{label} has an invalid topic: '{}'", item.topic));
+        }
+        if item.qos > 2 {
+            return Err(anyhow!("{label} has an invalid QoS: {}", item.qos));
+        }
+    }
+    Ok(())
+}
+
+/// Imported items replace same-topic entries in place (keeping their ids); the rest are appended.
+fn merge_by_topic(
+    existing: &mut Vec<TopicCatalogItem>,
+    incoming: Vec<TopicCatalogItem>,
+) -> (usize, usize, usize) {
+    let mut added = 0;
+    let mut updated = 0;
+    for mut item in incoming {
+        match existing
+            .iter_mut()
+            .find(|current| current.topic == item.topic)
+        {
+            Some(current) => {
+                item.id = std::mem::take(&mut current.id);
+                *current = item;
+                updated += 1;
+            }
+            None => {
+                existing.push(item);
+                added += 1;
+            }
+        }
+    }
+    let deduped = dedupe_ids(std::mem::take(existing));
+    *existing = deduped;
+    (added, updated, 0)
+}
+
+fn dedupe_ids(items: Vec<TopicCatalogItem>) -> Vec<TopicCatalogItem> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut item)| {
+            if item.id.trim().is_empty() || !seen.insert(item.id.clone()) {
+                item.id = format!("imported-{}-{index}", now_millis());
+                seen.insert(item.id.clone());
+            }
+            if item.name.trim().is_empty() {
+                item.name = item.topic.clone();
+            }
+            item
+        })
+        .collect()
+}
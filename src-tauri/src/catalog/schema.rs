@@ -0,0 +1,162 @@
+use serde_json::{Map, Value};
+
+/// Validates a value against the JSON Schema keywords catalogs use in practice: `type`, `enum`,
+/// `const`, `properties`, `required`, `additionalProperties`, `items`, numeric and length bounds.
+/// Unknown keywords are ignored. Returns one message per violation, prefixed with a JSON pointer.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+    let at = if path.is_empty() { "/" } else { path };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            errors.push(format!(
+                "{at}: expected {}, found {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!("{at}: value is not one of the allowed values"));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{at}: value must equal {expected}"));
+        }
+    }
+
+    match value {
+        Value::Object(object) => check_object(schema, object, path, errors),
+        Value::Array(items) => {
+            check_bounds(
+                schema,
+                "minItems",
+                "maxItems",
+                items.len(),
+                "items",
+                at,
+                errors,
+            );
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            check_bounds(
+                schema,
+                "minLength",
+                "maxLength",
+                length,
+                "characters",
+                at,
+                errors,
+            );
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    errors.push(format!("{at}: {number} is below the minimum {minimum}"));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    errors.push(format!("{at}: {number} is above the maximum {maximum}"));
+                }
+            }
+        }
+        Value::Bool(_) | Value::Null => {}
+    }
+}
+
+fn check_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let at = if path.is_empty() { "/" } else { path };
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                errors.push(format!("{at}: missing required property '{name}'"));
+            }
+        }
+    }
+    for (name, child) in object {
+        let child_path = format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"));
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(child_schema) => check(child_schema, child, &child_path, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{at}: unexpected property '{name}'"));
+                }
+                Some(extra @ Value::Object(_)) => check(extra, child, &child_path, errors),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn check_bounds(
+    schema: &Map<String, Value>,
+    min_key: &str,
+    max_key: &str,
+    actual: usize,
+    unit: &str,
+    at: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(min) = schema.get(min_key).and_then(Value::as_u64) {
+        if (actual as u64) < min {
+            errors.push(format!(
+                "{at}: has {actual} {unit}, at least {min} required"
+            ));
+        }
+    }
+    if let Some(max) = schema.get(max_key).and_then(Value::as_u64) {
+        if actual as u64 > max {
+            errors.push(format!("{at}: has {actual} {unit}, at most {max} allowed"));
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_i64().is_some() || value.as_u64().is_some(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,144 @@
+//! Copies text to the system clipboard from the backend, using the clipboard tool each platform
+//! ships with, so large selections never have to pass through the webview.
+
+use crate::history::escape_csv;
+use crate::models::{HistoryMessageRecord, MessageDirection};
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Formats history records as `csv`, `json` or `markdown`.
+pub fn format_records(records: &[HistoryMessageRecord], format: &str) -> Result<String> {
+    match format.to_ascii_lowercase().as_str() {
+        "csv" => {
+            let mut out = String::from("id,timestamp,topic,payload,qos,retain,direction\n");
+            for record in records {
+                out.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    record.id,
+                    record.timestamp,
+                    escape_csv(&record.topic),
+                    escape_csv(&record.payload),
+                    record.qos,
+                    if record.retain { 1 } else { 0 },
+                    direction(record)
+                ));
+            }
+            Ok(out)
+        }
+        "json" => serde_json::to_string_pretty(records).context("failed to serialize records"),
+        "markdown" | "md" => {
+            let mut out = String::from(
+                "| id | time | topic | payload | qos | retain | direction |\n\
+                 |---:|---|---|---|---:|---|---|\n",
+            );
+            for record in records {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} | {} |\n",
+                    record.id,
+                    format_time(record.timestamp),
+                    markdown_cell(&record.topic),
+                    markdown_cell(&record.payload),
+                    record.qos,
+                    if record.retain { "yes" } else { "no" },
+                    direction(record)
+                ));
+            }
+            Ok(out)
+        }
+        other => bail!("unsupported clipboard format: {other}"),
+    }
+}
+
+pub fn copy_text(text: &str) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        // `clip.exe` mangles anything outside the console code page.
+        pipe_to(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+                 Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+            ],
+            text,
+        )
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        pipe_to("pbcopy", &[], text)
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let mut tools: Vec<(&str, &[&str])> = vec![
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ];
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.insert(0, ("wl-copy", &[]));
+        }
+        let mut last_error = None;
+        for (program, args) in tools {
+            match pipe_to(program, args, text) {
+                Ok(()) => return Ok(()),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("no clipboard tool available"))
+            .context("install wl-clipboard, xclip or xsel to copy to the clipboard"))
+    }
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to start {program}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .with_context(|| format!("failed to write to {program}"))?;
+    }
+    let status = child
+        .wait()
+        .with_context(|| format!("failed to wait for {program}"))?;
+    if !status.success() {
+        bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+fn direction(record: &HistoryMessageRecord) -> &'static str {
+    if matches!(record.direction, MessageDirection::Out) {
+        "out"
+    } else {
+        "in"
+    }
+}
+
+fn format_time(millis: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis as i64)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string()
+        })
+        .unwrap_or_else(|| millis.to_string())
+}
+
+/// Keeps a value on one table row: pipes are escaped and line breaks become `<br>`.
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
@@ -1,15 +1,68 @@
 use crate::ai::payload::generate_payload;
+use crate::catalog::{self, asyncapi, codegen, docs};
+use crate::clipboard;
 use crate::config_store;
+use crate::crash;
+use crate::credentials;
+use crate::export_log;
+use crate::history::ExportTimeZone;
+use crate::homeassistant;
+use crate::integrations::{elasticsearch, influx, mosquitto};
+use crate::inventory;
+use crate::jobs::JobProgress;
+use crate::json_preview;
+use crate::labels;
 use crate::models::{
-    AiConfig, AppConfigPaths, AuthIdentity, BrokerConfig, ConnectionProfile, HistoryExportResult,
-    HistoryMessageRecord, NativeAppConfig, ResolvedConnection, TransportProtocol,
+    AiConfig, AlertEventRecord, AppConfigPaths, AuthIdentity, AutomationConfig, AutomationRule,
+    BenchmarkWorkload, BrokerConfig, BrokerSysStats, ClientIdCollision, ConnectLimitsConfig,
+    ConnectionDuplicate, ConnectionDuplicateOverrides, ConnectionEventRecord, ConnectionHealth,
+    ConnectionInfo, ConnectionProfile, ConnectionTopicDocument, ControlApiConfig, ControlApiStatus,
+    CorrelationThread, CrashReportSummary, CredentialAuditReport, CredentialExpiry, DataWipeReport,
+    DecoderPluginStatus, EchoLatencyStats, ElasticsearchExportOptions, EmbeddedBrokerConfig,
+    EmbeddedBrokerStatus, ExportRecord, ExportRequest, FeedConfig, FeedStatus, FileWatcherConfig,
+    FileWatcherMetrics, FileWatchersConfig, HaDiscoveryRegistry, HistoryCheckpointResult,
+    HistoryDiskUsage, HistoryExportResult, HistoryMessageRecord, HistoryPauseStatus, HistoryRepair,
+    HistorySearchHit, HistorySearchQuery, HistorySort, HistoryStreamQuery, HistoryTimeSource,
+    InfluxMapping, IntegrationsConfig, InventoryImportResult, InventoryMapping, JobKind, JobStatus,
+    JsonPreviewNode, KafkaBridgeConfig, KafkaBridgeMetrics, KeepAliveStats, MemoryLimitsConfig,
+    MessageDirection, MqttBatchItem, MqttRequestResult, MultiPublishResult, NativeAppConfig,
+    Payload, PayloadSizeHistogram, PayloadTemplate, PayloadTemplateDraft, PayloadTimestampRule,
+    PayloadTimestampsConfig, PcapImportOptions, PcapImportResult, PinnedMessageRecord,
+    PluginsConfig, PublishViolation, Qos2Flow, ReplayExportOptions, ResolvedConnection,
+    RetainedSnapshotImportResult, RuleBundleImportResult, RuleTestRange, RuleTestResult,
+    RuleTestSample, RuntimeMetrics, SessionHooksConfig, ShortcutsConfig, SocketTapConfig,
+    SocketTapMetrics, SocketTapsConfig, StorageGuardConfig, StorageStatus, SubscribeOptions,
+    SubscriptionPreset, TelemetryConfig, TelemetryStatus, TopicCatalogImportResult,
+    TopicCatalogMergeStrategy, TopicDiscoveryDraft, TopicFieldStats, TopicFilterValidation,
+    TopicListExportOptions, TopicUnreadCount, TransformTestResult, TransformsConfig,
+    TransportProtocol, TrayBadges, UserProperty,
 };
 use crate::mqtt::now_millis;
+use crate::mqtt::sys_stats::SYS_TOPIC_FILTER;
+use crate::mqtt::{benchmark, client_id, memory, tls, transform, validate};
+use crate::payload_time;
+use crate::pcap;
+use crate::plugins;
+use crate::profiles;
+use crate::replay::ReplayWriter;
+use crate::rules::{alert_log, bundle as rule_bundle, dry_run};
+use crate::shortcuts;
+use crate::snapshot;
 use crate::state::AppState;
-use rfd::FileDialog;
+use crate::subscription_presets;
+use crate::telemetry;
+use crate::templates;
+use crate::tray;
+use crate::windows;
+use crate::wipe;
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use tauri::{Manager, State};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+use tokio::task::JoinSet;
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn mqtt_connect(
@@ -20,36 +73,237 @@ pub async fn mqtt_connect(
     identities: Vec<AuthIdentity>,
 ) -> Result<(), String> {
     let resolved = resolve_connection(profile, brokers, identities)?;
+    if let Some(other) = state.mqtt_manager.client_id_conflict(&resolved) {
+        // Brokers disconnect the older session, so the two profiles would keep kicking each other.
+        tracing::warn!(
+            "client id '{}' of {} is already in use by {other}",
+            resolved.client_id,
+            resolved.id
+        );
+        let _ = app.emit(
+            "client-id-collision",
+            ClientIdCollision {
+                client_id: resolved.client_id.clone(),
+                broker: client_id::broker_key(&resolved.host, resolved.port),
+                connection_ids: vec![other, resolved.id.clone()],
+            },
+        );
+    }
     state
         .mqtt_manager
         .connect(app, resolved)
         .map_err(|e| e.to_string())
 }
 
+/// Expiry of the client certificates and tokens of `identities`, for the identity editor.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn identity_expiry(
+    identities: Vec<AuthIdentity>,
+) -> Result<Vec<CredentialExpiry>, String> {
+    Ok(credentials::check(&identities, now_millis()))
+}
+
+/// Audits the saved configuration for plaintext, weak and reused secrets and unused identities.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn credentials_audit(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<CredentialAuditReport, String> {
+    let sources = credentials::AuditSources {
+        app_config: config_store::load_config(&app).map_err(|e| e.to_string())?,
+        automation: state
+            .rule_manager
+            .config(&app)
+            .await
+            .map_err(|e| e.to_string())?,
+        integrations: state
+            .kafka_bridges
+            .config(&app)
+            .await
+            .map_err(|e| e.to_string())?,
+        control_api: state.control_api.config(&app).map_err(|e| e.to_string())?,
+        feed: state.feed.config(&app).map_err(|e| e.to_string())?,
+        telemetry: telemetry::load(&app).map_err(|e| e.to_string())?,
+    };
+    Ok(credentials::audit(&sources, now_millis()))
+}
+
+/// Lists profiles that resolve to the same client id on the same broker.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn client_id_collisions(
+    profiles: Vec<ConnectionProfile>,
+    brokers: Vec<BrokerConfig>,
+    identities: Vec<AuthIdentity>,
+) -> Result<Vec<ClientIdCollision>, String> {
+    let mut groups: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    for profile in profiles {
+        let Ok(resolved) = resolve_connection(profile, brokers.clone(), identities.clone()) else {
+            continue;
+        };
+        if resolved.client_id.trim().is_empty() {
+            continue;
+        }
+        groups
+            .entry((
+                client_id::broker_key(&resolved.host, resolved.port),
+                resolved.client_id,
+            ))
+            .or_default()
+            .push(resolved.id);
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|((broker, client_id), connection_ids)| ClientIdCollision {
+            client_id,
+            broker,
+            connection_ids,
+        })
+        .collect())
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn mqtt_disconnect(
     state: State<'_, AppState>,
     connection_id: String,
 ) -> Result<(), String> {
+    state.sys_stats.stop(&connection_id);
+    state.memory.remove(&connection_id);
+    state.flow_control.remove(&connection_id);
+    state.keep_alive.remove(&connection_id);
+    state.health.remove(&connection_id);
+    state.qos2.clear_pending(&connection_id);
+    state.publish_acks.remove(&connection_id);
+    state.subscription_acks.remove(&connection_id);
+    state.echo_latency.remove(&connection_id);
     state
         .mqtt_manager
         .disconnect(&connection_id)
         .map_err(|e| e.to_string())
 }
 
+/// Acknowledges the highest `mqtt-message-batch`/`mqtt-message-summary` sequence processed.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_batch_ack(
+    state: State<'_, AppState>,
+    connection_id: String,
+    seq: u64,
+) -> Result<(), String> {
+    state.flow_control.ack(&connection_id, seq);
+    Ok(())
+}
+
+/// Subscribes to `topic`. `options` carries the MQTT 5 subscription options (no local, retain
+/// as published, retain handling) and is ignored on 3.1.1 connections.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn mqtt_subscribe(
     state: State<'_, AppState>,
     connection_id: String,
     topic: String,
     qos: u8,
+    options: Option<SubscribeOptions>,
+) -> Result<(), String> {
+    state
+        .mqtt_manager
+        .subscribe(&connection_id, topic, qos, options.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Subscribes to `topic` without keeping the subscription: the session unsubscribes after
+/// `maxMessages` messages or `durationSecs` seconds, whichever comes first, and emits
+/// `mqtt-peek-ended`. Without either limit the peek stops after `DEFAULT_PEEK_MESSAGES`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_peek(
+    state: State<'_, AppState>,
+    connection_id: String,
+    topic: String,
+    qos: u8,
+    max_messages: Option<u32>,
+    duration_secs: Option<u64>,
 ) -> Result<(), String> {
+    if !crate::mqtt::is_valid_topic_filter(&topic) {
+        return Err(format!("invalid topic filter '{topic}'"));
+    }
+    let max_messages = max_messages.filter(|max| *max > 0);
+    let duration = duration_secs
+        .filter(|secs| *secs > 0)
+        .map(std::time::Duration::from_secs);
+    let max_messages = match (max_messages, duration) {
+        (None, None) => Some(DEFAULT_PEEK_MESSAGES),
+        (max_messages, _) => max_messages,
+    };
     state
         .mqtt_manager
-        .subscribe(&connection_id, topic, qos)
+        .peek(&connection_id, topic, qos, max_messages, duration)
+        .map_err(|e| e.to_string())
+}
+
+/// Structural preview of a stored JSON message, for browsing huge payloads as a lazy tree.
+#[tauri::command(rename_all = "camelCase")]
+#[allow(clippy::too_many_arguments)]
+pub async fn history_json_preview(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    message_id: i64,
+    path: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    depth: Option<usize>,
+) -> Result<JsonPreviewNode, String> {
+    let record = state
+        .history_manager
+        .query_by_ids(&app, &connection_id, vec![message_id])
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "the message is no longer in history".to_string())?;
+    tokio::task::spawn_blocking(move || {
+        json_preview::preview(
+            &record.payload,
+            path.as_deref(),
+            offset.unwrap_or(0),
+            limit,
+            depth,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("{e:#}"))
+}
+
+/// Copies a saved connection with its topic catalog and default subscriptions; the copy is
+/// saved right after the original.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connection_duplicate(
+    app: tauri::AppHandle,
+    connection_id: String,
+    overrides: Option<ConnectionDuplicateOverrides>,
+) -> Result<ConnectionDuplicate, String> {
+    profiles::duplicate(&app, &connection_id, overrides.unwrap_or_default())
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command(rename_all = "camelCase")]
+pub async fn subscription_presets_list(
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> Result<Vec<SubscriptionPreset>, String> {
+    subscription_presets::list(&app, &connection_id).map_err(|e| e.to_string())
+}
+
+/// Replaces a profile's default subscriptions; they are applied from the next connect.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn subscription_presets_save(
+    app: tauri::AppHandle,
+    connection_id: String,
+    subscriptions: Vec<SubscriptionPreset>,
+) -> Result<Vec<SubscriptionPreset>, String> {
+    subscription_presets::save(&app, &connection_id, subscriptions).map_err(|e| e.to_string())
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn mqtt_unsubscribe(
     state: State<'_, AppState>,
@@ -62,26 +316,371 @@ pub async fn mqtt_unsubscribe(
         .map_err(|e| e.to_string())
 }
 
+/// Queues a publish and returns its id. For QoS 1 and 2 the broker's PUBACK or PUBCOMP
+/// arrives later as an `mqtt-publish-ack` event carrying the same id.
 #[tauri::command(rename_all = "camelCase")]
+#[allow(clippy::too_many_arguments)]
 pub async fn mqtt_publish(
-    state: State<'_, AppState>,
     app: tauri::AppHandle,
     connection_id: String,
     topic: String,
     payload: String,
     qos: u8,
     retain: bool,
+    user_properties: Option<Vec<UserProperty>>,
+    confirmation: Option<String>,
+) -> Result<u64, String> {
+    require_confirmation(&app, &[connection_id.as_str()], confirmation.as_deref())?;
+    publish_recorded(
+        &app,
+        &connection_id,
+        topic,
+        Payload::from(payload),
+        qos,
+        retain,
+        user_properties.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Publishes an MQTT 5 request with a response topic and correlation data and waits for the
+/// reply carrying the same correlation data. Without `responseTopic` a unique one is used.
+#[tauri::command(rename_all = "camelCase")]
+#[allow(clippy::too_many_arguments)]
+pub async fn mqtt_request(
+    app: tauri::AppHandle,
+    connection_id: String,
+    topic: String,
+    payload: String,
+    qos: u8,
+    response_topic: Option<String>,
+    timeout_ms: Option<u64>,
+    user_properties: Option<Vec<UserProperty>>,
+    confirmation: Option<String>,
+) -> Result<MqttRequestResult, String> {
+    require_confirmation(&app, &[connection_id.as_str()], confirmation.as_deref())?;
+    let timeout = timeout_ms.map_or(crate::mqtt::request::DEFAULT_TIMEOUT, |ms| {
+        std::time::Duration::from_millis(ms).min(crate::mqtt::request::MAX_TIMEOUT)
+    });
+    crate::mqtt::request::send(
+        &app,
+        &connection_id,
+        topic,
+        Payload::from(payload),
+        qos,
+        response_topic,
+        timeout,
+        user_properties.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| format!("{e:#}"))
+}
+
+/// Publishes the same message on several connections at once, e.g. to compare how brokers
+/// handle it. Results come back in the order of `connection_ids`; acknowledgements follow as
+/// `mqtt-publish-ack` events.
+#[tauri::command(rename_all = "camelCase")]
+#[allow(clippy::too_many_arguments)]
+pub async fn mqtt_publish_multi(
+    app: tauri::AppHandle,
+    connection_ids: Vec<String>,
+    topic: String,
+    payload: String,
+    qos: u8,
+    retain: bool,
+    user_properties: Option<Vec<UserProperty>>,
+    confirmation: Option<String>,
+) -> Result<Vec<MultiPublishResult>, String> {
+    let mut seen = HashSet::new();
+    let connection_ids = connection_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect::<Vec<_>>();
+    if connection_ids.is_empty() {
+        return Err("select at least one connection".to_string());
+    }
+    require_confirmation(
+        &app,
+        &connection_ids
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        confirmation.as_deref(),
+    )?;
+
+    let payload = Payload::from(payload);
+    let user_properties = user_properties.unwrap_or_default();
+    let mut tasks = JoinSet::new();
+    for (index, connection_id) in connection_ids.iter().enumerate() {
+        let app = app.clone();
+        let connection_id = connection_id.clone();
+        let topic = topic.clone();
+        let payload = payload.clone();
+        let user_properties = user_properties.clone();
+        tasks.spawn(async move {
+            let result = publish_recorded(
+                &app,
+                &connection_id,
+                topic,
+                payload,
+                qos,
+                retain,
+                user_properties,
+            )
+            .await;
+            (index, result)
+        });
+    }
+
+    let mut results = connection_ids
+        .into_iter()
+        .map(|connection_id| MultiPublishResult {
+            connection_id,
+            publish_id: None,
+            error: None,
+        })
+        .collect::<Vec<_>>();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, result) = joined.map_err(|e| e.to_string())?;
+        match result {
+            Ok(publish_id) => results[index].publish_id = Some(publish_id),
+            Err(error) => results[index].error = Some(error),
+        }
+    }
+    Ok(results)
+}
+
+/// Refuses the command when one of `connection_ids` is a saved production profile and
+/// `confirmation` doesn't name it. Unsaved connections are not checked.
+fn require_confirmation(
+    app: &tauri::AppHandle,
+    connection_ids: &[&str],
+    confirmation: Option<&str>,
 ) -> Result<(), String> {
-    state
+    profiles::require_confirmation(app, connection_ids, confirmation).map_err(|e| e.to_string())
+}
+
+async fn publish_recorded(
+    app: &tauri::AppHandle,
+    connection_id: &str,
+    topic: String,
+    payload: Payload,
+    qos: u8,
+    retain: bool,
+    user_properties: Vec<UserProperty>,
+) -> Result<u64, String> {
+    let state = app.state::<AppState>();
+    let publish_id = state
         .mqtt_manager
-        .publish(&connection_id, topic.clone(), payload.clone(), qos, retain)
+        .publish(
+            connection_id,
+            topic.clone(),
+            payload.clone(),
+            qos,
+            retain,
+            user_properties.clone(),
+        )
         .map_err(|e| e.to_string())?;
 
+    state
+        .recent
+        .record_outgoing(connection_id, &topic, &payload, qos, retain);
     state
         .history_manager
-        .append_outgoing(&app, &connection_id, &topic, &payload, qos, retain)
+        .append_outgoing(
+            app,
+            connection_id,
+            &topic,
+            payload,
+            qos,
+            retain,
+            user_properties,
+        )
         .await
-        .map_err(|e| format!("published, but failed to persist outgoing history: {e}"))
+        .map_err(|e| format!("published, but failed to persist outgoing history: {e}"))?;
+    Ok(publish_id)
+}
+
+/// Switches `mqtt-ping` events for a connection on or off; the setting survives reconnects.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_ping_debug_set(
+    state: State<'_, AppState>,
+    connection_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    state.keep_alive.set_debug(&connection_id, enabled);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_keep_alive_stats(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<KeepAliveStats, String> {
+    Ok(state.keep_alive.stats(&connection_id))
+}
+
+/// Outgoing QoS 1/2 publishes still waiting for the broker's acknowledgement in the publish
+/// journal of a connection with `replayUnackedPublishes`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn publish_journal_pending(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<usize, String> {
+    Ok(state.publish_journal.pending(&connection_id))
+}
+
+/// Drops a connection's unacknowledged publishes so the next session doesn't republish them.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn publish_journal_clear(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<(), String> {
+    state.publish_journal.clear(&connection_id);
+    Ok(())
+}
+
+/// Runs the same benchmark workload against two saved connections, one after the other, in a
+/// background job whose result is the comparative `BenchmarkReport`; returns the job id.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn benchmark_compare(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_ids: Vec<String>,
+    workload: Option<BenchmarkWorkload>,
+    confirmation: Option<String>,
+) -> Result<String, String> {
+    let workload = workload.unwrap_or_default();
+    benchmark::validate(&workload).map_err(|e| e.to_string())?;
+    if connection_ids.len() != 2 || connection_ids[0] == connection_ids[1] {
+        return Err("select two different connections to compare".to_string());
+    }
+    require_confirmation(
+        &app,
+        &[&connection_ids[0], &connection_ids[1]],
+        confirmation.as_deref(),
+    )?;
+    let config = config_store::load_config(&app).map_err(|e| e.to_string())?;
+    let connections = connection_ids
+        .iter()
+        .map(|id| {
+            let profile = config
+                .connections
+                .iter()
+                .find(|profile| &profile.id == id)
+                .cloned()
+                .ok_or_else(|| format!("connection {id} not found"))?;
+            resolve_connection(profile, config.brokers.clone(), config.identities.clone())
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let label = format!(
+        "Benchmark: {} vs {}",
+        connections[0].name, connections[1].name
+    );
+    Ok(state
+        .jobs
+        .start(&app, JobKind::Benchmark, label, move |progress| {
+            benchmark::run(connections, workload, progress)
+        }))
+}
+
+/// The connection's current health score. Score changes are kept in the connection event log
+/// as `healthChanged` events.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connection_health(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<ConnectionHealth, String> {
+    Ok(state.health.health(&connection_id))
+}
+
+/// Starts or stops recording the QoS 2 handshake of outgoing publishes. Enabling discards
+/// flows recorded earlier.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_qos2_verify_set(
+    state: State<'_, AppState>,
+    connection_id: String,
+    enabled: bool,
+) -> Result<(), String> {
+    state.qos2.set_enabled(&connection_id, enabled);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_qos2_flows(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<Vec<Qos2Flow>, String> {
+    Ok(state.qos2.flows(&connection_id))
+}
+
+/// End-to-end latency between outgoing publishes and their echo on the session's own
+/// subscriptions, per topic.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_echo_latency(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<Vec<EchoLatencyStats>, String> {
+    Ok(state.echo_latency.stats(&connection_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_echo_latency_reset(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<(), String> {
+    state.echo_latency.remove(&connection_id);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_topic_filter_validate(filter: String) -> Result<TopicFilterValidation, String> {
+    Ok(crate::mqtt::validate_topic_filter(&filter))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_topic_matches(filter: String, topic: String) -> Result<bool, String> {
+    Ok(crate::mqtt::topic_matches(&filter, &topic))
+}
+
+/// Resolved address, TLS parameters and WebSocket URL of a connected session.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connection_info(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<ConnectionInfo, String> {
+    let mut info = state
+        .mqtt_manager
+        .connection_info(&connection_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("transport details for {connection_id} are not available yet"))?;
+    info.keep_alive_secs = state.keep_alive.stats(&connection_id).interval_secs;
+    Ok(info)
+}
+
+/// Dry-runs a publish: reports every topic, QoS, size and schema violation without sending.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mqtt_publish_validate(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    topic: String,
+    payload: String,
+    qos: u8,
+) -> Result<Vec<PublishViolation>, String> {
+    let capabilities = state
+        .mqtt_manager
+        .capabilities(&connection_id)
+        .map_err(|e| e.to_string())?;
+    let document = catalog::load(&app, &connection_id).map_err(|e| e.to_string())?;
+    Ok(validate::check_publish(
+        &topic,
+        &payload,
+        qos,
+        capabilities,
+        &document.topics,
+    ))
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -101,8 +700,8 @@ pub async fn ai_generate_payload(
         prompt_system.as_deref(),
         prompt_user.as_deref(),
     )
-        .await
-        .map_err(|e| e.to_string())
+    .await
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -112,7 +711,43 @@ pub async fn load_app_config(app: tauri::AppHandle) -> Result<NativeAppConfig, S
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn save_app_config(app: tauri::AppHandle, config: NativeAppConfig) -> Result<(), String> {
-    config_store::save_config(&app, &config).map_err(|e| e.to_string())
+    for profile in &config.connections {
+        labels::validate(profile).map_err(|e| e.to_string())?;
+    }
+    // Templates and subscription presets are owned by their own commands; a stale copy from the
+    // caller must not overwrite edits made elsewhere.
+    config_store::update_config(&app, |stored| {
+        let mut config = config;
+        subscription_presets::keep_stored(stored, &mut config);
+        let templates = std::mem::take(&mut stored.publisher_templates);
+        *stored = config;
+        stored.publisher_templates = templates;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+    // Connection names or profiles may have changed.
+    tray::refresh(&app);
+    Ok(())
+}
+
+/// Connections whose labels match `selector`, e.g. `environment=prod,site!=berlin,critical`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connections_by_label(
+    app: tauri::AppHandle,
+    selector: String,
+) -> Result<Vec<ConnectionProfile>, String> {
+    let selector = labels::LabelSelector::parse(&selector).map_err(|e| e.to_string())?;
+    let config = config_store::load_config(&app).map_err(|e| e.to_string())?;
+    Ok(labels::select(config.connections, &selector))
+}
+
+/// Label keys in use, each with the values seen across connections.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connection_labels_list(
+    app: tauri::AppHandle,
+) -> Result<BTreeMap<String, BTreeSet<String>>, String> {
+    let config = config_store::load_config(&app).map_err(|e| e.to_string())?;
+    Ok(labels::catalog(&config.connections))
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -145,10 +780,16 @@ pub async fn history_query_latest(
     app: tauri::AppHandle,
     connection_id: String,
     limit: Option<usize>,
+    sort: Option<HistorySort>,
 ) -> Result<Vec<HistoryMessageRecord>, String> {
     state
         .history_manager
-        .query_latest(&app, &connection_id, limit.unwrap_or(200))
+        .query_latest(
+            &app,
+            &connection_id,
+            limit.unwrap_or(200),
+            sort.unwrap_or_default(),
+        )
         .await
         .map_err(|e| e.to_string())
 }
@@ -161,6 +802,7 @@ pub async fn history_query_before(
     before_ts: u64,
     before_id: i64,
     limit: Option<usize>,
+    sort: Option<HistorySort>,
 ) -> Result<Vec<HistoryMessageRecord>, String> {
     state
         .history_manager
@@ -170,147 +812,2393 @@ pub async fn history_query_before(
             before_ts,
             before_id,
             limit.unwrap_or(200),
+            sort.unwrap_or_default(),
         )
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn history_clear(
+pub async fn history_pin(
     state: State<'_, AppState>,
     app: tauri::AppHandle,
     connection_id: String,
+    message_id: i64,
+    note: Option<String>,
 ) -> Result<(), String> {
+    let note = note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
     state
         .history_manager
-        .clear_connection(&app, &connection_id)
+        .pin(&app, &connection_id, message_id, note)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn history_delete_connection(
+pub async fn history_unpin(
     state: State<'_, AppState>,
     app: tauri::AppHandle,
     connection_id: String,
+    message_id: i64,
 ) -> Result<(), String> {
     state
         .history_manager
-        .delete_connection(&app, &connection_id)
+        .unpin(&app, &connection_id, message_id)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn history_export(
+pub async fn history_unread_counts(
     state: State<'_, AppState>,
     app: tauri::AppHandle,
     connection_id: String,
-    format: Option<String>,
-    from_ts: Option<u64>,
-    to_ts: Option<u64>,
-    output_path: Option<String>,
-) -> Result<HistoryExportResult, String> {
-    let normalized_format = format
-        .as_deref()
-        .map(str::to_lowercase)
-        .unwrap_or_else(|| "ndjson".to_string());
+    topic_filter: Option<String>,
+) -> Result<Vec<TopicUnreadCount>, String> {
+    let topic_filter = checked_topic_filter(topic_filter)?;
     state
         .history_manager
-        .export_connection(
-            &app,
-            &connection_id,
-            &normalized_format,
-            from_ts,
-            to_ts,
-            output_path.as_deref(),
-        )
+        .query_unread(&app, &connection_id, topic_filter)
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Marks every topic matching `topicFilter`, or all topics, read up to its latest message.
 #[tauri::command(rename_all = "camelCase")]
-pub async fn history_pick_export_path(
+pub async fn history_mark_read(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
     connection_id: String,
-    format: Option<String>,
-) -> Result<Option<String>, String> {
-    let normalized_format = format
-        .as_deref()
-        .map(str::to_lowercase)
-        .unwrap_or_else(|| "ndjson".to_string());
-    let ext = if normalized_format == "csv" {
-        "csv"
-    } else {
-        "ndjson"
-    };
-    let file_name = format!(
-        "{}-history-{}.{}",
-        safe_name(&connection_id),
-        now_millis(),
-        ext
-    );
-
-    let mut dialog = FileDialog::new().set_file_name(&file_name);
-    dialog = if ext == "csv" {
-        dialog.add_filter("CSV", &["csv"])
-    } else {
-        dialog.add_filter("NDJSON", &["ndjson"])
-    };
+    topic_filter: Option<String>,
+) -> Result<u64, String> {
+    let topic_filter = checked_topic_filter(topic_filter)?;
+    state
+        .history_manager
+        .mark_read(&app, &connection_id, topic_filter)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    Ok(dialog.save_file().map(|p| normalize_selected_path(p, ext)))
+/// Trimmed topic filter, `None` when blank; errors when it is not a valid filter.
+fn checked_topic_filter(topic_filter: Option<String>) -> Result<Option<String>, String> {
+    let topic_filter = topic_filter
+        .map(|filter| filter.trim().to_string())
+        .filter(|filter| !filter.is_empty());
+    if let Some(filter) = &topic_filter {
+        if !crate::mqtt::is_valid_topic_filter(filter) {
+            return Err(format!("invalid topic filter: {filter}"));
+        }
+    }
+    Ok(topic_filter)
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn topic_catalog_export(
+pub async fn history_query_pinned(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
     connection_id: String,
-    content: String,
-) -> Result<Option<String>, String> {
-    let file_name = format!(
+) -> Result<Vec<PinnedMessageRecord>, String> {
+    state
+        .history_manager
+        .query_pinned(&app, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Most rows one clipboard copy will format.
+const MAX_CLIPBOARD_ROWS: usize = 10_000;
+/// Messages written to history per batch while importing a capture.
+const PCAP_IMPORT_CHUNK: usize = 500;
+/// Messages a peek subscription waits for when neither a count nor a duration is given.
+const DEFAULT_PEEK_MESSAGES: u32 = 10;
+
+/// Formats the given history records (`csv`, `json` or `markdown`, default `csv`) and puts them
+/// on the system clipboard. Returns how many records were copied.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_copy_to_clipboard(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    ids: Vec<i64>,
+    format: Option<String>,
+) -> Result<usize, String> {
+    if ids.is_empty() {
+        return Err("no messages selected".to_string());
+    }
+    if ids.len() > MAX_CLIPBOARD_ROWS {
+        return Err(format!(
+            "at most {MAX_CLIPBOARD_ROWS} messages can be copied at once; export larger selections"
+        ));
+    }
+    let records = state
+        .history_manager
+        .query_by_ids(&app, &connection_id, ids)
+        .await
+        .map_err(|e| e.to_string())?;
+    if records.is_empty() {
+        return Err("the selected messages are no longer in history".to_string());
+    }
+
+    let format = format.unwrap_or_else(|| "csv".to_string());
+    let text = clipboard::format_records(&records, &format).map_err(|e| format!("{e:#}"))?;
+    tokio::task::spawn_blocking(move || clipboard::copy_text(&text))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{e:#}"))?;
+    Ok(records.len())
+}
+
+/// History databases that failed the start-up integrity check and were replaced this run.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_repairs(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<HistoryRepair>, String> {
+    state
+        .history_manager
+        .repairs(&app)
+        .map_err(|e| e.to_string())
+}
+
+/// Disk space used by each connection's history, the exports folder, and free space left.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_disk_usage(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<HistoryDiskUsage, String> {
+    let profiles = config_store::load_config(&app)
+        .map_err(|e| e.to_string())?
+        .connections
+        .into_iter()
+        .map(|profile| (profile.id, profile.name))
+        .collect();
+    state
+        .history_manager
+        .disk_usage(&app, profiles)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Stops storing a connection's messages while it stays live in the UI, e.g. for a firehose.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_pause(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> Result<HistoryPauseStatus, String> {
+    let status = state.history_manager.pause(&connection_id);
+    windows::emit_for_connection(
+        &app,
+        "history-pause-changed",
+        &connection_id,
+        status.clone(),
+    );
+    Ok(status)
+}
+
+/// Starts storing a paused connection's messages again; the result reports how many were
+/// skipped meanwhile.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_resume(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> Result<HistoryPauseStatus, String> {
+    let status = state.history_manager.resume(&connection_id);
+    windows::emit_for_connection(
+        &app,
+        "history-pause-changed",
+        &connection_id,
+        status.clone(),
+    );
+    Ok(status)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_paused_list(
+    state: State<'_, AppState>,
+) -> Result<Vec<HistoryPauseStatus>, String> {
+    Ok(state.history_manager.paused_connections())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn storage_guard_load(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<StorageGuardConfig, String> {
+    state
+        .storage_guard
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Saves the low-disk policy and re-checks free space right away.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn storage_guard_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: StorageGuardConfig,
+) -> Result<StorageGuardConfig, String> {
+    state
+        .storage_guard
+        .save_config(&app, config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn storage_guard_status(state: State<'_, AppState>) -> Result<StorageStatus, String> {
+    Ok(state.storage_guard.status())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_clear(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    confirmation: Option<String>,
+) -> Result<(), String> {
+    require_confirmation(&app, &[connection_id.as_str()], confirmation.as_deref())?;
+    state.recent.clear(&connection_id);
+    state
+        .history_manager
+        .clear_connection(&app, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_delete_connection(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    confirmation: Option<String>,
+) -> Result<(), String> {
+    require_confirmation(&app, &[connection_id.as_str()], confirmation.as_deref())?;
+    state.recent.clear(&connection_id);
+    state
+        .history_manager
+        .delete_connection(&app, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+#[allow(clippy::too_many_arguments)]
+pub async fn history_export(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    format: Option<String>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    time_zone: Option<String>,
+    payload_regex: Option<String>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let request = ExportRequest::History {
+        connection_id: connection_id.clone(),
+        format: format.clone(),
+        time_zone: time_zone.clone(),
+        payload_regex: payload_regex.clone(),
+    };
+    let normalized_format = format
+        .as_deref()
+        .map(str::to_lowercase)
+        .unwrap_or_else(|| "ndjson".to_string());
+    let time_zone = resolve_export_time_zone(&app, time_zone)?;
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export history: {connection_id}");
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::HistoryExport,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            history
+                .export_connection(
+                    &job_app,
+                    &connection_id,
+                    &normalized_format,
+                    from_ts,
+                    to_ts,
+                    time_zone,
+                    payload_regex.as_deref(),
+                    output_path.as_deref(),
+                    &progress,
+                )
+                .await
+        },
+    ))
+}
+
+/// Starts a chunked query whose rows arrive as `history-query-chunk` events on the calling window.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_query_stream(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    connection_id: String,
+    query: Option<HistoryStreamQuery>,
+) -> Result<String, String> {
+    state
+        .history_manager
+        .start_stream_query(
+            &app,
+            &state.jobs,
+            window.label().to_string(),
+            &connection_id,
+            query.unwrap_or_default(),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Background jobs still running, oldest first.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn jobs_list(state: State<'_, AppState>) -> Result<Vec<JobStatus>, String> {
+    Ok(state.jobs.list())
+}
+
+/// Asks a background job to stop; its final `job-progress` event reports `cancelled`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn job_cancel(state: State<'_, AppState>, job_id: String) -> Result<bool, String> {
+    Ok(state.jobs.cancel(&job_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_topic_stats(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    topic: String,
+    path: String,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+) -> Result<TopicFieldStats, String> {
+    let topic = topic.trim();
+    if !crate::mqtt::is_valid_topic_filter(topic) {
+        return Err("invalid topic".to_string());
+    }
+    if path.trim().is_empty() {
+        return Err("field path is required".to_string());
+    }
+    state
+        .history_manager
+        .topic_field_stats(&app, &connection_id, topic, &path, from_ts, to_ts)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Payload size buckets for a range, to find the topics whose messages a broker may reject.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_payload_sizes(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    topic: Option<String>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    limit: Option<usize>,
+) -> Result<PayloadSizeHistogram, String> {
+    let topic = topic
+        .map(|topic| topic.trim().to_string())
+        .filter(|topic| !topic.is_empty());
+    if let Some(topic) = &topic {
+        if !crate::mqtt::is_valid_topic_filter(topic) {
+            return Err("invalid topic".to_string());
+        }
+    }
+    state
+        .history_manager
+        .payload_size_histogram(&app, &connection_id, topic, from_ts, to_ts, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn correlation_thread(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    correlation_id: String,
+) -> Result<CorrelationThread, String> {
+    state
+        .history_manager
+        .correlation_thread(&app, &connection_id, &correlation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_checkpoint(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> Result<HistoryCheckpointResult, String> {
+    state
+        .history_manager
+        .checkpoint(&app, &connection_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_search(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_ids: Vec<String>,
+    query: Option<HistorySearchQuery>,
+) -> Result<Vec<HistorySearchHit>, String> {
+    state
+        .history_manager
+        .search_all(&app, &connection_ids, query.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+#[allow(clippy::too_many_arguments)]
+pub async fn history_export_all(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_ids: Vec<String>,
+    format: Option<String>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    time_zone: Option<String>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let request = ExportRequest::HistoryAll {
+        connection_ids: connection_ids.clone(),
+        format: format.clone(),
+        time_zone: time_zone.clone(),
+    };
+    let normalized_format = format
+        .as_deref()
+        .map(str::to_lowercase)
+        .unwrap_or_else(|| "ndjson".to_string());
+    let time_zone = resolve_export_time_zone(&app, time_zone)?;
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export history: {} connections", connection_ids.len());
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::HistoryExportAll,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            history
+                .export_all(
+                    &job_app,
+                    &connection_ids,
+                    &normalized_format,
+                    from_ts,
+                    to_ts,
+                    time_zone,
+                    output_path.as_deref(),
+                    &progress,
+                )
+                .await
+        },
+    ))
+}
+
+/// The zone exports render ISO-8601 times in: the one passed, else the configured default.
+/// `None` leaves exports with epoch milliseconds only.
+fn resolve_export_time_zone(
+    app: &tauri::AppHandle,
+    time_zone: Option<String>,
+) -> Result<Option<ExportTimeZone>, String> {
+    let time_zone = match time_zone {
+        Some(time_zone) => Some(time_zone),
+        None => {
+            config_store::load_config(app)
+                .map_err(|e| e.to_string())?
+                .export_time_zone
+        }
+    };
+    time_zone
+        .as_deref()
+        .map(str::trim)
+        .filter(|time_zone| !time_zone.is_empty())
+        .map(ExportTimeZone::parse)
+        .transpose()
+        .map_err(|e| e.to_string())
+}
+
+/// Starts an export job that is added to the export log once it completes, so
+/// `export_rerun` can repeat it over another window.
+#[allow(clippy::too_many_arguments)]
+fn start_logged_export<F, Fut>(
+    state: &AppState,
+    app: &tauri::AppHandle,
+    kind: JobKind,
+    label: String,
+    request: ExportRequest,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    run: F,
+) -> String
+where
+    F: FnOnce(JobProgress) -> Fut,
+    Fut: Future<Output = anyhow::Result<HistoryExportResult>> + Send + 'static,
+{
+    let export_log = state.export_log.clone();
+    let log_app = app.clone();
+    let record_label = label.clone();
+    state.jobs.start(app, kind, label, move |progress| {
+        let work = run(progress);
+        async move {
+            let started_at = now_millis();
+            let result = work.await?;
+            let record = ExportRecord {
+                id: export_log::new_record_id(),
+                label: record_label,
+                request,
+                from_ts,
+                to_ts,
+                path: result.path.clone(),
+                count: result.count,
+                started_at,
+                duration_ms: now_millis().saturating_sub(started_at),
+            };
+            if let Err(error) = export_log.record(&log_app, record) {
+                tracing::warn!("failed to update export log: {error:#}");
+            }
+            Ok(result)
+        }
+    })
+}
+
+/// Completed exports, newest first.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_log_list(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<ExportRecord>, String> {
+    state.export_log.list(&app).map_err(|e| e.to_string())
+}
+
+/// Runs a logged export again with the same parameters. Without `fromTs`/`toTs` the original
+/// window is moved forward to now; the output goes to `outputPath` or a new default file.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_rerun(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    id: String,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let record = state.export_log.get(&app, &id).map_err(|e| e.to_string())?;
+    let (from_ts, to_ts) = export_log::rerun_window(&record, from_ts, to_ts, now_millis());
+    match record.request {
+        ExportRequest::History {
+            connection_id,
+            format,
+            time_zone,
+            payload_regex,
+        } => {
+            history_export(
+                state,
+                app,
+                connection_id,
+                format,
+                from_ts,
+                to_ts,
+                time_zone,
+                payload_regex,
+                output_path,
+            )
+            .await
+        }
+        ExportRequest::HistoryAll {
+            connection_ids,
+            format,
+            time_zone,
+        } => {
+            history_export_all(
+                state,
+                app,
+                connection_ids,
+                format,
+                from_ts,
+                to_ts,
+                time_zone,
+                output_path,
+            )
+            .await
+        }
+        ExportRequest::Influx {
+            connection_id,
+            mapping,
+        } => {
+            history_export_influx(
+                state,
+                app,
+                connection_id,
+                mapping,
+                from_ts,
+                to_ts,
+                output_path,
+            )
+            .await
+        }
+        ExportRequest::Elasticsearch {
+            connection_id,
+            options,
+        } => {
+            history_export_elasticsearch(
+                state,
+                app,
+                connection_id,
+                options,
+                from_ts,
+                to_ts,
+                output_path,
+            )
+            .await
+        }
+        ExportRequest::Mosquitto {
+            connection_id,
+            output_format,
+        } => {
+            history_export_mosquitto(
+                state,
+                app,
+                connection_id,
+                output_format,
+                from_ts,
+                to_ts,
+                output_path,
+            )
+            .await
+        }
+        ExportRequest::Replay {
+            connection_id,
+            options,
+        } => {
+            history_export_replay(
+                state,
+                app,
+                connection_id,
+                Some(options),
+                from_ts,
+                to_ts,
+                output_path,
+            )
+            .await
+        }
+        ExportRequest::Topics {
+            connection_id,
+            options,
+        } => {
+            history_export_topics(
+                state,
+                app,
+                connection_id,
+                Some(options),
+                from_ts,
+                to_ts,
+                output_path,
+            )
+            .await
+        }
+    }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_export_influx(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    mapping: InfluxMapping,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    if mapping.measurement.trim().is_empty() {
+        return Err("Influx measurement is required".to_string());
+    }
+
+    let request = ExportRequest::Influx {
+        connection_id: connection_id.clone(),
+        mapping: mapping.clone(),
+    };
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export to InfluxDB line protocol: {connection_id}");
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::InfluxExport,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            history
+                .export_lines(
+                    &job_app,
+                    &connection_id,
+                    "lp",
+                    from_ts,
+                    to_ts,
+                    output_path.as_deref(),
+                    &progress,
+                    move |record| {
+                        influx::to_line(&mapping, &record.topic, &record.payload, record.timestamp)
+                    },
+                )
+                .await
+        },
+    ))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_export_elasticsearch(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    options: ElasticsearchExportOptions,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    elasticsearch::validate(&options).map_err(|e| e.to_string())?;
+
+    let request = ExportRequest::Elasticsearch {
+        connection_id: connection_id.clone(),
+        options: options.clone(),
+    };
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export to Elasticsearch bulk: {connection_id}");
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::ElasticsearchExport,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            let source_id = connection_id.clone();
+            history
+                .export_lines(
+                    &job_app,
+                    &connection_id,
+                    "ndjson",
+                    from_ts,
+                    to_ts,
+                    output_path.as_deref(),
+                    &progress,
+                    move |record| elasticsearch::to_bulk_pair(&options, &source_id, record),
+                )
+                .await
+        },
+    ))
+}
+
+/// Exports history as text lines rendered like `mosquitto_sub -F`, for scripts that already
+/// parse that output.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_export_mosquitto(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    output_format: String,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let line_format = mosquitto::LineFormat::parse(&output_format).map_err(|e| e.to_string())?;
+
+    let request = ExportRequest::Mosquitto {
+        connection_id: connection_id.clone(),
+        output_format,
+    };
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export as mosquitto_sub output: {connection_id}");
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::TextExport,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            history
+                .export_lines(
+                    &job_app,
+                    &connection_id,
+                    "txt",
+                    from_ts,
+                    to_ts,
+                    output_path.as_deref(),
+                    &progress,
+                    move |record| Some(line_format.render(record)),
+                )
+                .await
+        },
+    ))
+}
+
+/// Exports received traffic with its timing as a standalone replay file: NDJSON with
+/// relative offsets, or a `mosquitto_pub` shell script.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_export_replay(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    options: Option<ReplayExportOptions>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let request = ExportRequest::Replay {
+        connection_id: connection_id.clone(),
+        options: options.clone(),
+    };
+    let mut writer = ReplayWriter::new(&connection_id, options).map_err(|e| e.to_string())?;
+
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export replay script: {connection_id}");
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::ReplayExport,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            let ext = writer.extension();
+            history
+                .export_lines(
+                    &job_app,
+                    &connection_id,
+                    ext,
+                    from_ts,
+                    to_ts,
+                    output_path.as_deref(),
+                    &progress,
+                    move |record| writer.line(record),
+                )
+                .await
+        },
+    ))
+}
+
+/// Exports the distinct topics in a connection's history, optionally with a sample payload and
+/// first/last seen times each, as raw material for documentation and the topic catalog.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_export_topics(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    options: Option<TopicListExportOptions>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    if let Some(filter) = options.topic_filter.as_deref().map(str::trim) {
+        if !filter.is_empty() && !crate::mqtt::is_valid_topic_filter(filter) {
+            return Err(format!("invalid topic filter '{filter}'"));
+        }
+    }
+
+    let request = ExportRequest::Topics {
+        connection_id: connection_id.clone(),
+        options: options.clone(),
+    };
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export topic list: {connection_id}");
+    Ok(start_logged_export(
+        &state,
+        &app,
+        JobKind::TopicListExport,
+        label,
+        request,
+        from_ts,
+        to_ts,
+        move |progress| async move {
+            history
+                .export_topics(
+                    &job_app,
+                    &connection_id,
+                    options,
+                    from_ts,
+                    to_ts,
+                    output_path.as_deref(),
+                    &progress,
+                )
+                .await
+        },
+    ))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connection_events_query(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<ConnectionEventRecord>, String> {
+    state
+        .history_manager
+        .query_events(&app, &connection_id, from_ts, to_ts, limit.unwrap_or(200))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connection_events_export(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    format: Option<String>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let history = state.history_manager.clone();
+    let job_app = app.clone();
+    let label = format!("Export connection events: {connection_id}");
+    Ok(state.jobs.start(
+        &app,
+        JobKind::EventsExport,
+        label,
+        move |progress| async move {
+            history
+                .export_events(
+                    &job_app,
+                    &connection_id,
+                    format.as_deref().unwrap_or("ndjson"),
+                    output_path.as_deref(),
+                    &progress,
+                )
+                .await
+        },
+    ))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_pick_export_path(
+    connection_id: String,
+    format: Option<String>,
+) -> Result<Option<String>, String> {
+    let normalized_format = format
+        .as_deref()
+        .map(str::to_lowercase)
+        .unwrap_or_else(|| "ndjson".to_string());
+    let ext = match normalized_format.as_str() {
+        "csv" => "csv",
+        "xlsx" => "xlsx",
+        _ => "ndjson",
+    };
+    let file_name = format!(
+        "{}-history-{}.{}",
+        safe_name(&connection_id),
+        now_millis(),
+        ext
+    );
+
+    let mut dialog = FileDialog::new().set_file_name(&file_name);
+    dialog = match ext {
+        "csv" => dialog.add_filter("CSV", &["csv"]),
+        "xlsx" => dialog.add_filter("Excel workbook", &["xlsx"]),
+        _ => dialog.add_filter("NDJSON", &["ndjson"]),
+    };
+
+    Ok(dialog.save_file().map(|p| normalize_selected_path(p, ext)))
+}
+
+/// Saves the last value of every topic (as of `at`, default now) as a JSON snapshot. Prompts for
+/// a location unless `output_path` is given.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn retained_snapshot_export(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    topic_filter: Option<String>,
+    at: Option<u64>,
+    retained_only: Option<bool>,
+    output_path: Option<String>,
+) -> Result<Option<String>, String> {
+    let topic_filter = topic_filter
+        .map(|filter| filter.trim().to_string())
+        .filter(|filter| !filter.is_empty());
+    if let Some(filter) = &topic_filter {
+        if !crate::mqtt::is_valid_topic_filter(filter) {
+            return Err(format!("invalid topic filter: {filter}"));
+        }
+    }
+
+    let records = state
+        .history_manager
+        .latest_state(&app, &connection_id, at, retained_only.unwrap_or(true))
+        .await
+        .map_err(|e| e.to_string())?;
+    let captured_at = at.unwrap_or_else(now_millis);
+    let snapshot = snapshot::build(
+        &connection_id,
+        captured_at,
+        topic_filter.as_deref(),
+        records,
+    );
+    let content = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+
+    let path = match output_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let file_name = format!(
+                "{}-retained-{}.json",
+                safe_name(&connection_id),
+                captured_at
+            );
+            let selected = FileDialog::new()
+                .set_file_name(&file_name)
+                .add_filter("JSON", &["json"])
+                .save_file();
+            let Some(path) = selected else {
+                return Ok(None);
+            };
+            path
+        }
+    };
+
+    let normalized = normalize_selected_path(path, "json");
+    let normalized_path = PathBuf::from(&normalized);
+    if let Some(parent) = normalized_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(Some(normalized))
+}
+
+/// Loads the MQTT PUBLISH packets of a pcap / pcapng capture into a connection's history;
+/// returns a job id.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn history_import_pcap(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    path: String,
+    options: Option<PcapImportOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    if options.broker_ports.is_empty() {
+        return Err("at least one broker port is required".to_string());
+    }
+    if let Some(filter) = options.topic_filter.as_deref().map(str::trim) {
+        if !filter.is_empty() && !crate::mqtt::is_valid_topic_filter(filter) {
+            return Err(format!("invalid topic filter '{filter}'"));
+        }
+    }
+
+    let job_app = app.clone();
+    let label = format!("Import capture: {connection_id}");
+    Ok(state.jobs.start(
+        &app,
+        JobKind::PcapImport,
+        label,
+        move |progress| async move {
+            let capture = tokio::task::spawn_blocking(move || {
+                pcap::read_publishes(Path::new(&path), &options)
+            })
+            .await??;
+            progress.checkpoint()?;
+
+            let history = job_app.state::<AppState>().history_manager.clone();
+            progress.set_total(capture.messages.len() as u64);
+            for chunk in capture.messages.chunks(PCAP_IMPORT_CHUNK) {
+                history
+                    .append_batch(&job_app, &connection_id, chunk)
+                    .await?;
+                progress.advance(chunk.len() as u64);
+                progress.checkpoint()?;
+            }
+            Ok(PcapImportResult {
+                packets: capture.packets,
+                streams: capture.streams,
+                imported: capture.messages.len() as u64,
+                filtered: capture.filtered,
+                malformed: capture.malformed,
+            })
+        },
+    ))
+}
+
+/// Republishes a snapshot's entries as retained messages on a connected session; returns a job id.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn retained_snapshot_import(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    path_or_content: String,
+    topic_filter: Option<String>,
+    confirmation: Option<String>,
+) -> Result<String, String> {
+    require_confirmation(&app, &[connection_id.as_str()], confirmation.as_deref())?;
+    let snapshot = snapshot::load(&path_or_content).map_err(|e| e.to_string())?;
+    let topic_filter = topic_filter
+        .map(|filter| filter.trim().to_string())
+        .filter(|filter| !filter.is_empty());
+    if !state.mqtt_manager.is_connected(&connection_id) {
+        return Err(format!("connection {connection_id} is not connected"));
+    }
+
+    let job_app = app.clone();
+    let label = format!("Import retained snapshot: {connection_id}");
+    Ok(state.jobs.start(
+        &app,
+        JobKind::SnapshotImport,
+        label,
+        move |progress| async move {
+            let state = job_app.state::<AppState>();
+            progress.set_total(snapshot.entries.len() as u64);
+            let mut result = RetainedSnapshotImportResult {
+                published: 0,
+                skipped: 0,
+            };
+            for entry in snapshot.entries {
+                if topic_filter
+                    .as_deref()
+                    .is_some_and(|filter| !crate::mqtt::topic_matches(filter, &entry.topic))
+                {
+                    result.skipped += 1;
+                    progress.tick()?;
+                    continue;
+                }
+
+                let payload = snapshot::entry_payload(&entry)?;
+                state.mqtt_manager.publish(
+                    &connection_id,
+                    entry.topic.clone(),
+                    payload.clone(),
+                    entry.qos,
+                    true,
+                    Vec::new(),
+                )?;
+                state.recent.record_outgoing(
+                    &connection_id,
+                    &entry.topic,
+                    &payload,
+                    entry.qos,
+                    true,
+                );
+                state
+                    .history_manager
+                    .append_outgoing(
+                        &job_app,
+                        &connection_id,
+                        &entry.topic,
+                        payload,
+                        entry.qos,
+                        true,
+                        Vec::new(),
+                    )
+                    .await?;
+                result.published += 1;
+                progress.tick()?;
+            }
+            Ok(result)
+        },
+    ))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_catalog_export(
+    connection_id: String,
+    content: String,
+) -> Result<Option<String>, String> {
+    let file_name = format!(
         "{}-topic-catalog-{}.json",
         safe_name(&connection_id),
         now_millis()
     );
 
-    let selected = FileDialog::new()
-        .set_file_name(&file_name)
-        .add_filter("JSON", &["json"])
-        .save_file();
+    let selected = FileDialog::new()
+        .set_file_name(&file_name)
+        .add_filter("JSON", &["json"])
+        .save_file();
+
+    let Some(path) = selected else {
+        return Ok(None);
+    };
+
+    let normalized = normalize_selected_path(path, "json");
+    let normalized_path = PathBuf::from(&normalized);
+    if let Some(parent) = normalized_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(Some(normalized))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_catalog_import(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    path_or_content: String,
+    strategy: Option<TopicCatalogMergeStrategy>,
+) -> Result<String, String> {
+    let job_app = app.clone();
+    let label = format!("Import topic catalog: {connection_id}");
+    Ok(state.jobs.start(
+        &app,
+        JobKind::CatalogImport,
+        label,
+        move |progress| async move {
+            progress.set_total(1);
+            let result = tokio::task::spawn_blocking(move || {
+                catalog::import(
+                    &job_app,
+                    &connection_id,
+                    &path_or_content,
+                    strategy.unwrap_or_default(),
+                )
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result)?;
+            progress.advance(1);
+            Ok(result)
+        },
+    ))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_discovery_start(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<TopicDiscoveryDraft, String> {
+    Ok(state.topic_discovery.start(&connection_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_discovery_stop(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<TopicDiscoveryDraft, String> {
+    Ok(state.topic_discovery.stop(&connection_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_discovery_draft(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<TopicDiscoveryDraft, String> {
+    Ok(state.topic_discovery.draft(&connection_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_discovery_discard(
+    state: State<'_, AppState>,
+    connection_id: String,
+    topics: Option<Vec<String>>,
+) -> Result<TopicDiscoveryDraft, String> {
+    Ok(state
+        .topic_discovery
+        .discard(&connection_id, topics.as_deref()))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_discovery_promote(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    topics: Vec<String>,
+) -> Result<TopicCatalogImportResult, String> {
+    let items = state.topic_discovery.catalog_items(&connection_id, &topics);
+    let result = catalog::merge_into(
+        &app,
+        &connection_id,
+        items,
+        TopicCatalogMergeStrategy::MergeByTopic,
+    )
+    .map_err(|e| e.to_string())?;
+    state.topic_discovery.discard(&connection_id, Some(&topics));
+    Ok(result)
+}
+
+/// Maps a CSV device or broker inventory onto new connection profiles, brokers and
+/// identities. Nothing is saved; the caller merges the records into its configuration.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn inventory_import_csv(
+    path_or_content: String,
+    mapping: InventoryMapping,
+    brokers: Vec<BrokerConfig>,
+    identities: Vec<AuthIdentity>,
+) -> Result<InventoryImportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        inventory::import(&path_or_content, &mapping, &brokers, &identities)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_catalog_import_asyncapi(
+    content: String,
+) -> Result<ConnectionTopicDocument, String> {
+    asyncapi::import(&content).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_catalog_export_asyncapi(
+    app: tauri::AppHandle,
+    connection_id: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    let document = catalog::load(&app, &connection_id).map_err(|e| e.to_string())?;
+    let title = catalog::title(&app, &connection_id).map_err(|e| e.to_string())?;
+
+    asyncapi::export(
+        &title,
+        &document,
+        asyncapi::DocumentFormat::parse(format.as_deref()),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_catalog_export_docs(
+    app: tauri::AppHandle,
+    connection_id: String,
+    format: Option<String>,
+) -> Result<Option<String>, String> {
+    let format = docs::DocsFormat::parse(format.as_deref());
+    let document = catalog::load(&app, &connection_id).map_err(|e| e.to_string())?;
+    let title = catalog::title(&app, &connection_id).map_err(|e| e.to_string())?;
+
+    let ext = format.extension();
+    let file_name = format!(
+        "{}-topic-docs-{}.{}",
+        safe_name(&connection_id),
+        now_millis(),
+        ext
+    );
+    let filter = match format {
+        docs::DocsFormat::Markdown => "Markdown",
+        docs::DocsFormat::Html => "HTML",
+    };
+    let selected = FileDialog::new()
+        .set_file_name(&file_name)
+        .add_filter(filter, &[ext])
+        .save_file();
+
+    let Some(path) = selected else {
+        return Ok(None);
+    };
+
+    let normalized = normalize_selected_path(path, ext);
+    let normalized_path = PathBuf::from(&normalized);
+    if let Some(parent) = normalized_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let content = docs::render(&title, &document, format);
+    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(Some(normalized))
+}
+
+/// Writes typed client stubs for a connection's catalog (`typescript` or `rust`) into
+/// `topics.ts` or `topics.rs` in `directory`, or a folder the user picks; returns the file written, or `None` when cancelled.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn topic_catalog_generate_code(
+    app: tauri::AppHandle,
+    connection_id: String,
+    language: Option<String>,
+    directory: Option<String>,
+) -> Result<Option<String>, String> {
+    let language = codegen::CodegenLanguage::parse(language.as_deref());
+    let document = catalog::load(&app, &connection_id).map_err(|e| e.to_string())?;
+    let title = catalog::title(&app, &connection_id).map_err(|e| e.to_string())?;
+
+    let directory = match directory
+        .map(|directory| directory.trim().to_string())
+        .filter(|directory| !directory.is_empty())
+    {
+        Some(directory) => PathBuf::from(directory),
+        None => match FileDialog::new().pick_folder() {
+            Some(directory) => directory,
+            None => return Ok(None),
+        },
+    };
+    fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+
+    let path = directory.join(format!("topics.{}", language.extension()));
+    let content = codegen::render(&title, &document, language);
+    fs::write(&path, content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Disconnects everything and deletes all history, exports, settings and stored credentials.
+/// `confirm_token` must be the phrase the confirmation dialog asks the user to type.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn app_wipe_all_data(
+    app: tauri::AppHandle,
+    confirm_token: String,
+    confirmation: Option<String>,
+) -> Result<DataWipeReport, String> {
+    // The wipe deletes every connection's history, production ones included; with several of
+    // them `confirmation` lists one name per line.
+    let config = config_store::load_config(&app).map_err(|e| e.to_string())?;
+    let connection_ids = config
+        .connections
+        .iter()
+        .map(|profile| profile.id.as_str())
+        .collect::<Vec<_>>();
+    require_confirmation(&app, &connection_ids, confirmation.as_deref())?;
+    wipe::wipe_all(&app, &confirm_token)
+        .await
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn app_config_export(content: String) -> Result<Option<String>, String> {
+    let file_name = format!("mqtt-nexus-backup-{}.json", now_millis());
+
+    let selected = FileDialog::new()
+        .set_file_name(&file_name)
+        .add_filter("JSON", &["json"])
+        .save_file();
+
+    let Some(path) = selected else {
+        return Ok(None);
+    };
+
+    let normalized = normalize_selected_path(path, "json");
+    let normalized_path = PathBuf::from(&normalized);
+    if let Some(parent) = normalized_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(Some(normalized))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rules_load(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<AutomationConfig, String> {
+    state
+        .rule_manager
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rules_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    rules: Vec<AutomationRule>,
+) -> Result<AutomationConfig, String> {
+    state
+        .rule_manager
+        .save_rules(&app, rules)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Writes the rules with `ruleIds` (default: all) to a standalone bundle file. Prompts for a
+/// location unless `outputPath` is given.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rules_bundle_export(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    rule_ids: Option<Vec<String>>,
+    output_path: Option<String>,
+) -> Result<Option<String>, String> {
+    let config = state
+        .rule_manager
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())?;
+    let exported_at = now_millis();
+    let bundle = rule_bundle::build(&config, rule_ids.as_deref(), exported_at);
+    let content = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+
+    let path = match output_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let selected = FileDialog::new()
+                .set_file_name(format!("rules-{exported_at}.json"))
+                .add_filter("JSON", &["json"])
+                .save_file();
+            let Some(path) = selected else {
+                return Ok(None);
+            };
+            path
+        }
+    };
+
+    let normalized = normalize_selected_path(path, "json");
+    let normalized_path = PathBuf::from(&normalized);
+    if let Some(parent) = normalized_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(Some(normalized))
+}
+
+/// Imports a rule bundle from a file path or inline JSON. Rules replace saved ones with the
+/// same id and are otherwise added; with `replace` the saved rules are discarded first.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rules_bundle_import(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    path_or_content: String,
+    replace: Option<bool>,
+) -> Result<RuleBundleImportResult, String> {
+    let bundle = rule_bundle::load(&path_or_content).map_err(|e| e.to_string())?;
+    state
+        .rule_manager
+        .import_rules(&app, bundle.rules, replace.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rule_command_allow(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    program: String,
+) -> Result<Option<AutomationConfig>, String> {
+    let description = format!(
+        "Rules will be able to run this program with MQTT message data:\n\n{}\n\nOnly allow programs you trust.",
+        program.trim()
+    );
+    // The dialog blocks until answered, so it must not run on an async worker.
+    let confirmed = tokio::task::spawn_blocking(move || {
+        MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Allow local command")
+            .set_description(description)
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if confirmed != MessageDialogResult::Yes {
+        return Ok(None);
+    }
+
+    state
+        .rule_manager
+        .allow_command(&app, &program)
+        .await
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rule_command_revoke(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    program: String,
+) -> Result<AutomationConfig, String> {
+    state
+        .rule_manager
+        .revoke_command(&app, &program)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn rule_test(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    rule: AutomationRule,
+    samples: Option<Vec<RuleTestSample>>,
+    range: Option<RuleTestRange>,
+) -> Result<RuleTestResult, String> {
+    let range = range.unwrap_or_default();
+    let messages = match samples {
+        Some(samples) => {
+            let fallback_id = range
+                .connection_id
+                .or_else(|| rule.connection_id.clone())
+                .unwrap_or_default();
+            samples
+                .into_iter()
+                .map(|sample| {
+                    let item = MqttBatchItem {
+                        topic: sample.topic,
+                        payload: Payload::from(sample.payload),
+                        qos: 0,
+                        retain: false,
+                        direction: MessageDirection::In,
+                        timestamp: sample.timestamp.unwrap_or_else(now_millis),
+                        dup: false,
+                        packet_id: None,
+                        correlation_id: None,
+                        response_topic: None,
+                        user_properties: Vec::new(),
+                        is_binary: false,
+                    };
+                    (
+                        sample.connection_id.unwrap_or_else(|| fallback_id.clone()),
+                        item,
+                    )
+                })
+                .collect::<Vec<_>>()
+        }
+        None => {
+            let connection_id = range
+                .connection_id
+                .or_else(|| rule.connection_id.clone())
+                .ok_or_else(|| "connectionId is required to test against history".to_string())?;
+            state
+                .history_manager
+                .query_range(
+                    &app,
+                    &connection_id,
+                    range.from_ts,
+                    range.to_ts,
+                    range.limit.unwrap_or(500),
+                    HistoryTimeSource::Arrival,
+                    HistorySort::TimestampAsc,
+                )
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|record| matches!(record.direction, MessageDirection::In))
+                .map(|record| {
+                    let item = MqttBatchItem {
+                        topic: record.topic,
+                        payload: Payload::from(record.payload),
+                        qos: record.qos,
+                        retain: record.retain,
+                        direction: record.direction,
+                        timestamp: record.timestamp,
+                        dup: record.dup,
+                        packet_id: record.packet_id,
+                        correlation_id: record.correlation_id,
+                        response_topic: record.response_topic,
+                        user_properties: record.user_properties,
+                        is_binary: record.is_binary,
+                    };
+                    (connection_id.clone(), item)
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let config = state
+        .rule_manager
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(dry_run::evaluate(
+        &rule,
+        &messages,
+        &config.command_allow_list,
+    ))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn alert_log_query(
+    app: tauri::AppHandle,
+    connection_id: Option<String>,
+    rule_id: Option<String>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<AlertEventRecord>, String> {
+    alert_log::query(
+        &app,
+        connection_id,
+        rule_id,
+        from_ts,
+        to_ts,
+        limit.unwrap_or(200),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn broker_start(
+    state: State<'_, AppState>,
+    config: EmbeddedBrokerConfig,
+) -> Result<EmbeddedBrokerStatus, String> {
+    state
+        .broker_manager
+        .start(config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn broker_stop(state: State<'_, AppState>) -> Result<EmbeddedBrokerStatus, String> {
+    state.broker_manager.stop().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn broker_status(state: State<'_, AppState>) -> Result<EmbeddedBrokerStatus, String> {
+    Ok(state.broker_manager.status().await)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn broker_sys_stats_start(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> Result<(), String> {
+    state
+        .mqtt_manager
+        .subscribe(
+            &connection_id,
+            SYS_TOPIC_FILTER.to_string(),
+            0,
+            SubscribeOptions::default(),
+        )
+        .map_err(|e| e.to_string())?;
+    state.sys_stats.start(app, &connection_id);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn broker_sys_stats_stop(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<(), String> {
+    if !state.sys_stats.is_tracking(&connection_id) {
+        return Ok(());
+    }
+    state.sys_stats.stop(&connection_id);
+    // The session may already be gone; stopping the tracker is what matters.
+    let _ = state
+        .mqtt_manager
+        .unsubscribe(&connection_id, SYS_TOPIC_FILTER.to_string());
+    Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn broker_sys_stats(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<Option<BrokerSysStats>, String> {
+    Ok(state.sys_stats.stats(&connection_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kafka_bridges_load(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<IntegrationsConfig, String> {
+    state
+        .kafka_bridges
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kafka_bridges_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    bridges: Vec<KafkaBridgeConfig>,
+) -> Result<IntegrationsConfig, String> {
+    state
+        .kafka_bridges
+        .save_bridges(&app, bridges)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kafka_bridge_start(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    bridge_id: String,
+) -> Result<KafkaBridgeMetrics, String> {
+    state
+        .kafka_bridges
+        .start(&app, &bridge_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kafka_bridge_stop(
+    state: State<'_, AppState>,
+    bridge_id: String,
+) -> Result<KafkaBridgeMetrics, String> {
+    Ok(state.kafka_bridges.stop(&bridge_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kafka_bridge_metrics(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<KafkaBridgeMetrics>, String> {
+    state
+        .kafka_bridges
+        .metrics(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn file_watchers_load(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<FileWatchersConfig, String> {
+    state
+        .file_watchers
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn file_watchers_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    watchers: Vec<FileWatcherConfig>,
+) -> Result<FileWatchersConfig, String> {
+    state
+        .file_watchers
+        .save_watchers(&app, watchers)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn file_watcher_start(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    watcher_id: String,
+) -> Result<FileWatcherMetrics, String> {
+    state
+        .file_watchers
+        .start(&app, &watcher_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn file_watcher_stop(
+    state: State<'_, AppState>,
+    watcher_id: String,
+) -> Result<FileWatcherMetrics, String> {
+    Ok(state.file_watchers.stop(&watcher_id))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn file_watcher_metrics(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<FileWatcherMetrics>, String> {
+    state
+        .file_watchers
+        .metrics(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn socket_taps_load(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<SocketTapsConfig, String> {
+    state
+        .socket_taps
+        .config(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn socket_taps_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    taps: Vec<SocketTapConfig>,
+) -> Result<SocketTapsConfig, String> {
+    state
+        .socket_taps
+        .save_taps(&app, taps)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn socket_tap_start(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    tap_id: String,
+) -> Result<SocketTapMetrics, String> {
+    state
+        .socket_taps
+        .start(&app, &tap_id)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    let Some(path) = selected else {
-        return Ok(None);
-    };
+#[tauri::command(rename_all = "camelCase")]
+pub async fn socket_tap_stop(
+    state: State<'_, AppState>,
+    tap_id: String,
+) -> Result<SocketTapMetrics, String> {
+    Ok(state.socket_taps.stop(&tap_id))
+}
 
-    let normalized = normalize_selected_path(path, "json");
-    let normalized_path = PathBuf::from(&normalized);
-    if let Some(parent) = normalized_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
+#[tauri::command(rename_all = "camelCase")]
+pub async fn socket_tap_metrics(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<SocketTapMetrics>, String> {
+    state
+        .socket_taps
+        .metrics(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
-    Ok(Some(normalized))
+#[tauri::command(rename_all = "camelCase")]
+pub async fn window_open_connection(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+) -> Result<String, String> {
+    let config = config_store::load_config(&app).map_err(|e| e.to_string())?;
+    let name = config
+        .connections
+        .iter()
+        .find(|profile| profile.id == connection_id)
+        .map(|profile| profile.name.clone())
+        .ok_or_else(|| format!("unknown connection: {connection_id}"))?;
+    let title = format!("{} — {name}", app.package_info().name);
+    windows::open_connection_window(&app, &state.windows, &connection_id, &title)
+        .map_err(|e| e.to_string())
 }
 
+/// Returns the connection the calling window is scoped to, or `None` for the main window.
 #[tauri::command(rename_all = "camelCase")]
-pub async fn app_config_export(content: String) -> Result<Option<String>, String> {
-    let file_name = format!("mqtt-nexus-backup-{}.json", now_millis());
+pub async fn window_connection_context(
+    state: State<'_, AppState>,
+    window: tauri::WebviewWindow,
+) -> Result<Option<String>, String> {
+    Ok(state.windows.connection_for(window.label()))
+}
 
-    let selected = FileDialog::new()
-        .set_file_name(&file_name)
-        .add_filter("JSON", &["json"])
-        .save_file();
+#[tauri::command(rename_all = "camelCase")]
+pub async fn tray_set_badges(app: tauri::AppHandle, badges: TrayBadges) -> Result<(), String> {
+    tray::set_badges(&app, &badges);
+    Ok(())
+}
 
-    let Some(path) = selected else {
-        return Ok(None);
-    };
+#[tauri::command(rename_all = "camelCase")]
+pub async fn memory_limits_load(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<MemoryLimitsConfig, String> {
+    state.memory.load_config(&app).map_err(|e| e.to_string())
+}
 
-    let normalized = normalize_selected_path(path, "json");
-    let normalized_path = PathBuf::from(&normalized);
-    if let Some(parent) = normalized_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+#[tauri::command(rename_all = "camelCase")]
+pub async fn memory_limits_save(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config: MemoryLimitsConfig,
+) -> Result<MemoryLimitsConfig, String> {
+    state
+        .memory
+        .save_config(&app, &config)
+        .map_err(|e| e.to_string())?;
+    state
+        .topic_discovery
+        .set_max_topics(config.max_discovery_topics);
+    state.recent.set_capacity(config.recent_messages);
+    Ok(config)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connect_limits_load(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ConnectLimitsConfig, String> {
+    state
+        .mqtt_manager
+        .throttle()
+        .load_config(&app)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn connect_limits_save(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config: ConnectLimitsConfig,
+) -> Result<ConnectLimitsConfig, String> {
+    state
+        .mqtt_manager
+        .throttle()
+        .save_config(&app, &config)
+        .map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// Serves the in-memory ring buffer so views can refill without touching SQLite.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn messages_recent(
+    state: State<'_, AppState>,
+    connection_id: String,
+    limit: Option<usize>,
+    topic_filter: Option<String>,
+) -> Result<Vec<MqttBatchItem>, String> {
+    let topic_filter = topic_filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty());
+    if topic_filter.is_some_and(|filter| !crate::mqtt::is_valid_topic_filter(filter)) {
+        return Err("invalid topic filter".to_string());
     }
+    Ok(state
+        .recent
+        .query(&connection_id, limit.unwrap_or(usize::MAX), topic_filter))
+}
 
-    fs::write(&normalized_path, content.as_bytes()).map_err(|e| e.to_string())?;
-    Ok(Some(normalized))
+/// Per-connection buffer sizes and drop counts, plus process RSS where available.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn app_runtime_metrics(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RuntimeMetrics, String> {
+    let limits = state.memory.load_config(&app).map_err(|e| e.to_string())?;
+    let mut connections = state.memory.snapshot();
+    for metrics in &mut connections {
+        metrics.connected = state.mqtt_manager.is_connected(&metrics.connection_id);
+        let (topics, bytes) = state.topic_discovery.usage(&metrics.connection_id);
+        metrics.discovery_topics = topics;
+        metrics.discovery_bytes = bytes;
+        let (messages, bytes) = state.recent.usage(&metrics.connection_id);
+        metrics.recent_messages = messages;
+        metrics.recent_bytes = bytes;
+        let keep_alive = state.keep_alive.stats(&metrics.connection_id);
+        metrics.missed_pingresps = keep_alive.missed_pingresps;
+        metrics.last_ping_rtt_ms = keep_alive.last_rtt_ms;
+    }
+    connections.sort_by(|a, b| a.connection_id.cmp(&b.connection_id));
+
+    Ok(RuntimeMetrics {
+        resident_bytes: memory::resident_bytes(),
+        limits,
+        connections,
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn shortcuts_load(app: tauri::AppHandle) -> Result<ShortcutsConfig, String> {
+    shortcuts::load(&app).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn shortcuts_save(
+    app: tauri::AppHandle,
+    config: ShortcutsConfig,
+) -> Result<ShortcutsConfig, String> {
+    shortcuts::save(&app, &config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn plugins_load(app: tauri::AppHandle) -> Result<PluginsConfig, String> {
+    plugins::load(&app).map_err(|e| e.to_string())
+}
+
+/// Saves decoder plugins and reloads them, returning each plugin's load status.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn plugins_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: PluginsConfig,
+) -> Result<Vec<DecoderPluginStatus>, String> {
+    state.plugins.save(&app, &config).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn plugins_status(
+    state: State<'_, AppState>,
+) -> Result<Vec<DecoderPluginStatus>, String> {
+    Ok(state.plugins.statuses())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn transforms_load(app: tauri::AppHandle) -> Result<TransformsConfig, String> {
+    transform::load(&app).map_err(|e| e.to_string())
+}
+
+/// Compiles, saves and activates inbound transform scripts; nothing changes if any fails.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn transforms_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: TransformsConfig,
+) -> Result<TransformsConfig, String> {
+    state
+        .transforms
+        .save(&app, &config)
+        .map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn session_hooks_load(app: tauri::AppHandle) -> Result<SessionHooksConfig, String> {
+    crate::mqtt::hooks::load(&app).map_err(|e| e.to_string())
+}
+
+/// Compiles, saves and activates session hook scripts; nothing changes if any fails.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn session_hooks_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: SessionHooksConfig,
+) -> Result<SessionHooksConfig, String> {
+    state
+        .session_hooks
+        .save(&app, &config)
+        .map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn payload_timestamps_load(
+    app: tauri::AppHandle,
+) -> Result<PayloadTimestampsConfig, String> {
+    payload_time::load(&app).map_err(|e| e.to_string())
+}
+
+/// Saves per-connection rules for reading device timestamps out of payloads. Only messages
+/// stored after saving get a payload timestamp.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn payload_timestamps_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: PayloadTimestampsConfig,
+) -> Result<PayloadTimestampsConfig, String> {
+    state
+        .payload_timestamps
+        .save(&app, &config)
+        .map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn payload_timestamp_test(
+    state: State<'_, AppState>,
+    rule: PayloadTimestampRule,
+    payload: String,
+) -> Result<Option<u64>, String> {
+    state
+        .payload_timestamps
+        .test(&rule, &payload)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn templates_list(
+    app: tauri::AppHandle,
+    connection_id: Option<String>,
+    query: Option<String>,
+) -> Result<Vec<PayloadTemplate>, String> {
+    templates::list(&app, connection_id.as_deref(), query.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn template_create(
+    app: tauri::AppHandle,
+    draft: PayloadTemplateDraft,
+) -> Result<PayloadTemplate, String> {
+    templates::create(&app, draft).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn template_update(
+    app: tauri::AppHandle,
+    id: String,
+    draft: PayloadTemplateDraft,
+) -> Result<PayloadTemplate, String> {
+    templates::update(&app, &id, draft).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn template_delete(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    templates::delete(&app, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn template_duplicate(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<PayloadTemplate, String> {
+    templates::duplicate(&app, &id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn templates_import(
+    app: tauri::AppHandle,
+    templates: Vec<PayloadTemplate>,
+    replace: bool,
+) -> Result<Vec<PayloadTemplate>, String> {
+    templates::import(&app, templates, replace).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn transform_test(
+    state: State<'_, AppState>,
+    script: String,
+    topic: String,
+    payload: String,
+) -> Result<TransformTestResult, String> {
+    state
+        .transforms
+        .test(&script, &topic, &payload)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn crash_reports_list(app: tauri::AppHandle) -> Result<Vec<CrashReportSummary>, String> {
+    crash::list(&app).map_err(|e| e.to_string())
+}
+
+/// Opens one report, or the crash directory itself when no file name is given.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn crash_report_open(
+    app: tauri::AppHandle,
+    file_name: Option<String>,
+) -> Result<(), String> {
+    let path = crash::report_path(&app, file_name.as_deref()).map_err(|e| e.to_string())?;
+    app.opener()
+        .open_path(path.display().to_string(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn telemetry_config_load(app: tauri::AppHandle) -> Result<TelemetryStatus, String> {
+    let config = telemetry::load(&app).map_err(|e| e.to_string())?;
+    Ok(TelemetryStatus {
+        config,
+        active: telemetry::is_active(),
+        restart_required: false,
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn telemetry_config_save(
+    app: tauri::AppHandle,
+    config: TelemetryConfig,
+) -> Result<TelemetryStatus, String> {
+    telemetry::save(&app, &config).map_err(|e| e.to_string())?;
+    // The tracing subscriber is process-global, so exporter changes apply on next launch.
+    Ok(TelemetryStatus {
+        config,
+        active: telemetry::is_active(),
+        restart_required: true,
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn control_api_status(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<ControlApiStatus, String> {
+    Ok(state.control_api.status(&app).await)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn control_api_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: ControlApiConfig,
+) -> Result<ControlApiStatus, String> {
+    state
+        .control_api
+        .save(&app, config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn control_api_rotate_token(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<ControlApiStatus, String> {
+    state
+        .control_api
+        .rotate_token(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn feed_status(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<FeedStatus, String> {
+    Ok(state.feed.status(&app).await)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn feed_save(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    config: FeedConfig,
+) -> Result<FeedStatus, String> {
+    state
+        .feed
+        .save(&app, config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn ha_discovery_registry(
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+    connection_id: String,
+    prefix: Option<String>,
+) -> Result<HaDiscoveryRegistry, String> {
+    let prefix = prefix
+        .as_deref()
+        .map(|value| value.trim().trim_end_matches('/'))
+        .filter(|value| !value.is_empty())
+        .unwrap_or(homeassistant::DEFAULT_DISCOVERY_PREFIX)
+        .to_string();
+    let records = state
+        .history_manager
+        .latest_per_topic(&app, &connection_id, &format!("{prefix}/"))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(homeassistant::build_registry(&prefix, &records))
 }
 
 fn safe_name(input: &str) -> String {
@@ -339,7 +3227,7 @@ fn normalize_selected_path(path: PathBuf, ext: &str) -> String {
     normalized.display().to_string()
 }
 
-fn resolve_connection(
+pub(crate) fn resolve_connection(
     profile: ConnectionProfile,
     brokers: Vec<BrokerConfig>,
     identities: Vec<AuthIdentity>,
@@ -379,12 +3267,32 @@ fn resolve_connection(
         return Err("Broker port is required".to_string());
     }
 
+    let mut client_id = client_id::expand(&client_id, &profile.id)?;
+    if profile.unique_client_id {
+        client_id.push_str(&client_id::unique_suffix());
+    }
+
     let protocol_version = match profile.protocol_version.unwrap_or(4) {
         5 => 5,
         3 | 4 => 4,
         _ => 4,
     };
 
+    let tls_path = |path: Option<String>| {
+        path.map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+    };
+    let tls_config = if matches!(protocol, TransportProtocol::Mqtts | TransportProtocol::Wss) {
+        tls::client_config(
+            tls_path(profile.ca_cert_path).as_deref(),
+            tls_path(profile.client_cert_path).as_deref(),
+            tls_path(profile.client_key_path).as_deref(),
+        )
+        .map_err(|e| format!("{e:#}"))?
+    } else {
+        None
+    };
+
     let normalized_path = if matches!(protocol, TransportProtocol::Ws | TransportProtocol::Wss) {
         if path.trim().is_empty() {
             "/mqtt".to_string()
@@ -397,6 +3305,7 @@ fn resolve_connection(
 
     Ok(ResolvedConnection {
         id: profile.id,
+        name: profile.name,
         host,
         port,
         protocol,
@@ -406,5 +3315,14 @@ fn resolve_connection(
         password,
         client_id,
         clean: profile.clean,
+        notify_on_disconnect: profile.notify_on_disconnect,
+        publish_rate_limit: profile.publish_rate_limit,
+        reconnect_on_takeover: profile.reconnect_on_takeover,
+        replay_unacked_publishes: profile.replay_unacked_publishes,
+        follow_server_redirects: profile.follow_server_redirects,
+        default_subscriptions: profile.default_subscriptions,
+        idle_disconnect: profile.idle_disconnect.filter(|idle| idle.minutes > 0),
+        tls_config,
+        redirect_hops: 0,
     })
 }
@@ -1,14 +1,21 @@
 use crate::models::{AppConfigPaths, NativeAppConfig};
 use crate::mqtt::now_millis;
 use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 
 const CONFIG_FILE_NAME: &str = "app-config.json";
 
-fn config_dir_path(app: &AppHandle) -> Result<PathBuf> {
+/// Serializes read-modify-write cycles on the config file so concurrent writers don't drop each
+/// other's changes.
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn config_dir_path(app: &AppHandle) -> Result<PathBuf> {
     let config_dir = app
         .path()
         .app_config_dir()
@@ -84,21 +91,70 @@ pub fn load_config(app: &AppHandle) -> Result<NativeAppConfig> {
 }
 
 pub fn save_config(app: &AppHandle, config: &NativeAppConfig) -> Result<()> {
+    let _guard = CONFIG_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    write_config(app, config)
+}
+
+/// Loads the config, applies `update` and writes the result back while holding the config lock.
+/// Nothing is written when `update` fails.
+pub fn update_config<R>(
+    app: &AppHandle,
+    update: impl FnOnce(&mut NativeAppConfig) -> Result<R>,
+) -> Result<R> {
+    let _guard = CONFIG_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut config = load_config(app)?;
+    let result = update(&mut config)?;
+    write_config(app, &config)?;
+    Ok(result)
+}
+
+fn write_config(app: &AppHandle, config: &NativeAppConfig) -> Result<()> {
     let path = config_file_path(app)?;
-    let temp_path = path.with_extension("json.tmp");
 
     let mut to_save = config.clone();
     to_save.updated_at = Some(now_millis());
 
     let content = serde_json::to_string_pretty(&to_save).context("failed to serialize config")?;
-    fs::write(&temp_path, content.as_bytes())
+    write_atomic(&path, content.as_bytes())
+}
+
+pub fn load_document<T: DeserializeOwned + Default>(app: &AppHandle, file_name: &str) -> Result<T> {
+    let path = config_dir_path(app)?.join(file_name);
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    if contents.trim().is_empty() {
+        return Ok(T::default());
+    }
+
+    serde_json::from_str::<T>(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn save_document<T: Serialize>(app: &AppHandle, file_name: &str, document: &T) -> Result<()> {
+    let path = config_dir_path(app)?.join(file_name);
+    let content = serde_json::to_string_pretty(document)
+        .with_context(|| format!("failed to serialize {file_name}"))?;
+    write_atomic(&path, content.as_bytes())
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, content)
         .with_context(|| format!("failed to write {}", temp_path.display()))?;
 
     if path.exists() {
-        fs::remove_file(&path).with_context(|| format!("failed to replace {}", path.display()))?;
+        fs::remove_file(path).with_context(|| format!("failed to replace {}", path.display()))?;
     }
 
-    fs::rename(&temp_path, &path).with_context(|| {
+    fs::rename(&temp_path, path).with_context(|| {
         format!(
             "failed to rename {} to {}",
             temp_path.display(),
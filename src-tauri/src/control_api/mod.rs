@@ -0,0 +1,150 @@
+mod routes;
+
+use crate::config_store;
+use crate::models::{ControlApiConfig, ControlApiStatus};
+use crate::mqtt::now_millis;
+use anyhow::{Context, Result, anyhow};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, watch};
+use tokio::task::JoinHandle;
+
+pub const CONTROL_API_FILE_NAME: &str = "control-api.json";
+pub const DEFAULT_CONTROL_API_PORT: u16 = 18_830;
+const BIND_ADDRESS: &str = "127.0.0.1";
+
+#[derive(Clone, Default)]
+pub struct ControlApiManager {
+    running: Arc<Mutex<Option<RunningApi>>>,
+}
+
+struct RunningApi {
+    port: u16,
+    started_at: u64,
+    shutdown_tx: watch::Sender<bool>,
+    serve_task: JoinHandle<()>,
+}
+
+impl ControlApiManager {
+    /// Starts the API on launch when the user opted in previously.
+    pub async fn start_from_config(&self, app: &AppHandle) -> Result<()> {
+        let config = self.config(app)?;
+        if config.enabled {
+            self.start(app, &config).await?;
+        }
+        Ok(())
+    }
+
+    pub fn config(&self, app: &AppHandle) -> Result<ControlApiConfig> {
+        config_store::load_document::<ControlApiConfig>(app, CONTROL_API_FILE_NAME)
+    }
+
+    /// Persists the config and brings the server in line with it, restarting on port or token changes.
+    pub async fn save(
+        &self,
+        app: &AppHandle,
+        mut config: ControlApiConfig,
+    ) -> Result<ControlApiStatus> {
+        if config.port == Some(0) {
+            return Err(anyhow!("control API port must be greater than zero"));
+        }
+        let token_missing = config
+            .token
+            .as_deref()
+            .map(str::trim)
+            .is_none_or(str::is_empty);
+        if token_missing {
+            config.token = Some(generate_token());
+        }
+        config_store::save_document(app, CONTROL_API_FILE_NAME, &config)?;
+
+        self.stop().await;
+        if config.enabled {
+            self.start(app, &config).await?;
+        }
+        Ok(self.status(app).await)
+    }
+
+    pub async fn rotate_token(&self, app: &AppHandle) -> Result<ControlApiStatus> {
+        let mut config = self.config(app)?;
+        config.token = Some(generate_token());
+        self.save(app, config).await
+    }
+
+    pub async fn status(&self, app: &AppHandle) -> ControlApiStatus {
+        let config = self.config(app).unwrap_or_default();
+        let running = self.running.lock().await;
+        ControlApiStatus {
+            running: running.is_some(),
+            port: running.as_ref().map(|api| api.port),
+            started_at: running.as_ref().map(|api| api.started_at),
+            config,
+        }
+    }
+
+    async fn start(&self, app: &AppHandle, config: &ControlApiConfig) -> Result<()> {
+        let token = config
+            .token
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| anyhow!("control API token is required"))?
+            .to_string();
+        let port = config.port.unwrap_or(DEFAULT_CONTROL_API_PORT);
+
+        let mut running = self.running.lock().await;
+        let listener = TcpListener::bind((BIND_ADDRESS, port))
+            .await
+            .with_context(|| format!("failed to bind control API on {BIND_ADDRESS}:{port}"))?;
+
+        let router = routes::router(app.clone(), token);
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let serve_task = tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = shutdown_rx.changed().await;
+            };
+            if let Err(error) = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                tracing::error!("control API server failed: {error}");
+            }
+        });
+
+        *running = Some(RunningApi {
+            port,
+            started_at: now_millis(),
+            shutdown_tx,
+            serve_task,
+        });
+        Ok(())
+    }
+
+    async fn stop(&self) {
+        let mut running = self.running.lock().await;
+        if let Some(api) = running.take() {
+            let _ = api.shutdown_tx.send(true);
+            api.serve_task.abort();
+            // Wait for the listener to drop so a restart can rebind the same port.
+            let _ = api.serve_task.await;
+        }
+    }
+}
+
+pub(crate) fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    left.iter()
+        .zip(right)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+pub(crate) fn generate_token() -> String {
+    rand::random::<[u8; 24]>()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -0,0 +1,321 @@
+use super::constant_time_eq;
+use crate::commands::resolve_connection;
+use crate::config_store;
+use crate::labels::{self, LabelSelector};
+use crate::models::{
+    ControlApiConnection, HistoryMessageRecord, HistorySort, HistoryTimeSource, Payload,
+    SubscribeOptions, UserProperty,
+};
+use crate::mqtt::MqttError;
+use crate::state::AppState;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+const DEFAULT_HISTORY_LIMIT: usize = 200;
+
+#[derive(Clone)]
+struct ApiContext {
+    app: AppHandle,
+    token: Arc<str>,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl ApiError {
+    fn bad_request(message: impl ToString) -> Self {
+        Self(StatusCode::BAD_REQUEST, message.to_string())
+    }
+
+    fn not_found(message: impl ToString) -> Self {
+        Self(StatusCode::NOT_FOUND, message.to_string())
+    }
+
+    fn forbidden(message: impl ToString) -> Self {
+        Self(StatusCode::FORBIDDEN, message.to_string())
+    }
+
+    fn internal(message: impl ToString) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, message.to_string())
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscribeBody {
+    topic: String,
+    #[serde(default)]
+    qos: u8,
+    #[serde(default)]
+    options: SubscribeOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UnsubscribeBody {
+    topic: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishBody {
+    topic: String,
+    #[serde(default)]
+    payload: String,
+    #[serde(default)]
+    qos: u8,
+    #[serde(default)]
+    retain: bool,
+    /// Only accepted on MQTT 5 connections.
+    #[serde(default)]
+    user_properties: Vec<UserProperty>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListParams {
+    /// Label selector, e.g. `environment=prod,site=berlin`.
+    labels: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryParams {
+    limit: Option<usize>,
+    before_ts: Option<u64>,
+    before_id: Option<i64>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    #[serde(default)]
+    time_source: HistoryTimeSource,
+    #[serde(default)]
+    sort: HistorySort,
+}
+
+pub(super) fn router(app: AppHandle, token: String) -> Router {
+    let context = ApiContext {
+        app,
+        token: Arc::from(token),
+    };
+
+    Router::new()
+        .route("/api/v1/connections", get(list_connections))
+        .route("/api/v1/connections/{id}/connect", post(connect))
+        .route("/api/v1/connections/{id}/disconnect", post(disconnect))
+        .route("/api/v1/connections/{id}/subscribe", post(subscribe))
+        .route("/api/v1/connections/{id}/unsubscribe", post(unsubscribe))
+        .route("/api/v1/connections/{id}/publish", post(publish))
+        .route("/api/v1/connections/{id}/history", get(history))
+        .route_layer(middleware::from_fn_with_state(
+            context.clone(),
+            require_token,
+        ))
+        .with_state(context)
+}
+
+async fn require_token(
+    State(context): State<ApiContext>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if !constant_time_eq(provided.as_bytes(), context.token.as_bytes()) {
+        return ApiError(StatusCode::UNAUTHORIZED, "invalid or missing token".into())
+            .into_response();
+    }
+    next.run(request).await
+}
+
+async fn list_connections(
+    State(context): State<ApiContext>,
+    Query(params): Query<ListParams>,
+) -> ApiResult<Vec<ControlApiConnection>> {
+    let selector = LabelSelector::parse(params.labels.as_deref().unwrap_or_default())
+        .map_err(ApiError::bad_request)?;
+    let config = config_store::load_config(&context.app).map_err(ApiError::internal)?;
+    let state = context.app.state::<AppState>();
+    let connections = labels::select(config.connections, &selector)
+        .into_iter()
+        .map(|profile| ControlApiConnection {
+            connected: state.mqtt_manager.is_connected(&profile.id),
+            id: profile.id,
+            name: profile.name,
+            group: profile.group,
+            labels: profile.labels,
+        })
+        .collect();
+    Ok(Json(connections))
+}
+
+async fn connect(
+    State(context): State<ApiContext>,
+    Path(connection_id): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    let config = config_store::load_config(&context.app).map_err(ApiError::internal)?;
+    let profile = config
+        .connections
+        .into_iter()
+        .find(|profile| profile.id == connection_id)
+        .ok_or_else(|| ApiError::not_found(format!("unknown connection: {connection_id}")))?;
+    let resolved = resolve_connection(profile, config.brokers, config.identities)
+        .map_err(ApiError::bad_request)?;
+
+    let state = context.app.state::<AppState>();
+    state
+        .mqtt_manager
+        .connect(context.app.clone(), resolved)
+        .map_err(ApiError::internal)?;
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn disconnect(
+    State(context): State<ApiContext>,
+    Path(connection_id): Path<String>,
+) -> ApiResult<serde_json::Value> {
+    let state = context.app.state::<AppState>();
+    state
+        .mqtt_manager
+        .disconnect(&connection_id)
+        .map_err(ApiError::not_found)?;
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn subscribe(
+    State(context): State<ApiContext>,
+    Path(connection_id): Path<String>,
+    Json(body): Json<SubscribeBody>,
+) -> ApiResult<serde_json::Value> {
+    let state = context.app.state::<AppState>();
+    state
+        .mqtt_manager
+        .subscribe(&connection_id, body.topic, body.qos, body.options)
+        .map_err(ApiError::not_found)?;
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn unsubscribe(
+    State(context): State<ApiContext>,
+    Path(connection_id): Path<String>,
+    Json(body): Json<UnsubscribeBody>,
+) -> ApiResult<serde_json::Value> {
+    let state = context.app.state::<AppState>();
+    state
+        .mqtt_manager
+        .unsubscribe(&connection_id, body.topic)
+        .map_err(ApiError::not_found)?;
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn publish(
+    State(context): State<ApiContext>,
+    Path(connection_id): Path<String>,
+    Json(body): Json<PublishBody>,
+) -> ApiResult<serde_json::Value> {
+    crate::profiles::refuse_production(&context.app, &connection_id)
+        .map_err(ApiError::forbidden)?;
+    let state = context.app.state::<AppState>();
+    let payload = Payload::from(body.payload);
+    state
+        .mqtt_manager
+        .publish(
+            &connection_id,
+            body.topic.clone(),
+            payload.clone(),
+            body.qos,
+            body.retain,
+            body.user_properties.clone(),
+        )
+        .map_err(|error| match error {
+            MqttError::UserPropertiesUnsupported(_) => ApiError::bad_request(error),
+            _ => ApiError::not_found(error),
+        })?;
+
+    state
+        .recent
+        .record_outgoing(&connection_id, &body.topic, &payload, body.qos, body.retain);
+    state
+        .history_manager
+        .append_outgoing(
+            &context.app,
+            &connection_id,
+            &body.topic,
+            payload,
+            body.qos,
+            body.retain,
+            body.user_properties,
+        )
+        .await
+        .map_err(|e| {
+            ApiError::internal(format!(
+                "published, but failed to persist outgoing history: {e}"
+            ))
+        })?;
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn history(
+    State(context): State<ApiContext>,
+    Path(connection_id): Path<String>,
+    Query(params): Query<HistoryParams>,
+) -> ApiResult<Vec<HistoryMessageRecord>> {
+    let state = context.app.state::<AppState>();
+    let limit = params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let records = match (params.before_ts, params.before_id) {
+        (Some(before_ts), Some(before_id)) => {
+            state
+                .history_manager
+                .query_before(
+                    &context.app,
+                    &connection_id,
+                    before_ts,
+                    before_id,
+                    limit,
+                    params.sort,
+                )
+                .await
+        }
+        _ if params.from_ts.is_some() || params.to_ts.is_some() => {
+            state
+                .history_manager
+                .query_range(
+                    &context.app,
+                    &connection_id,
+                    params.from_ts,
+                    params.to_ts,
+                    limit,
+                    params.time_source,
+                    params.sort,
+                )
+                .await
+        }
+        _ => {
+            state
+                .history_manager
+                .query_latest(&context.app, &connection_id, limit, params.sort)
+                .await
+        }
+    }
+    .map_err(ApiError::internal)?;
+    Ok(Json(records))
+}
@@ -0,0 +1,215 @@
+use crate::config_store;
+use crate::models::CrashReportSummary;
+use anyhow::{Context, Result, anyhow};
+use chrono::{SecondsFormat, Utc};
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::future::Future;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+use tauri::AppHandle;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context as LayerContext;
+
+const CRASH_DIR_NAME: &str = "crashes";
+const CRASH_FILE_PREFIX: &str = "crash-";
+const CRASH_FILE_EXT: &str = "txt";
+const MAX_LOG_LINES: usize = 200;
+
+static LOG_TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+static APP_VERSION: OnceLock<String> = OnceLock::new();
+
+tokio::task_local! {
+    static TASK_NAME: &'static str;
+}
+
+/// Installs a panic hook that writes a crash report before delegating to the default hook.
+pub fn install(app: &AppHandle) {
+    let _ = APP_VERSION.set(app.package_info().version.to_string());
+    match crash_dir(app) {
+        Ok(dir) => {
+            let _ = CRASH_DIR.set(dir);
+        }
+        Err(error) => eprintln!("crash reports disabled: {error:#}"),
+    }
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(path) = write_report(info) {
+            eprintln!("crash report written to {}", path.display());
+        }
+        previous(info);
+    }));
+}
+
+/// Names a background task so a panic inside it is attributed in the crash report.
+pub fn monitored<F: Future>(name: &'static str, future: F) -> impl Future<Output = F::Output> {
+    TASK_NAME.scope(name, future)
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<CrashReportSummary>> {
+    let dir = crash_dir(app)?;
+    let mut reports = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let entry = entry.context("failed to read crash directory entry")?;
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !file_name.starts_with(CRASH_FILE_PREFIX) {
+            continue;
+        }
+        let metadata = entry
+            .metadata()
+            .context("failed to read crash report metadata")?;
+        let created_at = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let message = fs::read_to_string(&path).ok().and_then(|contents| {
+            contents
+                .lines()
+                .find_map(|line| line.strip_prefix("message: "))
+                .map(str::to_string)
+        });
+        reports.push(CrashReportSummary {
+            file_name: file_name.to_string(),
+            path: path.display().to_string(),
+            created_at,
+            size: metadata.len(),
+            message,
+        });
+    }
+    reports.sort_by_key(|report| std::cmp::Reverse(report.created_at));
+    Ok(reports)
+}
+
+/// Resolves a report by file name, refusing anything outside the crash directory.
+pub fn report_path(app: &AppHandle, file_name: Option<&str>) -> Result<PathBuf> {
+    let dir = crash_dir(app)?;
+    let Some(file_name) = file_name else {
+        return Ok(dir);
+    };
+    if file_name.contains(['/', '\\']) || !file_name.starts_with(CRASH_FILE_PREFIX) {
+        return Err(anyhow!("invalid crash report name: {file_name}"));
+    }
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return Err(anyhow!("crash report not found: {file_name}"));
+    }
+    Ok(path)
+}
+
+fn crash_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = config_store::config_dir_path(app)?.join(CRASH_DIR_NAME);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create crash directory: {}", dir.display()))?;
+    Ok(dir)
+}
+
+fn write_report(info: &PanicHookInfo<'_>) -> Option<PathBuf> {
+    let dir = CRASH_DIR.get()?;
+    let now = Utc::now();
+
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string());
+    let location = info
+        .location()
+        .map(|location| {
+            format!(
+                "{}:{}:{}",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    let thread = std::thread::current();
+    let task = TASK_NAME.try_with(|name| *name).unwrap_or("-");
+
+    let mut report = String::new();
+    let _ = writeln!(report, "NexusMQTT crash report");
+    let _ = writeln!(
+        report,
+        "version: {}",
+        APP_VERSION.get().map(String::as_str).unwrap_or("unknown")
+    );
+    let _ = writeln!(
+        report,
+        "time: {}",
+        now.to_rfc3339_opts(SecondsFormat::Millis, true)
+    );
+    let _ = writeln!(
+        report,
+        "os: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "thread: {}", thread.name().unwrap_or("unnamed"));
+    let _ = writeln!(report, "task: {task}");
+    let _ = writeln!(report, "message: {message}");
+    let _ = writeln!(report, "location: {location}");
+    let _ = writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture());
+    let _ = writeln!(report, "\nrecent log:");
+    // The panic may have happened while the tail was locked; skip it rather than deadlock.
+    if let Ok(tail) = LOG_TAIL.try_lock() {
+        for line in tail.iter() {
+            let _ = writeln!(report, "{line}");
+        }
+    }
+
+    let path = dir.join(format!(
+        "{CRASH_FILE_PREFIX}{}.{CRASH_FILE_EXT}",
+        now.format("%Y%m%d-%H%M%S%.3f")
+    ));
+    fs::write(&path, report).ok()?;
+    Some(path)
+}
+
+/// Keeps the last few hundred log events in memory for crash reports.
+pub struct TailLayer;
+
+impl<S: Subscriber> Layer<S> for TailLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {:>5} {}:",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            metadata.level(),
+            metadata.target()
+        );
+        event.record(&mut LineVisitor(&mut line));
+
+        if let Ok(mut tail) = LOG_TAIL.lock() {
+            if tail.len() >= MAX_LOG_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+}
+
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
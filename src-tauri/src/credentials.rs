@@ -0,0 +1,442 @@
+//! Tracks when identity credentials lapse: the notAfter date of a client certificate, read from
+//! the file, and a manually entered token expiry. A background check warns ahead of time so
+//! connections don't start failing out of the blue.
+//!
+//! Also audits the saved configuration for plaintext, weak and reused secrets.
+
+use crate::config_store;
+use crate::crash;
+use crate::models::{
+    AuthIdentity, AutomationConfig, ControlApiConfig, CredentialAuditReport, CredentialExpiry,
+    CredentialFinding, CredentialFindingKind, CredentialKind, FeedConfig, FindingSeverity,
+    IntegrationsConfig, NativeAppConfig, RuleAction, TelemetryConfig,
+};
+use crate::mqtt::now_millis;
+use anyhow::{Context, Result, anyhow};
+use chrono::NaiveDate;
+use rustls::pki_types::CertificateDer;
+use rustls::pki_types::pem::PemObject;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tokio::time::{self, Duration};
+
+pub const DEFAULT_WARNING_DAYS: u32 = 14;
+const CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const MIN_PASSWORD_LEN: usize = 8;
+/// Defaults shipped by brokers and tutorials, compared case-insensitively.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password",
+    "passw0rd",
+    "123456",
+    "12345678",
+    "123456789",
+    "admin",
+    "administrator",
+    "public",
+    "guest",
+    "mqtt",
+    "mosquitto",
+    "emqx",
+    "root",
+    "test",
+    "secret",
+    "changeme",
+    "qwerty",
+    "letmein",
+    "default",
+];
+/// Header names that carry credentials when they contain one of these, case-insensitively.
+const SECRET_HEADER_MARKERS: &[&str] = &["authorization", "token", "key", "secret", "cookie"];
+
+/// Runs the periodic expiry check and remembers what it already warned about, so each
+/// credential is reported once when it enters its warning window and once when it lapses.
+#[derive(Clone, Default)]
+pub struct CredentialMonitor {
+    warned: Arc<Mutex<HashSet<String>>>,
+}
+
+impl CredentialMonitor {
+    /// Checks the saved identities now and every `CHECK_INTERVAL_SECS`, emitting
+    /// `credential-expiry` with the credentials that newly need attention.
+    pub fn start(&self, app: AppHandle) {
+        let monitor = self.clone();
+        tokio::spawn(crash::monitored("credential-expiry", async move {
+            let mut interval = time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let identities = match config_store::load_config(&app) {
+                    Ok(config) => config.identities,
+                    Err(error) => {
+                        tracing::warn!("failed to load identities for expiry check: {error:#}");
+                        continue;
+                    }
+                };
+                let due = monitor.newly_due(check(&identities, now_millis()));
+                if due.is_empty() {
+                    continue;
+                }
+                for expiry in &due {
+                    tracing::warn!(
+                        identity = expiry.identity_name.as_str(),
+                        kind = ?expiry.kind,
+                        expires_at = expiry.expires_at,
+                        error = expiry.error.as_deref(),
+                        "identity credential needs attention"
+                    );
+                }
+                let _ = app.emit("credential-expiry", due);
+            }
+        }));
+    }
+
+    fn newly_due(&self, expiries: Vec<CredentialExpiry>) -> Vec<CredentialExpiry> {
+        let mut warned = self
+            .warned
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        expiries
+            .into_iter()
+            .filter(|expiry| expiry.expired || expiry.warning || expiry.error.is_some())
+            .filter(|expiry| {
+                warned.insert(format!(
+                    "{}:{:?}:{:?}:{}",
+                    expiry.identity_id, expiry.kind, expiry.expires_at, expiry.expired
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Expiry status of every tracked credential of `identities`.
+pub fn check(identities: &[AuthIdentity], now: u64) -> Vec<CredentialExpiry> {
+    let mut expiries = Vec::new();
+    for identity in identities {
+        let warning_days = identity.expiry_warning_days.unwrap_or(DEFAULT_WARNING_DAYS);
+        let path = identity
+            .client_cert_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty());
+        if let Some(path) = path {
+            let read = certificate_not_after(path);
+            expiries.push(expiry(
+                identity,
+                CredentialKind::ClientCertificate,
+                read.as_ref().ok().copied(),
+                read.err().map(|error| format!("{error:#}")),
+                warning_days,
+                now,
+            ));
+        }
+        if let Some(expires_at) = identity.token_expires_at {
+            expiries.push(expiry(
+                identity,
+                CredentialKind::Token,
+                Some(expires_at),
+                None,
+                warning_days,
+                now,
+            ));
+        }
+    }
+    expiries
+}
+
+fn expiry(
+    identity: &AuthIdentity,
+    kind: CredentialKind,
+    expires_at: Option<u64>,
+    error: Option<String>,
+    warning_days: u32,
+    now: u64,
+) -> CredentialExpiry {
+    let expired = expires_at.is_some_and(|at| at <= now);
+    CredentialExpiry {
+        identity_id: identity.id.clone(),
+        identity_name: identity.name.clone(),
+        kind,
+        expires_at,
+        days_left: expires_at.map(|at| (at as i64 - now as i64) / DAY_MS as i64),
+        expired,
+        warning: !expired
+            && expires_at.is_some_and(|at| at - now <= u64::from(warning_days) * DAY_MS),
+        error,
+    }
+}
+
+/// notAfter of the first certificate in a PEM or DER file, in epoch milliseconds.
+pub fn certificate_not_after(path: &str) -> Result<u64> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {path}"))?;
+    let der = if bytes.starts_with(b"-----BEGIN") {
+        CertificateDer::from_pem_slice(&bytes)
+            .map_err(|error| anyhow!("no certificate found in {path}: {error}"))?
+    } else {
+        CertificateDer::from(bytes)
+    };
+    parse_not_after(&der).ok_or_else(|| anyhow!("{path} is not an X.509 certificate"))
+}
+
+/// Walks `Certificate -> TBSCertificate -> Validity` far enough to read notAfter.
+fn parse_not_after(der: &[u8]) -> Option<u64> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, tbs, _) = der_element(certificate)?;
+    // An explicit `[0]` version comes before the serial number when present.
+    let (tag, _, rest) = der_element(tbs)?;
+    let rest = if tag == 0xa0 {
+        der_element(rest)?.2
+    } else {
+        rest
+    };
+    let (_, _, rest) = der_element(rest)?; // signature algorithm
+    let (_, _, rest) = der_element(rest)?; // issuer
+    let (_, validity, _) = der_element(rest)?;
+    let (_, _, validity) = der_element(validity)?; // notBefore
+    let (tag, not_after, _) = der_element(validity)?;
+    parse_der_time(tag, not_after)
+}
+
+/// Splits the first DER element off `input` as `(tag, contents, rest)`.
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first & 0x80 == 0 {
+        (usize::from(first), rest)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let (bytes, rest) = rest.split_at(count);
+        let len = bytes
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | usize::from(*byte));
+        (len, rest)
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let (contents, rest) = rest.split_at(len);
+    Some((tag, contents, rest))
+}
+
+/// UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime (`YYYYMMDDHHMMSSZ`) to epoch milliseconds.
+fn parse_der_time(tag: u8, value: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let year: i32 = text.get(..2)?.parse().ok()?;
+            (
+                if year >= 50 { 1900 + year } else { 2000 + year },
+                &text[2..],
+            )
+        }
+        0x18 => (text.get(..4)?.parse().ok()?, &text[4..]),
+        _ => return None,
+    };
+    let field = |index: usize| -> Option<u32> { rest.get(index..index + 2)?.parse().ok() };
+    let timestamp = NaiveDate::from_ymd_opt(year, field(0)?, field(2)?)?
+        .and_hms_opt(field(4)?, field(6)?, field(8)?)?
+        .and_utc()
+        .timestamp_millis();
+    u64::try_from(timestamp).ok()
+}
+
+/// The configuration documents the audit looks through.
+pub struct AuditSources {
+    pub app_config: NativeAppConfig,
+    pub automation: AutomationConfig,
+    pub integrations: IntegrationsConfig,
+    pub control_api: ControlApiConfig,
+    pub feed: FeedConfig,
+    pub telemetry: TelemetryConfig,
+}
+
+/// A secret found in the configuration. Only `value` holds the secret, and it never leaves
+/// this module.
+struct StoredSecret {
+    location: String,
+    username: Option<String>,
+    /// A user-chosen password, as opposed to a generated token or an API key.
+    password: bool,
+    value: String,
+}
+
+/// Reports every secret stored in plaintext, weak passwords, secrets reused in several places
+/// and identities no profile refers to. Findings describe where a secret lives, never its value.
+pub fn audit(sources: &AuditSources, now: u64) -> CredentialAuditReport {
+    let secrets = stored_secrets(sources);
+    let mut findings = Vec::new();
+
+    for secret in &secrets {
+        findings.push(CredentialFinding {
+            kind: CredentialFindingKind::PlaintextSecret,
+            severity: FindingSeverity::Medium,
+            locations: vec![secret.location.clone()],
+            detail: "stored unencrypted in the configuration".to_string(),
+        });
+        if let Some(reason) = secret.password.then(|| weakness(secret)).flatten() {
+            findings.push(CredentialFinding {
+                kind: CredentialFindingKind::WeakPassword,
+                severity: FindingSeverity::High,
+                locations: vec![secret.location.clone()],
+                detail: reason.to_string(),
+            });
+        }
+    }
+
+    let mut by_value: BTreeMap<&str, Vec<&StoredSecret>> = BTreeMap::new();
+    for secret in &secrets {
+        by_value
+            .entry(secret.value.as_str())
+            .or_default()
+            .push(secret);
+    }
+    for shared in by_value.values().filter(|shared| shared.len() > 1) {
+        findings.push(CredentialFinding {
+            kind: CredentialFindingKind::DuplicateSecret,
+            severity: FindingSeverity::High,
+            locations: shared
+                .iter()
+                .map(|secret| secret.location.clone())
+                .collect(),
+            detail: format!("the same secret is used in {} places", shared.len()),
+        });
+    }
+
+    let app_config = &sources.app_config;
+    for identity in &app_config.identities {
+        let used = app_config
+            .connections
+            .iter()
+            .any(|profile| profile.identity_id.as_deref() == Some(identity.id.as_str()));
+        if !used {
+            findings.push(CredentialFinding {
+                kind: CredentialFindingKind::UnusedIdentity,
+                severity: FindingSeverity::Low,
+                locations: vec![format!("identity '{}'", identity.name)],
+                detail: "not used by any connection profile".to_string(),
+            });
+        }
+    }
+
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    CredentialAuditReport {
+        secrets_scanned: secrets.len(),
+        findings,
+        audited_at: now,
+    }
+}
+
+fn weakness(secret: &StoredSecret) -> Option<&'static str> {
+    let lower = secret.value.to_lowercase();
+    if COMMON_PASSWORDS.contains(&lower.as_str()) {
+        Some("a commonly used default password")
+    } else if secret
+        .username
+        .as_deref()
+        .is_some_and(|username| username.eq_ignore_ascii_case(&secret.value))
+    {
+        Some("the password equals the username")
+    } else if secret.value.chars().count() < MIN_PASSWORD_LEN {
+        Some("shorter than 8 characters")
+    } else {
+        None
+    }
+}
+
+fn stored_secrets(sources: &AuditSources) -> Vec<StoredSecret> {
+    let mut secrets = Vec::new();
+    let mut push =
+        |location: String, username: Option<&String>, password: bool, value: Option<&String>| {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                secrets.push(StoredSecret {
+                    location,
+                    username: username.cloned(),
+                    password,
+                    value: value.clone(),
+                });
+            }
+        };
+
+    let app_config = &sources.app_config;
+    for profile in &app_config.connections {
+        push(
+            format!("connection '{}' password", profile.name),
+            profile.username.as_ref(),
+            true,
+            profile.password.as_ref(),
+        );
+    }
+    for identity in &app_config.identities {
+        push(
+            format!("identity '{}' password", identity.name),
+            identity.username.as_ref(),
+            true,
+            identity.password.as_ref(),
+        );
+    }
+    if let Some(ai) = &app_config.ai_config {
+        push("AI API key".to_string(), None, false, ai.api_key.as_ref());
+    }
+    for rule in &sources.automation.rules {
+        for action in &rule.actions {
+            match action {
+                RuleAction::Webhook { headers, .. } => {
+                    for (name, value) in headers {
+                        if is_secret_header(name) {
+                            push(
+                                format!("rule '{}' webhook header {name}", rule.name),
+                                None,
+                                false,
+                                Some(value),
+                            );
+                        }
+                    }
+                }
+                RuleAction::Influx { token, .. } => push(
+                    format!("rule '{}' InfluxDB token", rule.name),
+                    None,
+                    false,
+                    token.as_ref(),
+                ),
+                RuleAction::RunCommand { .. } | RuleAction::Notify { .. } => {}
+            }
+        }
+    }
+    for bridge in &sources.integrations.kafka_bridges {
+        push(
+            format!("Kafka bridge '{}' password", bridge.name),
+            bridge.username.as_ref(),
+            true,
+            bridge.password.as_ref(),
+        );
+    }
+    push(
+        "control API token".to_string(),
+        None,
+        false,
+        sources.control_api.token.as_ref(),
+    );
+    push(
+        "WebSocket feed token".to_string(),
+        None,
+        false,
+        sources.feed.token.as_ref(),
+    );
+    for (name, value) in &sources.telemetry.headers {
+        if is_secret_header(name) {
+            push(format!("telemetry header {name}"), None, false, Some(value));
+        }
+    }
+    secrets
+}
+
+fn is_secret_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_HEADER_MARKERS
+        .iter()
+        .any(|marker| name.contains(marker))
+}
@@ -0,0 +1,72 @@
+use crate::config_store;
+use crate::models::{ExportLogDocument, ExportRecord};
+use anyhow::{Result, anyhow};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+pub const EXPORT_LOG_FILE_NAME: &str = "export-log.json";
+/// Oldest records are dropped past this.
+const MAX_RECORDS: usize = 100;
+
+/// Completed exports with the parameters they ran with, so a recurring export can be repeated
+/// over a new time window instead of being set up again.
+#[derive(Clone, Default)]
+pub struct ExportLog {
+    /// Serializes read-modify-write cycles on the log file.
+    lock: Arc<Mutex<()>>,
+}
+
+impl ExportLog {
+    pub fn list(&self, app: &AppHandle) -> Result<Vec<ExportRecord>> {
+        let _lock = self.lock();
+        Ok(config_store::load_document::<ExportLogDocument>(app, EXPORT_LOG_FILE_NAME)?.records)
+    }
+
+    pub fn get(&self, app: &AppHandle, id: &str) -> Result<ExportRecord> {
+        self.list(app)?
+            .into_iter()
+            .find(|record| record.id == id)
+            .ok_or_else(|| anyhow!("export {id} is not in the export log"))
+    }
+
+    pub fn record(&self, app: &AppHandle, record: ExportRecord) -> Result<()> {
+        let _lock = self.lock();
+        let mut document =
+            config_store::load_document::<ExportLogDocument>(app, EXPORT_LOG_FILE_NAME)?;
+        document.records.insert(0, record);
+        document.records.truncate(MAX_RECORDS);
+        config_store::save_document(app, EXPORT_LOG_FILE_NAME, &document)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub fn new_record_id() -> String {
+    format!("exp-{:016x}", rand::random::<u64>())
+}
+
+/// The window a re-run covers when the caller doesn't pass one: the original window moved
+/// forward so it ends now (a bounded window), or starts as long before now as it did before
+/// the original run (an open-ended one). Explicit bounds are used as given.
+pub fn rerun_window(
+    record: &ExportRecord,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    now: u64,
+) -> (Option<u64>, Option<u64>) {
+    if from_ts.is_some() || to_ts.is_some() {
+        return (from_ts, to_ts);
+    }
+    match (record.from_ts, record.to_ts) {
+        (Some(from), Some(to)) => (Some(now.saturating_sub(to.saturating_sub(from))), Some(now)),
+        (Some(from), None) => (
+            Some(now.saturating_sub(record.started_at.saturating_sub(from))),
+            None,
+        ),
+        (from, to) => (from, to),
+    }
+}
@@ -0,0 +1,185 @@
+mod socket;
+
+use crate::config_store;
+use crate::control_api::generate_token;
+use crate::models::{FeedConfig, FeedStatus, MqttMessageBatchPayload, MqttStatusPayload};
+use crate::mqtt::now_millis;
+use anyhow::{Context, Result, anyhow};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::AppHandle;
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, broadcast, watch};
+use tokio::task::JoinHandle;
+
+pub const FEED_FILE_NAME: &str = "feed.json";
+pub const DEFAULT_FEED_PORT: u16 = 18_831;
+const BIND_ADDRESS: &str = "127.0.0.1";
+const FEED_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+pub struct FeedManager {
+    inner: Arc<FeedInner>,
+}
+
+struct FeedInner {
+    events: broadcast::Sender<FeedEvent>,
+    subscribers: AtomicUsize,
+    running: Mutex<Option<RunningFeed>>,
+}
+
+struct RunningFeed {
+    port: u16,
+    started_at: u64,
+    shutdown_tx: watch::Sender<bool>,
+    serve_task: JoinHandle<()>,
+}
+
+#[derive(Clone)]
+pub(crate) enum FeedEvent {
+    Status(Arc<MqttStatusPayload>),
+    Batch(Arc<MqttMessageBatchPayload>),
+}
+
+impl Default for FeedManager {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(FEED_CAPACITY);
+        Self {
+            inner: Arc::new(FeedInner {
+                events,
+                subscribers: AtomicUsize::new(0),
+                running: Mutex::new(None),
+            }),
+        }
+    }
+}
+
+impl FeedManager {
+    pub async fn start_from_config(&self, app: &AppHandle) -> Result<()> {
+        let mut config = self.config(app)?;
+        if config.enabled {
+            // Configs saved before the token became mandatory may not have one yet.
+            if ensure_token(&mut config) {
+                config_store::save_document(app, FEED_FILE_NAME, &config)?;
+            }
+            self.start(&config).await?;
+        }
+        Ok(())
+    }
+
+    pub fn config(&self, app: &AppHandle) -> Result<FeedConfig> {
+        config_store::load_document::<FeedConfig>(app, FEED_FILE_NAME)
+    }
+
+    /// Persists the config and restarts the feed; a missing token is generated, since any web
+    /// page could otherwise connect to the local socket.
+    pub async fn save(&self, app: &AppHandle, mut config: FeedConfig) -> Result<FeedStatus> {
+        if config.port == Some(0) {
+            return Err(anyhow!("feed port must be greater than zero"));
+        }
+        ensure_token(&mut config);
+        config_store::save_document(app, FEED_FILE_NAME, &config)?;
+
+        self.stop().await;
+        if config.enabled {
+            self.start(&config).await?;
+        }
+        Ok(self.status(app).await)
+    }
+
+    pub async fn status(&self, app: &AppHandle) -> FeedStatus {
+        let config = self.config(app).unwrap_or_default();
+        let running = self.inner.running.lock().await;
+        FeedStatus {
+            running: running.is_some(),
+            port: running.as_ref().map(|feed| feed.port),
+            started_at: running.as_ref().map(|feed| feed.started_at),
+            subscribers: self.inner.subscribers.load(Ordering::Relaxed),
+            config,
+        }
+    }
+
+    /// Mirrors an `mqtt-status` event to feed subscribers; a no-op when nobody is listening.
+    pub fn publish_status(&self, payload: &MqttStatusPayload) {
+        if self.inner.events.receiver_count() > 0 {
+            let _ = self
+                .inner
+                .events
+                .send(FeedEvent::Status(Arc::new(payload.clone())));
+        }
+    }
+
+    /// Mirrors an `mqtt-message-batch` event to feed subscribers.
+    pub fn publish_batch(&self, payload: &MqttMessageBatchPayload) {
+        if self.inner.events.receiver_count() > 0 {
+            let _ = self
+                .inner
+                .events
+                .send(FeedEvent::Batch(Arc::new(payload.clone())));
+        }
+    }
+
+    async fn start(&self, config: &FeedConfig) -> Result<()> {
+        let port = config.port.unwrap_or(DEFAULT_FEED_PORT);
+        let token = config
+            .token
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| anyhow!("feed token is required"))?
+            .to_string();
+
+        let mut running = self.inner.running.lock().await;
+        let listener = TcpListener::bind((BIND_ADDRESS, port))
+            .await
+            .with_context(|| format!("failed to bind feed on {BIND_ADDRESS}:{port}"))?;
+
+        let router = socket::router(self.clone(), token);
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let serve_task = tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = shutdown_rx.changed().await;
+            };
+            if let Err(error) = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                tracing::error!("feed server failed: {error}");
+            }
+        });
+
+        *running = Some(RunningFeed {
+            port,
+            started_at: now_millis(),
+            shutdown_tx,
+            serve_task,
+        });
+        Ok(())
+    }
+
+    async fn stop(&self) {
+        let mut running = self.inner.running.lock().await;
+        if let Some(feed) = running.take() {
+            let _ = feed.shutdown_tx.send(true);
+            feed.serve_task.abort();
+            let _ = feed.serve_task.await;
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<FeedEvent> {
+        self.inner.events.subscribe()
+    }
+}
+
+/// Fills in a token when the config has none, returning whether it did.
+fn ensure_token(config: &mut FeedConfig) -> bool {
+    let missing = config
+        .token
+        .as_deref()
+        .map(str::trim)
+        .is_none_or(str::is_empty);
+    if missing {
+        config.token = Some(generate_token());
+    }
+    missing
+}
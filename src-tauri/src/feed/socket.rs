@@ -0,0 +1,160 @@
+use super::{FeedEvent, FeedManager};
+use crate::control_api::constant_time_eq;
+use crate::models::{MqttBatchItem, MqttMessageBatchPayload};
+use crate::mqtt::topic_matches;
+use axum::Router;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Clone)]
+struct FeedContext {
+    feed: FeedManager,
+    token: Arc<str>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeedParams {
+    token: Option<String>,
+    connection_id: Option<String>,
+    /// Comma-separated MQTT topic filters; empty means every topic.
+    topics: Option<String>,
+}
+
+struct FeedFilter {
+    connection_id: Option<String>,
+    topics: Vec<String>,
+}
+
+pub(super) fn router(feed: FeedManager, token: String) -> Router {
+    Router::new()
+        .route("/feed", get(upgrade))
+        .with_state(FeedContext {
+            feed,
+            token: Arc::from(token),
+        })
+}
+
+async fn upgrade(
+    State(context): State<FeedContext>,
+    Query(params): Query<FeedParams>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    // Browsers always send `Origin` on WebSocket handshakes and other clients don't, so this
+    // keeps web pages from reading the feed even if they learn the token.
+    if headers.contains_key(header::ORIGIN) {
+        return (StatusCode::FORBIDDEN, "browser origins are not allowed").into_response();
+    }
+    let provided = params.token.unwrap_or_default();
+    if !constant_time_eq(provided.as_bytes(), context.token.as_bytes()) {
+        return (StatusCode::UNAUTHORIZED, "invalid or missing token").into_response();
+    }
+
+    let filter = FeedFilter {
+        connection_id: params
+            .connection_id
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty()),
+        topics: params
+            .topics
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+
+    ws.on_upgrade(move |socket| stream(socket, context.feed, filter))
+}
+
+async fn stream(mut socket: WebSocket, feed: FeedManager, filter: FeedFilter) {
+    let mut events = feed.subscribe();
+    feed.inner.subscribers.fetch_add(1, Ordering::Relaxed);
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let frame = match event {
+                    Ok(event) => match filter.apply(&event) {
+                        Some(frame) => frame,
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        json!({ "event": "feed-lagged", "payload": { "skipped": skipped } })
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(frame.to_string().into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => {
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+
+    feed.inner.subscribers.fetch_sub(1, Ordering::Relaxed);
+}
+
+impl FeedFilter {
+    fn apply(&self, event: &FeedEvent) -> Option<serde_json::Value> {
+        match event {
+            FeedEvent::Status(payload) => {
+                if !self.accepts_connection(&payload.connection_id) {
+                    return None;
+                }
+                Some(json!({ "event": "mqtt-status", "payload": payload.as_ref() }))
+            }
+            FeedEvent::Batch(payload) => {
+                if !self.accepts_connection(&payload.connection_id) {
+                    return None;
+                }
+                if self.topics.is_empty() {
+                    return Some(
+                        json!({ "event": "mqtt-message-batch", "payload": payload.as_ref() }),
+                    );
+                }
+
+                let messages: Vec<MqttBatchItem> = payload
+                    .messages
+                    .iter()
+                    .filter(|item| {
+                        self.topics
+                            .iter()
+                            .any(|topic_filter| topic_matches(topic_filter, &item.topic))
+                    })
+                    .cloned()
+                    .collect();
+                if messages.is_empty() {
+                    return None;
+                }
+                let filtered = MqttMessageBatchPayload {
+                    connection_id: payload.connection_id.clone(),
+                    seq: payload.seq,
+                    messages,
+                };
+                Some(json!({ "event": "mqtt-message-batch", "payload": filtered }))
+            }
+        }
+    }
+
+    fn accepts_connection(&self, connection_id: &str) -> bool {
+        self.connection_id
+            .as_deref()
+            .is_none_or(|expected| expected == connection_id)
+    }
+}
@@ -1,18 +1,71 @@
-use crate::models::{HistoryExportResult, HistoryMessageRecord, MessageDirection, MqttBatchItem};
-use crate::mqtt::now_millis;
+use crate::config_store;
+use crate::crash;
+use crate::disk;
+use crate::jobs::{JobManager, JobProgress};
+use crate::models::{
+    ConnectionDiskUsage, ConnectionEventKind, ConnectionEventRecord, CorrelationThread,
+    HistoryCheckpointResult, HistoryDiskUsage, HistoryExportResult, HistoryMessageRecord,
+    HistoryPauseStatus, HistoryQueryChunk, HistoryRepair, HistorySearchHit, HistorySearchQuery,
+    HistorySort, HistoryStreamQuery, HistoryTimeSource, JobKind, MessageDirection, MqttBatchItem,
+    Payload, PayloadSizeBucket, PayloadSizeDistribution, PayloadSizeHistogram, PinnedMessageRecord,
+    TopicFieldStats, TopicListExportOptions, TopicListFormat, TopicPayloadSizes, TopicUnreadCount,
+    UserProperty,
+};
+use crate::mqtt::{now_millis, topic_matches};
+use crate::rules::json_path;
+use crate::state::AppState;
+use crate::xlsx::{self, Cell};
 use anyhow::{Context, Result};
-use dashmap::DashMap;
+use chrono::{DateTime, FixedOffset, Offset, SecondsFormat};
+use dashmap::{DashMap, DashSet};
+use regex::{Regex, RegexBuilder};
+use rusqlite::functions::FunctionFlags;
 use rusqlite::{Connection, OpenFlags, params};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
-use tauri::{AppHandle, Manager};
-use tokio::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::{self, Duration};
 
-const HISTORY_DIR_NAME: &str = "history";
+pub(crate) const HISTORY_DIR_NAME: &str = "history";
 const EXPORTS_DIR_NAME: &str = "exports";
+const DEFAULT_EXPORT_TEMPLATE: &str = "{connectionId}-history-{timestamp}.{ext}";
 const MAX_QUERY_LIMIT: usize = 1000;
+const DEFAULT_SEARCH_LIMIT: usize = 200;
+/// Per-connection databases searched at once by cross-connection queries.
+const MAX_PARALLEL_SEARCHES: usize = 4;
+/// Rows buffered per connection while merging a cross-connection export.
+const MERGE_PAGE_ROWS: usize = 512;
+/// Pages after which SQLite checkpoints on commit; the default of 1000 lags behind floods.
+const WAL_AUTOCHECKPOINT_PAGES: i64 = 2000;
+/// Caps the `-wal` file size left behind after a checkpoint.
+const WAL_JOURNAL_SIZE_LIMIT: i64 = 64 * 1024 * 1024;
+const CHECKPOINT_INTERVAL_SECS: u64 = 300;
+/// Column added by the most recent schema migration; read-only opens migrate when it is missing.
+const LATEST_MESSAGE_COLUMN: &str = "is_binary";
+const MAX_THREAD_MESSAGES: usize = 1000;
+const DEFAULT_STREAM_CHUNK: usize = 500;
+const MAX_STREAM_CHUNK: usize = 5000;
+/// Inclusive upper bounds of the payload size buckets; a last bucket holds everything larger.
+const PAYLOAD_SIZE_BOUNDS: [u64; 8] = [
+    64,
+    256,
+    1024,
+    4 * 1024,
+    16 * 1024,
+    64 * 1024,
+    256 * 1024,
+    1024 * 1024,
+];
+const DEFAULT_SIZE_TOPICS: usize = 50;
+/// Compiled size cap for payload regexes, so a pathological pattern can't stall a query.
+const PAYLOAD_REGEX_SIZE_LIMIT: usize = 1024 * 1024;
 
 #[derive(Clone, Default)]
 pub struct HistoryManager {
@@ -25,6 +78,17 @@ struct HistoryInner {
     root_dir: OnceLock<PathBuf>,
     exports_dir: OnceLock<PathBuf>,
     guards: DashMap<String, Arc<RwLock<()>>>,
+    /// Connections written since their last explicit checkpoint.
+    dirty: DashSet<String>,
+    /// Set by the storage guard while the disk is nearly full.
+    writes_paused: AtomicBool,
+    /// Messages dropped instead of stored while writes were paused.
+    skipped_messages: AtomicU64,
+    /// Connections the user stopped recording, with when that started and how many messages
+    /// went unstored since.
+    paused: DashMap<String, (u64, u64)>,
+    /// Databases moved aside by the start-up integrity scan.
+    repairs: Mutex<Vec<HistoryRepair>>,
 }
 
 impl HistoryManager {
@@ -76,6 +140,13 @@ impl HistoryManager {
         })?;
 
         cleanup_deleting_files(&history_root)?;
+        let repairs = repair_corrupt_databases(&history_root);
+        if !repairs.is_empty() {
+            let _ = app.emit("history-repaired", &repairs);
+            if let Ok(mut recorded) = self.inner.repairs.lock() {
+                recorded.extend(repairs);
+            }
+        }
 
         let _ = self.inner.root_dir.set(history_root.clone());
         let _ = self.inner.exports_dir.set(exports_dir.clone());
@@ -83,6 +154,17 @@ impl HistoryManager {
         Ok((history_root, exports_dir))
     }
 
+    /// Databases the start-up integrity scan replaced this run.
+    pub fn repairs(&self, app: &AppHandle) -> Result<Vec<HistoryRepair>> {
+        self.ensure_paths(app)?;
+        Ok(self
+            .inner
+            .repairs
+            .lock()
+            .map(|repairs| repairs.clone())
+            .unwrap_or_default())
+    }
+
     fn guard_for(&self, connection_id: &str) -> Arc<RwLock<()>> {
         if let Some(existing) = self.inner.guards.get(connection_id) {
             return Arc::clone(existing.value());
@@ -101,6 +183,7 @@ impl HistoryManager {
         root.join(format!("{}.db", safe_connection_id(connection_id)))
     }
 
+    #[tracing::instrument(skip(self, app, messages), fields(count = messages.len()), err)]
     pub async fn append_batch(
         &self,
         app: &AppHandle,
@@ -110,45 +193,70 @@ impl HistoryManager {
         if messages.is_empty() {
             return Ok(());
         }
+        if let Some(mut paused) = self.inner.paused.get_mut(connection_id) {
+            paused.1 += messages.len() as u64;
+            return Ok(());
+        }
+        if self.writes_paused() {
+            self.inner
+                .skipped_messages
+                .fetch_add(messages.len() as u64, Ordering::Relaxed);
+            return Ok(());
+        }
 
         let (root, _) = self.ensure_paths(app)?;
         let db_path = self.db_path(&root, connection_id);
         let guard = self.guard_for(connection_id);
         let to_insert = messages.to_vec();
+        let payload_times = app
+            .state::<AppState>()
+            .payload_timestamps
+            .extract_batch(connection_id, messages);
         let _read_guard = guard.read().await;
 
-        tokio::task::spawn_blocking(move || insert_batch(&db_path, &to_insert))
+        tokio::task::spawn_blocking(move || insert_batch(&db_path, &to_insert, &payload_times))
             .await
             .context("append batch task join failed")??;
+        self.inner.dirty.insert(connection_id.to_string());
 
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn append_outgoing(
         &self,
         app: &AppHandle,
         connection_id: &str,
         topic: &str,
-        payload: &str,
+        payload: Payload,
         qos: u8,
         retain: bool,
+        user_properties: Vec<UserProperty>,
     ) -> Result<()> {
         let item = MqttBatchItem {
             topic: topic.to_string(),
-            payload: payload.to_string(),
+            is_binary: payload.is_binary(),
+            payload,
             qos,
             retain,
             direction: MessageDirection::Out,
             timestamp: now_millis(),
+            dup: false,
+            packet_id: None,
+            correlation_id: None,
+            response_topic: None,
+            user_properties,
         };
         self.append_batch(app, connection_id, &[item]).await
     }
 
+    #[tracing::instrument(skip(self, app), err)]
     pub async fn query_latest(
         &self,
         app: &AppHandle,
         connection_id: &str,
         limit: usize,
+        sort: HistorySort,
     ) -> Result<Vec<HistoryMessageRecord>> {
         let bounded_limit = limit.clamp(1, MAX_QUERY_LIMIT);
         let (root, _) = self.ensure_paths(app)?;
@@ -160,11 +268,12 @@ impl HistoryManager {
         let guard = self.guard_for(connection_id);
         let _read_guard = guard.read().await;
 
-        tokio::task::spawn_blocking(move || query_latest_rows(&db_path, bounded_limit))
+        tokio::task::spawn_blocking(move || query_latest_rows(&db_path, bounded_limit, sort))
             .await
             .context("query latest task join failed")?
     }
 
+    #[tracing::instrument(skip(self, app), err)]
     pub async fn query_before(
         &self,
         app: &AppHandle,
@@ -172,6 +281,7 @@ impl HistoryManager {
         before_ts: u64,
         before_id: i64,
         limit: usize,
+        sort: HistorySort,
     ) -> Result<Vec<HistoryMessageRecord>> {
         let bounded_limit = limit.clamp(1, MAX_QUERY_LIMIT);
         let (root, _) = self.ensure_paths(app)?;
@@ -184,235 +294,2144 @@ impl HistoryManager {
         let _read_guard = guard.read().await;
 
         tokio::task::spawn_blocking(move || {
-            query_before_rows(&db_path, before_ts as i64, before_id, bounded_limit)
+            query_before_rows(&db_path, before_ts as i64, before_id, bounded_limit, sort)
         })
         .await
         .context("query before task join failed")?
     }
 
-    pub async fn clear_connection(&self, app: &AppHandle, connection_id: &str) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn query_range(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+        limit: usize,
+        time_source: HistoryTimeSource,
+        sort: HistorySort,
+    ) -> Result<Vec<HistoryMessageRecord>> {
+        let bounded_limit = limit.clamp(1, MAX_QUERY_LIMIT);
         let (root, _) = self.ensure_paths(app)?;
         let db_path = self.db_path(&root, connection_id);
-        let guard = self.guard_for(connection_id);
-        let _write_guard = guard.write().await;
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
 
-        tokio::task::spawn_blocking(move || clear_db_file(&db_path))
-            .await
-            .context("clear history task join failed")??;
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
 
-        Ok(())
+        tokio::task::spawn_blocking(move || {
+            query_range_rows(
+                &db_path,
+                from_ts.map(|v| v as i64),
+                to_ts.map(|v| v as i64),
+                bounded_limit,
+                time_source,
+                sort,
+            )
+        })
+        .await
+        .context("query range task join failed")?
     }
 
-    pub async fn delete_connection(&self, app: &AppHandle, connection_id: &str) -> Result<()> {
+    pub async fn append_event(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        kind: ConnectionEventKind,
+        detail: Option<String>,
+    ) -> Result<()> {
+        if self.writes_paused() {
+            return Ok(());
+        }
         let (root, _) = self.ensure_paths(app)?;
         let db_path = self.db_path(&root, connection_id);
         let guard = self.guard_for(connection_id);
-        let _write_guard = guard.write().await;
+        let _read_guard = guard.read().await;
+        let timestamp = now_millis();
 
-        tokio::task::spawn_blocking(move || delete_db_file(&db_path))
+        tokio::task::spawn_blocking(move || insert_event(&db_path, timestamp, kind, detail))
             .await
-            .context("delete history task join failed")??;
-
-        self.inner.guards.remove(connection_id);
+            .context("append event task join failed")??;
 
         Ok(())
     }
 
-    pub async fn export_connection(
+    /// Returns lifecycle events newest first.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn query_events(
         &self,
         app: &AppHandle,
         connection_id: &str,
-        format: &str,
         from_ts: Option<u64>,
         to_ts: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<ConnectionEventRecord>> {
+        let bounded_limit = limit.clamp(1, MAX_QUERY_LIMIT);
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || {
+            query_event_rows(
+                &db_path,
+                from_ts.map(|v| v as i64),
+                to_ts.map(|v| v as i64),
+                bounded_limit,
+            )
+        })
+        .await
+        .context("query events task join failed")?
+    }
+
+    #[tracing::instrument(skip(self, app, progress), err)]
+    pub async fn export_events(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        format: &str,
         output_path: Option<&str>,
+        progress: &JobProgress,
     ) -> Result<HistoryExportResult> {
         let (root, exports_dir) = self.ensure_paths(app)?;
         let db_path = self.db_path(&root, connection_id);
         if !db_path.exists() {
-            return Err(anyhow::anyhow!("no history found for this connection"));
+            return Err(anyhow::anyhow!("no events found for this connection"));
         }
 
         let guard = self.guard_for(connection_id);
         let _read_guard = guard.read().await;
 
-        let safe_id = safe_connection_id(connection_id);
-        let ext = if format.eq_ignore_ascii_case("csv") {
-            "csv"
-        } else {
-            "ndjson"
-        };
+        let is_csv = format.eq_ignore_ascii_case("csv");
+        let ext = if is_csv { "csv" } else { "ndjson" };
         let output_path = if let Some(user_path) = output_path {
             normalize_output_path(PathBuf::from(user_path), ext)
         } else {
-            exports_dir.join(format!("{safe_id}-history-{}.{}", now_millis(), ext))
+            exports_dir.join(format!(
+                "{}-events-{}.{}",
+                safe_connection_id(connection_id),
+                now_millis(),
+                ext
+            ))
         };
-        let format_owned = format.to_string();
 
-        tokio::task::spawn_blocking(move || {
-            export_rows(
-                &db_path,
-                &output_path,
-                &format_owned,
-                from_ts.map(|v| v as i64),
-                to_ts.map(|v| v as i64),
-            )
-        })
+        run_export(
+            output_path,
+            progress,
+            "export events",
+            move |path, progress| export_event_rows(&db_path, path, is_csv, progress),
+        )
         .await
-        .context("export history task join failed")?
     }
-}
 
-fn insert_batch(path: &Path, rows: &[MqttBatchItem]) -> Result<()> {
-    let mut conn = open_rw_connection(path)?;
-    let tx = conn
-        .transaction()
-        .context("failed to start history transaction")?;
-    let mut stmt = tx
-        .prepare(
-            "INSERT INTO message_history (ts_ms, topic, payload, qos, retain, direction)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        )
-        .context("failed to prepare history insert statement")?;
+    /// Returns the most recent record of every topic under `prefix`, which is how retained
+    /// state (discovery configs, catalog samples) is reconstructed from history.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn latest_per_topic(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        prefix: &str,
+    ) -> Result<Vec<HistoryMessageRecord>> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
 
-    for row in rows {
-        stmt.execute(params![
-            row.timestamp as i64,
-            row.topic,
-            row.payload,
-            row.qos as i64,
-            if row.retain { 1 } else { 0 },
-            direction_to_int(row.direction),
-        ])
-        .context("failed to insert history row")?;
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+        let prefix = prefix.to_string();
+
+        tokio::task::spawn_blocking(move || latest_per_topic_rows(&db_path, &prefix))
+            .await
+            .context("latest per topic task join failed")?
     }
 
-    drop(stmt);
-    tx.commit()
-        .context("failed to commit history transaction")?;
-    Ok(())
-}
+    /// Returns the last received message of every topic as of `at` (inclusive), optionally only
+    /// those delivered with the retain flag.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn latest_state(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        at: Option<u64>,
+        retained_only: bool,
+    ) -> Result<Vec<HistoryMessageRecord>> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
 
-fn query_latest_rows(path: &Path, limit: usize) -> Result<Vec<HistoryMessageRecord>> {
-    let conn = open_ro_connection(path)?;
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, ts_ms, topic, payload, qos, retain, direction
-             FROM message_history
-             ORDER BY ts_ms DESC, id DESC
-             LIMIT ?1",
-        )
-        .context("failed to prepare latest history query")?;
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+        let at = at.map(|value| value as i64).unwrap_or(i64::MAX);
 
-    let mut rows = stmt
-        .query_map([limit as i64], row_to_record)
-        .context("failed to execute latest history query")?
-        .collect::<rusqlite::Result<Vec<_>>>()
-        .context("failed to map latest history rows")?;
+        tokio::task::spawn_blocking(move || latest_state_rows(&db_path, at, retained_only))
+            .await
+            .context("latest state task join failed")?
+    }
 
-    rows.reverse();
-    Ok(rows)
-}
+    /// Pins a stored message so it is returned by [`Self::query_pinned`]; re-pinning updates the note.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn pin(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        message_id: i64,
+        note: Option<String>,
+    ) -> Result<()> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
 
-fn query_before_rows(
-    path: &Path,
-    before_ts: i64,
-    before_id: i64,
-    limit: usize,
-) -> Result<Vec<HistoryMessageRecord>> {
-    let conn = open_ro_connection(path)?;
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, ts_ms, topic, payload, qos, retain, direction
-             FROM message_history
-             WHERE (ts_ms < ?1) OR (ts_ms = ?1 AND id < ?2)
-             ORDER BY ts_ms DESC, id DESC
-             LIMIT ?3",
-        )
-        .context("failed to prepare paged history query")?;
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || pin_row(&db_path, message_id, note))
+            .await
+            .context("pin message task join failed")?
+    }
+
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn unpin(&self, app: &AppHandle, connection_id: &str, message_id: i64) -> Result<()> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(());
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || unpin_row(&db_path, message_id))
+            .await
+            .context("unpin message task join failed")?
+    }
+
+    /// Pinned messages of a connection, most recently pinned first.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn query_pinned(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+    ) -> Result<Vec<PinnedMessageRecord>> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || query_pinned_rows(&db_path))
+            .await
+            .context("query pinned task join failed")?
+    }
+
+    /// Incoming messages received after each topic's read marker, most recently active topic
+    /// first. Topics never marked read count their whole history.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn query_unread(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        topic_filter: Option<String>,
+    ) -> Result<Vec<TopicUnreadCount>> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || query_unread_rows(&db_path, topic_filter.as_deref()))
+            .await
+            .context("query unread task join failed")?
+    }
+
+    /// Moves the read marker of every topic matching `topic_filter` (all topics when `None`) to
+    /// its latest message. Returns how many topics were marked.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn mark_read(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        topic_filter: Option<String>,
+    ) -> Result<u64> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(0);
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || mark_read_rows(&db_path, topic_filter.as_deref()))
+            .await
+            .context("mark read task join failed")?
+    }
+
+    /// Records with the given ids, oldest first. Ids that no longer exist are skipped.
+    #[tracing::instrument(skip(self, app, ids), fields(count = ids.len()), err)]
+    pub async fn query_by_ids(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        ids: Vec<i64>,
+    ) -> Result<Vec<HistoryMessageRecord>> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || query_id_rows(&db_path, &ids))
+            .await
+            .context("query history ids task join failed")?
+    }
+
+    /// Runs a `TRUNCATE` checkpoint so the connection's `-wal` file is folded back and emptied.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn checkpoint(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+    ) -> Result<HistoryCheckpointResult> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _write_guard = guard.write().await;
+        self.inner.dirty.remove(connection_id);
+
+        let (busy, log_frames, checkpointed_frames) =
+            tokio::task::spawn_blocking(move || checkpoint_db_file(&db_path))
+                .await
+                .context("checkpoint task join failed")??;
+
+        Ok(HistoryCheckpointResult {
+            connection_id: connection_id.to_string(),
+            busy,
+            log_frames,
+            checkpointed_frames,
+        })
+    }
+
+    /// Periodically checkpoints every connection that has been written to since the last pass.
+    pub fn start_checkpointer(&self, app: AppHandle) {
+        let manager = self.clone();
+        tokio::spawn(crash::monitored("history-checkpointer", async move {
+            let mut interval = time::interval(Duration::from_secs(CHECKPOINT_INTERVAL_SECS));
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let dirty = manager
+                    .inner
+                    .dirty
+                    .iter()
+                    .map(|entry| entry.key().clone())
+                    .collect::<Vec<_>>();
+                for connection_id in dirty {
+                    if let Err(error) = manager.checkpoint(&app, &connection_id).await {
+                        tracing::warn!("history checkpoint failed for {connection_id}: {error:#}");
+                    }
+                }
+            }
+        }));
+    }
+
+    pub fn set_writes_paused(&self, paused: bool) {
+        self.inner.writes_paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn writes_paused(&self) -> bool {
+        self.inner.writes_paused.load(Ordering::Relaxed)
+    }
+
+    pub fn skipped_messages(&self) -> u64 {
+        self.inner.skipped_messages.load(Ordering::Relaxed)
+    }
+
+    /// Stops storing messages for `connection_id`; they still reach the live view, rules and
+    /// bridges. Pausing an already paused connection keeps its counters.
+    pub fn pause(&self, connection_id: &str) -> HistoryPauseStatus {
+        self.inner
+            .paused
+            .entry(connection_id.to_string())
+            .or_insert((now_millis(), 0));
+        self.pause_status(connection_id)
+    }
+
+    pub fn resume(&self, connection_id: &str) -> HistoryPauseStatus {
+        let skipped_messages = self
+            .inner
+            .paused
+            .remove(connection_id)
+            .map_or(0, |(_, (_, skipped))| skipped);
+        HistoryPauseStatus {
+            skipped_messages,
+            ..pause_status(connection_id, None)
+        }
+    }
+
+    pub fn pause_status(&self, connection_id: &str) -> HistoryPauseStatus {
+        pause_status(
+            connection_id,
+            self.inner.paused.get(connection_id).map(|entry| *entry),
+        )
+    }
+
+    pub fn paused_connections(&self) -> Vec<HistoryPauseStatus> {
+        self.inner
+            .paused
+            .iter()
+            .map(|entry| pause_status(entry.key(), Some(*entry.value())))
+            .collect()
+    }
+
+    /// Deletes the oldest `percent` of each connection's unpinned messages, skipping databases
+    /// that already have that much free space inside them. The freed pages are reused by later
+    /// inserts, so the files stop growing rather than shrink. Returns the rows removed.
+    #[tracing::instrument(skip(self, app, connection_ids), err)]
+    pub async fn prune_oldest(
+        &self,
+        app: &AppHandle,
+        connection_ids: &[String],
+        percent: u8,
+    ) -> Result<u64> {
+        let (root, _) = self.ensure_paths(app)?;
+        let mut removed = 0;
+        for connection_id in connection_ids {
+            let db_path = self.db_path(&root, connection_id);
+            if !db_path.exists() {
+                continue;
+            }
+            let guard = self.guard_for(connection_id);
+            let _write_guard = guard.write().await;
+            removed += tokio::task::spawn_blocking(move || prune_db_file(&db_path, percent))
+                .await
+                .context("prune history task join failed")??;
+        }
+        Ok(removed)
+    }
+
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn clear_connection(&self, app: &AppHandle, connection_id: &str) -> Result<()> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        let guard = self.guard_for(connection_id);
+        let _write_guard = guard.write().await;
+
+        tokio::task::spawn_blocking(move || clear_db_file(&db_path))
+            .await
+            .context("clear history task join failed")??;
+
+        Ok(())
+    }
+
+    /// Disk usage of every history database, matched to `profiles` (id, name) where possible.
+    #[tracing::instrument(skip(self, app, profiles), err)]
+    pub async fn disk_usage(
+        &self,
+        app: &AppHandle,
+        profiles: Vec<(String, String)>,
+    ) -> Result<HistoryDiskUsage> {
+        let (root, exports_dir) = self.ensure_paths(app)?;
+        let mut usage =
+            tokio::task::spawn_blocking(move || measure_disk_usage(&root, &exports_dir, profiles))
+                .await
+                .context("history disk usage task join failed")??;
+        for connection in &mut usage.connections {
+            connection.paused = self.inner.paused.contains_key(&connection.connection_id);
+        }
+        Ok(usage)
+    }
+
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn delete_connection(&self, app: &AppHandle, connection_id: &str) -> Result<()> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        let guard = self.guard_for(connection_id);
+        let _write_guard = guard.write().await;
+
+        tokio::task::spawn_blocking(move || delete_db_file(&db_path))
+            .await
+            .context("delete history task join failed")??;
+
+        self.inner.guards.remove(connection_id);
+        self.inner.dirty.remove(connection_id);
+        self.inner.paused.remove(connection_id);
+
+        Ok(())
+    }
+
+    /// Deletes every history database and everything under the exports directory, holding the
+    /// write guard of each connection seen this run while its file goes.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn wipe_all(&self, app: &AppHandle) -> Result<HistoryWipe> {
+        let (root, exports_dir) = self.ensure_paths(app)?;
+        let guards = self
+            .inner
+            .guards
+            .iter()
+            .map(|entry| Arc::clone(entry.value()))
+            .collect::<Vec<_>>();
+        let mut write_guards = Vec::with_capacity(guards.len());
+        for guard in guards {
+            write_guards.push(guard.write_owned().await);
+        }
+
+        let wiped = tokio::task::spawn_blocking(move || wipe_history_files(&root, &exports_dir))
+            .await
+            .context("wipe history task join failed")??;
+
+        self.inner.dirty.clear();
+        drop(write_guards);
+        Ok(wiped)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip(self, app, progress), err)]
+    pub async fn export_connection(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        format: &str,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+        time_zone: Option<ExportTimeZone>,
+        payload_regex: Option<&str>,
+        output_path: Option<&str>,
+        progress: &JobProgress,
+    ) -> Result<HistoryExportResult> {
+        let payload_regex = self::payload_regex(payload_regex)?;
+        let (root, exports_dir) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        let ext = export_extension(format);
+        let output_path = if let Some(user_path) = output_path {
+            normalize_output_path(PathBuf::from(user_path), ext)
+        } else {
+            default_export_path(app, &exports_dir, connection_id, from_ts, to_ts, ext)?
+        };
+        run_export(
+            output_path,
+            progress,
+            "export history",
+            move |path, progress| {
+                export_rows(
+                    &db_path,
+                    path,
+                    ext,
+                    from_ts.map(|v| v as i64),
+                    to_ts.map(|v| v as i64),
+                    time_zone,
+                    payload_regex.as_deref(),
+                    progress,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Exports rows through a caller-supplied line formatter; rows mapped to `None` are skipped.
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip(self, app, format_line, progress), err)]
+    pub async fn export_lines<F>(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        ext: &str,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+        output_path: Option<&str>,
+        progress: &JobProgress,
+        format_line: F,
+    ) -> Result<HistoryExportResult>
+    where
+        F: FnMut(&HistoryMessageRecord) -> Option<String> + Send + 'static,
+    {
+        let (root, exports_dir) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        let output_path = if let Some(user_path) = output_path {
+            normalize_output_path(PathBuf::from(user_path), ext)
+        } else {
+            default_export_path(app, &exports_dir, connection_id, from_ts, to_ts, ext)?
+        };
+
+        run_export(
+            output_path,
+            progress,
+            "export history",
+            move |path, progress| {
+                export_mapped_rows(
+                    &db_path,
+                    path,
+                    from_ts.map(|v| v as i64),
+                    to_ts.map(|v| v as i64),
+                    format_line,
+                    progress,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Writes the distinct topics seen in range, ordered by name, as a JSON array or CSV.
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip(self, app, progress), err)]
+    pub async fn export_topics(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        options: TopicListExportOptions,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+        output_path: Option<&str>,
+        progress: &JobProgress,
+    ) -> Result<HistoryExportResult> {
+        let (root, exports_dir) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        let ext = match options.format {
+            TopicListFormat::Json => "json",
+            TopicListFormat::Csv => "csv",
+        };
+        let output_path = if let Some(user_path) = output_path {
+            normalize_output_path(PathBuf::from(user_path), ext)
+        } else {
+            default_export_path(app, &exports_dir, connection_id, from_ts, to_ts, ext)?
+        };
+        run_export(
+            output_path,
+            progress,
+            "export topic list",
+            move |path, progress| {
+                export_topic_rows(
+                    &db_path,
+                    path,
+                    &options,
+                    from_ts.map(|v| v as i64),
+                    to_ts.map(|v| v as i64),
+                    progress,
+                )
+            },
+        )
+        .await
+    }
+
+    /// Searches several connections in parallel (at most `MAX_PARALLEL_SEARCHES` databases
+    /// open at once) and returns the newest matches across all of them.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn search_all(
+        &self,
+        app: &AppHandle,
+        connection_ids: &[String],
+        query: HistorySearchQuery,
+    ) -> Result<Vec<HistorySearchHit>> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_QUERY_LIMIT);
+        let mut query = query;
+        query.payload_regex = payload_regex(query.payload_regex.as_deref())?;
+        let (root, _) = self.ensure_paths(app)?;
+        let permits = Arc::new(Semaphore::new(MAX_PARALLEL_SEARCHES));
+        let query = Arc::new(query);
+
+        let mut tasks = JoinSet::new();
+        for connection_id in connection_ids {
+            let db_path = self.db_path(&root, connection_id);
+            if !db_path.exists() {
+                continue;
+            }
+            let guard = self.guard_for(connection_id);
+            let permits = Arc::clone(&permits);
+            let query = Arc::clone(&query);
+            let connection_id = connection_id.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await?;
+                let _read_guard = guard.read_owned().await;
+                tokio::task::spawn_blocking(move || {
+                    search_rows(&db_path, &connection_id, &query, limit)
+                })
+                .await
+                .context("history search task join failed")?
+            });
+        }
+
+        let mut hits: Vec<HistorySearchHit> = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            hits.extend(result.context("history search task join failed")??);
+            // Each worker returns at most `limit` rows; trim as we go so memory stays bounded.
+            sort_hits(
+                &mut hits,
+                query.sort.unwrap_or(HistorySort::TimestampDesc),
+                query.time_source,
+            );
+            hits.truncate(limit);
+        }
+        Ok(hits)
+    }
+
+    /// Streams every connection's history into one file ordered by timestamp, paging through
+    /// each database so only `MERGE_PAGE_ROWS` rows per connection are held in memory.
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip(self, app, progress), err)]
+    pub async fn export_all(
+        &self,
+        app: &AppHandle,
+        connection_ids: &[String],
+        format: &str,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+        time_zone: Option<ExportTimeZone>,
+        output_path: Option<&str>,
+        progress: &JobProgress,
+    ) -> Result<HistoryExportResult> {
+        let (root, exports_dir) = self.ensure_paths(app)?;
+
+        let mut sources = Vec::new();
+        let mut read_guards = Vec::new();
+        for connection_id in connection_ids {
+            let db_path = self.db_path(&root, connection_id);
+            if !db_path.exists() {
+                continue;
+            }
+            read_guards.push(self.guard_for(connection_id).read_owned().await);
+            sources.push((connection_id.clone(), db_path));
+        }
+        if sources.is_empty() {
+            return Err(anyhow::anyhow!("no history found for these connections"));
+        }
+
+        let ext = export_extension(format);
+        let output_path = if let Some(user_path) = output_path {
+            normalize_output_path(PathBuf::from(user_path), ext)
+        } else {
+            exports_dir.join(format!("all-history-{}.{}", now_millis(), ext))
+        };
+        let result = run_export(
+            output_path,
+            progress,
+            "export all history",
+            move |path, progress| {
+                export_merged_rows(
+                    sources,
+                    path,
+                    ext,
+                    from_ts.map(|v| v as i64),
+                    to_ts.map(|v| v as i64),
+                    time_zone,
+                    progress,
+                )
+            },
+        )
+        .await;
+        drop(read_guards);
+        result
+    }
+
+    /// Runs a range query as a background job, emitting `history-query-chunk` events to the
+    /// `target` webview, and returns the job id that tags every chunk.
+    #[tracing::instrument(skip(self, app, jobs), err)]
+    pub async fn start_stream_query(
+        &self,
+        app: &AppHandle,
+        jobs: &JobManager,
+        target: String,
+        connection_id: &str,
+        mut query: HistoryStreamQuery,
+    ) -> Result<String> {
+        query.payload_regex = payload_regex(query.payload_regex.as_deref())?;
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Err(anyhow::anyhow!("no history found for this connection"));
+        }
+
+        let guard = self.guard_for(connection_id);
+        let app = app.clone();
+        let connection_id = connection_id.to_string();
+        let label = format!("History query: {connection_id}");
+        let job_app = app.clone();
+        Ok(jobs.start(
+            &app,
+            JobKind::HistoryQuery,
+            label,
+            move |progress| async move {
+                let app = job_app;
+                let _read_guard = guard.read_owned().await;
+                let job_id = progress.job_id().to_string();
+                let chunk = move |seq: u64, records: Vec<HistoryMessageRecord>| HistoryQueryChunk {
+                    job_id: job_id.clone(),
+                    connection_id: connection_id.clone(),
+                    seq,
+                    records,
+                    done: false,
+                    cancelled: false,
+                    error: None,
+                };
+
+                let emit_app = app.clone();
+                let emit_target = target.clone();
+                let emit_chunk = chunk.clone();
+                let stream_progress = progress.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    stream_range_rows(&db_path, &query, &stream_progress, |seq, records| {
+                        let _ = emit_app.emit_to(
+                            emit_target.as_str(),
+                            "history-query-chunk",
+                            emit_chunk(seq, records),
+                        );
+                    })
+                })
+                .await
+                .context("history query stream task join failed")
+                .and_then(|result| result);
+
+                let mut last = chunk(0, Vec::new());
+                last.done = true;
+                last.cancelled = progress.is_cancelled();
+                match &result {
+                    Ok(seq) => last.seq = *seq,
+                    Err(error) => last.error = Some(format!("{error:#}")),
+                }
+                let _ = app.emit_to(target.as_str(), "history-query-chunk", last);
+                progress.checkpoint()?;
+                result
+            },
+        ))
+    }
+
+    /// Count/min/max/mean/last of a numeric JSON field for a topic (or topic filter) and range.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn topic_field_stats(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        topic: &str,
+        path: &str,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+    ) -> Result<TopicFieldStats> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        if !db_path.exists() {
+            return Ok(TopicFieldStats::default());
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+        let topic = topic.to_string();
+        let path = path.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            topic_field_stats_rows(
+                &db_path,
+                &topic,
+                &path,
+                from_ts.map(|v| v as i64),
+                to_ts.map(|v| v as i64),
+            )
+        })
+        .await
+        .context("topic stats task join failed")?
+    }
+
+    /// Payload size distribution over a range, overall and per topic (optionally limited to a
+    /// topic or filter), with the topics carrying the largest payloads first.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn payload_size_histogram(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        topic: Option<String>,
+        from_ts: Option<u64>,
+        to_ts: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<PayloadSizeHistogram> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        let limit = limit
+            .unwrap_or(DEFAULT_SIZE_TOPICS)
+            .clamp(1, MAX_QUERY_LIMIT);
+        if !db_path.exists() {
+            return Ok(PayloadSizeHistogram {
+                overall: empty_size_distribution(),
+                topics: Vec::new(),
+                truncated: false,
+            });
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+
+        tokio::task::spawn_blocking(move || {
+            payload_size_rows(
+                &db_path,
+                topic.as_deref(),
+                from_ts.map(|v| v as i64),
+                to_ts.map(|v| v as i64),
+                limit,
+            )
+        })
+        .await
+        .context("payload size task join failed")?
+    }
+
+    /// Assembles the request/response exchange sharing one v5 correlation id, oldest first.
+    #[tracing::instrument(skip(self, app), err)]
+    pub async fn correlation_thread(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        correlation_id: &str,
+    ) -> Result<CorrelationThread> {
+        let (root, _) = self.ensure_paths(app)?;
+        let db_path = self.db_path(&root, connection_id);
+        let mut thread = CorrelationThread {
+            correlation_id: correlation_id.to_string(),
+            ..CorrelationThread::default()
+        };
+        if !db_path.exists() {
+            return Ok(thread);
+        }
+
+        let guard = self.guard_for(connection_id);
+        let _read_guard = guard.read().await;
+        let id = correlation_id.to_string();
+        let messages = tokio::task::spawn_blocking(move || correlation_rows(&db_path, &id))
+            .await
+            .context("correlation thread task join failed")??;
+
+        // The request is the first message that names a response topic; replies arrive there.
+        thread.request_topic = messages
+            .iter()
+            .find(|record| record.response_topic.is_some())
+            .or(messages.first())
+            .map(|record| record.topic.clone());
+        thread.response_topic = messages
+            .iter()
+            .find_map(|record| record.response_topic.clone());
+        thread.started_at = messages.first().map(|record| record.timestamp);
+        thread.finished_at = messages.last().map(|record| record.timestamp);
+        thread.messages = messages;
+        Ok(thread)
+    }
+}
+
+fn insert_batch(path: &Path, rows: &[MqttBatchItem], payload_times: &[Option<u64>]) -> Result<()> {
+    let mut conn = open_rw_connection(path)?;
+    let tx = conn
+        .transaction()
+        .context("failed to start history transaction")?;
+    let mut stmt = tx
+        .prepare(
+            "INSERT INTO message_history
+                 (ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                  correlation_id, response_topic, payload_ts_ms, user_properties, is_binary)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        )
+        .context("failed to prepare history insert statement")?;
+
+    for (row, payload_time) in rows.iter().zip(payload_times) {
+        stmt.execute(params![
+            row.timestamp as i64,
+            row.topic,
+            row.payload.as_str(),
+            row.qos as i64,
+            if row.retain { 1 } else { 0 },
+            direction_to_int(row.direction),
+            if row.dup { 1 } else { 0 },
+            row.packet_id.map(i64::from),
+            row.correlation_id,
+            row.response_topic,
+            payload_time.map(|ts| ts as i64),
+            (!row.user_properties.is_empty())
+                .then(|| serde_json::to_string(&row.user_properties))
+                .transpose()?,
+            if row.is_binary { 1 } else { 0 },
+        ])
+        .context("failed to insert history row")?;
+    }
+
+    drop(stmt);
+    tx.commit()
+        .context("failed to commit history transaction")?;
+    Ok(())
+}
+
+fn query_latest_rows(
+    path: &Path,
+    limit: usize,
+    sort: HistorySort,
+) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let mut stmt = conn
+        .prepare(&sorted_page(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             ORDER BY ts_ms DESC, id DESC
+             LIMIT ?1",
+            sort,
+        ))
+        .context("failed to prepare latest history query")?;
+
+    stmt.query_map([limit as i64], row_to_record)
+        .context("failed to execute latest history query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map latest history rows")
+}
+
+fn pin_row(path: &Path, message_id: i64, note: Option<String>) -> Result<()> {
+    let conn = open_rw_connection(path)?;
+    let inserted = conn
+        .execute(
+            "INSERT INTO pinned_messages (message_id, pinned_at, note)
+             SELECT id, ?2, ?3 FROM message_history WHERE id = ?1
+             ON CONFLICT(message_id) DO UPDATE SET note = excluded.note",
+            params![message_id, now_millis() as i64, note],
+        )
+        .context("failed to pin message")?;
+    if inserted == 0 {
+        return Err(anyhow::anyhow!("message {message_id} not found in history"));
+    }
+    Ok(())
+}
+
+fn unpin_row(path: &Path, message_id: i64) -> Result<()> {
+    let conn = open_rw_connection(path)?;
+    conn.execute(
+        "DELETE FROM pinned_messages WHERE message_id = ?1",
+        params![message_id],
+    )
+    .context("failed to unpin message")?;
+    Ok(())
+}
+
+fn query_unread_rows(path: &Path, topic_filter: Option<&str>) -> Result<Vec<TopicUnreadCount>> {
+    // Read-write so databases created before read markers existed get the side table first.
+    let conn = open_rw_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.topic, COUNT(*), MAX(m.ts_ms), r.read_at
+             FROM message_history m
+             LEFT JOIN read_markers r ON r.topic = m.topic
+             WHERE m.direction = 0 AND m.id > COALESCE(r.last_read_id, 0)
+             GROUP BY m.topic
+             ORDER BY MAX(m.ts_ms) DESC, m.topic ASC",
+        )
+        .context("failed to prepare unread query")?;
+    let mut rows = stmt.query([]).context("failed to execute unread query")?;
+
+    let mut counts = Vec::new();
+    while let Some(row) = rows.next().context("failed to iterate unread rows")? {
+        let topic: String = row.get(0)?;
+        if topic_filter.is_some_and(|filter| !topic_matches(filter, &topic)) {
+            continue;
+        }
+        counts.push(TopicUnreadCount {
+            topic,
+            unread: row.get::<_, i64>(1)? as u64,
+            last_message_at: row.get::<_, i64>(2)? as u64,
+            last_read_at: row.get::<_, Option<i64>>(3)?.map(|ts| ts as u64),
+        });
+    }
+    Ok(counts)
+}
+
+fn mark_read_rows(path: &Path, topic_filter: Option<&str>) -> Result<u64> {
+    let mut conn = open_rw_connection(path)?;
+    let tx = conn
+        .transaction()
+        .context("failed to start read marker transaction")?;
+    let latest = {
+        let mut stmt = tx
+            .prepare("SELECT topic, MAX(id) FROM message_history GROUP BY topic")
+            .context("failed to prepare latest message query")?;
+        stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .context("failed to execute latest message query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map latest message rows")?
+    };
+
+    let read_at = now_millis() as i64;
+    let mut marked = 0;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO read_markers (topic, last_read_id, read_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(topic) DO UPDATE SET
+                     last_read_id = MAX(last_read_id, excluded.last_read_id),
+                     read_at = excluded.read_at",
+            )
+            .context("failed to prepare read marker statement")?;
+        for (topic, last_id) in latest {
+            if topic_filter.is_some_and(|filter| !topic_matches(filter, &topic)) {
+                continue;
+            }
+            stmt.execute(params![topic, last_id, read_at])
+                .context("failed to update read marker")?;
+            marked += 1;
+        }
+    }
+    tx.commit().context("failed to commit read markers")?;
+    Ok(marked)
+}
+
+fn query_pinned_rows(path: &Path) -> Result<Vec<PinnedMessageRecord>> {
+    // Read-write so databases created before pinning existed get the side table first.
+    let conn = open_rw_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.ts_ms, m.topic, m.payload, m.qos, m.retain, m.direction, m.dup,
+                    m.packet_id, m.correlation_id, m.response_topic, m.payload_ts_ms,
+                    m.user_properties, m.is_binary, p.pinned_at, p.note
+             FROM pinned_messages p
+             JOIN message_history m ON m.id = p.message_id
+             ORDER BY p.pinned_at DESC, m.id DESC",
+        )
+        .context("failed to prepare pinned history query")?;
+
+    stmt.query_map([], |row| {
+        Ok(PinnedMessageRecord {
+            record: row_to_record(row)?,
+            pinned_at: row.get::<_, i64>(14)? as u64,
+            note: row.get(15)?,
+        })
+    })
+    .context("failed to execute pinned history query")?
+    .collect::<rusqlite::Result<Vec<_>>>()
+    .context("failed to map pinned history rows")
+}
+
+fn query_id_rows(path: &Path, ids: &[i64]) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE id = ?1",
+        )
+        .context("failed to prepare history id query")?;
+
+    let mut records = Vec::with_capacity(ids.len());
+    for id in ids {
+        let mut rows = stmt
+            .query(params![id])
+            .context("failed to execute history id query")?;
+        if let Some(row) = rows.next().context("failed to read history id row")? {
+            records.push(row_to_record(row).context("failed to parse history id row")?);
+        }
+    }
+    records.sort_by_key(|record| (record.timestamp, record.id));
+    records.dedup_by_key(|record| record.id);
+    Ok(records)
+}
+
+fn query_before_rows(
+    path: &Path,
+    before_ts: i64,
+    before_id: i64,
+    limit: usize,
+    sort: HistorySort,
+) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let mut stmt = conn
+        .prepare(&sorted_page(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE (ts_ms < ?1) OR (ts_ms = ?1 AND id < ?2)
+             ORDER BY ts_ms DESC, id DESC
+             LIMIT ?3",
+            sort,
+        ))
+        .context("failed to prepare paged history query")?;
+
+    stmt.query_map(params![before_ts, before_id, limit as i64], row_to_record)
+        .context("failed to execute paged history query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map paged history rows")
+}
+
+/// Orders the rows of a newest-first page query by `sort`. The page still holds the newest
+/// rows before the cursor, so paging by timestamp and id works whatever the sort.
+fn sorted_page(page_sql: &str, sort: HistorySort) -> String {
+    format!(
+        "SELECT * FROM ({page_sql}) ORDER BY {}",
+        order_by(sort, "ts_ms")
+    )
+}
+
+fn query_range_rows(
+    path: &Path,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    limit: usize,
+    time_source: HistoryTimeSource,
+    sort: HistorySort,
+) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let ts = time_column(time_source);
+    let order = order_by(sort, ts);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE (?1 IS NULL OR {ts} >= ?1)
+               AND (?2 IS NULL OR {ts} <= ?2)
+             ORDER BY {order}
+             LIMIT ?3"
+        ))
+        .context("failed to prepare ranged history query")?;
+
+    stmt.query_map(params![from_ts, to_ts, limit as i64], row_to_record)
+        .context("failed to execute ranged history query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map ranged history rows")
+}
+
+/// Emits rows in the query's sort order in chunks; returns the sequence number for the final event.
+fn stream_range_rows<F>(
+    path: &Path,
+    query: &HistoryStreamQuery,
+    progress: &JobProgress,
+    mut emit: F,
+) -> Result<u64>
+where
+    F: FnMut(u64, Vec<HistoryMessageRecord>),
+{
+    let chunk_size = query
+        .chunk_size
+        .unwrap_or(DEFAULT_STREAM_CHUNK)
+        .clamp(1, MAX_STREAM_CHUNK);
+    let topic_filter = query
+        .topic_filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty());
+
+    let conn = open_ro_connection(path)?;
+    let ts = time_column(query.time_source);
+    let order = order_by(query.sort, ts);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE (?1 IS NULL OR {ts} >= ?1)
+               AND (?2 IS NULL OR {ts} <= ?2)
+               AND (?3 IS NULL OR payload REGEXP ?3)
+             ORDER BY {order}"
+        ))
+        .context("failed to prepare streamed history query")?;
+    let mut rows = stmt
+        .query(params![
+            query.from_ts.map(|v| v as i64),
+            query.to_ts.map(|v| v as i64),
+            query.payload_regex
+        ])
+        .context("failed to execute streamed history query")?;
+
+    let mut seq = 0;
+    let mut chunk = Vec::with_capacity(chunk_size);
+    while let Some(row) = rows
+        .next()
+        .context("failed to iterate streamed history rows")?
+    {
+        let record = row_to_record(row).context("failed to parse streamed history row")?;
+        if topic_filter.is_some_and(|filter| !topic_matches(filter, &record.topic)) {
+            continue;
+        }
+        chunk.push(record);
+        if chunk.len() >= chunk_size {
+            if progress.is_cancelled() {
+                return Ok(seq + 1);
+            }
+            progress.advance(chunk.len() as u64);
+            seq += 1;
+            emit(
+                seq,
+                std::mem::replace(&mut chunk, Vec::with_capacity(chunk_size)),
+            );
+        }
+    }
+    if !chunk.is_empty() && !progress.is_cancelled() {
+        progress.advance(chunk.len() as u64);
+        seq += 1;
+        emit(seq, chunk);
+    }
+    Ok(seq + 1)
+}
+
+fn topic_field_stats_rows(
+    db_path: &Path,
+    topic: &str,
+    path: &str,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+) -> Result<TopicFieldStats> {
+    let is_filter = topic.contains(['+', '#']);
+    let conn = open_ro_connection(db_path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT ts_ms, topic, payload
+             FROM message_history
+             WHERE (?1 IS NULL OR ts_ms >= ?1)
+               AND (?2 IS NULL OR ts_ms <= ?2)
+               AND (?3 IS NULL OR topic = ?3)
+             ORDER BY ts_ms ASC, id ASC",
+        )
+        .context("failed to prepare topic stats query")?;
+    let mut rows = stmt
+        .query(params![
+            from_ts,
+            to_ts,
+            if is_filter { None } else { Some(topic) }
+        ])
+        .context("failed to execute topic stats query")?;
+
+    let mut stats = TopicFieldStats::default();
+    let mut sum = 0.0;
+    while let Some(row) = rows.next().context("failed to iterate topic stats rows")? {
+        if is_filter && !topic_matches(topic, &row.get::<_, String>(1)?) {
+            continue;
+        }
+        let payload: String = row.get(2)?;
+        let Some(value) = json_path::select_number(&payload, path) else {
+            stats.skipped += 1;
+            continue;
+        };
+        stats.count += 1;
+        sum += value;
+        stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+        stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+        stats.last = Some(value);
+        stats.last_timestamp = Some(row.get::<_, i64>(0)? as u64);
+    }
+    if stats.count > 0 {
+        stats.mean = Some(sum / stats.count as f64);
+    }
+    Ok(stats)
+}
+
+fn payload_size_rows(
+    db_path: &Path,
+    topic: Option<&str>,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    limit: usize,
+) -> Result<PayloadSizeHistogram> {
+    let is_filter = topic.is_some_and(|topic| topic.contains(['+', '#']));
+    let bucket = PAYLOAD_SIZE_BOUNDS
+        .iter()
+        .enumerate()
+        .map(|(index, bound)| format!("WHEN size <= {bound} THEN {index} "))
+        .collect::<String>();
+    let conn = open_ro_connection(db_path)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT topic, CASE {bucket}ELSE {} END AS bucket,
+                    COUNT(*), SUM(size), MAX(size)
+             FROM (
+                 SELECT topic, length(CAST(payload AS BLOB)) AS size
+                 FROM message_history
+                 WHERE (?1 IS NULL OR ts_ms >= ?1)
+                   AND (?2 IS NULL OR ts_ms <= ?2)
+                   AND (?3 IS NULL OR topic = ?3)
+             )
+             GROUP BY topic, bucket",
+            PAYLOAD_SIZE_BOUNDS.len()
+        ))
+        .context("failed to prepare payload size query")?;
+    let mut rows = stmt
+        .query(params![
+            from_ts,
+            to_ts,
+            if is_filter { None } else { topic }
+        ])
+        .context("failed to execute payload size query")?;
+
+    let mut overall = empty_size_distribution();
+    let mut topics: HashMap<String, PayloadSizeDistribution> = HashMap::new();
+    while let Some(row) = rows.next().context("failed to iterate payload size rows")? {
+        let name: String = row.get(0)?;
+        if let Some(filter) = topic.filter(|_| is_filter) {
+            if !topic_matches(filter, &name) {
+                continue;
+            }
+        }
+        let bucket = row.get::<_, i64>(1)? as usize;
+        let count = row.get::<_, i64>(2)? as u64;
+        let total_bytes = row.get::<_, i64>(3)? as u64;
+        let largest_bytes = row.get::<_, i64>(4)? as u64;
+        for sizes in [
+            &mut overall,
+            topics.entry(name).or_insert_with(empty_size_distribution),
+        ] {
+            sizes.count += count;
+            sizes.total_bytes += total_bytes;
+            sizes.largest_bytes = sizes.largest_bytes.max(largest_bytes);
+            sizes.buckets[bucket].count += count;
+        }
+    }
+
+    let mut topics = topics
+        .into_iter()
+        .map(|(topic, sizes)| TopicPayloadSizes { topic, sizes })
+        .collect::<Vec<_>>();
+    topics.sort_by(|a, b| {
+        b.sizes
+            .largest_bytes
+            .cmp(&a.sizes.largest_bytes)
+            .then_with(|| a.topic.cmp(&b.topic))
+    });
+    let truncated = topics.len() > limit;
+    topics.truncate(limit);
+    Ok(PayloadSizeHistogram {
+        overall,
+        topics,
+        truncated,
+    })
+}
+
+fn empty_size_distribution() -> PayloadSizeDistribution {
+    PayloadSizeDistribution {
+        count: 0,
+        total_bytes: 0,
+        largest_bytes: 0,
+        buckets: PAYLOAD_SIZE_BOUNDS
+            .iter()
+            .map(|bound| Some(*bound))
+            .chain([None])
+            .map(|max_bytes| PayloadSizeBucket {
+                max_bytes,
+                count: 0,
+            })
+            .collect(),
+    }
+}
+
+fn correlation_rows(path: &Path, correlation_id: &str) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE correlation_id = ?1
+             ORDER BY ts_ms ASC, id ASC
+             LIMIT ?2",
+        )
+        .context("failed to prepare correlation query")?;
+
+    stmt.query_map(
+        params![correlation_id, MAX_THREAD_MESSAGES as i64],
+        row_to_record,
+    )
+    .context("failed to execute correlation query")?
+    .collect::<rusqlite::Result<Vec<_>>>()
+    .context("failed to map correlation rows")
+}
+
+fn insert_event(
+    path: &Path,
+    timestamp: u64,
+    kind: ConnectionEventKind,
+    detail: Option<String>,
+) -> Result<()> {
+    let conn = open_rw_connection(path)?;
+    conn.execute(
+        "INSERT INTO connection_events (ts_ms, kind, detail) VALUES (?1, ?2, ?3)",
+        params![timestamp as i64, kind.as_str(), detail],
+    )
+    .context("failed to insert connection event")?;
+    Ok(())
+}
+
+fn has_events_table(conn: &Connection) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'connection_events')",
+        [],
+        |row| row.get(0),
+    )
+    .context("failed to inspect history schema")
+}
+
+fn query_event_rows(
+    path: &Path,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    limit: usize,
+) -> Result<Vec<ConnectionEventRecord>> {
+    let conn = open_ro_connection(path)?;
+    // Databases written before the event log existed only gain the table on their next write.
+    if !has_events_table(&conn)? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, kind, detail
+             FROM connection_events
+             WHERE (?1 IS NULL OR ts_ms >= ?1)
+               AND (?2 IS NULL OR ts_ms <= ?2)
+             ORDER BY ts_ms DESC, id DESC
+             LIMIT ?3",
+        )
+        .context("failed to prepare event query")?;
+
+    stmt.query_map(params![from_ts, to_ts, limit as i64], row_to_event)
+        .context("failed to execute event query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map event rows")
+}
+
+fn export_event_rows(
+    db_path: &Path,
+    output_path: &Path,
+    is_csv: bool,
+    progress: &JobProgress,
+) -> Result<HistoryExportResult> {
+    let conn = open_ro_connection(db_path)?;
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create export directory: {}", parent.display()))?;
+    }
+
+    let file = fs::File::create(output_path)
+        .with_context(|| format!("failed to create export file: {}", output_path.display()))?;
+    let mut writer = BufWriter::new(file);
+    if is_csv {
+        writer
+            .write_all(b"id,timestamp,kind,detail\n")
+            .context("failed to write csv header")?;
+    }
+
+    let mut count: u64 = 0;
+    if has_events_table(&conn)? {
+        let total: i64 = conn
+            .query_row("SELECT COUNT(*) FROM connection_events", [], |row| {
+                row.get(0)
+            })
+            .context("failed to count events")?;
+        progress.set_total(total as u64);
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, ts_ms, kind, detail FROM connection_events ORDER BY ts_ms ASC, id ASC",
+            )
+            .context("failed to prepare event export query")?;
+        let mut rows = stmt
+            .query([])
+            .context("failed to execute event export query")?;
+        while let Some(row) = rows.next().context("failed to iterate event rows")? {
+            let record = row_to_event(row).context("failed to parse event row")?;
+            let line = if is_csv {
+                format!(
+                    "{},{},{},{}",
+                    record.id,
+                    record.timestamp,
+                    record.kind.as_str(),
+                    escape_csv(record.detail.as_deref().unwrap_or_default())
+                )
+            } else {
+                serde_json::to_string(&record).context("failed to serialize event row")?
+            };
+            writer
+                .write_all(line.as_bytes())
+                .context("failed to write event row")?;
+            writer
+                .write_all(b"\n")
+                .context("failed to write export newline")?;
+            count += 1;
+            progress.tick()?;
+        }
+    }
+
+    writer.flush().context("failed to flush export writer")?;
+
+    Ok(HistoryExportResult {
+        path: output_path.display().to_string(),
+        count,
+    })
+}
+
+fn row_to_event(row: &rusqlite::Row<'_>) -> rusqlite::Result<ConnectionEventRecord> {
+    let kind: String = row.get(2)?;
+    Ok(ConnectionEventRecord {
+        id: row.get(0)?,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        kind: ConnectionEventKind::from_str_lossy(&kind),
+        detail: row.get(3)?,
+    })
+}
+
+fn latest_per_topic_rows(path: &Path, prefix: &str) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE id IN (
+                 SELECT MAX(id) FROM message_history
+                 WHERE substr(topic, 1, length(?1)) = ?1
+                 GROUP BY topic
+             )
+             ORDER BY topic ASC",
+        )
+        .context("failed to prepare latest per topic query")?;
+
+    stmt.query_map(params![prefix], row_to_record)
+        .context("failed to execute latest per topic query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map latest per topic rows")
+}
+
+fn latest_state_rows(
+    path: &Path,
+    at: i64,
+    retained_only: bool,
+) -> Result<Vec<HistoryMessageRecord>> {
+    let conn = open_ro_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE id IN (
+                 SELECT MAX(id) FROM message_history
+                 WHERE direction = 0 AND ts_ms <= ?1 AND (?2 = 0 OR retain = 1)
+                 GROUP BY topic
+             )
+             ORDER BY topic ASC",
+        )
+        .context("failed to prepare latest state query")?;
+
+    stmt.query_map(params![at, i64::from(retained_only)], row_to_record)
+        .context("failed to execute latest state query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to map latest state rows")
+}
+
+#[allow(clippy::too_many_arguments)]
+fn export_rows(
+    db_path: &Path,
+    output_path: &Path,
+    format: &str,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    time_zone: Option<ExportTimeZone>,
+    payload_regex: Option<&str>,
+    progress: &JobProgress,
+) -> Result<HistoryExportResult> {
+    let conn = open_ro_connection(db_path)?;
+    // The number of regex matches isn't known until the scan ends, so such exports report
+    // progress without a total.
+    if payload_regex.is_none() {
+        progress.set_total(count_range(&conn, from_ts, to_ts)?);
+    }
+    let mut sink = ExportSink::create(output_path, format, false, time_zone)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE (?1 IS NULL OR ts_ms >= ?1)
+               AND (?2 IS NULL OR ts_ms <= ?2)
+               AND (?3 IS NULL OR payload REGEXP ?3)
+             ORDER BY ts_ms ASC, id ASC",
+        )
+        .context("failed to prepare export query")?;
+
+    let mut rows = stmt
+        .query(params![from_ts, to_ts, payload_regex])
+        .context("failed to execute export query")?;
+
+    let mut count: u64 = 0;
+    while let Some(row) = rows.next().context("failed to iterate export rows")? {
+        let record = row_to_record(row).context("failed to parse export row")?;
+        sink.write(None, record)?;
+        count += 1;
+        progress.tick()?;
+    }
+
+    sink.finish()?;
+
+    Ok(HistoryExportResult {
+        path: output_path.display().to_string(),
+        count,
+    })
+}
+
+fn export_topic_rows(
+    db_path: &Path,
+    output_path: &Path,
+    options: &TopicListExportOptions,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    progress: &JobProgress,
+) -> Result<HistoryExportResult> {
+    let conn = open_ro_connection(db_path)?;
+    let total = conn
+        .query_row(
+            "SELECT COUNT(DISTINCT topic) FROM message_history
+             WHERE (?1 IS NULL OR ts_ms >= ?1)
+               AND (?2 IS NULL OR ts_ms <= ?2)",
+            params![from_ts, to_ts],
+            |row| row.get::<_, i64>(0),
+        )
+        .context("failed to count topics")?;
+    progress.set_total(total as u64);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create export directory: {}", parent.display()))?;
+    }
+
+    let file = fs::File::create(output_path)
+        .with_context(|| format!("failed to create export file: {}", output_path.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.topic, t.messages, t.first_seen, t.last_seen, m.payload
+             FROM (
+                 SELECT topic, COUNT(*) AS messages, MIN(ts_ms) AS first_seen,
+                        MAX(ts_ms) AS last_seen, MAX(id) AS last_id
+                 FROM message_history
+                 WHERE (?1 IS NULL OR ts_ms >= ?1)
+                   AND (?2 IS NULL OR ts_ms <= ?2)
+                 GROUP BY topic
+             ) t
+             JOIN message_history m ON m.id = t.last_id
+             ORDER BY t.topic ASC",
+        )
+        .context("failed to prepare topic list query")?;
+    let mut rows = stmt
+        .query(params![from_ts, to_ts])
+        .context("failed to execute topic list query")?;
+
+    let filter = options
+        .topic_filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty());
+    let csv = matches!(options.format, TopicListFormat::Csv);
+    if csv {
+        let mut header = String::from("topic,messages");
+        if options.include_seen {
+            header.push_str(",first_seen,last_seen");
+        }
+        if options.include_samples {
+            header.push_str(",sample_payload");
+        }
+        writeln!(writer, "{header}").context("failed to write topic list header")?;
+    } else {
+        writer
+            .write_all(b"[")
+            .context("failed to write topic list")?;
+    }
+
+    let mut count: u64 = 0;
+    while let Some(row) = rows.next().context("failed to iterate topic rows")? {
+        progress.tick()?;
+        let topic: String = row.get(0)?;
+        if filter.is_some_and(|filter| !topic_matches(filter, &topic)) {
+            continue;
+        }
+        let messages = row.get::<_, i64>(1)? as u64;
+        let first_seen = row.get::<_, i64>(2)? as u64;
+        let last_seen = row.get::<_, i64>(3)? as u64;
+        let sample: String = row.get(4)?;
+
+        let line = if csv {
+            let mut line = format!("{},{messages}", escape_csv(&topic));
+            if options.include_seen {
+                line.push_str(&format!(",{first_seen},{last_seen}"));
+            }
+            if options.include_samples {
+                line.push(',');
+                line.push_str(&escape_csv(&sample));
+            }
+            line
+        } else {
+            let mut entry = serde_json::json!({ "topic": topic, "messages": messages });
+            if options.include_seen {
+                entry["firstSeen"] = first_seen.into();
+                entry["lastSeen"] = last_seen.into();
+            }
+            if options.include_samples {
+                entry["samplePayload"] = sample.into();
+            }
+            format!("{}\n  {entry}", if count == 0 { "" } else { "," })
+        };
+        if csv {
+            writeln!(writer, "{line}")
+        } else {
+            writer.write_all(line.as_bytes())
+        }
+        .context("failed to write topic row")?;
+        count += 1;
+    }
+
+    if !csv {
+        writer
+            .write_all(if count == 0 { b"]\n" } else { b"\n]\n" })
+            .context("failed to write topic list")?;
+    }
+    writer.flush().context("failed to flush export writer")?;
+
+    Ok(HistoryExportResult {
+        path: output_path.display().to_string(),
+        count,
+    })
+}
+
+fn export_mapped_rows<F>(
+    db_path: &Path,
+    output_path: &Path,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    mut format_line: F,
+    progress: &JobProgress,
+) -> Result<HistoryExportResult>
+where
+    F: FnMut(&HistoryMessageRecord) -> Option<String>,
+{
+    let conn = open_ro_connection(db_path)?;
+    progress.set_total(count_range(&conn, from_ts, to_ts)?);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create export directory: {}", parent.display()))?;
+    }
+
+    let file = fs::File::create(output_path)
+        .with_context(|| format!("failed to create export file: {}", output_path.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE (?1 IS NULL OR ts_ms >= ?1)
+               AND (?2 IS NULL OR ts_ms <= ?2)
+             ORDER BY ts_ms ASC, id ASC",
+        )
+        .context("failed to prepare export query")?;
+
+    let mut rows = stmt
+        .query(params![from_ts, to_ts])
+        .context("failed to execute export query")?;
+
+    let mut count: u64 = 0;
+    while let Some(row) = rows.next().context("failed to iterate export rows")? {
+        let record = row_to_record(row).context("failed to parse export row")?;
+        progress.tick()?;
+        let Some(line) = format_line(&record) else {
+            continue;
+        };
+        writer
+            .write_all(line.as_bytes())
+            .context("failed to write export row")?;
+        writer
+            .write_all(b"\n")
+            .context("failed to write export newline")?;
+        count += 1;
+    }
+
+    writer.flush().context("failed to flush export writer")?;
+
+    Ok(HistoryExportResult {
+        path: output_path.display().to_string(),
+        count,
+    })
+}
+
+fn search_rows(
+    db_path: &Path,
+    connection_id: &str,
+    query: &HistorySearchQuery,
+    limit: usize,
+) -> Result<Vec<HistorySearchHit>> {
+    let conn = open_ro_connection(db_path)?;
+    let pattern = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(|text| format!("%{}%", escape_like(text)));
+    let topic_filter = query
+        .topic_filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|filter| !filter.is_empty());
+
+    let ts = time_column(query.time_source);
+    let order = order_by(query.sort.unwrap_or(HistorySort::TimestampDesc), ts);
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+             FROM message_history
+             WHERE (?1 IS NULL OR {ts} >= ?1)
+               AND (?2 IS NULL OR {ts} <= ?2)
+               AND (?3 IS NULL OR topic LIKE ?3 ESCAPE '\\' OR payload LIKE ?3 ESCAPE '\\')
+               AND (?4 IS NULL OR payload REGEXP ?4)
+             ORDER BY {order}"
+        ))
+        .context("failed to prepare history search query")?;
+    let mut rows = stmt
+        .query(params![
+            query.from_ts.map(|v| v as i64),
+            query.to_ts.map(|v| v as i64),
+            pattern,
+            query.payload_regex
+        ])
+        .context("failed to execute history search query")?;
+
+    let mut hits = Vec::new();
+    while let Some(row) = rows
+        .next()
+        .context("failed to iterate history search rows")?
+    {
+        let record = row_to_record(row).context("failed to parse history search row")?;
+        if topic_filter.is_some_and(|filter| !topic_matches(filter, &record.topic)) {
+            continue;
+        }
+        hits.push(HistorySearchHit {
+            connection_id: connection_id.to_string(),
+            record,
+        });
+        if hits.len() >= limit {
+            break;
+        }
+    }
+    Ok(hits)
+}
+
+/// Merges hits from several connections in the order `order_by` gives within one.
+fn sort_hits(hits: &mut [HistorySearchHit], sort: HistorySort, time_source: HistoryTimeSource) {
+    hits.sort_by(|a, b| {
+        let oldest_first = || {
+            record_time(&a.record, time_source)
+                .cmp(&record_time(&b.record, time_source))
+                .then_with(|| a.connection_id.cmp(&b.connection_id))
+                .then_with(|| a.record.id.cmp(&b.record.id))
+        };
+        match sort {
+            HistorySort::TimestampAsc => oldest_first(),
+            HistorySort::TimestampDesc => record_time(&b.record, time_source)
+                .cmp(&record_time(&a.record, time_source))
+                .then_with(|| a.connection_id.cmp(&b.connection_id))
+                .then_with(|| b.record.id.cmp(&a.record.id)),
+            HistorySort::Topic => a.record.topic.cmp(&b.record.topic).then_with(oldest_first),
+            HistorySort::PayloadSize => b
+                .record
+                .payload
+                .len()
+                .cmp(&a.record.payload.len())
+                .then_with(oldest_first),
+        }
+    });
+}
+
+/// `ORDER BY` terms for `sort`, with `ts` as the time expression; ties fall back to time and id
+/// so results are stable between calls.
+fn order_by(sort: HistorySort, ts: &str) -> String {
+    match sort {
+        HistorySort::TimestampAsc => format!("{ts} ASC, id ASC"),
+        HistorySort::TimestampDesc => format!("{ts} DESC, id DESC"),
+        HistorySort::Topic => format!("topic ASC, {ts} ASC, id ASC"),
+        HistorySort::PayloadSize => {
+            format!("length(CAST(payload AS BLOB)) DESC, {ts} ASC, id ASC")
+        }
+    }
+}
 
-    let mut rows = stmt
-        .query_map(params![before_ts, before_id, limit as i64], row_to_record)
-        .context("failed to execute paged history query")?
-        .collect::<rusqlite::Result<Vec<_>>>()
-        .context("failed to map paged history rows")?;
+/// SQL expression for the time a query filters and sorts by.
+fn time_column(time_source: HistoryTimeSource) -> &'static str {
+    match time_source {
+        HistoryTimeSource::Arrival => "ts_ms",
+        // Matches the `idx_message_event_ts` expression index.
+        HistoryTimeSource::Payload => "COALESCE(payload_ts_ms, ts_ms)",
+    }
+}
 
-    rows.reverse();
-    Ok(rows)
+fn record_time(record: &HistoryMessageRecord, time_source: HistoryTimeSource) -> u64 {
+    match time_source {
+        HistoryTimeSource::Arrival => record.timestamp,
+        HistoryTimeSource::Payload => record.payload_timestamp.unwrap_or(record.timestamp),
+    }
 }
 
-fn export_rows(
-    db_path: &Path,
+fn escape_like(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Keyset-paged reader over one connection's history in ascending timestamp order.
+struct MergeCursor {
+    connection_id: String,
+    conn: Connection,
+    buffer: VecDeque<HistoryMessageRecord>,
+    last_key: Option<(i64, i64)>,
+    exhausted: bool,
+}
+
+impl MergeCursor {
+    fn next(
+        &mut self,
+        from_ts: Option<i64>,
+        to_ts: Option<i64>,
+    ) -> Result<Option<HistoryMessageRecord>> {
+        if self.buffer.is_empty() && !self.exhausted {
+            self.fill(from_ts, to_ts)?;
+        }
+        Ok(self.buffer.pop_front())
+    }
+
+    fn fill(&mut self, from_ts: Option<i64>, to_ts: Option<i64>) -> Result<()> {
+        let (last_ts, last_id) = match self.last_key {
+            Some((ts, id)) => (Some(ts), Some(id)),
+            None => (None, None),
+        };
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT id, ts_ms, topic, payload, qos, retain, direction, dup, packet_id,
+                    correlation_id, response_topic, payload_ts_ms, user_properties, is_binary
+                 FROM message_history
+                 WHERE (?1 IS NULL OR ts_ms >= ?1)
+                   AND (?2 IS NULL OR ts_ms <= ?2)
+                   AND (?3 IS NULL OR (ts_ms, id) > (?3, ?4))
+                 ORDER BY ts_ms ASC, id ASC
+                 LIMIT ?5",
+            )
+            .context("failed to prepare merged export query")?;
+        let page = stmt
+            .query_map(
+                params![from_ts, to_ts, last_ts, last_id, MERGE_PAGE_ROWS as i64],
+                row_to_record,
+            )
+            .context("failed to execute merged export query")?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to map merged export rows")?;
+
+        self.exhausted = page.len() < MERGE_PAGE_ROWS;
+        if let Some(last) = page.last() {
+            self.last_key = Some((last.timestamp as i64, last.id));
+        }
+        self.buffer.extend(page);
+        Ok(())
+    }
+}
+
+fn export_merged_rows(
+    sources: Vec<(String, PathBuf)>,
     output_path: &Path,
     format: &str,
     from_ts: Option<i64>,
     to_ts: Option<i64>,
+    time_zone: Option<ExportTimeZone>,
+    progress: &JobProgress,
 ) -> Result<HistoryExportResult> {
-    let conn = open_ro_connection(db_path)?;
-    if let Some(parent) = output_path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("failed to create export directory: {}", parent.display()))?;
-    }
-
-    let file = fs::File::create(output_path)
-        .with_context(|| format!("failed to create export file: {}", output_path.display()))?;
-    let mut writer = BufWriter::new(file);
-
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, ts_ms, topic, payload, qos, retain, direction
-             FROM message_history
-             WHERE (?1 IS NULL OR ts_ms >= ?1)
-               AND (?2 IS NULL OR ts_ms <= ?2)
-             ORDER BY ts_ms ASC, id ASC",
-        )
-        .context("failed to prepare export query")?;
-
-    let mut rows = stmt
-        .query(params![from_ts, to_ts])
-        .context("failed to execute export query")?;
-
-    let is_csv = format.eq_ignore_ascii_case("csv");
-    if is_csv {
-        writer
-            .write_all(b"id,timestamp,topic,payload,qos,retain,direction\n")
-            .context("failed to write csv header")?;
+    let mut total = 0;
+    let mut cursors = sources
+        .into_iter()
+        .map(|(connection_id, db_path)| {
+            let conn = open_ro_connection(&db_path)?;
+            total += count_range(&conn, from_ts, to_ts)?;
+            Ok(MergeCursor {
+                connection_id,
+                conn,
+                buffer: VecDeque::new(),
+                last_key: None,
+                exhausted: false,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    progress.set_total(total);
+    let mut sink = ExportSink::create(output_path, format, true, time_zone)?;
+
+    // Heap of the next row from each cursor, keyed so the oldest row pops first.
+    let mut heads = BinaryHeap::new();
+    let mut pending: Vec<Option<HistoryMessageRecord>> = Vec::with_capacity(cursors.len());
+    for (index, cursor) in cursors.iter_mut().enumerate() {
+        let head = cursor.next(from_ts, to_ts)?;
+        if let Some(record) = &head {
+            heads.push(Reverse((record.timestamp, index, record.id)));
+        }
+        pending.push(head);
     }
 
     let mut count: u64 = 0;
-    while let Some(row) = rows.next().context("failed to iterate export rows")? {
-        let record = row_to_record(row).context("failed to parse export row")?;
-        if is_csv {
-            let line = format!(
-                "{},{},{},{},{},{},{}\n",
-                record.id,
-                record.timestamp,
-                escape_csv(&record.topic),
-                escape_csv(&record.payload),
-                record.qos,
-                if record.retain { 1 } else { 0 },
-                if matches!(record.direction, MessageDirection::Out) {
-                    "out"
-                } else {
-                    "in"
-                }
-            );
-            writer
-                .write_all(line.as_bytes())
-                .context("failed to write csv row")?;
-        } else {
-            let line = serde_json::to_string(&record).context("failed to serialize ndjson row")?;
-            writer
-                .write_all(line.as_bytes())
-                .context("failed to write ndjson row")?;
-            writer
-                .write_all(b"\n")
-                .context("failed to write ndjson newline")?;
-        }
+    while let Some(Reverse((_, index, _))) = heads.pop() {
+        let Some(record) = pending[index].take() else {
+            continue;
+        };
+        sink.write(Some(&cursors[index].connection_id), record)?;
         count += 1;
+        progress.tick()?;
+
+        let next = cursors[index].next(from_ts, to_ts)?;
+        if let Some(record) = &next {
+            heads.push(Reverse((record.timestamp, index, record.id)));
+        }
+        pending[index] = next;
     }
 
-    writer.flush().context("failed to flush export writer")?;
+    sink.finish()?;
 
     Ok(HistoryExportResult {
         path: output_path.display().to_string(),
@@ -420,6 +2439,46 @@ fn export_rows(
     })
 }
 
+fn checkpoint_db_file(path: &Path) -> Result<(bool, i64, i64)> {
+    let conn = open_rw_connection(path)?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+        Ok((row.get::<_, i64>(0)? != 0, row.get(1)?, row.get(2)?))
+    })
+    .context("failed to checkpoint history database")
+}
+
+fn prune_db_file(path: &Path, percent: u8) -> Result<u64> {
+    let conn = open_rw_connection(path)?;
+    let page_count: i64 = conn
+        .query_row("PRAGMA page_count", [], |row| row.get(0))
+        .context("failed to read history page count")?;
+    let free_pages: i64 = conn
+        .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+        .context("failed to read history free pages")?;
+    if page_count == 0 || free_pages * 100 >= page_count * i64::from(percent) {
+        return Ok(0);
+    }
+
+    let rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM message_history", [], |row| row.get(0))
+        .context("failed to count history rows")?;
+    let to_remove = (rows * i64::from(percent) / 100).max(1);
+    let removed = conn
+        .execute(
+            "DELETE FROM message_history WHERE id IN (
+                 SELECT id FROM message_history
+                 WHERE id NOT IN (SELECT message_id FROM pinned_messages)
+                 ORDER BY id
+                 LIMIT ?1
+             )",
+            params![to_remove],
+        )
+        .context("failed to prune history rows")?;
+    drop(conn);
+    checkpoint_db_file(path)?;
+    Ok(removed as u64)
+}
+
 fn clear_db_file(path: &Path) -> Result<()> {
     if !path.exists() {
         let _ = open_rw_connection(path)?;
@@ -478,6 +2537,233 @@ fn delete_db_file(path: &Path) -> Result<()> {
     Ok(())
 }
 
+fn pause_status(connection_id: &str, paused: Option<(u64, u64)>) -> HistoryPauseStatus {
+    HistoryPauseStatus {
+        connection_id: connection_id.to_string(),
+        paused: paused.is_some(),
+        paused_at: paused.map(|(paused_at, _)| paused_at),
+        skipped_messages: paused.map_or(0, |(_, skipped)| skipped),
+    }
+}
+
+fn measure_disk_usage(
+    root: &Path,
+    exports_dir: &Path,
+    profiles: Vec<(String, String)>,
+) -> Result<HistoryDiskUsage> {
+    let file_size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let names = profiles
+        .into_iter()
+        .map(|(id, name)| (format!("{}.db", safe_connection_id(&id)), (id, name)))
+        .collect::<std::collections::HashMap<_, _>>();
+
+    let mut connections = Vec::new();
+    let mut pending_delete_bytes = 0;
+    let entries =
+        fs::read_dir(root).with_context(|| format!("failed to scan {}", root.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !path.is_file() {
+            continue;
+        }
+        if file_name.contains(".deleting.") {
+            pending_delete_bytes += file_size(&path);
+            continue;
+        }
+        if !file_name.ends_with(".db") {
+            continue;
+        }
+
+        let db_bytes = file_size(&path);
+        let wal_bytes = file_size(&path.with_file_name(format!("{file_name}-wal")));
+        let shm_bytes = file_size(&path.with_file_name(format!("{file_name}-shm")));
+        let (connection_id, name) = match names.get(file_name) {
+            Some((id, name)) => (id.clone(), Some(name.clone())),
+            None => (file_name.trim_end_matches(".db").to_string(), None),
+        };
+        connections.push(ConnectionDiskUsage {
+            connection_id,
+            name,
+            db_bytes,
+            wal_bytes,
+            shm_bytes,
+            total_bytes: db_bytes + wal_bytes + shm_bytes,
+            paused: false,
+        });
+    }
+    connections.sort_by_key(|usage| std::cmp::Reverse(usage.total_bytes));
+
+    let exports_bytes = dir_size(exports_dir);
+    let total_bytes = connections
+        .iter()
+        .map(|usage| usage.total_bytes)
+        .sum::<u64>()
+        + exports_bytes
+        + pending_delete_bytes;
+    Ok(HistoryDiskUsage {
+        history_dir: root.display().to_string(),
+        connections,
+        exports_bytes,
+        pending_delete_bytes,
+        total_bytes,
+        available_bytes: disk::available_space(root),
+    })
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+/// Files removed by [`HistoryManager::wipe_all`].
+pub struct HistoryWipe {
+    pub databases: u64,
+    pub export_files: u64,
+    pub bytes: u64,
+}
+
+fn wipe_history_files(root: &Path, exports_dir: &Path) -> Result<HistoryWipe> {
+    let mut wiped = HistoryWipe {
+        databases: 0,
+        export_files: 0,
+        bytes: 0,
+    };
+    let entries =
+        fs::read_dir(root).with_context(|| format!("failed to scan {}", root.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() && is_quarantined(&path) {
+            wiped.bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to delete {}", path.display()))?;
+            continue;
+        }
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("db") {
+            continue;
+        }
+        wiped.bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        delete_db_file(&path)?;
+        wiped.databases += 1;
+    }
+    cleanup_deleting_files(root)?;
+
+    let (files, bytes) = crate::wipe::remove_dir_contents(exports_dir)?;
+    wiped.export_files = files;
+    wiped.bytes += bytes;
+    Ok(wiped)
+}
+
+/// Runs `PRAGMA integrity_check` on every history database and moves the ones that fail, or
+/// can't be opened at all, aside with a `.corrupt` suffix so a fresh database takes their place
+/// instead of every later history call failing.
+fn repair_corrupt_databases(root: &Path) -> Vec<HistoryRepair> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut repairs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("db") {
+            continue;
+        }
+        let Err(reason) = check_integrity(&path) else {
+            continue;
+        };
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        tracing::error!(file = file_name.as_str(), %reason, "history database is damaged");
+        match quarantine_db_file(&path) {
+            Ok(moved_to) => {
+                if let Err(error) = open_rw_connection(&path) {
+                    tracing::error!(
+                        file = file_name.as_str(),
+                        "failed to recreate history database: {error:#}"
+                    );
+                }
+                repairs.push(HistoryRepair {
+                    file_name,
+                    moved_to: moved_to.display().to_string(),
+                    reason,
+                    repaired_at: now_millis(),
+                });
+            }
+            Err(error) => tracing::error!(
+                file = file_name.as_str(),
+                "failed to move damaged history database aside: {error:#}"
+            ),
+        }
+    }
+    repairs
+}
+
+fn check_integrity(path: &Path) -> std::result::Result<(), String> {
+    let conn = Connection::open(path).map_err(|error| error.to_string())?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .map_err(|error| error.to_string())?;
+    let mut stmt = conn
+        .prepare("PRAGMA integrity_check")
+        .map_err(|error| error.to_string())?;
+    let problems = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|error| error.to_string())?;
+    match problems.as_slice() {
+        [ok] if ok == "ok" => Ok(()),
+        _ => Err(problems.join("; ")),
+    }
+}
+
+/// Renames `path` and its WAL/shared-memory files to `<name>.corrupt`, adding a timestamp when
+/// an earlier incident already took that name.
+fn quarantine_db_file(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("history database has no file name")?;
+    let mut target = path.with_file_name(format!("{file_name}.corrupt"));
+    if target.exists() {
+        target = path.with_file_name(format!("{file_name}.{}.corrupt", now_millis()));
+    }
+    let target_name = target
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string();
+    for suffix in ["-wal", "-shm"] {
+        let companion = path.with_file_name(format!("{file_name}{suffix}"));
+        if companion.exists() {
+            fs::rename(
+                &companion,
+                path.with_file_name(format!("{target_name}{suffix}")),
+            )
+            .with_context(|| format!("failed to move {}", companion.display()))?;
+        }
+    }
+    fs::rename(path, &target).with_context(|| format!("failed to move {}", path.display()))?;
+    Ok(target)
+}
+
+/// A database moved aside by [`quarantine_db_file`], or one of its companions.
+fn is_quarantined(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.ends_with(".corrupt")
+                || name.ends_with(".corrupt-wal")
+                || name.ends_with(".corrupt-shm")
+        })
+}
+
 fn cleanup_deleting_files(root: &Path) -> Result<()> {
     let entries =
         fs::read_dir(root).with_context(|| format!("failed to scan {}", root.display()))?;
@@ -520,21 +2806,69 @@ fn open_ro_connection(path: &Path) -> Result<Connection> {
     let mut conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
         .with_context(|| format!("failed to open sqlite file read-only: {}", path.display()))?;
     configure_connection(&mut conn, true)?;
+    if !has_message_column(&conn, LATEST_MESSAGE_COLUMN)? {
+        // Databases from older versions gain new columns on their next read-write open.
+        drop(conn);
+        drop(open_rw_connection(path)?);
+        return open_ro_connection(path);
+    }
     Ok(conn)
 }
 
 fn configure_connection(conn: &mut Connection, read_only: bool) -> Result<()> {
     conn.busy_timeout(std::time::Duration::from_secs(5))
         .context("failed to set sqlite busy timeout")?;
+    register_regexp(conn)?;
     if !read_only {
         conn.pragma_update(None, "journal_mode", "WAL")
             .context("failed to set sqlite WAL mode")?;
         conn.pragma_update(None, "synchronous", "FULL")
             .context("failed to set sqlite synchronous mode")?;
+        conn.pragma_update(None, "wal_autocheckpoint", WAL_AUTOCHECKPOINT_PAGES)
+            .context("failed to set sqlite WAL autocheckpoint")?;
+        conn.pragma_update(None, "journal_size_limit", WAL_JOURNAL_SIZE_LIMIT)
+            .context("failed to set sqlite journal size limit")?;
     }
     Ok(())
 }
 
+/// Backs SQLite's `X REGEXP Y` operator with the `regex` crate, so payload filters run inside
+/// the query instead of on rows shipped to the webview. Each statement compiles its pattern once.
+fn register_regexp(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let regex: Arc<Regex> = ctx.get_or_create_aux(
+                0,
+                |pattern| -> std::result::Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                    Ok(compile_payload_regex(pattern.as_str()?)?)
+                },
+            )?;
+            let text = ctx.get_raw(1).as_str_or_null().unwrap_or_default();
+            Ok(text.is_some_and(|text| regex.is_match(text)))
+        },
+    )
+    .context("failed to register sqlite regexp function")
+}
+
+fn compile_payload_regex(pattern: &str) -> std::result::Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(PAYLOAD_REGEX_SIZE_LIMIT)
+        .build()
+}
+
+/// Trims a payload regex from a request and checks it compiles, so a bad pattern fails the
+/// call instead of every row of the query.
+fn payload_regex(pattern: Option<&str>) -> Result<Option<String>> {
+    let Some(pattern) = pattern.map(str::trim).filter(|pattern| !pattern.is_empty()) else {
+        return Ok(None);
+    };
+    compile_payload_regex(pattern).context("invalid payload regex")?;
+    Ok(Some(pattern.to_string()))
+}
+
 fn init_schema(conn: &Connection) -> Result<()> {
     conn.execute_batch(
         "
@@ -549,12 +2883,94 @@ fn init_schema(conn: &Connection) -> Result<()> {
         );
         CREATE INDEX IF NOT EXISTS idx_message_ts_id ON message_history(ts_ms DESC, id DESC);
         CREATE INDEX IF NOT EXISTS idx_message_topic_ts ON message_history(topic, ts_ms DESC);
+        CREATE TABLE IF NOT EXISTS connection_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ts_ms INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            detail TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_event_ts_id ON connection_events(ts_ms DESC, id DESC);
         ",
     )
     .context("failed to initialize history schema")?;
+    migrate_schema(conn)
+}
+
+fn migrate_schema(conn: &Connection) -> Result<()> {
+    if !has_message_column(conn, "dup")? {
+        conn.execute_batch(
+            "
+            ALTER TABLE message_history ADD COLUMN dup INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE message_history ADD COLUMN packet_id INTEGER;
+            ",
+        )
+        .context("failed to add dup/packet id columns to history")?;
+    }
+    if !has_message_column(conn, "correlation_id")? {
+        conn.execute_batch(
+            "
+            ALTER TABLE message_history ADD COLUMN correlation_id TEXT;
+            ALTER TABLE message_history ADD COLUMN response_topic TEXT;
+            CREATE INDEX IF NOT EXISTS idx_message_correlation
+                ON message_history(correlation_id, ts_ms) WHERE correlation_id IS NOT NULL;
+            ",
+        )
+        .context("failed to add correlation columns to history")?;
+    }
+    if !has_message_column(conn, "payload_ts_ms")? {
+        conn.execute_batch(
+            "
+            ALTER TABLE message_history ADD COLUMN payload_ts_ms INTEGER;
+            CREATE INDEX IF NOT EXISTS idx_message_event_ts
+                ON message_history(COALESCE(payload_ts_ms, ts_ms), id);
+            ",
+        )
+        .context("failed to add payload timestamp column to history")?;
+    }
+    if !has_message_column(conn, "user_properties")? {
+        // JSON array of `{key, value}` objects; NULL when the message had none.
+        conn.execute_batch("ALTER TABLE message_history ADD COLUMN user_properties TEXT;")
+            .context("failed to add user properties column to history")?;
+    }
+    if !has_message_column(conn, "is_binary")? {
+        // Set when `payload` holds base64 of bytes that were not UTF-8.
+        conn.execute_batch(
+            "ALTER TABLE message_history ADD COLUMN is_binary INTEGER NOT NULL DEFAULT 0;",
+        )
+        .context("failed to add binary flag column to history")?;
+    }
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS pinned_messages (
+            message_id INTEGER PRIMARY KEY,
+            pinned_at INTEGER NOT NULL,
+            note TEXT
+        );
+        ",
+    )
+    .context("failed to create pinned messages table")?;
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS read_markers (
+            topic TEXT PRIMARY KEY,
+            last_read_id INTEGER NOT NULL,
+            read_at INTEGER NOT NULL
+        );
+        ",
+    )
+    .context("failed to create read markers table")?;
     Ok(())
 }
 
+fn has_message_column(conn: &Connection, column: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info('message_history') WHERE name = ?1)",
+        params![column],
+        |row| row.get(0),
+    )
+    .context("failed to inspect history schema")
+}
+
 fn row_to_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryMessageRecord> {
     let direction_val: i64 = row.get(6)?;
     Ok(HistoryMessageRecord {
@@ -569,6 +2985,16 @@ fn row_to_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryMessageReco
         } else {
             MessageDirection::In
         },
+        dup: row.get::<_, i64>(7)? == 1,
+        packet_id: row.get::<_, Option<i64>>(8)?.map(|id| id as u16),
+        correlation_id: row.get(9)?,
+        response_topic: row.get(10)?,
+        payload_timestamp: row.get::<_, Option<i64>>(11)?.map(|ts| ts as u64),
+        user_properties: row
+            .get::<_, Option<String>>(12)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        is_binary: row.get::<_, i64>(13)? == 1,
     })
 }
 
@@ -604,11 +3030,382 @@ fn deleting_path(path: &Path) -> PathBuf {
     path.with_file_name(format!("{file_name}.deleting.{}", now_millis()))
 }
 
-fn escape_csv(input: &str) -> String {
+/// File extension for a requested history export format; unknown formats fall back to NDJSON.
+fn export_extension(format: &str) -> &'static str {
+    if format.eq_ignore_ascii_case("csv") {
+        "csv"
+    } else if format.eq_ignore_ascii_case("xlsx") {
+        "xlsx"
+    } else {
+        "ndjson"
+    }
+}
+
+/// Writes exported message rows in one of the history export formats. `with_connection` adds a
+/// leading connection id column, for exports spanning several connections.
+/// Zone that exported timestamps are also written in, as ISO-8601 text next to the epoch value.
+#[derive(Debug, Clone, Copy)]
+pub enum ExportTimeZone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl ExportTimeZone {
+    /// Accepts `UTC`, `local`, or a fixed offset such as `+02:00`, `-0530` or `UTC+8`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim().to_ascii_uppercase();
+        match text.as_str() {
+            "UTC" | "GMT" | "Z" => return Ok(Self::Utc),
+            "LOCAL" => return Ok(Self::Local),
+            _ => {}
+        }
+        let invalid = || {
+            anyhow::anyhow!(
+                "unsupported time zone '{text}'; use UTC, local or an offset like +02:00"
+            )
+        };
+        let offset = text
+            .strip_prefix("UTC")
+            .or_else(|| text.strip_prefix("GMT"))
+            .unwrap_or(&text);
+        let (sign, rest) = match offset.as_bytes().first() {
+            Some(b'+') => (1, &offset[1..]),
+            Some(b'-') => (-1, &offset[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some(parts) => parts,
+            None if rest.len() == 4 => rest.split_at(2),
+            None => (rest, "0"),
+        };
+        let hours = hours.parse::<i32>().map_err(|_| invalid())?;
+        let minutes = minutes.parse::<i32>().map_err(|_| invalid())?;
+        if hours > 14 || minutes > 59 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(invalid)
+    }
+
+    fn format(self, millis: u64) -> String {
+        let time = DateTime::from_timestamp_millis(millis as i64).unwrap_or_default();
+        match self {
+            Self::Utc => time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            Self::Local => time
+                .with_timezone(&chrono::Local)
+                .to_rfc3339_opts(SecondsFormat::Millis, false),
+            Self::Fixed(offset) => time
+                .with_timezone(&offset)
+                .to_rfc3339_opts(SecondsFormat::Millis, false),
+        }
+    }
+
+    /// Shift from UTC to wall-clock time in this zone at `millis`, for spreadsheet cells,
+    /// which carry no zone of their own.
+    fn offset_millis(self, millis: u64) -> i64 {
+        let seconds = match self {
+            Self::Utc => 0,
+            Self::Local => DateTime::from_timestamp_millis(millis as i64)
+                .unwrap_or_default()
+                .with_timezone(&chrono::Local)
+                .offset()
+                .fix()
+                .local_minus_utc(),
+            Self::Fixed(offset) => offset.local_minus_utc(),
+        };
+        i64::from(seconds) * 1000
+    }
+}
+
+/// NDJSON row with the rendered time added next to the record's fields.
+#[derive(serde::Serialize)]
+struct TimedRow<'a, T: serde::Serialize> {
+    #[serde(flatten)]
+    row: &'a T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+}
+
+struct ExportSink {
+    writer: ExportWriter,
+    time_zone: Option<ExportTimeZone>,
+}
+
+enum ExportWriter {
+    Ndjson(BufWriter<fs::File>),
+    Csv(BufWriter<fs::File>),
+    Xlsx(xlsx::SheetWriter),
+}
+
+impl ExportSink {
+    fn create(
+        output_path: &Path,
+        format: &str,
+        with_connection: bool,
+        time_zone: Option<ExportTimeZone>,
+    ) -> Result<Self> {
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create export directory: {}", parent.display())
+            })?;
+        }
+        let mut columns = Vec::with_capacity(10);
+        if with_connection {
+            columns.push("connection_id");
+        }
+        columns.extend(["id", "timestamp"]);
+        // Spreadsheet timestamps are already dates, shown in the chosen zone.
+        if time_zone.is_some() && export_extension(format) == "csv" {
+            columns.push("time");
+        }
+        columns.extend([
+            "topic",
+            "payload",
+            "qos",
+            "retain",
+            "direction",
+            "is_binary",
+        ]);
+
+        let writer = if export_extension(format) == "xlsx" {
+            ExportWriter::Xlsx(xlsx::SheetWriter::create(output_path, &columns)?)
+        } else {
+            let file = fs::File::create(output_path).with_context(|| {
+                format!("failed to create export file: {}", output_path.display())
+            })?;
+            let mut writer = BufWriter::new(file);
+            if export_extension(format) == "ndjson" {
+                ExportWriter::Ndjson(writer)
+            } else {
+                writer
+                    .write_all(format!("{}\n", columns.join(",")).as_bytes())
+                    .context("failed to write csv header")?;
+                ExportWriter::Csv(writer)
+            }
+        };
+        Ok(Self { writer, time_zone })
+    }
+
+    fn write(&mut self, connection_id: Option<&str>, record: HistoryMessageRecord) -> Result<()> {
+        let direction = if matches!(record.direction, MessageDirection::Out) {
+            "out"
+        } else {
+            "in"
+        };
+        let time = self.time_zone.map(|zone| zone.format(record.timestamp));
+        match &mut self.writer {
+            ExportWriter::Ndjson(writer) => {
+                let mut line = match connection_id {
+                    Some(connection_id) => serde_json::to_string(&TimedRow {
+                        row: &HistorySearchHit {
+                            connection_id: connection_id.to_string(),
+                            record,
+                        },
+                        time,
+                    }),
+                    None => serde_json::to_string(&TimedRow { row: &record, time }),
+                }
+                .context("failed to serialize ndjson row")?;
+                line.push('\n');
+                writer
+                    .write_all(line.as_bytes())
+                    .context("failed to write ndjson row")
+            }
+            ExportWriter::Csv(writer) => {
+                let mut line = connection_id
+                    .map(|connection_id| format!("{},", escape_csv(connection_id)))
+                    .unwrap_or_default();
+                line.push_str(&format!("{},{},", record.id, record.timestamp));
+                if let Some(time) = time {
+                    line.push_str(&format!("{time},"));
+                }
+                line.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    escape_csv(&record.topic),
+                    escape_csv(&record.payload),
+                    record.qos,
+                    if record.retain { 1 } else { 0 },
+                    direction,
+                    if record.is_binary { 1 } else { 0 }
+                ));
+                writer
+                    .write_all(line.as_bytes())
+                    .context("failed to write csv row")
+            }
+            ExportWriter::Xlsx(sheet) => {
+                let shifted = self.time_zone.map_or(record.timestamp, |zone| {
+                    record
+                        .timestamp
+                        .saturating_add_signed(zone.offset_millis(record.timestamp))
+                });
+                let mut cells = Vec::with_capacity(9);
+                if let Some(connection_id) = connection_id {
+                    cells.push(Cell::Text(connection_id));
+                }
+                cells.extend([
+                    Cell::Int(record.id),
+                    Cell::DateTime(shifted),
+                    Cell::Text(&record.topic),
+                    Cell::Text(&record.payload),
+                    Cell::Int(i64::from(record.qos)),
+                    Cell::Bool(record.retain),
+                    Cell::Text(direction),
+                    Cell::Bool(record.is_binary),
+                ]);
+                sheet.write_row(&cells)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.writer {
+            ExportWriter::Ndjson(mut writer) | ExportWriter::Csv(mut writer) => {
+                writer.flush().context("failed to flush export writer")
+            }
+            ExportWriter::Xlsx(sheet) => sheet.finish(),
+        }
+    }
+}
+
+pub(crate) fn escape_csv(input: &str) -> String {
     let escaped = input.replace('"', "\"\"");
     format!("\"{escaped}\"")
 }
 
+/// Runs a blocking export on the blocking pool and deletes its partial output when the job
+/// was cancelled midway.
+async fn run_export<F>(
+    output_path: PathBuf,
+    progress: &JobProgress,
+    what: &'static str,
+    export: F,
+) -> Result<HistoryExportResult>
+where
+    F: FnOnce(&Path, &JobProgress) -> Result<HistoryExportResult> + Send + 'static,
+{
+    let job = progress.clone();
+    let path = output_path.clone();
+    let result = tokio::task::spawn_blocking(move || export(&path, &job))
+        .await
+        .with_context(|| format!("{what} task join failed"))?;
+    if result.is_err() && progress.is_cancelled() {
+        let _ = fs::remove_file(&output_path);
+    }
+    result
+}
+
+fn count_range(conn: &Connection, from_ts: Option<i64>, to_ts: Option<i64>) -> Result<u64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM message_history
+         WHERE (?1 IS NULL OR ts_ms >= ?1)
+           AND (?2 IS NULL OR ts_ms <= ?2)",
+        params![from_ts, to_ts],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count as u64)
+    .context("failed to count export rows")
+}
+
+/// Default path for a history export, from the configured directory and filename template.
+/// Never overwrites: an existing file gets a numeric suffix.
+fn default_export_path(
+    app: &AppHandle,
+    exports_dir: &Path,
+    connection_id: &str,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    ext: &str,
+) -> Result<PathBuf> {
+    let config = config_store::load_config(app)?;
+    let dir = match config
+        .export_directory
+        .as_deref()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+    {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("failed to create export directory: {}", dir.display()))?;
+            dir
+        }
+        None => exports_dir.to_path_buf(),
+    };
+    let template = config
+        .export_filename_template
+        .as_deref()
+        .map(str::trim)
+        .filter(|template| !template.is_empty())
+        .unwrap_or(DEFAULT_EXPORT_TEMPLATE);
+    let connection_name = config
+        .connections
+        .iter()
+        .find(|profile| profile.id == connection_id)
+        .map_or(connection_id, |profile| profile.name.as_str());
+
+    let now = now_millis();
+    let file_name = template
+        .replace("{connectionId}", &safe_connection_id(connection_id))
+        .replace("{connection}", &safe_file_component(connection_name))
+        .replace(
+            "{from}",
+            &from_ts.map_or_else(|| "start".to_string(), format_export_time),
+        )
+        .replace("{to}", &format_export_time(to_ts.unwrap_or(now)))
+        .replace("{timestamp}", &now.to_string())
+        .replace("{ext}", ext);
+    let file_name = safe_file_component(&file_name);
+
+    let path = normalize_output_path(dir.join(&file_name), ext);
+    if !path.exists() {
+        return Ok(path);
+    }
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("export")
+        .to_string();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or(ext)
+        .to_string();
+    Ok((1..)
+        .map(|n| dir.join(format!("{stem}-{n}.{extension}")))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path))
+}
+
+fn format_export_time(millis: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis as i64)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y%m%d-%H%M%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| millis.to_string())
+}
+
+/// Replaces characters that are not allowed in file names on any desktop platform.
+fn safe_file_component(raw: &str) -> String {
+    let out = raw
+        .trim()
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect::<String>();
+    if out.is_empty() || out.chars().all(|ch| ch == '.') {
+        "export".to_string()
+    } else {
+        out
+    }
+}
+
 fn normalize_output_path(path: PathBuf, ext: &str) -> PathBuf {
     let has_ext = path.extension().and_then(|v| v.to_str()).is_some();
     if has_ext {
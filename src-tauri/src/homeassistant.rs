@@ -0,0 +1,260 @@
+use crate::models::{HaDevice, HaDiscoveryRegistry, HaEntity, HistoryMessageRecord};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_DISCOVERY_PREFIX: &str = "homeassistant";
+
+/// Abbreviations Home Assistant accepts in discovery payloads, limited to the keys we surface.
+const ENTITY_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("avty_t", "availability_topic"),
+    ("cmd_t", "command_topic"),
+    ("dev", "device"),
+    ("dev_cla", "device_class"),
+    ("json_attr_t", "json_attributes_topic"),
+    ("p", "platform"),
+    ("stat_t", "state_topic"),
+    ("uniq_id", "unique_id"),
+    ("unit_of_meas", "unit_of_measurement"),
+];
+
+const DEVICE_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("ids", "identifiers"),
+    ("mdl", "model"),
+    ("mf", "manufacturer"),
+    ("sw", "sw_version"),
+];
+
+struct DiscoveryTopic<'a> {
+    component: &'a str,
+    node_id: Option<&'a str>,
+    object_id: &'a str,
+}
+
+/// Rebuilds the device/entity registry from the latest discovery config per topic.
+pub fn build_registry(prefix: &str, records: &[HistoryMessageRecord]) -> HaDiscoveryRegistry {
+    let mut devices: BTreeMap<String, HaDevice> = BTreeMap::new();
+    let mut issues = Vec::new();
+    let mut entity_count = 0;
+
+    for record in records {
+        let Some(topic) = parse_topic(prefix, &record.topic) else {
+            continue;
+        };
+        // An empty retained config is how Home Assistant removes an entity.
+        if record.payload.trim().is_empty() {
+            continue;
+        }
+        let config = match serde_json::from_str::<Value>(&record.payload) {
+            Ok(Value::Object(config)) => expand(config, ENTITY_ABBREVIATIONS),
+            Ok(_) => {
+                issues.push(format!(
+                    "{}: discovery payload is not a JSON object",
+                    record.topic
+                ));
+                continue;
+            }
+            Err(error) => {
+                issues.push(format!("{}: invalid JSON ({error})", record.topic));
+                continue;
+            }
+        };
+
+        let entities = if topic.component == "device" {
+            device_components(&topic, &config, record)
+        } else {
+            vec![entity_from(
+                topic.component,
+                topic.node_id,
+                topic.object_id,
+                &config,
+                &config,
+                record,
+            )]
+        };
+
+        let device = parse_device(config.get("device"));
+        let key = device
+            .as_ref()
+            .and_then(|device| device.id.clone())
+            .unwrap_or_default();
+        let entry = devices.entry(key).or_insert_with(|| {
+            device.unwrap_or(HaDevice {
+                id: None,
+                name: None,
+                manufacturer: None,
+                model: None,
+                sw_version: None,
+                entities: Vec::new(),
+            })
+        });
+        entity_count += entities.len();
+        entry.entities.extend(entities);
+    }
+
+    HaDiscoveryRegistry {
+        prefix: prefix.to_string(),
+        devices: devices.into_values().collect(),
+        entity_count,
+        issues,
+    }
+}
+
+fn parse_topic<'a>(prefix: &str, topic: &'a str) -> Option<DiscoveryTopic<'a>> {
+    let rest = topic.strip_prefix(prefix)?.strip_prefix('/')?;
+    let rest = rest.strip_suffix("/config")?;
+    let parts = rest.split('/').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [component, object_id] => Some(DiscoveryTopic {
+            component,
+            node_id: None,
+            object_id,
+        }),
+        [component, node_id, object_id] => Some(DiscoveryTopic {
+            component,
+            node_id: Some(node_id),
+            object_id,
+        }),
+        _ => None,
+    }
+}
+
+/// Device-based discovery bundles several components under `cmps`, sharing `~`, `dev` and topics.
+fn device_components(
+    topic: &DiscoveryTopic<'_>,
+    config: &Map<String, Value>,
+    record: &HistoryMessageRecord,
+) -> Vec<HaEntity> {
+    let Some(Value::Object(components)) = config.get("cmps").or_else(|| config.get("components"))
+    else {
+        return Vec::new();
+    };
+
+    components
+        .iter()
+        .filter_map(|(object_id, component)| {
+            let Value::Object(component) = component else {
+                return None;
+            };
+            let component = expand(component.clone(), ENTITY_ABBREVIATIONS);
+            let platform = component
+                .get("platform")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+                .to_string();
+            Some(entity_from(
+                &platform,
+                Some(topic.object_id),
+                object_id,
+                &component,
+                config,
+                record,
+            ))
+        })
+        .collect()
+}
+
+fn entity_from(
+    component: &str,
+    node_id: Option<&str>,
+    object_id: &str,
+    config: &Map<String, Value>,
+    shared: &Map<String, Value>,
+    record: &HistoryMessageRecord,
+) -> HaEntity {
+    let base = shared.get("~").and_then(Value::as_str);
+    let text = |key: &str| {
+        config
+            .get(key)
+            .or_else(|| shared.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let topic = |key: &str| text(key).map(|value| expand_base(&value, base));
+
+    let mut availability_topics = Vec::new();
+    if let Some(value) = topic("availability_topic") {
+        availability_topics.push(value);
+    }
+    if let Some(Value::Array(entries)) = config
+        .get("availability")
+        .or_else(|| config.get("avty"))
+        .or_else(|| shared.get("availability"))
+        .or_else(|| shared.get("avty"))
+    {
+        for entry in entries {
+            let value = entry
+                .get("topic")
+                .or_else(|| entry.get("t"))
+                .and_then(Value::as_str);
+            if let Some(value) = value {
+                availability_topics.push(expand_base(value, base));
+            }
+        }
+    }
+
+    HaEntity {
+        component: component.to_string(),
+        node_id: node_id.map(str::to_string),
+        object_id: object_id.to_string(),
+        unique_id: text("unique_id"),
+        name: text("name"),
+        discovery_topic: record.topic.clone(),
+        state_topic: topic("state_topic"),
+        command_topic: topic("command_topic"),
+        availability_topics,
+        json_attributes_topic: topic("json_attributes_topic"),
+        device_class: text("device_class"),
+        unit_of_measurement: text("unit_of_measurement"),
+        updated_at: record.timestamp,
+    }
+}
+
+fn parse_device(value: Option<&Value>) -> Option<HaDevice> {
+    let Some(Value::Object(device)) = value else {
+        return None;
+    };
+    let device = expand(device.clone(), DEVICE_ABBREVIATIONS);
+    let text = |key: &str| device.get(key).and_then(Value::as_str).map(str::to_string);
+    let id = match device.get("identifiers") {
+        Some(Value::Array(ids)) => ids.first().and_then(Value::as_str).map(str::to_string),
+        Some(Value::String(id)) => Some(id.clone()),
+        _ => None,
+    }
+    .or_else(|| text("name"));
+
+    Some(HaDevice {
+        id,
+        name: text("name"),
+        manufacturer: text("manufacturer"),
+        model: text("model"),
+        sw_version: text("sw_version"),
+        entities: Vec::new(),
+    })
+}
+
+fn expand(config: Map<String, Value>, abbreviations: &[(&str, &str)]) -> Map<String, Value> {
+    config
+        .into_iter()
+        .map(|(key, value)| {
+            let key = abbreviations
+                .iter()
+                .find(|(short, _)| *short == key)
+                .map(|(_, long)| long.to_string())
+                .unwrap_or(key);
+            (key, value)
+        })
+        .collect()
+}
+
+fn expand_base(value: &str, base: Option<&str>) -> String {
+    let Some(base) = base else {
+        return value.to_string();
+    };
+    if let Some(rest) = value.strip_prefix('~') {
+        format!("{base}{rest}")
+    } else if let Some(rest) = value.strip_suffix('~') {
+        format!("{rest}{base}")
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,65 @@
+use crate::models::{ElasticsearchExportOptions, HistoryMessageRecord};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{Map, Value, json};
+
+pub const DEFAULT_TIMESTAMP_FIELD: &str = "@timestamp";
+
+const INVALID_INDEX_CHARS: &[char] = &['\\', '/', '*', '?', '"', '<', '>', '|', ' ', ',', '#', ':'];
+
+pub fn validate(options: &ElasticsearchExportOptions) -> Result<()> {
+    let index = options.index.trim();
+    if index.is_empty() {
+        return Err(anyhow!("Elasticsearch index name is required"));
+    }
+    if index != index.to_lowercase() {
+        return Err(anyhow!("Elasticsearch index names must be lowercase"));
+    }
+    if index.starts_with(['-', '_', '+']) || index == "." || index == ".." {
+        return Err(anyhow!(
+            "Elasticsearch index name cannot start with -, _ or +"
+        ));
+    }
+    if index.contains(INVALID_INDEX_CHARS) {
+        return Err(anyhow!(
+            "Elasticsearch index name contains an invalid character"
+        ));
+    }
+    Ok(())
+}
+
+/// Renders one record as a `_bulk` action line followed by its document line.
+pub fn to_bulk_pair(
+    options: &ElasticsearchExportOptions,
+    connection_id: &str,
+    record: &HistoryMessageRecord,
+) -> Option<String> {
+    let timestamp = DateTime::from_timestamp_millis(i64::try_from(record.timestamp).ok()?)?
+        .to_rfc3339_opts(SecondsFormat::Millis, true);
+    let timestamp_field = options
+        .timestamp_field
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_TIMESTAMP_FIELD);
+
+    let mut document = Map::new();
+    document.insert(timestamp_field.to_string(), Value::String(timestamp));
+    document.insert(
+        "connectionId".into(),
+        Value::String(connection_id.to_string()),
+    );
+    document.insert("topic".into(), Value::String(record.topic.clone()));
+    document.insert("payload".into(), Value::String(record.payload.clone()));
+    document.insert("isBinary".into(), json!(record.is_binary));
+    // Only structured payloads get a parsed copy so scalar payloads can't clash with object mappings.
+    if let Ok(parsed @ Value::Object(_)) = serde_json::from_str::<Value>(&record.payload) {
+        document.insert("payloadJson".into(), parsed);
+    }
+    document.insert("qos".into(), json!(record.qos));
+    document.insert("retain".into(), json!(record.retain));
+    document.insert("direction".into(), json!(record.direction));
+
+    let action = json!({ "index": { "_index": options.index.trim() } });
+    Some(format!("{action}\n{}", Value::Object(document)))
+}
@@ -0,0 +1,537 @@
+use crate::config_store;
+use crate::crash;
+use crate::models::{
+    FileWatchMode, FileWatcherConfig, FileWatcherMetrics, FileWatchersConfig, Payload,
+};
+use crate::mqtt::now_millis;
+use crate::state::AppState;
+use anyhow::{Context, Result, anyhow, bail};
+use dashmap::DashMap;
+use glob::{MatchOptions, Pattern};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, MissedTickBehavior};
+
+pub const FILE_WATCHERS_FILE_NAME: &str = "file-watchers.json";
+const MIN_POLL_INTERVAL_MS: u64 = 200;
+/// Files bigger than this are reported as failures instead of being published.
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+/// Stops a recursive scan from walking an entire disk by mistake.
+const MAX_WATCHED_FILES: usize = 10_000;
+
+#[derive(Clone, Default)]
+pub struct FileWatchManager {
+    inner: Arc<FileWatchInner>,
+}
+
+#[derive(Default)]
+struct FileWatchInner {
+    config: RwLock<Option<FileWatchersConfig>>,
+    running: DashMap<String, RunningWatcher>,
+}
+
+struct RunningWatcher {
+    task: JoinHandle<()>,
+    counters: Arc<WatchCounters>,
+}
+
+#[derive(Default)]
+struct WatchCounters {
+    started_at: AtomicU64,
+    watched_files: AtomicU64,
+    published: AtomicU64,
+    failed: AtomicU64,
+    last_published_at: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl WatchCounters {
+    fn record_error(&self, error: &anyhow::Error) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut slot) = self.last_error.lock() {
+            *slot = Some(format!("{error:#}"));
+        }
+    }
+}
+
+/// Size and modification time; a file counts as changed when either differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileSignature {
+    len: u64,
+    modified_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FileEvent {
+    Created,
+    Modified,
+    Removed,
+}
+
+impl FileEvent {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+impl FileWatchManager {
+    pub async fn config(&self, app: &AppHandle) -> Result<FileWatchersConfig> {
+        if let Some(config) = self.inner.config.read().await.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let mut slot = self.inner.config.write().await;
+        if let Some(config) = slot.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let loaded =
+            config_store::load_document::<FileWatchersConfig>(app, FILE_WATCHERS_FILE_NAME)?;
+        *slot = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    pub async fn save_watchers(
+        &self,
+        app: &AppHandle,
+        watchers: Vec<FileWatcherConfig>,
+    ) -> Result<FileWatchersConfig> {
+        for watcher in &watchers {
+            validate_watcher(watcher)?;
+        }
+
+        let mut slot = self.inner.config.write().await;
+        let mut next = match slot.as_ref() {
+            Some(config) => config.clone(),
+            None => {
+                config_store::load_document::<FileWatchersConfig>(app, FILE_WATCHERS_FILE_NAME)?
+            }
+        };
+        next.watchers = watchers;
+        config_store::save_document(app, FILE_WATCHERS_FILE_NAME, &next)?;
+        *slot = Some(next.clone());
+        Ok(next)
+    }
+
+    pub async fn start(&self, app: &AppHandle, watcher_id: &str) -> Result<FileWatcherMetrics> {
+        let config = self
+            .config(app)
+            .await?
+            .watchers
+            .into_iter()
+            .find(|watcher| watcher.id == watcher_id)
+            .ok_or_else(|| anyhow!("file watcher not found: {watcher_id}"))?;
+        validate_watcher(&config)?;
+        if !Path::new(&config.path).is_dir() {
+            bail!("watched directory does not exist: {}", config.path);
+        }
+
+        self.stop(watcher_id);
+
+        let counters = Arc::new(WatchCounters::default());
+        counters.started_at.store(now_millis(), Ordering::Relaxed);
+        let task = tokio::spawn(crash::monitored(
+            "file-watcher",
+            run_watcher(app.clone(), config, Arc::clone(&counters)),
+        ));
+
+        let watcher = RunningWatcher { task, counters };
+        let metrics = metrics_of(watcher_id, Some(&watcher));
+        self.inner.running.insert(watcher_id.to_string(), watcher);
+        Ok(metrics)
+    }
+
+    pub fn stop(&self, watcher_id: &str) -> FileWatcherMetrics {
+        let removed = self.inner.running.remove(watcher_id).map(|(_, watcher)| {
+            watcher.task.abort();
+            watcher
+        });
+        let mut metrics = metrics_of(watcher_id, removed.as_ref());
+        metrics.running = false;
+        metrics
+    }
+
+    pub async fn metrics(&self, app: &AppHandle) -> Result<Vec<FileWatcherMetrics>> {
+        let config = self.config(app).await?;
+        Ok(config
+            .watchers
+            .iter()
+            .map(|watcher| {
+                let running = self.inner.running.get(&watcher.id);
+                metrics_of(&watcher.id, running.as_deref())
+            })
+            .collect())
+    }
+}
+
+/// Polls the directory and publishes files once they have settled: a change is only published
+/// when the next scan sees the same size and mtime, so half-written files aren't sent.
+async fn run_watcher(app: AppHandle, config: FileWatcherConfig, counters: Arc<WatchCounters>) {
+    let root = PathBuf::from(&config.path);
+    let pattern = match Pattern::new(&config.pattern) {
+        Ok(pattern) => pattern,
+        Err(error) => {
+            counters.record_error(&anyhow!("invalid file pattern: {error}"));
+            return;
+        }
+    };
+
+    let mut known = HashMap::new();
+    if !config.publish_existing {
+        match scan(&root, &pattern, config.recursive).await {
+            Ok(files) => known = files,
+            Err(error) => counters.record_error(&error),
+        }
+    }
+    let mut pending: HashMap<PathBuf, FileSignature> = HashMap::new();
+
+    let mut ticker = time::interval(Duration::from_millis(
+        config.poll_interval_ms.max(MIN_POLL_INTERVAL_MS),
+    ));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let files = match scan(&root, &pattern, config.recursive).await {
+            Ok(files) => files,
+            Err(error) => {
+                counters.record_error(&error);
+                continue;
+            }
+        };
+        counters
+            .watched_files
+            .store(files.len() as u64, Ordering::Relaxed);
+
+        for (path, signature) in &files {
+            if known.get(path) == Some(signature) {
+                pending.remove(path);
+                continue;
+            }
+            if pending.get(path) != Some(signature) {
+                pending.insert(path.clone(), *signature);
+                continue;
+            }
+            pending.remove(path);
+            let event = if known.insert(path.clone(), *signature).is_some() {
+                FileEvent::Modified
+            } else {
+                FileEvent::Created
+            };
+            publish_change(
+                &app,
+                &config,
+                &root,
+                path,
+                Some(*signature),
+                event,
+                &counters,
+            )
+            .await;
+        }
+
+        let removed = known
+            .keys()
+            .filter(|path| !files.contains_key(*path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in removed {
+            known.remove(&path);
+            if config.mode == FileWatchMode::Notification {
+                publish_change(
+                    &app,
+                    &config,
+                    &root,
+                    &path,
+                    None,
+                    FileEvent::Removed,
+                    &counters,
+                )
+                .await;
+            }
+        }
+        pending.retain(|path, _| files.contains_key(path));
+    }
+}
+
+async fn publish_change(
+    app: &AppHandle,
+    config: &FileWatcherConfig,
+    root: &Path,
+    path: &Path,
+    signature: Option<FileSignature>,
+    event: FileEvent,
+    counters: &WatchCounters,
+) {
+    match publish_file(app, config, root, path, signature, event).await {
+        Ok(()) => {
+            counters.published.fetch_add(1, Ordering::Relaxed);
+            counters
+                .last_published_at
+                .store(now_millis(), Ordering::Relaxed);
+        }
+        Err(error) => counters.record_error(&error),
+    }
+}
+
+async fn publish_file(
+    app: &AppHandle,
+    config: &FileWatcherConfig,
+    root: &Path,
+    path: &Path,
+    signature: Option<FileSignature>,
+    event: FileEvent,
+) -> Result<()> {
+    let relative = relative_path(root, path);
+    let topic = render_topic(&config.topic_template, &relative, event);
+    if topic.is_empty() || topic.contains(['+', '#', '\0']) {
+        bail!("topic {topic:?} rendered for {relative} is not a valid topic name");
+    }
+
+    let payload = match config.mode {
+        FileWatchMode::Contents => {
+            if signature.is_some_and(|signature| signature.len > MAX_FILE_BYTES) {
+                bail!("{relative} is larger than {MAX_FILE_BYTES} bytes");
+            }
+            let path = path.to_path_buf();
+            let relative = relative.clone();
+            tokio::task::spawn_blocking(move || read_contents(&path, &relative))
+                .await
+                .context("read file task join failed")??
+        }
+        FileWatchMode::Notification => Payload::from(
+            serde_json::json!({
+                "event": event.as_str(),
+                "path": relative,
+                "file": path.display().to_string(),
+                "size": signature.map(|signature| signature.len),
+                "modifiedAt": signature.map(|signature| signature.modified_ms),
+            })
+            .to_string(),
+        ),
+    };
+
+    crate::profiles::refuse_production(app, &config.connection_id)?;
+    let state = app.state::<AppState>();
+    state.mqtt_manager.publish(
+        &config.connection_id,
+        topic.clone(),
+        payload.clone(),
+        config.qos,
+        config.retain,
+        Vec::new(),
+    )?;
+    state.recent.record_outgoing(
+        &config.connection_id,
+        &topic,
+        &payload,
+        config.qos,
+        config.retain,
+    );
+    state
+        .history_manager
+        .append_outgoing(
+            app,
+            &config.connection_id,
+            &topic,
+            payload,
+            config.qos,
+            config.retain,
+            Vec::new(),
+        )
+        .await
+}
+
+async fn scan(
+    root: &Path,
+    pattern: &Pattern,
+    recursive: bool,
+) -> Result<HashMap<PathBuf, FileSignature>> {
+    let root = root.to_path_buf();
+    let pattern = pattern.clone();
+    tokio::task::spawn_blocking(move || scan_dir(&root, &pattern, recursive))
+        .await
+        .context("scan directory task join failed")?
+}
+
+fn scan_dir(
+    root: &Path,
+    pattern: &Pattern,
+    recursive: bool,
+) -> Result<HashMap<PathBuf, FileSignature>> {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    let match_relative = pattern.as_str().contains('/');
+
+    let mut files = HashMap::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("failed to read directory: {}", dir.display()))?;
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if metadata.is_dir() {
+                if recursive {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if !metadata.is_file() {
+                continue;
+            }
+
+            let matched = if match_relative {
+                pattern.matches_with(&relative_path(root, &path), options)
+            } else {
+                pattern.matches_with(&entry.file_name().to_string_lossy(), options)
+            };
+            if !matched {
+                continue;
+            }
+            if files.len() >= MAX_WATCHED_FILES {
+                bail!("more than {MAX_WATCHED_FILES} files match; narrow the pattern");
+            }
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default();
+            files.insert(
+                path,
+                FileSignature {
+                    len: metadata.len(),
+                    modified_ms,
+                },
+            );
+        }
+    }
+    Ok(files)
+}
+
+/// A file's bytes as the payload for "contents" mode, binary files included.
+fn read_contents(path: &Path, relative: &str) -> Result<Payload> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {relative}"))?;
+    Ok(Payload::from_bytes(bytes.into()))
+}
+
+/// `path` relative to the watched directory, always with `/` separators.
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn render_topic(template: &str, relative: &str, event: FileEvent) -> String {
+    let (dir, name) = relative.rsplit_once('/').unwrap_or(("", relative));
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, ext),
+        _ => (name, ""),
+    };
+    template
+        .replace("{path}", relative)
+        .replace("{dir}", dir)
+        .replace("{name}", name)
+        .replace("{stem}", stem)
+        .replace("{ext}", ext)
+        .replace("{event}", event.as_str())
+        .replace("//", "/")
+        .trim_matches('/')
+        .to_string()
+}
+
+fn validate_watcher(watcher: &FileWatcherConfig) -> Result<()> {
+    if watcher.path.trim().is_empty() {
+        bail!("file watcher '{}' has no directory", watcher.name);
+    }
+    if watcher.connection_id.trim().is_empty() {
+        bail!("file watcher '{}' has no connection", watcher.name);
+    }
+    Pattern::new(&watcher.pattern)
+        .map_err(|error| anyhow!("file watcher '{}' pattern: {error}", watcher.name))?;
+    if watcher.topic_template.trim().is_empty() {
+        bail!("file watcher '{}' has no topic", watcher.name);
+    }
+    if watcher.topic_template.contains(['+', '#']) {
+        bail!(
+            "file watcher '{}' topic cannot contain the wildcards '+' or '#'",
+            watcher.name
+        );
+    }
+    if watcher.qos > 2 {
+        bail!(
+            "file watcher '{}' has invalid QoS {}",
+            watcher.name,
+            watcher.qos
+        );
+    }
+    Ok(())
+}
+
+fn metrics_of(watcher_id: &str, watcher: Option<&RunningWatcher>) -> FileWatcherMetrics {
+    let Some(watcher) = watcher else {
+        return FileWatcherMetrics {
+            id: watcher_id.to_string(),
+            running: false,
+            started_at: None,
+            watched_files: 0,
+            published: 0,
+            failed: 0,
+            last_published_at: None,
+            last_error: None,
+        };
+    };
+
+    let counters = &watcher.counters;
+    let last_published_at = counters.last_published_at.load(Ordering::Relaxed);
+    FileWatcherMetrics {
+        id: watcher_id.to_string(),
+        running: !watcher.task.is_finished(),
+        started_at: Some(counters.started_at.load(Ordering::Relaxed)),
+        watched_files: counters.watched_files.load(Ordering::Relaxed),
+        published: counters.published.load(Ordering::Relaxed),
+        failed: counters.failed.load(Ordering::Relaxed),
+        last_published_at: (last_published_at > 0).then_some(last_published_at),
+        last_error: counters
+            .last_error
+            .lock()
+            .ok()
+            .and_then(|slot| slot.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_file_contents_are_published_unchanged() {
+        let bytes = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0xff];
+        let path = std::env::temp_dir().join(format!("file-watch-{}.bin", std::process::id()));
+        fs::write(&path, bytes).unwrap();
+        let payload = read_contents(&path, "image.bin");
+        let _ = fs::remove_file(&path);
+
+        let payload = payload.unwrap();
+        assert!(payload.is_binary());
+        assert_eq!(payload.into_bytes().as_ref(), bytes);
+    }
+}
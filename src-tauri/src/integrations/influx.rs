@@ -0,0 +1,175 @@
+use crate::integrations::{http_client, validate_http_url};
+use crate::models::{InfluxColumnMapping, InfluxMapping};
+use crate::rules::json_path;
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+
+/// Renders one message as an InfluxDB line-protocol record with millisecond precision.
+pub fn to_line(
+    mapping: &InfluxMapping,
+    topic: &str,
+    payload: &str,
+    timestamp_ms: u64,
+) -> Option<String> {
+    let segments = topic.split('/').collect::<Vec<_>>();
+    let parsed = serde_json::from_str::<Value>(payload).ok();
+
+    let measurement = expand_measurement(&mapping.measurement, topic, &segments);
+    if measurement.is_empty() {
+        return None;
+    }
+
+    let mut line = escape_measurement(&measurement);
+    for tag in &mapping.tags {
+        let Some(value) = resolve_column(tag, &segments, parsed.as_ref()) else {
+            continue;
+        };
+        let text = match value {
+            Value::String(text) => text,
+            other => other.to_string(),
+        };
+        if text.is_empty() {
+            continue;
+        }
+        line.push(',');
+        line.push_str(&escape_key(&tag.name));
+        line.push('=');
+        line.push_str(&escape_key(&text));
+    }
+
+    let mut fields = Vec::new();
+    if mapping.fields.is_empty() {
+        let field = match &parsed {
+            Some(Value::Number(_)) | Some(Value::Bool(_)) => parsed.clone()?,
+            _ => Value::String(payload.to_string()),
+        };
+        fields.push(format!("value={}", format_field(&field, false)?));
+    } else {
+        for column in &mapping.fields {
+            if let Some(value) = resolve_column(column, &segments, parsed.as_ref()) {
+                if let Some(formatted) = format_field(&value, column.integer) {
+                    fields.push(format!("{}={formatted}", escape_key(&column.name)));
+                }
+            }
+        }
+    }
+    if fields.is_empty() {
+        return None;
+    }
+
+    line.push(' ');
+    line.push_str(&fields.join(","));
+    line.push(' ');
+    line.push_str(&timestamp_ms.to_string());
+    Some(line)
+}
+
+pub async fn write(
+    url: &str,
+    org: Option<&str>,
+    bucket: &str,
+    token: Option<&str>,
+    body: String,
+) -> Result<()> {
+    validate_http_url(url, "InfluxDB")?;
+    if bucket.trim().is_empty() {
+        return Err(anyhow!("InfluxDB bucket is required"));
+    }
+
+    let endpoint = format!("{}/api/v2/write", url.trim().trim_end_matches('/'));
+    let mut query = vec![("bucket", bucket.trim()), ("precision", "ms")];
+    if let Some(org) = org.map(str::trim).filter(|value| !value.is_empty()) {
+        query.push(("org", org));
+    }
+
+    let mut request = http_client()
+        .post(endpoint)
+        .query(&query)
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(body);
+    if let Some(token) = token.map(str::trim).filter(|value| !value.is_empty()) {
+        request = request.header("Authorization", format!("Token {token}"));
+    }
+
+    let response = request.send().await.context("InfluxDB write failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        let detail = response.text().await.unwrap_or_default();
+        return Err(anyhow!("InfluxDB responded with {status}: {detail}"));
+    }
+    Ok(())
+}
+
+fn expand_measurement(template: &str, topic: &str, segments: &[&str]) -> String {
+    let mut out = template.replace("{topic}", topic);
+    for (index, segment) in segments.iter().enumerate() {
+        out = out.replace(&format!("{{{index}}}"), segment);
+    }
+    out.trim().to_string()
+}
+
+fn resolve_column(
+    column: &InfluxColumnMapping,
+    segments: &[&str],
+    parsed: Option<&Value>,
+) -> Option<Value> {
+    if let Some(index) = column.segment {
+        return segments
+            .get(index)
+            .map(|segment| Value::String(segment.to_string()));
+    }
+    let path = column.path.as_deref()?;
+    json_path::select(parsed?, path).cloned()
+}
+
+/// Numbers are floats unless the column asks for integers, so a field whose values happen
+/// to be whole in one message doesn't conflict with the same field's type in the next.
+fn format_field(value: &Value, integer: bool) -> Option<String> {
+    match value {
+        Value::Number(number) if integer && (number.is_i64() || number.is_u64()) => {
+            Some(format!("{number}i"))
+        }
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::String(text) => Some(format!(
+            "\"{}\"",
+            text.replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+        Value::Null => None,
+        other => Some(format!(
+            "\"{}\"",
+            other.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+    }
+}
+
+/// Measurement names only escape commas and spaces; an `=` is literal there.
+fn escape_measurement(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for ch in raw.chars() {
+        match ch {
+            ',' | ' ' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn escape_key(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for ch in raw.chars() {
+        match ch {
+            ',' | '=' | ' ' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
@@ -0,0 +1,413 @@
+use crate::config_store;
+use crate::crash;
+use crate::models::{
+    IntegrationsConfig, KafkaBridgeConfig, KafkaBridgeMetrics, MessageDirection, MqttBatchItem,
+};
+use crate::mqtt::{now_millis, topic_matches};
+use anyhow::{Context, Result, anyhow};
+use dashmap::DashMap;
+use rskafka::chrono::{DateTime, Utc};
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::{Client, ClientBuilder, Credentials, SaslConfig};
+use rskafka::record::Record;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+use tokio::sync::{RwLock, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration};
+
+pub const INTEGRATIONS_FILE_NAME: &str = "integrations.json";
+const PRODUCE_BATCH_MAX: usize = 500;
+/// Messages a bridge buffers while Kafka is slow or down; further ones are counted as failed.
+const QUEUE_CAPACITY: usize = 10_000;
+const RETRY_DELAY_SECS: u64 = 5;
+const MAX_RETRY_DELAY_SECS: u64 = 60;
+/// Produce attempts per batch before its records are given up, so a record Kafka always
+/// rejects can't hold up the bridge forever.
+const MAX_PRODUCE_ATTEMPTS: u32 = 5;
+
+#[derive(Clone, Default)]
+pub struct KafkaBridgeManager {
+    inner: Arc<KafkaInner>,
+}
+
+#[derive(Default)]
+struct KafkaInner {
+    config: RwLock<Option<IntegrationsConfig>>,
+    running: DashMap<String, RunningBridge>,
+}
+
+struct RunningBridge {
+    config: KafkaBridgeConfig,
+    tx: mpsc::Sender<MqttBatchItem>,
+    task: JoinHandle<()>,
+    counters: Arc<BridgeCounters>,
+}
+
+#[derive(Default)]
+struct BridgeCounters {
+    started_at: AtomicU64,
+    forwarded: AtomicU64,
+    failed: AtomicU64,
+    pending: AtomicU64,
+    last_forwarded_at: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl BridgeCounters {
+    fn record_error(&self, error: &anyhow::Error) {
+        if let Ok(mut slot) = self.last_error.lock() {
+            *slot = Some(format!("{error:#}"));
+        }
+    }
+}
+
+impl KafkaBridgeManager {
+    pub async fn config(&self, app: &AppHandle) -> Result<IntegrationsConfig> {
+        if let Some(config) = self.inner.config.read().await.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let mut slot = self.inner.config.write().await;
+        if let Some(config) = slot.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let loaded =
+            config_store::load_document::<IntegrationsConfig>(app, INTEGRATIONS_FILE_NAME)?;
+        *slot = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    pub async fn save_bridges(
+        &self,
+        app: &AppHandle,
+        bridges: Vec<KafkaBridgeConfig>,
+    ) -> Result<IntegrationsConfig> {
+        for bridge in &bridges {
+            validate_bridge(bridge)?;
+        }
+
+        // Read, change and save under one write lock so concurrent saves can't drop each other.
+        let mut slot = self.inner.config.write().await;
+        let mut next = match slot.as_ref() {
+            Some(config) => config.clone(),
+            None => config_store::load_document::<IntegrationsConfig>(app, INTEGRATIONS_FILE_NAME)?,
+        };
+        next.kafka_bridges = bridges;
+        config_store::save_document(app, INTEGRATIONS_FILE_NAME, &next)?;
+        *slot = Some(next.clone());
+        Ok(next)
+    }
+
+    pub async fn start(&self, app: &AppHandle, bridge_id: &str) -> Result<KafkaBridgeMetrics> {
+        let config = self
+            .config(app)
+            .await?
+            .kafka_bridges
+            .into_iter()
+            .find(|bridge| bridge.id == bridge_id)
+            .ok_or_else(|| anyhow!("kafka bridge not found: {bridge_id}"))?;
+        validate_bridge(&config)?;
+
+        self.stop(bridge_id);
+
+        let (tx, rx) = mpsc::channel::<MqttBatchItem>(QUEUE_CAPACITY);
+        let counters = Arc::new(BridgeCounters::default());
+        counters.started_at.store(now_millis(), Ordering::Relaxed);
+        let task = tokio::spawn(crash::monitored(
+            "kafka-bridge",
+            run_bridge(config.clone(), rx, Arc::clone(&counters)),
+        ));
+
+        let bridge = RunningBridge {
+            config,
+            tx,
+            task,
+            counters,
+        };
+        let metrics = metrics_of(bridge_id, Some(&bridge));
+        self.inner.running.insert(bridge_id.to_string(), bridge);
+        Ok(metrics)
+    }
+
+    pub fn stop(&self, bridge_id: &str) -> KafkaBridgeMetrics {
+        let removed = self.inner.running.remove(bridge_id).map(|(_, bridge)| {
+            bridge.task.abort();
+            bridge
+        });
+        let mut metrics = metrics_of(bridge_id, removed.as_ref());
+        metrics.running = false;
+        metrics
+    }
+
+    pub async fn metrics(&self, app: &AppHandle) -> Result<Vec<KafkaBridgeMetrics>> {
+        let config = self.config(app).await?;
+        Ok(config
+            .kafka_bridges
+            .iter()
+            .map(|bridge| {
+                let running = self.inner.running.get(&bridge.id);
+                metrics_of(&bridge.id, running.as_deref())
+            })
+            .collect())
+    }
+
+    pub fn dispatch(&self, connection_id: &str, batch: &[MqttBatchItem]) {
+        for bridge in self.inner.running.iter() {
+            if bridge.config.connection_id != connection_id {
+                continue;
+            }
+            for item in batch {
+                if !matches!(item.direction, MessageDirection::In)
+                    || !topic_matches(&bridge.config.topic_filter, &item.topic)
+                {
+                    continue;
+                }
+                match bridge.tx.try_send(item.clone()) {
+                    Ok(()) => {
+                        bridge.counters.pending.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(mpsc::error::TrySendError::Full(_)) => {
+                        bridge.counters.failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+fn validate_bridge(bridge: &KafkaBridgeConfig) -> Result<()> {
+    if bridge.brokers.iter().all(|broker| broker.trim().is_empty()) {
+        return Err(anyhow!("kafka bridge '{}' has no brokers", bridge.name));
+    }
+    if bridge.topic_filter.trim().is_empty() {
+        return Err(anyhow!(
+            "kafka bridge '{}' has an empty topic filter",
+            bridge.name
+        ));
+    }
+    if bridge.kafka_topic.trim().is_empty() {
+        return Err(anyhow!("kafka bridge '{}' has no kafka topic", bridge.name));
+    }
+    Ok(())
+}
+
+fn metrics_of(bridge_id: &str, bridge: Option<&RunningBridge>) -> KafkaBridgeMetrics {
+    let Some(bridge) = bridge else {
+        return KafkaBridgeMetrics {
+            id: bridge_id.to_string(),
+            running: false,
+            started_at: None,
+            forwarded: 0,
+            failed: 0,
+            pending: 0,
+            last_forwarded_at: None,
+            last_error: None,
+        };
+    };
+
+    let counters = &bridge.counters;
+    let last_forwarded_at = counters.last_forwarded_at.load(Ordering::Relaxed);
+    KafkaBridgeMetrics {
+        id: bridge_id.to_string(),
+        running: !bridge.task.is_finished(),
+        started_at: Some(counters.started_at.load(Ordering::Relaxed)),
+        forwarded: counters.forwarded.load(Ordering::Relaxed),
+        failed: counters.failed.load(Ordering::Relaxed),
+        pending: counters.pending.load(Ordering::Relaxed),
+        last_forwarded_at: (last_forwarded_at > 0).then_some(last_forwarded_at),
+        last_error: counters
+            .last_error
+            .lock()
+            .ok()
+            .and_then(|slot| slot.clone()),
+    }
+}
+
+async fn run_bridge(
+    config: KafkaBridgeConfig,
+    mut rx: mpsc::Receiver<MqttBatchItem>,
+    counters: Arc<BridgeCounters>,
+) {
+    let mut client: Option<Client> = None;
+    let mut partitions: HashMap<String, PartitionClient> = HashMap::new();
+
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < PRODUCE_BATCH_MAX {
+            match rx.try_recv() {
+                Ok(item) => batch.push(item),
+                Err(_) => break,
+            }
+        }
+
+        let mut grouped: HashMap<String, Vec<Record>> = HashMap::new();
+        for item in &batch {
+            grouped
+                .entry(kafka_topic_for(&config.kafka_topic, &item.topic))
+                .or_default()
+                .push(to_record(item));
+        }
+
+        for (topic, records) in grouped {
+            let count = records.len() as u64;
+            let mut attempt = 0;
+            let delivered = loop {
+                attempt += 1;
+                let result = produce(
+                    &config,
+                    &mut client,
+                    &mut partitions,
+                    &topic,
+                    records.clone(),
+                )
+                .await;
+                let Err(error) = result else {
+                    break true;
+                };
+                counters.record_error(&error);
+                // Drop cached clients so the retry reconnects from scratch.
+                client = None;
+                partitions.clear();
+                if attempt >= MAX_PRODUCE_ATTEMPTS {
+                    break false;
+                }
+                // New messages queue up in the bounded channel meanwhile.
+                let delay = (RETRY_DELAY_SECS << (attempt - 1)).min(MAX_RETRY_DELAY_SECS);
+                time::sleep(Duration::from_secs(delay)).await;
+            };
+            counters.pending.fetch_sub(count, Ordering::Relaxed);
+            if delivered {
+                counters.forwarded.fetch_add(count, Ordering::Relaxed);
+                counters
+                    .last_forwarded_at
+                    .store(now_millis(), Ordering::Relaxed);
+            } else {
+                counters.failed.fetch_add(count, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+async fn produce(
+    config: &KafkaBridgeConfig,
+    client: &mut Option<Client>,
+    partitions: &mut HashMap<String, PartitionClient>,
+    topic: &str,
+    records: Vec<Record>,
+) -> Result<()> {
+    if client.is_none() {
+        *client = Some(build_client(config).await?);
+    }
+    let Some(connected) = client.as_ref() else {
+        return Err(anyhow!("kafka client unavailable"));
+    };
+
+    if !partitions.contains_key(topic) {
+        let partition = connected
+            .partition_client(
+                topic.to_string(),
+                config.partition.unwrap_or(0),
+                UnknownTopicHandling::Error,
+            )
+            .await
+            .with_context(|| format!("failed to open kafka topic {topic}"))?;
+        partitions.insert(topic.to_string(), partition);
+    }
+
+    let Some(partition) = partitions.get(topic) else {
+        return Err(anyhow!("kafka partition client unavailable"));
+    };
+    partition
+        .produce(records, Compression::NoCompression)
+        .await
+        .with_context(|| format!("failed to produce to kafka topic {topic}"))?;
+    Ok(())
+}
+
+async fn build_client(config: &KafkaBridgeConfig) -> Result<Client> {
+    let brokers = config
+        .brokers
+        .iter()
+        .map(|broker| broker.trim().to_string())
+        .filter(|broker| !broker.is_empty())
+        .collect::<Vec<_>>();
+    let mut builder = ClientBuilder::new(brokers).client_id("mqtt-nexus");
+
+    if let Some(username) = config
+        .username
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        let credentials = Credentials::new(
+            username.to_string(),
+            config.password.clone().unwrap_or_default(),
+        );
+        let sasl = match config
+            .sasl_mechanism
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("scram-sha-256") => SaslConfig::ScramSha256(credentials),
+            Some("scram-sha-512") => SaslConfig::ScramSha512(credentials),
+            _ => SaslConfig::Plain(credentials),
+        };
+        builder = builder.sasl_config(sasl);
+    }
+
+    if config.tls {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        // Both crypto backends end up in the dependency graph, so pick one explicitly.
+        let tls = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .context("failed to configure kafka TLS")?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+        builder = builder.tls_config(Arc::new(tls));
+    }
+
+    builder
+        .build()
+        .await
+        .context("failed to connect to kafka brokers")
+}
+
+/// `{topic}` becomes the MQTT topic with `/` mapped to `.`; `{N}` picks a single topic level.
+fn kafka_topic_for(template: &str, mqtt_topic: &str) -> String {
+    let mut out = template.replace("{topic}", &mqtt_topic.replace('/', "."));
+    for (index, segment) in mqtt_topic.split('/').enumerate() {
+        out = out.replace(&format!("{{{index}}}"), segment);
+    }
+    out.trim().to_string()
+}
+
+fn to_record(item: &MqttBatchItem) -> Record {
+    let mut headers = BTreeMap::new();
+    headers.insert("mqtt_topic".to_string(), item.topic.as_bytes().to_vec());
+    headers.insert("mqtt_qos".to_string(), item.qos.to_string().into_bytes());
+    headers.insert(
+        "mqtt_retain".to_string(),
+        if item.retain {
+            b"1".to_vec()
+        } else {
+            b"0".to_vec()
+        },
+    );
+
+    Record {
+        key: Some(item.topic.as_bytes().to_vec()),
+        value: Some(item.payload.as_bytes().to_vec()),
+        headers,
+        timestamp: DateTime::<Utc>::from_timestamp_millis(item.timestamp as i64)
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,31 @@
+pub mod elasticsearch;
+pub mod file_watch;
+pub mod influx;
+pub mod kafka;
+pub mod mosquitto;
+pub mod socket_tap;
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const HTTP_TIMEOUT_SECS: u64 = 10;
+
+pub fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+pub fn validate_http_url(url: &str, label: &str) -> anyhow::Result<()> {
+    let url = url.trim();
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "{label} URL must start with http:// or https://"
+    ))
+}
@@ -0,0 +1,145 @@
+use crate::models::HistoryMessageRecord;
+use anyhow::{Result, bail};
+use chrono::DateTime;
+use serde_json::json;
+use std::fmt::Write;
+
+/// A `mosquitto_sub -F` format string, parsed once so each exported message only renders.
+///
+/// Supports `%t` topic, `%p` payload, `%q` QoS, `%r` retain flag, `%l` payload length, `%m`
+/// packet id, `%I` ISO-8601 local time, `%U` Unix time with nanoseconds, `%x` / `%X` hex
+/// payload, `%j` JSON and `%%`, plus the `\n`, `\t`, `\r`, `\a`, `\e`, `\v`, `\0` and `\\`
+/// escapes. Like mosquitto_sub, every message is followed by a newline.
+pub struct LineFormat {
+    pieces: Vec<Piece>,
+}
+
+enum Piece {
+    Text(String),
+    Topic,
+    Payload,
+    Qos,
+    Retain,
+    Length,
+    MessageId,
+    IsoTime,
+    UnixTime,
+    Hex,
+    HexUpper,
+    Json,
+}
+
+impl LineFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        if format.is_empty() {
+            bail!("output format is required");
+        }
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = format.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '%' => {
+                    let piece = match chars.next() {
+                        Some('%') => {
+                            text.push('%');
+                            continue;
+                        }
+                        Some('t') => Piece::Topic,
+                        Some('p') => Piece::Payload,
+                        Some('q') => Piece::Qos,
+                        Some('r') => Piece::Retain,
+                        Some('l') => Piece::Length,
+                        Some('m') => Piece::MessageId,
+                        Some('I') => Piece::IsoTime,
+                        Some('U') => Piece::UnixTime,
+                        Some('x') => Piece::Hex,
+                        Some('X') => Piece::HexUpper,
+                        Some('j') => Piece::Json,
+                        Some(other) => bail!("unsupported format token '%{other}'"),
+                        None => bail!("format ends with a lone '%'"),
+                    };
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(piece);
+                }
+                '\\' => text.push(match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('a') => '\x07',
+                    Some('e') => '\x1b',
+                    Some('v') => '\x0b',
+                    Some('0') => '\0',
+                    Some('\\') => '\\',
+                    Some(other) => bail!("unsupported escape '\\{other}'"),
+                    None => bail!("format ends with a lone '\\'"),
+                }),
+                other => text.push(other),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(Self { pieces })
+    }
+
+    pub fn render(&self, record: &HistoryMessageRecord) -> String {
+        let mut line = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => line.push_str(text),
+                Piece::Topic => line.push_str(&record.topic),
+                Piece::Payload => line.push_str(&record.payload),
+                Piece::Qos => line.push_str(&record.qos.to_string()),
+                Piece::Retain => line.push(if record.retain { '1' } else { '0' }),
+                Piece::Length => line.push_str(&record.payload.len().to_string()),
+                Piece::MessageId => line.push_str(&record.packet_id.unwrap_or(0).to_string()),
+                Piece::IsoTime => line.push_str(&iso_time(record.timestamp)),
+                Piece::UnixTime => {
+                    let _ = write!(
+                        line,
+                        "{}.{:09}",
+                        record.timestamp / 1000,
+                        (record.timestamp % 1000) * 1_000_000
+                    );
+                }
+                Piece::Hex => push_hex(&mut line, &record.payload, false),
+                Piece::HexUpper => push_hex(&mut line, &record.payload, true),
+                Piece::Json => line.push_str(
+                    &json!({
+                        "tst": iso_time(record.timestamp),
+                        "topic": record.topic,
+                        "qos": record.qos,
+                        "retain": u8::from(record.retain),
+                        "payloadlen": record.payload.len(),
+                        "mid": record.packet_id.unwrap_or(0),
+                        "payload": record.payload,
+                    })
+                    .to_string(),
+                ),
+            }
+        }
+        line
+    }
+}
+
+/// mosquitto_sub's `%I`: local time with a numeric offset, e.g. `2024-05-01T12:00:00+0200`.
+fn iso_time(timestamp_ms: u64) -> String {
+    DateTime::from_timestamp_millis(timestamp_ms as i64)
+        .unwrap_or_default()
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%dT%H:%M:%S%z")
+        .to_string()
+}
+
+fn push_hex(line: &mut String, payload: &str, upper: bool) {
+    for byte in payload.as_bytes() {
+        let _ = if upper {
+            write!(line, "{byte:02X}")
+        } else {
+            write!(line, "{byte:02x}")
+        };
+    }
+}
@@ -0,0 +1,403 @@
+use crate::config_store;
+use crate::crash;
+use crate::models::{
+    MessageDirection, MqttBatchItem, SocketTapConfig, SocketTapMetrics, SocketTapProtocol,
+    SocketTapsConfig,
+};
+use crate::mqtt::{is_valid_topic_filter, now_millis, topic_matches};
+use anyhow::{Context, Result, anyhow, bail};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::{RwLock, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+
+pub const SOCKET_TAPS_FILE_NAME: &str = "socket-taps.json";
+/// Messages buffered per tap before new ones are dropped.
+const TAP_QUEUE_CAPACITY: usize = 10_000;
+const WRITE_BATCH_MAX: usize = 500;
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+const RETRY_DELAY_SECS: u64 = 3;
+/// Largest UDP payload that fits in a single IPv4 datagram.
+const MAX_DATAGRAM_BYTES: usize = 65_507;
+
+#[derive(Clone, Default)]
+pub struct SocketTapManager {
+    inner: Arc<SocketTapInner>,
+}
+
+#[derive(Default)]
+struct SocketTapInner {
+    config: RwLock<Option<SocketTapsConfig>>,
+    running: DashMap<String, RunningTap>,
+}
+
+struct RunningTap {
+    config: SocketTapConfig,
+    tx: mpsc::Sender<MqttBatchItem>,
+    task: JoinHandle<()>,
+    counters: Arc<TapCounters>,
+}
+
+#[derive(Default)]
+struct TapCounters {
+    started_at: AtomicU64,
+    connected: AtomicBool,
+    forwarded: AtomicU64,
+    dropped: AtomicU64,
+    last_forwarded_at: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl TapCounters {
+    fn record_error(&self, error: &anyhow::Error) {
+        if let Ok(mut slot) = self.last_error.lock() {
+            *slot = Some(format!("{error:#}"));
+        }
+    }
+
+    fn forwarded(&self, count: u64) {
+        self.forwarded.fetch_add(count, Ordering::Relaxed);
+        self.last_forwarded_at
+            .store(now_millis(), Ordering::Relaxed);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TapLine<'a> {
+    connection_id: &'a str,
+    #[serde(flatten)]
+    item: &'a MqttBatchItem,
+}
+
+impl SocketTapManager {
+    pub async fn config(&self, app: &AppHandle) -> Result<SocketTapsConfig> {
+        if let Some(config) = self.inner.config.read().await.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let mut slot = self.inner.config.write().await;
+        if let Some(config) = slot.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let loaded = config_store::load_document::<SocketTapsConfig>(app, SOCKET_TAPS_FILE_NAME)?;
+        *slot = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    pub async fn save_taps(
+        &self,
+        app: &AppHandle,
+        taps: Vec<SocketTapConfig>,
+    ) -> Result<SocketTapsConfig> {
+        for tap in &taps {
+            validate_tap(tap)?;
+        }
+
+        let mut slot = self.inner.config.write().await;
+        let mut next = match slot.as_ref() {
+            Some(config) => config.clone(),
+            None => config_store::load_document::<SocketTapsConfig>(app, SOCKET_TAPS_FILE_NAME)?,
+        };
+        next.taps = taps;
+        config_store::save_document(app, SOCKET_TAPS_FILE_NAME, &next)?;
+        *slot = Some(next.clone());
+        Ok(next)
+    }
+
+    pub async fn start(&self, app: &AppHandle, tap_id: &str) -> Result<SocketTapMetrics> {
+        let config = self
+            .config(app)
+            .await?
+            .taps
+            .into_iter()
+            .find(|tap| tap.id == tap_id)
+            .ok_or_else(|| anyhow!("socket tap not found: {tap_id}"))?;
+        validate_tap(&config)?;
+
+        self.stop(tap_id);
+
+        let (tx, rx) = mpsc::channel::<MqttBatchItem>(TAP_QUEUE_CAPACITY);
+        let counters = Arc::new(TapCounters::default());
+        counters.started_at.store(now_millis(), Ordering::Relaxed);
+        let task = tokio::spawn(crash::monitored(
+            "socket-tap",
+            run_tap(config.clone(), rx, Arc::clone(&counters)),
+        ));
+
+        let tap = RunningTap {
+            config,
+            tx,
+            task,
+            counters,
+        };
+        let metrics = metrics_of(tap_id, Some(&tap));
+        self.inner.running.insert(tap_id.to_string(), tap);
+        Ok(metrics)
+    }
+
+    pub fn stop(&self, tap_id: &str) -> SocketTapMetrics {
+        let removed = self.inner.running.remove(tap_id).map(|(_, tap)| {
+            tap.task.abort();
+            tap
+        });
+        let mut metrics = metrics_of(tap_id, removed.as_ref());
+        metrics.running = false;
+        metrics.connected = false;
+        metrics
+    }
+
+    pub async fn metrics(&self, app: &AppHandle) -> Result<Vec<SocketTapMetrics>> {
+        let config = self.config(app).await?;
+        Ok(config
+            .taps
+            .iter()
+            .map(|tap| {
+                let running = self.inner.running.get(&tap.id);
+                metrics_of(&tap.id, running.as_deref())
+            })
+            .collect())
+    }
+
+    /// Queues matching messages without waiting; a full queue drops them so a slow consumer
+    /// never holds up the session.
+    pub fn dispatch(&self, connection_id: &str, batch: &[MqttBatchItem]) {
+        for tap in self.inner.running.iter() {
+            if tap.config.connection_id != connection_id {
+                continue;
+            }
+            for item in batch {
+                if !matches!(item.direction, MessageDirection::In)
+                    || !topic_matches(&tap.config.topic_filter, &item.topic)
+                {
+                    continue;
+                }
+                if tap.tx.try_send(item.clone()).is_err() {
+                    tap.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+fn validate_tap(tap: &SocketTapConfig) -> Result<()> {
+    if tap.connection_id.trim().is_empty() {
+        bail!("socket tap '{}' has no connection", tap.name);
+    }
+    if !is_valid_topic_filter(&tap.topic_filter) {
+        bail!("socket tap '{}' has an invalid topic filter", tap.name);
+    }
+    if tap.host.trim().is_empty() {
+        bail!("socket tap '{}' has no host", tap.name);
+    }
+    if tap.port == 0 {
+        bail!("socket tap '{}' has no port", tap.name);
+    }
+    Ok(())
+}
+
+fn metrics_of(tap_id: &str, tap: Option<&RunningTap>) -> SocketTapMetrics {
+    let Some(tap) = tap else {
+        return SocketTapMetrics {
+            id: tap_id.to_string(),
+            running: false,
+            connected: false,
+            started_at: None,
+            forwarded: 0,
+            dropped: 0,
+            last_forwarded_at: None,
+            last_error: None,
+        };
+    };
+
+    let counters = &tap.counters;
+    let last_forwarded_at = counters.last_forwarded_at.load(Ordering::Relaxed);
+    SocketTapMetrics {
+        id: tap_id.to_string(),
+        running: !tap.task.is_finished(),
+        connected: counters.connected.load(Ordering::Relaxed),
+        started_at: Some(counters.started_at.load(Ordering::Relaxed)),
+        forwarded: counters.forwarded.load(Ordering::Relaxed),
+        dropped: counters.dropped.load(Ordering::Relaxed),
+        last_forwarded_at: (last_forwarded_at > 0).then_some(last_forwarded_at),
+        last_error: counters
+            .last_error
+            .lock()
+            .ok()
+            .and_then(|slot| slot.clone()),
+    }
+}
+
+async fn run_tap(
+    config: SocketTapConfig,
+    rx: mpsc::Receiver<MqttBatchItem>,
+    counters: Arc<TapCounters>,
+) {
+    match config.protocol {
+        SocketTapProtocol::Tcp => run_tcp(config, rx, counters).await,
+        SocketTapProtocol::Udp => run_udp(config, rx, counters).await,
+    }
+}
+
+/// Writes NDJSON over a TCP connection, reconnecting after failures. Messages arriving while
+/// the endpoint is unreachable are dropped rather than replayed, since this is a live tap.
+async fn run_tcp(
+    config: SocketTapConfig,
+    mut rx: mpsc::Receiver<MqttBatchItem>,
+    counters: Arc<TapCounters>,
+) {
+    let mut stream: Option<TcpStream> = None;
+    let mut retry_at: Option<Instant> = None;
+
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < WRITE_BATCH_MAX {
+            match rx.try_recv() {
+                Ok(item) => batch.push(item),
+                Err(_) => break,
+            }
+        }
+        let count = batch.len() as u64;
+
+        if stream.is_none() {
+            if retry_at.is_some_and(|at| Instant::now() < at) {
+                counters.dropped.fetch_add(count, Ordering::Relaxed);
+                continue;
+            }
+            match connect_tcp(&config).await {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    retry_at = None;
+                    counters.connected.store(true, Ordering::Relaxed);
+                }
+                Err(error) => {
+                    counters.record_error(&error);
+                    counters.dropped.fetch_add(count, Ordering::Relaxed);
+                    retry_at = Some(Instant::now() + Duration::from_secs(RETRY_DELAY_SECS));
+                    continue;
+                }
+            }
+        }
+        let Some(connected) = stream.as_mut() else {
+            continue;
+        };
+
+        let mut buffer = Vec::new();
+        for item in &batch {
+            if let Err(error) = serde_json::to_writer(&mut buffer, &line(&config, item)) {
+                counters.record_error(&anyhow!("failed to serialize message: {error}"));
+                continue;
+            }
+            buffer.push(b'\n');
+        }
+        match connected.write_all(&buffer).await {
+            Ok(()) => counters.forwarded(count),
+            Err(error) => {
+                counters.record_error(&anyhow!(
+                    "write to {}:{} failed: {error}",
+                    config.host,
+                    config.port
+                ));
+                counters.dropped.fetch_add(count, Ordering::Relaxed);
+                counters.connected.store(false, Ordering::Relaxed);
+                stream = None;
+                retry_at = Some(Instant::now() + Duration::from_secs(RETRY_DELAY_SECS));
+            }
+        }
+    }
+}
+
+async fn connect_tcp(config: &SocketTapConfig) -> Result<TcpStream> {
+    let stream = time::timeout(
+        Duration::from_secs(CONNECT_TIMEOUT_SECS),
+        TcpStream::connect((config.host.as_str(), config.port)),
+    )
+    .await
+    .map_err(|_| anyhow!("connecting to {}:{} timed out", config.host, config.port))?
+    .with_context(|| format!("failed to connect to {}:{}", config.host, config.port))?;
+    let _ = stream.set_nodelay(true);
+    Ok(stream)
+}
+
+/// Sends one JSON object per datagram. Send errors (such as ICMP port unreachable reported
+/// on a later send) drop the message but keep the tap running.
+async fn run_udp(
+    config: SocketTapConfig,
+    mut rx: mpsc::Receiver<MqttBatchItem>,
+    counters: Arc<TapCounters>,
+) {
+    let socket = match bind_udp(&config).await {
+        Ok(socket) => socket,
+        Err(error) => {
+            counters.record_error(&error);
+            return;
+        }
+    };
+    counters.connected.store(true, Ordering::Relaxed);
+
+    while let Some(item) = rx.recv().await {
+        let datagram = match serde_json::to_vec(&line(&config, &item)) {
+            Ok(datagram) => datagram,
+            Err(error) => {
+                counters.record_error(&anyhow!("failed to serialize message: {error}"));
+                counters.dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+        };
+        if datagram.len() > MAX_DATAGRAM_BYTES {
+            counters.record_error(&anyhow!(
+                "message on {} is too large for a UDP datagram",
+                item.topic
+            ));
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        match socket.send(&datagram).await {
+            Ok(_) => counters.forwarded(1),
+            Err(error) => {
+                counters.record_error(&anyhow!(
+                    "send to {}:{} failed: {error}",
+                    config.host,
+                    config.port
+                ));
+                counters.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+async fn bind_udp(config: &SocketTapConfig) -> Result<UdpSocket> {
+    let target = tokio::net::lookup_host((config.host.as_str(), config.port))
+        .await
+        .with_context(|| format!("failed to resolve {}", config.host))?
+        .next()
+        .ok_or_else(|| anyhow!("{} did not resolve to an address", config.host))?;
+    let local: SocketAddr = if target.is_ipv6() {
+        "[::]:0".parse()?
+    } else {
+        "0.0.0.0:0".parse()?
+    };
+    let socket = UdpSocket::bind(local)
+        .await
+        .context("failed to open UDP socket")?;
+    socket
+        .connect(target)
+        .await
+        .with_context(|| format!("failed to target {target}"))?;
+    Ok(socket)
+}
+
+fn line<'a>(config: &'a SocketTapConfig, item: &'a MqttBatchItem) -> TapLine<'a> {
+    TapLine {
+        connection_id: &config.connection_id,
+        item,
+    }
+}
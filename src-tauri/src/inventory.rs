@@ -0,0 +1,383 @@
+//! Turns a CSV device or broker inventory into connection profiles, brokers and identities,
+//! so fleets can be onboarded without entering every device by hand.
+//!
+//! Every row becomes one connection profile. Rows on the same host, port, transport and path
+//! share one broker, and rows with the same username and password share one identity; both
+//! reuse a matching existing record instead of creating a duplicate.
+
+use crate::models::{
+    AuthIdentity, BrokerConfig, ConnectionProfile, InventoryImportResult, InventoryMapping,
+    InventoryRowError, TransportProtocol,
+};
+use crate::profiles::random_id;
+use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+const MAX_ROWS: usize = 10_000;
+
+pub fn import(
+    path_or_content: &str,
+    mapping: &InventoryMapping,
+    existing_brokers: &[BrokerConfig],
+    existing_identities: &[AuthIdentity],
+) -> Result<InventoryImportResult> {
+    let trimmed = path_or_content.trim();
+    if trimmed.is_empty() {
+        bail!("inventory source is empty");
+    }
+    let content = if trimmed.contains('\n') {
+        path_or_content.to_string()
+    } else {
+        fs::read_to_string(trimmed)
+            .with_context(|| format!("failed to read inventory file: {trimmed}"))?
+    };
+    let delimiter = match mapping.delimiter.as_deref() {
+        None | Some("") => ',',
+        Some("\\t") => '\t',
+        Some(delimiter) => {
+            let mut chars = delimiter.chars();
+            match (chars.next(), chars.next()) {
+                (Some(delimiter), None) => delimiter,
+                _ => bail!("the delimiter must be a single character"),
+            }
+        }
+    };
+
+    let mut records = parse_csv(content.trim_start_matches('\u{feff}'), delimiter).into_iter();
+    let Some((_, header)) = records.next() else {
+        bail!("the inventory has no header row");
+    };
+    let columns = header
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.trim().to_lowercase(), index))
+        .collect::<HashMap<_, _>>();
+    let column = |name: &Option<String>| -> Result<Option<usize>> {
+        match name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            None => Ok(None),
+            Some(name) => columns
+                .get(&name.to_lowercase())
+                .copied()
+                .map(Some)
+                .with_context(|| format!("column '{name}' is not in the header")),
+        }
+    };
+    let host_column = column(&mapping.host_column)?.context("a host column is required")?;
+    let fields = Columns {
+        name: column(&mapping.name_column)?,
+        group: column(&mapping.group_column)?,
+        port: column(&mapping.port_column)?,
+        protocol: column(&mapping.protocol_column)?,
+        path: column(&mapping.path_column)?,
+        protocol_version: column(&mapping.protocol_version_column)?,
+        username: column(&mapping.username_column)?,
+        password: column(&mapping.password_column)?,
+        client_id: column(&mapping.client_id_column)?,
+        broker_name: column(&mapping.broker_name_column)?,
+        identity_name: column(&mapping.identity_name_column)?,
+    };
+    let label_columns = mapping
+        .label_columns
+        .iter()
+        .map(|name| Ok((name.trim().to_string(), column(&Some(name.clone()))?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut importer = Importer {
+        mapping,
+        brokers: existing_brokers
+            .iter()
+            .map(|broker| {
+                let key = broker_key(
+                    &broker.host,
+                    broker.port,
+                    broker.protocol,
+                    broker.path.as_deref(),
+                );
+                (key, broker.id.clone())
+            })
+            .collect(),
+        identities: existing_identities
+            .iter()
+            .map(|identity| {
+                let key = (
+                    identity.username.clone().unwrap_or_default(),
+                    identity.password.clone().unwrap_or_default(),
+                );
+                (key, identity.id.clone())
+            })
+            .collect(),
+        result: InventoryImportResult::default(),
+    };
+    for (line, record) in records {
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        if importer.result.rows >= MAX_ROWS {
+            bail!("inventories are limited to {MAX_ROWS} rows");
+        }
+        importer.result.rows += 1;
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let row = Row {
+            host: cell(Some(host_column)),
+            name: cell(fields.name),
+            group: cell(fields.group),
+            port: cell(fields.port),
+            protocol: cell(fields.protocol),
+            path: cell(fields.path),
+            protocol_version: cell(fields.protocol_version),
+            username: cell(fields.username),
+            password: cell(fields.password),
+            client_id: cell(fields.client_id),
+            broker_name: cell(fields.broker_name),
+            identity_name: cell(fields.identity_name),
+            labels: label_columns
+                .iter()
+                .filter_map(|(name, index)| Some((name.clone(), cell(*index)?)))
+                .collect(),
+        };
+        if let Err(error) = importer.row(row) {
+            importer.result.errors.push(InventoryRowError {
+                line,
+                message: format!("{error:#}"),
+            });
+        }
+    }
+    Ok(importer.result)
+}
+
+struct Columns {
+    name: Option<usize>,
+    group: Option<usize>,
+    port: Option<usize>,
+    protocol: Option<usize>,
+    path: Option<usize>,
+    protocol_version: Option<usize>,
+    username: Option<usize>,
+    password: Option<usize>,
+    client_id: Option<usize>,
+    broker_name: Option<usize>,
+    identity_name: Option<usize>,
+}
+
+struct Row {
+    host: Option<String>,
+    name: Option<String>,
+    group: Option<String>,
+    port: Option<String>,
+    protocol: Option<String>,
+    path: Option<String>,
+    protocol_version: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    client_id: Option<String>,
+    broker_name: Option<String>,
+    identity_name: Option<String>,
+    labels: BTreeMap<String, String>,
+}
+
+struct Importer<'a> {
+    mapping: &'a InventoryMapping,
+    brokers: HashMap<String, String>,
+    identities: HashMap<(String, String), String>,
+    result: InventoryImportResult,
+}
+
+impl Importer<'_> {
+    fn row(&mut self, row: Row) -> Result<()> {
+        let host = row.host.context("the host is empty")?;
+        let protocol = match row.protocol.as_deref() {
+            Some(protocol) => parse_protocol(protocol)?,
+            None => self
+                .mapping
+                .default_protocol
+                .unwrap_or(TransportProtocol::Mqtt),
+        };
+        let port = match row.port.as_deref() {
+            Some(port) => port
+                .parse::<u16>()
+                .ok()
+                .filter(|port| *port != 0)
+                .with_context(|| format!("invalid port '{port}'"))?,
+            None => self
+                .mapping
+                .default_port
+                .unwrap_or_else(|| default_port(protocol)),
+        };
+        let protocol_version = match row.protocol_version.as_deref() {
+            Some("5" | "5.0") => 5,
+            Some("3" | "3.1" | "4" | "3.1.1") => 4,
+            Some(version) => bail!("unsupported MQTT version '{version}'"),
+            None => self.mapping.default_protocol_version.unwrap_or(4),
+        };
+        let uses_websocket = matches!(protocol, TransportProtocol::Ws | TransportProtocol::Wss);
+        let path = row.path.filter(|_| uses_websocket);
+        let ssl = matches!(protocol, TransportProtocol::Mqtts | TransportProtocol::Wss);
+
+        let key = broker_key(&host, port, protocol, path.as_deref());
+        let broker_id = match self.brokers.get(&key) {
+            Some(id) => id.clone(),
+            None => {
+                let id = random_id();
+                self.result.brokers.push(BrokerConfig {
+                    id: id.clone(),
+                    name: row.broker_name.unwrap_or_else(|| format!("{host}:{port}")),
+                    host: host.clone(),
+                    port,
+                    protocol,
+                    path: path.clone(),
+                    ssl,
+                });
+                self.brokers.insert(key, id.clone());
+                id
+            }
+        };
+
+        let identity_id = if row.username.is_some() || row.password.is_some() {
+            let key = (
+                row.username.clone().unwrap_or_default(),
+                row.password.clone().unwrap_or_default(),
+            );
+            Some(match self.identities.get(&key) {
+                Some(id) => id.clone(),
+                None => {
+                    let id = random_id();
+                    self.result.identities.push(AuthIdentity {
+                        id: id.clone(),
+                        name: row
+                            .identity_name
+                            .or_else(|| row.username.clone())
+                            .unwrap_or_else(|| format!("{host} credentials")),
+                        username: row.username.clone(),
+                        password: row.password.clone(),
+                        client_id: None,
+                        client_cert_path: None,
+                        token_expires_at: None,
+                        expiry_warning_days: None,
+                    });
+                    self.identities.insert(key, id.clone());
+                    id
+                }
+            })
+        } else {
+            None
+        };
+
+        self.result.connections.push(ConnectionProfile {
+            id: random_id(),
+            name: row.name.unwrap_or_else(|| host.clone()),
+            group: row.group.or_else(|| self.mapping.default_group.clone()),
+            color_tag: None,
+            broker_id: Some(broker_id),
+            identity_id,
+            host,
+            port,
+            protocol,
+            protocol_version: Some(protocol_version),
+            path,
+            ssl,
+            username: None,
+            password: None,
+            client_id: row
+                .client_id
+                .unwrap_or_else(|| format!("nexus-{:08x}", rand::random::<u32>())),
+            clean: true,
+            notify_on_disconnect: false,
+            publish_rate_limit: None,
+            reconnect_on_takeover: false,
+            replay_unacked_publishes: false,
+            follow_server_redirects: false,
+            unique_client_id: false,
+            default_subscriptions: Vec::new(),
+            labels: row.labels,
+            ca_cert_path: None,
+            client_cert_path: None,
+            client_key_path: None,
+            environment: None,
+            idle_disconnect: None,
+        });
+        Ok(())
+    }
+}
+
+fn parse_protocol(value: &str) -> Result<TransportProtocol> {
+    Ok(match value.to_ascii_lowercase().as_str() {
+        "mqtt" | "tcp" => TransportProtocol::Mqtt,
+        "mqtts" | "ssl" | "tls" => TransportProtocol::Mqtts,
+        "ws" => TransportProtocol::Ws,
+        "wss" => TransportProtocol::Wss,
+        _ => bail!("unknown transport '{value}'"),
+    })
+}
+
+fn default_port(protocol: TransportProtocol) -> u16 {
+    match protocol {
+        TransportProtocol::Mqtt => 1883,
+        TransportProtocol::Mqtts => 8883,
+        TransportProtocol::Ws => 8083,
+        TransportProtocol::Wss => 8084,
+    }
+}
+
+fn broker_key(host: &str, port: u16, protocol: TransportProtocol, path: Option<&str>) -> String {
+    format!(
+        "{}:{port}:{protocol:?}:{}",
+        host.trim().to_lowercase(),
+        path.unwrap_or_default().trim()
+    )
+}
+
+/// Splits RFC 4180 CSV into records, each with the 1-based line it starts on. Quoted fields
+/// may contain the delimiter, doubled quotes and line breaks.
+fn parse_csv(content: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(ch);
+                }
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            _ if ch == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    records
+}
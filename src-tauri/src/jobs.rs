@@ -0,0 +1,213 @@
+use crate::crash;
+use crate::models::{JobKind, JobState, JobStatus};
+use crate::mqtt::now_millis;
+use anyhow::Result;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tauri::{AppHandle, Emitter};
+use tokio::time::{self, Duration};
+
+const PROGRESS_INTERVAL_MS: u64 = 250;
+
+/// Returned through `anyhow` by [`JobProgress::checkpoint`] so the runner can tell a
+/// cancellation apart from a failure.
+#[derive(Debug, thiserror::Error)]
+#[error("cancelled")]
+pub struct JobCancelled;
+
+/// Shared counters a running job updates and polls; cheap enough to touch once per row.
+#[derive(Clone)]
+pub struct JobProgress {
+    inner: Arc<ProgressInner>,
+}
+
+#[derive(Default)]
+struct ProgressInner {
+    job_id: String,
+    cancelled: AtomicBool,
+    done: AtomicU64,
+    /// 0 until the job knows its size.
+    total: AtomicU64,
+}
+
+impl JobProgress {
+    fn new(job_id: &str) -> Self {
+        Self {
+            inner: Arc::new(ProgressInner {
+                job_id: job_id.to_string(),
+                ..ProgressInner::default()
+            }),
+        }
+    }
+
+    pub fn job_id(&self) -> &str {
+        &self.inner.job_id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Errors with [`JobCancelled`] once the job has been cancelled.
+    pub fn checkpoint(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(JobCancelled.into());
+        }
+        Ok(())
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.inner.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, units: u64) {
+        self.inner.done.fetch_add(units, Ordering::Relaxed);
+    }
+
+    /// Counts one unit of work, then checks for cancellation.
+    pub fn tick(&self) -> Result<()> {
+        self.advance(1);
+        self.checkpoint()
+    }
+
+    fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn counts(&self) -> (u64, Option<u64>) {
+        let total = self.inner.total.load(Ordering::Relaxed);
+        (
+            self.inner.done.load(Ordering::Relaxed),
+            (total > 0).then_some(total),
+        )
+    }
+}
+
+struct JobEntry {
+    kind: JobKind,
+    label: String,
+    started_at: u64,
+    progress: JobProgress,
+}
+
+impl JobEntry {
+    fn status(&self, job_id: &str, state: JobState) -> JobStatus {
+        let (done, total) = self.progress.counts();
+        JobStatus {
+            job_id: job_id.to_string(),
+            kind: self.kind,
+            label: self.label.clone(),
+            state,
+            done,
+            total,
+            started_at: self.started_at,
+            finished_at: None,
+            result: None,
+            error: None,
+        }
+    }
+}
+
+/// Background jobs for long operations. Every job reports through `job-progress` events while it
+/// runs, and once more with its result, error or cancellation when it ends.
+#[derive(Clone, Default)]
+pub struct JobManager {
+    jobs: Arc<DashMap<String, Arc<JobEntry>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl JobManager {
+    /// Spawns `run` as a job and returns its id. `run` gets the job's progress handle, which it
+    /// should advance as it works and check for cancellation.
+    pub fn start<F, Fut, T>(
+        &self,
+        app: &AppHandle,
+        kind: JobKind,
+        label: impl Into<String>,
+        run: F,
+    ) -> String
+    where
+        F: FnOnce(JobProgress) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        T: Serialize,
+    {
+        let job_id = format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let entry = Arc::new(JobEntry {
+            kind,
+            label: label.into(),
+            started_at: now_millis(),
+            progress: JobProgress::new(&job_id),
+        });
+        self.jobs.insert(job_id.clone(), Arc::clone(&entry));
+        let work = run(entry.progress.clone());
+
+        let jobs = Arc::clone(&self.jobs);
+        let app = app.clone();
+        let id = job_id.clone();
+        tokio::spawn(crash::monitored("background-job", async move {
+            let _ = app.emit("job-progress", entry.status(&id, JobState::Running));
+            tokio::pin!(work);
+            let mut ticker = time::interval(Duration::from_millis(PROGRESS_INTERVAL_MS));
+            ticker.tick().await;
+            let mut reported = entry.progress.counts();
+            let outcome = loop {
+                tokio::select! {
+                    outcome = &mut work => break outcome,
+                    _ = ticker.tick() => {
+                        let counts = entry.progress.counts();
+                        if counts != reported {
+                            reported = counts;
+                            let _ = app.emit("job-progress", entry.status(&id, JobState::Running));
+                        }
+                    }
+                }
+            };
+
+            jobs.remove(&id);
+            let mut status = match outcome {
+                Ok(value) => JobStatus {
+                    result: serde_json::to_value(value).ok(),
+                    ..entry.status(&id, JobState::Completed)
+                },
+                Err(error) if error.is::<JobCancelled>() || entry.progress.is_cancelled() => {
+                    entry.status(&id, JobState::Cancelled)
+                }
+                Err(error) => {
+                    tracing::warn!(job_id = %id, kind = ?entry.kind, "job failed: {error:#}");
+                    JobStatus {
+                        error: Some(format!("{error:#}")),
+                        ..entry.status(&id, JobState::Failed)
+                    }
+                }
+            };
+            status.finished_at = Some(now_millis());
+            let _ = app.emit("job-progress", status);
+        }));
+
+        job_id
+    }
+
+    /// Asks a job to stop at its next checkpoint; returns false for unknown or finished jobs.
+    pub fn cancel(&self, job_id: &str) -> bool {
+        match self.jobs.get(job_id) {
+            Some(entry) => {
+                entry.progress.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn list(&self) -> Vec<JobStatus> {
+        let mut jobs = self
+            .jobs
+            .iter()
+            .map(|entry| entry.value().status(entry.key(), JobState::Running))
+            .collect::<Vec<_>>();
+        jobs.sort_by_key(|job| job.started_at);
+        jobs
+    }
+}
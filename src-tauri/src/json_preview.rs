@@ -0,0 +1,141 @@
+//! Structural previews of large JSON payloads, so the UI can show a tree of a multi-megabyte
+//! message one level at a time instead of parsing and rendering all of it.
+
+use crate::models::{JsonPreviewKind, JsonPreviewNode};
+use crate::rules::json_path;
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+
+const DEFAULT_CHILD_LIMIT: usize = 50;
+const MAX_CHILD_LIMIT: usize = 1_000;
+const DEFAULT_DEPTH: usize = 1;
+const MAX_DEPTH: usize = 8;
+const MAX_VALUE_CHARS: usize = 256;
+
+/// Previews the value at `path` (the JSONPath subset of the rules engine, `$` for the root)
+/// down to `depth` levels. Objects and arrays list at most `limit` children starting at
+/// `offset`; deeper levels always start at their first child. Object keys come back sorted.
+pub fn preview(
+    payload: &str,
+    path: Option<&str>,
+    offset: usize,
+    limit: Option<usize>,
+    depth: Option<usize>,
+) -> Result<JsonPreviewNode> {
+    let root = serde_json::from_str::<Value>(payload).context("the payload is not valid JSON")?;
+    let path = path.map(str::trim).filter(|path| !path.is_empty());
+    let value = match path {
+        Some(path) => json_path::select(&root, path)
+            .ok_or_else(|| anyhow!("nothing at '{path}' in the payload"))?,
+        None => &root,
+    };
+    let limits = Limits {
+        limit: limit
+            .unwrap_or(DEFAULT_CHILD_LIMIT)
+            .clamp(1, MAX_CHILD_LIMIT),
+        depth: depth.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH),
+    };
+    Ok(node(
+        None,
+        path.unwrap_or("$").to_string(),
+        value,
+        offset,
+        limits.depth,
+        &limits,
+    ))
+}
+
+struct Limits {
+    limit: usize,
+    depth: usize,
+}
+
+fn node(
+    key: Option<String>,
+    path: String,
+    value: &Value,
+    offset: usize,
+    depth: usize,
+    limits: &Limits,
+) -> JsonPreviewNode {
+    let mut preview = JsonPreviewNode {
+        key,
+        path,
+        kind: kind(value),
+        size: None,
+        value: None,
+        value_truncated: false,
+        children: None,
+        offset: 0,
+        remaining: 0,
+    };
+    match value {
+        Value::Object(map) => {
+            preview.size = Some(map.len());
+            if depth > 0 {
+                let children = map
+                    .iter()
+                    .skip(offset)
+                    .take(limits.limit)
+                    .map(|(key, child)| {
+                        let path = child_key_path(&preview.path, key);
+                        node(Some(key.clone()), path, child, 0, depth - 1, limits)
+                    })
+                    .collect::<Vec<_>>();
+                preview.offset = offset.min(map.len());
+                preview.remaining = map.len() - preview.offset - children.len();
+                preview.children = Some(children);
+            }
+        }
+        Value::Array(items) => {
+            preview.size = Some(items.len());
+            if depth > 0 {
+                let children = items
+                    .iter()
+                    .enumerate()
+                    .skip(offset)
+                    .take(limits.limit)
+                    .map(|(index, child)| {
+                        let path = format!("{}[{index}]", preview.path);
+                        node(Some(index.to_string()), path, child, 0, depth - 1, limits)
+                    })
+                    .collect::<Vec<_>>();
+                preview.offset = offset.min(items.len());
+                preview.remaining = items.len() - preview.offset - children.len();
+                preview.children = Some(children);
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            preview.size = Some(length);
+            preview.value_truncated = length > MAX_VALUE_CHARS;
+            preview.value = Some(text.chars().take(MAX_VALUE_CHARS).collect());
+        }
+        scalar => preview.value = Some(scalar.to_string()),
+    }
+    preview
+}
+
+fn kind(value: &Value) -> JsonPreviewKind {
+    match value {
+        Value::Null => JsonPreviewKind::Null,
+        Value::Bool(_) => JsonPreviewKind::Bool,
+        Value::Number(_) => JsonPreviewKind::Number,
+        Value::String(_) => JsonPreviewKind::String,
+        Value::Array(_) => JsonPreviewKind::Array,
+        Value::Object(_) => JsonPreviewKind::Object,
+    }
+}
+
+/// `$.key` for plain keys, `$['key']` for keys the path syntax can't spell with a dot.
+fn child_key_path(parent: &str, key: &str) -> String {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '-');
+    if plain {
+        format!("{parent}.{key}")
+    } else {
+        format!("{parent}['{key}']")
+    }
+}
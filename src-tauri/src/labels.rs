@@ -0,0 +1,104 @@
+//! Key/value labels on connection profiles (`environment=prod`, `site=berlin`) and the
+//! selectors used to pick connections by them.
+
+use crate::models::ConnectionProfile;
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, BTreeSet};
+
+const MAX_KEY_CHARS: usize = 63;
+const MAX_VALUE_CHARS: usize = 255;
+
+/// A comma-separated list of `key=value` (the label must have that value), `key!=value`
+/// (it must not) and bare `key` (the label must be set) requirements, all of which must hold.
+#[derive(Debug, Default)]
+pub struct LabelSelector {
+    requirements: Vec<Requirement>,
+}
+
+#[derive(Debug)]
+enum Requirement {
+    Equals(String, String),
+    NotEquals(String, String),
+    Exists(String),
+}
+
+impl LabelSelector {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut requirements = Vec::new();
+        for part in text
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            let requirement = if let Some((key, value)) = part.split_once("!=") {
+                Requirement::NotEquals(checked_key(key)?, value.trim().to_string())
+            } else if let Some((key, value)) = part.split_once('=') {
+                Requirement::Equals(checked_key(key)?, value.trim().to_string())
+            } else {
+                Requirement::Exists(checked_key(part)?)
+            };
+            requirements.push(requirement);
+        }
+        Ok(Self { requirements })
+    }
+
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.requirements
+            .iter()
+            .all(|requirement| match requirement {
+                Requirement::Equals(key, value) => labels.get(key) == Some(value),
+                Requirement::NotEquals(key, value) => labels.get(key) != Some(value),
+                Requirement::Exists(key) => labels.contains_key(key),
+            })
+    }
+}
+
+/// Profiles whose labels satisfy `selector`, in config order.
+pub fn select(
+    profiles: Vec<ConnectionProfile>,
+    selector: &LabelSelector,
+) -> Vec<ConnectionProfile> {
+    profiles
+        .into_iter()
+        .filter(|profile| selector.matches(&profile.labels))
+        .collect()
+}
+
+/// Every label key in use with the distinct values seen for it, for selector suggestions.
+pub fn catalog(profiles: &[ConnectionProfile]) -> BTreeMap<String, BTreeSet<String>> {
+    let mut keys = BTreeMap::<String, BTreeSet<String>>::new();
+    for (key, value) in profiles.iter().flat_map(|profile| &profile.labels) {
+        keys.entry(key.clone()).or_default().insert(value.clone());
+    }
+    keys
+}
+
+pub fn validate(profile: &ConnectionProfile) -> Result<()> {
+    for (key, value) in &profile.labels {
+        checked_key(key).map_err(|error| anyhow::anyhow!("{}: {error}", profile.name))?;
+        if value.chars().count() > MAX_VALUE_CHARS || value.contains(',') {
+            bail!(
+                "{}: label '{key}' must be at most {MAX_VALUE_CHARS} characters without commas",
+                profile.name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn checked_key(key: &str) -> Result<String> {
+    let key = key.trim();
+    if key.is_empty() {
+        bail!("label key is required");
+    }
+    if key.chars().count() > MAX_KEY_CHARS
+        || !key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | '/'))
+    {
+        bail!(
+            "invalid label key '{key}'; use up to {MAX_KEY_CHARS} letters, digits, '-', '_', '.' or '/'"
+        );
+    }
+    Ok(key.to_string())
+}
@@ -1,27 +1,150 @@
 mod ai;
+mod broker;
+mod catalog;
+mod clipboard;
 mod commands;
 mod config_store;
+mod control_api;
+mod crash;
+mod credentials;
+mod disk;
+mod export_log;
+mod feed;
 mod history;
+mod homeassistant;
+mod integrations;
+mod inventory;
+mod jobs;
+mod json_preview;
+mod labels;
 mod models;
 mod mqtt;
+mod payload_time;
+mod pcap;
+mod plugins;
+mod profiles;
+mod replay;
+mod rules;
+mod shortcuts;
+mod snapshot;
 mod state;
+mod storage_guard;
+mod subscription_presets;
+mod telemetry;
+mod templates;
+mod tray;
+mod windows;
+mod wipe;
+mod xlsx;
 
 use commands::{
-    ai_generate_payload, app_config_export, app_ready, get_app_config_paths, history_clear,
-    history_delete_connection, history_export, history_pick_export_path, history_query_before,
-    history_query_latest, load_app_config, mqtt_connect, mqtt_disconnect, mqtt_publish,
-    mqtt_subscribe, mqtt_unsubscribe, open_app_config_dir, save_app_config, topic_catalog_export,
+    ai_generate_payload, alert_log_query, app_config_export, app_ready, app_runtime_metrics,
+    app_wipe_all_data, benchmark_compare, broker_start, broker_status, broker_stop,
+    broker_sys_stats, broker_sys_stats_start, broker_sys_stats_stop, client_id_collisions,
+    connect_limits_load, connect_limits_save, connection_duplicate, connection_events_export,
+    connection_events_query, connection_health, connection_info, connection_labels_list,
+    connections_by_label, control_api_rotate_token, control_api_save, control_api_status,
+    correlation_thread, crash_report_open, crash_reports_list, credentials_audit, export_log_list,
+    export_rerun, feed_save, feed_status, file_watcher_metrics, file_watcher_start,
+    file_watcher_stop, file_watchers_load, file_watchers_save, get_app_config_paths,
+    ha_discovery_registry, history_checkpoint, history_clear, history_copy_to_clipboard,
+    history_delete_connection, history_disk_usage, history_export, history_export_all,
+    history_export_elasticsearch, history_export_influx, history_export_mosquitto,
+    history_export_replay, history_export_topics, history_import_pcap, history_json_preview,
+    history_mark_read, history_pause, history_paused_list, history_payload_sizes,
+    history_pick_export_path, history_pin, history_query_before, history_query_latest,
+    history_query_pinned, history_query_stream, history_repairs, history_resume, history_search,
+    history_topic_stats, history_unpin, history_unread_counts, identity_expiry,
+    inventory_import_csv, job_cancel, jobs_list, kafka_bridge_metrics, kafka_bridge_start,
+    kafka_bridge_stop, kafka_bridges_load, kafka_bridges_save, load_app_config, memory_limits_load,
+    memory_limits_save, messages_recent, mqtt_batch_ack, mqtt_connect, mqtt_disconnect,
+    mqtt_echo_latency, mqtt_echo_latency_reset, mqtt_keep_alive_stats, mqtt_peek,
+    mqtt_ping_debug_set, mqtt_publish, mqtt_publish_multi, mqtt_publish_validate, mqtt_qos2_flows,
+    mqtt_qos2_verify_set, mqtt_request, mqtt_subscribe, mqtt_topic_filter_validate,
+    mqtt_topic_matches, mqtt_unsubscribe, open_app_config_dir, payload_timestamp_test,
+    payload_timestamps_load, payload_timestamps_save, plugins_load, plugins_save, plugins_status,
+    publish_journal_clear, publish_journal_pending, retained_snapshot_export,
+    retained_snapshot_import, rule_command_allow, rule_command_revoke, rule_test,
+    rules_bundle_export, rules_bundle_import, rules_load, rules_save, save_app_config,
+    session_hooks_load, session_hooks_save, shortcuts_load, shortcuts_save, socket_tap_metrics,
+    socket_tap_start, socket_tap_stop, socket_taps_load, socket_taps_save, storage_guard_load,
+    storage_guard_save, storage_guard_status, subscription_presets_list, subscription_presets_save,
+    telemetry_config_load, telemetry_config_save, template_create, template_delete,
+    template_duplicate, template_update, templates_import, templates_list, topic_catalog_export,
+    topic_catalog_export_asyncapi, topic_catalog_export_docs, topic_catalog_generate_code,
+    topic_catalog_import, topic_catalog_import_asyncapi, topic_discovery_discard,
+    topic_discovery_draft, topic_discovery_promote, topic_discovery_start, topic_discovery_stop,
+    transform_test, transforms_load, transforms_save, tray_set_badges, window_connection_context,
+    window_open_connection,
 };
+use models::SecondInstancePayload;
 use state::AppState;
 use std::time::Duration;
-use tauri::Manager;
 use tauri::WebviewWindowBuilder;
+use tauri::{AppHandle, Emitter, Manager};
+
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
 
 pub fn run() {
     tauri::Builder::default()
+        // Must be registered first so a second launch exits before touching config or SQLite files.
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            focus_main_window(app);
+            let _ = app.emit("second-instance", SecondInstancePayload { args, cwd });
+        }))
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(shortcuts::plugin())
         .manage(AppState::new())
         .setup(|app| {
+            crash::install(app.handle());
+            telemetry::init(app.handle());
+            if let Err(error) = tray::init(app.handle()) {
+                tracing::warn!("tray icon unavailable: {error:#}");
+            }
+            shortcuts::init(app.handle());
+            {
+                let state = app.state::<AppState>();
+                state.plugins.init(app.handle());
+                state.transforms.init(app.handle());
+                state.session_hooks.init(app.handle());
+                state.payload_timestamps.init(app.handle());
+                match state.memory.load_config(app.handle()) {
+                    Ok(limits) => {
+                        state
+                            .topic_discovery
+                            .set_max_topics(limits.max_discovery_topics);
+                        state.recent.set_capacity(limits.recent_messages);
+                    }
+                    Err(error) => tracing::warn!("failed to load memory limits: {error:#}"),
+                }
+                if let Err(error) = state.mqtt_manager.throttle().load_config(app.handle()) {
+                    tracing::warn!("failed to load connection limits: {error:#}");
+                }
+            }
+
+            let api_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = api_handle.state::<AppState>();
+                if let Err(error) = state.control_api.start_from_config(&api_handle).await {
+                    tracing::error!("failed to start control API: {error:#}");
+                }
+                if let Err(error) = state.feed.start_from_config(&api_handle).await {
+                    tracing::error!("failed to start WebSocket feed: {error:#}");
+                }
+                state.history_manager.start_checkpointer(api_handle.clone());
+                state.storage_guard.start(api_handle.clone());
+                state.health.start(api_handle.clone());
+                state.credentials.start(api_handle.clone());
+                state.publish_journal.start(api_handle.clone());
+            });
+
             let app_handle = app.handle().clone();
             let main_window_config = app
                 .config()
@@ -73,6 +196,147 @@ pub fn run() {
             history_pick_export_path,
             topic_catalog_export,
             app_config_export,
+            rules_load,
+            rules_save,
+            rule_command_allow,
+            rule_command_revoke,
+            rule_test,
+            alert_log_query,
+            broker_start,
+            broker_stop,
+            broker_status,
+            history_export_influx,
+            kafka_bridges_load,
+            kafka_bridges_save,
+            kafka_bridge_start,
+            kafka_bridge_stop,
+            kafka_bridge_metrics,
+            telemetry_config_load,
+            telemetry_config_save,
+            control_api_status,
+            control_api_save,
+            control_api_rotate_token,
+            feed_status,
+            feed_save,
+            history_export_elasticsearch,
+            ha_discovery_registry,
+            topic_catalog_import_asyncapi,
+            topic_catalog_export_asyncapi,
+            topic_catalog_import,
+            topic_discovery_start,
+            topic_discovery_stop,
+            topic_discovery_draft,
+            topic_discovery_discard,
+            topic_discovery_promote,
+            topic_catalog_export_docs,
+            broker_sys_stats_start,
+            broker_sys_stats_stop,
+            broker_sys_stats,
+            connection_events_query,
+            connection_events_export,
+            crash_reports_list,
+            crash_report_open,
+            tray_set_badges,
+            window_open_connection,
+            window_connection_context,
+            shortcuts_load,
+            shortcuts_save,
+            app_runtime_metrics,
+            memory_limits_load,
+            memory_limits_save,
+            mqtt_batch_ack,
+            history_search,
+            history_export_all,
+            history_checkpoint,
+            messages_recent,
+            history_query_stream,
+            history_topic_stats,
+            correlation_thread,
+            client_id_collisions,
+            mqtt_publish_validate,
+            mqtt_topic_filter_validate,
+            mqtt_topic_matches,
+            jobs_list,
+            job_cancel,
+            plugins_load,
+            plugins_save,
+            plugins_status,
+            transforms_load,
+            transforms_save,
+            transform_test,
+            templates_list,
+            template_create,
+            template_update,
+            template_delete,
+            template_duplicate,
+            templates_import,
+            retained_snapshot_export,
+            retained_snapshot_import,
+            history_pin,
+            history_unpin,
+            history_query_pinned,
+            mqtt_ping_debug_set,
+            mqtt_keep_alive_stats,
+            mqtt_qos2_verify_set,
+            mqtt_qos2_flows,
+            history_copy_to_clipboard,
+            file_watchers_load,
+            file_watchers_save,
+            file_watcher_start,
+            file_watcher_stop,
+            file_watcher_metrics,
+            socket_taps_load,
+            socket_taps_save,
+            socket_tap_start,
+            socket_tap_stop,
+            socket_tap_metrics,
+            app_wipe_all_data,
+            history_disk_usage,
+            storage_guard_load,
+            storage_guard_save,
+            storage_guard_status,
+            mqtt_publish_multi,
+            payload_timestamps_load,
+            payload_timestamps_save,
+            payload_timestamp_test,
+            subscription_presets_list,
+            subscription_presets_save,
+            history_export_mosquitto,
+            connections_by_label,
+            connection_labels_list,
+            history_import_pcap,
+            history_export_replay,
+            connection_health,
+            history_pause,
+            history_resume,
+            history_paused_list,
+            history_export_topics,
+            publish_journal_pending,
+            publish_journal_clear,
+            history_repairs,
+            mqtt_peek,
+            connect_limits_load,
+            connect_limits_save,
+            connection_info,
+            history_payload_sizes,
+            identity_expiry,
+            credentials_audit,
+            inventory_import_csv,
+            export_log_list,
+            export_rerun,
+            connection_duplicate,
+            history_json_preview,
+            topic_catalog_generate_code,
+            benchmark_compare,
+            session_hooks_load,
+            session_hooks_save,
+            history_unread_counts,
+            history_mark_read,
+            mqtt_request,
+            rules_bundle_export,
+            rules_bundle_import,
+            mqtt_echo_latency,
+            mqtt_echo_latency_reset,
             app_ready,
         ])
         .run(tauri::generate_context!())
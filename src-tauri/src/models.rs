@@ -1,5 +1,9 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use base64::prelude::*;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Deref;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -21,6 +25,71 @@ pub struct AuthIdentity {
     pub username: Option<String>,
     pub password: Option<String>,
     pub client_id: Option<String>,
+    /// Client certificate (PEM or DER) whose notAfter date is tracked.
+    pub client_cert_path: Option<String>,
+    /// When the password, if it is a token, stops being accepted.
+    pub token_expires_at: Option<u64>,
+    /// How long before expiry to warn; `credentials::DEFAULT_WARNING_DAYS` when unset.
+    pub expiry_warning_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CredentialFindingKind {
+    PlaintextSecret,
+    WeakPassword,
+    DuplicateSecret,
+    UnusedIdentity,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialFinding {
+    pub kind: CredentialFindingKind,
+    pub severity: FindingSeverity,
+    /// Where the secret is stored, e.g. `identity 'Admin' password`; never the secret itself.
+    pub locations: Vec<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialAuditReport {
+    /// Most severe first.
+    pub findings: Vec<CredentialFinding>,
+    pub secrets_scanned: usize,
+    pub audited_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CredentialKind {
+    ClientCertificate,
+    Token,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialExpiry {
+    pub identity_id: String,
+    pub identity_name: String,
+    pub kind: CredentialKind,
+    pub expires_at: Option<u64>,
+    /// Whole days until expiry; negative once expired.
+    pub days_left: Option<i64>,
+    pub expired: bool,
+    /// Inside the identity's warning window but not yet expired.
+    pub warning: bool,
+    /// Why the expiry date couldn't be read, e.g. a missing certificate file.
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +111,213 @@ pub struct ConnectionProfile {
     pub password: Option<String>,
     pub client_id: String,
     pub clean: bool,
+    /// Raise an OS notification when the connection drops without a user-initiated disconnect.
+    #[serde(default)]
+    pub notify_on_disconnect: bool,
+    #[serde(default)]
+    pub publish_rate_limit: Option<PublishRateLimit>,
+    /// Keep reconnecting after a client id takeover instead of stopping the session.
+    #[serde(default)]
+    pub reconnect_on_takeover: bool,
+    /// Keep outgoing QoS 1/2 publishes on disk until acknowledged and republish the
+    /// unacknowledged ones after reconnecting.
+    #[serde(default)]
+    pub replay_unacked_publishes: bool,
+    /// Reconnect to the server an MQTT 5 broker points at when it disconnects with
+    /// "Use another server" or "Server moved".
+    #[serde(default)]
+    pub follow_server_redirects: bool,
+    /// Append a fresh random suffix to the client id on every connect, so the same profile
+    /// opened on two machines doesn't take over the other's session.
+    #[serde(default)]
+    pub unique_client_id: bool,
+    /// Subscribed automatically after every CONNACK. Edited through the
+    /// `subscription_presets_*` commands.
+    #[serde(default)]
+    pub default_subscriptions: Vec<SubscriptionPreset>,
+    /// Free-form labels such as `environment=prod`, for selecting connections by them.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// CA certificate (PEM or DER) trusted instead of the public roots, for brokers with a
+    /// private or self-signed CA.
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    /// Client certificate and private key presented for mutual TLS.
+    #[serde(default)]
+    pub client_cert_path: Option<String>,
+    #[serde(default)]
+    pub client_key_path: Option<String>,
+    /// Production connections refuse publishes and history clears or deletes unless the
+    /// command carries the connection's name as its confirmation.
+    #[serde(default)]
+    pub environment: Option<ConnectionEnvironment>,
+    /// Disconnect after a period without activity, for metered links where an idle
+    /// connection still costs money.
+    #[serde(default)]
+    pub idle_disconnect: Option<IdleDisconnect>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionEnvironment {
+    Dev,
+    Staging,
+    Prod,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonPreviewKind {
+    Object,
+    Array,
+    String,
+    Number,
+    Bool,
+    Null,
+}
+
+/// One value of a JSON payload preview. `children` is `None` below the requested depth; the
+/// UI asks again with this node's `path` to expand it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonPreviewNode {
+    /// Object key or array index; `None` for the previewed value itself.
+    pub key: Option<String>,
+    pub path: String,
+    pub kind: JsonPreviewKind,
+    /// Entries of an object or array, characters of a string.
+    pub size: Option<usize>,
+    /// Scalars as JSON text, strings unquoted and cut to their first 256 characters.
+    pub value: Option<String>,
+    pub value_truncated: bool,
+    pub children: Option<Vec<JsonPreviewNode>>,
+    /// Index of the first returned child.
+    pub offset: usize,
+    /// Children after the returned ones, for a "show more" that asks with a larger offset.
+    pub remaining: usize,
+}
+
+/// Fields to change on a duplicated connection profile; unset fields keep the original's
+/// values, except the client id, which is regenerated.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConnectionDuplicateOverrides {
+    pub name: Option<String>,
+    pub client_id: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDuplicate {
+    pub profile: ConnectionProfile,
+    pub topic_document: Option<ConnectionTopicDocument>,
+}
+
+/// Which CSV columns feed which fields in `inventory::import`, by header name (matched
+/// case-insensitively). Only the host column is required.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InventoryMapping {
+    /// Field separator; `,` when unset, `\t` for tabs.
+    pub delimiter: Option<String>,
+    pub host_column: Option<String>,
+    pub name_column: Option<String>,
+    pub group_column: Option<String>,
+    pub port_column: Option<String>,
+    /// `mqtt`/`tcp`, `mqtts`/`ssl`/`tls`, `ws` or `wss`.
+    pub protocol_column: Option<String>,
+    pub path_column: Option<String>,
+    /// `3.1.1` (or `4`) or `5`.
+    pub protocol_version_column: Option<String>,
+    pub username_column: Option<String>,
+    pub password_column: Option<String>,
+    pub client_id_column: Option<String>,
+    pub broker_name_column: Option<String>,
+    pub identity_name_column: Option<String>,
+    /// Columns copied into each profile's labels, under the column name.
+    pub label_columns: Vec<String>,
+    pub default_group: Option<String>,
+    /// Used when there is no port column or the cell is empty; otherwise the transport's
+    /// usual port.
+    pub default_port: Option<u16>,
+    pub default_protocol: Option<TransportProtocol>,
+    pub default_protocol_version: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryRowError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// New records from an inventory import; rows that matched existing brokers or identities
+/// reference those instead of appearing here.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryImportResult {
+    pub rows: usize,
+    pub connections: Vec<ConnectionProfile>,
+    pub brokers: Vec<BrokerConfig>,
+    pub identities: Vec<AuthIdentity>,
+    pub errors: Vec<InventoryRowError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionPreset {
+    pub topic: String,
+    pub qos: u8,
+    #[serde(default)]
+    pub options: SubscribeOptions,
+}
+
+/// MQTT 5 subscription options; ignored on 3.1.1 connections.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeOptions {
+    #[serde(default)]
+    pub no_local: bool,
+    #[serde(default)]
+    pub retain_as_published: bool,
+    #[serde(default)]
+    pub retain_handling: RetainHandling,
+}
+
+/// When the broker sends retained messages for a new subscription.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RetainHandling {
+    #[default]
+    OnSubscribe,
+    OnNewSubscribe,
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionPresetsPayload {
+    pub connection_id: String,
+    pub subscriptions: Vec<SubscriptionPreset>,
+}
+
+/// Disconnects a session after `minutes` without activity; zero disables it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IdleDisconnect {
+    pub minutes: u32,
+    /// Only commands count as activity, not incoming messages.
+    pub ignore_inbound: bool,
+}
+
+/// Outgoing publish limits; unset or zero rates are unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PublishRateLimit {
+    pub messages_per_sec: Option<u32>,
+    pub bytes_per_sec: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,13 +347,44 @@ pub enum TransportProtocol {
     Wss,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ConnectionStatus {
     Disconnected,
     Connecting,
     Connected,
     Error,
+    /// The broker dropped the session because another client connected with the same id.
+    #[serde(rename = "takenOver")]
+    TakenOver,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PeekEndReason {
+    /// The requested number of messages arrived.
+    Count,
+    Timeout,
+}
+
+/// Emitted as `mqtt-peek-ended` once a peek subscription unsubscribed itself.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeekEndedPayload {
+    pub connection_id: String,
+    pub topic: String,
+    pub received: u32,
+    pub reason: PeekEndReason,
+}
+
+/// Emitted as `mqtt-publish-saturation` when the rate limiter starts or stops holding publishes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishSaturationPayload {
+    pub connection_id: String,
+    pub saturated: bool,
+    /// Commands waiting behind the throttled publish.
+    pub queued: usize,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -87,24 +394,183 @@ pub struct MqttStatusPayload {
     pub status: ConnectionStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
+    /// Negotiated session parameters, present on `Connected`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionParameters>,
+    /// Server the broker redirected this connection to, when it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<ServerRedirect>,
+}
+
+/// Server reference from an MQTT 5 "Use another server" / "Server moved" DISCONNECT.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerRedirect {
+    /// The reference exactly as the broker sent it.
+    pub reference: String,
+    pub host: String,
+    /// Port from the reference; the current port is kept when it has none.
+    pub port: Option<u16>,
+    /// "Server moved" (permanent) rather than "Use another server" (temporary).
+    pub permanent: bool,
+    /// Whether the client is reconnecting to the referenced server.
+    pub following: bool,
+}
+
+/// Session parameters as the broker accepted them in CONNACK.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionParameters {
+    pub client_id: String,
+    /// The broker assigned the id because the client connected with an empty one.
+    pub client_id_assigned: bool,
+    pub keep_alive_secs: u16,
+    /// The broker replaced the requested keep-alive with its own.
+    pub keep_alive_overridden: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MqttMessageBatchPayload {
     pub connection_id: String,
+    /// Per-session sequence number the frontend acknowledges via `mqtt_batch_ack`.
+    pub seq: u64,
     pub messages: Vec<MqttBatchItem>,
 }
 
+/// Sent as `mqtt-message-summary` instead of a full batch while the UI is falling behind.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttBatchSummaryPayload {
+    pub connection_id: String,
+    pub seq: u64,
+    pub total: usize,
+    pub topics: Vec<MqttTopicSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttTopicSummary {
+    pub count: usize,
+    pub last: MqttBatchItem,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MqttBatchItem {
     pub topic: String,
-    pub payload: String,
+    pub payload: Payload,
     pub qos: u8,
     pub retain: bool,
     pub direction: MessageDirection,
     pub timestamp: u64,
+    /// Set by the broker on QoS 1/2 redeliveries.
+    pub dup: bool,
+    /// Packet identifier of QoS 1/2 messages; absent for QoS 0 and outgoing messages.
+    pub packet_id: Option<u16>,
+    /// MQTT v5 correlation data, as text when it is UTF-8 and `0x`-prefixed hex otherwise.
+    pub correlation_id: Option<String>,
+    pub response_topic: Option<String>,
+    /// MQTT v5 user properties, in the order they were sent.
+    pub user_properties: Vec<UserProperty>,
+    /// The payload was not UTF-8 and holds its bytes as base64.
+    pub is_binary: bool,
+}
+
+/// One MQTT v5 user property. Keys may repeat, so properties travel as an ordered list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserProperty {
+    pub key: String,
+    pub value: String,
+}
+
+/// UTF-8 message payload backed by reference-counted `Bytes`, so cloning it between the
+/// session, history, rules and emit stages never copies the body.
+///
+/// A binary payload to be published reads as its base64 and keeps the original bytes for the
+/// wire.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Payload {
+    text: Bytes,
+    binary: Option<Bytes>,
+}
+
+impl Payload {
+    /// Takes ownership of bytes to publish. Bytes that are not UTF-8 read as their base64 and
+    /// still go out unchanged.
+    pub fn from_bytes(bytes: Bytes) -> Self {
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => Self::text(bytes),
+            Err(_) => Self {
+                text: Bytes::from(BASE64_STANDARD.encode(&bytes)),
+                binary: Some(bytes),
+            },
+        }
+    }
+
+    /// Takes ownership of bytes received from a broker, keeping binary data intact as base64.
+    /// The flag tells whether the payload was encoded.
+    pub fn from_wire(bytes: Bytes) -> (Self, bool) {
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => (Self::text(bytes), false),
+            Err(_) => (Self::from(BASE64_STANDARD.encode(&bytes)), true),
+        }
+    }
+
+    /// Decodes a payload stored as base64 with an `is_binary` flag, so it is published as the
+    /// original bytes.
+    pub fn from_base64(encoded: &str) -> Result<Self, base64::DecodeError> {
+        let bytes = BASE64_STANDARD.decode(encoded.trim())?;
+        Ok(Self::from_bytes(Bytes::from(bytes)))
+    }
+
+    fn text(bytes: Bytes) -> Self {
+        Self {
+            text: bytes,
+            binary: None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: every constructor guarantees the text bytes are valid UTF-8.
+        unsafe { std::str::from_utf8_unchecked(&self.text) }
+    }
+
+    /// The text is base64 of bytes that are not UTF-8.
+    pub fn is_binary(&self) -> bool {
+        self.binary.is_some()
+    }
+
+    /// The bytes to put on the wire.
+    pub fn into_bytes(self) -> Bytes {
+        self.binary.unwrap_or(self.text)
+    }
+}
+
+impl Deref for Payload {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<String> for Payload {
+    fn from(value: String) -> Self {
+        Self::text(Bytes::from(value))
+    }
+}
+
+impl From<&str> for Payload {
+    fn from(value: &str) -> Self {
+        Self::text(Bytes::copy_from_slice(value.as_bytes()))
+    }
+}
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize)]
@@ -121,6 +587,22 @@ pub struct PayloadTemplate {
     pub name: String,
     pub topic: String,
     pub payload: String,
+    /// Connection the template belongs to; `None` makes it available on every connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_id: Option<String>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// User-editable fields of a [`PayloadTemplate`], used for create and update.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadTemplateDraft {
+    pub name: String,
+    pub topic: String,
+    pub payload: String,
+    #[serde(default)]
+    pub connection_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -156,7 +638,7 @@ pub struct ConnectionTopicDocument {
     pub topics: Vec<TopicCatalogItem>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct NativeAppConfig {
     pub connections: Vec<ConnectionProfile>,
@@ -170,28 +652,16 @@ pub struct NativeAppConfig {
     pub active_connection_id: Option<String>,
     pub publisher_templates: Vec<PayloadTemplate>,
     pub connection_topic_docs: HashMap<String, ConnectionTopicDocument>,
+    /// Directory for history exports made without an explicit path; defaults to the app's exports dir.
+    pub export_directory: Option<String>,
+    /// Filename for those exports. Placeholders: `{connection}`, `{connectionId}`, `{from}`, `{to}`,
+    /// `{timestamp}` and `{ext}`.
+    pub export_filename_template: Option<String>,
+    /// Zone for ISO-8601 times added to exports: `UTC`, `local` or an offset like `+02:00`.
+    pub export_time_zone: Option<String>,
     pub updated_at: Option<u64>,
 }
 
-impl Default for NativeAppConfig {
-    fn default() -> Self {
-        Self {
-            connections: Vec::new(),
-            brokers: Vec::new(),
-            identities: Vec::new(),
-            ai_config: None,
-            ai_prompts: None,
-            sidebar_open: None,
-            language: None,
-            theme: None,
-            active_connection_id: None,
-            publisher_templates: Vec::new(),
-            connection_topic_docs: HashMap::new(),
-            updated_at: None,
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppConfigPaths {
@@ -209,25 +679,1840 @@ pub struct HistoryMessageRecord {
     pub qos: u8,
     pub retain: bool,
     pub direction: MessageDirection,
+    pub dup: bool,
+    pub packet_id: Option<u16>,
+    pub correlation_id: Option<String>,
+    pub response_topic: Option<String>,
+    /// Time the device reported inside the payload, when a timestamp rule matched the topic.
+    pub payload_timestamp: Option<u64>,
+    pub user_properties: Vec<UserProperty>,
+    /// The payload is base64 of non-UTF-8 bytes.
+    pub is_binary: bool,
+}
+
+/// Which time history queries filter and sort by.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryTimeSource {
+    /// When the message reached the app.
+    #[default]
+    Arrival,
+    /// The timestamp extracted from the payload, falling back to arrival time.
+    Payload,
 }
 
+/// Row order for history queries, applied in SQL.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum HistorySort {
+    #[default]
+    TimestampAsc,
+    TimestampDesc,
+    /// Alphabetical by topic, oldest first within a topic.
+    Topic,
+    /// Largest payload first, oldest first among equal sizes.
+    PayloadSize,
+}
+
+/// A history record the user pinned, kept at the top of the message view across restarts.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct HistoryExportResult {
-    pub path: String,
+pub struct PinnedMessageRecord {
+    #[serde(flatten)]
+    pub record: HistoryMessageRecord,
+    pub pinned_at: u64,
+    pub note: Option<String>,
+}
+
+/// Outcome of `mqtt_request`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttRequestResult {
+    /// Correlation data sent with the request, as text.
+    pub correlation_id: String,
+    pub response_topic: String,
+    /// Time until the reply arrived, or the whole timeout when none did.
+    pub elapsed_ms: u64,
+    /// The matching reply; `None` when the request timed out.
+    pub reply: Option<MqttBatchItem>,
+}
+
+/// Incoming messages on one topic since its read marker.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicUnreadCount {
+    pub topic: String,
+    pub unread: u64,
+    pub last_message_at: u64,
+    /// When the topic was last marked read; `None` if it never was.
+    pub last_read_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationThread {
+    pub correlation_id: String,
+    pub request_topic: Option<String>,
+    pub response_topic: Option<String>,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub messages: Vec<HistoryMessageRecord>,
+}
+
+/// Disk space taken by one connection's history database and its SQLite sidecar files.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiskUsage {
+    pub connection_id: String,
+    /// Profile name; `None` for databases left behind by connections that no longer exist.
+    pub name: Option<String>,
+    pub db_bytes: u64,
+    pub wal_bytes: u64,
+    pub shm_bytes: u64,
+    pub total_bytes: u64,
+    /// Recording was paused with `history_pause`.
+    pub paused: bool,
+}
+
+/// Whether a connection's messages are currently kept out of its history.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPauseStatus {
+    pub connection_id: String,
+    pub paused: bool,
+    pub paused_at: Option<u64>,
+    /// Messages received but not stored since the pause started.
+    pub skipped_messages: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryDiskUsage {
+    pub history_dir: String,
+    /// Largest first.
+    pub connections: Vec<ConnectionDiskUsage>,
+    pub exports_bytes: u64,
+    /// Files still waiting to be deleted after a deferred removal.
+    pub pending_delete_bytes: u64,
+    pub total_bytes: u64,
+    /// Free space on the volume holding the history directory, when it can be determined.
+    pub available_bytes: Option<u64>,
+}
+
+/// What the storage guard does once free disk space drops below the threshold.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LowDiskAction {
+    /// Stop recording history until space is freed.
+    #[default]
+    PauseWrites,
+    /// Delete the oldest unpinned messages so the databases stop growing.
+    PruneOldest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StorageGuardConfig {
+    pub enabled: bool,
+    pub min_free_bytes: u64,
+    pub action: LowDiskAction,
+    /// Share of each connection's messages removed per prune pass.
+    pub prune_percent: u8,
+    pub check_interval_secs: u64,
+}
+
+impl Default for StorageGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_free_bytes: 512 * 1024 * 1024,
+            action: LowDiskAction::PauseWrites,
+            prune_percent: 10,
+            check_interval_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatus {
+    /// Free space is below the configured threshold.
+    pub low: bool,
+    pub writes_paused: bool,
+    pub available_bytes: Option<u64>,
+    pub min_free_bytes: u64,
+    pub action: LowDiskAction,
+    pub skipped_messages: u64,
+    pub pruned_messages: u64,
+    pub last_error: Option<String>,
+    pub checked_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistorySearchQuery {
+    /// Substring matched against topic and payload.
+    pub text: Option<String>,
+    pub topic_filter: Option<String>,
+    pub from_ts: Option<u64>,
+    pub to_ts: Option<u64>,
+    pub limit: Option<usize>,
+    /// Rust `regex` syntax, matched against the payload in SQL.
+    pub payload_regex: Option<String>,
+    pub time_source: HistoryTimeSource,
+    /// Newest first when unset.
+    pub sort: Option<HistorySort>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchHit {
+    pub connection_id: String,
+    #[serde(flatten)]
+    pub record: HistoryMessageRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryStreamQuery {
+    pub from_ts: Option<u64>,
+    pub to_ts: Option<u64>,
+    pub topic_filter: Option<String>,
+    pub chunk_size: Option<usize>,
+    /// Rust `regex` syntax, matched against the payload in SQL.
+    pub payload_regex: Option<String>,
+    pub time_source: HistoryTimeSource,
+    pub sort: HistorySort,
+}
+
+/// Payload of `history-query-chunk`; the last event of a job has `done` set and no records.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQueryChunk {
+    pub job_id: String,
+    pub connection_id: String,
+    pub seq: u64,
+    pub records: Vec<HistoryMessageRecord>,
+    pub done: bool,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicFieldStats {
     pub count: u64,
+    /// Messages in range whose payload had no numeric value at the path.
+    pub skipped: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub last: Option<f64>,
+    pub last_timestamp: Option<u64>,
 }
 
-#[derive(Debug, Clone)]
-pub struct ResolvedConnection {
-    pub id: String,
-    pub host: String,
-    pub port: u16,
-    pub protocol: TransportProtocol,
-    pub protocol_version: u8,
-    pub path: String,
-    pub username: Option<String>,
-    pub password: Option<String>,
-    pub client_id: String,
-    pub clean: bool,
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadSizeBucket {
+    /// Inclusive upper bound in bytes; `None` for the open-ended last bucket.
+    pub max_bytes: Option<u64>,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadSizeDistribution {
+    pub count: u64,
+    pub total_bytes: u64,
+    pub largest_bytes: u64,
+    pub buckets: Vec<PayloadSizeBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicPayloadSizes {
+    pub topic: String,
+    #[serde(flatten)]
+    pub sizes: PayloadSizeDistribution,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadSizeHistogram {
+    pub overall: PayloadSizeDistribution,
+    /// Largest payloads first, cut at the requested number of topics.
+    pub topics: Vec<TopicPayloadSizes>,
+    pub truncated: bool,
+}
+
+/// A history database that failed its integrity check and was replaced with an empty one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRepair {
+    pub file_name: String,
+    /// Where the damaged file was moved; its `-wal`/`-shm` companions sit next to it.
+    pub moved_to: String,
+    pub reason: String,
+    pub repaired_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryCheckpointResult {
+    pub connection_id: String,
+    /// True when readers prevented the checkpoint from completing.
+    pub busy: bool,
+    pub log_frames: i64,
+    pub checkpointed_frames: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryExportResult {
+    pub path: String,
+    pub count: u64,
+}
+
+/// The parameters of an export apart from its time window and output path, as logged for
+/// `export_rerun`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExportRequest {
+    #[serde(rename_all = "camelCase")]
+    History {
+        connection_id: String,
+        format: Option<String>,
+        time_zone: Option<String>,
+        payload_regex: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    HistoryAll {
+        connection_ids: Vec<String>,
+        format: Option<String>,
+        time_zone: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Influx {
+        connection_id: String,
+        mapping: InfluxMapping,
+    },
+    #[serde(rename_all = "camelCase")]
+    Elasticsearch {
+        connection_id: String,
+        options: ElasticsearchExportOptions,
+    },
+    #[serde(rename_all = "camelCase")]
+    Mosquitto {
+        connection_id: String,
+        output_format: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Replay {
+        connection_id: String,
+        options: ReplayExportOptions,
+    },
+    #[serde(rename_all = "camelCase")]
+    Topics {
+        connection_id: String,
+        options: TopicListExportOptions,
+    },
+}
+
+/// A completed export in the export log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRecord {
+    pub id: String,
+    pub label: String,
+    pub request: ExportRequest,
+    pub from_ts: Option<u64>,
+    pub to_ts: Option<u64>,
+    pub path: String,
+    pub count: u64,
+    pub started_at: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportLogDocument {
+    /// Newest first.
+    pub records: Vec<ExportRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedConnection {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub protocol: TransportProtocol,
+    pub protocol_version: u8,
+    pub path: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub client_id: String,
+    pub clean: bool,
+    pub notify_on_disconnect: bool,
+    pub publish_rate_limit: Option<PublishRateLimit>,
+    pub reconnect_on_takeover: bool,
+    pub replay_unacked_publishes: bool,
+    pub follow_server_redirects: bool,
+    pub default_subscriptions: Vec<SubscriptionPreset>,
+    pub idle_disconnect: Option<IdleDisconnect>,
+    /// Custom CA or client certificate config for TLS transports, built from the profile.
+    pub tls_config: Option<Arc<rustls::ClientConfig>>,
+    /// Redirects already followed to reach this server, to stop redirect loops.
+    pub redirect_hops: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub connection_id: Option<String>,
+    pub topic_filter: String,
+    pub payload_contains: Option<String>,
+    pub threshold: Option<AlertThreshold>,
+    pub actions: Vec<RuleAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertThreshold {
+    pub path: String,
+    pub operator: ThresholdOperator,
+    pub value: f64,
+    #[serde(default = "default_consecutive")]
+    pub consecutive: u32,
+    pub clear_value: Option<f64>,
+}
+
+fn default_consecutive() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdOperator {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Neq,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleAction {
+    #[serde(rename_all = "camelCase")]
+    RunCommand {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        payload_mode: CommandPayloadMode,
+        timeout_ms: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Notify { title: Option<String> },
+    #[serde(rename_all = "camelCase")]
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Influx {
+        url: String,
+        org: Option<String>,
+        bucket: String,
+        token: Option<String>,
+        mapping: InfluxMapping,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandPayloadMode {
+    #[default]
+    Stdin,
+    Argument,
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutomationConfig {
+    pub rules: Vec<AutomationRule>,
+    pub command_allow_list: Vec<String>,
+}
+
+/// Automation rules shared as one file, as written by `rules_bundle_export`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: u64,
+    pub rules: Vec<AutomationRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleBundleImportResult {
+    pub added: usize,
+    pub replaced: usize,
+    pub config: AutomationConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleActionPayload {
+    pub rule_id: String,
+    pub connection_id: String,
+    pub topic: String,
+    pub action: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertEventKind {
+    Raised,
+    Cleared,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEventRecord {
+    pub id: i64,
+    pub timestamp: u64,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub connection_id: String,
+    pub topic: String,
+    pub path: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub kind: AlertEventKind,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTestSample {
+    pub topic: String,
+    pub payload: String,
+    pub connection_id: Option<String>,
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleTestRange {
+    pub connection_id: Option<String>,
+    pub from_ts: Option<u64>,
+    pub to_ts: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTestMatch {
+    pub topic: String,
+    pub payload: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<AlertEventKind>,
+    pub actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTestResult {
+    pub evaluated: usize,
+    pub matched: usize,
+    pub matches: Vec<RuleTestMatch>,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElasticsearchExportOptions {
+    pub index: String,
+    pub timestamp_field: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedBrokerConfig {
+    pub port: u16,
+    pub bind_address: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedBrokerClient {
+    pub client_id: String,
+    pub username: Option<String>,
+    pub remote_addr: String,
+    pub connected_at: u64,
+    pub subscriptions: Vec<String>,
+    pub messages_in: u64,
+    pub messages_out: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedBrokerStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub bind_address: Option<String>,
+    pub auth_enabled: bool,
+    pub started_at: Option<u64>,
+    pub retained_count: usize,
+    pub clients: Vec<EmbeddedBrokerClient>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InfluxMapping {
+    pub measurement: String,
+    pub tags: Vec<InfluxColumnMapping>,
+    pub fields: Vec<InfluxColumnMapping>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfluxColumnMapping {
+    pub name: String,
+    pub segment: Option<usize>,
+    pub path: Option<String>,
+    /// Writes whole numbers as line-protocol integers; numbers are floats otherwise.
+    #[serde(default)]
+    pub integer: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IntegrationsConfig {
+    pub kafka_bridges: Vec<KafkaBridgeConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KafkaBridgeConfig {
+    pub id: String,
+    pub name: String,
+    pub connection_id: String,
+    pub topic_filter: String,
+    pub brokers: Vec<String>,
+    pub kafka_topic: String,
+    pub partition: Option<i32>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub sasl_mechanism: Option<String>,
+    #[serde(default)]
+    pub tls: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KafkaBridgeMetrics {
+    pub id: String,
+    pub running: bool,
+    pub started_at: Option<u64>,
+    pub forwarded: u64,
+    pub failed: u64,
+    pub pending: u64,
+    pub last_forwarded_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileWatchersConfig {
+    pub watchers: Vec<FileWatcherConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FileWatchMode {
+    /// Publish the file's contents as the payload.
+    #[default]
+    Contents,
+    /// Publish a JSON description of the change instead of the file.
+    Notification,
+}
+
+/// Polls a directory and publishes files matching `pattern` when they change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileWatcherConfig {
+    pub id: String,
+    pub name: String,
+    pub connection_id: String,
+    /// Directory to watch.
+    pub path: String,
+    /// Glob matched against file names, or against the path relative to `path` when it
+    /// contains a `/`.
+    #[serde(default = "default_watch_pattern")]
+    pub pattern: String,
+    #[serde(default)]
+    pub recursive: bool,
+    /// Topic with `{name}`, `{stem}`, `{ext}`, `{path}`, `{dir}` and `{event}` placeholders.
+    pub topic_template: String,
+    #[serde(default)]
+    pub mode: FileWatchMode,
+    #[serde(default)]
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    /// Publish files already present when the watcher starts.
+    #[serde(default)]
+    pub publish_existing: bool,
+    #[serde(default = "default_watch_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+fn default_watch_pattern() -> String {
+    "*".to_string()
+}
+
+fn default_watch_interval_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileWatcherMetrics {
+    pub id: String,
+    pub running: bool,
+    pub started_at: Option<u64>,
+    pub watched_files: u64,
+    pub published: u64,
+    pub failed: u64,
+    pub last_published_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SocketTapsConfig {
+    pub taps: Vec<SocketTapConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SocketTapProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+/// Streams received messages matching `topic_filter` to a TCP or UDP endpoint, one JSON object per line
+/// (TCP) or per datagram (UDP).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketTapConfig {
+    pub id: String,
+    pub name: String,
+    pub connection_id: String,
+    pub topic_filter: String,
+    #[serde(default)]
+    pub protocol: SocketTapProtocol,
+    #[serde(default = "default_tap_host")]
+    pub host: String,
+    pub port: u16,
+}
+
+fn default_tap_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketTapMetrics {
+    pub id: String,
+    pub running: bool,
+    /// Whether the TCP connection is currently open; always true for UDP while running.
+    pub connected: bool,
+    pub started_at: Option<u64>,
+    pub forwarded: u64,
+    /// Messages dropped because the endpoint was unreachable or not keeping up.
+    pub dropped: u64,
+    pub last_forwarded_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// What `app_wipe_all_data` removed. Failures are listed rather than stopping the wipe.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataWipeReport {
+    pub disconnected: Vec<String>,
+    pub history_databases: u64,
+    pub export_files: u64,
+    /// Top-level files and folders removed from the app config directory.
+    pub config_entries: Vec<String>,
+    pub bytes_removed: u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TelemetryConfig {
+    pub otlp_endpoint: Option<String>,
+    pub service_name: Option<String>,
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryStatus {
+    pub config: TelemetryConfig,
+    pub active: bool,
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ControlApiConfig {
+    pub enabled: bool,
+    pub port: Option<u16>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlApiStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub started_at: Option<u64>,
+    pub config: ControlApiConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlApiConnection {
+    pub id: String,
+    pub name: String,
+    pub group: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    pub connected: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeedConfig {
+    pub enabled: bool,
+    pub port: Option<u16>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub started_at: Option<u64>,
+    pub subscribers: usize,
+    pub config: FeedConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaEntity {
+    pub component: String,
+    pub node_id: Option<String>,
+    pub object_id: String,
+    pub unique_id: Option<String>,
+    pub name: Option<String>,
+    pub discovery_topic: String,
+    pub state_topic: Option<String>,
+    pub command_topic: Option<String>,
+    pub availability_topics: Vec<String>,
+    pub json_attributes_topic: Option<String>,
+    pub device_class: Option<String>,
+    pub unit_of_measurement: Option<String>,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaDevice {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub sw_version: Option<String>,
+    pub entities: Vec<HaEntity>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HaDiscoveryRegistry {
+    pub prefix: String,
+    pub devices: Vec<HaDevice>,
+    pub entity_count: usize,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TopicCatalogMergeStrategy {
+    Replace,
+    #[default]
+    MergeByTopic,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicCatalogImportResult {
+    pub connection_id: String,
+    pub document: ConnectionTopicDocument,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredTopic {
+    pub topic: String,
+    pub message_count: u64,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub qos: u8,
+    pub retain: bool,
+    pub content_type: String,
+    pub sample_payload: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicDiscoveryDraft {
+    pub connection_id: String,
+    pub active: bool,
+    pub started_at: Option<u64>,
+    pub truncated: bool,
+    pub topics: Vec<DiscoveredTopic>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerSysStats {
+    pub connection_id: String,
+    pub broker: Option<String>,
+    pub node: Option<String>,
+    pub version: Option<String>,
+    pub uptime_seconds: Option<u64>,
+    pub clients_connected: Option<u64>,
+    pub clients_total: Option<u64>,
+    pub clients_maximum: Option<u64>,
+    pub messages_received: Option<u64>,
+    pub messages_sent: Option<u64>,
+    pub messages_received_per_min: Option<f64>,
+    pub messages_sent_per_min: Option<f64>,
+    pub bytes_received: Option<u64>,
+    pub bytes_sent: Option<u64>,
+    pub retained_messages: Option<u64>,
+    pub subscriptions: Option<u64>,
+    pub topics: Option<u64>,
+    pub heap_bytes: Option<u64>,
+    pub updated_at: u64,
+    pub raw: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionEventKind {
+    Connecting,
+    Reconnecting,
+    Connected,
+    Disconnected,
+    Error,
+    TakenOver,
+    Subscribed,
+    Unsubscribed,
+    HealthChanged,
+}
+
+impl ConnectionEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Connecting => "connecting",
+            Self::Reconnecting => "reconnecting",
+            Self::Connected => "connected",
+            Self::Disconnected => "disconnected",
+            Self::Error => "error",
+            Self::TakenOver => "takenOver",
+            Self::Subscribed => "subscribed",
+            Self::Unsubscribed => "unsubscribed",
+            Self::HealthChanged => "healthChanged",
+        }
+    }
+
+    pub fn from_str_lossy(value: &str) -> Self {
+        match value {
+            "connecting" => Self::Connecting,
+            "reconnecting" => Self::Reconnecting,
+            "connected" => Self::Connected,
+            "disconnected" => Self::Disconnected,
+            "takenOver" => Self::TakenOver,
+            "subscribed" => Self::Subscribed,
+            "unsubscribed" => Self::Unsubscribed,
+            "healthChanged" => Self::HealthChanged,
+            _ => Self::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionEventRecord {
+    pub id: i64,
+    pub timestamp: u64,
+    pub kind: ConnectionEventKind,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportSummary {
+    pub file_name: String,
+    pub path: String,
+    pub created_at: u64,
+    pub size: u64,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayBadges {
+    pub unread: u32,
+    pub alerts: u32,
+}
+
+/// Arguments (including deep links) of a launch that was redirected to the running instance.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondInstancePayload {
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShortcutAction {
+    PublishLastPayload,
+    TogglePauseStream,
+    ToggleWindow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutBinding {
+    pub action: ShortcutAction,
+    pub accelerator: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShortcutsConfig {
+    pub bindings: Vec<ShortcutBinding>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalShortcutPayload {
+    pub action: ShortcutAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MemoryLimitsConfig {
+    pub max_queued_messages: usize,
+    pub max_queued_bytes: u64,
+    pub max_discovery_topics: usize,
+    /// Messages kept in memory per connection for `messages_recent`; 0 disables the buffer.
+    pub recent_messages: usize,
+}
+
+impl Default for MemoryLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_queued_messages: 50_000,
+            max_queued_bytes: 64 * 1024 * 1024,
+            max_discovery_topics: 5000,
+            recent_messages: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConnectLimitsConfig {
+    /// Connection attempts allowed to be between starting and the CONNACK at the same time.
+    pub max_concurrent_connects: usize,
+    /// Minimum gap between the starts of two connection attempts.
+    pub stagger_ms: u64,
+}
+
+impl Default for ConnectLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_connects: 4,
+            stagger_ms: 250,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionMemoryMetrics {
+    pub connection_id: String,
+    pub connected: bool,
+    pub queued_messages: usize,
+    pub queued_bytes: u64,
+    pub dropped_messages: u64,
+    pub discovery_topics: usize,
+    pub discovery_bytes: u64,
+    pub recent_messages: usize,
+    pub recent_bytes: u64,
+    pub missed_pingresps: u64,
+    pub last_ping_rtt_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeMetrics {
+    pub resident_bytes: Option<u64>,
+    pub limits: MemoryLimitsConfig,
+    pub connections: Vec<ConnectionMemoryMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientIdCollision {
+    pub client_id: String,
+    pub broker: String,
+    pub connection_ids: Vec<String>,
+}
+
+/// Publish limits advertised by the broker (v5 CONNACK) or imposed by the client.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerCapabilities {
+    pub max_qos: u8,
+    pub max_packet_size: Option<u32>,
+    pub retain_available: bool,
+}
+
+impl Default for BrokerCapabilities {
+    fn default() -> Self {
+        Self {
+            max_qos: 2,
+            max_packet_size: None,
+            retain_available: true,
+        }
+    }
+}
+
+/// Transport details of a live connection, from the probe in `mqtt::endpoint`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionInfo {
+    pub connection_id: String,
+    pub transport: TransportProtocol,
+    pub protocol_version: u8,
+    pub host: String,
+    pub port: u16,
+    pub websocket_url: Option<String>,
+    /// Every address the host resolved to, in resolver order.
+    pub resolved_addresses: Vec<String>,
+    /// The address that accepted the connection, as `ip:port`.
+    pub remote_address: Option<String>,
+    pub local_address: Option<String>,
+    pub tls_version: Option<String>,
+    pub tls_cipher: Option<String>,
+    pub alpn: Option<String>,
+    /// Why part of the details is missing, e.g. a failed TLS handshake.
+    pub probe_error: Option<String>,
+    pub measured_at: u64,
+    /// Keep-alive the session pings on, after any CONNACK override; 0 means the broker
+    /// disabled it.
+    pub keep_alive_secs: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PublishViolationKind {
+    Topic,
+    Qos,
+    PayloadSize,
+    Schema,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishViolation {
+    pub kind: PublishViolationKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicFilterValidation {
+    pub valid: bool,
+    pub shared_group: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// The scripted load `benchmark_compare` runs against each broker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BenchmarkWorkload {
+    /// Publish rates, in messages per second, of the ladder's single-subscriber steps.
+    pub rate_steps: Vec<u32>,
+    pub step_secs: u32,
+    /// Subscribers of the fan-out step; 0 skips it.
+    pub fan_out_subscribers: u32,
+    pub fan_out_rate: u32,
+    pub payload_bytes: usize,
+    pub qos: u8,
+}
+
+impl Default for BenchmarkWorkload {
+    fn default() -> Self {
+        Self {
+            rate_steps: vec![100, 500, 1_000],
+            step_secs: 5,
+            fan_out_subscribers: 5,
+            fan_out_rate: 100,
+            payload_bytes: 256,
+            qos: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BenchmarkStepKind {
+    RateLadder,
+    FanOut,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkStep {
+    pub kind: BenchmarkStepKind,
+    pub target_rate: u32,
+    pub subscribers: u32,
+    pub sent: u64,
+    /// Deliveries due: messages sent times subscribers.
+    pub expected: u64,
+    pub received: u64,
+    /// Publishes the client refused to queue.
+    pub publish_errors: u64,
+    /// Connection errors of the step's clients after they connected.
+    pub connection_errors: u64,
+    pub sent_per_sec: f64,
+    pub received_per_sec: f64,
+    /// Share of expected deliveries that didn't arrive, 0 to 1.
+    pub loss_rate: f64,
+    pub latency_p50_ms: Option<f64>,
+    pub latency_p95_ms: Option<f64>,
+    pub latency_p99_ms: Option<f64>,
+    pub latency_max_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkBrokerResult {
+    pub connection_id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub steps: Vec<BenchmarkStep>,
+    /// Set when every step failed, e.g. because the broker was unreachable.
+    pub error: Option<String>,
+}
+
+/// The connections that did best in one step; `None` when no broker completed it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkComparison {
+    pub step: usize,
+    pub kind: BenchmarkStepKind,
+    pub target_rate: u32,
+    pub subscribers: u32,
+    pub best_throughput: Option<String>,
+    /// Lowest 95th percentile latency.
+    pub best_latency: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub workload: BenchmarkWorkload,
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub brokers: Vec<BenchmarkBrokerResult>,
+    pub comparisons: Vec<BenchmarkComparison>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    HistoryExport,
+    HistoryExportAll,
+    InfluxExport,
+    ElasticsearchExport,
+    TextExport,
+    ReplayExport,
+    TopicListExport,
+    EventsExport,
+    HistoryQuery,
+    CatalogImport,
+    SnapshotImport,
+    PcapImport,
+    Benchmark,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of `job-progress` events and entries of `jobs_list`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    pub job_id: String,
+    pub kind: JobKind,
+    pub label: String,
+    pub state: JobState,
+    pub done: u64,
+    pub total: Option<u64>,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    /// The operation's return value, on `Completed`.
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderPluginConfig {
+    pub id: String,
+    pub name: String,
+    /// Path to the `.wasm` module.
+    pub path: String,
+    /// Topic filters whose payloads the plugin decodes (and encodes, if it exports `encode`).
+    pub topics: Vec<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PluginsConfig {
+    pub decoders: Vec<DecoderPluginConfig>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderPluginStatus {
+    pub id: String,
+    pub name: String,
+    pub loaded: bool,
+    pub supports_encode: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformScript {
+    pub script: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PayloadTimestampFormat {
+    /// Numbers are epoch seconds or milliseconds by magnitude; strings are RFC 3339.
+    #[default]
+    Auto,
+    EpochSeconds,
+    EpochMillis,
+    Rfc3339,
+    /// A `chrono` format string in `pattern`; values without an offset are taken as UTC.
+    Pattern,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadTimestampRule {
+    pub topic_filter: String,
+    /// JSONPath of the timestamp inside the payload, e.g. `$.ts`.
+    pub path: String,
+    #[serde(default)]
+    pub format: PayloadTimestampFormat,
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PayloadTimestampsConfig {
+    /// Timestamp rules keyed by connection id; the first rule matching a topic wins.
+    pub connections: HashMap<String, Vec<PayloadTimestampRule>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransformsConfig {
+    /// Inbound transform scripts keyed by connection id.
+    pub connections: HashMap<String, TransformScript>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformErrorPayload {
+    pub connection_id: String,
+    pub topic: String,
+    pub error: String,
+    /// Errors since the previous event that were not reported individually.
+    pub suppressed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHookScript {
+    pub script: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Topic filter selecting the incoming messages passed to `onMessage`; without one the
+    /// hook is never called.
+    #[serde(default)]
+    pub message_filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionHooksConfig {
+    /// Session hook scripts keyed by connection id.
+    pub connections: HashMap<String, SessionHookScript>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHookErrorPayload {
+    pub connection_id: String,
+    /// Hook function that failed: `onConnect`, `onDisconnect` or `onMessage`.
+    pub hook: String,
+    pub error: String,
+    /// Errors since the previous event that were not reported individually.
+    pub suppressed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformTestResult {
+    pub dropped: bool,
+    pub topic: String,
+    pub payload: String,
+}
+
+/// Last value of every topic on a connection at a point in time, as written by
+/// `retained_snapshot_export`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetainedSnapshot {
+    pub version: u32,
+    #[serde(default)]
+    pub connection_id: String,
+    #[serde(default)]
+    pub captured_at: u64,
+    pub entries: Vec<RetainedSnapshotEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetainedSnapshotEntry {
+    pub topic: String,
+    pub payload: String,
+    /// The payload is base64 of non-UTF-8 bytes.
+    #[serde(default)]
+    pub is_binary: bool,
+    #[serde(default)]
+    pub qos: u8,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PcapImportOptions {
+    /// TCP ports the broker listened on; traffic to them is outgoing, from them incoming.
+    pub broker_ports: Vec<u16>,
+    /// Protocol level for sessions whose CONNECT is not in the capture; 4 when unset.
+    pub protocol_version: Option<u8>,
+    pub topic_filter: Option<String>,
+}
+
+impl Default for PcapImportOptions {
+    fn default() -> Self {
+        Self {
+            broker_ports: vec![1883],
+            protocol_version: None,
+            topic_filter: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayFormat {
+    /// A header line, then one `{offsetMs, topic, payload, qos, retain}` object per message.
+    #[default]
+    Ndjson,
+    /// A POSIX shell script calling `mosquitto_pub` with `sleep` in between.
+    Shell,
+}
+
+/// What a replay does with messages that were received with the retain flag.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayRetainMode {
+    /// Republish them retained, as recorded.
+    #[default]
+    Preserve,
+    /// Republish them as ordinary messages, so the target broker's retained store stays as is.
+    Strip,
+    /// Leave them out.
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReplayExportOptions {
+    pub format: ReplayFormat,
+    /// Also replay messages this app published; by default only received traffic is kept.
+    pub include_outgoing: bool,
+    pub topic_filter: Option<String>,
+    /// Longest pause kept between two messages, so idle stretches don't stall the replay.
+    pub max_gap_ms: Option<u64>,
+    /// Playback speed multiplier applied to the recorded gaps.
+    pub speed: f64,
+    pub retain: ReplayRetainMode,
+}
+
+impl Default for ReplayExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ReplayFormat::default(),
+            include_outgoing: false,
+            topic_filter: None,
+            max_gap_ms: None,
+            speed: 1.0,
+            retain: ReplayRetainMode::default(),
+        }
+    }
+}
+
+/// An outgoing QoS 1/2 publish not yet acknowledged by the broker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournaledPublish {
+    pub id: u64,
+    pub publish_id: u64,
+    pub topic: String,
+    pub payload: String,
+    /// The payload is base64 of non-UTF-8 bytes.
+    #[serde(default)]
+    pub is_binary: bool,
+    pub qos: u8,
+    pub retain: bool,
+    #[serde(default)]
+    pub user_properties: Vec<UserProperty>,
+    pub journaled_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PublishJournalDocument {
+    pub connections: BTreeMap<String, Vec<JournaledPublish>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TopicListFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// What `history_export_topics` writes for each distinct topic besides its name and count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TopicListExportOptions {
+    pub format: TopicListFormat,
+    /// The most recent payload seen on the topic.
+    pub include_samples: bool,
+    /// First and last message timestamps.
+    pub include_seen: bool,
+    pub topic_filter: Option<String>,
+}
+
+impl Default for TopicListExportOptions {
+    fn default() -> Self {
+        Self {
+            format: TopicListFormat::default(),
+            include_samples: true,
+            include_seen: true,
+            topic_filter: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PcapImportResult {
+    pub packets: u64,
+    pub streams: u64,
+    pub imported: u64,
+    /// PUBLISH packets left out by the topic filter.
+    pub filtered: u64,
+    /// Packets that could not be decoded, usually because the capture started mid-packet.
+    pub malformed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetainedSnapshotImportResult {
+    pub published: usize,
+    /// Entries left out by the topic filter.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PingEventKind {
+    Sent,
+    Response,
+    /// A PINGREQ got no PINGRESP before the next one was due or the connection dropped.
+    Missed,
+}
+
+/// Payload of `mqtt-ping`, emitted only for connections with ping debugging switched on.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttPingPayload {
+    pub connection_id: String,
+    pub kind: PingEventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtt_ms: Option<u64>,
+    pub timestamp: u64,
+}
+
+/// Keep-alive round trips of a connection's current session.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KeepAliveStats {
+    pub connection_id: String,
+    pub debug: bool,
+    pub pings_sent: u64,
+    pub responses: u64,
+    pub missed_pingresps: u64,
+    pub last_rtt_ms: Option<u64>,
+    pub max_rtt_ms: Option<u64>,
+    pub last_ping_at: Option<u64>,
+    /// Effective keep-alive once connected, including a server override; 0 means the broker
+    /// disabled keep-alive.
+    pub interval_secs: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthGrade {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthGrade {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded => "degraded",
+            Self::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+/// A connection's health score (0-100) over the last hour and the inputs behind it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionHealth {
+    pub connection_id: String,
+    pub score: u8,
+    pub grade: HealthGrade,
+    pub reconnects: u32,
+    pub errors: u32,
+    pub missed_pings: u32,
+    pub rtt_avg_ms: Option<u64>,
+    pub rtt_jitter_ms: Option<u64>,
+    pub ack_latency_ms: Option<u64>,
+    pub computed_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Qos2StepKind {
+    Publish,
+    /// The PUBLISH was sent again with the DUP flag, typically after a reconnect.
+    Retransmit,
+    PubRec,
+    PubRel,
+    PubComp,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Qos2Step {
+    pub kind: Qos2StepKind,
+    pub timestamp: u64,
+    /// Reason reported by an MQTT 5 broker when the step did not succeed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+/// One outgoing QoS 2 message and the packets exchanged for it so far.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiPublishResult {
+    pub connection_id: String,
+    /// Set when the publish was queued; match it against `mqtt-publish-ack` events.
+    pub publish_id: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PublishAckPacket {
+    Puback,
+    Pubrec,
+    Pubcomp,
+}
+
+/// Broker acknowledgement of one QoS 1 / 2 publish, matched to the id `mqtt_publish` returned.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishAckPayload {
+    pub connection_id: String,
+    pub publish_id: u64,
+    pub topic: String,
+    pub packet_id: u16,
+    pub qos: u8,
+    /// Packet that settled the publish: PUBACK for QoS 1, PUBCOMP for QoS 2, or a failing PUBREC.
+    pub packet: PublishAckPacket,
+    /// Time from writing the publish to this acknowledgement.
+    pub latency_ms: u64,
+    /// False for reason codes of 0x80 and above.
+    pub success: bool,
+    pub reason_code: u8,
+    pub reason: String,
+    /// Optional human-readable detail sent by the broker.
+    pub reason_string: Option<String>,
+}
+
+/// Publish-to-echo latency of one topic the session both publishes to and subscribes to.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EchoLatencyStats {
+    pub topic: String,
+    pub samples: u64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+    pub last_ms: f64,
+    /// Publishes still waiting for their echo.
+    pub awaiting_echo: usize,
+    pub bucket_ms: u64,
+    /// Upper bounds of `EchoLatencyBucket::bins`; the extra last bin holds slower samples.
+    pub bin_upper_ms: Vec<f64>,
+    /// Oldest first; minutes without samples are absent.
+    pub buckets: Vec<EchoLatencyBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EchoLatencyBucket {
+    pub start: u64,
+    pub samples: u32,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+    pub bins: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionAckKind {
+    Subscribe,
+    Unsubscribe,
+}
+
+/// Broker result for one topic filter of a SUBSCRIBE or UNSUBSCRIBE.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionResultPayload {
+    pub connection_id: String,
+    pub kind: SubscriptionAckKind,
+    pub topic: String,
+    pub packet_id: u16,
+    /// False for reason codes of 0x80 and above.
+    pub success: bool,
+    /// QoS the broker granted; set for accepted subscriptions only.
+    pub granted_qos: Option<u8>,
+    pub reason_code: u8,
+    pub reason: String,
+    /// Optional human-readable detail sent by an MQTT 5 broker.
+    pub reason_string: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Qos2Flow {
+    pub connection_id: String,
+    pub packet_id: u16,
+    pub topic: String,
+    pub queued_at: u64,
+    pub steps: Vec<Qos2Step>,
+    /// PUBCOMP arrived, or the broker rejected the message.
+    pub complete: bool,
+    pub total_ms: Option<u64>,
 }
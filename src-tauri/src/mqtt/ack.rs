@@ -0,0 +1,256 @@
+use crate::models::{PublishAckPacket, PublishAckPayload};
+use crate::windows;
+use dashmap::DashMap;
+use rumqttc::v5::mqttbytes::v5::{PubAckReason, PubCompReason, PubRecReason};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::AppHandle;
+
+/// Delivers the broker's acknowledgement of each outgoing QoS 1 / 2 publish, so a rejected
+/// message shows up as a failure instead of looking sent.
+///
+/// QoS 1 publishes are confirmed by their PUBACK and QoS 2 publishes by their PUBCOMP; a failing
+/// PUBREC ends a QoS 2 flow early. MQTT 3.1.1 acknowledgements carry no reason code and always
+/// report success.
+///
+/// Publishes are queued by the command loop and matched to `Outgoing::Publish` events in order,
+/// the same way [`super::qos2::Qos2Tracer`] does, then to acknowledgements by packet id.
+#[derive(Clone, Default)]
+pub struct PublishAcks {
+    connections: Arc<DashMap<String, Arc<Mutex<AckState>>>>,
+}
+
+#[derive(Default)]
+struct AckState {
+    queued: VecDeque<QueuedPublish>,
+    /// Written publishes awaiting their acknowledgement, by packet id.
+    in_flight: HashMap<u16, QueuedPublish>,
+}
+
+struct QueuedPublish {
+    publish_id: u64,
+    topic: String,
+    qos: u8,
+    /// When the publish was written, for acknowledgement latency.
+    sent_at: Option<Instant>,
+}
+
+impl PublishAcks {
+    /// Called by the command loop just before a publish is handed to rumqttc.
+    pub fn queued(&self, connection_id: &str, publish_id: u64, topic: &str, qos: u8) {
+        lock(&self.state(connection_id))
+            .queued
+            .push_back(QueuedPublish {
+                publish_id,
+                topic: topic.to_string(),
+                qos,
+                sent_at: None,
+            });
+    }
+
+    /// Undoes [`Self::queued`] when rumqttc rejected the publish.
+    pub fn rejected(&self, connection_id: &str) {
+        if let Some(state) = self.connections.get(connection_id) {
+            lock(&state).queued.pop_back();
+        }
+    }
+
+    pub fn publish_sent(&self, connection_id: &str, packet_id: u16) {
+        let Some(state) = self.connections.get(connection_id) else {
+            return;
+        };
+        let mut state = lock(&state);
+        if packet_id != 0 && state.in_flight.contains_key(&packet_id) {
+            // Retransmission after reconnecting; the original entry still waits for its ack.
+            return;
+        }
+        let Some(mut publish) = state.queued.pop_front() else {
+            return;
+        };
+        publish.sent_at = Some(Instant::now());
+        if publish.qos > 0 && packet_id != 0 {
+            state.in_flight.insert(packet_id, publish);
+        }
+    }
+
+    /// Handles the PUBACK or PUBREC for `packet_id`, returning the acknowledgement latency in
+    /// milliseconds and whether the broker accepted the publish.
+    ///
+    /// Emits `mqtt-publish-ack` unless an accepted QoS 2 publish still waits for its PUBCOMP.
+    pub fn acked(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        packet_id: u16,
+        packet: PublishAckPacket,
+        reason_code: u8,
+        reason_string: Option<String>,
+    ) -> Option<(u64, bool)> {
+        let state = self.connections.get(connection_id)?;
+        let mut state = lock(&state);
+        let publish = state.in_flight.remove(&packet_id)?;
+        let latency_ms = elapsed_ms(&publish);
+        // 0x10 "no matching subscribers" is still a successful publish.
+        let success = reason_code < 0x80;
+        if success && matches!(packet, PublishAckPacket::Pubrec) {
+            state.in_flight.insert(packet_id, publish);
+            return Some((latency_ms, success));
+        }
+        drop(state);
+        emit(
+            app,
+            connection_id,
+            packet_id,
+            publish,
+            packet,
+            reason_code,
+            reason_string,
+        );
+        Some((latency_ms, success))
+    }
+
+    /// Handles the PUBCOMP that completes the QoS 2 publish that used `packet_id`.
+    pub fn completed(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        packet_id: u16,
+        reason_code: u8,
+        reason_string: Option<String>,
+    ) {
+        let Some(publish) = self
+            .connections
+            .get(connection_id)
+            .and_then(|state| lock(&state).in_flight.remove(&packet_id))
+        else {
+            return;
+        };
+        emit(
+            app,
+            connection_id,
+            packet_id,
+            publish,
+            PublishAckPacket::Pubcomp,
+            reason_code,
+            reason_string,
+        );
+    }
+
+    /// Drops everything tracked for a session that is going away.
+    pub fn remove(&self, connection_id: &str) {
+        self.connections.remove(connection_id);
+    }
+
+    fn state(&self, connection_id: &str) -> Arc<Mutex<AckState>> {
+        Arc::clone(
+            self.connections
+                .entry(connection_id.to_string())
+                .or_default()
+                .value(),
+        )
+    }
+}
+
+fn emit(
+    app: &AppHandle,
+    connection_id: &str,
+    packet_id: u16,
+    publish: QueuedPublish,
+    packet: PublishAckPacket,
+    reason_code: u8,
+    reason_string: Option<String>,
+) {
+    let latency_ms = elapsed_ms(&publish);
+    let success = reason_code < 0x80;
+    if !success {
+        tracing::warn!(
+            connection_id,
+            topic = publish.topic.as_str(),
+            reason = reason_name(reason_code),
+            "broker rejected publish"
+        );
+    }
+    windows::emit_for_connection(
+        app,
+        "mqtt-publish-ack",
+        connection_id,
+        PublishAckPayload {
+            connection_id: connection_id.to_string(),
+            publish_id: publish.publish_id,
+            topic: publish.topic,
+            packet_id,
+            qos: publish.qos,
+            packet,
+            latency_ms,
+            success,
+            reason_code,
+            reason: reason_name(reason_code).to_string(),
+            reason_string,
+        },
+    );
+}
+
+fn elapsed_ms(publish: &QueuedPublish) -> u64 {
+    publish
+        .sent_at
+        .map_or(0, |sent_at| sent_at.elapsed().as_millis() as u64)
+}
+
+fn lock(state: &Mutex<AckState>) -> std::sync::MutexGuard<'_, AckState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub fn puback_code(reason: PubAckReason) -> u8 {
+    match reason {
+        PubAckReason::Success => 0x00,
+        PubAckReason::NoMatchingSubscribers => 0x10,
+        PubAckReason::UnspecifiedError => 0x80,
+        PubAckReason::ImplementationSpecificError => 0x83,
+        PubAckReason::NotAuthorized => 0x87,
+        PubAckReason::TopicNameInvalid => 0x90,
+        PubAckReason::PacketIdentifierInUse => 0x91,
+        PubAckReason::QuotaExceeded => 0x97,
+        PubAckReason::PayloadFormatInvalid => 0x99,
+    }
+}
+
+pub fn pubrec_code(reason: PubRecReason) -> u8 {
+    match reason {
+        PubRecReason::Success => 0x00,
+        PubRecReason::NoMatchingSubscribers => 0x10,
+        PubRecReason::UnspecifiedError => 0x80,
+        PubRecReason::ImplementationSpecificError => 0x83,
+        PubRecReason::NotAuthorized => 0x87,
+        PubRecReason::TopicNameInvalid => 0x90,
+        PubRecReason::PacketIdentifierInUse => 0x91,
+        PubRecReason::QuotaExceeded => 0x97,
+        PubRecReason::PayloadFormatInvalid => 0x99,
+    }
+}
+
+pub fn pubcomp_code(reason: PubCompReason) -> u8 {
+    match reason {
+        PubCompReason::Success => 0x00,
+        PubCompReason::PacketIdentifierNotFound => 0x92,
+    }
+}
+
+/// PUBACK, PUBREC and PUBCOMP share one reason code table.
+fn reason_name(code: u8) -> &'static str {
+    match code {
+        0x00 => "Success",
+        0x10 => "No matching subscribers",
+        0x80 => "Unspecified error",
+        0x83 => "Implementation specific error",
+        0x87 => "Not authorized",
+        0x90 => "Topic name invalid",
+        0x91 => "Packet identifier in use",
+        0x92 => "Packet identifier not found",
+        0x97 => "Quota exceeded",
+        0x99 => "Payload format invalid",
+        _ => "Unknown reason",
+    }
+}
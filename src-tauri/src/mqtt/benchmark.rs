@@ -0,0 +1,426 @@
+use crate::jobs::JobProgress;
+use crate::models::{
+    BenchmarkBrokerResult, BenchmarkComparison, BenchmarkReport, BenchmarkStep, BenchmarkStepKind,
+    BenchmarkWorkload, ResolvedConnection,
+};
+use crate::mqtt::session::build_v4_options;
+use crate::mqtt::{now_millis, qos_from_u8};
+use anyhow::{Result, anyhow, bail};
+use rumqttc::{AsyncClient, Event, Incoming};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+
+const MAX_RATE_STEPS: usize = 10;
+const MAX_RATE: u32 = 20_000;
+const MAX_STEP_SECS: u32 = 60;
+const MAX_SUBSCRIBERS: u32 = 50;
+const MIN_PAYLOAD_BYTES: usize = 16;
+const MAX_PAYLOAD_BYTES: usize = 256 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a step waits for messages still in flight after its last publish.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(3);
+const PUBLISH_TICK: Duration = Duration::from_millis(5);
+const MAX_LATENCY_SAMPLES: usize = 1_000_000;
+
+pub fn validate(workload: &BenchmarkWorkload) -> Result<()> {
+    if workload.rate_steps.is_empty() && workload.fan_out_subscribers == 0 {
+        bail!("the workload has no rate steps and no fan-out step");
+    }
+    if workload.rate_steps.len() > MAX_RATE_STEPS {
+        bail!("at most {MAX_RATE_STEPS} rate steps are supported");
+    }
+    let rates = workload
+        .rate_steps
+        .iter()
+        .chain((workload.fan_out_subscribers > 0).then_some(&workload.fan_out_rate));
+    for rate in rates {
+        if *rate == 0 || *rate > MAX_RATE {
+            bail!("publish rates must be between 1 and {MAX_RATE} messages per second");
+        }
+    }
+    if workload.step_secs == 0 || workload.step_secs > MAX_STEP_SECS {
+        bail!("steps must last between 1 and {MAX_STEP_SECS} seconds");
+    }
+    if workload.fan_out_subscribers > MAX_SUBSCRIBERS {
+        bail!("fan-out is limited to {MAX_SUBSCRIBERS} subscribers");
+    }
+    if !(MIN_PAYLOAD_BYTES..=MAX_PAYLOAD_BYTES).contains(&workload.payload_bytes) {
+        bail!("payloads must be between {MIN_PAYLOAD_BYTES} and {MAX_PAYLOAD_BYTES} bytes");
+    }
+    if workload.qos > 2 {
+        bail!("invalid QoS {}", workload.qos);
+    }
+    Ok(())
+}
+
+/// Runs the same workload against each broker in turn, one after the other so they don't
+/// compete for the local network: a publish rate ladder with one subscriber, then one rate
+/// with `fan_out_subscribers` subscribers.
+///
+/// Benchmarks use their own short-lived clients (client id plus a `-bench` suffix) on
+/// `nexus-bench/...` topics, so open sessions and history are left alone. They speak MQTT
+/// 3.1.1 on every broker so the protocol version doesn't skew the comparison. Latency is
+/// measured from publish to delivery on this machine.
+pub async fn run(
+    connections: Vec<ResolvedConnection>,
+    workload: BenchmarkWorkload,
+    progress: JobProgress,
+) -> Result<BenchmarkReport> {
+    let started_at = now_millis();
+    let run_id = format!("{:08x}", rand::random::<u32>());
+    let mut steps = workload
+        .rate_steps
+        .iter()
+        .map(|rate| (BenchmarkStepKind::RateLadder, *rate, 1))
+        .collect::<Vec<_>>();
+    if workload.fan_out_subscribers > 0 {
+        steps.push((
+            BenchmarkStepKind::FanOut,
+            workload.fan_out_rate,
+            workload.fan_out_subscribers,
+        ));
+    }
+    progress.set_total((steps.len() * connections.len()) as u64);
+
+    let mut brokers = Vec::with_capacity(connections.len());
+    for (broker_index, cfg) in connections.iter().enumerate() {
+        let mut result = BenchmarkBrokerResult {
+            connection_id: cfg.id.clone(),
+            name: cfg.name.clone(),
+            host: cfg.host.clone(),
+            port: cfg.port,
+            steps: Vec::with_capacity(steps.len()),
+            error: None,
+        };
+        for (step_index, (kind, rate, subscribers)) in steps.iter().enumerate() {
+            progress.checkpoint()?;
+            let topic = format!("nexus-bench/{run_id}/{broker_index}/{step_index}");
+            let step = run_step(
+                cfg,
+                &topic,
+                *kind,
+                *rate,
+                *subscribers,
+                &workload,
+                &progress,
+            )
+            .await
+            .unwrap_or_else(|error| BenchmarkStep {
+                error: Some(format!("{error:#}")),
+                ..empty_step(*kind, *rate, *subscribers)
+            });
+            result.steps.push(step);
+            progress.advance(1);
+        }
+        if result.steps.iter().all(|step| step.error.is_some()) {
+            result.error = result.steps.first().and_then(|step| step.error.clone());
+        }
+        brokers.push(result);
+    }
+
+    let comparisons = compare(&brokers);
+    Ok(BenchmarkReport {
+        workload,
+        started_at,
+        finished_at: now_millis(),
+        brokers,
+        comparisons,
+    })
+}
+
+async fn run_step(
+    cfg: &ResolvedConnection,
+    topic: &str,
+    kind: BenchmarkStepKind,
+    rate: u32,
+    subscribers: u32,
+    workload: &BenchmarkWorkload,
+    progress: &JobProgress,
+) -> Result<BenchmarkStep> {
+    let origin = Instant::now();
+    let stats = Arc::new(Delivery::default());
+    let errors = Arc::new(AtomicU64::new(0));
+    let mut clients = Vec::new();
+
+    let outcome = async {
+        for index in 0..subscribers {
+            let client = BenchClient::connect(
+                cfg,
+                format!("{}-bench{}", cfg.client_id, index + 1),
+                Some((Arc::clone(&stats), origin)),
+                Arc::clone(&errors),
+            )
+            .await?;
+            client.subscribe(topic, workload.qos).await?;
+            clients.push(client);
+        }
+        let publisher = BenchClient::connect(
+            cfg,
+            format!("{}-bench0", cfg.client_id),
+            None,
+            Arc::clone(&errors),
+        )
+        .await?;
+        let counts = publish_for(&publisher, topic, rate, workload, origin, progress).await;
+        clients.push(publisher);
+        let (sent, publish_errors) = counts?;
+
+        let expected = sent * u64::from(subscribers);
+        let drain_deadline = Instant::now() + DRAIN_TIMEOUT;
+        while stats.received.load(Ordering::Relaxed) < expected && Instant::now() < drain_deadline {
+            time::sleep(Duration::from_millis(50)).await;
+        }
+        Ok::<_, anyhow::Error>((sent, publish_errors, expected))
+    }
+    .await;
+    for client in clients {
+        client.close().await;
+    }
+    let (sent, publish_errors, expected) = outcome?;
+
+    let secs = f64::from(workload.step_secs);
+    let received = stats.received.load(Ordering::Relaxed);
+    let mut latencies = std::mem::take(
+        &mut *stats
+            .latencies_us
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    );
+    latencies.sort_unstable();
+    let percentile = |fraction: f64| {
+        (!latencies.is_empty()).then(|| {
+            let index = ((latencies.len() - 1) as f64 * fraction).round() as usize;
+            latencies[index] as f64 / 1000.0
+        })
+    };
+    Ok(BenchmarkStep {
+        sent,
+        expected,
+        received,
+        publish_errors,
+        connection_errors: errors.load(Ordering::Relaxed),
+        sent_per_sec: sent as f64 / secs,
+        received_per_sec: received as f64 / secs,
+        loss_rate: if expected == 0 {
+            0.0
+        } else {
+            expected.saturating_sub(received) as f64 / expected as f64
+        },
+        latency_p50_ms: percentile(0.5),
+        latency_p95_ms: percentile(0.95),
+        latency_p99_ms: percentile(0.99),
+        latency_max_ms: percentile(1.0),
+        ..empty_step(kind, rate, subscribers)
+    })
+}
+
+/// Publishes at `rate` messages per second for the step's duration, catching up in small
+/// bursts when the client falls behind. Returns the messages sent and the publishes refused.
+async fn publish_for(
+    publisher: &BenchClient,
+    topic: &str,
+    rate: u32,
+    workload: &BenchmarkWorkload,
+    origin: Instant,
+    progress: &JobProgress,
+) -> Result<(u64, u64)> {
+    let qos = qos_from_u8(workload.qos);
+    let start = Instant::now();
+    let duration = Duration::from_secs(u64::from(workload.step_secs));
+    let mut attempts = 0u64;
+    let mut publish_errors = 0u64;
+    while start.elapsed() < duration {
+        progress.checkpoint()?;
+        let due = (start.elapsed().as_secs_f64() * f64::from(rate)) as u64;
+        while attempts < due {
+            let mut payload = vec![b'*'; workload.payload_bytes];
+            payload[..8].copy_from_slice(&attempts.to_be_bytes());
+            let sent_us = origin.elapsed().as_micros() as u64;
+            payload[8..16].copy_from_slice(&sent_us.to_be_bytes());
+            attempts += 1;
+            if publisher
+                .client
+                .publish(topic, qos, false, payload)
+                .await
+                .is_err()
+            {
+                publish_errors += 1;
+            }
+        }
+        time::sleep(PUBLISH_TICK).await;
+    }
+    Ok((attempts - publish_errors, publish_errors))
+}
+
+#[derive(Default)]
+struct Delivery {
+    received: AtomicU64,
+    latencies_us: Mutex<Vec<u64>>,
+}
+
+struct BenchClient {
+    client: AsyncClient,
+    task: JoinHandle<()>,
+    suback: Mutex<Option<oneshot::Receiver<()>>>,
+}
+
+impl BenchClient {
+    async fn connect(
+        cfg: &ResolvedConnection,
+        client_id: String,
+        delivery: Option<(Arc<Delivery>, Instant)>,
+        errors: Arc<AtomicU64>,
+    ) -> Result<Self> {
+        let mut options = build_v4_options(&ResolvedConnection {
+            client_id,
+            clean: true,
+            ..cfg.clone()
+        });
+        options.set_max_packet_size(MAX_PAYLOAD_BYTES + 1024, MAX_PAYLOAD_BYTES + 1024);
+        let (client, mut eventloop) = AsyncClient::new(options, 1024);
+        let (connack_tx, connack_rx) = oneshot::channel::<Result<()>>();
+        let (suback_tx, suback_rx) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let mut connack_tx = Some(connack_tx);
+            let mut suback_tx = Some(suback_tx);
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                        if let Some(tx) = connack_tx.take() {
+                            let _ = tx.send(Ok(()));
+                        }
+                    }
+                    Ok(Event::Incoming(Incoming::SubAck(_))) => {
+                        if let Some(tx) = suback_tx.take() {
+                            let _ = tx.send(());
+                        }
+                    }
+                    Ok(Event::Incoming(Incoming::Publish(publish))) => {
+                        let Some((delivery, origin)) = &delivery else {
+                            continue;
+                        };
+                        delivery.received.fetch_add(1, Ordering::Relaxed);
+                        if let Some(sent_us) = publish
+                            .payload
+                            .get(8..16)
+                            .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+                            .map(u64::from_be_bytes)
+                        {
+                            let now_us = origin.elapsed().as_micros() as u64;
+                            let mut latencies = delivery
+                                .latencies_us
+                                .lock()
+                                .unwrap_or_else(|poisoned| poisoned.into_inner());
+                            if latencies.len() < MAX_LATENCY_SAMPLES {
+                                latencies.push(now_us.saturating_sub(sent_us));
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        if let Some(tx) = connack_tx.take() {
+                            let _ = tx.send(Err(anyhow!("failed to connect: {error}")));
+                            return;
+                        }
+                        errors.fetch_add(1, Ordering::Relaxed);
+                        time::sleep(Duration::from_millis(250)).await;
+                    }
+                }
+            }
+        });
+        let connected = match time::timeout(CONNECT_TIMEOUT, connack_rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(anyhow!("the benchmark client stopped before connecting")),
+            Err(_) => Err(anyhow!("timed out waiting for CONNACK")),
+        };
+        if let Err(error) = connected {
+            task.abort();
+            return Err(error);
+        }
+        Ok(Self {
+            client,
+            task,
+            suback: Mutex::new(Some(suback_rx)),
+        })
+    }
+
+    async fn subscribe(&self, topic: &str, qos: u8) -> Result<()> {
+        self.client.subscribe(topic, qos_from_u8(qos)).await?;
+        let suback = self
+            .suback
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take()
+            .ok_or_else(|| anyhow!("already subscribed"))?;
+        match time::timeout(CONNECT_TIMEOUT, suback).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(anyhow!("the benchmark client stopped before subscribing")),
+            Err(_) => Err(anyhow!("timed out waiting for SUBACK")),
+        }
+    }
+
+    async fn close(self) {
+        let _ = time::timeout(Duration::from_secs(1), self.client.disconnect()).await;
+        time::sleep(Duration::from_millis(50)).await;
+        self.task.abort();
+    }
+}
+
+fn empty_step(kind: BenchmarkStepKind, target_rate: u32, subscribers: u32) -> BenchmarkStep {
+    BenchmarkStep {
+        kind,
+        target_rate,
+        subscribers,
+        sent: 0,
+        expected: 0,
+        received: 0,
+        publish_errors: 0,
+        connection_errors: 0,
+        sent_per_sec: 0.0,
+        received_per_sec: 0.0,
+        loss_rate: 0.0,
+        latency_p50_ms: None,
+        latency_p95_ms: None,
+        latency_p99_ms: None,
+        latency_max_ms: None,
+        error: None,
+    }
+}
+
+/// Step by step, which broker delivered more and which had the lower 95th percentile latency.
+fn compare(brokers: &[BenchmarkBrokerResult]) -> Vec<BenchmarkComparison> {
+    let Some(first) = brokers.first() else {
+        return Vec::new();
+    };
+    (0..first.steps.len())
+        .map(|index| {
+            let steps = brokers
+                .iter()
+                .filter_map(|broker| {
+                    let step = broker.steps.get(index)?;
+                    step.error.is_none().then_some((broker, step))
+                })
+                .collect::<Vec<_>>();
+            let best_throughput = steps
+                .iter()
+                .max_by(|a, b| a.1.received_per_sec.total_cmp(&b.1.received_per_sec))
+                .map(|(broker, _)| broker.connection_id.clone());
+            let best_latency = steps
+                .iter()
+                .filter_map(|(broker, step)| Some((broker, step.latency_p95_ms?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(broker, _)| broker.connection_id.clone());
+            BenchmarkComparison {
+                step: index,
+                kind: first.steps[index].kind,
+                target_rate: first.steps[index].target_rate,
+                subscribers: first.steps[index].subscribers,
+                best_throughput,
+                best_latency,
+            }
+        })
+        .collect()
+}
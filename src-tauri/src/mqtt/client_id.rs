@@ -0,0 +1,74 @@
+use rand::Rng;
+use rand::distr::Alphanumeric;
+
+const MAX_RANDOM_CHARS: usize = 32;
+const UNIQUE_SUFFIX_CHARS: usize = 6;
+
+/// Expands `{{hostname}}`, `{{randN}}` (N random lowercase alphanumerics), `{{timestamp}}`
+/// and `{{connectionId}}` in a client id template. Unknown placeholders are rejected.
+pub fn expand(template: &str, connection_id: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| format!("unterminated placeholder in client id '{template}'"))?;
+        out.push_str(&placeholder(after[..end].trim(), connection_id)?);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn placeholder(name: &str, connection_id: &str) -> Result<String, String> {
+    match name {
+        "hostname" => Ok(hostname()),
+        "timestamp" => Ok(crate::mqtt::now_millis().to_string()),
+        "connectionId" => Ok(connection_id.to_string()),
+        _ => {
+            let count = name
+                .strip_prefix("rand")
+                .and_then(|digits| digits.parse::<usize>().ok())
+                .filter(|count| (1..=MAX_RANDOM_CHARS).contains(count))
+                .ok_or_else(|| format!("unknown client id placeholder '{{{{{name}}}}}'"))?;
+            Ok(random_chars(count))
+        }
+    }
+}
+
+/// Suffix for profiles that want a distinct client id per connect. Never persisted.
+pub fn unique_suffix() -> String {
+    format!("-{}", random_chars(UNIQUE_SUFFIX_CHARS))
+}
+
+fn random_chars(count: usize) -> String {
+    rand::rng()
+        .sample_iter(Alphanumeric)
+        .take(count)
+        .map(|byte| char::from(byte).to_ascii_lowercase())
+        .collect()
+}
+
+/// Host name reduced to characters brokers accept in client ids.
+fn hostname() -> String {
+    let raw = gethostname::gethostname().to_string_lossy().into_owned();
+    let cleaned = raw
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '-' || *ch == '_')
+        .collect::<String>();
+    if cleaned.is_empty() {
+        "localhost".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Identifies a broker endpoint for client id collision checks.
+pub fn broker_key(host: &str, port: u16) -> String {
+    format!("{}:{port}", host.trim().to_ascii_lowercase())
+}
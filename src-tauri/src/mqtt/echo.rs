@@ -0,0 +1,207 @@
+use crate::models::{EchoLatencyBucket, EchoLatencyStats};
+use crate::mqtt::now_millis;
+use dashmap::DashMap;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the latency bins, in milliseconds; a last bin takes everything slower.
+pub const BIN_UPPER_MS: [f64; 12] = [
+    1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0,
+];
+const BUCKET_MS: u64 = 60_000;
+/// One hour of one-minute buckets per topic.
+const MAX_BUCKETS: usize = 60;
+/// Publishes whose echo hasn't arrived by then are forgotten.
+const ECHO_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_PENDING: usize = 1024;
+
+/// End-to-end latency of publishes the session receives back through its own subscriptions.
+///
+/// Outgoing publishes are remembered per topic by correlation data, or by a hash of the wire
+/// payload when there is none, and matched oldest-first against incoming messages on the same
+/// topic. Topics the app never publishes to cost one map lookup per incoming message.
+#[derive(Clone, Default)]
+pub struct EchoLatency {
+    connections: Arc<DashMap<String, HashMap<String, TopicEcho>>>,
+}
+
+#[derive(Default)]
+struct TopicEcho {
+    pending: VecDeque<(u64, Instant)>,
+    samples: u64,
+    min_ms: f64,
+    max_ms: f64,
+    sum_ms: f64,
+    last_ms: f64,
+    buckets: VecDeque<Bucket>,
+}
+
+struct Bucket {
+    start: u64,
+    samples: u32,
+    min_ms: f64,
+    max_ms: f64,
+    sum_ms: f64,
+    bins: [u32; BIN_UPPER_MS.len() + 1],
+}
+
+impl EchoLatency {
+    /// Called by the command loop just before a publish is handed to rumqttc.
+    pub fn sent(
+        &self,
+        connection_id: &str,
+        topic: &str,
+        correlation: Option<&[u8]>,
+        payload: &[u8],
+    ) {
+        let key = echo_key(correlation, payload);
+        let mut topics = self
+            .connections
+            .entry(connection_id.to_string())
+            .or_default();
+        let echo = topics.entry(topic.to_string()).or_default();
+        let now = Instant::now();
+        echo.expire(now);
+        if echo.pending.len() >= MAX_PENDING {
+            echo.pending.pop_front();
+        }
+        echo.pending.push_back((key, now));
+    }
+
+    /// Matches an incoming message against the publishes still waiting for their echo.
+    pub fn received(
+        &self,
+        connection_id: &str,
+        topic: &str,
+        correlation: Option<&[u8]>,
+        payload: &[u8],
+    ) {
+        let Some(mut topics) = self.connections.get_mut(connection_id) else {
+            return;
+        };
+        let Some(echo) = topics.get_mut(topic) else {
+            return;
+        };
+        if echo.pending.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        echo.expire(now);
+        let key = echo_key(correlation, payload);
+        let Some(index) = echo.pending.iter().position(|(pending, _)| *pending == key) else {
+            return;
+        };
+        if let Some((_, sent_at)) = echo.pending.remove(index) {
+            echo.record(now.duration_since(sent_at).as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Statistics for every topic with at least one matched echo, busiest first.
+    pub fn stats(&self, connection_id: &str) -> Vec<EchoLatencyStats> {
+        let Some(topics) = self.connections.get(connection_id) else {
+            return Vec::new();
+        };
+        let mut stats: Vec<EchoLatencyStats> = topics
+            .iter()
+            .filter(|(_, echo)| echo.samples > 0)
+            .map(|(topic, echo)| EchoLatencyStats {
+                topic: topic.clone(),
+                samples: echo.samples,
+                min_ms: echo.min_ms,
+                max_ms: echo.max_ms,
+                avg_ms: echo.sum_ms / echo.samples as f64,
+                last_ms: echo.last_ms,
+                awaiting_echo: echo.pending.len(),
+                bucket_ms: BUCKET_MS,
+                bin_upper_ms: BIN_UPPER_MS.to_vec(),
+                buckets: echo
+                    .buckets
+                    .iter()
+                    .map(|bucket| EchoLatencyBucket {
+                        start: bucket.start,
+                        samples: bucket.samples,
+                        min_ms: bucket.min_ms,
+                        max_ms: bucket.max_ms,
+                        avg_ms: bucket.sum_ms / f64::from(bucket.samples),
+                        bins: bucket.bins.to_vec(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        stats.sort_by(|a, b| {
+            b.samples
+                .cmp(&a.samples)
+                .then_with(|| a.topic.cmp(&b.topic))
+        });
+        stats
+    }
+
+    /// Drops every sample and pending publish for the connection.
+    pub fn remove(&self, connection_id: &str) {
+        self.connections.remove(connection_id);
+    }
+}
+
+impl TopicEcho {
+    fn expire(&mut self, now: Instant) {
+        while let Some((_, sent_at)) = self.pending.front() {
+            if now.duration_since(*sent_at) < ECHO_TIMEOUT {
+                break;
+            }
+            self.pending.pop_front();
+        }
+    }
+
+    fn record(&mut self, latency_ms: f64) {
+        if self.samples == 0 || latency_ms < self.min_ms {
+            self.min_ms = latency_ms;
+        }
+        self.max_ms = self.max_ms.max(latency_ms);
+        self.sum_ms += latency_ms;
+        self.last_ms = latency_ms;
+        self.samples += 1;
+
+        let start = now_millis() / BUCKET_MS * BUCKET_MS;
+        if self
+            .buckets
+            .back()
+            .is_none_or(|bucket| bucket.start != start)
+        {
+            if self.buckets.len() >= MAX_BUCKETS {
+                self.buckets.pop_front();
+            }
+            self.buckets.push_back(Bucket {
+                start,
+                samples: 0,
+                min_ms: latency_ms,
+                max_ms: latency_ms,
+                sum_ms: 0.0,
+                bins: [0; BIN_UPPER_MS.len() + 1],
+            });
+        }
+        let Some(bucket) = self.buckets.back_mut() else {
+            return;
+        };
+        bucket.samples += 1;
+        bucket.min_ms = bucket.min_ms.min(latency_ms);
+        bucket.max_ms = bucket.max_ms.max(latency_ms);
+        bucket.sum_ms += latency_ms;
+        let bin = BIN_UPPER_MS
+            .iter()
+            .position(|upper| latency_ms <= *upper)
+            .unwrap_or(BIN_UPPER_MS.len());
+        bucket.bins[bin] += 1;
+    }
+}
+
+/// Correlation data identifies a message on its own; otherwise the payload has to.
+fn echo_key(correlation: Option<&[u8]>, payload: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match correlation {
+        Some(data) => (0u8, data).hash(&mut hasher),
+        None => (1u8, payload).hash(&mut hasher),
+    }
+    hasher.finish()
+}
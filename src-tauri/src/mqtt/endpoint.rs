@@ -0,0 +1,164 @@
+use crate::models::{ConnectionInfo, ResolvedConnection, TransportProtocol};
+use crate::mqtt::now_millis;
+use crate::mqtt::session::build_ws_broker_url;
+use anyhow::{Context, Result, anyhow};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Works out which endpoint a connected session actually reached: the addresses the broker
+/// host resolves to, and the address, TLS version, cipher suite and ALPN protocol a second,
+/// short-lived connection negotiates right after the CONNACK.
+///
+/// rumqttc doesn't expose its socket, hence the probe. It resolves and connects the same way
+/// (addresses in resolver order), so it lands where the session did unless DNS or a load
+/// balancer hands out a different backend between the two connections. ALPN reflects what the
+/// endpoint picks from `mqtt` (or `http/1.1` for WebSockets), as the session itself offers none.
+pub fn probe(connection_id: &str, cfg: &ResolvedConnection) -> ConnectionInfo {
+    let websocket_url = match cfg.protocol {
+        TransportProtocol::Ws => Some(build_ws_broker_url(cfg, false)),
+        TransportProtocol::Wss => Some(build_ws_broker_url(cfg, true)),
+        _ => None,
+    };
+    let (host, port) = match &websocket_url {
+        Some(url) => url_host_port(url, cfg.port),
+        None => (cfg.host.trim().to_string(), cfg.port),
+    };
+    let mut info = ConnectionInfo {
+        connection_id: connection_id.to_string(),
+        transport: cfg.protocol,
+        protocol_version: cfg.protocol_version,
+        host: host.clone(),
+        port,
+        websocket_url,
+        resolved_addresses: Vec::new(),
+        remote_address: None,
+        local_address: None,
+        tls_version: None,
+        tls_cipher: None,
+        alpn: None,
+        probe_error: None,
+        measured_at: now_millis(),
+        keep_alive_secs: None,
+    };
+    if let Err(error) = probe_into(&mut info, &host, port, cfg.tls_config.as_deref()) {
+        info.probe_error = Some(format!("{error:#}"));
+    }
+    info
+}
+
+fn probe_into(
+    info: &mut ConnectionInfo,
+    host: &str,
+    port: u16,
+    tls_config: Option<&rustls::ClientConfig>,
+) -> Result<()> {
+    let addresses = (host, port)
+        .to_socket_addrs()
+        .with_context(|| format!("failed to resolve {host}"))?
+        .collect::<Vec<SocketAddr>>();
+    info.resolved_addresses = addresses
+        .iter()
+        .map(|address| address.ip().to_string())
+        .collect();
+
+    let mut last_error = None;
+    let mut stream = None;
+    for address in &addresses {
+        match TcpStream::connect_timeout(address, PROBE_TIMEOUT) {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(error) => last_error = Some(error),
+        }
+    }
+    let mut stream = match (stream, last_error) {
+        (Some(stream), _) => stream,
+        (None, Some(error)) => return Err(anyhow!("failed to connect to {host}:{port}: {error}")),
+        (None, None) => return Err(anyhow!("{host} did not resolve to any address")),
+    };
+    info.remote_address = stream.peer_addr().ok().map(|address| address.to_string());
+    info.local_address = stream.local_addr().ok().map(|address| address.to_string());
+
+    if !matches!(
+        info.transport,
+        TransportProtocol::Mqtts | TransportProtocol::Wss
+    ) {
+        return Ok(());
+    }
+    stream.set_read_timeout(Some(PROBE_TIMEOUT))?;
+    stream.set_write_timeout(Some(PROBE_TIMEOUT))?;
+
+    // A profile's own CA and client certificate apply to the probe as well.
+    let mut config = match tls_config {
+        Some(config) => config.clone(),
+        None => {
+            let mut roots = rustls::RootCertStore::empty();
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            // Both crypto backends end up in the dependency graph, so pick one explicitly.
+            rustls::ClientConfig::builder_with_provider(Arc::new(
+                rustls::crypto::ring::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()
+            .context("failed to configure TLS")?
+            .with_root_certificates(roots)
+            .with_no_client_auth()
+        }
+    };
+    config.alpn_protocols = vec![match info.transport {
+        TransportProtocol::Wss => b"http/1.1".to_vec(),
+        _ => b"mqtt".to_vec(),
+    }];
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .with_context(|| format!("invalid TLS server name '{host}'"))?;
+    let mut tls = rustls::ClientConnection::new(Arc::new(config), server_name)
+        .context("failed to start TLS handshake")?;
+    while tls.is_handshaking() {
+        tls.complete_io(&mut stream)
+            .context("TLS handshake failed")?;
+    }
+
+    info.tls_version = tls.protocol_version().map(|version| match version {
+        rustls::ProtocolVersion::TLSv1_2 => "TLS 1.2".to_string(),
+        rustls::ProtocolVersion::TLSv1_3 => "TLS 1.3".to_string(),
+        other => format!("{other:?}"),
+    });
+    info.tls_cipher = tls
+        .negotiated_cipher_suite()
+        .map(|suite| format!("{:?}", suite.suite()));
+    info.alpn = tls
+        .alpn_protocol()
+        .map(|protocol| String::from_utf8_lossy(protocol).into_owned());
+    tls.send_close_notify();
+    let _ = tls.complete_io(&mut stream);
+    Ok(())
+}
+
+/// Host and port of a `ws://` or `wss://` URL, falling back to `default_port`.
+fn url_host_port(url: &str, default_port: u16) -> (String, u16) {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        // IPv6 literal, e.g. `[::1]:8883`.
+        if let Some((host, after)) = bracketed.split_once(']') {
+            let port = after
+                .strip_prefix(':')
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(default_port);
+            return (host.to_string(), port);
+        }
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) => match port.parse() {
+            Ok(port) => (host.to_string(), port),
+            Err(_) => (authority.to_string(), default_port),
+        },
+        None => (authority.to_string(), default_port),
+    }
+}
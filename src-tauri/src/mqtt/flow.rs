@@ -0,0 +1,98 @@
+use crate::models::{MqttBatchItem, MqttBatchSummaryPayload, MqttTopicSummary};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const MIN_BATCH: usize = 50;
+const MAX_BATCH: usize = 2000;
+/// Unacknowledged emits after which the UI only receives per-topic summaries.
+const SUMMARY_LAG: u64 = 8;
+
+/// Tracks batch sequence numbers the frontend has processed, per connection.
+#[derive(Clone, Default)]
+pub struct FlowControl {
+    windows: Arc<DashMap<String, Arc<FlowWindow>>>,
+}
+
+#[derive(Default)]
+pub struct FlowWindow {
+    sent: AtomicU64,
+    acked: AtomicU64,
+}
+
+impl FlowControl {
+    pub fn register(&self, connection_id: &str) -> Arc<FlowWindow> {
+        let window = Arc::new(FlowWindow::default());
+        self.windows
+            .insert(connection_id.to_string(), Arc::clone(&window));
+        window
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.windows.remove(connection_id);
+    }
+
+    pub fn ack(&self, connection_id: &str, seq: u64) {
+        if let Some(window) = self.windows.get(connection_id) {
+            let sent = window.sent.load(Ordering::Relaxed);
+            window.acked.fetch_max(seq.min(sent), Ordering::Relaxed);
+        }
+    }
+}
+
+impl FlowWindow {
+    pub fn next_seq(&self) -> u64 {
+        self.sent.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Emits the UI has not acknowledged yet. Flow control stays off until the first ack so
+    /// frontends that never acknowledge keep receiving every batch.
+    fn lag(&self) -> u64 {
+        let acked = self.acked.load(Ordering::Relaxed);
+        if acked == 0 {
+            return 0;
+        }
+        self.sent.load(Ordering::Relaxed).saturating_sub(acked)
+    }
+
+    /// Doubles the batch size for each unacknowledged emit beyond the first.
+    pub fn batch_limit(&self) -> usize {
+        let lag = self.lag().clamp(1, 7) as u32;
+        (MIN_BATCH << (lag - 1)).min(MAX_BATCH)
+    }
+
+    pub fn should_summarize(&self) -> bool {
+        self.lag() >= SUMMARY_LAG
+    }
+}
+
+/// Collapses a batch into per-topic counts and the latest message for each topic.
+pub fn summarize(
+    connection_id: &str,
+    seq: u64,
+    batch: &[MqttBatchItem],
+) -> MqttBatchSummaryPayload {
+    let mut topics: HashMap<&str, MqttTopicSummary> = HashMap::new();
+    for item in batch {
+        topics
+            .entry(item.topic.as_str())
+            .and_modify(|summary| {
+                summary.count += 1;
+                summary.last = item.clone();
+            })
+            .or_insert_with(|| MqttTopicSummary {
+                count: 1,
+                last: item.clone(),
+            });
+    }
+
+    let mut topics = topics.into_values().collect::<Vec<_>>();
+    topics.sort_by(|a, b| a.last.topic.cmp(&b.last.topic));
+    MqttBatchSummaryPayload {
+        connection_id: connection_id.to_string(),
+        seq,
+        total: batch.len(),
+        topics,
+    }
+}
@@ -0,0 +1,271 @@
+use crate::crash;
+use crate::models::{ConnectionEventKind, ConnectionHealth, HealthGrade};
+use crate::mqtt::now_millis;
+use crate::mqtt::session::record_event;
+use crate::state::AppState;
+use crate::windows;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tokio::time::{self, Duration};
+
+/// Reconnects, errors and missed pings count against the score for this long.
+const WINDOW_MS: u64 = 60 * 60 * 1000;
+/// Round-trip and acknowledgement samples kept per connection.
+const SAMPLE_LIMIT: usize = 30;
+const SCORE_INTERVAL_SECS: u64 = 15;
+/// Score movement that is written to the connection event log even without a grade change.
+const SCORE_STEP: u8 = 10;
+const HEALTHY_SCORE: u8 = 80;
+const DEGRADED_SCORE: u8 = 50;
+
+/// Scores each connection's broker link from recent reconnects, errors, missed pings, ping
+/// round-trip jitter and MQTT 5 acknowledgement latency, so a degrading broker shows up before
+/// it falls over.
+///
+/// Trackers outlive sessions, since reconnects are what they count.
+#[derive(Clone, Default)]
+pub struct HealthMonitor {
+    trackers: Arc<DashMap<String, Arc<HealthTracker>>>,
+}
+
+#[derive(Default)]
+pub struct HealthTracker {
+    state: Mutex<HealthState>,
+}
+
+#[derive(Default)]
+struct HealthState {
+    connects: VecDeque<u64>,
+    errors: VecDeque<u64>,
+    missed_pings: VecDeque<u64>,
+    rtts_ms: VecDeque<u64>,
+    ack_latencies_ms: VecDeque<u64>,
+    /// Score and grade last written to the event log.
+    recorded: Option<(u8, HealthGrade)>,
+    emitted: Option<ConnectionHealth>,
+}
+
+impl HealthMonitor {
+    pub fn tracker(&self, connection_id: &str) -> Arc<HealthTracker> {
+        Arc::clone(
+            self.trackers
+                .entry(connection_id.to_string())
+                .or_default()
+                .value(),
+        )
+    }
+
+    pub fn health(&self, connection_id: &str) -> ConnectionHealth {
+        match self.trackers.get(connection_id) {
+            Some(tracker) => tracker.score(connection_id, now_millis()),
+            None => HealthState::default().score(connection_id, now_millis()),
+        }
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.trackers.remove(connection_id);
+    }
+
+    /// Rescores connected sessions every `SCORE_INTERVAL_SECS`, emitting `connection-health`
+    /// when a score changes.
+    pub fn start(&self, app: AppHandle) {
+        let monitor = self.clone();
+        tokio::spawn(crash::monitored("connection-health", async move {
+            let mut interval = time::interval(Duration::from_secs(SCORE_INTERVAL_SECS));
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let trackers = monitor
+                    .trackers
+                    .iter()
+                    .map(|entry| (entry.key().clone(), Arc::clone(entry.value())))
+                    .collect::<Vec<_>>();
+                let state = app.state::<AppState>();
+                for (connection_id, tracker) in trackers {
+                    if state.mqtt_manager.is_connected(&connection_id) {
+                        tracker.publish(&app, &connection_id);
+                    }
+                }
+            }
+        }));
+    }
+}
+
+impl HealthTracker {
+    pub fn connected(&self) {
+        push_event(&mut self.lock().connects, now_millis());
+    }
+
+    pub fn error(&self) {
+        push_event(&mut self.lock().errors, now_millis());
+    }
+
+    pub fn missed_ping(&self) {
+        push_event(&mut self.lock().missed_pings, now_millis());
+    }
+
+    pub fn ping_rtt(&self, rtt_ms: u64) {
+        push_sample(&mut self.lock().rtts_ms, rtt_ms);
+    }
+
+    /// A PUBACK or PUBREC arrived; rejections also count as errors.
+    pub fn acked(&self, latency_ms: u64, success: bool) {
+        let mut state = self.lock();
+        push_sample(&mut state.ack_latencies_ms, latency_ms);
+        if !success {
+            push_event(&mut state.errors, now_millis());
+        }
+    }
+
+    fn score(&self, connection_id: &str, now: u64) -> ConnectionHealth {
+        self.lock().score(connection_id, now)
+    }
+
+    fn publish(&self, app: &AppHandle, connection_id: &str) {
+        let mut state = self.lock();
+        let health = state.score(connection_id, now_millis());
+        if state
+            .emitted
+            .as_ref()
+            .is_some_and(|emitted| same_inputs(emitted, &health))
+        {
+            return;
+        }
+
+        match state.recorded {
+            None => state.recorded = Some((health.score, health.grade)),
+            Some((score, grade))
+                if grade != health.grade || score.abs_diff(health.score) >= SCORE_STEP =>
+            {
+                state.recorded = Some((health.score, health.grade));
+                record_event(
+                    app,
+                    connection_id,
+                    ConnectionEventKind::HealthChanged,
+                    Some(format!(
+                        "score {score} -> {} ({}): {}",
+                        health.score,
+                        health.grade.as_str(),
+                        summary(&health)
+                    )),
+                );
+            }
+            Some(_) => {}
+        }
+        state.emitted = Some(health.clone());
+        drop(state);
+        windows::emit_for_connection(app, "connection-health", connection_id, health);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HealthState {
+    fn score(&mut self, connection_id: &str, now: u64) -> ConnectionHealth {
+        let cutoff = now.saturating_sub(WINDOW_MS);
+        for events in [&mut self.connects, &mut self.errors, &mut self.missed_pings] {
+            while events.front().is_some_and(|at| *at < cutoff) {
+                events.pop_front();
+            }
+        }
+
+        let reconnects = self.connects.len().saturating_sub(1) as u32;
+        let errors = self.errors.len() as u32;
+        let missed_pings = self.missed_pings.len() as u32;
+        let rtt_avg_ms = mean(&self.rtts_ms);
+        let rtt_jitter_ms = std_dev(&self.rtts_ms);
+        let ack_latency_ms = mean(&self.ack_latencies_ms);
+
+        let penalty = (reconnects * 10).min(40)
+            + (errors * 5).min(25)
+            + (missed_pings * 10).min(20)
+            + rtt_jitter_ms.map_or(0, |jitter| (jitter / 20).min(15) as u32)
+            + rtt_avg_ms.map_or(0, |rtt| (rtt / 200).min(10) as u32)
+            + ack_latency_ms.map_or(0, |latency| (latency / 100).min(15) as u32);
+        let score = 100u32.saturating_sub(penalty) as u8;
+        let grade = if score >= HEALTHY_SCORE {
+            HealthGrade::Healthy
+        } else if score >= DEGRADED_SCORE {
+            HealthGrade::Degraded
+        } else {
+            HealthGrade::Unhealthy
+        };
+
+        ConnectionHealth {
+            connection_id: connection_id.to_string(),
+            score,
+            grade,
+            reconnects,
+            errors,
+            missed_pings,
+            rtt_avg_ms,
+            rtt_jitter_ms,
+            ack_latency_ms,
+            computed_at: now,
+        }
+    }
+}
+
+fn push_event(events: &mut VecDeque<u64>, at: u64) {
+    events.push_back(at);
+}
+
+fn push_sample(samples: &mut VecDeque<u64>, value: u64) {
+    if samples.len() == SAMPLE_LIMIT {
+        samples.pop_front();
+    }
+    samples.push_back(value);
+}
+
+fn mean(samples: &VecDeque<u64>) -> Option<u64> {
+    (!samples.is_empty()).then(|| samples.iter().sum::<u64>() / samples.len() as u64)
+}
+
+/// Population standard deviation; needs at least two samples.
+fn std_dev(samples: &VecDeque<u64>) -> Option<u64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let count = samples.len() as f64;
+    let mean = samples.iter().sum::<u64>() as f64 / count;
+    let variance = samples
+        .iter()
+        .map(|sample| (*sample as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    Some(variance.sqrt().round() as u64)
+}
+
+fn same_inputs(left: &ConnectionHealth, right: &ConnectionHealth) -> bool {
+    left.score == right.score
+        && left.reconnects == right.reconnects
+        && left.errors == right.errors
+        && left.missed_pings == right.missed_pings
+        && left.rtt_avg_ms == right.rtt_avg_ms
+        && left.rtt_jitter_ms == right.rtt_jitter_ms
+        && left.ack_latency_ms == right.ack_latency_ms
+}
+
+fn summary(health: &ConnectionHealth) -> String {
+    let mut parts = vec![
+        format!("{} reconnects", health.reconnects),
+        format!("{} errors", health.errors),
+        format!("{} missed pings", health.missed_pings),
+    ];
+    if let Some(rtt) = health.rtt_avg_ms {
+        parts.push(format!(
+            "rtt {rtt} ms ± {} ms",
+            health.rtt_jitter_ms.unwrap_or(0)
+        ));
+    }
+    if let Some(latency) = health.ack_latency_ms {
+        parts.push(format!("ack {latency} ms"));
+    }
+    parts.join(", ")
+}
@@ -0,0 +1,389 @@
+use crate::config_store;
+use crate::models::{MqttBatchItem, Payload, SessionHookErrorPayload, SessionHooksConfig};
+use crate::mqtt::{is_valid_topic_filter, now_millis, topic_matches};
+use crate::state::AppState;
+use crate::windows;
+use anyhow::{Result, anyhow, bail};
+use dashmap::DashMap;
+use rhai::{AST, CallFnOptions, Dynamic, Engine, ImmutableString, Scope};
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Manager};
+
+pub const SESSION_HOOKS_FILE_NAME: &str = "session-hooks.json";
+
+const MAX_OPERATIONS: u64 = 200_000;
+const MAX_STRING_BYTES: usize = 8 * 1024 * 1024;
+/// Hook publishes allowed per connection per second, so an `onMessage` hook that publishes to
+/// a topic it also receives can't flood the broker.
+const MAX_PUBLISHES_PER_SEC: u64 = 50;
+/// At most one `session-hook-error` event per connection per interval; the rest are counted.
+const ERROR_EVENT_INTERVAL_MS: u64 = 1000;
+
+const ON_CONNECT: &str = "onConnect";
+const ON_DISCONNECT: &str = "onDisconnect";
+const ON_MESSAGE: &str = "onMessage";
+
+thread_local! {
+    /// Publishes requested by the hook running on this thread; `None` where hooks can't
+    /// publish.
+    static PENDING: RefCell<Option<Vec<HookPublish>>> = const { RefCell::new(None) };
+}
+
+struct HookPublish {
+    topic: String,
+    payload: Payload,
+    qos: u8,
+    retain: bool,
+}
+
+struct CompiledHooks {
+    ast: AST,
+    message_filter: Option<String>,
+    on_connect: bool,
+    on_disconnect: bool,
+    on_message: bool,
+    window_started_at: AtomicU64,
+    window_publishes: AtomicU64,
+    last_error_at: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+/// Per-connection Rhai scripts called on session events, e.g. to publish a birth message after
+/// every connect.
+///
+/// A script defines any of these functions:
+/// - `onConnect()` after each CONNACK, reconnects included;
+/// - `onDisconnect(reason)` when the connection drops or is closed;
+/// - `onMessage(topic, payload, json)` for incoming messages matching the hook's message filter,
+///   after transforms ran; `json` is the parsed payload, or `()` when it is not JSON.
+///
+/// Hooks call `publish(topic, payload)`, optionally with `qos` and `retain`, to publish on the
+/// same connection; maps and arrays are sent as JSON. Publishes go out once the hook returns.
+/// `onDisconnect` runs after the session stopped, so publishing from it is a script error.
+#[derive(Clone)]
+pub struct SessionHooks {
+    engine: Arc<Engine>,
+    scripts: Arc<DashMap<String, Arc<CompiledHooks>>>,
+}
+
+impl Default for SessionHooks {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(MAX_STRING_BYTES)
+            .set_max_array_size(100_000)
+            .set_max_map_size(100_000);
+        engine.on_print(|text| tracing::debug!("session hook: {text}"));
+        engine.on_debug(|text, _, _| tracing::debug!("session hook: {text}"));
+        engine.register_fn("publish", |topic: &str, payload: Dynamic| {
+            queue_publish(topic, payload, 0, false)
+        });
+        engine.register_fn("publish", |topic: &str, payload: Dynamic, qos: i64| {
+            queue_publish(topic, payload, qos, false)
+        });
+        engine.register_fn(
+            "publish",
+            |topic: &str, payload: Dynamic, qos: i64, retain: bool| {
+                queue_publish(topic, payload, qos, retain)
+            },
+        );
+        Self {
+            engine: Arc::new(engine),
+            scripts: Arc::default(),
+        }
+    }
+}
+
+impl SessionHooks {
+    pub fn init(&self, app: &AppHandle) {
+        match load(app) {
+            Ok(config) => {
+                if let Err(error) = self.apply_config(&config) {
+                    tracing::warn!("failed to compile session hooks: {error:#}");
+                }
+            }
+            Err(error) => tracing::warn!("failed to load session hooks: {error:#}"),
+        }
+    }
+
+    /// Compiles every enabled script, then persists and activates them; nothing changes if any
+    /// script fails to compile.
+    pub fn save(&self, app: &AppHandle, config: &SessionHooksConfig) -> Result<()> {
+        self.compile_all(config)?;
+        config_store::save_document(app, SESSION_HOOKS_FILE_NAME, config)?;
+        self.apply_config(config)
+    }
+
+    pub fn on_connect(&self, app: &AppHandle, connection_id: &str) {
+        let Some(hooks) = self.hooks(connection_id).filter(|hooks| hooks.on_connect) else {
+            return;
+        };
+        self.run(app, connection_id, &hooks, ON_CONNECT, Vec::new());
+    }
+
+    pub fn on_disconnect(&self, app: &AppHandle, connection_id: &str, reason: &str) {
+        let Some(hooks) = self
+            .hooks(connection_id)
+            .filter(|hooks| hooks.on_disconnect)
+        else {
+            return;
+        };
+        let args = vec![Dynamic::from(ImmutableString::from(reason))];
+        self.run(app, connection_id, &hooks, ON_DISCONNECT, args);
+    }
+
+    pub fn on_message(&self, app: &AppHandle, connection_id: &str, item: &MqttBatchItem) {
+        let Some(hooks) = self.hooks(connection_id).filter(|hooks| {
+            hooks.on_message
+                && hooks
+                    .message_filter
+                    .as_deref()
+                    .is_some_and(|filter| topic_matches(filter, &item.topic))
+        }) else {
+            return;
+        };
+        let json = serde_json::from_str::<serde_json::Value>(&item.payload)
+            .ok()
+            .filter(|value| value.is_object() || value.is_array())
+            .and_then(|value| rhai::serde::to_dynamic(value).ok())
+            .unwrap_or(Dynamic::UNIT);
+        let args = vec![
+            Dynamic::from(ImmutableString::from(item.topic.as_str())),
+            Dynamic::from(ImmutableString::from(item.payload.as_str())),
+            json,
+        ];
+        self.run(app, connection_id, &hooks, ON_MESSAGE, args);
+    }
+
+    fn hooks(&self, connection_id: &str) -> Option<Arc<CompiledHooks>> {
+        self.scripts
+            .get(connection_id)
+            .map(|entry| Arc::clone(entry.value()))
+    }
+
+    fn run(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        hooks: &CompiledHooks,
+        name: &str,
+        args: Vec<Dynamic>,
+    ) {
+        PENDING.with(|pending| *pending.borrow_mut() = (name != ON_DISCONNECT).then(Vec::new));
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            CallFnOptions::new().eval_ast(false),
+            &mut Scope::new(),
+            &hooks.ast,
+            name,
+            args,
+        );
+        let publishes = PENDING
+            .with(|pending| pending.borrow_mut().take())
+            .unwrap_or_default();
+        if let Err(error) = result {
+            report_error(app, connection_id, hooks, name, &error.to_string());
+            return;
+        }
+
+        let allowed = hooks.admit(publishes.len() as u64);
+        if allowed < publishes.len() {
+            report_error(
+                app,
+                connection_id,
+                hooks,
+                name,
+                &format!(
+                    "dropped {} publishes over the limit of {MAX_PUBLISHES_PER_SEC} per second",
+                    publishes.len() - allowed
+                ),
+            );
+        }
+        for publish in publishes.into_iter().take(allowed) {
+            let app = app.clone();
+            let connection_id = connection_id.to_string();
+            tokio::spawn(async move {
+                if let Err(error) = send(&app, &connection_id, publish).await {
+                    tracing::warn!(connection_id, "session hook publish failed: {error:#}");
+                }
+            });
+        }
+    }
+
+    fn compile_all(&self, config: &SessionHooksConfig) -> Result<Vec<(String, CompiledHooks)>> {
+        config
+            .connections
+            .iter()
+            .filter(|(_, hook)| hook.enabled && !hook.script.trim().is_empty())
+            .map(|(connection_id, hook)| {
+                let ast = self
+                    .engine
+                    .compile(&hook.script)
+                    .map_err(|error| anyhow!("session hooks for '{connection_id}': {error}"))?;
+                let message_filter = hook
+                    .message_filter
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|filter| !filter.is_empty());
+                if let Some(filter) = message_filter {
+                    if !is_valid_topic_filter(filter) {
+                        bail!(
+                            "session hooks for '{connection_id}': invalid message filter '{filter}'"
+                        );
+                    }
+                }
+                let defines = |name: &str, params: usize| {
+                    ast.iter_functions()
+                        .any(|function| function.name == name && function.params.len() == params)
+                };
+                let hooks = CompiledHooks {
+                    message_filter: message_filter.map(str::to_string),
+                    on_connect: defines(ON_CONNECT, 0),
+                    on_disconnect: defines(ON_DISCONNECT, 1),
+                    on_message: defines(ON_MESSAGE, 3),
+                    ast,
+                    window_started_at: AtomicU64::new(0),
+                    window_publishes: AtomicU64::new(0),
+                    last_error_at: AtomicU64::new(0),
+                    suppressed: AtomicU64::new(0),
+                };
+                Ok((connection_id.clone(), hooks))
+            })
+            .collect()
+    }
+
+    fn apply_config(&self, config: &SessionHooksConfig) -> Result<()> {
+        let compiled = self.compile_all(config)?;
+        self.scripts.clear();
+        for (connection_id, hooks) in compiled {
+            self.scripts.insert(connection_id, Arc::new(hooks));
+        }
+        Ok(())
+    }
+}
+
+impl CompiledHooks {
+    /// Counts `count` publishes against this second's budget and returns how many fit.
+    fn admit(&self, count: u64) -> usize {
+        let now = now_millis();
+        let started = self.window_started_at.load(Ordering::Relaxed);
+        if now.saturating_sub(started) >= 1000
+            && self
+                .window_started_at
+                .compare_exchange(started, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.window_publishes.store(0, Ordering::Relaxed);
+        }
+        let before = self.window_publishes.fetch_add(count, Ordering::Relaxed);
+        MAX_PUBLISHES_PER_SEC.saturating_sub(before).min(count) as usize
+    }
+}
+
+pub fn load(app: &AppHandle) -> Result<SessionHooksConfig> {
+    config_store::load_document::<SessionHooksConfig>(app, SESSION_HOOKS_FILE_NAME)
+}
+
+fn queue_publish(
+    topic: &str,
+    payload: Dynamic,
+    qos: i64,
+    retain: bool,
+) -> std::result::Result<(), Box<rhai::EvalAltResult>> {
+    if topic.is_empty() || topic.contains(['+', '#']) || !is_valid_topic_filter(topic) {
+        return Err(format!("invalid publish topic '{topic}'").into());
+    }
+    let qos = u8::try_from(qos)
+        .ok()
+        .filter(|qos| *qos <= 2)
+        .ok_or_else(|| format!("invalid QoS {qos}"))?;
+    let payload = if payload.is_map() || payload.is_array() {
+        rhai::serde::from_dynamic::<serde_json::Value>(&payload)?.to_string()
+    } else if payload.is_string() {
+        payload.into_immutable_string()?.to_string()
+    } else {
+        payload.to_string()
+    };
+    PENDING.with(|pending| match pending.borrow_mut().as_mut() {
+        Some(pending) => {
+            pending.push(HookPublish {
+                topic: topic.to_string(),
+                payload: Payload::from(payload),
+                qos,
+                retain,
+            });
+            Ok(())
+        }
+        None => Err(format!(
+            "publish is not available in {ON_DISCONNECT}; the session has stopped"
+        )
+        .into()),
+    })
+}
+
+async fn send(app: &AppHandle, connection_id: &str, publish: HookPublish) -> Result<()> {
+    crate::profiles::refuse_production(app, connection_id)?;
+    let state = app.state::<AppState>();
+    state.mqtt_manager.publish(
+        connection_id,
+        publish.topic.clone(),
+        publish.payload.clone(),
+        publish.qos,
+        publish.retain,
+        Vec::new(),
+    )?;
+    state.recent.record_outgoing(
+        connection_id,
+        &publish.topic,
+        &publish.payload,
+        publish.qos,
+        publish.retain,
+    );
+    state
+        .history_manager
+        .append_outgoing(
+            app,
+            connection_id,
+            &publish.topic,
+            publish.payload,
+            publish.qos,
+            publish.retain,
+            Vec::new(),
+        )
+        .await
+}
+
+fn report_error(
+    app: &AppHandle,
+    connection_id: &str,
+    hooks: &CompiledHooks,
+    hook: &str,
+    error: &str,
+) {
+    let now = now_millis();
+    let last = hooks.last_error_at.load(Ordering::Relaxed);
+    if now.saturating_sub(last) < ERROR_EVENT_INTERVAL_MS
+        || hooks
+            .last_error_at
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+    {
+        hooks.suppressed.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    windows::emit_for_connection(
+        app,
+        "session-hook-error",
+        connection_id,
+        SessionHookErrorPayload {
+            connection_id: connection_id.to_string(),
+            hook: hook.to_string(),
+            error: error.to_string(),
+            suppressed: hooks.suppressed.swap(0, Ordering::Relaxed),
+        },
+    );
+}
@@ -0,0 +1,37 @@
+use crate::mqtt::now_millis;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Last activity on a session, for disconnecting it once it sat idle for too long.
+///
+/// Commands sent through the session handle count as activity; incoming publishes count too
+/// unless `ignore_inbound` is set, so a chatty topic can't keep a metered connection open.
+pub struct IdleTracker {
+    last_activity: AtomicU64,
+    ignore_inbound: bool,
+}
+
+impl IdleTracker {
+    pub fn new(ignore_inbound: bool) -> Self {
+        Self {
+            last_activity: AtomicU64::new(now_millis()),
+            ignore_inbound,
+        }
+    }
+
+    pub fn touch(&self) {
+        self.last_activity.store(now_millis(), Ordering::Relaxed);
+    }
+
+    pub fn inbound(&self) {
+        if !self.ignore_inbound {
+            self.touch();
+        }
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        Duration::from_millis(
+            now_millis().saturating_sub(self.last_activity.load(Ordering::Relaxed)),
+        )
+    }
+}
@@ -0,0 +1,257 @@
+use crate::config_store;
+use crate::crash;
+use crate::models::{JournaledPublish, Payload, PublishJournalDocument, UserProperty};
+use crate::mqtt::now_millis;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+use tokio::time::{self, Duration};
+
+pub const PUBLISH_JOURNAL_FILE_NAME: &str = "publish-journal.json";
+/// Oldest entries are dropped past this, so a broker that never acks can't grow the file forever.
+const MAX_ENTRIES_PER_CONNECTION: usize = 1000;
+const FLUSH_INTERVAL_MS: u64 = 1000;
+
+/// Keeps outgoing QoS 1/2 publishes of connections with `replay_unacked_publishes` on disk
+/// until the broker acknowledges them, and hands the leftovers back when the next session
+/// connects, so a dropped connection doesn't silently lose messages.
+///
+/// Publishes are matched to packet ids in order, like [`super::ack::PublishAcks`]. Within one
+/// session rumqttc retransmits its own in-flight packets, so only entries carried over from an
+/// earlier session (or an earlier run of the app) are replayed.
+#[derive(Clone, Default)]
+pub struct PublishJournal {
+    inner: Arc<Mutex<JournalInner>>,
+}
+
+#[derive(Default)]
+struct JournalInner {
+    loaded: bool,
+    /// Changed since the last flush to disk.
+    dirty: bool,
+    next_id: u64,
+    connections: HashMap<String, ConnectionJournal>,
+}
+
+#[derive(Default)]
+struct ConnectionJournal {
+    entries: Vec<JournaledPublish>,
+    /// Entries from before the current session, waiting for its CONNACK.
+    carried_over: Vec<u64>,
+    /// Journal ids handed to rumqttc but not yet written.
+    queued: VecDeque<u64>,
+    in_flight: HashMap<u16, u64>,
+}
+
+impl PublishJournal {
+    /// Marks everything still journaled for `connection_id` as due for replay.
+    pub fn begin_session(&self, app: &AppHandle, connection_id: &str) {
+        let mut inner = self.lock(app);
+        let journal = inner
+            .connections
+            .entry(connection_id.to_string())
+            .or_default();
+        journal.carried_over = journal.entries.iter().map(|entry| entry.id).collect();
+        journal.queued.clear();
+        journal.in_flight.clear();
+    }
+
+    /// Removes and returns the carried-over entries, oldest first. The command loop journals
+    /// them again as it republishes them.
+    pub fn take_replay(&self, connection_id: &str) -> Vec<JournaledPublish> {
+        let mut inner = self.lock_loaded();
+        let Some(journal) = inner.connections.get_mut(connection_id) else {
+            return Vec::new();
+        };
+        let carried_over = std::mem::take(&mut journal.carried_over);
+        if carried_over.is_empty() {
+            return Vec::new();
+        }
+        let (replay, keep) = std::mem::take(&mut journal.entries)
+            .into_iter()
+            .partition(|entry| carried_over.contains(&entry.id));
+        journal.entries = keep;
+        inner.dirty = true;
+        replay
+    }
+
+    /// Called by the command loop just before a publish is handed to rumqttc.
+    #[allow(clippy::too_many_arguments)]
+    pub fn queued(
+        &self,
+        connection_id: &str,
+        publish_id: u64,
+        topic: &str,
+        payload: &Payload,
+        qos: u8,
+        retain: bool,
+        user_properties: &[UserProperty],
+    ) {
+        if qos == 0 {
+            return;
+        }
+        let mut inner = self.lock_loaded();
+        inner.next_id += 1;
+        let id = inner.next_id;
+        let journal = inner
+            .connections
+            .entry(connection_id.to_string())
+            .or_default();
+        if journal.entries.len() >= MAX_ENTRIES_PER_CONNECTION {
+            let dropped = journal.entries.remove(0);
+            tracing::warn!(
+                connection_id,
+                topic = dropped.topic.as_str(),
+                "publish journal full, dropping oldest unacknowledged publish"
+            );
+        }
+        journal.entries.push(JournaledPublish {
+            id,
+            publish_id,
+            topic: topic.to_string(),
+            payload: payload.as_str().to_string(),
+            is_binary: payload.is_binary(),
+            qos,
+            retain,
+            user_properties: user_properties.to_vec(),
+            journaled_at: now_millis(),
+        });
+        journal.queued.push_back(id);
+        inner.dirty = true;
+    }
+
+    /// Undoes [`Self::queued`] when rumqttc rejected the publish.
+    pub fn rejected(&self, connection_id: &str, qos: u8) {
+        if qos == 0 {
+            return;
+        }
+        let mut inner = self.lock_loaded();
+        let Some(journal) = inner.connections.get_mut(connection_id) else {
+            return;
+        };
+        if let Some(id) = journal.queued.pop_back() {
+            journal.entries.retain(|entry| entry.id != id);
+            inner.dirty = true;
+        }
+    }
+
+    pub fn publish_sent(&self, connection_id: &str, packet_id: u16) {
+        if packet_id == 0 {
+            return;
+        }
+        let mut inner = self.lock_loaded();
+        let Some(journal) = inner.connections.get_mut(connection_id) else {
+            return;
+        };
+        if journal.in_flight.contains_key(&packet_id) {
+            return;
+        }
+        if let Some(id) = journal.queued.pop_front() {
+            journal.in_flight.insert(packet_id, id);
+        }
+    }
+
+    /// The broker took responsibility for the publish (PUBACK, or PUBREC for QoS 2).
+    pub fn acked(&self, connection_id: &str, packet_id: u16) {
+        let mut inner = self.lock_loaded();
+        let Some(journal) = inner.connections.get_mut(connection_id) else {
+            return;
+        };
+        if let Some(id) = journal.in_flight.remove(&packet_id) {
+            journal.entries.retain(|entry| entry.id != id);
+            inner.dirty = true;
+        }
+    }
+
+    pub fn pending(&self, connection_id: &str) -> usize {
+        self.lock_loaded()
+            .connections
+            .get(connection_id)
+            .map_or(0, |journal| journal.entries.len())
+    }
+
+    /// Discards everything journaled for `connection_id`, so nothing is replayed.
+    pub fn clear(&self, connection_id: &str) {
+        let mut inner = self.lock_loaded();
+        if let Some(journal) = inner.connections.get_mut(connection_id) {
+            journal.entries.clear();
+            journal.carried_over.clear();
+            journal.queued.clear();
+            journal.in_flight.clear();
+            inner.dirty = true;
+        }
+    }
+
+    /// Writes the journal to disk at most every `FLUSH_INTERVAL_MS` while it changes.
+    pub fn start(&self, app: AppHandle) {
+        let journal = self.clone();
+        tokio::spawn(crash::monitored("publish-journal", async move {
+            let mut interval = time::interval(Duration::from_millis(FLUSH_INTERVAL_MS));
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(error) = journal.flush(&app) {
+                    tracing::warn!("failed to save publish journal: {error:#}");
+                }
+            }
+        }));
+    }
+
+    fn flush(&self, app: &AppHandle) -> anyhow::Result<()> {
+        let document = {
+            let mut inner = self.lock(app);
+            if !inner.dirty {
+                return Ok(());
+            }
+            inner.dirty = false;
+            PublishJournalDocument {
+                connections: inner
+                    .connections
+                    .iter()
+                    .filter(|(_, journal)| !journal.entries.is_empty())
+                    .map(|(connection_id, journal)| {
+                        (connection_id.clone(), journal.entries.clone())
+                    })
+                    .collect(),
+            }
+        };
+        config_store::save_document(app, PUBLISH_JOURNAL_FILE_NAME, &document).inspect_err(|_| {
+            self.lock_loaded().dirty = true;
+        })
+    }
+
+    /// Locks the journal, reading it from disk on first use.
+    fn lock(&self, app: &AppHandle) -> std::sync::MutexGuard<'_, JournalInner> {
+        let mut inner = self.lock_loaded();
+        if !inner.loaded {
+            inner.loaded = true;
+            let document = config_store::load_document::<PublishJournalDocument>(
+                app,
+                PUBLISH_JOURNAL_FILE_NAME,
+            )
+            .unwrap_or_else(|error| {
+                tracing::warn!("failed to load publish journal: {error:#}");
+                PublishJournalDocument::default()
+            });
+            for (connection_id, entries) in document.connections {
+                inner.next_id = entries
+                    .iter()
+                    .map(|entry| entry.id)
+                    .fold(inner.next_id, u64::max);
+                inner
+                    .connections
+                    .entry(connection_id)
+                    .or_default()
+                    .entries
+                    .extend(entries);
+            }
+        }
+        inner
+    }
+
+    fn lock_loaded(&self) -> std::sync::MutexGuard<'_, JournalInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
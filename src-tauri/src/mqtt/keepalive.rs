@@ -0,0 +1,166 @@
+use crate::models::{KeepAliveStats, MqttPingPayload, PingEventKind};
+use crate::mqtt::now_millis;
+use crate::windows;
+use dashmap::{DashMap, DashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::AppHandle;
+
+/// Keep-alive round trips per connection, plus which connections stream `mqtt-ping` events.
+#[derive(Clone, Default)]
+pub struct KeepAliveMonitor {
+    trackers: Arc<DashMap<String, Arc<PingTracker>>>,
+    /// Outlives sessions so the debug toggle survives reconnects.
+    debug: Arc<DashSet<String>>,
+}
+
+pub struct PingTracker {
+    connection_id: String,
+    debug: Arc<DashSet<String>>,
+    state: Mutex<PingState>,
+}
+
+#[derive(Default)]
+struct PingState {
+    outstanding: Option<Instant>,
+    pings_sent: u64,
+    responses: u64,
+    missed_pingresps: u64,
+    last_rtt_ms: Option<u64>,
+    max_rtt_ms: Option<u64>,
+    last_ping_at: Option<u64>,
+    interval_secs: Option<u16>,
+}
+
+impl KeepAliveMonitor {
+    /// Starts fresh stats for a new session of `connection_id`.
+    pub fn register(&self, connection_id: &str) -> Arc<PingTracker> {
+        let tracker = Arc::new(PingTracker {
+            connection_id: connection_id.to_string(),
+            debug: Arc::clone(&self.debug),
+            state: Mutex::default(),
+        });
+        self.trackers
+            .insert(connection_id.to_string(), Arc::clone(&tracker));
+        tracker
+    }
+
+    pub fn set_debug(&self, connection_id: &str, enabled: bool) {
+        if enabled {
+            self.debug.insert(connection_id.to_string());
+        } else {
+            self.debug.remove(connection_id);
+        }
+    }
+
+    pub fn stats(&self, connection_id: &str) -> KeepAliveStats {
+        match self.trackers.get(connection_id) {
+            Some(tracker) => tracker.stats(),
+            None => KeepAliveStats {
+                connection_id: connection_id.to_string(),
+                debug: self.debug.contains(connection_id),
+                ..KeepAliveStats::default()
+            },
+        }
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.trackers.remove(connection_id);
+    }
+}
+
+impl PingTracker {
+    /// A PINGREQ went out. One still unanswered at this point counts as missed, which the
+    /// return value reports.
+    pub fn sent(&self, app: &AppHandle) -> bool {
+        let missed = {
+            let mut state = self.lock();
+            let missed = state.outstanding.replace(Instant::now()).is_some();
+            if missed {
+                state.missed_pingresps += 1;
+            }
+            state.pings_sent += 1;
+            state.last_ping_at = Some(now_millis());
+            missed
+        };
+        if missed {
+            self.emit(app, PingEventKind::Missed, None);
+        }
+        self.emit(app, PingEventKind::Sent, None);
+        missed
+    }
+
+    /// Returns the round-trip time of the ping this answers.
+    pub fn response(&self, app: &AppHandle) -> Option<u64> {
+        let rtt_ms = {
+            let mut state = self.lock();
+            let sent_at = state.outstanding.take()?;
+            let rtt_ms = sent_at.elapsed().as_millis() as u64;
+            state.responses += 1;
+            state.last_rtt_ms = Some(rtt_ms);
+            state.max_rtt_ms = Some(state.max_rtt_ms.map_or(rtt_ms, |max| max.max(rtt_ms)));
+            rtt_ms
+        };
+        self.emit(app, PingEventKind::Response, Some(rtt_ms));
+        Some(rtt_ms)
+    }
+
+    /// Records the keep-alive the current connection pings on, after any server override.
+    pub fn set_interval(&self, secs: u16) {
+        self.lock().interval_secs = Some(secs);
+    }
+
+    /// The connection dropped; a ping still waiting for its PINGRESP is counted as missed.
+    pub fn connection_lost(&self, app: &AppHandle) -> bool {
+        let missed = {
+            let mut state = self.lock();
+            let missed = state.outstanding.take().is_some();
+            if missed {
+                state.missed_pingresps += 1;
+            }
+            missed
+        };
+        if missed {
+            self.emit(app, PingEventKind::Missed, None);
+        }
+        missed
+    }
+
+    fn stats(&self) -> KeepAliveStats {
+        let state = self.lock();
+        KeepAliveStats {
+            connection_id: self.connection_id.clone(),
+            debug: self.debug.contains(&self.connection_id),
+            pings_sent: state.pings_sent,
+            responses: state.responses,
+            missed_pingresps: state.missed_pingresps,
+            last_rtt_ms: state.last_rtt_ms,
+            max_rtt_ms: state.max_rtt_ms,
+            last_ping_at: state.last_ping_at,
+            interval_secs: state.interval_secs,
+        }
+    }
+
+    fn emit(&self, app: &AppHandle, kind: PingEventKind, rtt_ms: Option<u64>) {
+        if !self.debug.contains(&self.connection_id) {
+            return;
+        }
+        windows::emit_for_connection(
+            app,
+            "mqtt-ping",
+            &self.connection_id,
+            MqttPingPayload {
+                connection_id: self.connection_id.clone(),
+                kind,
+                rtt_ms,
+                timestamp: now_millis(),
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PingState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -1,25 +1,48 @@
-use crate::models::ResolvedConnection;
+use crate::models::{
+    BrokerCapabilities, ConnectionEventKind, ConnectionInfo, Payload, ResolvedConnection,
+    SubscribeOptions, UserProperty,
+};
 use crate::mqtt::MqttError;
-use crate::mqtt::session::{MqttSessionHandle, SessionCommand, start_session};
+use crate::mqtt::client_id::broker_key;
+use crate::mqtt::request::ResponseRoute;
+use crate::mqtt::session::{MqttSessionHandle, SessionCommand, record_event, start_session};
+use crate::mqtt::throttle::ConnectThrottle;
 
 use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use tauri::AppHandle;
 
 #[derive(Default)]
 pub struct MqttManager {
     sessions: DashMap<String, MqttSessionHandle>,
+    next_publish_id: AtomicU64,
+    throttle: ConnectThrottle,
 }
 
 impl MqttManager {
     pub fn new() -> Self {
         Self {
             sessions: DashMap::new(),
+            next_publish_id: AtomicU64::new(0),
+            throttle: ConnectThrottle::default(),
         }
     }
 
+    /// Limits how many sessions connect at once; sessions wait on it before each attempt.
+    pub fn throttle(&self) -> &ConnectThrottle {
+        &self.throttle
+    }
+
     pub fn connect(&self, app: AppHandle, connection: ResolvedConnection) -> Result<(), MqttError> {
         if let Some((_, existing)) = self.sessions.remove(&connection.id) {
             tokio::spawn(existing.shutdown());
+            record_event(
+                &app,
+                &connection.id,
+                ConnectionEventKind::Reconnecting,
+                None,
+            );
         }
 
         let session = start_session(app, connection.clone())?;
@@ -36,12 +59,108 @@ impl MqttManager {
         }
     }
 
-    pub fn subscribe(&self, connection_id: &str, topic: String, qos: u8) -> Result<(), MqttError> {
+    /// Shuts down every session and returns the ids that were connected.
+    pub async fn disconnect_all(&self) -> Vec<String> {
+        let ids = self
+            .sessions
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        let mut disconnected = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some((_, session)) = self.sessions.remove(&id) {
+                session.shutdown().await;
+                disconnected.push(id);
+            }
+        }
+        disconnected
+    }
+
+    /// Another live session using the same client id on the same broker, if any.
+    pub fn client_id_conflict(&self, connection: &ResolvedConnection) -> Option<String> {
+        if connection.client_id.trim().is_empty() {
+            return None;
+        }
+        let key = broker_key(&connection.host, connection.port);
+        self.session_using(&connection.client_id, &key, &connection.id)
+    }
+
+    pub fn session_using(
+        &self,
+        client_id: &str,
+        broker_key: &str,
+        excluding: &str,
+    ) -> Option<String> {
+        self.sessions
+            .iter()
+            .find(|session| {
+                session.key() != excluding
+                    && session.client_id() == client_id
+                    && session.broker_key() == broker_key
+            })
+            .map(|session| session.key().clone())
+    }
+
+    pub fn is_connected(&self, connection_id: &str) -> bool {
+        self.sessions.contains_key(connection_id)
+    }
+
+    pub fn capabilities(&self, connection_id: &str) -> Result<BrokerCapabilities, MqttError> {
+        self.sessions
+            .get(connection_id)
+            .map(|session| session.capabilities())
+            .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))
+    }
+
+    pub fn protocol_version(&self, connection_id: &str) -> Result<u8, MqttError> {
+        self.sessions
+            .get(connection_id)
+            .map(|session| session.protocol_version())
+            .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))
+    }
+
+    /// `None` until the session connected and its transport probe finished.
+    pub fn connection_info(
+        &self,
+        connection_id: &str,
+    ) -> Result<Option<ConnectionInfo>, MqttError> {
+        self.sessions
+            .get(connection_id)
+            .map(|session| session.connection_info())
+            .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))
+    }
+
+    pub fn subscribe(
+        &self,
+        connection_id: &str,
+        topic: String,
+        qos: u8,
+        options: SubscribeOptions,
+    ) -> Result<(), MqttError> {
+        let session = self
+            .sessions
+            .get(connection_id)
+            .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))?;
+        session.send(SessionCommand::Subscribe {
+            topic,
+            qos,
+            options,
+        })
+    }
+
+    pub fn peek(
+        &self,
+        connection_id: &str,
+        topic: String,
+        qos: u8,
+        max_messages: Option<u32>,
+        duration: Option<Duration>,
+    ) -> Result<(), MqttError> {
         let session = self
             .sessions
             .get(connection_id)
             .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))?;
-        session.send(SessionCommand::Subscribe { topic, qos })
+        session.peek(topic, qos, max_messages, duration)
     }
 
     pub fn unsubscribe(&self, connection_id: &str, topic: String) -> Result<(), MqttError> {
@@ -56,19 +175,60 @@ impl MqttManager {
         &self,
         connection_id: &str,
         topic: String,
-        payload: String,
+        payload: Payload,
         qos: u8,
         retain: bool,
-    ) -> Result<(), MqttError> {
+        user_properties: Vec<UserProperty>,
+    ) -> Result<u64, MqttError> {
         let session = self
             .sessions
             .get(connection_id)
             .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))?;
+        if !user_properties.is_empty() && session.protocol_version() != 5 {
+            return Err(MqttError::UserPropertiesUnsupported(
+                connection_id.to_string(),
+            ));
+        }
+        let publish_id = self.next_publish_id.fetch_add(1, Ordering::Relaxed) + 1;
         session.send(SessionCommand::Publish {
+            publish_id,
             topic,
             payload,
             qos,
             retain,
-        })
+            user_properties,
+            response: None,
+        })?;
+        Ok(publish_id)
+    }
+
+    /// Queues an MQTT 5 request publish carrying `response`; see [`super::request::send`].
+    pub fn request(
+        &self,
+        connection_id: &str,
+        topic: String,
+        payload: Payload,
+        qos: u8,
+        user_properties: Vec<UserProperty>,
+        response: ResponseRoute,
+    ) -> Result<u64, MqttError> {
+        let session = self
+            .sessions
+            .get(connection_id)
+            .ok_or_else(|| MqttError::ConnectionNotFound(connection_id.to_string()))?;
+        if session.protocol_version() != 5 {
+            return Err(MqttError::RequestsUnsupported(connection_id.to_string()));
+        }
+        let publish_id = self.next_publish_id.fetch_add(1, Ordering::Relaxed) + 1;
+        session.send(SessionCommand::Publish {
+            publish_id,
+            topic,
+            payload,
+            qos,
+            retain: false,
+            user_properties,
+            response: Some(response),
+        })?;
+        Ok(publish_id)
     }
 }
@@ -0,0 +1,139 @@
+use crate::config_store;
+use crate::models::{ConnectionMemoryMetrics, MemoryLimitsConfig, MqttBatchItem};
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use tauri::AppHandle;
+
+pub const MEMORY_LIMITS_FILE_NAME: &str = "memory-limits.json";
+
+/// Caps the per-connection batch queues; messages arriving while a queue is full are shed.
+#[derive(Clone, Default)]
+pub struct MemoryGuard {
+    limits: Arc<QueueLimits>,
+    queues: Arc<DashMap<String, Arc<QueueGauge>>>,
+}
+
+struct QueueLimits {
+    max_messages: AtomicUsize,
+    max_bytes: AtomicU64,
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        let defaults = MemoryLimitsConfig::default();
+        Self {
+            max_messages: AtomicUsize::new(defaults.max_queued_messages),
+            max_bytes: AtomicU64::new(defaults.max_queued_bytes),
+        }
+    }
+}
+
+pub struct QueueGauge {
+    limits: Arc<QueueLimits>,
+    messages: AtomicUsize,
+    bytes: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl QueueGauge {
+    /// Reserves room for one message; returns false (and counts the drop) when over either cap.
+    pub fn try_admit(&self, item: &MqttBatchItem) -> bool {
+        let size = item_size(item);
+        let max_messages = self.limits.max_messages.load(Ordering::Relaxed);
+        let max_bytes = self.limits.max_bytes.load(Ordering::Relaxed);
+        if self.messages.load(Ordering::Relaxed) >= max_messages
+            || self.bytes.load(Ordering::Relaxed) + size > max_bytes
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        true
+    }
+
+    pub fn release(&self, item: &MqttBatchItem) {
+        self.messages.fetch_sub(1, Ordering::Relaxed);
+        self.bytes.fetch_sub(item_size(item), Ordering::Relaxed);
+    }
+}
+
+impl MemoryGuard {
+    pub fn load_config(&self, app: &AppHandle) -> Result<MemoryLimitsConfig> {
+        let config =
+            config_store::load_document::<MemoryLimitsConfig>(app, MEMORY_LIMITS_FILE_NAME)?;
+        self.apply(&config);
+        Ok(config)
+    }
+
+    pub fn save_config(&self, app: &AppHandle, config: &MemoryLimitsConfig) -> Result<()> {
+        if config.max_queued_messages == 0
+            || config.max_queued_bytes == 0
+            || config.max_discovery_topics == 0
+        {
+            return Err(anyhow!("memory limits must be greater than zero"));
+        }
+        config_store::save_document(app, MEMORY_LIMITS_FILE_NAME, config)?;
+        self.apply(config);
+        Ok(())
+    }
+
+    fn apply(&self, config: &MemoryLimitsConfig) {
+        self.limits
+            .max_messages
+            .store(config.max_queued_messages, Ordering::Relaxed);
+        self.limits
+            .max_bytes
+            .store(config.max_queued_bytes, Ordering::Relaxed);
+    }
+
+    /// Starts a fresh gauge for a new session, replacing counters from any previous one.
+    pub fn register(&self, connection_id: &str) -> Arc<QueueGauge> {
+        let gauge = Arc::new(QueueGauge {
+            limits: Arc::clone(&self.limits),
+            messages: AtomicUsize::new(0),
+            bytes: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+        self.queues
+            .insert(connection_id.to_string(), Arc::clone(&gauge));
+        gauge
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.queues.remove(connection_id);
+    }
+
+    pub fn snapshot(&self) -> Vec<ConnectionMemoryMetrics> {
+        self.queues
+            .iter()
+            .map(|entry| ConnectionMemoryMetrics {
+                connection_id: entry.key().clone(),
+                queued_messages: entry.messages.load(Ordering::Relaxed),
+                queued_bytes: entry.bytes.load(Ordering::Relaxed),
+                dropped_messages: entry.dropped.load(Ordering::Relaxed),
+                ..ConnectionMemoryMetrics::default()
+            })
+            .collect()
+    }
+}
+
+fn item_size(item: &MqttBatchItem) -> u64 {
+    (item.topic.len() + item.payload.len()) as u64
+}
+
+/// Resident set size of this process, where the platform exposes it cheaply.
+#[cfg(target_os = "linux")]
+pub fn resident_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn resident_bytes() -> Option<u64> {
+    None
+}
@@ -1,6 +1,30 @@
+pub mod ack;
+pub mod benchmark;
+pub mod client_id;
+pub mod echo;
+pub mod endpoint;
+pub mod flow;
+pub mod health;
+pub mod hooks;
+pub mod idle;
+pub mod journal;
+pub mod keepalive;
 pub mod manager;
+pub mod memory;
+pub mod peek;
+pub mod qos2;
+pub mod rate_limit;
+pub mod recent;
+pub mod request;
 pub mod session;
+pub mod suback;
+pub mod sys_stats;
+pub mod throttle;
+pub mod tls;
+pub mod transform;
+pub mod validate;
 
+use crate::models::TopicFilterValidation;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -9,6 +33,12 @@ pub enum MqttError {
     ConnectionNotFound(String),
     #[error("connection command channel closed")]
     CommandChannelClosed,
+    #[error("already subscribed to {0}; unsubscribe before peeking")]
+    AlreadySubscribed(String),
+    #[error("{0} is not an MQTT 5 connection, so it can't send user properties")]
+    UserPropertiesUnsupported(String),
+    #[error("{0} is not an MQTT 5 connection, so it can't send requests with a response topic")]
+    RequestsUnsupported(String),
     #[error("mqtt error: {0}")]
     Mqtt(#[from] rumqttc::ClientError),
 }
@@ -35,3 +65,112 @@ pub fn now_millis() -> u64 {
         .unwrap_or_default()
         .as_millis() as u64
 }
+
+/// Topic strings are length-prefixed with a u16 on the wire.
+const MAX_TOPIC_BYTES: usize = u16::MAX as usize;
+
+/// Checks MQTT filter syntax: `#` only as the last level, wildcards only as whole levels.
+pub fn is_valid_topic_filter(filter: &str) -> bool {
+    validate_topic_filter(filter).valid
+}
+
+/// Splits `$share/{group}/{filter}` into its group and inner filter.
+pub fn shared_subscription(filter: &str) -> Option<(&str, &str)> {
+    filter.strip_prefix("$share/")?.split_once('/')
+}
+
+/// Full filter check, including `$share` syntax and MQTT's UTF-8 string rules. Control
+/// characters are only warnings since the spec discourages rather than forbids them.
+pub fn validate_topic_filter(filter: &str) -> TopicFilterValidation {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if filter.len() > MAX_TOPIC_BYTES {
+        errors.push(format!(
+            "filter is {} bytes, above the {MAX_TOPIC_BYTES} byte limit",
+            filter.len()
+        ));
+    }
+    if filter.contains('\0') {
+        errors.push("filter contains the null character U+0000".to_string());
+    } else if filter.chars().any(char::is_control) {
+        warnings.push("filter contains control characters, which brokers may reject".to_string());
+    }
+
+    let mut shared_group = None;
+    let inner = if filter == "$share" || filter.starts_with("$share/") {
+        match shared_subscription(filter) {
+            Some((group, inner)) => {
+                if group.is_empty() {
+                    errors.push("shared subscription group name is empty".to_string());
+                } else if group.contains(['+', '#']) {
+                    errors.push(
+                        "shared subscription group name cannot contain wildcards".to_string(),
+                    );
+                }
+                shared_group = Some(group.to_string());
+                Some(inner)
+            }
+            None => {
+                errors
+                    .push("shared subscriptions need the form $share/{group}/{filter}".to_string());
+                None
+            }
+        }
+    } else {
+        Some(filter)
+    };
+
+    match inner {
+        None => {}
+        Some("") => errors.push("filter is empty".to_string()),
+        Some(inner) => {
+            let levels = inner.split('/').collect::<Vec<_>>();
+            for (index, level) in levels.iter().enumerate() {
+                match *level {
+                    "#" if index != levels.len() - 1 => {
+                        errors.push("'#' must be the last level of the filter".to_string());
+                    }
+                    "#" | "+" => {}
+                    other if other.contains(['#', '+']) => {
+                        errors.push(format!(
+                            "level {} ('{other}') mixes wildcards with other characters",
+                            index + 1
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    TopicFilterValidation {
+        valid: errors.is_empty(),
+        shared_group,
+        errors,
+        warnings,
+    }
+}
+
+/// Canonical filter matcher shared by subscriptions, rules and the frontend. `$share` filters
+/// match on their inner filter, since brokers deliver shared messages on the original topic.
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let filter = shared_subscription(filter).map_or(filter, |(_, inner)| inner);
+    let mut filter_levels = filter.split('/');
+    let mut topic_levels = topic.split('/');
+
+    // Wildcards at the first level never match `$`-prefixed system topics.
+    if topic.starts_with('$') && (filter.starts_with('#') || filter.starts_with('+')) {
+        return false;
+    }
+
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(expected), Some(actual)) if expected == actual => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::models::{PeekEndReason, PeekEndedPayload};
+use crate::mqtt::{MqttError, topic_matches};
+use crate::windows;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Temporary subscriptions of one session that unsubscribe themselves after a number of
+/// messages or a timeout, for sampling a topic without keeping the subscription.
+///
+/// The command loop reports regular subscribes and unsubscribes so a peek never removes a
+/// subscription the user keeps: peeking at one is refused, and subscribing to a peeked filter
+/// turns the peek into a regular subscription.
+#[derive(Default)]
+pub struct PeekSubscriptions {
+    state: Mutex<PeekState>,
+}
+
+#[derive(Default)]
+struct PeekState {
+    next_id: u64,
+    subscribed: HashSet<String>,
+    peeks: HashMap<String, Peek>,
+}
+
+struct Peek {
+    id: u64,
+    max_messages: Option<u32>,
+    received: u32,
+}
+
+impl PeekSubscriptions {
+    /// Registers a peek on `filter`, replacing an earlier one on the same filter, and returns
+    /// its id for [`Self::expire`].
+    pub fn begin(&self, filter: &str, max_messages: Option<u32>) -> Result<u64, MqttError> {
+        let mut state = self.lock();
+        if state.subscribed.contains(filter) {
+            return Err(MqttError::AlreadySubscribed(filter.to_string()));
+        }
+        state.next_id += 1;
+        let id = state.next_id;
+        state.peeks.insert(
+            filter.to_string(),
+            Peek {
+                id,
+                max_messages,
+                received: 0,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn subscribed(&self, filter: &str) {
+        let mut state = self.lock();
+        state.peeks.remove(filter);
+        state.subscribed.insert(filter.to_string());
+    }
+
+    pub fn unsubscribed(&self, filter: &str) {
+        let mut state = self.lock();
+        state.peeks.remove(filter);
+        state.subscribed.remove(filter);
+    }
+
+    /// Counts a received message against the peeks matching `topic` and returns the filters
+    /// that reached their message limit, with their message counts.
+    pub fn observe(&self, topic: &str) -> Vec<(String, u32)> {
+        let mut state = self.lock();
+        if state.peeks.is_empty() {
+            return Vec::new();
+        }
+        let mut finished = Vec::new();
+        state.peeks.retain(|filter, peek| {
+            if !topic_matches(filter, topic) {
+                return true;
+            }
+            peek.received += 1;
+            if peek.max_messages.is_some_and(|max| peek.received >= max) {
+                finished.push((filter.clone(), peek.received));
+                return false;
+            }
+            true
+        });
+        finished
+    }
+
+    /// Ends peek `id` on `filter` when its time is up, unless it already ended or was replaced.
+    pub fn expire(&self, filter: &str, id: u64) -> Option<u32> {
+        let mut state = self.lock();
+        if state.peeks.get(filter).is_none_or(|peek| peek.id != id) {
+            return None;
+        }
+        state.peeks.remove(filter).map(|peek| peek.received)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PeekState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub fn emit_ended(
+    app: &AppHandle,
+    connection_id: &str,
+    topic: String,
+    received: u32,
+    reason: PeekEndReason,
+) {
+    windows::emit_for_connection(
+        app,
+        "mqtt-peek-ended",
+        connection_id,
+        PeekEndedPayload {
+            connection_id: connection_id.to_string(),
+            topic,
+            received,
+            reason,
+        },
+    );
+}
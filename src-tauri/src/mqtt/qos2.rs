@@ -0,0 +1,182 @@
+use crate::models::{Qos2Flow, Qos2Step, Qos2StepKind};
+use crate::mqtt::now_millis;
+use crate::windows;
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+/// Flows kept per connection; the oldest are dropped first.
+const MAX_FLOWS: usize = 500;
+
+/// Records the PUBLISH → PUBREC → PUBREL → PUBCOMP exchange of outgoing QoS 2 messages on
+/// connections where verification is switched on.
+///
+/// rumqttc reports outgoing publishes by packet id only, so publishes are queued by the command
+/// loop and matched to `Outgoing::Publish` events in order.
+#[derive(Clone, Default)]
+pub struct Qos2Tracer {
+    logs: Arc<DashMap<String, Arc<Qos2Log>>>,
+}
+
+struct Qos2Log {
+    enabled: AtomicBool,
+    state: Mutex<Qos2State>,
+}
+
+#[derive(Default)]
+struct Qos2State {
+    /// Publishes handed to rumqttc that have not been written yet, as (topic, qos, queued at).
+    queued: VecDeque<(String, u8, u64)>,
+    flows: VecDeque<Qos2Flow>,
+}
+
+impl Qos2Tracer {
+    /// Turning verification on starts a fresh log; turning it off keeps the log for inspection.
+    pub fn set_enabled(&self, connection_id: &str, enabled: bool) {
+        if enabled {
+            self.logs.insert(
+                connection_id.to_string(),
+                Arc::new(Qos2Log {
+                    enabled: AtomicBool::new(true),
+                    state: Mutex::default(),
+                }),
+            );
+        } else if let Some(log) = self.logs.get(connection_id) {
+            log.enabled.store(false, Ordering::Relaxed);
+            log.lock().queued.clear();
+        }
+    }
+
+    /// Recorded flows, oldest first.
+    pub fn flows(&self, connection_id: &str) -> Vec<Qos2Flow> {
+        self.logs
+            .get(connection_id)
+            .map(|log| log.lock().flows.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forgets queued publishes when the session is torn down. Recorded flows stay readable.
+    pub fn clear_pending(&self, connection_id: &str) {
+        if let Some(log) = self.logs.get(connection_id) {
+            log.lock().queued.clear();
+        }
+    }
+
+    /// Called by the command loop just before a publish is handed to rumqttc.
+    pub fn queued(&self, connection_id: &str, topic: &str, qos: u8) {
+        if let Some(log) = self.active(connection_id) {
+            log.lock()
+                .queued
+                .push_back((topic.to_string(), qos, now_millis()));
+        }
+    }
+
+    /// Undoes [`Self::queued`] when rumqttc rejected the publish.
+    pub fn rejected(&self, connection_id: &str) {
+        if let Some(log) = self.active(connection_id) {
+            log.lock().queued.pop_back();
+        }
+    }
+
+    pub fn publish_sent(&self, app: &AppHandle, connection_id: &str, packet_id: u16) {
+        let Some(log) = self.active(connection_id) else {
+            return;
+        };
+        let mut state = log.lock();
+        if packet_id != 0 {
+            if let Some(flow) = find_open(&mut state.flows, packet_id) {
+                // A PUBLISH for an open flow is a retransmission after reconnecting.
+                flow.steps.push(step(Qos2StepKind::Retransmit, None));
+                emit(app, flow);
+                return;
+            }
+        }
+
+        let Some((topic, qos, queued_at)) = state.queued.pop_front() else {
+            return;
+        };
+        if qos != 2 || packet_id == 0 {
+            return;
+        }
+        if state.flows.len() >= MAX_FLOWS {
+            state.flows.pop_front();
+        }
+        let flow = Qos2Flow {
+            connection_id: connection_id.to_string(),
+            packet_id,
+            topic,
+            queued_at,
+            steps: vec![step(Qos2StepKind::Publish, None)],
+            complete: false,
+            total_ms: None,
+        };
+        emit(app, &flow);
+        state.flows.push_back(flow);
+    }
+
+    /// Records PUBREC, PUBREL or PUBCOMP. A PUBREC carrying an error reason ends the flow, since
+    /// the broker won't expect a PUBREL for it.
+    pub fn ack(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        packet_id: u16,
+        kind: Qos2StepKind,
+        failure: Option<String>,
+    ) {
+        let Some(log) = self.active(connection_id) else {
+            return;
+        };
+        let mut state = log.lock();
+        let Some(flow) = find_open(&mut state.flows, packet_id) else {
+            return;
+        };
+        let finished = matches!(kind, Qos2StepKind::PubComp) || failure.is_some();
+        flow.steps.push(step(kind, failure));
+        if finished {
+            flow.complete = true;
+            flow.total_ms = flow
+                .steps
+                .first()
+                .zip(flow.steps.last())
+                .map(|(first, last)| last.timestamp.saturating_sub(first.timestamp));
+        }
+        emit(app, flow);
+    }
+
+    fn active(&self, connection_id: &str) -> Option<Arc<Qos2Log>> {
+        let log = self.logs.get(connection_id)?;
+        log.enabled
+            .load(Ordering::Relaxed)
+            .then(|| Arc::clone(log.value()))
+    }
+}
+
+impl Qos2Log {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Qos2State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn find_open(flows: &mut VecDeque<Qos2Flow>, packet_id: u16) -> Option<&mut Qos2Flow> {
+    flows
+        .iter_mut()
+        .rev()
+        .find(|flow| !flow.complete && flow.packet_id == packet_id)
+}
+
+fn step(kind: Qos2StepKind, failure: Option<String>) -> Qos2Step {
+    Qos2Step {
+        kind,
+        timestamp: now_millis(),
+        failure,
+    }
+}
+
+fn emit(app: &AppHandle, flow: &Qos2Flow) {
+    windows::emit_for_connection(app, "mqtt-qos2-flow", &flow.connection_id, flow.clone());
+}
@@ -0,0 +1,85 @@
+use crate::models::PublishRateLimit;
+use tokio::time::{Duration, Instant};
+
+/// Token buckets for outgoing publishes; each allows up to one second of burst.
+pub struct PublishLimiter {
+    messages: Option<Bucket>,
+    bytes: Option<Bucket>,
+}
+
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            tokens: rate,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.updated = now;
+    }
+
+    /// Time until `cost` can be taken. Costs above the burst size only wait for a full
+    /// bucket and then go into debt, so one oversized payload cannot stall forever.
+    fn delay(&self, cost: f64) -> Duration {
+        let needed = cost.min(self.rate);
+        if self.tokens >= needed {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((needed - self.tokens) / self.rate)
+        }
+    }
+}
+
+impl PublishLimiter {
+    pub fn new(limit: &PublishRateLimit) -> Option<Self> {
+        let messages = limit
+            .messages_per_sec
+            .filter(|rate| *rate > 0)
+            .map(|rate| Bucket::new(rate as f64));
+        let bytes = limit
+            .bytes_per_sec
+            .filter(|rate| *rate > 0)
+            .map(|rate| Bucket::new(rate as f64));
+        if messages.is_none() && bytes.is_none() {
+            return None;
+        }
+        Some(Self { messages, bytes })
+    }
+
+    /// How long the next publish of `size` bytes has to wait.
+    pub fn delay(&mut self, size: usize) -> Duration {
+        let now = Instant::now();
+        let mut delay = Duration::ZERO;
+        if let Some(bucket) = &mut self.messages {
+            bucket.refill(now);
+            delay = delay.max(bucket.delay(1.0));
+        }
+        if let Some(bucket) = &mut self.bytes {
+            bucket.refill(now);
+            delay = delay.max(bucket.delay(size as f64));
+        }
+        delay
+    }
+
+    pub fn consume(&mut self, size: usize) {
+        let now = Instant::now();
+        if let Some(bucket) = &mut self.messages {
+            bucket.refill(now);
+            bucket.tokens -= 1.0;
+        }
+        if let Some(bucket) = &mut self.bytes {
+            bucket.refill(now);
+            bucket.tokens -= size as f64;
+        }
+    }
+}
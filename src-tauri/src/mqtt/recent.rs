@@ -0,0 +1,113 @@
+use crate::models::{MessageDirection, MqttBatchItem, Payload};
+use crate::mqtt::{now_millis, topic_matches};
+use dashmap::DashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const DEFAULT_RECENT_MESSAGES: usize = 1000;
+
+/// Last N messages per connection, kept so views can refill without querying SQLite.
+#[derive(Clone)]
+pub struct RecentMessages {
+    buffers: Arc<DashMap<String, VecDeque<MqttBatchItem>>>,
+    capacity: Arc<AtomicUsize>,
+}
+
+impl Default for RecentMessages {
+    fn default() -> Self {
+        Self {
+            buffers: Arc::default(),
+            capacity: Arc::new(AtomicUsize::new(DEFAULT_RECENT_MESSAGES)),
+        }
+    }
+}
+
+impl RecentMessages {
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        for mut buffer in self.buffers.iter_mut() {
+            let excess = buffer.len().saturating_sub(capacity);
+            buffer.drain(..excess);
+        }
+    }
+
+    pub fn record(&self, connection_id: &str, batch: &[MqttBatchItem]) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        if capacity == 0 {
+            return;
+        }
+        let mut buffer = self.buffers.entry(connection_id.to_string()).or_default();
+        let skip = batch.len().saturating_sub(capacity);
+        for item in &batch[skip..] {
+            if buffer.len() >= capacity {
+                buffer.pop_front();
+            }
+            buffer.push_back(item.clone());
+        }
+    }
+
+    pub fn record_outgoing(
+        &self,
+        connection_id: &str,
+        topic: &str,
+        payload: &Payload,
+        qos: u8,
+        retain: bool,
+    ) {
+        self.record(
+            connection_id,
+            &[MqttBatchItem {
+                topic: topic.to_string(),
+                payload: payload.clone(),
+                qos,
+                retain,
+                direction: MessageDirection::Out,
+                timestamp: now_millis(),
+                dup: false,
+                packet_id: None,
+                correlation_id: None,
+                response_topic: None,
+                user_properties: Vec::new(),
+                is_binary: payload.is_binary(),
+            }],
+        );
+    }
+
+    /// Newest `limit` messages matching the optional filter, oldest first.
+    pub fn query(
+        &self,
+        connection_id: &str,
+        limit: usize,
+        topic_filter: Option<&str>,
+    ) -> Vec<MqttBatchItem> {
+        let Some(buffer) = self.buffers.get(connection_id) else {
+            return Vec::new();
+        };
+        let mut items = buffer
+            .iter()
+            .rev()
+            .filter(|item| topic_filter.is_none_or(|filter| topic_matches(filter, &item.topic)))
+            .take(limit)
+            .cloned()
+            .collect::<Vec<_>>();
+        items.reverse();
+        items
+    }
+
+    /// Message count and approximate bytes buffered for a connection.
+    pub fn usage(&self, connection_id: &str) -> (usize, u64) {
+        let Some(buffer) = self.buffers.get(connection_id) else {
+            return (0, 0);
+        };
+        let bytes = buffer
+            .iter()
+            .map(|item| (item.topic.len() + item.payload.len()) as u64)
+            .sum();
+        (buffer.len(), bytes)
+    }
+
+    pub fn clear(&self, connection_id: &str) {
+        self.buffers.remove(connection_id);
+    }
+}
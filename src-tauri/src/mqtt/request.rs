@@ -0,0 +1,157 @@
+use crate::models::{MessageDirection, MqttBatchItem, MqttRequestResult, Payload, UserProperty};
+use crate::mqtt::{MqttError, now_millis};
+use crate::profiles::random_id;
+use crate::state::AppState;
+use anyhow::{Result, bail};
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(300);
+const RESPONSE_TOPIC_PREFIX: &str = "mqtt-nexus/replies";
+
+/// MQTT v5 response topic and correlation data attached to a request publish.
+#[derive(Debug, Clone)]
+pub struct ResponseRoute {
+    pub topic: String,
+    pub correlation_data: Bytes,
+}
+
+/// Requests waiting for their reply, keyed by connection and correlation data.
+///
+/// Sessions hand every incoming message carrying correlation data to [`Self::resolve`] before
+/// transforms run, so a script dropping or rewriting replies can't make a request time out.
+#[derive(Clone, Default)]
+pub struct PendingRequests {
+    waiting: Arc<DashMap<(String, String), oneshot::Sender<MqttBatchItem>>>,
+}
+
+impl PendingRequests {
+    pub fn resolve(&self, connection_id: &str, item: &MqttBatchItem) {
+        let Some(correlation_id) = &item.correlation_id else {
+            return;
+        };
+        if self.waiting.is_empty() {
+            return;
+        }
+        let key = (connection_id.to_string(), correlation_id.clone());
+        if let Some((_, reply)) = self.waiting.remove(&key) {
+            let _ = reply.send(item.clone());
+        }
+    }
+
+    fn register(
+        &self,
+        connection_id: &str,
+        correlation_id: &str,
+    ) -> oneshot::Receiver<MqttBatchItem> {
+        let (tx, rx) = oneshot::channel();
+        self.waiting
+            .insert((connection_id.to_string(), correlation_id.to_string()), tx);
+        rx
+    }
+
+    fn cancel(&self, connection_id: &str, correlation_id: &str) {
+        self.waiting
+            .remove(&(connection_id.to_string(), correlation_id.to_string()));
+    }
+}
+
+/// Publishes `payload` with a response topic and fresh correlation data, then waits up to
+/// `timeout` for the reply carrying the same correlation data.
+///
+/// The response topic is peeked for the length of the request unless it is already subscribed,
+/// and the request is recorded like any other outgoing publish.
+#[allow(clippy::too_many_arguments)]
+pub async fn send(
+    app: &AppHandle,
+    connection_id: &str,
+    topic: String,
+    payload: Payload,
+    qos: u8,
+    response_topic: Option<String>,
+    timeout: Duration,
+    user_properties: Vec<UserProperty>,
+) -> Result<MqttRequestResult> {
+    let state = app.state::<AppState>();
+    let correlation_id = random_id();
+    let response_topic = response_topic
+        .map(|topic| topic.trim().to_string())
+        .filter(|topic| !topic.is_empty())
+        .unwrap_or_else(|| format!("{RESPONSE_TOPIC_PREFIX}/{correlation_id}"));
+    if response_topic.contains(['+', '#']) {
+        bail!("response topic can't contain wildcards: {response_topic}");
+    }
+    // Checked before peeking so a v3.1.1 connection isn't left subscribed to the reply topic.
+    if state.mqtt_manager.protocol_version(connection_id)? != 5 {
+        return Err(MqttError::RequestsUnsupported(connection_id.to_string()).into());
+    }
+
+    match state.mqtt_manager.peek(
+        connection_id,
+        response_topic.clone(),
+        qos,
+        None,
+        Some(timeout),
+    ) {
+        Ok(()) | Err(MqttError::AlreadySubscribed(_)) => {}
+        Err(error) => return Err(error.into()),
+    }
+    let reply = state.requests.register(connection_id, &correlation_id);
+    let started = Instant::now();
+    let published = state.mqtt_manager.request(
+        connection_id,
+        topic.clone(),
+        payload.clone(),
+        qos,
+        user_properties.clone(),
+        ResponseRoute {
+            topic: response_topic.clone(),
+            correlation_data: Bytes::from(correlation_id.clone().into_bytes()),
+        },
+    );
+    if let Err(error) = published {
+        state.requests.cancel(connection_id, &correlation_id);
+        return Err(error.into());
+    }
+
+    let item = MqttBatchItem {
+        topic,
+        payload,
+        qos,
+        retain: false,
+        direction: MessageDirection::Out,
+        timestamp: now_millis(),
+        dup: false,
+        packet_id: None,
+        correlation_id: Some(correlation_id.clone()),
+        response_topic: Some(response_topic.clone()),
+        user_properties,
+        is_binary: false,
+    };
+    state
+        .recent
+        .record(connection_id, std::slice::from_ref(&item));
+    state
+        .history_manager
+        .append_batch(app, connection_id, &[item])
+        .await?;
+
+    let reply = match tokio::time::timeout(timeout, reply).await {
+        Ok(Ok(reply)) => Some(reply),
+        Ok(Err(_)) | Err(_) => {
+            state.requests.cancel(connection_id, &correlation_id);
+            None
+        }
+    };
+    Ok(MqttRequestResult {
+        correlation_id,
+        response_topic,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        reply,
+    })
+}
@@ -1,17 +1,47 @@
+use crate::crash;
 use crate::models::{
-    ConnectionStatus, MessageDirection, MqttBatchItem, MqttMessageBatchPayload, MqttStatusPayload,
-    ResolvedConnection, TransportProtocol,
+    BrokerCapabilities, ConnectionEventKind, ConnectionInfo, ConnectionStatus, MessageDirection,
+    MqttBatchItem, MqttMessageBatchPayload, MqttStatusPayload, Payload, PeekEndReason,
+    PublishAckPacket, PublishSaturationPayload, Qos2StepKind, ResolvedConnection, RetainHandling,
+    ServerRedirect, SessionParameters, SubscribeOptions, SubscriptionAckKind, SubscriptionPreset,
+    TransportProtocol, UserProperty,
 };
+use crate::mqtt::ack;
+use crate::mqtt::client_id::broker_key;
+use crate::mqtt::endpoint;
+use crate::mqtt::flow::{self, FlowWindow};
+use crate::mqtt::idle::IdleTracker;
+use crate::mqtt::journal::PublishJournal;
+use crate::mqtt::memory::QueueGauge;
+use crate::mqtt::peek::{self, PeekSubscriptions};
+use crate::mqtt::rate_limit::PublishLimiter;
+use crate::mqtt::request::ResponseRoute;
+use crate::mqtt::suback;
 use crate::mqtt::{MqttError, now_millis, qos_from_u8, qos_to_u8};
+use crate::windows;
 
-use rumqttc::{self, AsyncClient, Event, Incoming, MqttOptions, Outgoing, Transport};
-use tauri::{AppHandle, Emitter, Manager};
+use rumqttc::v5::mqttbytes::v5::{
+    DisconnectReasonCode, Filter, PubCompReason, PubRecReason, PublishProperties, RetainForwardRule,
+};
+use rumqttc::{
+    self, AsyncClient, Event, Incoming, MqttOptions, Outgoing, TlsConfiguration, Transport,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tokio::time::{self, Duration};
+use tracing::Instrument;
 
-const BATCH_MAX: usize = 50;
 const BATCH_FLUSH_MS: u64 = 75;
+const KEEP_ALIVE_SECS: u16 = 30;
+const TAKEOVER_RETRY_MS: u64 = 5000;
+/// Redirects followed in a row before giving up, in case brokers point at each other.
+const MAX_REDIRECT_HOPS: u8 = 3;
+/// rumqttc's default outgoing packet limit for v3.1.1 clients, which we never override.
+const V4_MAX_OUTGOING_PACKET: u32 = 10 * 1024;
 
 enum ClientKind {
     V4(AsyncClient),
@@ -39,47 +69,188 @@ pub enum SessionCommand {
     Subscribe {
         topic: String,
         qos: u8,
+        options: SubscribeOptions,
     },
     Unsubscribe {
         topic: String,
     },
+    /// Subscribes until [`PeekSubscriptions`] ends peek `id`, or `duration` passes.
+    Peek {
+        topic: String,
+        qos: u8,
+        id: u64,
+        duration: Option<Duration>,
+    },
     Publish {
+        /// Echoed back in `mqtt-publish-ack` so the caller can match the broker's verdict.
+        publish_id: u64,
         topic: String,
-        payload: String,
+        payload: Payload,
         qos: u8,
         retain: bool,
+        /// Only sent on MQTT 5 connections; [`super::manager::MqttManager::publish`] refuses
+        /// them on older ones.
+        user_properties: Vec<UserProperty>,
+        /// Response topic and correlation data of a request; MQTT 5 only, like user properties.
+        response: Option<ResponseRoute>,
     },
     Disconnect,
 }
 
 pub struct MqttSessionHandle {
+    client_id: String,
+    protocol_version: u8,
+    broker_key: String,
+    capabilities: Arc<Mutex<BrokerCapabilities>>,
+    /// Transport details of the current connection, once [`endpoint::probe`] finished.
+    endpoint: Arc<Mutex<Option<ConnectionInfo>>>,
+    closing: Arc<AtomicBool>,
     command_tx: mpsc::UnboundedSender<SessionCommand>,
+    peeks: Arc<PeekSubscriptions>,
+    idle: Arc<IdleTracker>,
     command_task: JoinHandle<()>,
     event_task: JoinHandle<()>,
     batch_task: JoinHandle<()>,
+    idle_task: Option<JoinHandle<()>>,
 }
 
 impl MqttSessionHandle {
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    pub fn broker_key(&self) -> &str {
+        &self.broker_key
+    }
+
+    pub fn protocol_version(&self) -> u8 {
+        self.protocol_version
+    }
+
+    pub fn capabilities(&self) -> BrokerCapabilities {
+        *self
+            .capabilities
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        self.endpoint
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Subscribes to `topic` until `max_messages` arrived or `duration` passed.
+    pub fn peek(
+        &self,
+        topic: String,
+        qos: u8,
+        max_messages: Option<u32>,
+        duration: Option<Duration>,
+    ) -> Result<(), MqttError> {
+        let id = self.peeks.begin(&topic, max_messages)?;
+        self.send(SessionCommand::Peek {
+            topic,
+            qos,
+            id,
+            duration,
+        })
+    }
+
     pub fn send(&self, command: SessionCommand) -> Result<(), MqttError> {
+        if !matches!(command, SessionCommand::Disconnect) {
+            self.idle.touch();
+        }
         self.command_tx
             .send(command)
             .map_err(|_| MqttError::CommandChannelClosed)
     }
 
     pub async fn shutdown(self) {
+        self.closing.store(true, Ordering::SeqCst);
         let _ = self.command_tx.send(SessionCommand::Disconnect);
         self.command_task.abort();
         self.event_task.abort();
         self.batch_task.abort();
+        if let Some(idle_task) = self.idle_task {
+            idle_task.abort();
+        }
     }
 }
 
+#[tracing::instrument(skip_all, fields(connection_id = %cfg.id, protocol_version = cfg.protocol_version))]
 pub fn start_session(
     app: AppHandle,
     cfg: ResolvedConnection,
 ) -> Result<MqttSessionHandle, MqttError> {
     let (command_tx, command_rx) = mpsc::unbounded_channel::<SessionCommand>();
     let (message_tx, message_rx) = mpsc::unbounded_channel::<MqttBatchItem>();
+    let protocol_version = cfg.protocol_version;
+    let closing = Arc::new(AtomicBool::new(false));
+    let peeks = Arc::new(PeekSubscriptions::default());
+    let gauge = app
+        .state::<crate::state::AppState>()
+        .memory
+        .register(&cfg.id);
+    let pings = app
+        .state::<crate::state::AppState>()
+        .keep_alive
+        .register(&cfg.id);
+    let health = app
+        .state::<crate::state::AppState>()
+        .health
+        .tracker(&cfg.id);
+    let flow_window = app
+        .state::<crate::state::AppState>()
+        .flow_control
+        .register(&cfg.id);
+    let qos2 = app.state::<crate::state::AppState>().qos2.clone();
+    let throttle = app
+        .state::<crate::state::AppState>()
+        .mqtt_manager
+        .throttle()
+        .clone();
+    let publish_acks = app.state::<crate::state::AppState>().publish_acks.clone();
+    let subscription_acks = app
+        .state::<crate::state::AppState>()
+        .subscription_acks
+        .clone();
+    let echo_latency = app.state::<crate::state::AppState>().echo_latency.clone();
+    let journal = cfg.replay_unacked_publishes.then(|| {
+        let journal = app
+            .state::<crate::state::AppState>()
+            .publish_journal
+            .clone();
+        journal.begin_session(&app, &cfg.id);
+        journal
+    });
+    let drop_notice = cfg
+        .notify_on_disconnect
+        .then(|| DropNotice::new(&cfg.name, closing.clone()));
+    let capabilities = Arc::new(Mutex::new(if cfg.protocol_version == 5 {
+        BrokerCapabilities::default()
+    } else {
+        BrokerCapabilities {
+            max_packet_size: Some(V4_MAX_OUTGOING_PACKET),
+            ..BrokerCapabilities::default()
+        }
+    }));
+    let endpoint = Arc::new(Mutex::new(None));
+    let plugins = app.state::<crate::state::AppState>().plugins.clone();
+    let transforms = app.state::<crate::state::AppState>().transforms.clone();
+    let hooks = app.state::<crate::state::AppState>().session_hooks.clone();
+    let requests = app.state::<crate::state::AppState>().requests.clone();
+    let idle = Arc::new(IdleTracker::new(
+        cfg.idle_disconnect
+            .as_ref()
+            .is_some_and(|idle| idle.ignore_inbound),
+    ));
+    let takeover = Arc::new(Takeover {
+        client_id: cfg.client_id.clone(),
+        broker_key: broker_key(&cfg.host, cfg.port),
+        reconnect: cfg.reconnect_on_takeover,
+    });
 
     emit_status(
         &app,
@@ -87,10 +258,21 @@ pub fn start_session(
             connection_id: cfg.id.clone(),
             status: ConnectionStatus::Connecting,
             last_error: None,
+            session: None,
+            redirect: None,
         },
     );
 
-    let batch_task = tokio::spawn(run_batch_emitter(app.clone(), cfg.id.clone(), message_rx));
+    let batch_task = tokio::spawn(crash::monitored(
+        "mqtt-batch-emitter",
+        run_batch_emitter(
+            app.clone(),
+            cfg.id.clone(),
+            message_rx,
+            Arc::clone(&gauge),
+            flow_window,
+        ),
+    ));
 
     let (client_kind, event_task) = if cfg.protocol_version == 5 {
         let options = build_v5_options(&cfg);
@@ -98,28 +280,270 @@ pub fn start_session(
         let app_handle = app.clone();
         let connection_id = cfg.id.clone();
         let message_tx_clone = message_tx.clone();
-        let event_task = tokio::spawn(async move {
+        let drop_notice = drop_notice.clone();
+        let gauge = Arc::clone(&gauge);
+        let takeover = Arc::clone(&takeover);
+        let pings = Arc::clone(&pings);
+        let health = Arc::clone(&health);
+        let qos2 = qos2.clone();
+        let publish_acks = publish_acks.clone();
+        let subscription_acks = subscription_acks.clone();
+        let echo_latency = echo_latency.clone();
+        let journal = journal.clone();
+        let peeks = Arc::clone(&peeks);
+        let capabilities = Arc::clone(&capabilities);
+        let endpoint = Arc::clone(&endpoint);
+        let plugins = plugins.clone();
+        let transforms = transforms.clone();
+        let hooks = hooks.clone();
+        let idle = Arc::clone(&idle);
+        let requests = requests.clone();
+        let redirect_cfg = cfg.clone();
+        let closing = closing.clone();
+        let preset_tx = command_tx.clone();
+        let throttle = throttle.clone();
+        let event_task = tokio::spawn(crash::monitored("mqtt-event-loop", async move {
+            let mut announced_takeover = false;
+            let mut pending_redirect = None;
+            let mut connected = false;
+            // Whether `onDisconnect` is still owed for the current connection.
+            let mut hooks_connected = false;
+            let mut connect_permit = None;
             loop {
+                if !connected && connect_permit.is_none() {
+                    connect_permit = throttle.acquire().await;
+                }
+                if !connected {
+                    reset_keep_alive(&mut eventloop.options);
+                }
                 match eventloop.poll().await {
-                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::ConnAck(_))) => {
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::ConnAck(connack))) => {
+                        connected = true;
+                        connect_permit = None;
+                        announced_takeover = false;
+                        pending_redirect = None;
+                        health.connected();
+                        probe_endpoint(&endpoint, &connection_id, &redirect_cfg);
+                        subscribe_presets(&preset_tx, &redirect_cfg.default_subscriptions);
+                        replay_journal(&preset_tx, journal.as_ref(), &connection_id);
+                        let keep_alive_secs = connack_keep_alive(
+                            &mut eventloop.options,
+                            connack
+                                .properties
+                                .as_ref()
+                                .and_then(|properties| properties.server_keep_alive),
+                        );
+                        let mut session = SessionParameters {
+                            client_id: takeover.client_id.clone(),
+                            client_id_assigned: false,
+                            keep_alive_secs,
+                            keep_alive_overridden: keep_alive_secs != KEEP_ALIVE_SECS,
+                        };
+                        let advertised = match connack.properties {
+                            Some(properties) => {
+                                if let Some(assigned) = properties.assigned_client_identifier {
+                                    session.client_id = assigned;
+                                    session.client_id_assigned = true;
+                                }
+                                BrokerCapabilities {
+                                    max_qos: properties.max_qos.unwrap_or(2),
+                                    max_packet_size: properties.max_packet_size,
+                                    retain_available: properties.retain_available != Some(0),
+                                }
+                            }
+                            None => BrokerCapabilities::default(),
+                        };
+                        pings.set_interval(session.keep_alive_secs);
+                        *capabilities
+                            .lock()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner()) = advertised;
                         emit_status(
                             &app_handle,
                             MqttStatusPayload {
                                 connection_id: connection_id.clone(),
                                 status: ConnectionStatus::Connected,
                                 last_error: None,
+                                session: Some(session),
+                                redirect: None,
                             },
                         );
+                        hooks_connected = true;
+                        hooks.on_connect(&app_handle, &connection_id);
                     }
                     Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::Publish(publish))) => {
-                        let _ = message_tx_clone.send(MqttBatchItem {
-                            topic: String::from_utf8_lossy(publish.topic.as_ref()).into_owned(),
-                            payload: String::from_utf8_lossy(publish.payload.as_ref()).into_owned(),
+                        let topic = String::from_utf8_lossy(publish.topic.as_ref()).into_owned();
+                        echo_latency.received(
+                            &connection_id,
+                            &topic,
+                            publish
+                                .properties
+                                .as_ref()
+                                .and_then(|props| props.correlation_data.as_deref()),
+                            &publish.payload,
+                        );
+                        let (correlation_id, response_topic, user_properties) =
+                            match publish.properties {
+                                Some(properties) => (
+                                    properties.correlation_data.as_deref().map(correlation_key),
+                                    properties.response_topic,
+                                    properties
+                                        .user_properties
+                                        .into_iter()
+                                        .map(|(key, value)| UserProperty { key, value })
+                                        .collect(),
+                                ),
+                                None => (None, None, Vec::new()),
+                            };
+                        idle.inbound();
+                        end_peeks(&app_handle, &connection_id, &peeks, &preset_tx, &topic);
+                        let (payload, is_binary) = plugins.decode(&topic, publish.payload);
+                        let item = MqttBatchItem {
+                            payload,
+                            topic,
                             qos: qos_to_u8_v5(publish.qos),
                             retain: publish.retain,
                             direction: MessageDirection::In,
                             timestamp: now_millis(),
-                        });
+                            dup: publish.dup,
+                            packet_id: (publish.pkid != 0).then_some(publish.pkid),
+                            correlation_id,
+                            response_topic,
+                            user_properties,
+                            is_binary,
+                        };
+                        requests.resolve(&connection_id, &item);
+                        let Some(item) = transforms.apply(&app_handle, &connection_id, item) else {
+                            continue;
+                        };
+                        hooks.on_message(&app_handle, &connection_id, &item);
+                        if gauge.try_admit(&item) {
+                            let _ = message_tx_clone.send(item);
+                        }
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::Disconnect(
+                        disconnect,
+                    ))) => {
+                        announced_takeover =
+                            disconnect.reason_code == DisconnectReasonCode::SessionTakenOver;
+                        pending_redirect = server_redirect(&disconnect);
+                    }
+                    Ok(rumqttc::v5::Event::Outgoing(Outgoing::PingReq)) => {
+                        if pings.sent(&app_handle) {
+                            health.missed_ping();
+                        }
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::PingResp(_))) => {
+                        if let Some(rtt_ms) = pings.response(&app_handle) {
+                            health.ping_rtt(rtt_ms);
+                        }
+                    }
+                    Ok(rumqttc::v5::Event::Outgoing(
+                        Outgoing::Subscribe(pkid) | Outgoing::Unsubscribe(pkid),
+                    )) => {
+                        subscription_acks.sent(&connection_id, pkid);
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::SubAck(suback))) => {
+                        let results: Vec<_> =
+                            suback.return_codes.iter().map(suback::suback_v5).collect();
+                        subscription_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            suback.pkid,
+                            &results,
+                            suback.properties.and_then(|props| props.reason_string),
+                        );
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::UnsubAck(unsuback))) => {
+                        let results: Vec<_> =
+                            unsuback.reasons.iter().map(suback::unsuback_v5).collect();
+                        subscription_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            unsuback.pkid,
+                            &results,
+                            unsuback.properties.and_then(|props| props.reason_string),
+                        );
+                    }
+                    Ok(rumqttc::v5::Event::Outgoing(Outgoing::Publish(pkid))) => {
+                        qos2.publish_sent(&app_handle, &connection_id, pkid);
+                        publish_acks.publish_sent(&connection_id, pkid);
+                        if let Some(journal) = &journal {
+                            journal.publish_sent(&connection_id, pkid);
+                        }
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::PubAck(puback))) => {
+                        if let Some(journal) = &journal {
+                            journal.acked(&connection_id, puback.pkid);
+                        }
+                        if let Some((latency_ms, success)) = publish_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            puback.pkid,
+                            PublishAckPacket::Puback,
+                            ack::puback_code(puback.reason),
+                            puback.properties.and_then(|props| props.reason_string),
+                        ) {
+                            health.acked(latency_ms, success);
+                        }
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::PubRec(pubrec))) => {
+                        if let Some(journal) = &journal {
+                            journal.acked(&connection_id, pubrec.pkid);
+                        }
+                        if let Some((latency_ms, success)) = publish_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            pubrec.pkid,
+                            PublishAckPacket::Pubrec,
+                            ack::pubrec_code(pubrec.reason),
+                            pubrec
+                                .properties
+                                .as_ref()
+                                .and_then(|props| props.reason_string.clone()),
+                        ) {
+                            health.acked(latency_ms, success);
+                        }
+                        let failure = match pubrec.reason {
+                            PubRecReason::Success | PubRecReason::NoMatchingSubscribers => None,
+                            reason => Some(format!("{reason:?}")),
+                        };
+                        qos2.ack(
+                            &app_handle,
+                            &connection_id,
+                            pubrec.pkid,
+                            Qos2StepKind::PubRec,
+                            failure,
+                        );
+                    }
+                    Ok(rumqttc::v5::Event::Outgoing(Outgoing::PubRel(pkid))) => {
+                        qos2.ack(
+                            &app_handle,
+                            &connection_id,
+                            pkid,
+                            Qos2StepKind::PubRel,
+                            None,
+                        );
+                    }
+                    Ok(rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::PubComp(pubcomp))) => {
+                        publish_acks.completed(
+                            &app_handle,
+                            &connection_id,
+                            pubcomp.pkid,
+                            ack::pubcomp_code(pubcomp.reason),
+                            pubcomp
+                                .properties
+                                .as_ref()
+                                .and_then(|props| props.reason_string.clone()),
+                        );
+                        let failure = (pubcomp.reason != PubCompReason::Success)
+                            .then(|| format!("{:?}", pubcomp.reason));
+                        qos2.ack(
+                            &app_handle,
+                            &connection_id,
+                            pubcomp.pkid,
+                            Qos2StepKind::PubComp,
+                            failure,
+                        );
                     }
                     Ok(rumqttc::v5::Event::Outgoing(Outgoing::Disconnect)) => {
                         emit_status(
@@ -128,24 +552,65 @@ pub fn start_session(
                                 connection_id: connection_id.clone(),
                                 status: ConnectionStatus::Disconnected,
                                 last_error: None,
+                                session: None,
+                                redirect: None,
                             },
                         );
+                        if std::mem::take(&mut hooks_connected) {
+                            hooks.on_disconnect(&app_handle, &connection_id, "client disconnect");
+                        }
                     }
                     Ok(_) => {}
                     Err(error) => {
-                        emit_status(
+                        connected = false;
+                        if std::mem::take(&mut hooks_connected) {
+                            hooks.on_disconnect(&app_handle, &connection_id, &error.to_string());
+                        }
+                        connect_permit = None;
+                        clear_endpoint(&endpoint);
+                        if pings.connection_lost(&app_handle) {
+                            health.missed_ping();
+                        }
+                        if !closing.load(Ordering::SeqCst) {
+                            health.error();
+                        }
+                        // rumqttc reports a broker DISCONNECT as an error while the packet
+                        // itself is still queued as an event.
+                        if let Some(disconnect) = queued_disconnect(&eventloop.state.events) {
+                            announced_takeover |=
+                                disconnect.reason_code == DisconnectReasonCode::SessionTakenOver;
+                            if pending_redirect.is_none() {
+                                pending_redirect = server_redirect(disconnect);
+                            }
+                        }
+                        if let Some(redirect) = pending_redirect.take() {
+                            handle_redirect(
+                                &app_handle,
+                                &redirect_cfg,
+                                drop_notice.as_ref(),
+                                &closing,
+                                redirect,
+                            );
+                            break;
+                        }
+                        let taken_over = announced_takeover
+                            || takeover.by_local_session(&app_handle, &connection_id);
+                        let keep_polling = report_poll_error(
                             &app_handle,
-                            MqttStatusPayload {
-                                connection_id: connection_id.clone(),
-                                status: ConnectionStatus::Error,
-                                last_error: Some(error.to_string()),
-                            },
-                        );
-                        break;
+                            &connection_id,
+                            drop_notice.as_ref(),
+                            &takeover,
+                            taken_over,
+                            error.to_string(),
+                        )
+                        .await;
+                        if !keep_polling {
+                            break;
+                        }
                     }
                 }
             }
-        });
+        }));
 
         (ClientKind::V5(client), event_task)
     } else {
@@ -154,29 +619,205 @@ pub fn start_session(
         let app_handle = app.clone();
         let connection_id = cfg.id.clone();
         let message_tx_clone = message_tx.clone();
+        let drop_notice = drop_notice.clone();
+        let gauge = Arc::clone(&gauge);
+        let takeover = Arc::clone(&takeover);
+        let pings = Arc::clone(&pings);
+        let health = Arc::clone(&health);
+        let closing = closing.clone();
+        let journal = journal.clone();
+        let peeks = Arc::clone(&peeks);
+        let qos2 = qos2.clone();
+        let plugins = plugins.clone();
+        let publish_acks = publish_acks.clone();
+        let subscription_acks = subscription_acks.clone();
+        let echo_latency = echo_latency.clone();
+        let transforms = transforms.clone();
+        let hooks = hooks.clone();
+        let idle = Arc::clone(&idle);
+        let preset_tx = command_tx.clone();
+        let presets = cfg.default_subscriptions.clone();
+        let endpoint = Arc::clone(&endpoint);
+        let endpoint_cfg = cfg.clone();
 
-        let event_task = tokio::spawn(async move {
+        let event_task = tokio::spawn(crash::monitored("mqtt-event-loop", async move {
+            let mut connected = false;
+            // Whether `onDisconnect` is still owed for the current connection.
+            let mut hooks_connected = false;
+            let mut connect_permit = None;
             loop {
+                if !connected && connect_permit.is_none() {
+                    connect_permit = throttle.acquire().await;
+                }
                 match eventloop.poll().await {
                     Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                        connected = true;
+                        connect_permit = None;
+                        health.connected();
+                        pings.set_interval(KEEP_ALIVE_SECS);
+                        probe_endpoint(&endpoint, &connection_id, &endpoint_cfg);
+                        subscribe_presets(&preset_tx, &presets);
+                        replay_journal(&preset_tx, journal.as_ref(), &connection_id);
                         emit_status(
                             &app_handle,
                             MqttStatusPayload {
                                 connection_id: connection_id.clone(),
                                 status: ConnectionStatus::Connected,
                                 last_error: None,
+                                // MQTT 3.1.1 has no server keep-alive.
+                                session: Some(SessionParameters {
+                                    client_id: takeover.client_id.clone(),
+                                    client_id_assigned: false,
+                                    keep_alive_secs: KEEP_ALIVE_SECS,
+                                    keep_alive_overridden: false,
+                                }),
+                                redirect: None,
                             },
                         );
+                        hooks_connected = true;
+                        hooks.on_connect(&app_handle, &connection_id);
                     }
                     Ok(Event::Incoming(Incoming::Publish(publish))) => {
-                        let _ = message_tx_clone.send(MqttBatchItem {
+                        idle.inbound();
+                        echo_latency.received(
+                            &connection_id,
+                            &publish.topic,
+                            None,
+                            &publish.payload,
+                        );
+                        end_peeks(
+                            &app_handle,
+                            &connection_id,
+                            &peeks,
+                            &preset_tx,
+                            &publish.topic,
+                        );
+                        let (payload, is_binary) = plugins.decode(&publish.topic, publish.payload);
+                        let item = MqttBatchItem {
+                            payload,
                             topic: publish.topic,
-                            payload: String::from_utf8_lossy(publish.payload.as_ref()).into_owned(),
                             qos: qos_to_u8(publish.qos),
                             retain: publish.retain,
                             direction: MessageDirection::In,
                             timestamp: now_millis(),
-                        });
+                            dup: publish.dup,
+                            packet_id: (publish.pkid != 0).then_some(publish.pkid),
+                            correlation_id: None,
+                            response_topic: None,
+                            user_properties: Vec::new(),
+                            is_binary,
+                        };
+                        let Some(item) = transforms.apply(&app_handle, &connection_id, item) else {
+                            continue;
+                        };
+                        hooks.on_message(&app_handle, &connection_id, &item);
+                        if gauge.try_admit(&item) {
+                            let _ = message_tx_clone.send(item);
+                        }
+                    }
+                    Ok(Event::Outgoing(Outgoing::PingReq)) => {
+                        if pings.sent(&app_handle) {
+                            health.missed_ping();
+                        }
+                    }
+                    Ok(Event::Incoming(Incoming::PingResp)) => {
+                        if let Some(rtt_ms) = pings.response(&app_handle) {
+                            health.ping_rtt(rtt_ms);
+                        }
+                    }
+                    Ok(Event::Outgoing(
+                        Outgoing::Subscribe(pkid) | Outgoing::Unsubscribe(pkid),
+                    )) => {
+                        subscription_acks.sent(&connection_id, pkid);
+                    }
+                    Ok(Event::Incoming(Incoming::SubAck(suback))) => {
+                        let results: Vec<_> =
+                            suback.return_codes.iter().map(suback::suback_v4).collect();
+                        subscription_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            suback.pkid,
+                            &results,
+                            None,
+                        );
+                    }
+                    Ok(Event::Incoming(Incoming::UnsubAck(unsuback))) => {
+                        subscription_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            unsuback.pkid,
+                            &[],
+                            None,
+                        );
+                    }
+                    Ok(Event::Outgoing(Outgoing::Publish(pkid))) => {
+                        qos2.publish_sent(&app_handle, &connection_id, pkid);
+                        publish_acks.publish_sent(&connection_id, pkid);
+                        if let Some(journal) = &journal {
+                            journal.publish_sent(&connection_id, pkid);
+                        }
+                    }
+                    Ok(Event::Incoming(Incoming::PubAck(puback))) => {
+                        if let Some(journal) = &journal {
+                            journal.acked(&connection_id, puback.pkid);
+                        }
+                        if let Some((latency_ms, success)) = publish_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            puback.pkid,
+                            PublishAckPacket::Puback,
+                            0x00,
+                            None,
+                        ) {
+                            health.acked(latency_ms, success);
+                        }
+                    }
+                    Ok(Event::Incoming(Incoming::PubRec(pubrec))) => {
+                        if let Some(journal) = &journal {
+                            journal.acked(&connection_id, pubrec.pkid);
+                        }
+                        if let Some((latency_ms, success)) = publish_acks.acked(
+                            &app_handle,
+                            &connection_id,
+                            pubrec.pkid,
+                            PublishAckPacket::Pubrec,
+                            0x00,
+                            None,
+                        ) {
+                            health.acked(latency_ms, success);
+                        }
+                        qos2.ack(
+                            &app_handle,
+                            &connection_id,
+                            pubrec.pkid,
+                            Qos2StepKind::PubRec,
+                            None,
+                        );
+                    }
+                    Ok(Event::Outgoing(Outgoing::PubRel(pkid))) => {
+                        qos2.ack(
+                            &app_handle,
+                            &connection_id,
+                            pkid,
+                            Qos2StepKind::PubRel,
+                            None,
+                        );
+                    }
+                    Ok(Event::Incoming(Incoming::PubComp(pubcomp))) => {
+                        publish_acks.completed(
+                            &app_handle,
+                            &connection_id,
+                            pubcomp.pkid,
+                            0x00,
+                            None,
+                        );
+                        qos2.ack(
+                            &app_handle,
+                            &connection_id,
+                            pubcomp.pkid,
+                            Qos2StepKind::PubComp,
+                            None,
+                        );
                     }
                     Ok(Event::Outgoing(Outgoing::Disconnect)) => {
                         emit_status(
@@ -185,105 +826,353 @@ pub fn start_session(
                                 connection_id: connection_id.clone(),
                                 status: ConnectionStatus::Disconnected,
                                 last_error: None,
+                                session: None,
+                                redirect: None,
                             },
                         );
+                        if std::mem::take(&mut hooks_connected) {
+                            hooks.on_disconnect(&app_handle, &connection_id, "client disconnect");
+                        }
                     }
                     Ok(_) => {}
                     Err(error) => {
-                        emit_status(
+                        connected = false;
+                        if std::mem::take(&mut hooks_connected) {
+                            hooks.on_disconnect(&app_handle, &connection_id, &error.to_string());
+                        }
+                        connect_permit = None;
+                        clear_endpoint(&endpoint);
+                        if pings.connection_lost(&app_handle) {
+                            health.missed_ping();
+                        }
+                        if !closing.load(Ordering::SeqCst) {
+                            health.error();
+                        }
+                        // v3.1.1 brokers just close the socket, so only local takeovers are known.
+                        let taken_over = takeover.by_local_session(&app_handle, &connection_id);
+                        let keep_polling = report_poll_error(
                             &app_handle,
-                            MqttStatusPayload {
-                                connection_id: connection_id.clone(),
-                                status: ConnectionStatus::Error,
-                                last_error: Some(error.to_string()),
-                            },
-                        );
-                        break;
+                            &connection_id,
+                            drop_notice.as_ref(),
+                            &takeover,
+                            taken_over,
+                            error.to_string(),
+                        )
+                        .await;
+                        if !keep_polling {
+                            break;
+                        }
                     }
                 }
             }
-        });
+        }));
 
         (ClientKind::V4(client), event_task)
     };
 
+    let limiter = cfg
+        .publish_rate_limit
+        .as_ref()
+        .and_then(PublishLimiter::new);
+    let idle_task = cfg
+        .idle_disconnect
+        .as_ref()
+        .map(|config| spawn_idle_watchdog(&app, &cfg.id, Arc::clone(&idle), config.minutes));
+    let client_id = cfg.client_id.clone();
+    let broker_key = broker_key(&cfg.host, cfg.port);
     let connection_id = cfg.id;
     let app_handle = app;
 
-    let command_task = tokio::spawn(async move {
-        run_command_loop(app_handle, connection_id, client_kind, command_rx).await;
-    });
+    let command_task = tokio::spawn(crash::monitored(
+        "mqtt-command-loop",
+        run_command_loop(
+            app_handle,
+            connection_id,
+            client_kind,
+            command_rx,
+            closing.clone(),
+            limiter,
+            journal,
+            Arc::clone(&peeks),
+            command_tx.clone(),
+        ),
+    ));
 
     Ok(MqttSessionHandle {
+        client_id,
+        protocol_version,
+        broker_key,
+        capabilities,
+        endpoint,
+        closing,
         command_tx,
+        peeks,
+        idle,
         command_task,
         event_task,
         batch_task,
+        idle_task,
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_command_loop(
     app: AppHandle,
     connection_id: String,
     client: ClientKind,
     mut command_rx: mpsc::UnboundedReceiver<SessionCommand>,
+    closing: Arc<AtomicBool>,
+    mut limiter: Option<PublishLimiter>,
+    journal: Option<PublishJournal>,
+    peeks: Arc<PeekSubscriptions>,
+    command_tx: mpsc::UnboundedSender<SessionCommand>,
 ) {
+    let plugins = app.state::<crate::state::AppState>().plugins.clone();
+    let qos2 = app.state::<crate::state::AppState>().qos2.clone();
+    let publish_acks = app.state::<crate::state::AppState>().publish_acks.clone();
+    let subscription_acks = app
+        .state::<crate::state::AppState>()
+        .subscription_acks
+        .clone();
+    let echo_latency = app.state::<crate::state::AppState>().echo_latency.clone();
+    let mut saturated = false;
     while let Some(command) = command_rx.recv().await {
         let is_disconnect = matches!(command, SessionCommand::Disconnect);
-        let result: Result<(), String> = match (&client, command) {
-            (ClientKind::V4(c), SessionCommand::Subscribe { topic, qos }) => c
-                .subscribe(topic, qos_from_u8(qos))
-                .await
-                .map(|_| ())
-                .map_err(|e| e.to_string()),
-            (ClientKind::V5(c), SessionCommand::Subscribe { topic, qos }) => c
-                .subscribe(topic, qos_from_u8_v5(qos))
-                .await
-                .map(|_| ())
-                .map_err(|e| e.to_string()),
-            (ClientKind::V4(c), SessionCommand::Unsubscribe { topic }) => c
-                .unsubscribe(topic)
-                .await
-                .map(|_| ())
-                .map_err(|e| e.to_string()),
-            (ClientKind::V5(c), SessionCommand::Unsubscribe { topic }) => c
-                .unsubscribe(topic)
-                .await
-                .map(|_| ())
-                .map_err(|e| e.to_string()),
-            (
-                ClientKind::V4(c),
-                SessionCommand::Publish {
-                    topic,
-                    payload,
-                    qos,
-                    retain,
-                },
-            ) => c
-                .publish(topic, qos_from_u8(qos), retain, payload)
-                .await
-                .map(|_| ())
-                .map_err(|e| e.to_string()),
-            (
-                ClientKind::V5(c),
-                SessionCommand::Publish {
-                    topic,
-                    payload,
-                    qos,
-                    retain,
-                },
-            ) => c
-                .publish(topic, qos_from_u8_v5(qos), retain, payload)
-                .await
-                .map(|_| ())
-                .map_err(|e| e.to_string()),
-            (ClientKind::V4(c), SessionCommand::Disconnect) => {
-                c.disconnect().await.map(|_| ()).map_err(|e| e.to_string())
+        if is_disconnect {
+            closing.store(true, Ordering::SeqCst);
+        }
+        // Later commands stay queued in the channel while a publish waits for tokens.
+        if let (Some(limiter), SessionCommand::Publish { payload, .. }) = (&mut limiter, &command) {
+            let wait = limiter.delay(payload.len());
+            if !wait.is_zero() {
+                if !saturated {
+                    saturated = true;
+                    emit_saturation(&app, &connection_id, true, command_rx.len());
+                }
+                time::sleep(wait).await;
             }
-            (ClientKind::V5(c), SessionCommand::Disconnect) => {
-                c.disconnect().await.map(|_| ()).map_err(|e| e.to_string())
+            limiter.consume(payload.len());
+        }
+        if saturated && command_rx.is_empty() {
+            saturated = false;
+            emit_saturation(&app, &connection_id, false, 0);
+        }
+        let span = command_span(&connection_id, &command);
+        match &command {
+            SessionCommand::Peek {
+                topic,
+                id,
+                duration: Some(duration),
+                ..
+            } => schedule_peek_expiry(
+                &app,
+                &connection_id,
+                &peeks,
+                &command_tx,
+                topic.clone(),
+                *id,
+                *duration,
+            ),
+            SessionCommand::Subscribe { topic, .. } => peeks.subscribed(topic),
+            SessionCommand::Unsubscribe { topic } => peeks.unsubscribed(topic),
+            _ => {}
+        }
+        let logged = match &command {
+            SessionCommand::Subscribe { topic, qos, .. } => Some((
+                ConnectionEventKind::Subscribed,
+                format!("{topic} (QoS {qos})"),
+            )),
+            SessionCommand::Peek { topic, qos, .. } => Some((
+                ConnectionEventKind::Subscribed,
+                format!("{topic} (QoS {qos}, peek)"),
+            )),
+            SessionCommand::Unsubscribe { topic } => {
+                Some((ConnectionEventKind::Unsubscribed, topic.clone()))
             }
+            _ => None,
         };
+        // Peeks are subscriptions too, so a refused peek is reported the same way.
+        let ack_kind = match &command {
+            SessionCommand::Subscribe { topic, .. } | SessionCommand::Peek { topic, .. } => {
+                Some((SubscriptionAckKind::Subscribe, topic))
+            }
+            SessionCommand::Unsubscribe { topic } => {
+                Some((SubscriptionAckKind::Unsubscribe, topic))
+            }
+            _ => None,
+        };
+        if let Some((kind, topic)) = ack_kind {
+            subscription_acks.queued(&connection_id, kind, vec![topic.clone()]);
+        }
+        let awaits_ack = ack_kind.is_some();
+        let result: Result<(), String> = async {
+            match (&client, command) {
+                (
+                    ClientKind::V4(c),
+                    SessionCommand::Subscribe { topic, qos, .. }
+                    | SessionCommand::Peek { topic, qos, .. },
+                ) => c
+                    .subscribe(topic, qos_from_u8(qos))
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                (
+                    ClientKind::V5(c),
+                    SessionCommand::Subscribe {
+                        topic,
+                        qos,
+                        options,
+                    },
+                ) => c
+                    .subscribe_many([v5_filter(topic, qos, options)])
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                (ClientKind::V5(c), SessionCommand::Peek { topic, qos, .. }) => c
+                    .subscribe_many([v5_filter(topic, qos, SubscribeOptions::default())])
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                (ClientKind::V4(c), SessionCommand::Unsubscribe { topic }) => c
+                    .unsubscribe(topic)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                (ClientKind::V5(c), SessionCommand::Unsubscribe { topic }) => c
+                    .unsubscribe(topic)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                (
+                    ClientKind::V4(c),
+                    SessionCommand::Publish {
+                        publish_id,
+                        topic,
+                        payload,
+                        qos,
+                        retain,
+                        ..
+                    },
+                ) => {
+                    if let Some(journal) = &journal {
+                        journal.queued(
+                            &connection_id,
+                            publish_id,
+                            &topic,
+                            &payload,
+                            qos,
+                            retain,
+                            &[],
+                        );
+                    }
+                    let bytes = plugins.encode(&topic, payload).map_err(|e| {
+                        if let Some(journal) = &journal {
+                            journal.rejected(&connection_id, qos);
+                        }
+                        format!("{e:#}")
+                    })?;
+                    qos2.queued(&connection_id, &topic, qos);
+                    publish_acks.queued(&connection_id, publish_id, &topic, qos);
+                    echo_latency.sent(&connection_id, &topic, None, &bytes);
+                    c.publish_bytes(topic, qos_from_u8(qos), retain, bytes)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| {
+                            qos2.rejected(&connection_id);
+                            publish_acks.rejected(&connection_id);
+                            if let Some(journal) = &journal {
+                                journal.rejected(&connection_id, qos);
+                            }
+                            e.to_string()
+                        })
+                }
+                (
+                    ClientKind::V5(c),
+                    SessionCommand::Publish {
+                        publish_id,
+                        topic,
+                        payload,
+                        qos,
+                        retain,
+                        user_properties,
+                        response,
+                    },
+                ) => {
+                    if let Some(journal) = &journal {
+                        journal.queued(
+                            &connection_id,
+                            publish_id,
+                            &topic,
+                            &payload,
+                            qos,
+                            retain,
+                            &user_properties,
+                        );
+                    }
+                    let bytes = plugins.encode(&topic, payload).map_err(|e| {
+                        if let Some(journal) = &journal {
+                            journal.rejected(&connection_id, qos);
+                        }
+                        format!("{e:#}")
+                    })?;
+                    qos2.queued(&connection_id, &topic, qos);
+                    publish_acks.queued(&connection_id, publish_id, &topic, qos);
+                    echo_latency.sent(
+                        &connection_id,
+                        &topic,
+                        response
+                            .as_ref()
+                            .map(|route| route.correlation_data.as_ref()),
+                        &bytes,
+                    );
+                    let (response_topic, correlation_data) = match response {
+                        Some(route) => (Some(route.topic), Some(route.correlation_data)),
+                        None => (None, None),
+                    };
+                    let properties = PublishProperties {
+                        user_properties: user_properties
+                            .into_iter()
+                            .map(|property| (property.key, property.value))
+                            .collect(),
+                        response_topic,
+                        correlation_data,
+                        ..PublishProperties::default()
+                    };
+                    c.publish_bytes_with_properties(
+                        topic,
+                        qos_from_u8_v5(qos),
+                        retain,
+                        bytes,
+                        properties,
+                    )
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| {
+                        qos2.rejected(&connection_id);
+                        publish_acks.rejected(&connection_id);
+                        if let Some(journal) = &journal {
+                            journal.rejected(&connection_id, qos);
+                        }
+                        e.to_string()
+                    })
+                }
+                (ClientKind::V4(c), SessionCommand::Disconnect) => {
+                    c.disconnect().await.map(|_| ()).map_err(|e| e.to_string())
+                }
+                (ClientKind::V5(c), SessionCommand::Disconnect) => {
+                    c.disconnect().await.map(|_| ()).map_err(|e| e.to_string())
+                }
+            }
+        }
+        .instrument(span)
+        .await;
+
+        if awaits_ack && result.is_err() {
+            subscription_acks.rejected(&connection_id);
+        }
+        if let (Ok(()), Some((kind, detail))) = (&result, logged) {
+            record_event(&app, &connection_id, kind, Some(detail));
+        }
 
         if let Err(error) = result {
             emit_status(
@@ -292,6 +1181,8 @@ async fn run_command_loop(
                     connection_id: connection_id.clone(),
                     status: ConnectionStatus::Error,
                     last_error: Some(error.to_string()),
+                    session: None,
+                    redirect: None,
                 },
             );
         }
@@ -303,6 +1194,8 @@ async fn run_command_loop(
                     connection_id: connection_id.clone(),
                     status: ConnectionStatus::Disconnected,
                     last_error: None,
+                    session: None,
+                    redirect: None,
                 },
             );
             break;
@@ -310,28 +1203,277 @@ async fn run_command_loop(
     }
 }
 
+/// Correlation data as a lookup key: the text itself when it is UTF-8, else `0x`-prefixed hex.
+pub(crate) fn correlation_key(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => {
+            let hex = data
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>();
+            format!("0x{hex}")
+        }
+    }
+}
+
+fn emit_saturation(app: &AppHandle, connection_id: &str, saturated: bool, queued: usize) {
+    windows::emit_for_connection(
+        app,
+        "mqtt-publish-saturation",
+        connection_id,
+        PublishSaturationPayload {
+            connection_id: connection_id.to_string(),
+            saturated,
+            queued,
+        },
+    );
+}
+
+fn v5_filter(topic: String, qos: u8, options: SubscribeOptions) -> Filter {
+    Filter {
+        path: topic,
+        qos: qos_from_u8_v5(qos),
+        nolocal: options.no_local,
+        preserve_retain: options.retain_as_published,
+        retain_forward_rule: match options.retain_handling {
+            RetainHandling::OnSubscribe => RetainForwardRule::OnEverySubscribe,
+            RetainHandling::OnNewSubscribe => RetainForwardRule::OnNewSubscribe,
+            RetainHandling::Never => RetainForwardRule::Never,
+        },
+    }
+}
+
+/// Republishes what the previous session left unacknowledged, oldest first.
+fn replay_journal(
+    command_tx: &mpsc::UnboundedSender<SessionCommand>,
+    journal: Option<&PublishJournal>,
+    connection_id: &str,
+) {
+    let Some(journal) = journal else {
+        return;
+    };
+    let replay = journal.take_replay(connection_id);
+    if !replay.is_empty() {
+        tracing::info!(
+            connection_id,
+            count = replay.len(),
+            "republishing unacknowledged publishes"
+        );
+    }
+    for entry in replay {
+        let payload = if entry.is_binary {
+            match Payload::from_base64(&entry.payload) {
+                Ok(payload) => payload,
+                Err(error) => {
+                    tracing::warn!(
+                        connection_id,
+                        topic = entry.topic,
+                        "dropping journaled publish: {error}"
+                    );
+                    continue;
+                }
+            }
+        } else {
+            Payload::from(entry.payload)
+        };
+        let _ = command_tx.send(SessionCommand::Publish {
+            publish_id: entry.publish_id,
+            topic: entry.topic,
+            payload,
+            qos: entry.qos,
+            retain: entry.retain,
+            user_properties: entry.user_properties,
+            response: None,
+        });
+    }
+}
+
+/// Unsubscribes the peeks that `topic` brought to their message limit.
+fn end_peeks(
+    app: &AppHandle,
+    connection_id: &str,
+    peeks: &PeekSubscriptions,
+    command_tx: &mpsc::UnboundedSender<SessionCommand>,
+    topic: &str,
+) {
+    for (filter, received) in peeks.observe(topic) {
+        let _ = command_tx.send(SessionCommand::Unsubscribe {
+            topic: filter.clone(),
+        });
+        peek::emit_ended(app, connection_id, filter, received, PeekEndReason::Count);
+    }
+}
+
+/// Disconnects the session once it saw no activity for `minutes`, the way the tray's
+/// disconnect item does.
+fn spawn_idle_watchdog(
+    app: &AppHandle,
+    connection_id: &str,
+    idle: Arc<IdleTracker>,
+    minutes: u32,
+) -> JoinHandle<()> {
+    let app = app.clone();
+    let connection_id = connection_id.to_string();
+    let timeout = Duration::from_secs(u64::from(minutes) * 60);
+    tokio::spawn(async move {
+        loop {
+            let idle_for = idle.idle_for();
+            if idle_for < timeout {
+                time::sleep(timeout - idle_for).await;
+                continue;
+            }
+            tracing::info!(connection_id, minutes, "disconnecting idle session");
+            emit_status(
+                &app,
+                MqttStatusPayload {
+                    connection_id: connection_id.clone(),
+                    status: ConnectionStatus::Disconnected,
+                    last_error: Some(format!("Disconnected after {minutes} idle minutes")),
+                    session: None,
+                    redirect: None,
+                },
+            );
+            let state = app.state::<crate::state::AppState>();
+            state.sys_stats.stop(&connection_id);
+            // Shutting the session down aborts this task, so nothing may follow.
+            let _ = state.mqtt_manager.disconnect(&connection_id);
+            return;
+        }
+    })
+}
+
+fn schedule_peek_expiry(
+    app: &AppHandle,
+    connection_id: &str,
+    peeks: &Arc<PeekSubscriptions>,
+    command_tx: &mpsc::UnboundedSender<SessionCommand>,
+    topic: String,
+    id: u64,
+    duration: Duration,
+) {
+    let app = app.clone();
+    let connection_id = connection_id.to_string();
+    let peeks = Arc::clone(peeks);
+    let command_tx = command_tx.clone();
+    tokio::spawn(async move {
+        time::sleep(duration).await;
+        let Some(received) = peeks.expire(&topic, id) else {
+            return;
+        };
+        if command_tx
+            .send(SessionCommand::Unsubscribe {
+                topic: topic.clone(),
+            })
+            .is_ok()
+        {
+            peek::emit_ended(
+                &app,
+                &connection_id,
+                topic,
+                received,
+                PeekEndReason::Timeout,
+            );
+        }
+    });
+}
+
+/// Probes the transport details of the connection that just came up in the background.
+fn probe_endpoint(
+    endpoint: &Arc<Mutex<Option<ConnectionInfo>>>,
+    connection_id: &str,
+    cfg: &ResolvedConnection,
+) {
+    let endpoint = Arc::clone(endpoint);
+    let connection_id = connection_id.to_string();
+    let cfg = cfg.clone();
+    tokio::spawn(async move {
+        match tokio::task::spawn_blocking(move || endpoint::probe(&connection_id, &cfg)).await {
+            Ok(info) => {
+                *endpoint
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(info);
+            }
+            Err(error) => tracing::warn!("transport probe failed: {error}"),
+        }
+    });
+}
+
+fn clear_endpoint(endpoint: &Mutex<Option<ConnectionInfo>>) {
+    *endpoint
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Queues the profile's default subscriptions; called on every CONNACK so they survive
+/// reconnects of clean sessions.
+fn subscribe_presets(
+    command_tx: &mpsc::UnboundedSender<SessionCommand>,
+    presets: &[SubscriptionPreset],
+) {
+    for preset in presets {
+        let _ = command_tx.send(SessionCommand::Subscribe {
+            topic: preset.topic.clone(),
+            qos: preset.qos,
+            options: preset.options,
+        });
+    }
+}
+
+fn command_span(connection_id: &str, command: &SessionCommand) -> tracing::Span {
+    match command {
+        SessionCommand::Subscribe { topic, qos, .. } => {
+            tracing::info_span!("mqtt.subscribe", connection_id, topic = topic.as_str(), qos)
+        }
+        SessionCommand::Unsubscribe { topic } => {
+            tracing::info_span!("mqtt.unsubscribe", connection_id, topic = topic.as_str())
+        }
+        SessionCommand::Peek { topic, qos, .. } => {
+            tracing::info_span!("mqtt.peek", connection_id, topic = topic.as_str(), qos)
+        }
+        SessionCommand::Publish {
+            topic,
+            payload,
+            qos,
+            retain,
+            ..
+        } => tracing::info_span!(
+            "mqtt.publish",
+            connection_id,
+            topic = topic.as_str(),
+            qos,
+            retain,
+            bytes = payload.len()
+        ),
+        SessionCommand::Disconnect => tracing::info_span!("mqtt.disconnect", connection_id),
+    }
+}
+
 async fn run_batch_emitter(
     app: AppHandle,
     connection_id: String,
     mut message_rx: mpsc::UnboundedReceiver<MqttBatchItem>,
+    gauge: Arc<QueueGauge>,
+    flow_window: Arc<FlowWindow>,
 ) {
     let mut interval = time::interval(Duration::from_millis(BATCH_FLUSH_MS));
     interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
-    let mut buffer: Vec<MqttBatchItem> = Vec::with_capacity(BATCH_MAX);
+    let mut buffer: Vec<MqttBatchItem> = Vec::with_capacity(flow::MIN_BATCH);
 
     loop {
         tokio::select! {
             maybe_msg = message_rx.recv() => {
                 match maybe_msg {
                     Some(msg) => {
+                        gauge.release(&msg);
                         buffer.push(msg);
-                        if buffer.len() >= BATCH_MAX {
-                            flush_batch(&app, &connection_id, &mut buffer).await;
+                        if buffer.len() >= flow_window.batch_limit() {
+                            flush_batch(&app, &connection_id, &mut buffer, &flow_window).await;
                         }
                     }
                     None => {
                         if !buffer.is_empty() {
-                            flush_batch(&app, &connection_id, &mut buffer).await;
+                            flush_batch(&app, &connection_id, &mut buffer, &flow_window).await;
                         }
                         break;
                     }
@@ -339,24 +1481,34 @@ async fn run_batch_emitter(
             }
             _ = interval.tick() => {
                 if !buffer.is_empty() {
-                    flush_batch(&app, &connection_id, &mut buffer).await;
+                    flush_batch(&app, &connection_id, &mut buffer, &flow_window).await;
                 }
             }
         }
     }
 }
 
-async fn flush_batch(app: &AppHandle, connection_id: &str, buffer: &mut Vec<MqttBatchItem>) {
+#[tracing::instrument(skip(app, buffer, flow_window), fields(count = buffer.len()))]
+async fn flush_batch(
+    app: &AppHandle,
+    connection_id: &str,
+    buffer: &mut Vec<MqttBatchItem>,
+    flow_window: &FlowWindow,
+) {
     let batch = std::mem::take(buffer);
 
     if batch.is_empty() {
         return;
     }
 
-    let history_manager = app
-        .state::<crate::state::AppState>()
-        .history_manager
-        .clone();
+    let state = app.state::<crate::state::AppState>();
+    let history_manager = state.history_manager.clone();
+    let rule_manager = state.rule_manager.clone();
+    state.kafka_bridges.dispatch(connection_id, &batch);
+    state.socket_taps.dispatch(connection_id, &batch);
+    state.topic_discovery.observe(connection_id, &batch);
+    state.sys_stats.observe(connection_id, &batch);
+    state.recent.record(connection_id, &batch);
     if let Err(error) = history_manager
         .append_batch(app, connection_id, &batch)
         .await
@@ -367,23 +1519,300 @@ async fn flush_batch(app: &AppHandle, connection_id: &str, buffer: &mut Vec<Mqtt
                 connection_id: connection_id.to_string(),
                 status: ConnectionStatus::Error,
                 last_error: Some(format!("failed to persist history: {error}")),
+                session: None,
+                redirect: None,
             },
         );
     }
 
+    rule_manager.process_batch(app, connection_id, &batch).await;
+
+    let seq = flow_window.next_seq();
+    let summarize = flow_window.should_summarize();
+    let summary = summarize.then(|| flow::summarize(connection_id, seq, &batch));
     let payload = MqttMessageBatchPayload {
         connection_id: connection_id.to_string(),
+        seq,
         messages: batch,
     };
 
-    let _ = app.emit("mqtt-message-batch", payload);
+    // The WebSocket feed has its own backpressure, so it always receives the full batch.
+    state.feed.publish_batch(&payload);
+    match summary {
+        Some(summary) => {
+            windows::emit_for_connection(app, "mqtt-message-summary", connection_id, summary)
+        }
+        None => windows::emit_for_connection(app, "mqtt-message-batch", connection_id, payload),
+    }
+}
+
+/// Persists a lifecycle event in the connection's history database without blocking the caller.
+pub(crate) fn record_event(
+    app: &AppHandle,
+    connection_id: &str,
+    kind: ConnectionEventKind,
+    detail: Option<String>,
+) {
+    let app = app.clone();
+    let connection_id = connection_id.to_string();
+    tokio::spawn(async move {
+        let history_manager = app
+            .state::<crate::state::AppState>()
+            .history_manager
+            .clone();
+        if let Err(error) = history_manager
+            .append_event(&app, &connection_id, kind, detail)
+            .await
+        {
+            tracing::warn!("failed to record connection event for {connection_id}: {error}");
+        }
+    });
+}
+
+/// Client identity used to recognise client id takeovers, and what to do after one.
+struct Takeover {
+    client_id: String,
+    broker_key: String,
+    reconnect: bool,
+}
+
+impl Takeover {
+    /// Whether another session in this app now holds the same client id on the same broker.
+    fn by_local_session(&self, app: &AppHandle, connection_id: &str) -> bool {
+        !self.client_id.is_empty()
+            && app
+                .state::<crate::state::AppState>()
+                .mqtt_manager
+                .session_using(&self.client_id, &self.broker_key, connection_id)
+                .is_some()
+    }
+}
+
+/// Reports a failed poll. Returns true when polling should continue so rumqttc reconnects,
+/// which only happens after a takeover on profiles that opted into reconnecting.
+async fn report_poll_error(
+    app: &AppHandle,
+    connection_id: &str,
+    drop_notice: Option<&DropNotice>,
+    takeover: &Takeover,
+    taken_over: bool,
+    error: String,
+) -> bool {
+    let (status, reason) = if taken_over {
+        (
+            ConnectionStatus::TakenOver,
+            format!(
+                "session taken over by another client using client id '{}'",
+                takeover.client_id
+            ),
+        )
+    } else {
+        (ConnectionStatus::Error, error)
+    };
+    if let Some(notice) = drop_notice {
+        notice.show(app, &reason);
+    }
+    emit_status(
+        app,
+        MqttStatusPayload {
+            connection_id: connection_id.to_string(),
+            status,
+            last_error: Some(reason),
+            session: None,
+            redirect: None,
+        },
+    );
+
+    if taken_over && takeover.reconnect {
+        time::sleep(Duration::from_millis(TAKEOVER_RETRY_MS)).await;
+        return true;
+    }
+    false
+}
+
+fn queued_disconnect(
+    events: &std::collections::VecDeque<rumqttc::v5::Event>,
+) -> Option<&rumqttc::v5::mqttbytes::v5::Disconnect> {
+    events.iter().rev().find_map(|event| match event {
+        rumqttc::v5::Event::Incoming(rumqttc::v5::Incoming::Disconnect(disconnect)) => {
+            Some(disconnect)
+        }
+        _ => None,
+    })
+}
+
+/// Reads the server reference of a "Use another server" / "Server moved" DISCONNECT.
+fn server_redirect(disconnect: &rumqttc::v5::mqttbytes::v5::Disconnect) -> Option<ServerRedirect> {
+    let permanent = match disconnect.reason_code {
+        DisconnectReasonCode::UseAnotherServer => false,
+        DisconnectReasonCode::ServerMoved => true,
+        _ => return None,
+    };
+    let reference = disconnect.properties.as_ref()?.server_reference.clone()?;
+    let (host, port) = parse_server_reference(&reference)?;
+    Some(ServerRedirect {
+        reference,
+        host,
+        port,
+        permanent,
+        following: false,
+    })
+}
+
+/// Host and optional port of the first entry in a server reference. The spec leaves the format
+/// open; brokers send `host`, `host:port` or `[v6]:port`, sometimes with a URL scheme.
+fn parse_server_reference(reference: &str) -> Option<(String, Option<u16>)> {
+    let first = reference.split_whitespace().next()?;
+    let first = first.split_once("://").map_or(first, |(_, rest)| rest);
+    let authority = first.split('/').next().unwrap_or_default();
+
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, tail) = rest.split_once(']')?;
+        match tail.strip_prefix(':') {
+            Some(port) => (host, Some(port.parse().ok()?)),
+            None if tail.is_empty() => (host, None),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') => (host, Some(port.parse().ok()?)),
+            // A bare IPv6 address.
+            _ => (authority, None),
+        }
+    };
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+/// Starts a new session against the referenced server when the profile follows redirects;
+/// otherwise reports the redirect as the reason the connection ended.
+fn handle_redirect(
+    app: &AppHandle,
+    cfg: &ResolvedConnection,
+    drop_notice: Option<&DropNotice>,
+    closing: &AtomicBool,
+    mut redirect: ServerRedirect,
+) {
+    let target = match redirect.port {
+        Some(port) => format!("{}:{port}", redirect.host),
+        None => redirect.host.clone(),
+    };
+    let moved = if redirect.permanent {
+        "moved this connection"
+    } else {
+        "asked this connection to move"
+    };
+
+    if closing.load(Ordering::SeqCst) {
+        return;
+    }
+    if cfg.follow_server_redirects && cfg.redirect_hops < MAX_REDIRECT_HOPS {
+        redirect.following = true;
+        tracing::info!(connection_id = %cfg.id, "following broker redirect to {target}");
+        let mut next = cfg.clone();
+        next.host = redirect.host.clone();
+        if let Some(port) = redirect.port {
+            next.port = port;
+        }
+        next.redirect_hops += 1;
+        emit_status(
+            app,
+            MqttStatusPayload {
+                connection_id: cfg.id.clone(),
+                status: ConnectionStatus::Connecting,
+                last_error: None,
+                session: None,
+                redirect: Some(redirect),
+            },
+        );
+
+        let app = app.clone();
+        tokio::spawn(async move {
+            let connection_id = next.id.clone();
+            if let Err(error) = app
+                .state::<crate::state::AppState>()
+                .mqtt_manager
+                .connect(app.clone(), next)
+            {
+                tracing::warn!("failed to follow redirect for {connection_id}: {error}");
+            }
+        });
+        return;
+    }
+
+    let reason = if cfg.follow_server_redirects {
+        format!("broker {moved} to {target}; stopped after {MAX_REDIRECT_HOPS} redirects")
+    } else {
+        format!("broker {moved} to {target}")
+    };
+    if let Some(notice) = drop_notice {
+        notice.show(app, &reason);
+    }
+    emit_status(
+        app,
+        MqttStatusPayload {
+            connection_id: cfg.id.clone(),
+            status: ConnectionStatus::Error,
+            last_error: Some(reason),
+            session: None,
+            redirect: Some(redirect),
+        },
+    );
+}
+
+/// OS notification for connections that drop without a user-initiated disconnect.
+#[derive(Clone)]
+struct DropNotice {
+    name: String,
+    closing: Arc<AtomicBool>,
+}
+
+impl DropNotice {
+    fn new(name: &str, closing: Arc<AtomicBool>) -> Self {
+        Self {
+            name: name.to_string(),
+            closing,
+        }
+    }
+
+    fn show(&self, app: &AppHandle, reason: &str) {
+        if self.closing.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Err(error) = app
+            .notification()
+            .builder()
+            .title(format!("{} disconnected", self.name))
+            .body(reason)
+            .show()
+        {
+            tracing::warn!("failed to show disconnect notification: {error}");
+        }
+    }
 }
 
 fn emit_status(app: &AppHandle, payload: MqttStatusPayload) {
-    let _ = app.emit("mqtt-status", payload);
+    let kind = match payload.status {
+        ConnectionStatus::Connecting => ConnectionEventKind::Connecting,
+        ConnectionStatus::Connected => ConnectionEventKind::Connected,
+        ConnectionStatus::Disconnected => ConnectionEventKind::Disconnected,
+        ConnectionStatus::Error => ConnectionEventKind::Error,
+        ConnectionStatus::TakenOver => ConnectionEventKind::TakenOver,
+    };
+    record_event(
+        app,
+        &payload.connection_id,
+        kind,
+        payload.last_error.clone(),
+    );
+    crate::tray::on_status(app, &payload);
+    app.state::<crate::state::AppState>()
+        .feed
+        .publish_status(&payload);
+    let connection_id = payload.connection_id.clone();
+    windows::emit_for_connection(app, "mqtt-status", &connection_id, payload);
 }
 
-fn build_ws_broker_url(cfg: &ResolvedConnection, secure: bool) -> String {
+pub fn build_ws_broker_url(cfg: &ResolvedConnection, secure: bool) -> String {
     let host_input = cfg.host.trim();
 
     if host_input.starts_with("ws://") || host_input.starts_with("wss://") {
@@ -401,7 +1830,7 @@ fn build_ws_broker_url(cfg: &ResolvedConnection, secure: bool) -> String {
     format!("{scheme}://{host_input}:{}{path}", cfg.port)
 }
 
-fn build_v4_options(cfg: &ResolvedConnection) -> MqttOptions {
+pub fn build_v4_options(cfg: &ResolvedConnection) -> MqttOptions {
     let broker = match cfg.protocol {
         TransportProtocol::Ws => build_ws_broker_url(cfg, false),
         TransportProtocol::Wss => build_ws_broker_url(cfg, true),
@@ -409,7 +1838,7 @@ fn build_v4_options(cfg: &ResolvedConnection) -> MqttOptions {
     };
 
     let mut options = MqttOptions::new(cfg.client_id.clone(), broker, cfg.port);
-    options.set_keep_alive(Duration::from_secs(30));
+    options.set_keep_alive(Duration::from_secs(u64::from(KEEP_ALIVE_SECS)));
     options.set_clean_session(cfg.clean);
 
     if let Some(username) = &cfg.username {
@@ -421,19 +1850,48 @@ fn build_v4_options(cfg: &ResolvedConnection) -> MqttOptions {
             options.set_transport(Transport::tcp());
         }
         TransportProtocol::Mqtts => {
-            options.set_transport(Transport::tls_with_default_config());
+            options.set_transport(match &cfg.tls_config {
+                Some(config) => {
+                    Transport::tls_with_config(TlsConfiguration::Rustls(Arc::clone(config)))
+                }
+                None => Transport::tls_with_default_config(),
+            });
         }
         TransportProtocol::Ws => {
             options.set_transport(Transport::ws());
         }
         TransportProtocol::Wss => {
-            options.set_transport(Transport::wss_with_default_config());
+            options.set_transport(match &cfg.tls_config {
+                Some(config) => {
+                    Transport::wss_with_config(TlsConfiguration::Rustls(Arc::clone(config)))
+                }
+                None => Transport::wss_with_default_config(),
+            });
         }
     }
 
     options
 }
 
+/// Asks the next CONNECT for the configured keep-alive; rumqttc keeps the last CONNACK's
+/// server keep-alive in the options otherwise.
+fn reset_keep_alive(options: &mut rumqttc::v5::MqttOptions) {
+    options.set_keep_alive(Duration::from_secs(u64::from(KEEP_ALIVE_SECS)));
+}
+
+/// The keep-alive rumqttc pings on after a CONNACK, which has already applied the server's
+/// value. 0 would fire its ping timer back to back, so pings are pushed out instead.
+fn connack_keep_alive(
+    options: &mut rumqttc::v5::MqttOptions,
+    server_keep_alive: Option<u16>,
+) -> u16 {
+    if server_keep_alive == Some(0) {
+        options.set_keep_alive(Duration::from_secs(u64::from(u16::MAX)));
+        return 0;
+    }
+    u16::try_from(options.keep_alive().as_secs()).unwrap_or(u16::MAX)
+}
+
 fn build_v5_options(cfg: &ResolvedConnection) -> rumqttc::v5::MqttOptions {
     let broker = match cfg.protocol {
         TransportProtocol::Ws => build_ws_broker_url(cfg, false),
@@ -442,7 +1900,7 @@ fn build_v5_options(cfg: &ResolvedConnection) -> rumqttc::v5::MqttOptions {
     };
 
     let mut options = rumqttc::v5::MqttOptions::new(cfg.client_id.clone(), broker, cfg.port);
-    options.set_keep_alive(Duration::from_secs(30));
+    options.set_keep_alive(Duration::from_secs(u64::from(KEEP_ALIVE_SECS)));
     options.set_clean_start(cfg.clean);
 
     if let Some(username) = &cfg.username {
@@ -454,15 +1912,58 @@ fn build_v5_options(cfg: &ResolvedConnection) -> rumqttc::v5::MqttOptions {
             options.set_transport(rumqttc::Transport::tcp());
         }
         TransportProtocol::Mqtts => {
-            options.set_transport(rumqttc::Transport::tls_with_default_config());
+            options.set_transport(match &cfg.tls_config {
+                Some(config) => rumqttc::Transport::tls_with_config(TlsConfiguration::Rustls(
+                    Arc::clone(config),
+                )),
+                None => rumqttc::Transport::tls_with_default_config(),
+            });
         }
         TransportProtocol::Ws => {
             options.set_transport(rumqttc::Transport::ws());
         }
         TransportProtocol::Wss => {
-            options.set_transport(rumqttc::Transport::wss_with_default_config());
+            options.set_transport(match &cfg.tls_config {
+                Some(config) => rumqttc::Transport::wss_with_config(TlsConfiguration::Rustls(
+                    Arc::clone(config),
+                )),
+                None => rumqttc::Transport::wss_with_default_config(),
+            });
         }
     }
 
     options
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> rumqttc::v5::MqttOptions {
+        let mut options = rumqttc::v5::MqttOptions::new("client", "localhost", 1883);
+        options.set_keep_alive(Duration::from_secs(u64::from(KEEP_ALIVE_SECS)));
+        options
+    }
+
+    #[test]
+    fn reconnect_without_override_uses_configured_keep_alive() {
+        let mut options = options();
+        // rumqttc stores the server keep-alive of the first CONNACK in the options.
+        options.set_keep_alive(Duration::from_secs(60));
+        assert_eq!(connack_keep_alive(&mut options, Some(60)), 60);
+
+        reset_keep_alive(&mut options);
+        assert_eq!(options.keep_alive().as_secs(), u64::from(KEEP_ALIVE_SECS));
+        assert_eq!(connack_keep_alive(&mut options, None), KEEP_ALIVE_SECS);
+    }
+
+    #[test]
+    fn disabled_keep_alive_is_not_requested_on_reconnect() {
+        let mut options = options();
+        assert_eq!(connack_keep_alive(&mut options, Some(0)), 0);
+        assert_eq!(options.keep_alive().as_secs(), u64::from(u16::MAX));
+
+        reset_keep_alive(&mut options);
+        assert_eq!(options.keep_alive().as_secs(), u64::from(KEEP_ALIVE_SECS));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::models::{SubscriptionAckKind, SubscriptionResultPayload};
+use crate::windows;
+use dashmap::DashMap;
+use rumqttc::SubscribeReasonCode;
+use rumqttc::v5::mqttbytes::v5::{SubscribeReasonCode as SubscribeReasonCodeV5, UnsubAckReason};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+/// Delivers the broker's SUBACK / UNSUBACK result for each topic filter, so a subscription the
+/// broker refused no longer looks like it succeeded.
+///
+/// Requests are matched to `Outgoing::Subscribe` / `Outgoing::Unsubscribe` events in order and
+/// then to acknowledgements by packet id, like [`super::ack::PublishAcks`].
+#[derive(Clone, Default)]
+pub struct SubscriptionAcks {
+    connections: Arc<DashMap<String, Arc<Mutex<SubAckState>>>>,
+}
+
+#[derive(Default)]
+struct SubAckState {
+    queued: VecDeque<QueuedRequest>,
+    /// Written requests awaiting their acknowledgement, by packet id.
+    in_flight: HashMap<u16, QueuedRequest>,
+}
+
+struct QueuedRequest {
+    kind: SubscriptionAckKind,
+    topics: Vec<String>,
+}
+
+/// Per-filter outcome reported by the broker: a reason code and, for subscriptions, the
+/// granted QoS.
+pub type FilterResult = (u8, Option<u8>);
+
+impl SubscriptionAcks {
+    /// Called by the command loop just before a subscribe or unsubscribe is handed to rumqttc.
+    pub fn queued(&self, connection_id: &str, kind: SubscriptionAckKind, topics: Vec<String>) {
+        lock(&self.state(connection_id))
+            .queued
+            .push_back(QueuedRequest { kind, topics });
+    }
+
+    /// Undoes [`Self::queued`] when rumqttc rejected the request.
+    pub fn rejected(&self, connection_id: &str) {
+        if let Some(state) = self.connections.get(connection_id) {
+            lock(&state).queued.pop_back();
+        }
+    }
+
+    pub fn sent(&self, connection_id: &str, packet_id: u16) {
+        let Some(state) = self.connections.get(connection_id) else {
+            return;
+        };
+        let mut state = lock(&state);
+        if state.in_flight.contains_key(&packet_id) {
+            return;
+        }
+        if let Some(request) = state.queued.pop_front() {
+            state.in_flight.insert(packet_id, request);
+        }
+    }
+
+    /// Emits one `mqtt-subscription-result` per topic filter of the request that used
+    /// `packet_id`. MQTT 3.1.1 UNSUBACKs carry no codes, so an empty `results` means success.
+    pub fn acked(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        packet_id: u16,
+        results: &[FilterResult],
+        reason_string: Option<String>,
+    ) {
+        let Some(request) = self
+            .connections
+            .get(connection_id)
+            .and_then(|state| lock(&state).in_flight.remove(&packet_id))
+        else {
+            return;
+        };
+        for (index, topic) in request.topics.into_iter().enumerate() {
+            let (reason_code, granted_qos) = results.get(index).copied().unwrap_or((0x00, None));
+            let success = reason_code < 0x80;
+            let reason = reason_name(request.kind, reason_code);
+            if !success {
+                tracing::warn!(
+                    connection_id,
+                    topic = topic.as_str(),
+                    reason,
+                    "broker rejected {}",
+                    match request.kind {
+                        SubscriptionAckKind::Subscribe => "subscription",
+                        SubscriptionAckKind::Unsubscribe => "unsubscribe",
+                    }
+                );
+            }
+            windows::emit_for_connection(
+                app,
+                "mqtt-subscription-result",
+                connection_id,
+                SubscriptionResultPayload {
+                    connection_id: connection_id.to_string(),
+                    kind: request.kind,
+                    topic,
+                    packet_id,
+                    success,
+                    granted_qos,
+                    reason_code,
+                    reason: reason.to_string(),
+                    reason_string: reason_string.clone(),
+                },
+            );
+        }
+    }
+
+    /// Drops everything tracked for a session that is going away.
+    pub fn remove(&self, connection_id: &str) {
+        self.connections.remove(connection_id);
+    }
+
+    fn state(&self, connection_id: &str) -> Arc<Mutex<SubAckState>> {
+        Arc::clone(
+            self.connections
+                .entry(connection_id.to_string())
+                .or_default()
+                .value(),
+        )
+    }
+}
+
+pub fn suback_v4(code: &SubscribeReasonCode) -> FilterResult {
+    match code {
+        SubscribeReasonCode::Success(qos) => (*qos as u8, Some(*qos as u8)),
+        SubscribeReasonCode::Failure => (0x80, None),
+    }
+}
+
+pub fn suback_v5(code: &SubscribeReasonCodeV5) -> FilterResult {
+    let code = match code {
+        SubscribeReasonCodeV5::Success(qos) => return (*qos as u8, Some(*qos as u8)),
+        SubscribeReasonCodeV5::Failure | SubscribeReasonCodeV5::Unspecified => 0x80,
+        SubscribeReasonCodeV5::ImplementationSpecific => 0x83,
+        SubscribeReasonCodeV5::NotAuthorized => 0x87,
+        SubscribeReasonCodeV5::TopicFilterInvalid => 0x8F,
+        SubscribeReasonCodeV5::PkidInUse => 0x91,
+        SubscribeReasonCodeV5::QuotaExceeded => 0x97,
+        SubscribeReasonCodeV5::SharedSubscriptionsNotSupported => 0x9E,
+        SubscribeReasonCodeV5::SubscriptionIdNotSupported => 0xA1,
+        SubscribeReasonCodeV5::WildcardSubscriptionsNotSupported => 0xA2,
+    };
+    (code, None)
+}
+
+pub fn unsuback_v5(reason: &UnsubAckReason) -> FilterResult {
+    let code = match reason {
+        UnsubAckReason::Success => 0x00,
+        UnsubAckReason::NoSubscriptionExisted => 0x11,
+        UnsubAckReason::UnspecifiedError => 0x80,
+        UnsubAckReason::ImplementationSpecificError => 0x83,
+        UnsubAckReason::NotAuthorized => 0x87,
+        UnsubAckReason::TopicFilterInvalid => 0x8F,
+        UnsubAckReason::PacketIdentifierInUse => 0x91,
+    };
+    (code, None)
+}
+
+/// SUBACK success codes double as the granted QoS, so they are named per request kind.
+fn reason_name(kind: SubscriptionAckKind, code: u8) -> &'static str {
+    match (kind, code) {
+        (SubscriptionAckKind::Subscribe, 0x00) => "Granted QoS 0",
+        (SubscriptionAckKind::Subscribe, 0x01) => "Granted QoS 1",
+        (SubscriptionAckKind::Subscribe, 0x02) => "Granted QoS 2",
+        (SubscriptionAckKind::Unsubscribe, 0x00) => "Success",
+        (SubscriptionAckKind::Unsubscribe, 0x11) => "No subscription existed",
+        (_, 0x80) => "Unspecified error",
+        (_, 0x83) => "Implementation specific error",
+        (_, 0x87) => "Not authorized",
+        (_, 0x8F) => "Topic filter invalid",
+        (_, 0x91) => "Packet identifier in use",
+        (_, 0x97) => "Quota exceeded",
+        (_, 0x9E) => "Shared subscriptions not supported",
+        (_, 0xA1) => "Subscription identifiers not supported",
+        (_, 0xA2) => "Wildcard subscriptions not supported",
+        _ => "Unknown reason",
+    }
+}
+
+fn lock(state: &Mutex<SubAckState>) -> std::sync::MutexGuard<'_, SubAckState> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,178 @@
+use crate::models::{BrokerSysStats, MqttBatchItem};
+use crate::mqtt::now_millis;
+use crate::windows;
+use dashmap::DashMap;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration};
+
+pub const SYS_TOPIC_FILTER: &str = "$SYS/#";
+const EMIT_INTERVAL_MS: u64 = 5000;
+
+#[derive(Clone, Default)]
+pub struct SysStatsTracker {
+    trackers: Arc<DashMap<String, Tracked>>,
+}
+
+struct Tracked {
+    stats: BrokerSysStats,
+    emit_task: JoinHandle<()>,
+}
+
+impl SysStatsTracker {
+    /// Starts collecting for a connection and emits `broker-sys-stats` whenever values changed.
+    pub fn start(&self, app: AppHandle, connection_id: &str) {
+        if self.trackers.contains_key(connection_id) {
+            return;
+        }
+
+        let trackers = Arc::clone(&self.trackers);
+        let id = connection_id.to_string();
+        let emit_task = tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_millis(EMIT_INTERVAL_MS));
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            let mut last_emitted = 0;
+            loop {
+                interval.tick().await;
+                let Some(stats) = trackers.get(&id).map(|tracked| tracked.stats.clone()) else {
+                    break;
+                };
+                if stats.updated_at > last_emitted {
+                    last_emitted = stats.updated_at;
+                    windows::emit_for_connection(&app, "broker-sys-stats", &id, stats);
+                }
+            }
+        });
+
+        self.trackers.insert(
+            connection_id.to_string(),
+            Tracked {
+                stats: BrokerSysStats {
+                    connection_id: connection_id.to_string(),
+                    ..BrokerSysStats::default()
+                },
+                emit_task,
+            },
+        );
+    }
+
+    pub fn stop(&self, connection_id: &str) {
+        if let Some((_, tracked)) = self.trackers.remove(connection_id) {
+            tracked.emit_task.abort();
+        }
+    }
+
+    pub fn is_tracking(&self, connection_id: &str) -> bool {
+        self.trackers.contains_key(connection_id)
+    }
+
+    pub fn stats(&self, connection_id: &str) -> Option<BrokerSysStats> {
+        self.trackers
+            .get(connection_id)
+            .map(|tracked| tracked.stats.clone())
+    }
+
+    pub fn observe(&self, connection_id: &str, batch: &[MqttBatchItem]) {
+        let Some(mut tracked) = self.trackers.get_mut(connection_id) else {
+            return;
+        };
+        let mut changed = false;
+        for item in batch {
+            if item.topic.starts_with("$SYS/") {
+                apply(&mut tracked.stats, &item.topic, item.payload.trim());
+                changed = true;
+            }
+        }
+        if changed {
+            tracked.stats.updated_at = now_millis();
+        }
+    }
+}
+
+/// Maps Mosquitto (`$SYS/broker/...`) and EMQX (`$SYS/brokers/<node>/...`) topics onto one shape.
+fn apply(stats: &mut BrokerSysStats, topic: &str, value: &str) {
+    stats.raw.insert(topic.to_string(), value.to_string());
+
+    let key = if let Some(rest) = topic.strip_prefix("$SYS/broker/") {
+        stats.broker.get_or_insert_with(|| "mosquitto".to_string());
+        rest
+    } else if let Some(rest) = topic.strip_prefix("$SYS/brokers/") {
+        stats.broker.get_or_insert_with(|| "emqx".to_string());
+        match rest.split_once('/') {
+            Some((node, rest)) => {
+                stats.node.get_or_insert_with(|| node.to_string());
+                rest
+            }
+            None => return,
+        }
+    } else {
+        return;
+    };
+
+    match key {
+        "version" => stats.version = Some(value.to_string()),
+        "uptime" => stats.uptime_seconds = parse_uptime(value),
+        "clients/connected" | "clients/active" | "stats/connections/count" => {
+            stats.clients_connected = parse_u64(value)
+        }
+        "clients/total" => stats.clients_total = parse_u64(value),
+        "clients/maximum" | "stats/connections/max" => stats.clients_maximum = parse_u64(value),
+        "messages/received" | "metrics/messages/received" => {
+            stats.messages_received = parse_u64(value)
+        }
+        "messages/sent" | "metrics/messages/sent" => stats.messages_sent = parse_u64(value),
+        "bytes/received" | "metrics/bytes/received" => stats.bytes_received = parse_u64(value),
+        "bytes/sent" | "metrics/bytes/sent" => stats.bytes_sent = parse_u64(value),
+        "retained messages/count" | "stats/retained/count" => {
+            stats.retained_messages = parse_u64(value)
+        }
+        "subscriptions/count" | "stats/subscriptions/count" => {
+            stats.subscriptions = parse_u64(value)
+        }
+        "stats/topics/count" => stats.topics = parse_u64(value),
+        "load/messages/received/1min" => stats.messages_received_per_min = parse_f64(value),
+        "load/messages/sent/1min" => stats.messages_sent_per_min = parse_f64(value),
+        "heap/current" | "heap/current size" => stats.heap_bytes = parse_u64(value),
+        _ => {}
+    }
+}
+
+fn parse_u64(value: &str) -> Option<u64> {
+    value
+        .split_whitespace()
+        .next()
+        .and_then(|number| number.parse().ok())
+}
+
+fn parse_f64(value: &str) -> Option<f64> {
+    value
+        .split_whitespace()
+        .next()
+        .and_then(|number| number.parse().ok())
+}
+
+/// Accepts Mosquitto's `"3600 seconds"` and EMQX's `"1 days, 2 hours, 3 minutes, 4 seconds"`.
+fn parse_uptime(value: &str) -> Option<u64> {
+    let mut total = 0u64;
+    let mut matched = false;
+    for part in value.split(',') {
+        let mut words = part.split_whitespace();
+        let (Some(amount), unit) = (words.next(), words.next()) else {
+            continue;
+        };
+        let Ok(amount) = amount.parse::<u64>() else {
+            continue;
+        };
+        let factor = match unit.unwrap_or("seconds").trim_end_matches('s') {
+            "day" => 86_400,
+            "hour" => 3_600,
+            "minute" => 60,
+            "second" => 1,
+            _ => continue,
+        };
+        total += amount * factor;
+        matched = true;
+    }
+    matched.then_some(total)
+}
@@ -0,0 +1,113 @@
+use crate::config_store;
+use crate::models::ConnectLimitsConfig;
+use anyhow::{Result, anyhow};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{self, Duration, Instant};
+
+pub const CONNECT_LIMITS_FILE_NAME: &str = "connect-limits.json";
+const MAX_STAGGER_MS: u64 = 60_000;
+
+/// Spreads out connection attempts so connecting many profiles at once doesn't run dozens of
+/// TLS handshakes in parallel and trip the broker's connection-rate limits.
+///
+/// A session holds a permit from the moment it starts connecting until the CONNACK or the
+/// failure, and attempts start at least `stagger_ms` apart. A lone connect never waits.
+#[derive(Clone, Default)]
+pub struct ConnectThrottle {
+    inner: Arc<ThrottleInner>,
+}
+
+struct ThrottleInner {
+    /// The limit and its semaphore, replaced when the limit changes; permits of the old
+    /// semaphore drain on their own.
+    permits: Mutex<(usize, Arc<Semaphore>)>,
+    stagger_ms: AtomicU64,
+    next_start: Mutex<Option<Instant>>,
+}
+
+impl Default for ThrottleInner {
+    fn default() -> Self {
+        let defaults = ConnectLimitsConfig::default();
+        Self {
+            permits: Mutex::new((
+                defaults.max_concurrent_connects,
+                Arc::new(Semaphore::new(defaults.max_concurrent_connects)),
+            )),
+            stagger_ms: AtomicU64::new(defaults.stagger_ms),
+            next_start: Mutex::new(None),
+        }
+    }
+}
+
+impl ConnectThrottle {
+    pub fn load_config(&self, app: &AppHandle) -> Result<ConnectLimitsConfig> {
+        let config =
+            config_store::load_document::<ConnectLimitsConfig>(app, CONNECT_LIMITS_FILE_NAME)?;
+        self.apply(&config);
+        Ok(config)
+    }
+
+    pub fn save_config(&self, app: &AppHandle, config: &ConnectLimitsConfig) -> Result<()> {
+        if config.max_concurrent_connects == 0 {
+            return Err(anyhow!(
+                "concurrent connection limit must be greater than zero"
+            ));
+        }
+        if config.stagger_ms > MAX_STAGGER_MS {
+            return Err(anyhow!(
+                "connection stagger must be at most {MAX_STAGGER_MS} ms"
+            ));
+        }
+        config_store::save_document(app, CONNECT_LIMITS_FILE_NAME, config)?;
+        self.apply(config);
+        Ok(())
+    }
+
+    fn apply(&self, config: &ConnectLimitsConfig) {
+        let mut permits = self
+            .inner
+            .permits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let limit = config.max_concurrent_connects.max(1);
+        if permits.0 != limit {
+            *permits = (limit, Arc::new(Semaphore::new(limit)));
+        }
+        drop(permits);
+        self.inner
+            .stagger_ms
+            .store(config.stagger_ms, Ordering::Relaxed);
+    }
+
+    /// Waits for a free slot and this attempt's place in the stagger; the attempt counts
+    /// against the limit until the returned permit is dropped.
+    pub async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let permits = Arc::clone(
+            &self
+                .inner
+                .permits
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .1,
+        );
+        let permit = permits.acquire_owned().await.ok();
+
+        let stagger = Duration::from_millis(self.inner.stagger_ms.load(Ordering::Relaxed));
+        let start = {
+            let mut next_start = self
+                .inner
+                .next_start
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = Instant::now();
+            let start = next_start.map_or(now, |next| next.max(now));
+            *next_start = Some(start + stagger);
+            start
+        };
+        time::sleep_until(start).await;
+        permit
+    }
+}
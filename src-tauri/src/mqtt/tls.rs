@@ -0,0 +1,75 @@
+use anyhow::{Context, Result, anyhow, bail};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use std::sync::Arc;
+
+/// Builds the rustls config for a connection with its own CA or client certificate, or `None`
+/// when it has neither and rumqttc's default config (the platform roots) applies.
+///
+/// A custom CA replaces the public roots, as it's meant for private brokers. Certificates and
+/// keys may be PEM or DER; a PEM CA file may hold several certificates.
+pub fn client_config(
+    ca_cert_path: Option<&str>,
+    client_cert_path: Option<&str>,
+    client_key_path: Option<&str>,
+) -> Result<Option<Arc<rustls::ClientConfig>>> {
+    let client_auth = match (client_cert_path, client_key_path) {
+        (None, None) => None,
+        (Some(cert_path), Some(key_path)) => Some((read_certs(cert_path)?, read_key(key_path)?)),
+        (Some(_), None) => bail!("a client certificate needs its private key"),
+        (None, Some(_)) => bail!("a client key needs its certificate"),
+    };
+    if ca_cert_path.is_none() && client_auth.is_none() {
+        return Ok(None);
+    }
+
+    let mut roots = rustls::RootCertStore::empty();
+    match ca_cert_path {
+        Some(path) => {
+            let (added, _) = roots.add_parsable_certificates(read_certs(path)?);
+            if added == 0 {
+                bail!("{path} holds no usable CA certificate");
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+    // Both crypto backends end up in the dependency graph, so pick one explicitly.
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .context("failed to configure TLS")?
+    .with_root_certificates(roots);
+    let config = match client_auth {
+        Some((certs, key)) => builder
+            .with_client_auth_cert(certs, key)
+            .context("the client certificate doesn't match its key")?,
+        None => builder.with_no_client_auth(),
+    };
+    Ok(Some(Arc::new(config)))
+}
+
+fn read_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {path}"))?;
+    if !bytes.starts_with(b"-----BEGIN") {
+        return Ok(vec![CertificateDer::from(bytes)]);
+    }
+    let certs = CertificateDer::pem_slice_iter(&bytes)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| anyhow!("invalid certificate in {path}: {error}"))?;
+    if certs.is_empty() {
+        bail!("no certificate found in {path}");
+    }
+    Ok(certs)
+}
+
+fn read_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {path}"))?;
+    if bytes.starts_with(b"-----BEGIN") {
+        PrivateKeyDer::from_pem_slice(&bytes)
+            .map_err(|error| anyhow!("no private key found in {path}: {error}"))
+    } else {
+        PrivateKeyDer::try_from(bytes)
+            .map_err(|error| anyhow!("{path} is not a private key: {error}"))
+    }
+}
@@ -0,0 +1,261 @@
+use crate::config_store;
+use crate::models::{
+    MqttBatchItem, Payload, TransformErrorPayload, TransformTestResult, TransformsConfig,
+};
+use crate::mqtt::{is_valid_topic_filter, now_millis};
+use crate::windows;
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use rhai::{AST, Dynamic, Engine, ImmutableString, Scope};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::AppHandle;
+
+pub const TRANSFORMS_FILE_NAME: &str = "transforms.json";
+
+const MAX_OPERATIONS: u64 = 200_000;
+const MAX_STRING_BYTES: usize = 8 * 1024 * 1024;
+/// At most one `transform-error` event per connection per interval; the rest are counted.
+const ERROR_EVENT_INTERVAL_MS: u64 = 1000;
+
+struct CompiledTransform {
+    ast: AST,
+    last_error_at: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+/// Per-connection Rhai scripts run on every incoming message before it reaches the UI, history
+/// and rules.
+///
+/// Scripts see `topic`, `payload`, `qos`, `retain` and `json` (the parsed payload, or `()` when it
+/// is not JSON). The result decides what happens to the message:
+/// - `false` drops it;
+/// - a string replaces the payload;
+/// - a map or array replaces the payload with its JSON;
+/// - anything else keeps the message, with `topic` and `payload` as the script left them.
+#[derive(Clone)]
+pub struct TransformRegistry {
+    engine: Arc<Engine>,
+    scripts: Arc<DashMap<String, Arc<CompiledTransform>>>,
+}
+
+impl Default for TransformRegistry {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(MAX_STRING_BYTES)
+            .set_max_array_size(100_000)
+            .set_max_map_size(100_000);
+        engine.on_print(|text| tracing::debug!("transform script: {text}"));
+        engine.on_debug(|text, _, _| tracing::debug!("transform script: {text}"));
+        Self {
+            engine: Arc::new(engine),
+            scripts: Arc::default(),
+        }
+    }
+}
+
+/// Outcome of running a script over one message.
+enum Outcome {
+    Keep(MqttBatchItem),
+    Drop,
+}
+
+impl TransformRegistry {
+    pub fn init(&self, app: &AppHandle) {
+        match load(app) {
+            Ok(config) => {
+                if let Err(error) = self.apply_config(&config) {
+                    tracing::warn!("failed to compile transform scripts: {error:#}");
+                }
+            }
+            Err(error) => tracing::warn!("failed to load transform scripts: {error:#}"),
+        }
+    }
+
+    /// Compiles every enabled script, then persists and activates them; nothing changes if any
+    /// script fails to compile.
+    pub fn save(&self, app: &AppHandle, config: &TransformsConfig) -> Result<()> {
+        self.compile_all(config)?;
+        config_store::save_document(app, TRANSFORMS_FILE_NAME, config)?;
+        self.apply_config(config)
+    }
+
+    /// Runs `script` over a sample message without touching any connection.
+    pub fn test(&self, script: &str, topic: &str, payload: &str) -> Result<TransformTestResult> {
+        let ast = self
+            .engine
+            .compile(script)
+            .map_err(|error| anyhow!("{error}"))?;
+        let item = MqttBatchItem {
+            topic: topic.to_string(),
+            payload: Payload::from(payload),
+            qos: 0,
+            retain: false,
+            direction: crate::models::MessageDirection::In,
+            timestamp: now_millis(),
+            dup: false,
+            packet_id: None,
+            correlation_id: None,
+            response_topic: None,
+            user_properties: Vec::new(),
+            is_binary: false,
+        };
+        Ok(match self.run(&ast, item)? {
+            Outcome::Keep(item) => TransformTestResult {
+                dropped: false,
+                topic: item.topic,
+                payload: item.payload.to_string(),
+            },
+            Outcome::Drop => TransformTestResult {
+                dropped: true,
+                topic: topic.to_string(),
+                payload: payload.to_string(),
+            },
+        })
+    }
+
+    /// Transforms one incoming message; `None` means the script dropped it. Script errors pass
+    /// the message through unchanged and are reported as `transform-error` events.
+    pub fn apply(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        item: MqttBatchItem,
+    ) -> Option<MqttBatchItem> {
+        let Some(transform) = self
+            .scripts
+            .get(connection_id)
+            .map(|entry| Arc::clone(entry.value()))
+        else {
+            return Some(item);
+        };
+        let topic = item.topic.clone();
+        match self.run(&transform.ast, item.clone()) {
+            Ok(Outcome::Keep(item)) => Some(item),
+            Ok(Outcome::Drop) => None,
+            Err(error) => {
+                report_error(app, connection_id, &transform, &topic, &error);
+                Some(item)
+            }
+        }
+    }
+
+    fn run(&self, ast: &AST, mut item: MqttBatchItem) -> Result<Outcome> {
+        let json = serde_json::from_str::<serde_json::Value>(&item.payload)
+            .ok()
+            .filter(|value| value.is_object() || value.is_array())
+            .map(rhai::serde::to_dynamic)
+            .transpose()
+            .map_err(|error| anyhow!("{error}"))?
+            .unwrap_or(Dynamic::UNIT);
+
+        let mut scope = Scope::new();
+        scope.push("topic", ImmutableString::from(item.topic.as_str()));
+        scope.push("payload", ImmutableString::from(item.payload.as_str()));
+        scope.push_constant("qos", i64::from(item.qos));
+        scope.push_constant("retain", item.retain);
+        scope.push("json", json);
+
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+            .map_err(|error| anyhow!("{error}"))?;
+
+        if result.as_bool() == Ok(false) {
+            return Ok(Outcome::Drop);
+        }
+        let topic = scope
+            .get_value::<ImmutableString>("topic")
+            .ok_or_else(|| anyhow!("`topic` must remain a string"))?;
+        if topic.contains(['+', '#']) || !is_valid_topic_filter(&topic) {
+            return Err(anyhow!("script produced an invalid topic '{topic}'"));
+        }
+        item.topic = topic.to_string();
+
+        let payload = if result.is_string() {
+            Payload::from(result.into_immutable_string().unwrap_or_default().as_str())
+        } else if result.is_map() || result.is_array() {
+            let value = rhai::serde::from_dynamic::<serde_json::Value>(&result)
+                .map_err(|error| anyhow!("{error}"))?;
+            Payload::from(value.to_string())
+        } else {
+            let payload = scope
+                .get_value::<ImmutableString>("payload")
+                .ok_or_else(|| anyhow!("`payload` must remain a string"))?;
+            Payload::from(payload.as_str())
+        };
+        // A rewritten payload is the script's text, no longer base64 of the original bytes.
+        item.is_binary &= payload == item.payload;
+        item.payload = payload;
+        Ok(Outcome::Keep(item))
+    }
+
+    fn compile_all(&self, config: &TransformsConfig) -> Result<Vec<(String, AST)>> {
+        config
+            .connections
+            .iter()
+            .filter(|(_, transform)| transform.enabled && !transform.script.trim().is_empty())
+            .map(|(connection_id, transform)| {
+                self.engine
+                    .compile(&transform.script)
+                    .map(|ast| (connection_id.clone(), ast))
+                    .map_err(|error| anyhow!("transform for '{connection_id}': {error}"))
+            })
+            .collect()
+    }
+
+    fn apply_config(&self, config: &TransformsConfig) -> Result<()> {
+        let compiled = self.compile_all(config)?;
+        self.scripts.clear();
+        for (connection_id, ast) in compiled {
+            self.scripts.insert(
+                connection_id,
+                Arc::new(CompiledTransform {
+                    ast,
+                    last_error_at: AtomicU64::new(0),
+                    suppressed: AtomicU64::new(0),
+                }),
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn load(app: &AppHandle) -> Result<TransformsConfig> {
+    config_store::load_document::<TransformsConfig>(app, TRANSFORMS_FILE_NAME)
+}
+
+fn report_error(
+    app: &AppHandle,
+    connection_id: &str,
+    transform: &CompiledTransform,
+    topic: &str,
+    error: &anyhow::Error,
+) {
+    let now = now_millis();
+    let last = transform.last_error_at.load(Ordering::Relaxed);
+    if now.saturating_sub(last) < ERROR_EVENT_INTERVAL_MS
+        || transform
+            .last_error_at
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+    {
+        transform.suppressed.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    windows::emit_for_connection(
+        app,
+        "transform-error",
+        connection_id,
+        TransformErrorPayload {
+            connection_id: connection_id.to_string(),
+            topic: topic.to_string(),
+            error: format!("{error:#}"),
+            suppressed: transform.suppressed.swap(0, Ordering::Relaxed),
+        },
+    );
+}
@@ -0,0 +1,124 @@
+use crate::catalog::schema;
+use crate::models::{
+    BrokerCapabilities, PublishViolation, PublishViolationKind, TopicCatalogItem, TopicDirection,
+};
+use crate::mqtt::topic_matches;
+use serde_json::Value;
+
+/// Topic names are length-prefixed with a u16 on the wire.
+const MAX_TOPIC_BYTES: usize = u16::MAX as usize;
+
+/// Checks a publish against MQTT topic rules, the broker's limits and any catalog schema for the
+/// topic, collecting every violation instead of stopping at the first.
+pub fn check_publish(
+    topic: &str,
+    payload: &str,
+    qos: u8,
+    capabilities: BrokerCapabilities,
+    catalog: &[TopicCatalogItem],
+) -> Vec<PublishViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |kind, message: String| {
+        violations.push(PublishViolation { kind, message });
+    };
+
+    if topic.is_empty() {
+        violation(PublishViolationKind::Topic, "topic is empty".to_string());
+    }
+    if topic.contains(['+', '#']) {
+        violation(
+            PublishViolationKind::Topic,
+            "topic names cannot contain the wildcards '+' or '#'".to_string(),
+        );
+    }
+    if topic.len() > MAX_TOPIC_BYTES {
+        violation(
+            PublishViolationKind::Topic,
+            format!(
+                "topic is {} bytes, above the {MAX_TOPIC_BYTES} byte limit",
+                topic.len()
+            ),
+        );
+    }
+    if topic.contains('\0') {
+        violation(
+            PublishViolationKind::Topic,
+            "topic contains the null character U+0000".to_string(),
+        );
+    } else if topic.chars().any(char::is_control) {
+        violation(
+            PublishViolationKind::Topic,
+            "topic contains control characters, which brokers may reject".to_string(),
+        );
+    }
+
+    if qos > 2 {
+        violation(
+            PublishViolationKind::Qos,
+            format!("QoS {qos} does not exist"),
+        );
+    } else if qos > capabilities.max_qos {
+        violation(
+            PublishViolationKind::Qos,
+            format!(
+                "broker supports QoS up to {}, but {qos} was requested",
+                capabilities.max_qos
+            ),
+        );
+    }
+
+    if let Some(limit) = capabilities.max_packet_size {
+        let size = publish_packet_size(topic, payload, qos);
+        if size > limit as usize {
+            violation(
+                PublishViolationKind::PayloadSize,
+                format!("publish packet is {size} bytes, above the {limit} byte maximum"),
+            );
+        }
+    }
+
+    for item in catalog.iter().filter(|item| {
+        !matches!(item.direction, TopicDirection::Subscribe) && topic_matches(&item.topic, topic)
+    }) {
+        let Some(raw_schema) = item.schema.as_deref() else {
+            continue;
+        };
+        let Ok(item_schema) = serde_json::from_str::<Value>(raw_schema)
+            .or_else(|_| serde_yaml::from_str::<Value>(raw_schema))
+        else {
+            continue;
+        };
+        match serde_json::from_str::<Value>(payload) {
+            Ok(value) => {
+                for error in schema::validate(&item_schema, &value) {
+                    violation(
+                        PublishViolationKind::Schema,
+                        format!("{} ({}): {error}", item.name, item.topic),
+                    );
+                }
+            }
+            Err(error) => violation(
+                PublishViolationKind::Schema,
+                format!(
+                    "{} ({}): payload is not JSON: {error}",
+                    item.name, item.topic
+                ),
+            ),
+        }
+    }
+
+    violations
+}
+
+/// Encoded PUBLISH size: fixed header, topic, packet id (QoS > 0), payload and the v5 property
+/// length byte, which overstates v3.1.1 packets by one byte.
+fn publish_packet_size(topic: &str, payload: &str, qos: u8) -> usize {
+    let remaining = 2 + topic.len() + if qos > 0 { 2 } else { 0 } + 1 + payload.len();
+    let length_bytes = match remaining {
+        0..=127 => 1,
+        128..=16_383 => 2,
+        16_384..=2_097_151 => 3,
+        _ => 4,
+    };
+    1 + length_bytes + remaining
+}
@@ -0,0 +1,154 @@
+//! Pulls device timestamps out of message payloads so history can be read in the order events
+//! happened rather than the order they arrived; devices that batch-upload old readings would
+//! otherwise all appear at upload time.
+
+use crate::config_store;
+use crate::models::{
+    MqttBatchItem, PayloadTimestampFormat, PayloadTimestampRule, PayloadTimestampsConfig,
+};
+use crate::mqtt::{is_valid_topic_filter, topic_matches};
+use crate::rules::json_path;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDateTime};
+use dashmap::DashMap;
+use serde_json::Value;
+use std::sync::Arc;
+use tauri::AppHandle;
+
+pub const PAYLOAD_TIMESTAMPS_FILE_NAME: &str = "payload-timestamps.json";
+
+/// Numbers below this are read as epoch seconds by the `auto` format (about year 5138 in
+/// seconds, March 1973 in milliseconds).
+const AUTO_SECONDS_LIMIT: f64 = 100_000_000_000.0;
+
+#[derive(Clone, Default)]
+pub struct PayloadTimestamps {
+    rules: Arc<DashMap<String, Arc<Vec<PayloadTimestampRule>>>>,
+}
+
+impl PayloadTimestamps {
+    pub fn init(&self, app: &AppHandle) {
+        match load(app) {
+            Ok(config) => self.apply(config),
+            Err(error) => tracing::warn!("failed to load payload timestamp rules: {error:#}"),
+        }
+    }
+
+    pub fn save(&self, app: &AppHandle, config: &PayloadTimestampsConfig) -> Result<()> {
+        for rule in config.connections.values().flatten() {
+            validate_rule(rule)?;
+        }
+        config_store::save_document(app, PAYLOAD_TIMESTAMPS_FILE_NAME, config)?;
+        self.apply(config.clone());
+        Ok(())
+    }
+
+    /// Runs one rule over a sample payload; `None` means the path or format did not match.
+    pub fn test(&self, rule: &PayloadTimestampRule, payload: &str) -> Result<Option<u64>> {
+        validate_rule(rule)?;
+        let Ok(root) = serde_json::from_str::<Value>(payload) else {
+            return Ok(None);
+        };
+        Ok(extract(rule, &root))
+    }
+
+    /// Payload timestamps for each message, in order, using the connection's rules.
+    pub fn extract_batch(&self, connection_id: &str, items: &[MqttBatchItem]) -> Vec<Option<u64>> {
+        let Some(rules) = self
+            .rules
+            .get(connection_id)
+            .map(|entry| Arc::clone(entry.value()))
+        else {
+            return vec![None; items.len()];
+        };
+        items
+            .iter()
+            .map(|item| {
+                let rule = rules
+                    .iter()
+                    .find(|rule| topic_matches(&rule.topic_filter, &item.topic))?;
+                let root = serde_json::from_str::<Value>(&item.payload).ok()?;
+                extract(rule, &root)
+            })
+            .collect()
+    }
+
+    fn apply(&self, config: PayloadTimestampsConfig) {
+        self.rules.clear();
+        for (connection_id, rules) in config.connections {
+            if !rules.is_empty() {
+                self.rules.insert(connection_id, Arc::new(rules));
+            }
+        }
+    }
+}
+
+pub fn load(app: &AppHandle) -> Result<PayloadTimestampsConfig> {
+    config_store::load_document::<PayloadTimestampsConfig>(app, PAYLOAD_TIMESTAMPS_FILE_NAME)
+}
+
+fn validate_rule(rule: &PayloadTimestampRule) -> Result<()> {
+    if !is_valid_topic_filter(rule.topic_filter.trim()) {
+        return Err(anyhow!("invalid topic filter '{}'", rule.topic_filter));
+    }
+    if rule.path.trim().is_empty() {
+        return Err(anyhow!(
+            "timestamp path is required for '{}'",
+            rule.topic_filter
+        ));
+    }
+    if rule.format == PayloadTimestampFormat::Pattern
+        && rule.pattern.as_deref().is_none_or(|p| p.trim().is_empty())
+    {
+        return Err(anyhow!(
+            "a format pattern is required for '{}'",
+            rule.topic_filter
+        ));
+    }
+    Ok(())
+}
+
+fn extract(rule: &PayloadTimestampRule, root: &Value) -> Option<u64> {
+    let value = json_path::select(root, &rule.path)?;
+    let millis = match rule.format {
+        PayloadTimestampFormat::Auto => match number(value) {
+            Some(n) if n.abs() < AUTO_SECONDS_LIMIT => n * 1000.0,
+            Some(n) => n,
+            None => return rfc3339(value.as_str()?),
+        },
+        PayloadTimestampFormat::EpochSeconds => number(value)? * 1000.0,
+        PayloadTimestampFormat::EpochMillis => number(value)?,
+        PayloadTimestampFormat::Rfc3339 => return rfc3339(value.as_str()?),
+        PayloadTimestampFormat::Pattern => {
+            return with_pattern(value.as_str()?, rule.pattern.as_deref()?);
+        }
+    };
+    (millis.is_finite() && millis >= 0.0).then_some(millis as u64)
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn rfc3339(text: &str) -> Option<u64> {
+    let millis = DateTime::parse_from_rfc3339(text.trim())
+        .ok()?
+        .timestamp_millis();
+    u64::try_from(millis).ok()
+}
+
+fn with_pattern(text: &str, pattern: &str) -> Option<u64> {
+    let text = text.trim();
+    let millis = match DateTime::parse_from_str(text, pattern) {
+        Ok(time) => time.timestamp_millis(),
+        Err(_) => NaiveDateTime::parse_from_str(text, pattern)
+            .ok()?
+            .and_utc()
+            .timestamp_millis(),
+    };
+    u64::try_from(millis).ok()
+}
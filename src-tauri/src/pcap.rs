@@ -0,0 +1,603 @@
+//! Reads MQTT PUBLISH packets out of pcap / pcapng captures, so Wireshark captures from the
+//! field can be loaded into a connection's history.
+//!
+//! Only plain MQTT over TCP is understood: TLS and WebSocket traffic is skipped. TCP streams
+//! are reassembled per direction; a capture that starts mid-stream is decoded from the first
+//! segment on, which works as long as that segment starts an MQTT packet.
+
+use crate::models::{MessageDirection, MqttBatchItem, Payload, PcapImportOptions};
+use crate::mqtt::session::correlation_key;
+use crate::mqtt::topic_matches;
+use anyhow::{Context, Result, bail};
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_INTERFACE: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+const IP_PROTOCOL_TCP: u8 = 6;
+
+const TCP_SYN: u8 = 0x02;
+/// Out-of-order segments held per stream before the gap is given up on.
+const MAX_PENDING_SEGMENTS: usize = 512;
+/// Larger "packets" mean the stream lost sync with the MQTT framing.
+const MAX_MQTT_PACKET: usize = 256 * 1024 * 1024;
+const MQTT_CONNECT: u8 = 1;
+const MQTT_PUBLISH: u8 = 3;
+
+/// Messages recovered from a capture plus counters for the import report.
+#[derive(Debug, Default)]
+pub struct CaptureMessages {
+    pub messages: Vec<MqttBatchItem>,
+    pub packets: u64,
+    pub streams: u64,
+    /// PUBLISH packets left out by the topic filter.
+    pub filtered: u64,
+    /// Packets that could not be decoded, usually because the capture started mid-packet.
+    pub malformed: u64,
+}
+
+pub fn read_publishes(path: &Path, options: &PcapImportOptions) -> Result<CaptureMessages> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut decoder = Decoder::new(options);
+    if data.len() >= 4 && read_u32(&data, 0, true) == Some(PCAPNG_SECTION_HEADER) {
+        read_pcapng(&data, &mut decoder)?;
+    } else {
+        read_pcap(&data, &mut decoder)?;
+    }
+    Ok(decoder.finish())
+}
+
+fn read_pcap(data: &[u8], decoder: &mut Decoder) -> Result<()> {
+    let (little, nanos) = match (read_u32(data, 0, true), read_u32(data, 0, false)) {
+        (Some(PCAP_MAGIC_MICROS), _) => (true, false),
+        (Some(PCAP_MAGIC_NANOS), _) => (true, true),
+        (_, Some(PCAP_MAGIC_MICROS)) => (false, false),
+        (_, Some(PCAP_MAGIC_NANOS)) => (false, true),
+        _ => bail!("not a pcap or pcapng file"),
+    };
+    let link_type = read_u32(data, 20, little).context("truncated pcap header")?;
+    let mut offset = 24;
+    while offset + 16 <= data.len() {
+        let seconds = u64::from(read_u32(data, offset, little).unwrap_or(0));
+        let fraction = u64::from(read_u32(data, offset + 4, little).unwrap_or(0));
+        let captured = read_u32(data, offset + 8, little).unwrap_or(0) as usize;
+        let start = offset + 16;
+        let Some(frame) = data.get(start..start + captured) else {
+            break;
+        };
+        let millis = seconds * 1000
+            + if nanos {
+                fraction / 1_000_000
+            } else {
+                fraction / 1000
+            };
+        decoder.frame(link_type, frame, millis);
+        offset = start + captured;
+    }
+    Ok(())
+}
+
+struct Interface {
+    link_type: u32,
+    /// Timestamp units per second.
+    units_per_sec: u64,
+}
+
+fn read_pcapng(data: &[u8], decoder: &mut Decoder) -> Result<()> {
+    let mut little = true;
+    let mut interfaces = Vec::<Interface>::new();
+    let mut last_millis = 0;
+    let mut offset = 0;
+    while offset + 12 <= data.len() {
+        let block_type = read_u32(data, offset, little).unwrap_or(0);
+        if block_type == PCAPNG_SECTION_HEADER {
+            little = match read_u32(data, offset + 8, true) {
+                Some(PCAPNG_BYTE_ORDER_MAGIC) => true,
+                _ if read_u32(data, offset + 8, false) == Some(PCAPNG_BYTE_ORDER_MAGIC) => false,
+                _ => bail!("invalid pcapng section header"),
+            };
+            interfaces.clear();
+        }
+        let length = read_u32(data, offset + 4, little).unwrap_or(0) as usize;
+        if length < 12 || offset + length > data.len() {
+            break;
+        }
+        let body = &data[offset + 8..offset + length - 4];
+        match block_type {
+            PCAPNG_INTERFACE if body.len() >= 8 => {
+                let link_type = u32::from(read_u16(body, 0, little).unwrap_or(0));
+                interfaces.push(Interface {
+                    link_type,
+                    units_per_sec: interface_resolution(&body[8..], little),
+                });
+            }
+            PCAPNG_ENHANCED_PACKET if body.len() >= 20 => {
+                let interface = read_u32(body, 0, little).unwrap_or(0) as usize;
+                let high = u64::from(read_u32(body, 4, little).unwrap_or(0));
+                let low = u64::from(read_u32(body, 8, little).unwrap_or(0));
+                let captured = read_u32(body, 12, little).unwrap_or(0) as usize;
+                if let (Some(interface), Some(frame)) =
+                    (interfaces.get(interface), body.get(20..20 + captured))
+                {
+                    let units = (high << 32) | low;
+                    last_millis = (u128::from(units) * 1000
+                        / u128::from(interface.units_per_sec.max(1)))
+                        as u64;
+                    decoder.frame(interface.link_type, frame, last_millis);
+                }
+            }
+            PCAPNG_SIMPLE_PACKET if body.len() >= 4 => {
+                // Simple packets carry no timestamp; reuse the previous one.
+                if let Some(interface) = interfaces.first() {
+                    decoder.frame(interface.link_type, &body[4..], last_millis);
+                }
+            }
+            _ => {}
+        }
+        offset += length;
+    }
+    Ok(())
+}
+
+/// Reads `if_tsresol` from an interface block's options; microseconds when absent.
+fn interface_resolution(mut options: &[u8], little: bool) -> u64 {
+    while options.len() >= 4 {
+        let code = read_u16(options, 0, little).unwrap_or(0);
+        let length = read_u16(options, 2, little).unwrap_or(0) as usize;
+        if code == 0 {
+            break;
+        }
+        if code == PCAPNG_OPTION_TSRESOL && length >= 1 && options.len() > 4 {
+            let value = options[4];
+            let exponent = u32::from(value & 0x7f);
+            return if value & 0x80 == 0 {
+                10u64.checked_pow(exponent).unwrap_or(1_000_000)
+            } else {
+                2u64.checked_pow(exponent).unwrap_or(1_000_000)
+            };
+        }
+        let padded = 4 + length.div_ceil(4) * 4;
+        options = options.get(padded..).unwrap_or_default();
+    }
+    1_000_000
+}
+
+/// One direction of a TCP connection: client to broker or broker to client.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct StreamKey {
+    source: (IpAddr, u16),
+    destination: (IpAddr, u16),
+}
+
+impl StreamKey {
+    /// The client side's view of the connection, shared by both directions.
+    fn session(self, direction: MessageDirection) -> StreamKey {
+        match direction {
+            MessageDirection::Out => self,
+            MessageDirection::In => StreamKey {
+                source: self.destination,
+                destination: self.source,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stream {
+    next_seq: Option<u32>,
+    pending: BTreeMap<u32, Vec<u8>>,
+    buffer: Vec<u8>,
+    /// MQTT 5 topic aliases set by earlier publishes in this direction.
+    aliases: HashMap<u16, String>,
+}
+
+struct Decoder<'a> {
+    options: &'a PcapImportOptions,
+    streams: HashMap<StreamKey, Stream>,
+    /// Protocol level from each session's CONNECT.
+    versions: HashMap<StreamKey, u8>,
+    result: CaptureMessages,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(options: &'a PcapImportOptions) -> Self {
+        Self {
+            options,
+            streams: HashMap::new(),
+            versions: HashMap::new(),
+            result: CaptureMessages::default(),
+        }
+    }
+
+    fn finish(mut self) -> CaptureMessages {
+        self.result.streams = self.streams.len() as u64;
+        self.result
+    }
+
+    fn frame(&mut self, link_type: u32, frame: &[u8], millis: u64) {
+        self.result.packets += 1;
+        let Some(ip) = ip_packet(link_type, frame) else {
+            return;
+        };
+        let Some((source, destination, tcp)) = tcp_segment(ip) else {
+            return;
+        };
+        let is_broker = |port: u16| self.options.broker_ports.contains(&port);
+        let direction = if is_broker(destination.1) {
+            MessageDirection::Out
+        } else if is_broker(source.1) {
+            MessageDirection::In
+        } else {
+            return;
+        };
+        let Some(header_len) = tcp.get(12).map(|byte| usize::from(byte >> 4) * 4) else {
+            return;
+        };
+        let (Some(seq), Some(&flags), Some(payload)) =
+            (read_u32(tcp, 4, false), tcp.get(13), tcp.get(header_len..))
+        else {
+            return;
+        };
+
+        let key = StreamKey {
+            source,
+            destination,
+        };
+        let stream = self.streams.entry(key).or_default();
+        if flags & TCP_SYN != 0 {
+            *stream = Stream {
+                next_seq: Some(seq.wrapping_add(1)),
+                ..Stream::default()
+            };
+            return;
+        }
+        if payload.is_empty() {
+            return;
+        }
+        append_segment(stream, seq, payload);
+        self.drain(key, direction, millis);
+    }
+
+    /// Decodes every complete MQTT packet buffered on the stream.
+    fn drain(&mut self, key: StreamKey, direction: MessageDirection, millis: u64) {
+        let session = key.session(direction);
+        loop {
+            let Some(stream) = self.streams.get_mut(&key) else {
+                return;
+            };
+            let (packet_type, flags, body_start, total) = match fixed_header(&stream.buffer) {
+                Header::Complete(header) => header,
+                Header::Incomplete => return,
+                Header::Invalid => {
+                    stream.buffer.clear();
+                    self.result.malformed += 1;
+                    return;
+                }
+            };
+            if stream.buffer.len() < total {
+                return;
+            }
+            let packet = stream.buffer.drain(..total).collect::<Vec<u8>>();
+            let body = &packet[body_start..];
+            match packet_type {
+                MQTT_CONNECT if matches!(direction, MessageDirection::Out) => {
+                    if let Some(level) = connect_level(body) {
+                        self.versions.insert(session, level);
+                    }
+                }
+                MQTT_PUBLISH => {
+                    let version = self
+                        .versions
+                        .get(&session)
+                        .copied()
+                        .or(self.options.protocol_version)
+                        .unwrap_or(4);
+                    let decoded =
+                        decode_publish(flags, body, version, &mut stream.aliases, direction);
+                    match decoded {
+                        Some(mut item) => {
+                            item.timestamp = millis;
+                            let keep = self.options.topic_filter.as_deref().is_none_or(|filter| {
+                                filter.trim().is_empty() || topic_matches(filter, &item.topic)
+                            });
+                            if keep {
+                                self.result.messages.push(item);
+                            } else {
+                                self.result.filtered += 1;
+                            }
+                        }
+                        None => self.result.malformed += 1,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Adds a segment's data in sequence order, holding segments that arrive ahead of a gap.
+fn append_segment(stream: &mut Stream, seq: u32, payload: &[u8]) {
+    let next = *stream.next_seq.get_or_insert(seq);
+    let offset = seq.wrapping_sub(next) as i32;
+    if offset > 0 {
+        stream.pending.insert(seq, payload.to_vec());
+        if stream.pending.len() > MAX_PENDING_SEGMENTS {
+            // The gap was never filled: resume from the earliest held segment.
+            let Some((&first, _)) = stream.pending.iter().next() else {
+                return;
+            };
+            stream.buffer.clear();
+            stream.next_seq = Some(first);
+        } else {
+            return;
+        }
+    } else {
+        // Retransmissions may overlap data already taken.
+        let skip = offset.unsigned_abs() as usize;
+        if skip < payload.len() {
+            stream.buffer.extend_from_slice(&payload[skip..]);
+            stream.next_seq = Some(seq.wrapping_add(payload.len() as u32));
+        }
+    }
+    while let Some(next) = stream.next_seq {
+        let Some(data) = stream.pending.remove(&next) else {
+            break;
+        };
+        stream.buffer.extend_from_slice(&data);
+        stream.next_seq = Some(next.wrapping_add(data.len() as u32));
+    }
+}
+
+/// Strips the link layer and returns the IPv4 or IPv6 packet.
+fn ip_packet(link_type: u32, frame: &[u8]) -> Option<&[u8]> {
+    match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ethertype = read_u16(frame, offset, false)?;
+            while ethertype == ETHERTYPE_VLAN || ethertype == ETHERTYPE_QINQ {
+                offset += 4;
+                ethertype = read_u16(frame, offset, false)?;
+            }
+            matches!(ethertype, ETHERTYPE_IPV4 | ETHERTYPE_IPV6).then(|| &frame[offset + 2..])
+        }
+        LINKTYPE_NULL | LINKTYPE_LOOP => frame.get(4..),
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => Some(frame),
+        LINKTYPE_LINUX_SLL => frame.get(16..),
+        LINKTYPE_LINUX_SLL2 => frame.get(20..),
+        _ => None,
+    }
+}
+
+type Endpoint = (IpAddr, u16);
+
+/// Source, destination and TCP header plus data of an unfragmented TCP packet.
+fn tcp_segment(ip: &[u8]) -> Option<(Endpoint, Endpoint, &[u8])> {
+    let (source, destination, tcp) = match ip.first()? >> 4 {
+        4 => {
+            let header_len = usize::from(ip[0] & 0x0f) * 4;
+            let total_len = usize::from(read_u16(ip, 2, false)?);
+            let fragment = read_u16(ip, 6, false)?;
+            // More-fragments set or a non-zero offset.
+            if fragment & 0x3fff != 0 || *ip.get(9)? != IP_PROTOCOL_TCP {
+                return None;
+            }
+            let source: [u8; 4] = ip.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = ip.get(16..20)?.try_into().ok()?;
+            (
+                IpAddr::from(source),
+                IpAddr::from(destination),
+                ip.get(header_len..total_len.min(ip.len()))?,
+            )
+        }
+        6 => {
+            let payload_len = usize::from(read_u16(ip, 4, false)?);
+            let source: [u8; 16] = ip.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = ip.get(24..40)?.try_into().ok()?;
+            let end = (40 + payload_len).min(ip.len());
+            let mut next_header = *ip.get(6)?;
+            let mut offset = 40;
+            // Hop-by-hop, routing and destination options; fragments are not reassembled.
+            while matches!(next_header, 0 | 43 | 60) {
+                next_header = *ip.get(offset)?;
+                offset += (usize::from(*ip.get(offset + 1)?) + 1) * 8;
+            }
+            if next_header != IP_PROTOCOL_TCP {
+                return None;
+            }
+            (
+                IpAddr::from(source),
+                IpAddr::from(destination),
+                ip.get(offset..end)?,
+            )
+        }
+        _ => return None,
+    };
+    let source_port = read_u16(tcp, 0, false)?;
+    let destination_port = read_u16(tcp, 2, false)?;
+    (tcp.len() >= 20).then_some(((source, source_port), (destination, destination_port), tcp))
+}
+
+enum Header {
+    /// Packet type, flags, offset of the variable header and total packet length.
+    Complete((u8, u8, usize, usize)),
+    Incomplete,
+    Invalid,
+}
+
+fn fixed_header(buffer: &[u8]) -> Header {
+    let Some(&first) = buffer.first() else {
+        return Header::Incomplete;
+    };
+    let packet_type = first >> 4;
+    if packet_type == 0 {
+        return Header::Invalid;
+    }
+    let mut remaining = 0usize;
+    for index in 0..4 {
+        let Some(&byte) = buffer.get(1 + index) else {
+            return Header::Incomplete;
+        };
+        remaining |= usize::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            if remaining > MAX_MQTT_PACKET {
+                return Header::Invalid;
+            }
+            return Header::Complete((packet_type, first & 0x0f, index + 2, index + 2 + remaining));
+        }
+    }
+    Header::Invalid
+}
+
+fn connect_level(body: &[u8]) -> Option<u8> {
+    let name_len = usize::from(read_u16(body, 0, false)?);
+    body.get(2 + name_len).copied()
+}
+
+fn decode_publish(
+    flags: u8,
+    body: &[u8],
+    version: u8,
+    aliases: &mut HashMap<u16, String>,
+    direction: MessageDirection,
+) -> Option<MqttBatchItem> {
+    let qos = (flags >> 1) & 0x03;
+    if qos > 2 {
+        return None;
+    }
+    let topic_len = usize::from(read_u16(body, 0, false)?);
+    let mut topic = std::str::from_utf8(body.get(2..2 + topic_len)?)
+        .ok()?
+        .to_string();
+    let mut offset = 2 + topic_len;
+    let packet_id = if qos > 0 {
+        let id = read_u16(body, offset, false)?;
+        offset += 2;
+        Some(id)
+    } else {
+        None
+    };
+
+    let mut correlation_id = None;
+    let mut response_topic = None;
+    if version == 5 {
+        let (length, used) = read_varint(body.get(offset..)?)?;
+        offset += used;
+        let properties = body.get(offset..offset + length)?;
+        offset += length;
+        let mut alias = None;
+        let mut index = 0;
+        while index < properties.len() {
+            let id = properties[index];
+            index += 1;
+            match id {
+                // Payload format indicator.
+                0x01 => index += 1,
+                // Message expiry interval.
+                0x02 => index += 4,
+                0x23 => {
+                    alias = Some(read_u16(properties, index, false)?);
+                    index += 2;
+                }
+                // Response topic, correlation data and content type.
+                0x08 | 0x09 | 0x03 => {
+                    let len = usize::from(read_u16(properties, index, false)?);
+                    let value = properties.get(index + 2..index + 2 + len)?;
+                    match id {
+                        0x08 => response_topic = Some(String::from_utf8_lossy(value).into_owned()),
+                        0x09 => correlation_id = Some(correlation_key(value)),
+                        _ => {}
+                    }
+                    index += 2 + len;
+                }
+                // User property: two strings.
+                0x26 => {
+                    for _ in 0..2 {
+                        index += 2 + usize::from(read_u16(properties, index, false)?);
+                    }
+                }
+                // Subscription identifier.
+                0x0b => index += read_varint(properties.get(index..)?)?.1,
+                _ => return None,
+            }
+        }
+        if let Some(alias) = alias {
+            if topic.is_empty() {
+                topic = aliases.get(&alias)?.clone();
+            } else {
+                aliases.insert(alias, topic.clone());
+            }
+        }
+    }
+    if topic.is_empty() {
+        return None;
+    }
+
+    let (payload, is_binary) = Payload::from_wire(Bytes::copy_from_slice(body.get(offset..)?));
+    Some(MqttBatchItem {
+        topic,
+        payload,
+        qos,
+        retain: flags & 0x01 != 0,
+        direction,
+        timestamp: 0,
+        dup: flags & 0x08 != 0,
+        packet_id,
+        correlation_id,
+        response_topic,
+        user_properties: Vec::new(),
+        is_binary,
+    })
+}
+
+/// MQTT variable byte integer: value and bytes used.
+fn read_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut value = 0usize;
+    for (index, byte) in data.iter().take(4).enumerate() {
+        value |= usize::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+fn read_u16(data: &[u8], offset: usize, little: bool) -> Option<u16> {
+    let bytes: [u8; 2] = data.get(offset..offset + 2)?.try_into().ok()?;
+    Some(if little {
+        u16::from_le_bytes(bytes)
+    } else {
+        u16::from_be_bytes(bytes)
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize, little: bool) -> Option<u32> {
+    let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if little {
+        u32::from_le_bytes(bytes)
+    } else {
+        u32::from_be_bytes(bytes)
+    })
+}
@@ -0,0 +1,186 @@
+pub mod wasm;
+
+use crate::config_store;
+use crate::models::{DecoderPluginConfig, DecoderPluginStatus, Payload, PluginsConfig};
+use crate::mqtt::{is_valid_topic_filter, topic_matches};
+use anyhow::{Result, anyhow};
+use bytes::Bytes;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use tauri::AppHandle;
+use wasm::WasmCodec;
+
+pub const PLUGINS_FILE_NAME: &str = "plugins.json";
+
+struct LoadedDecoder {
+    id: String,
+    topics: Vec<String>,
+    codec: WasmCodec,
+}
+
+/// Decoder plugins currently loaded, in configuration order; the first whose topic patterns
+/// match a message handles it.
+#[derive(Clone, Default)]
+pub struct PluginRegistry {
+    decoders: Arc<RwLock<Vec<Arc<LoadedDecoder>>>>,
+    statuses: Arc<RwLock<Vec<DecoderPluginStatus>>>,
+}
+
+impl PluginRegistry {
+    /// Loads the saved plugins on startup; plugins that fail to load are reported in the status.
+    pub fn init(&self, app: &AppHandle) {
+        match load(app) {
+            Ok(config) => self.apply(&config),
+            Err(error) => tracing::warn!("failed to load decoder plugins: {error:#}"),
+        }
+    }
+
+    /// Validates the configuration, persists it, then reloads every plugin module.
+    pub fn save(
+        &self,
+        app: &AppHandle,
+        config: &PluginsConfig,
+    ) -> Result<Vec<DecoderPluginStatus>> {
+        validate(config)?;
+        config_store::save_document(app, PLUGINS_FILE_NAME, config)?;
+        self.apply(config);
+        Ok(self.statuses())
+    }
+
+    pub fn statuses(&self) -> Vec<DecoderPluginStatus> {
+        self.statuses
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Incoming payload after the matching plugin's `decode`, and whether it is base64 of binary
+    /// data. Payloads no plugin claims, or that the plugin rejects, pass through unchanged.
+    pub fn decode(&self, topic: &str, payload: Bytes) -> (Payload, bool) {
+        let Some(decoder) = self.find(topic, false) else {
+            return Payload::from_wire(payload);
+        };
+        match decoder.codec.decode(&payload) {
+            Ok(decoded) => Payload::from_wire(Bytes::from(decoded)),
+            Err(error) => {
+                tracing::debug!(plugin = %decoder.id, topic, "decode failed: {error:#}");
+                Payload::from_wire(payload)
+            }
+        }
+    }
+
+    /// Outgoing bytes after the matching plugin's `encode`. Unlike decoding, a failure is an
+    /// error: sending the unencoded text to a device expecting the binary format is worse.
+    pub fn encode(&self, topic: &str, payload: Payload) -> Result<Bytes> {
+        let Some(encoder) = self.find(topic, true) else {
+            return Ok(payload.into_bytes());
+        };
+        encoder
+            .codec
+            .encode(&payload.into_bytes())
+            .map(Bytes::from)
+            .map_err(|error| {
+                anyhow!(
+                    "plugin '{}' could not encode payload: {error:#}",
+                    encoder.id
+                )
+            })
+    }
+
+    fn find(&self, topic: &str, needs_encode: bool) -> Option<Arc<LoadedDecoder>> {
+        self.decoders
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .find(|decoder| {
+                (!needs_encode || decoder.codec.has_encode())
+                    && decoder
+                        .topics
+                        .iter()
+                        .any(|filter| topic_matches(filter, topic))
+            })
+            .cloned()
+    }
+
+    fn apply(&self, config: &PluginsConfig) {
+        let mut decoders = Vec::new();
+        let mut statuses = Vec::new();
+        for plugin in &config.decoders {
+            let mut status = DecoderPluginStatus {
+                id: plugin.id.clone(),
+                name: plugin.name.clone(),
+                loaded: false,
+                supports_encode: false,
+                error: None,
+            };
+            if plugin.enabled {
+                match WasmCodec::load(Path::new(plugin.path.trim())) {
+                    Ok(codec) => {
+                        status.loaded = true;
+                        status.supports_encode = codec.has_encode();
+                        decoders.push(Arc::new(LoadedDecoder {
+                            id: plugin.id.clone(),
+                            topics: plugin.topics.iter().map(|t| t.trim().to_string()).collect(),
+                            codec,
+                        }));
+                    }
+                    Err(error) => {
+                        tracing::warn!(plugin = %plugin.id, "failed to load decoder plugin: {error:#}");
+                        status.error = Some(format!("{error:#}"));
+                    }
+                }
+            }
+            statuses.push(status);
+        }
+        *self
+            .decoders
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = decoders;
+        *self
+            .statuses
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = statuses;
+    }
+}
+
+pub fn load(app: &AppHandle) -> Result<PluginsConfig> {
+    config_store::load_document::<PluginsConfig>(app, PLUGINS_FILE_NAME)
+}
+
+fn validate(config: &PluginsConfig) -> Result<()> {
+    let mut ids = HashSet::new();
+    for plugin in &config.decoders {
+        validate_plugin(plugin)?;
+        if !ids.insert(plugin.id.as_str()) {
+            return Err(anyhow!("duplicate plugin id '{}'", plugin.id));
+        }
+    }
+    Ok(())
+}
+
+fn validate_plugin(plugin: &DecoderPluginConfig) -> Result<()> {
+    if plugin.id.trim().is_empty() {
+        return Err(anyhow!("plugin id is required"));
+    }
+    if plugin.path.trim().is_empty() {
+        return Err(anyhow!("plugin '{}' needs a .wasm module path", plugin.id));
+    }
+    if plugin.topics.is_empty() {
+        return Err(anyhow!(
+            "plugin '{}' needs at least one topic pattern",
+            plugin.id
+        ));
+    }
+    if let Some(topic) = plugin
+        .topics
+        .iter()
+        .find(|topic| !is_valid_topic_filter(topic.trim()))
+    {
+        return Err(anyhow!(
+            "plugin '{}' has an invalid topic pattern '{topic}'",
+            plugin.id
+        ));
+    }
+    Ok(())
+}
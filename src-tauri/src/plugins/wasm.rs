@@ -0,0 +1,157 @@
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+use std::sync::Mutex;
+use wasmi::{Config, Engine, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Instructions a single decode/encode call may execute before it is aborted.
+const CALL_FUEL: u64 = 50_000_000;
+const MAX_MEMORY_BYTES: usize = 32 * 1024 * 1024;
+const MAX_OUTPUT_BYTES: usize = 8 * 1024 * 1024;
+
+/// A sandboxed codec module. The module gets no imports, so it can only compute; every call is
+/// bounded by fuel and a linear-memory cap.
+///
+/// Modules export `memory`, `alloc(len: i32) -> i32`, `decode(ptr: i32, len: i32) -> i64` and
+/// optionally `encode` with the same signature. Results pack the output buffer as
+/// `(ptr << 32) | len`; a negative result rejects the input.
+pub struct WasmCodec {
+    engine: Engine,
+    module: Module,
+    has_encode: bool,
+    /// Reused between calls; dropped after a trap so the next call starts from a fresh instance.
+    instance: Mutex<Option<(Store<StoreLimits>, Instance)>>,
+}
+
+impl WasmCodec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &bytes)
+            .map_err(|error| anyhow!("invalid WASM module: {error}"))?;
+
+        if let Some(import) = module.imports().next() {
+            return Err(anyhow!(
+                "plugin imports {}::{}, but decoder plugins may not import anything",
+                import.module(),
+                import.name()
+            ));
+        }
+        let exports = module
+            .exports()
+            .map(|export| export.name().to_string())
+            .collect::<Vec<_>>();
+        for required in ["memory", "alloc", "decode"] {
+            if !exports.iter().any(|name| name == required) {
+                return Err(anyhow!("plugin does not export `{required}`"));
+            }
+        }
+
+        let codec = Self {
+            has_encode: exports.iter().any(|name| name == "encode"),
+            engine,
+            module,
+            instance: Mutex::new(None),
+        };
+        // Instantiate once up front so a broken start function fails at load time.
+        let instance = codec.instantiate()?;
+        *codec.lock() = Some(instance);
+        Ok(codec)
+    }
+
+    pub fn has_encode(&self) -> bool {
+        self.has_encode
+    }
+
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        self.call("decode", input)
+    }
+
+    pub fn encode(&self, input: &[u8]) -> Result<Vec<u8>> {
+        self.call("encode", input)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<(Store<StoreLimits>, Instance)>> {
+        self.instance
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance)> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(CALL_FUEL)
+            .map_err(|error| anyhow!("{error}"))?;
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|error| anyhow!("failed to instantiate plugin: {error}"))?;
+        Ok((store, instance))
+    }
+
+    fn call(&self, function: &str, input: &[u8]) -> Result<Vec<u8>> {
+        let mut slot = self.lock();
+        if slot.is_none() {
+            *slot = Some(self.instantiate()?);
+        }
+        let Some((store, instance)) = slot.as_mut() else {
+            unreachable!("instance was just created");
+        };
+        let result = Self::invoke(store, *instance, function, input);
+        if result.is_err() {
+            *slot = None;
+        }
+        result
+    }
+
+    fn invoke(
+        store: &mut Store<StoreLimits>,
+        instance: Instance,
+        function: &str,
+        input: &[u8],
+    ) -> Result<Vec<u8>> {
+        store
+            .set_fuel(CALL_FUEL)
+            .map_err(|error| anyhow!("{error}"))?;
+        let memory = instance
+            .get_memory(&*store, "memory")
+            .ok_or_else(|| anyhow!("plugin does not export `memory`"))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&*store, "alloc")
+            .map_err(|error| anyhow!("bad `alloc` export: {error}"))?;
+        let run = instance
+            .get_typed_func::<(i32, i32), i64>(&*store, function)
+            .map_err(|error| anyhow!("bad `{function}` export: {error}"))?;
+
+        let len = i32::try_from(input.len()).context("payload too large for plugin")?;
+        let ptr = alloc
+            .call(&mut *store, len)
+            .map_err(|error| anyhow!("plugin `alloc` trapped: {error}"))?;
+        memory
+            .write(&mut *store, ptr as u32 as usize, input)
+            .map_err(|error| anyhow!("plugin `alloc` returned an invalid buffer: {error}"))?;
+
+        let packed = run
+            .call(&mut *store, (ptr, len))
+            .map_err(|error| anyhow!("plugin `{function}` trapped: {error}"))?;
+        if packed < 0 {
+            return Err(anyhow!("plugin rejected the payload"));
+        }
+        let out_ptr = (packed >> 32) as usize;
+        let out_len = (packed & 0xffff_ffff) as usize;
+        if out_len > MAX_OUTPUT_BYTES {
+            return Err(anyhow!("plugin output exceeds {MAX_OUTPUT_BYTES} bytes"));
+        }
+        let mut output = vec![0; out_len];
+        memory
+            .read(&*store, out_ptr, &mut output)
+            .map_err(|error| anyhow!("plugin returned an invalid buffer: {error}"))?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,131 @@
+//! Connection profile operations that span more than the profile itself, such as copying a
+//! profile together with its topic catalog.
+
+use crate::config_store;
+use crate::models::{ConnectionDuplicate, ConnectionDuplicateOverrides, ConnectionEnvironment};
+use crate::mqtt::now_millis;
+use anyhow::{Result, anyhow, bail};
+
+/// Copies a saved profile under a new id, along with its default subscriptions, labels and
+/// topic catalog, and saves the copy next to the original.
+///
+/// The copy gets a fresh random client id unless one is given, so the two profiles don't take
+/// over each other's session on the same broker.
+pub fn duplicate(
+    app: &tauri::AppHandle,
+    connection_id: &str,
+    overrides: ConnectionDuplicateOverrides,
+) -> Result<ConnectionDuplicate> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let name = trimmed(overrides.name);
+    let client_id = trimmed(overrides.client_id);
+    let host = trimmed(overrides.host);
+    if overrides.port == Some(0) {
+        bail!("port must be greater than zero");
+    }
+
+    config_store::update_config(app, |config| {
+        let index = config
+            .connections
+            .iter()
+            .position(|profile| profile.id == connection_id)
+            .ok_or_else(|| anyhow!("connection {connection_id} not found"))?;
+        let mut profile = config.connections[index].clone();
+        profile.id = random_id();
+        profile.name = name.unwrap_or_else(|| format!("{} (copy)", profile.name));
+        profile.client_id =
+            client_id.unwrap_or_else(|| format!("nexus-{:08x}", rand::random::<u32>()));
+        if let Some(host) = host {
+            // A new host no longer matches the linked broker, so the copy keeps its own.
+            profile.host = host;
+            profile.broker_id = None;
+        }
+        if let Some(port) = overrides.port {
+            profile.port = port;
+            profile.broker_id = None;
+        }
+
+        let topic_document = config
+            .connection_topic_docs
+            .get(connection_id)
+            .cloned()
+            .map(|mut document| {
+                document.updated_at = now_millis();
+                document
+            });
+        if let Some(document) = &topic_document {
+            config
+                .connection_topic_docs
+                .insert(profile.id.clone(), document.clone());
+        }
+        config.connections.insert(index + 1, profile.clone());
+        Ok(ConnectionDuplicate {
+            profile,
+            topic_document,
+        })
+    })
+}
+
+/// Refuses the action when one of `connection_ids` is a saved production profile whose name
+/// isn't among the lines of `confirmation`. Unsaved connections are not checked.
+pub fn require_confirmation(
+    app: &tauri::AppHandle,
+    connection_ids: &[&str],
+    confirmation: Option<&str>,
+) -> Result<()> {
+    let config = config_store::load_config(app)?;
+    for profile in &config.connections {
+        if profile.environment != Some(ConnectionEnvironment::Prod)
+            || !connection_ids.contains(&profile.id.as_str())
+        {
+            continue;
+        }
+        let confirmed = confirmation
+            .is_some_and(|names| names.lines().any(|name| name.trim() == profile.name.trim()));
+        if !confirmed {
+            bail!(
+                "'{}' is a production connection; confirm with its name to continue",
+                profile.name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// For unattended paths (control API, file watchers, hooks) that have nobody to confirm with:
+/// production profiles are refused outright.
+pub fn refuse_production(app: &tauri::AppHandle, connection_id: &str) -> Result<()> {
+    let config = config_store::load_config(app)?;
+    if let Some(profile) = config.connections.iter().find(|profile| {
+        profile.id == connection_id && profile.environment == Some(ConnectionEnvironment::Prod)
+    }) {
+        bail!(
+            "'{}' is a production connection; unattended publishing is disabled for it",
+            profile.name
+        );
+    }
+    Ok(())
+}
+
+/// A v4 UUID string, the id format the frontend uses for new records.
+pub fn random_id() -> String {
+    let mut bytes = rand::random::<[u8; 16]>();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
@@ -0,0 +1,151 @@
+//! Turns recorded history into a standalone replay file: NDJSON with relative offsets for
+//! tools that re-drive traffic, or a `mosquitto_pub` shell script that sleeps between messages.
+
+use crate::models::{
+    HistoryMessageRecord, MessageDirection, ReplayExportOptions, ReplayFormat, ReplayRetainMode,
+};
+use crate::mqtt::topic_matches;
+use anyhow::{Result, bail};
+use serde_json::json;
+
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+const MAX_SPEED: f64 = 1000.0;
+
+/// Renders records one at a time in timestamp order, keeping the timing state between them.
+pub struct ReplayWriter {
+    connection_id: String,
+    options: ReplayExportOptions,
+    started_at: Option<u64>,
+    previous_ts: u64,
+    /// Replay time of the previous message, after gap capping and speed-up.
+    offset_ms: u64,
+}
+
+impl ReplayWriter {
+    pub fn new(connection_id: &str, options: ReplayExportOptions) -> Result<Self> {
+        if !(options.speed > 0.0 && options.speed <= MAX_SPEED) {
+            bail!("replay speed must be greater than 0 and at most {MAX_SPEED}");
+        }
+        if let Some(filter) = options.topic_filter.as_deref().map(str::trim) {
+            if !filter.is_empty() && !crate::mqtt::is_valid_topic_filter(filter) {
+                bail!("invalid topic filter '{filter}'");
+            }
+        }
+        Ok(Self {
+            connection_id: connection_id.to_string(),
+            options,
+            started_at: None,
+            previous_ts: 0,
+            offset_ms: 0,
+        })
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self.options.format {
+            ReplayFormat::Ndjson => "ndjson",
+            ReplayFormat::Shell => "sh",
+        }
+    }
+
+    /// The lines for one record, preceded by the file header for the first one written.
+    pub fn line(&mut self, record: &HistoryMessageRecord) -> Option<String> {
+        if matches!(record.direction, MessageDirection::Out) && !self.options.include_outgoing {
+            return None;
+        }
+        if record.retain && self.options.retain == ReplayRetainMode::Skip {
+            return None;
+        }
+        let filter = self.options.topic_filter.as_deref().map(str::trim);
+        if filter.is_some_and(|filter| !filter.is_empty() && !topic_matches(filter, &record.topic))
+        {
+            return None;
+        }
+
+        let mut text = String::new();
+        let delay = match self.started_at {
+            None => {
+                self.started_at = Some(record.timestamp);
+                text.push_str(&self.header(record.timestamp));
+                0
+            }
+            Some(_) => {
+                let gap = record.timestamp.saturating_sub(self.previous_ts);
+                let gap = self.options.max_gap_ms.map_or(gap, |max| gap.min(max));
+                (gap as f64 / self.options.speed).round() as u64
+            }
+        };
+        self.previous_ts = record.timestamp;
+        self.offset_ms += delay;
+        let retain = record.retain && self.options.retain == ReplayRetainMode::Preserve;
+
+        match self.options.format {
+            ReplayFormat::Ndjson => text.push_str(
+                &json!({
+                    "offsetMs": self.offset_ms,
+                    "topic": record.topic,
+                    "payload": record.payload,
+                    "isBinary": record.is_binary,
+                    "qos": record.qos,
+                    "retain": retain,
+                })
+                .to_string(),
+            ),
+            ReplayFormat::Shell => {
+                if delay > 0 {
+                    text.push_str(&format!("sleep {}.{:03}\n", delay / 1000, delay % 1000));
+                }
+                // Binary payloads are piped in as their decoded bytes; `-m` would send the base64.
+                if record.is_binary {
+                    text.push_str(&format!(
+                        "printf '%s' {} | base64 -d | ",
+                        shell_quote(&record.payload)
+                    ));
+                }
+                text.push_str(&format!(
+                    "mosquitto_pub -h \"$HOST\" -p \"$PORT\" $MQTT_PUB_ARGS -t {} -q {}",
+                    shell_quote(&record.topic),
+                    record.qos
+                ));
+                if record.is_binary {
+                    text.push_str(" -s");
+                } else if record.payload.is_empty() {
+                    text.push_str(" -n");
+                } else {
+                    text.push_str(&format!(" -m {}", shell_quote(&record.payload)));
+                }
+                if retain {
+                    text.push_str(" -r");
+                }
+            }
+        }
+        Some(text)
+    }
+
+    fn header(&self, started_at: u64) -> String {
+        match self.options.format {
+            ReplayFormat::Ndjson => format!(
+                "{}\n",
+                json!({
+                    "replayVersion": REPLAY_FORMAT_VERSION,
+                    "connectionId": self.connection_id,
+                    "startedAt": started_at,
+                    "speed": self.options.speed,
+                })
+            ),
+            ReplayFormat::Shell => format!(
+                "#!/bin/sh\n\
+                 # Replays traffic recorded on connection {} from {started_at} (epoch ms) at {}x speed.\n\
+                 # Set MQTT_HOST, MQTT_PORT and MQTT_PUB_ARGS (e.g. \"-u user -P secret\") to pick the target.\n\
+                 HOST=\"${{MQTT_HOST:-localhost}}\"\n\
+                 PORT=\"${{MQTT_PORT:-1883}}\"\n",
+                self.connection_id.replace('\n', " "),
+                self.options.speed
+            ),
+        }
+    }
+}
+
+/// Single-quotes `text` for POSIX shells.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
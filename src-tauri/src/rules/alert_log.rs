@@ -0,0 +1,164 @@
+use crate::config_store;
+use crate::models::{AlertEventKind, AlertEventRecord};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+const ALERT_DB_FILE_NAME: &str = "alerts.db";
+const MAX_QUERY_LIMIT: usize = 1000;
+
+/// Inserts `events` in one transaction, returning them with their assigned ids.
+pub async fn append_many(
+    app: &AppHandle,
+    events: Vec<AlertEventRecord>,
+) -> Result<Vec<AlertEventRecord>> {
+    let path = db_path(app)?;
+    tokio::task::spawn_blocking(move || insert_events(&path, events))
+        .await
+        .context("alert log append task join failed")?
+}
+
+pub async fn query(
+    app: &AppHandle,
+    connection_id: Option<String>,
+    rule_id: Option<String>,
+    from_ts: Option<u64>,
+    to_ts: Option<u64>,
+    limit: usize,
+) -> Result<Vec<AlertEventRecord>> {
+    let path = db_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let bounded_limit = limit.clamp(1, MAX_QUERY_LIMIT);
+    tokio::task::spawn_blocking(move || {
+        query_events(
+            &path,
+            connection_id,
+            rule_id,
+            from_ts.map(|v| v as i64),
+            to_ts.map(|v| v as i64),
+            bounded_limit,
+        )
+    })
+    .await
+    .context("alert log query task join failed")?
+}
+
+fn db_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(config_store::config_dir_path(app)?.join(ALERT_DB_FILE_NAME))
+}
+
+fn open_connection(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)
+        .with_context(|| format!("failed to open sqlite file: {}", path.display()))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .context("failed to set sqlite busy timeout")?;
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS alert_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ts_ms INTEGER NOT NULL,
+            rule_id TEXT NOT NULL,
+            rule_name TEXT NOT NULL,
+            connection_id TEXT NOT NULL,
+            topic TEXT NOT NULL,
+            path TEXT NOT NULL,
+            value REAL NOT NULL,
+            threshold REAL NOT NULL,
+            kind INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_alert_ts_id ON alert_events(ts_ms DESC, id DESC);
+        ",
+    )
+    .context("failed to initialize alert log schema")?;
+    Ok(conn)
+}
+
+fn insert_events(path: &Path, mut events: Vec<AlertEventRecord>) -> Result<Vec<AlertEventRecord>> {
+    let mut conn = open_connection(path)?;
+    let tx = conn
+        .transaction()
+        .context("failed to start alert log transaction")?;
+    {
+        let mut statement = tx
+            .prepare(
+                "INSERT INTO alert_events
+                    (ts_ms, rule_id, rule_name, connection_id, topic, path, value, threshold, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .context("failed to prepare alert insert")?;
+        for event in &mut events {
+            statement
+                .execute(params![
+                    event.timestamp as i64,
+                    event.rule_id,
+                    event.rule_name,
+                    event.connection_id,
+                    event.topic,
+                    event.path,
+                    event.value,
+                    event.threshold,
+                    if event.kind == AlertEventKind::Raised {
+                        1
+                    } else {
+                        0
+                    },
+                ])
+                .context("failed to insert alert event")?;
+            event.id = tx.last_insert_rowid();
+        }
+    }
+    tx.commit().context("failed to commit alert events")?;
+    Ok(events)
+}
+
+fn query_events(
+    path: &Path,
+    connection_id: Option<String>,
+    rule_id: Option<String>,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    limit: usize,
+) -> Result<Vec<AlertEventRecord>> {
+    let conn = open_connection(path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, ts_ms, rule_id, rule_name, connection_id, topic, path, value, threshold, kind
+             FROM alert_events
+             WHERE (?1 IS NULL OR connection_id = ?1)
+               AND (?2 IS NULL OR rule_id = ?2)
+               AND (?3 IS NULL OR ts_ms >= ?3)
+               AND (?4 IS NULL OR ts_ms <= ?4)
+             ORDER BY ts_ms DESC, id DESC
+             LIMIT ?5",
+        )
+        .context("failed to prepare alert log query")?;
+
+    stmt.query_map(
+        params![connection_id, rule_id, from_ts, to_ts, limit as i64],
+        |row| {
+            Ok(AlertEventRecord {
+                id: row.get(0)?,
+                timestamp: row.get::<_, i64>(1)? as u64,
+                rule_id: row.get(2)?,
+                rule_name: row.get(3)?,
+                connection_id: row.get(4)?,
+                topic: row.get(5)?,
+                path: row.get(6)?,
+                value: row.get(7)?,
+                threshold: row.get(8)?,
+                kind: if row.get::<_, i64>(9)? == 1 {
+                    AlertEventKind::Raised
+                } else {
+                    AlertEventKind::Cleared
+                },
+            })
+        },
+    )
+    .context("failed to execute alert log query")?
+    .collect::<rusqlite::Result<Vec<_>>>()
+    .context("failed to map alert log rows")
+}
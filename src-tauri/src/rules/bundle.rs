@@ -0,0 +1,77 @@
+//! Rule bundles: automation rules written to a standalone file so a test scenario can be
+//! shared without the rest of the config. The command allow-list is deliberately left out; an
+//! imported rule that runs a program only loads once the receiver allowed that program.
+
+use crate::models::{AutomationConfig, AutomationRule, RuleBundle};
+use anyhow::{Context, Result, bail};
+use std::fs;
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Bundles the rules with the given ids, or every rule when `rule_ids` is `None`.
+pub fn build(
+    config: &AutomationConfig,
+    rule_ids: Option<&[String]>,
+    exported_at: u64,
+) -> RuleBundle {
+    let rules = config
+        .rules
+        .iter()
+        .filter(|rule| rule_ids.is_none_or(|ids| ids.contains(&rule.id)))
+        .cloned()
+        .collect();
+    RuleBundle {
+        version: BUNDLE_VERSION,
+        exported_at,
+        rules,
+    }
+}
+
+/// Reads a bundle from a file path or inline JSON.
+pub fn load(path_or_content: &str) -> Result<RuleBundle> {
+    let trimmed = path_or_content.trim();
+    if trimmed.is_empty() {
+        bail!("rule bundle source is empty");
+    }
+    let content = if trimmed.starts_with('{') {
+        path_or_content.to_string()
+    } else {
+        fs::read_to_string(trimmed)
+            .with_context(|| format!("failed to read rule bundle: {trimmed}"))?
+    };
+
+    let bundle =
+        serde_json::from_str::<RuleBundle>(&content).context("file is not a rule bundle")?;
+    if bundle.version > BUNDLE_VERSION {
+        bail!(
+            "rule bundle version {} is newer than supported version {BUNDLE_VERSION}",
+            bundle.version
+        );
+    }
+    Ok(bundle)
+}
+
+/// Rules after importing `incoming` into `current`: rules with a known id replace the existing
+/// one in place, the rest are appended. With `replace` the current rules are dropped first.
+/// Returns the merged rules and how many were added and replaced.
+pub fn merge(
+    current: Vec<AutomationRule>,
+    incoming: Vec<AutomationRule>,
+    replace: bool,
+) -> (Vec<AutomationRule>, usize, usize) {
+    let mut rules = if replace { Vec::new() } else { current };
+    let (mut added, mut replaced) = (0, 0);
+    for rule in incoming {
+        match rules.iter_mut().find(|existing| existing.id == rule.id) {
+            Some(existing) => {
+                *existing = rule;
+                replaced += 1;
+            }
+            None => {
+                rules.push(rule);
+                added += 1;
+            }
+        }
+    }
+    (rules, added, replaced)
+}
@@ -0,0 +1,82 @@
+use crate::models::{CommandPayloadMode, MqttBatchItem};
+use anyhow::{Context, Result, anyhow};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{self, Duration};
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const MAX_TIMEOUT_MS: u64 = 300_000;
+
+pub async fn run_command(
+    program: &str,
+    args: &[String],
+    payload_mode: CommandPayloadMode,
+    timeout_ms: Option<u64>,
+    connection_id: &str,
+    item: &MqttBatchItem,
+) -> Result<Option<String>> {
+    let mut command = Command::new(program);
+    command
+        .args(
+            args.iter()
+                .map(|arg| expand_placeholders(arg, connection_id, item)),
+        )
+        .env("MQTT_CONNECTION_ID", connection_id)
+        .env("MQTT_TOPIC", &item.topic)
+        .env("MQTT_QOS", item.qos.to_string())
+        .env("MQTT_RETAIN", if item.retain { "1" } else { "0" })
+        .env("MQTT_TIMESTAMP", item.timestamp.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    if payload_mode == CommandPayloadMode::Argument {
+        command.arg(item.payload.as_str());
+    }
+    command.stdin(if payload_mode == CommandPayloadMode::Stdin {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    });
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("failed to start {program}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(item.payload.as_bytes())
+            .await
+            .context("failed to write payload to command stdin")?;
+        drop(stdin);
+    }
+
+    let timeout = Duration::from_millis(
+        timeout_ms
+            .unwrap_or(DEFAULT_TIMEOUT_MS)
+            .clamp(1, MAX_TIMEOUT_MS),
+    );
+    let output = time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow!("{program} timed out after {}ms", timeout.as_millis()))?
+        .with_context(|| format!("failed to wait for {program}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(anyhow!("{program} exited with {}: {stderr}", output.status));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if stdout.is_empty() {
+        None
+    } else {
+        Some(stdout)
+    })
+}
+
+fn expand_placeholders(arg: &str, connection_id: &str, item: &MqttBatchItem) -> String {
+    arg.replace("{connectionId}", connection_id)
+        .replace("{topic}", &item.topic)
+        .replace("{payload}", &item.payload)
+}
@@ -0,0 +1,81 @@
+use super::{AlertState, json_path, rule_matches, step_threshold, validate_rule};
+use crate::models::{
+    AlertEventKind, AutomationRule, MqttBatchItem, RuleAction, RuleTestMatch, RuleTestResult,
+};
+use std::collections::HashMap;
+
+/// Replays messages through a rule with fresh threshold state and reports what would fire.
+/// Nothing is executed, logged, or emitted.
+pub fn evaluate(
+    rule: &AutomationRule,
+    messages: &[(String, MqttBatchItem)],
+    allow_list: &[String],
+) -> RuleTestResult {
+    let mut issues = Vec::new();
+    if let Err(error) = validate_rule(rule, allow_list) {
+        issues.push(error.to_string());
+    }
+    if !rule.enabled {
+        issues.push("rule is disabled and will not run live".to_string());
+    }
+
+    let mut states: HashMap<(String, String), AlertState> = HashMap::new();
+    let mut matches = Vec::new();
+
+    for (connection_id, item) in messages {
+        if !rule_matches(rule, connection_id, item) {
+            continue;
+        }
+
+        let (value, alert) = match &rule.threshold {
+            Some(threshold) => {
+                let value = json_path::select_number(&item.payload, &threshold.path);
+                let alert = value.and_then(|value| {
+                    let state = states
+                        .entry((connection_id.clone(), item.topic.clone()))
+                        .or_default();
+                    step_threshold(state, threshold, value)
+                });
+                (value, alert)
+            }
+            None => (None, None),
+        };
+
+        let fires = match &rule.threshold {
+            Some(_) => alert == Some(AlertEventKind::Raised),
+            None => true,
+        };
+
+        matches.push(RuleTestMatch {
+            topic: item.topic.clone(),
+            payload: item.payload.to_string(),
+            timestamp: item.timestamp,
+            value,
+            alert,
+            actions: if fires {
+                rule.actions.iter().map(describe_action).collect()
+            } else {
+                Vec::new()
+            },
+        });
+    }
+
+    RuleTestResult {
+        evaluated: messages.len(),
+        matched: matches.len(),
+        matches,
+        issues,
+    }
+}
+
+fn describe_action(action: &RuleAction) -> String {
+    match action {
+        RuleAction::RunCommand { program, .. } => format!("runCommand: {program}"),
+        RuleAction::Notify { title } => match title {
+            Some(title) => format!("notify: {title}"),
+            None => "notify".to_string(),
+        },
+        RuleAction::Webhook { url, .. } => format!("webhook: {url}"),
+        RuleAction::Influx { url, bucket, .. } => format!("influx: {url} ({bucket})"),
+    }
+}
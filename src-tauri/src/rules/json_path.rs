@@ -0,0 +1,48 @@
+use serde_json::Value;
+
+/// Resolves a small JSONPath subset: `$.a.b`, `$.list[0]`, `$['key with space']`.
+pub fn select<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut rest = path.trim();
+    rest = rest.strip_prefix('$').unwrap_or(rest);
+    let mut current = root;
+
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let key = &after_dot[..end];
+            if key.is_empty() {
+                return None;
+            }
+            current = current.get(key)?;
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']')?;
+            let segment = after_bracket[..end].trim();
+            let quoted = segment
+                .strip_prefix(['\'', '"'])
+                .and_then(|inner| inner.strip_suffix(['\'', '"']));
+            current = match quoted {
+                Some(key) => current.get(key)?,
+                None => current.get(segment.parse::<usize>().ok()?)?,
+            };
+            rest = &after_bracket[end + 1..];
+        } else {
+            // Allow paths written without the leading `$.`, e.g. `sensor.temperature`.
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            current = current.get(&rest[..end])?;
+            rest = &rest[end..];
+        }
+    }
+
+    Some(current)
+}
+
+pub fn select_number(payload: &str, path: &str) -> Option<f64> {
+    let root = serde_json::from_str::<Value>(payload).ok()?;
+    match select(&root, path)? {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        Value::Bool(flag) => Some(if *flag { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
@@ -0,0 +1,497 @@
+pub mod alert_log;
+pub mod bundle;
+pub mod command;
+pub mod dry_run;
+pub mod json_path;
+pub mod webhook;
+
+use crate::config_store;
+use crate::integrations::{influx, validate_http_url};
+use crate::models::{
+    AlertEventKind, AlertEventRecord, AlertThreshold, AutomationConfig, AutomationRule,
+    MessageDirection, MqttBatchItem, RuleAction, RuleActionPayload, RuleBundleImportResult,
+    ThresholdOperator,
+};
+use crate::mqtt::{now_millis, topic_matches};
+use anyhow::{Result, anyhow};
+use dashmap::{DashMap, DashSet};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{RwLock, Semaphore};
+
+const AUTOMATION_FILE_NAME: &str = "automation.json";
+const MAX_ACTIONS_PER_RULE: usize = 4;
+
+#[derive(Clone, Default)]
+pub struct RuleManager {
+    inner: Arc<RuleInner>,
+}
+
+#[derive(Default)]
+struct RuleInner {
+    config: RwLock<Option<AutomationConfig>>,
+    alert_states: DashMap<String, AlertState>,
+    /// Running-action permits per rule id.
+    action_slots: DashMap<String, Arc<Semaphore>>,
+    /// Rules currently dropping actions, so the warning is logged once per episode.
+    saturated: DashSet<String>,
+}
+
+#[derive(Default)]
+struct AlertState {
+    consecutive: u32,
+    active: bool,
+}
+
+#[derive(Clone)]
+struct ActionContext {
+    rule_id: String,
+    rule_name: String,
+    connection_id: String,
+    item: MqttBatchItem,
+    alert: Option<AlertEventRecord>,
+}
+
+impl RuleManager {
+    pub async fn config(&self, app: &AppHandle) -> Result<AutomationConfig> {
+        if let Some(config) = self.inner.config.read().await.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let mut slot = self.inner.config.write().await;
+        if let Some(config) = slot.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let loaded = config_store::load_document::<AutomationConfig>(app, AUTOMATION_FILE_NAME)?;
+        *slot = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    pub async fn save_rules(
+        &self,
+        app: &AppHandle,
+        rules: Vec<AutomationRule>,
+    ) -> Result<AutomationConfig> {
+        let saved = self
+            .update(app, |config| {
+                for rule in &rules {
+                    validate_rule(rule, &config.command_allow_list)?;
+                }
+                config.rules = rules;
+                Ok(())
+            })
+            .await?;
+        self.inner.alert_states.clear();
+        Ok(saved)
+    }
+
+    /// Merges the rules of an imported bundle into the saved ones; see [`bundle::merge`]. Every
+    /// imported rule is validated first, so a bad bundle changes nothing.
+    pub async fn import_rules(
+        &self,
+        app: &AppHandle,
+        rules: Vec<AutomationRule>,
+        replace: bool,
+    ) -> Result<RuleBundleImportResult> {
+        let mut counts = (0, 0);
+        let config = self
+            .update(app, |config| {
+                for rule in &rules {
+                    validate_rule(rule, &config.command_allow_list)?;
+                }
+                let (merged, added, replaced) =
+                    bundle::merge(std::mem::take(&mut config.rules), rules, replace);
+                config.rules = merged;
+                counts = (added, replaced);
+                Ok(())
+            })
+            .await?;
+        self.inner.alert_states.clear();
+        Ok(RuleBundleImportResult {
+            added: counts.0,
+            replaced: counts.1,
+            config,
+        })
+    }
+
+    pub async fn allow_command(&self, app: &AppHandle, program: &str) -> Result<AutomationConfig> {
+        let program = program.trim().to_string();
+        if program.is_empty() {
+            return Err(anyhow!("command path is required"));
+        }
+
+        self.update(app, |config| {
+            if !config.command_allow_list.contains(&program) {
+                config.command_allow_list.push(program);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn revoke_command(&self, app: &AppHandle, program: &str) -> Result<AutomationConfig> {
+        let program = program.trim();
+        self.update(app, |config| {
+            config.command_allow_list.retain(|entry| entry != program);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update<F>(&self, app: &AppHandle, apply: F) -> Result<AutomationConfig>
+    where
+        F: FnOnce(&mut AutomationConfig) -> Result<()>,
+    {
+        // Holding the write lock across read, change and save keeps concurrent updates from
+        // overwriting each other.
+        let mut slot = self.inner.config.write().await;
+        let mut next = match slot.as_ref() {
+            Some(config) => config.clone(),
+            None => config_store::load_document::<AutomationConfig>(app, AUTOMATION_FILE_NAME)?,
+        };
+        apply(&mut next)?;
+        config_store::save_document(app, AUTOMATION_FILE_NAME, &next)?;
+        *slot = Some(next.clone());
+        Ok(next)
+    }
+
+    pub async fn process_batch(
+        &self,
+        app: &AppHandle,
+        connection_id: &str,
+        batch: &[MqttBatchItem],
+    ) {
+        let config = match self.config(app).await {
+            Ok(config) => config,
+            Err(error) => {
+                tracing::error!("failed to load automation rules: {error}");
+                return;
+            }
+        };
+
+        let mut alerts = Vec::new();
+        for rule in config.rules.iter().filter(|rule| rule.enabled) {
+            for item in batch {
+                if !matches!(item.direction, MessageDirection::In)
+                    || !rule_matches(rule, connection_id, item)
+                {
+                    continue;
+                }
+
+                let context = ActionContext {
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    connection_id: connection_id.to_string(),
+                    item: item.clone(),
+                    alert: None,
+                };
+                match &rule.threshold {
+                    Some(threshold) => {
+                        if let Some(event) =
+                            self.evaluate_threshold(rule, threshold, connection_id, item)
+                        {
+                            alerts.push((rule.clone(), context, event));
+                        }
+                    }
+                    None => self.dispatch(app, rule, context, &config.command_allow_list),
+                }
+            }
+        }
+
+        // Alerts are written off the batch path, so an alert storm can't stall message ingestion.
+        if !alerts.is_empty() {
+            let manager = self.clone();
+            let app = app.clone();
+            let allow_list = config.command_allow_list;
+            tokio::spawn(async move { manager.record_alerts(&app, alerts, &allow_list).await });
+        }
+    }
+
+    /// Starts the rule's actions for one matching message. A rule runs at most
+    /// [`MAX_ACTIONS_PER_RULE`] actions at a time; while it is at the limit, further actions are
+    /// dropped so a flood of matching messages can't spawn a process or request per message.
+    fn dispatch(
+        &self,
+        app: &AppHandle,
+        rule: &AutomationRule,
+        context: ActionContext,
+        allow_list: &[String],
+    ) {
+        let slots = Arc::clone(
+            self.inner
+                .action_slots
+                .entry(rule.id.clone())
+                .or_insert_with(|| Arc::new(Semaphore::new(MAX_ACTIONS_PER_RULE)))
+                .value(),
+        );
+        for action in &rule.actions {
+            let Ok(permit) = Arc::clone(&slots).try_acquire_owned() else {
+                if self.inner.saturated.insert(rule.id.clone()) {
+                    tracing::warn!(
+                        rule = rule.name.as_str(),
+                        "rule is at its action limit; dropping actions until some finish"
+                    );
+                }
+                continue;
+            };
+            self.inner.saturated.remove(&rule.id);
+            let app = app.clone();
+            let context = context.clone();
+            let action = action.clone();
+            let allow_list = allow_list.to_vec();
+            tokio::spawn(async move {
+                run_action(app, context, action, allow_list).await;
+                drop(permit);
+            });
+        }
+    }
+
+    fn evaluate_threshold(
+        &self,
+        rule: &AutomationRule,
+        threshold: &AlertThreshold,
+        connection_id: &str,
+        item: &MqttBatchItem,
+    ) -> Option<AlertEventRecord> {
+        let value = json_path::select_number(&item.payload, &threshold.path)?;
+        let key = format!("{}\u{1f}{}\u{1f}{}", rule.id, connection_id, item.topic);
+        let mut state = self.inner.alert_states.entry(key).or_default();
+        let kind = step_threshold(&mut state, threshold, value)?;
+
+        Some(AlertEventRecord {
+            id: 0,
+            timestamp: now_millis(),
+            rule_id: rule.id.clone(),
+            rule_name: rule.name.clone(),
+            connection_id: connection_id.to_string(),
+            topic: item.topic.clone(),
+            path: threshold.path.clone(),
+            value,
+            threshold: threshold.value,
+            kind,
+        })
+    }
+
+    /// Stores one batch's alerts in a single transaction, then surfaces them and fires the
+    /// actions of raised ones with the stored record.
+    async fn record_alerts(
+        &self,
+        app: &AppHandle,
+        alerts: Vec<(AutomationRule, ActionContext, AlertEventRecord)>,
+        allow_list: &[String],
+    ) {
+        let events = alerts
+            .iter()
+            .map(|(_, _, event)| event.clone())
+            .collect::<Vec<_>>();
+        let events = match alert_log::append_many(app, events.clone()).await {
+            Ok(stored) => stored,
+            Err(error) => {
+                tracing::warn!("failed to persist alert events: {error}");
+                events
+            }
+        };
+        for ((rule, mut context, _), event) in alerts.into_iter().zip(events) {
+            let _ = app.emit("rule-alert", event.clone());
+            // Cleared alerts are logged and surfaced, but only a raise fires actions.
+            if event.kind == AlertEventKind::Cleared {
+                continue;
+            }
+            context.alert = Some(event);
+            self.dispatch(app, &rule, context, allow_list);
+        }
+    }
+}
+
+pub fn rule_matches(rule: &AutomationRule, connection_id: &str, item: &MqttBatchItem) -> bool {
+    if let Some(expected) = rule.connection_id.as_deref() {
+        if expected != connection_id {
+            return false;
+        }
+    }
+
+    if !topic_matches(&rule.topic_filter, &item.topic) {
+        return false;
+    }
+
+    match rule.payload_contains.as_deref() {
+        Some(needle) if !needle.is_empty() => item.payload.contains(needle),
+        _ => true,
+    }
+}
+
+fn step_threshold(
+    state: &mut AlertState,
+    threshold: &AlertThreshold,
+    value: f64,
+) -> Option<AlertEventKind> {
+    if state.active {
+        let clear_at = threshold.clear_value.unwrap_or(threshold.value);
+        if breached(threshold.operator, value, clear_at) {
+            return None;
+        }
+        state.active = false;
+        return Some(AlertEventKind::Cleared);
+    }
+
+    if !breached(threshold.operator, value, threshold.value) {
+        state.consecutive = 0;
+        return None;
+    }
+    state.consecutive += 1;
+    if state.consecutive < threshold.consecutive.max(1) {
+        return None;
+    }
+    state.consecutive = 0;
+    state.active = true;
+    Some(AlertEventKind::Raised)
+}
+
+fn breached(operator: ThresholdOperator, value: f64, threshold: f64) -> bool {
+    match operator {
+        ThresholdOperator::Gt => value > threshold,
+        ThresholdOperator::Gte => value >= threshold,
+        ThresholdOperator::Lt => value < threshold,
+        ThresholdOperator::Lte => value <= threshold,
+        ThresholdOperator::Eq => value == threshold,
+        ThresholdOperator::Neq => value != threshold,
+    }
+}
+
+fn validate_rule(rule: &AutomationRule, allow_list: &[String]) -> Result<()> {
+    if rule.topic_filter.trim().is_empty() {
+        return Err(anyhow!("rule '{}' has an empty topic filter", rule.name));
+    }
+
+    if let Some(threshold) = &rule.threshold {
+        if threshold.path.trim().is_empty() {
+            return Err(anyhow!("rule '{}' has an empty threshold path", rule.name));
+        }
+    }
+
+    for action in &rule.actions {
+        match action {
+            RuleAction::RunCommand { program, .. } => {
+                if !allow_list.iter().any(|entry| entry == program.trim()) {
+                    return Err(anyhow!(
+                        "rule '{}' runs '{}', which is not in the command allow-list",
+                        rule.name,
+                        program
+                    ));
+                }
+            }
+            RuleAction::Webhook { url, .. } => webhook::validate_url(url)?,
+            RuleAction::Notify { .. } => {}
+            RuleAction::Influx { url, bucket, .. } => {
+                validate_http_url(url, "InfluxDB")?;
+                if bucket.trim().is_empty() {
+                    return Err(anyhow!("rule '{}' has an empty InfluxDB bucket", rule.name));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_action(
+    app: AppHandle,
+    context: ActionContext,
+    action: RuleAction,
+    allow_list: Vec<String>,
+) {
+    let (action_name, result) = match &action {
+        RuleAction::RunCommand {
+            program,
+            args,
+            payload_mode,
+            timeout_ms,
+        } => {
+            let result = if allow_list.iter().any(|entry| entry == program.trim()) {
+                command::run_command(
+                    program.trim(),
+                    args,
+                    *payload_mode,
+                    *timeout_ms,
+                    &context.connection_id,
+                    &context.item,
+                )
+                .await
+            } else {
+                Err(anyhow!("'{program}' is not in the command allow-list"))
+            };
+            ("runCommand", result)
+        }
+        RuleAction::Notify { title } => ("notify", notify(&app, title.as_deref(), &context)),
+        RuleAction::Webhook { url, headers } => (
+            "webhook",
+            webhook::post(
+                url,
+                headers,
+                &context.rule_id,
+                &context.connection_id,
+                &context.item,
+                context.alert.as_ref(),
+            )
+            .await,
+        ),
+        RuleAction::Influx {
+            url,
+            org,
+            bucket,
+            token,
+            mapping,
+        } => {
+            let item = &context.item;
+            let result = match influx::to_line(mapping, &item.topic, &item.payload, item.timestamp)
+            {
+                Some(line) => influx::write(url, org.as_deref(), bucket, token.as_deref(), line)
+                    .await
+                    .map(|_| None),
+                None => Ok(Some("message produced no fields; skipped".to_string())),
+            };
+            ("influx", result)
+        }
+    };
+
+    let (ok, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(error) => (false, Some(error.to_string())),
+    };
+
+    let _ = app.emit(
+        "rule-action",
+        RuleActionPayload {
+            rule_id: context.rule_id,
+            connection_id: context.connection_id,
+            topic: context.item.topic,
+            action: action_name.to_string(),
+            ok,
+            detail,
+        },
+    );
+}
+
+fn notify(app: &AppHandle, title: Option<&str>, context: &ActionContext) -> Result<Option<String>> {
+    let title = title
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(&context.rule_name);
+    let body = match &context.alert {
+        Some(alert) => format!(
+            "{}: {} = {} (threshold {})",
+            alert.topic, alert.path, alert.value, alert.threshold
+        ),
+        None => context.item.topic.clone(),
+    };
+
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| anyhow!("failed to show notification: {e}"))?;
+    Ok(None)
+}
@@ -0,0 +1,57 @@
+use crate::integrations::{http_client, validate_http_url};
+use crate::models::{AlertEventRecord, MqttBatchItem};
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookBody<'a> {
+    rule_id: &'a str,
+    connection_id: &'a str,
+    topic: &'a str,
+    payload: &'a str,
+    qos: u8,
+    retain: bool,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alert: Option<&'a AlertEventRecord>,
+}
+
+pub fn validate_url(url: &str) -> Result<()> {
+    validate_http_url(url, "webhook")
+}
+
+pub async fn post(
+    url: &str,
+    headers: &HashMap<String, String>,
+    rule_id: &str,
+    connection_id: &str,
+    item: &MqttBatchItem,
+    alert: Option<&AlertEventRecord>,
+) -> Result<Option<String>> {
+    validate_url(url)?;
+
+    let body = WebhookBody {
+        rule_id,
+        connection_id,
+        topic: &item.topic,
+        payload: &item.payload,
+        qos: item.qos,
+        retain: item.retain,
+        timestamp: item.timestamp,
+        alert,
+    };
+
+    let mut request = http_client().post(url.trim()).json(&body);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = request.send().await.context("webhook request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow!("webhook responded with {status}"));
+    }
+    Ok(Some(status.to_string()))
+}
@@ -0,0 +1,105 @@
+use crate::config_store;
+use crate::models::{GlobalShortcutPayload, ShortcutAction, ShortcutsConfig};
+use crate::state::AppState;
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+pub const SHORTCUTS_FILE_NAME: &str = "shortcuts.json";
+
+/// Registered shortcut ids mapped to the action they trigger.
+#[derive(Clone, Default)]
+pub struct ShortcutRegistry {
+    actions: Arc<DashMap<u32, ShortcutAction>>,
+}
+
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(handle_shortcut)
+        .build()
+}
+
+/// Registers the persisted bindings on startup.
+pub fn init(app: &AppHandle) {
+    let config = match load(app) {
+        Ok(config) => config,
+        Err(error) => {
+            tracing::warn!("failed to load shortcuts: {error:#}");
+            return;
+        }
+    };
+    if let Err(error) = apply(app, &config) {
+        tracing::warn!("failed to register global shortcuts: {error:#}");
+    }
+}
+
+pub fn load(app: &AppHandle) -> Result<ShortcutsConfig> {
+    config_store::load_document::<ShortcutsConfig>(app, SHORTCUTS_FILE_NAME)
+}
+
+/// Validates and registers the bindings, then persists them; nothing is saved if any fails.
+pub fn save(app: &AppHandle, config: &ShortcutsConfig) -> Result<()> {
+    parse_bindings(config)?;
+    apply(app, config)?;
+    config_store::save_document(app, SHORTCUTS_FILE_NAME, config)
+}
+
+fn parse_bindings(config: &ShortcutsConfig) -> Result<Vec<(Shortcut, ShortcutAction)>> {
+    let mut parsed: Vec<(Shortcut, ShortcutAction)> = Vec::new();
+    for binding in config.bindings.iter().filter(|binding| binding.enabled) {
+        let shortcut = binding
+            .accelerator
+            .trim()
+            .parse::<Shortcut>()
+            .map_err(|error| anyhow!("invalid shortcut '{}': {error}", binding.accelerator))?;
+        if parsed
+            .iter()
+            .any(|(existing, _)| existing.id() == shortcut.id())
+        {
+            return Err(anyhow!(
+                "shortcut '{}' is assigned to more than one action",
+                binding.accelerator
+            ));
+        }
+        parsed.push((shortcut, binding.action));
+    }
+    Ok(parsed)
+}
+
+fn apply(app: &AppHandle, config: &ShortcutsConfig) -> Result<()> {
+    let bindings = parse_bindings(config)?;
+    let manager = app.global_shortcut();
+    let registry = app.state::<AppState>().shortcuts.clone();
+
+    manager.unregister_all()?;
+    registry.actions.clear();
+    for (shortcut, action) in bindings {
+        manager
+            .register(shortcut)
+            .map_err(|error| anyhow!("failed to register '{shortcut}': {error}"))?;
+        registry.actions.insert(shortcut.id(), action);
+    }
+    Ok(())
+}
+
+fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let registry = app.state::<AppState>().shortcuts.clone();
+    let Some(action) = registry
+        .actions
+        .get(&shortcut.id())
+        .map(|entry| *entry.value())
+    else {
+        return;
+    };
+
+    if action == ShortcutAction::ToggleWindow {
+        crate::tray::toggle_main_window(app);
+    }
+    let _ = app.emit("global-shortcut", GlobalShortcutPayload { action });
+}
@@ -0,0 +1,127 @@
+//! Retained-state snapshots: the last value of every topic at a point in time, exported from
+//! history and replayed onto a broker as retained messages.
+
+use crate::models::{HistoryMessageRecord, Payload, RetainedSnapshot, RetainedSnapshotEntry};
+use crate::mqtt::topic_matches;
+use anyhow::{Context, Result, bail};
+use std::fs;
+
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Builds a snapshot from the latest record of each topic. Empty payloads are left out because a
+/// retained empty message is how a topic's retained value gets cleared.
+pub fn build(
+    connection_id: &str,
+    captured_at: u64,
+    topic_filter: Option<&str>,
+    records: Vec<HistoryMessageRecord>,
+) -> RetainedSnapshot {
+    let entries = records
+        .into_iter()
+        .filter(|record| !record.payload.is_empty())
+        .filter(|record| topic_filter.is_none_or(|filter| topic_matches(filter, &record.topic)))
+        .map(|record| RetainedSnapshotEntry {
+            topic: record.topic,
+            payload: record.payload,
+            is_binary: record.is_binary,
+            qos: record.qos,
+            timestamp: record.timestamp,
+        })
+        .collect();
+
+    RetainedSnapshot {
+        version: SNAPSHOT_VERSION,
+        connection_id: connection_id.to_string(),
+        captured_at,
+        entries,
+    }
+}
+
+/// Reads a snapshot from a file path or inline JSON and checks it can be republished.
+pub fn load(path_or_content: &str) -> Result<RetainedSnapshot> {
+    let trimmed = path_or_content.trim();
+    if trimmed.is_empty() {
+        bail!("snapshot source is empty");
+    }
+    let content = if trimmed.starts_with('{') {
+        path_or_content.to_string()
+    } else {
+        fs::read_to_string(trimmed)
+            .with_context(|| format!("failed to read snapshot file: {trimmed}"))?
+    };
+
+    let snapshot = serde_json::from_str::<RetainedSnapshot>(&content)
+        .context("file is not a retained snapshot")?;
+    if snapshot.version > SNAPSHOT_VERSION {
+        bail!(
+            "snapshot version {} is newer than supported version {SNAPSHOT_VERSION}",
+            snapshot.version
+        );
+    }
+    for entry in &snapshot.entries {
+        if entry.topic.is_empty() || entry.topic.contains(['+', '#', '\0']) {
+            bail!("snapshot contains an invalid topic name: {:?}", entry.topic);
+        }
+        if entry.is_binary && Payload::from_base64(&entry.payload).is_err() {
+            bail!("snapshot entry {} is not valid base64", entry.topic);
+        }
+        if entry.qos > 2 {
+            bail!(
+                "snapshot entry {} has invalid QoS {}",
+                entry.topic,
+                entry.qos
+            );
+        }
+    }
+    Ok(snapshot)
+}
+
+/// The payload to republish for an entry; binary entries go out as their original bytes.
+pub fn entry_payload(entry: &RetainedSnapshotEntry) -> Result<Payload> {
+    if entry.is_binary {
+        Payload::from_base64(&entry.payload)
+            .with_context(|| format!("snapshot entry {} is not valid base64", entry.topic))
+    } else {
+        Ok(Payload::from(entry.payload.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MessageDirection;
+    use bytes::Bytes;
+
+    #[test]
+    fn binary_payload_survives_build_and_import() {
+        let original = Bytes::from_static(&[0x00, 0xff, 0xfe, 0x80, b'a', 0xc3]);
+        let (payload, is_binary) = Payload::from_wire(original.clone());
+        assert!(is_binary);
+        let record = HistoryMessageRecord {
+            id: 1,
+            timestamp: 1_000,
+            topic: "devices/1/blob".to_string(),
+            payload: payload.as_str().to_string(),
+            qos: 1,
+            retain: true,
+            direction: MessageDirection::In,
+            dup: false,
+            packet_id: None,
+            correlation_id: None,
+            response_topic: None,
+            payload_timestamp: None,
+            user_properties: Vec::new(),
+            is_binary,
+        };
+
+        let snapshot = build("conn", 2_000, None, vec![record]);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let loaded = load(&json).unwrap();
+
+        let entry = &loaded.entries[0];
+        assert!(entry.is_binary);
+        let republished = entry_payload(entry).unwrap();
+        assert!(republished.is_binary());
+        assert_eq!(republished.into_bytes(), original);
+    }
+}
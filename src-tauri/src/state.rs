@@ -1,10 +1,72 @@
+use crate::broker::BrokerManager;
+use crate::catalog::discovery::TopicDiscoveryManager;
+use crate::control_api::ControlApiManager;
+use crate::credentials::CredentialMonitor;
+use crate::export_log::ExportLog;
+use crate::feed::FeedManager;
 use crate::history::HistoryManager;
+use crate::integrations::file_watch::FileWatchManager;
+use crate::integrations::kafka::KafkaBridgeManager;
+use crate::integrations::socket_tap::SocketTapManager;
+use crate::jobs::JobManager;
 use crate::models::AiConfig;
+use crate::mqtt::ack::PublishAcks;
+use crate::mqtt::echo::EchoLatency;
+use crate::mqtt::flow::FlowControl;
+use crate::mqtt::health::HealthMonitor;
+use crate::mqtt::hooks::SessionHooks;
+use crate::mqtt::journal::PublishJournal;
+use crate::mqtt::keepalive::KeepAliveMonitor;
 use crate::mqtt::manager::MqttManager;
+use crate::mqtt::memory::MemoryGuard;
+use crate::mqtt::qos2::Qos2Tracer;
+use crate::mqtt::recent::RecentMessages;
+use crate::mqtt::request::PendingRequests;
+use crate::mqtt::suback::SubscriptionAcks;
+use crate::mqtt::sys_stats::SysStatsTracker;
+use crate::mqtt::transform::TransformRegistry;
+use crate::payload_time::PayloadTimestamps;
+use crate::plugins::PluginRegistry;
+use crate::rules::RuleManager;
+use crate::shortcuts::ShortcutRegistry;
+use crate::storage_guard::StorageGuard;
+use crate::tray::TrayState;
+use crate::windows::WindowRegistry;
 
 pub struct AppState {
     pub mqtt_manager: MqttManager,
     pub history_manager: HistoryManager,
+    pub storage_guard: StorageGuard,
+    pub rule_manager: RuleManager,
+    pub broker_manager: BrokerManager,
+    pub kafka_bridges: KafkaBridgeManager,
+    pub file_watchers: FileWatchManager,
+    pub socket_taps: SocketTapManager,
+    pub control_api: ControlApiManager,
+    pub feed: FeedManager,
+    pub topic_discovery: TopicDiscoveryManager,
+    pub sys_stats: SysStatsTracker,
+    pub tray: TrayState,
+    pub windows: WindowRegistry,
+    pub memory: MemoryGuard,
+    pub flow_control: FlowControl,
+    pub keep_alive: KeepAliveMonitor,
+    pub health: HealthMonitor,
+    pub credentials: CredentialMonitor,
+    pub qos2: Qos2Tracer,
+    pub publish_acks: PublishAcks,
+    pub subscription_acks: SubscriptionAcks,
+    pub echo_latency: EchoLatency,
+    pub publish_journal: PublishJournal,
+    pub recent: RecentMessages,
+    pub shortcuts: ShortcutRegistry,
+    pub jobs: JobManager,
+    pub export_log: ExportLog,
+    pub plugins: PluginRegistry,
+    pub transforms: TransformRegistry,
+    pub session_hooks: SessionHooks,
+    pub requests: PendingRequests,
+    pub payload_timestamps: PayloadTimestamps,
     pub ai_defaults: AiConfig,
 }
 
@@ -13,6 +75,37 @@ impl AppState {
         Self {
             mqtt_manager: MqttManager::new(),
             history_manager: HistoryManager::default(),
+            storage_guard: StorageGuard::default(),
+            rule_manager: RuleManager::default(),
+            broker_manager: BrokerManager::default(),
+            kafka_bridges: KafkaBridgeManager::default(),
+            file_watchers: FileWatchManager::default(),
+            socket_taps: SocketTapManager::default(),
+            control_api: ControlApiManager::default(),
+            feed: FeedManager::default(),
+            topic_discovery: TopicDiscoveryManager::default(),
+            sys_stats: SysStatsTracker::default(),
+            tray: TrayState::default(),
+            windows: WindowRegistry::default(),
+            memory: MemoryGuard::default(),
+            flow_control: FlowControl::default(),
+            keep_alive: KeepAliveMonitor::default(),
+            health: HealthMonitor::default(),
+            credentials: CredentialMonitor::default(),
+            qos2: Qos2Tracer::default(),
+            publish_acks: PublishAcks::default(),
+            subscription_acks: SubscriptionAcks::default(),
+            echo_latency: EchoLatency::default(),
+            publish_journal: PublishJournal::default(),
+            recent: RecentMessages::default(),
+            shortcuts: ShortcutRegistry::default(),
+            jobs: JobManager::default(),
+            export_log: ExportLog::default(),
+            plugins: PluginRegistry::default(),
+            transforms: TransformRegistry::default(),
+            session_hooks: SessionHooks::default(),
+            requests: PendingRequests::default(),
+            payload_timestamps: PayloadTimestamps::default(),
             ai_defaults: AiConfig {
                 base_url: None,
                 api_key: None,
@@ -0,0 +1,196 @@
+//! Watches free disk space and keeps history writes from filling the disk, since SQLite running
+//! out of space mid-transaction is far worse than a gap in the history.
+
+use crate::config_store;
+use crate::crash;
+use crate::disk;
+use crate::models::{LowDiskAction, StorageGuardConfig, StorageStatus};
+use crate::mqtt::now_millis;
+use crate::state::AppState;
+use anyhow::{Result, anyhow};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{Notify, RwLock};
+use tokio::time::{self, Duration};
+
+pub const STORAGE_GUARD_FILE_NAME: &str = "storage-guard.json";
+/// Space counts as recovered only this share of the threshold above it, so writes don't flap.
+const RESUME_MARGIN_DIVISOR: u64 = 10;
+/// When pruning, writes are still paused below this share of the threshold.
+const HARD_FLOOR_DIVISOR: u64 = 4;
+const MIN_CHECK_INTERVAL_SECS: u64 = 5;
+
+#[derive(Clone, Default)]
+pub struct StorageGuard {
+    inner: Arc<GuardInner>,
+}
+
+#[derive(Default)]
+struct GuardInner {
+    config: RwLock<Option<StorageGuardConfig>>,
+    status: Mutex<StorageStatus>,
+    pruned: AtomicU64,
+    /// Wakes the monitor early after the config changes.
+    wake: Notify,
+}
+
+impl StorageGuard {
+    pub async fn config(&self, app: &AppHandle) -> Result<StorageGuardConfig> {
+        if let Some(config) = self.inner.config.read().await.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let mut slot = self.inner.config.write().await;
+        if let Some(config) = slot.as_ref() {
+            return Ok(config.clone());
+        }
+
+        let loaded =
+            config_store::load_document::<StorageGuardConfig>(app, STORAGE_GUARD_FILE_NAME)?;
+        *slot = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    pub async fn save_config(
+        &self,
+        app: &AppHandle,
+        config: StorageGuardConfig,
+    ) -> Result<StorageGuardConfig> {
+        if config.min_free_bytes == 0 {
+            return Err(anyhow!("minimum free space must be greater than zero"));
+        }
+        if !(1..=90).contains(&config.prune_percent) {
+            return Err(anyhow!("prune percentage must be between 1 and 90"));
+        }
+        if config.check_interval_secs < MIN_CHECK_INTERVAL_SECS {
+            return Err(anyhow!(
+                "check interval must be at least {MIN_CHECK_INTERVAL_SECS} seconds"
+            ));
+        }
+
+        let mut slot = self.inner.config.write().await;
+        config_store::save_document(app, STORAGE_GUARD_FILE_NAME, &config)?;
+        *slot = Some(config.clone());
+        drop(slot);
+        self.inner.wake.notify_one();
+        Ok(config)
+    }
+
+    pub fn status(&self) -> StorageStatus {
+        self.inner
+            .status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_default()
+    }
+
+    /// Checks free space on start-up and then every `check_interval_secs`.
+    pub fn start(&self, app: AppHandle) {
+        let guard = self.clone();
+        tokio::spawn(crash::monitored("storage-guard", async move {
+            loop {
+                guard.check(&app).await;
+                let interval = guard
+                    .config(&app)
+                    .await
+                    .map(|config| config.check_interval_secs)
+                    .unwrap_or_else(|_| StorageGuardConfig::default().check_interval_secs)
+                    .max(MIN_CHECK_INTERVAL_SECS);
+                tokio::select! {
+                    _ = time::sleep(Duration::from_secs(interval)) => {}
+                    _ = guard.inner.wake.notified() => {}
+                }
+            }
+        }));
+    }
+
+    /// Runs one pass: measures free space, applies the configured action and emits
+    /// `history-storage-status` when anything changed.
+    async fn check(&self, app: &AppHandle) {
+        let config = self.config(app).await.unwrap_or_else(|error| {
+            tracing::warn!("failed to load storage guard config: {error:#}");
+            StorageGuardConfig::default()
+        });
+        let state = app.state::<AppState>();
+        let previous = self.status();
+        let available = config_store::config_dir_path(app)
+            .ok()
+            .and_then(|dir| disk::available_space(&dir));
+
+        let mut status = StorageStatus {
+            available_bytes: available,
+            min_free_bytes: config.min_free_bytes,
+            action: config.action,
+            ..StorageStatus::default()
+        };
+        if let (true, Some(available)) = (config.enabled, available) {
+            let threshold = if previous.low {
+                config.min_free_bytes + config.min_free_bytes / RESUME_MARGIN_DIVISOR
+            } else {
+                config.min_free_bytes
+            };
+            status.low = available < threshold;
+        }
+
+        if status.low {
+            match config.action {
+                LowDiskAction::PauseWrites => status.writes_paused = true,
+                LowDiskAction::PruneOldest => {
+                    if let Err(error) = self.prune(app, config.prune_percent).await {
+                        status.last_error = Some(format!("{error:#}"));
+                    }
+                    status.writes_paused = available.is_some_and(|available| {
+                        available < config.min_free_bytes / HARD_FLOOR_DIVISOR
+                    });
+                }
+            }
+        }
+        state
+            .history_manager
+            .set_writes_paused(status.writes_paused);
+        status.skipped_messages = state.history_manager.skipped_messages();
+        status.pruned_messages = self.inner.pruned.load(Ordering::Relaxed);
+
+        let changed = status.low != previous.low
+            || status.writes_paused != previous.writes_paused
+            || status.pruned_messages != previous.pruned_messages
+            || status.last_error != previous.last_error;
+        if status.low && !previous.low {
+            tracing::warn!(
+                available = ?status.available_bytes,
+                min_free = status.min_free_bytes,
+                "disk space low, history {}",
+                if status.writes_paused { "paused" } else { "pruning" }
+            );
+        } else if !status.low && previous.low {
+            tracing::info!(available = ?status.available_bytes, "disk space recovered");
+        }
+
+        status.checked_at = now_millis();
+        if let Ok(mut slot) = self.inner.status.lock() {
+            *slot = status.clone();
+        }
+        if changed {
+            let _ = app.emit("history-storage-status", &status);
+        }
+    }
+
+    async fn prune(&self, app: &AppHandle, percent: u8) -> Result<()> {
+        let connection_ids = config_store::load_config(app)?
+            .connections
+            .into_iter()
+            .map(|profile| profile.id)
+            .collect::<Vec<_>>();
+        let removed = app
+            .state::<AppState>()
+            .history_manager
+            .prune_oldest(app, &connection_ids, percent)
+            .await?;
+        if removed > 0 {
+            self.inner.pruned.fetch_add(removed, Ordering::Relaxed);
+            tracing::warn!(removed, "pruned oldest history to free disk space");
+        }
+        Ok(())
+    }
+}
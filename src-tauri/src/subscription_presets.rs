@@ -0,0 +1,77 @@
+//! Default subscriptions stored on connection profiles. Sessions subscribe to them after each
+//! CONNACK; edits go through these helpers so a stale config save can't drop them.
+
+use crate::config_store;
+use crate::models::{NativeAppConfig, SubscriptionPreset, SubscriptionPresetsPayload};
+use crate::mqtt::is_valid_topic_filter;
+use anyhow::{Result, anyhow, bail};
+use std::collections::HashSet;
+use tauri::{AppHandle, Emitter};
+
+pub fn list(app: &AppHandle, connection_id: &str) -> Result<Vec<SubscriptionPreset>> {
+    let config = config_store::load_config(app)?;
+    config
+        .connections
+        .into_iter()
+        .find(|profile| profile.id == connection_id)
+        .map(|profile| profile.default_subscriptions)
+        .ok_or_else(|| anyhow!("connection {connection_id} not found"))
+}
+
+/// Replaces the connection's presets. They apply from the next connect.
+pub fn save(
+    app: &AppHandle,
+    connection_id: &str,
+    presets: Vec<SubscriptionPreset>,
+) -> Result<Vec<SubscriptionPreset>> {
+    let presets = normalize(presets)?;
+    config_store::update_config(app, |config| {
+        let profile = config
+            .connections
+            .iter_mut()
+            .find(|profile| profile.id == connection_id)
+            .ok_or_else(|| anyhow!("connection {connection_id} not found"))?;
+        profile.default_subscriptions = presets.clone();
+        Ok(())
+    })?;
+    let _ = app.emit(
+        "subscription-presets-updated",
+        SubscriptionPresetsPayload {
+            connection_id: connection_id.to_string(),
+            subscriptions: presets.clone(),
+        },
+    );
+    Ok(presets)
+}
+
+/// Carries the stored presets over into a config the frontend is about to save.
+pub fn keep_stored(stored: &NativeAppConfig, incoming: &mut NativeAppConfig) {
+    for profile in &mut incoming.connections {
+        if let Some(existing) = stored
+            .connections
+            .iter()
+            .find(|existing| existing.id == profile.id)
+        {
+            profile.default_subscriptions = existing.default_subscriptions.clone();
+        }
+    }
+}
+
+fn normalize(presets: Vec<SubscriptionPreset>) -> Result<Vec<SubscriptionPreset>> {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(presets.len());
+    for mut preset in presets {
+        preset.topic = preset.topic.trim().to_string();
+        if !is_valid_topic_filter(&preset.topic) {
+            bail!("invalid topic filter '{}'", preset.topic);
+        }
+        if preset.qos > 2 {
+            bail!("invalid QoS {} for '{}'", preset.qos, preset.topic);
+        }
+        if !seen.insert(preset.topic.clone()) {
+            bail!("'{}' is listed more than once", preset.topic);
+        }
+        normalized.push(preset);
+    }
+    Ok(normalized)
+}
@@ -0,0 +1,114 @@
+use crate::config_store;
+use crate::crash;
+use crate::models::TelemetryConfig;
+use anyhow::{Context, Result, anyhow};
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+pub const TELEMETRY_FILE_NAME: &str = "telemetry.json";
+const DEFAULT_SERVICE_NAME: &str = "mqtt-nexus";
+const TRACES_PATH: &str = "/v1/traces";
+
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Installs the tracing subscriber: stderr logging and the crash-report log tail always,
+/// plus the OTLP trace exporter when an endpoint is configured.
+pub fn init(app: &AppHandle) {
+    let config = config_store::load_document::<TelemetryConfig>(app, TELEMETRY_FILE_NAME)
+        .unwrap_or_else(|error| {
+            eprintln!("failed to load telemetry config: {error}");
+            TelemetryConfig::default()
+        });
+
+    let provider = build_provider(&config).unwrap_or_else(|error| {
+        eprintln!("failed to start OTLP trace exporter: {error:#}");
+        None
+    });
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+    });
+
+    let installed = tracing_subscriber::registry()
+        .with(otel_layer)
+        .with(crash::TailLayer)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_target(false)
+                .with_filter(LevelFilter::INFO),
+        )
+        .try_init();
+    if let Err(error) = installed {
+        eprintln!("tracing subscriber already installed: {error}");
+        return;
+    }
+
+    if let Some(provider) = provider {
+        let _ = PROVIDER.set(provider);
+    }
+}
+
+pub fn load(app: &AppHandle) -> Result<TelemetryConfig> {
+    config_store::load_document::<TelemetryConfig>(app, TELEMETRY_FILE_NAME)
+}
+
+pub fn save(app: &AppHandle, config: &TelemetryConfig) -> Result<()> {
+    if let Some(endpoint) = normalized_endpoint(config) {
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            return Err(anyhow!("OTLP endpoint must start with http:// or https://"));
+        }
+    }
+    config_store::save_document(app, TELEMETRY_FILE_NAME, config)
+}
+
+pub fn is_active() -> bool {
+    PROVIDER.get().is_some()
+}
+
+fn build_provider(config: &TelemetryConfig) -> Result<Option<SdkTracerProvider>> {
+    let Some(endpoint) = normalized_endpoint(config) else {
+        return Ok(None);
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .with_headers(config.headers.clone())
+        .build()
+        .context("failed to build OTLP span exporter")?;
+
+    let service_name = config
+        .service_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_SERVICE_NAME)
+        .to_string();
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+    Ok(Some(provider))
+}
+
+fn normalized_endpoint(config: &TelemetryConfig) -> Option<String> {
+    let endpoint = config
+        .otlp_endpoint
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())?
+        .trim_end_matches('/');
+    if endpoint.ends_with(TRACES_PATH) {
+        Some(endpoint.to_string())
+    } else {
+        Some(format!("{endpoint}{TRACES_PATH}"))
+    }
+}
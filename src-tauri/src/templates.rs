@@ -0,0 +1,273 @@
+//! Publisher payload templates. They live in the app config but are edited one at a time through
+//! these helpers, so two views saving at once can't drop each other's templates.
+
+use crate::config_store;
+use crate::models::{NativeAppConfig, PayloadTemplate, PayloadTemplateDraft};
+use crate::mqtt::now_millis;
+use anyhow::{Result, anyhow, bail};
+use tauri::{AppHandle, Emitter};
+
+const MAX_NAME_CHARS: usize = 120;
+
+/// Templates visible on `connection_id` (its own plus global ones, or all when `None`) whose
+/// name, topic or payload contains `query`.
+pub fn list(
+    app: &AppHandle,
+    connection_id: Option<&str>,
+    query: Option<&str>,
+) -> Result<Vec<PayloadTemplate>> {
+    let config = config_store::load_config(app)?;
+    let query = query
+        .map(str::trim)
+        .filter(|query| !query.is_empty())
+        .map(str::to_lowercase);
+
+    Ok(config
+        .publisher_templates
+        .into_iter()
+        .filter(|template| {
+            connection_id.is_none_or(|id| visible_on(template.connection_id.as_deref(), id))
+        })
+        .filter(|template| {
+            query.as_deref().is_none_or(|query| {
+                template.name.to_lowercase().contains(query)
+                    || template.topic.to_lowercase().contains(query)
+                    || template.payload.to_lowercase().contains(query)
+            })
+        })
+        .collect())
+}
+
+pub fn create(app: &AppHandle, draft: PayloadTemplateDraft) -> Result<PayloadTemplate> {
+    let draft = normalize(draft)?;
+    let template = config_store::update_config(app, |config| {
+        check_draft(config, &draft, None)?;
+        let template = PayloadTemplate {
+            id: generate_id(&config.publisher_templates),
+            name: draft.name,
+            topic: draft.topic,
+            payload: draft.payload,
+            connection_id: draft.connection_id,
+            updated_at: now_millis(),
+        };
+        config.publisher_templates.push(template.clone());
+        Ok(template)
+    })?;
+    notify(app);
+    Ok(template)
+}
+
+pub fn update(app: &AppHandle, id: &str, draft: PayloadTemplateDraft) -> Result<PayloadTemplate> {
+    let draft = normalize(draft)?;
+    let template = config_store::update_config(app, |config| {
+        check_draft(config, &draft, Some(id))?;
+        let template = find_mut(config, id)?;
+        template.name = draft.name;
+        template.topic = draft.topic;
+        template.payload = draft.payload;
+        template.connection_id = draft.connection_id;
+        template.updated_at = now_millis();
+        Ok(template.clone())
+    })?;
+    notify(app);
+    Ok(template)
+}
+
+pub fn delete(app: &AppHandle, id: &str) -> Result<()> {
+    config_store::update_config(app, |config| {
+        let before = config.publisher_templates.len();
+        config
+            .publisher_templates
+            .retain(|template| template.id != id);
+        if config.publisher_templates.len() == before {
+            bail!("template {id} not found");
+        }
+        Ok(())
+    })?;
+    notify(app);
+    Ok(())
+}
+
+/// Copies a template under a free "<name> (copy)" name in the same scope.
+pub fn duplicate(app: &AppHandle, id: &str) -> Result<PayloadTemplate> {
+    let template = config_store::update_config(app, |config| {
+        let source = find_mut(config, id)?.clone();
+        let scope = source.connection_id.as_deref();
+        let mut name = format!("{} (copy)", source.name);
+        let mut attempt = 2;
+        while name_taken(&config.publisher_templates, &name, scope, None) {
+            name = format!("{} (copy {attempt})", source.name);
+            attempt += 1;
+        }
+
+        let template = PayloadTemplate {
+            id: generate_id(&config.publisher_templates),
+            name,
+            updated_at: now_millis(),
+            ..source
+        };
+        config.publisher_templates.push(template.clone());
+        Ok(template)
+    })?;
+    notify(app);
+    Ok(template)
+}
+
+/// Adds templates from an imported config, replacing existing ones with the same id. With
+/// `replace` the current library is discarded first. Returns the resulting library.
+pub fn import(
+    app: &AppHandle,
+    templates: Vec<PayloadTemplate>,
+    replace: bool,
+) -> Result<Vec<PayloadTemplate>> {
+    let incoming = templates
+        .into_iter()
+        .map(|template| {
+            let draft = normalize(PayloadTemplateDraft {
+                name: template.name,
+                topic: template.topic,
+                payload: template.payload,
+                connection_id: template.connection_id,
+            })?;
+            Ok(PayloadTemplate {
+                id: template.id.trim().to_string(),
+                name: draft.name,
+                topic: draft.topic,
+                payload: draft.payload,
+                connection_id: draft.connection_id,
+                updated_at: now_millis(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let library = config_store::update_config(app, |config| {
+        if replace {
+            config.publisher_templates.clear();
+        }
+        for mut template in incoming {
+            if let Some(existing) = config
+                .publisher_templates
+                .iter_mut()
+                .find(|existing| !template.id.is_empty() && existing.id == template.id)
+            {
+                *existing = template;
+                continue;
+            }
+            if template.id.is_empty() {
+                template.id = generate_id(&config.publisher_templates);
+            }
+            config.publisher_templates.push(template);
+        }
+        Ok(config.publisher_templates.clone())
+    })?;
+    notify(app);
+    Ok(library)
+}
+
+fn normalize(draft: PayloadTemplateDraft) -> Result<PayloadTemplateDraft> {
+    let name = draft.name.trim().to_string();
+    if name.is_empty() {
+        bail!("template name is empty");
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        bail!("template name is longer than {MAX_NAME_CHARS} characters");
+    }
+
+    let topic = draft.topic.trim().to_string();
+    if topic.is_empty() {
+        bail!("template topic is empty");
+    }
+    if topic.contains(['+', '#']) {
+        bail!("template topic cannot contain the wildcards '+' or '#'");
+    }
+    if topic.contains('\0') || topic.len() > u16::MAX as usize {
+        bail!("template topic is not a valid MQTT topic name");
+    }
+
+    Ok(PayloadTemplateDraft {
+        name,
+        topic,
+        payload: draft.payload,
+        connection_id: draft
+            .connection_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty()),
+    })
+}
+
+fn check_draft(
+    config: &NativeAppConfig,
+    draft: &PayloadTemplateDraft,
+    editing: Option<&str>,
+) -> Result<()> {
+    if let Some(connection_id) = &draft.connection_id {
+        if !config
+            .connections
+            .iter()
+            .any(|profile| &profile.id == connection_id)
+        {
+            bail!("connection {connection_id} not found");
+        }
+    }
+    if name_taken(
+        &config.publisher_templates,
+        &draft.name,
+        draft.connection_id.as_deref(),
+        editing,
+    ) {
+        bail!("a template named \"{}\" already exists", draft.name);
+    }
+    Ok(())
+}
+
+/// Whether another template visible alongside one in `scope` already uses `name`.
+fn name_taken(
+    templates: &[PayloadTemplate],
+    name: &str,
+    scope: Option<&str>,
+    editing: Option<&str>,
+) -> bool {
+    templates.iter().any(|template| {
+        Some(template.id.as_str()) != editing
+            && template.name.eq_ignore_ascii_case(name)
+            && scopes_overlap(template.connection_id.as_deref(), scope)
+    })
+}
+
+fn scopes_overlap(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+fn visible_on(template_scope: Option<&str>, connection_id: &str) -> bool {
+    template_scope.is_none_or(|scope| scope == connection_id)
+}
+
+fn find_mut<'a>(config: &'a mut NativeAppConfig, id: &str) -> Result<&'a mut PayloadTemplate> {
+    config
+        .publisher_templates
+        .iter_mut()
+        .find(|template| template.id == id)
+        .ok_or_else(|| anyhow!("template {id} not found"))
+}
+
+fn generate_id(existing: &[PayloadTemplate]) -> String {
+    loop {
+        let id = format!("tpl-{:016x}", rand::random::<u64>());
+        if existing.iter().all(|template| template.id != id) {
+            return id;
+        }
+    }
+}
+
+/// Pushes the full library to every window so open views pick up the change.
+fn notify(app: &AppHandle) {
+    match config_store::load_config(app) {
+        Ok(config) => {
+            let _ = app.emit("publisher-templates-updated", &config.publisher_templates);
+        }
+        Err(error) => tracing::warn!("failed to reload publisher templates: {error:#}"),
+    }
+}
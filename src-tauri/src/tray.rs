@@ -0,0 +1,255 @@
+use crate::commands::resolve_connection;
+use crate::config_store;
+use crate::models::{ConnectionStatus, MqttStatusPayload, TrayBadges};
+use crate::state::AppState;
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tauri::menu::{Menu, MenuBuilder, MenuEvent, MenuItem, SubmenuBuilder};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+const MENU_TOGGLE_WINDOW: &str = "toggle-window";
+const MENU_QUIT: &str = "quit";
+const CONNECT_PREFIX: &str = "connect:";
+const DISCONNECT_PREFIX: &str = "disconnect:";
+
+#[derive(Clone, Default)]
+pub struct TrayState {
+    inner: Arc<TrayInner>,
+}
+
+#[derive(Default)]
+struct TrayInner {
+    statuses: DashMap<String, ConnectionStatus>,
+    unread: AtomicU32,
+    alerts: AtomicU32,
+}
+
+impl TrayState {
+    fn connected_count(&self) -> usize {
+        self.inner
+            .statuses
+            .iter()
+            .filter(|entry| matches!(entry.value(), ConnectionStatus::Connected))
+            .count()
+    }
+
+    fn has_errors(&self) -> bool {
+        self.inner.statuses.iter().any(|entry| {
+            matches!(
+                entry.value(),
+                ConnectionStatus::Error | ConnectionStatus::TakenOver
+            )
+        })
+    }
+
+    fn status_of(&self, connection_id: &str) -> ConnectionStatus {
+        self.inner
+            .statuses
+            .get(connection_id)
+            .map(|entry| *entry.value())
+            .unwrap_or(ConnectionStatus::Disconnected)
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<()> {
+    let menu = build_menu(app)?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .tooltip(tooltip(app))
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(handle_icon_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app).context("failed to create tray icon")?;
+    Ok(())
+}
+
+/// Tracks session status for the tray; called from the same place `mqtt-status` is emitted.
+pub fn on_status(app: &AppHandle, payload: &MqttStatusPayload) {
+    let state = app.state::<AppState>();
+    let previous = state
+        .tray
+        .inner
+        .statuses
+        .insert(payload.connection_id.clone(), payload.status);
+    if previous != Some(payload.status) {
+        refresh(app);
+    }
+}
+
+pub fn set_badges(app: &AppHandle, badges: &TrayBadges) {
+    let state = app.state::<AppState>();
+    state
+        .tray
+        .inner
+        .unread
+        .store(badges.unread, Ordering::Relaxed);
+    state
+        .tray
+        .inner
+        .alerts
+        .store(badges.alerts, Ordering::Relaxed);
+    refresh(app);
+}
+
+/// Rebuilds the menu and tooltip, e.g. after connection profiles changed.
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(error) => tracing::warn!("failed to rebuild tray menu: {error:#}"),
+    }
+    let _ = tray.set_tooltip(Some(tooltip(app)));
+}
+
+fn tooltip(app: &AppHandle) -> String {
+    let state = app.state::<AppState>();
+    let name = app.package_info().name.clone();
+    let connected = state.tray.connected_count();
+    let alerts = state.tray.inner.alerts.load(Ordering::Relaxed);
+    let mut text = format!("{name} — {connected} connected");
+    if state.tray.has_errors() {
+        text.push_str(", errors");
+    }
+    if alerts > 0 {
+        text.push_str(&format!(", {alerts} alerts"));
+    }
+    text
+}
+
+fn build_menu(app: &AppHandle) -> Result<Menu<Wry>> {
+    let state = app.state::<AppState>();
+    let config = config_store::load_config(app)?;
+    let unread = state.tray.inner.unread.load(Ordering::Relaxed);
+    let alerts = state.tray.inner.alerts.load(Ordering::Relaxed);
+
+    let summary = MenuItem::with_id(
+        app,
+        "summary",
+        format!(
+            "{} connected · {unread} unread · {alerts} alerts",
+            state.tray.connected_count()
+        ),
+        false,
+        None::<&str>,
+    )?;
+
+    let mut connections = SubmenuBuilder::new(app, "Connections");
+    if config.connections.is_empty() {
+        connections = connections.item(&MenuItem::with_id(
+            app,
+            "no-connections",
+            "No saved connections",
+            false,
+            None::<&str>,
+        )?);
+    }
+    for profile in &config.connections {
+        let (marker, id) = match state.tray.status_of(&profile.id) {
+            ConnectionStatus::Connected => ("●", format!("{DISCONNECT_PREFIX}{}", profile.id)),
+            ConnectionStatus::Connecting => ("◐", format!("{DISCONNECT_PREFIX}{}", profile.id)),
+            ConnectionStatus::Error => ("✕", format!("{CONNECT_PREFIX}{}", profile.id)),
+            ConnectionStatus::TakenOver => ("⇄", format!("{CONNECT_PREFIX}{}", profile.id)),
+            ConnectionStatus::Disconnected => ("○", format!("{CONNECT_PREFIX}{}", profile.id)),
+        };
+        connections = connections.item(&MenuItem::with_id(
+            app,
+            id,
+            format!("{marker} {}", profile.name),
+            true,
+            None::<&str>,
+        )?);
+    }
+
+    let window_visible = app
+        .get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    let toggle_label = if window_visible {
+        "Hide window"
+    } else {
+        "Show window"
+    };
+
+    MenuBuilder::new(app)
+        .item(&summary)
+        .separator()
+        .item(&connections.build()?)
+        .separator()
+        .text(MENU_TOGGLE_WINDOW, toggle_label)
+        .text(MENU_QUIT, "Quit")
+        .build()
+        .context("failed to build tray menu")
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    if id == MENU_TOGGLE_WINDOW {
+        toggle_main_window(app);
+    } else if id == MENU_QUIT {
+        app.exit(0);
+    } else if let Some(connection_id) = id.strip_prefix(CONNECT_PREFIX) {
+        if let Err(error) = connect(app, connection_id) {
+            tracing::warn!("tray connect failed for {connection_id}: {error}");
+        }
+    } else if let Some(connection_id) = id.strip_prefix(DISCONNECT_PREFIX) {
+        let state = app.state::<AppState>();
+        state.sys_stats.stop(connection_id);
+        if let Err(error) = state.mqtt_manager.disconnect(connection_id) {
+            tracing::warn!("tray disconnect failed for {connection_id}: {error}");
+        }
+        let _ = app.emit("tray-connection-toggled", connection_id);
+    }
+}
+
+fn handle_icon_event(tray: &TrayIcon, event: TrayIconEvent) {
+    if let TrayIconEvent::Click {
+        button: MouseButton::Left,
+        button_state: MouseButtonState::Up,
+        ..
+    } = event
+    {
+        toggle_main_window(tray.app_handle());
+    }
+}
+
+pub(crate) fn toggle_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    refresh(app);
+}
+
+fn connect(app: &AppHandle, connection_id: &str) -> Result<(), String> {
+    let config = config_store::load_config(app).map_err(|e| e.to_string())?;
+    let profile = config
+        .connections
+        .into_iter()
+        .find(|profile| profile.id == connection_id)
+        .ok_or_else(|| format!("unknown connection: {connection_id}"))?;
+    let resolved = resolve_connection(profile, config.brokers, config.identities)?;
+    let state = app.state::<AppState>();
+    state
+        .mqtt_manager
+        .connect(app.clone(), resolved)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("tray-connection-toggled", connection_id);
+    Ok(())
+}
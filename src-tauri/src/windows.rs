@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{
+    AppHandle, Emitter, EventTarget, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent,
+};
+
+const CONNECTION_WINDOW_PREFIX: &str = "connection-";
+
+/// Maps secondary window labels to the connection each one is scoped to.
+#[derive(Clone, Default)]
+pub struct WindowRegistry {
+    connections: Arc<DashMap<String, String>>,
+}
+
+impl WindowRegistry {
+    pub fn connection_for(&self, label: &str) -> Option<String> {
+        self.connections
+            .get(label)
+            .map(|entry| entry.value().clone())
+    }
+}
+
+pub fn connection_window_label(connection_id: &str) -> String {
+    let safe = connection_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("{CONNECTION_WINDOW_PREFIX}{safe}")
+}
+
+/// Opens (or focuses) a window dedicated to one connection.
+pub fn open_connection_window(
+    app: &AppHandle,
+    registry: &WindowRegistry,
+    connection_id: &str,
+    title: &str,
+) -> Result<String> {
+    let label = connection_window_label(connection_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(label);
+    }
+
+    registry
+        .connections
+        .insert(label.clone(), connection_id.to_string());
+    let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::default())
+        .title(title)
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(900.0, 600.0)
+        .background_color(tauri::window::Color(0x0B, 0x14, 0x24, 0xFF))
+        .build()
+        .with_context(|| format!("failed to open window for connection {connection_id}"));
+    let window = match window {
+        Ok(window) => window,
+        Err(error) => {
+            registry.connections.remove(&label);
+            return Err(error);
+        }
+    };
+
+    let connections = Arc::clone(&registry.connections);
+    let closed_label = label.clone();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Destroyed) {
+            connections.remove(&closed_label);
+        }
+    });
+    Ok(label)
+}
+
+/// Emits a connection-scoped event: the main window (and other unscoped targets) receive
+/// everything, while a connection window only receives its own connection's events.
+pub fn emit_for_connection<S: Serialize + Clone>(
+    app: &AppHandle,
+    event: &str,
+    connection_id: &str,
+    payload: S,
+) {
+    let registry = app.state::<crate::state::AppState>().windows.clone();
+    let _ = app.emit_filter(event, payload, |target| {
+        let label = match target {
+            EventTarget::WebviewWindow { label }
+            | EventTarget::Webview { label }
+            | EventTarget::Window { label } => label,
+            _ => return true,
+        };
+        match registry.connections.get(label.as_str()) {
+            Some(scoped) => scoped.value() == connection_id,
+            None => true,
+        }
+    });
+}
@@ -0,0 +1,119 @@
+//! Removes everything the app has stored on this machine, for handing a device back.
+//!
+//! Connection credentials live in the app config rather than an OS keyring, so deleting the
+//! config directory is what clears them.
+
+use crate::config_store;
+use crate::history::HISTORY_DIR_NAME;
+use crate::models::DataWipeReport;
+use crate::state::AppState;
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+/// Text the user has to type to confirm a wipe.
+pub const CONFIRM_PHRASE: &str = "WIPE ALL DATA";
+
+pub async fn wipe_all(app: &AppHandle, confirm_token: &str) -> Result<DataWipeReport> {
+    if confirm_token.trim() != CONFIRM_PHRASE {
+        bail!("type \"{CONFIRM_PHRASE}\" to confirm wiping all data");
+    }
+
+    let state = app.state::<AppState>();
+    let disconnected = state.mqtt_manager.disconnect_all().await;
+    for connection_id in &disconnected {
+        state.sys_stats.stop(connection_id);
+        state.memory.remove(connection_id);
+        state.flow_control.remove(connection_id);
+        state.keep_alive.remove(connection_id);
+        state.qos2.clear_pending(connection_id);
+        state.publish_acks.remove(connection_id);
+    }
+
+    let mut report = DataWipeReport {
+        disconnected,
+        history_databases: 0,
+        export_files: 0,
+        config_entries: Vec::new(),
+        bytes_removed: 0,
+        errors: Vec::new(),
+    };
+    match state.history_manager.wipe_all(app).await {
+        Ok(history) => {
+            report.history_databases = history.databases;
+            report.export_files = history.export_files;
+            report.bytes_removed += history.bytes;
+        }
+        Err(error) => report.errors.push(format!("history: {error:#}")),
+    }
+
+    let config_dir = config_store::config_dir_path(app)?;
+    let entries = fs::read_dir(&config_dir)
+        .with_context(|| format!("failed to scan {}", config_dir.display()))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == HISTORY_DIR_NAME {
+            continue;
+        }
+        match remove_entry(&entry.path()) {
+            Ok(bytes) => {
+                report.bytes_removed += bytes;
+                report.config_entries.push(name);
+            }
+            Err(error) => report.errors.push(format!("{name}: {error:#}")),
+        }
+    }
+    report.config_entries.sort();
+
+    tracing::warn!(
+        disconnected = report.disconnected.len(),
+        history_databases = report.history_databases,
+        export_files = report.export_files,
+        config_entries = report.config_entries.len(),
+        errors = report.errors.len(),
+        "wiped all app data"
+    );
+    Ok(report)
+}
+
+/// Deletes everything inside `dir` but keeps the directory. Returns files and bytes removed.
+pub fn remove_dir_contents(dir: &Path) -> Result<(u64, u64)> {
+    if !dir.exists() {
+        return Ok((0, 0));
+    }
+    let mut files = 0;
+    let mut bytes = 0;
+    let entries = fs::read_dir(dir).with_context(|| format!("failed to scan {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Symlinks are removed themselves, never followed.
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            let (nested_files, nested_bytes) = remove_dir_contents(&path)?;
+            files += nested_files;
+            bytes += nested_bytes;
+            fs::remove_dir(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+        } else {
+            bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+            files += 1;
+        }
+    }
+    Ok((files, bytes))
+}
+
+fn remove_entry(path: &Path) -> Result<u64> {
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_dir()) {
+        let (_, bytes) = remove_dir_contents(path)?;
+        fs::remove_dir(path).with_context(|| format!("failed to remove {}", path.display()))?;
+        Ok(bytes)
+    } else {
+        let bytes = fs::symlink_metadata(path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))?;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,436 @@
+//! Minimal single-sheet XLSX writer for history exports.
+//!
+//! Rows are streamed to a scratch file while column widths are measured, then the workbook is
+//! zipped together around it, so large exports never sit in memory.
+
+use anyhow::{Context, Result, bail};
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Rows per worksheet allowed by Excel, header included.
+const MAX_ROWS: u32 = 1_048_576;
+/// Characters Excel keeps in a single cell.
+const MAX_CELL_CHARS: usize = 32_767;
+const MAX_COLUMN_WIDTH: usize = 80;
+const DATETIME_WIDTH: usize = 23;
+
+const STYLE_HEADER: u8 = 1;
+const STYLE_DATETIME: u8 = 2;
+
+/// Days between the Excel epoch (1899-12-30) and the Unix epoch.
+const EXCEL_UNIX_EPOCH_DAYS: f64 = 25_569.0;
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+pub enum Cell<'a> {
+    Int(i64),
+    Text(&'a str),
+    Bool(bool),
+    /// Unix milliseconds, shown as a UTC date and time.
+    DateTime(u64),
+}
+
+pub struct SheetWriter {
+    output_path: PathBuf,
+    rows_path: PathBuf,
+    rows: BufWriter<File>,
+    widths: Vec<usize>,
+    row_count: u32,
+}
+
+impl SheetWriter {
+    /// Starts a sheet with a bold, frozen header row.
+    pub fn create(output_path: &Path, headers: &[&str]) -> Result<Self> {
+        let rows_path = output_path.with_extension("xlsx-rows.tmp");
+        let file = File::create(&rows_path)
+            .with_context(|| format!("failed to create scratch file: {}", rows_path.display()))?;
+        let mut sheet = Self {
+            output_path: output_path.to_path_buf(),
+            rows_path,
+            rows: BufWriter::new(file),
+            widths: vec![0; headers.len()],
+            row_count: 0,
+        };
+        let header = headers
+            .iter()
+            .map(|name| Cell::Text(name))
+            .collect::<Vec<_>>();
+        sheet.push_row(&header, Some(STYLE_HEADER))?;
+        Ok(sheet)
+    }
+
+    pub fn write_row(&mut self, cells: &[Cell]) -> Result<()> {
+        self.push_row(cells, None)
+    }
+
+    /// Assembles the workbook at the output path and removes the scratch file.
+    pub fn finish(mut self) -> Result<()> {
+        self.rows.flush().context("failed to flush xlsx rows")?;
+        let rows = File::open(&self.rows_path).with_context(|| {
+            format!(
+                "failed to reopen scratch file: {}",
+                self.rows_path.display()
+            )
+        })?;
+        let file = File::create(&self.output_path).with_context(|| {
+            format!(
+                "failed to create export file: {}",
+                self.output_path.display()
+            )
+        })?;
+
+        let mut zip = ZipWriter::new(BufWriter::new(file));
+        zip.entry("[Content_Types].xml", |out| {
+            out.write_all(CONTENT_TYPES.as_bytes())
+        })?;
+        zip.entry("_rels/.rels", |out| out.write_all(ROOT_RELS.as_bytes()))?;
+        zip.entry("xl/workbook.xml", |out| out.write_all(WORKBOOK.as_bytes()))?;
+        zip.entry("xl/_rels/workbook.xml.rels", |out| {
+            out.write_all(WORKBOOK_RELS.as_bytes())
+        })?;
+        zip.entry("xl/styles.xml", |out| out.write_all(STYLES.as_bytes()))?;
+        let head = self.sheet_head();
+        zip.entry("xl/worksheets/sheet1.xml", |out| {
+            out.write_all(head.as_bytes())?;
+            io::copy(&mut BufReader::new(rows), out)?;
+            out.write_all(b"</sheetData></worksheet>")
+        })?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn push_row(&mut self, cells: &[Cell], style: Option<u8>) -> Result<()> {
+        if self.row_count >= MAX_ROWS {
+            bail!(
+                "XLSX sheets hold at most {} rows; narrow the time range",
+                MAX_ROWS - 1
+            );
+        }
+        self.row_count += 1;
+        let row = self.row_count;
+
+        let mut xml = format!("<row r=\"{row}\">");
+        for (index, cell) in cells.iter().enumerate() {
+            let reference = format!("{}{row}", column_name(index));
+            let style = match (style, cell) {
+                (Some(style), _) => format!(" s=\"{style}\""),
+                (None, Cell::DateTime(_)) => format!(" s=\"{STYLE_DATETIME}\""),
+                (None, _) => String::new(),
+            };
+            let width = match cell {
+                Cell::Int(value) => {
+                    xml.push_str(&format!("<c r=\"{reference}\"{style}><v>{value}</v></c>"));
+                    value.to_string().len()
+                }
+                Cell::Bool(value) => {
+                    let value = u8::from(*value);
+                    xml.push_str(&format!(
+                        "<c r=\"{reference}\"{style} t=\"b\"><v>{value}</v></c>"
+                    ));
+                    5
+                }
+                Cell::DateTime(ms) => {
+                    let serial = *ms as f64 / MS_PER_DAY + EXCEL_UNIX_EPOCH_DAYS;
+                    xml.push_str(&format!("<c r=\"{reference}\"{style}><v>{serial}</v></c>"));
+                    DATETIME_WIDTH
+                }
+                Cell::Text(text) => {
+                    xml.push_str(&format!(
+                        "<c r=\"{reference}\"{style} t=\"inlineStr\"><is><t xml:space=\"preserve\">"
+                    ));
+                    push_escaped(&mut xml, text);
+                    xml.push_str("</t></is></c>");
+                    text.lines()
+                        .next()
+                        .unwrap_or_default()
+                        .chars()
+                        .take(MAX_COLUMN_WIDTH)
+                        .count()
+                }
+            };
+            if let Some(max) = self.widths.get_mut(index) {
+                *max = (*max).max(width);
+            }
+        }
+        xml.push_str("</row>");
+
+        self.rows
+            .write_all(xml.as_bytes())
+            .context("failed to write xlsx row")
+    }
+
+    fn sheet_head(&self) -> String {
+        let mut head = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+             <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+             <sheetViews><sheetView workbookViewId=\"0\">\
+             <pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>\
+             <selection pane=\"bottomLeft\"/></sheetView></sheetViews>\
+             <sheetFormatPr defaultRowHeight=\"15\"/><cols>",
+        );
+        for (index, width) in self.widths.iter().enumerate() {
+            let column = index + 1;
+            let width = (*width).clamp(4, MAX_COLUMN_WIDTH) + 2;
+            head.push_str(&format!(
+                "<col min=\"{column}\" max=\"{column}\" width=\"{width}\" customWidth=\"1\"/>"
+            ));
+        }
+        head.push_str("</cols><sheetData>");
+        head
+    }
+}
+
+impl Drop for SheetWriter {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.rows_path);
+    }
+}
+
+/// Spreadsheet column letters for a zero-based index: A, B, …, Z, AA, AB, …
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Escapes cell text for XML, dropping characters XML 1.0 can't carry and truncating to what
+/// Excel will show.
+fn push_escaped(out: &mut String, text: &str) {
+    for ch in text.chars().take(MAX_CELL_CHARS) {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\t' | '\n' | '\r' => out.push(ch),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            _ => out.push(ch),
+        }
+    }
+}
+
+/// Just enough of the ZIP format for XLSX: deflated entries with trailing data descriptors,
+/// no ZIP64.
+struct ZipWriter<W: Write> {
+    out: W,
+    offset: u64,
+    entries: Vec<ZipEntry>,
+}
+
+struct ZipEntry {
+    name: &'static str,
+    crc: u32,
+    compressed: u32,
+    uncompressed: u32,
+    offset: u32,
+}
+
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+const METHOD_DEFLATE: u16 = 8;
+const ZIP_VERSION: u16 = 20;
+/// 1980-01-01, the earliest date ZIP can record.
+const DOS_DATE: u16 = 0x0021;
+
+impl<W: Write> ZipWriter<W> {
+    fn new(out: W) -> Self {
+        Self {
+            out,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    fn entry<F>(&mut self, name: &'static str, write: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        let offset = zip32(self.offset, "archive")?;
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        header.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        header.extend_from_slice(&FLAG_DATA_DESCRIPTOR.to_le_bytes());
+        header.extend_from_slice(&METHOD_DEFLATE.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&DOS_DATE.to_le_bytes());
+        // CRC and sizes follow the data in the descriptor.
+        header.extend_from_slice(&[0; 12]);
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        self.write_raw(&header)?;
+
+        let (crc, uncompressed, written) = {
+            let mut body = EntryBody {
+                encoder: DeflateEncoder::new(
+                    CountingWriter::new(&mut self.out),
+                    Compression::default(),
+                ),
+                crc: crc32fast::Hasher::new(),
+                size: 0,
+            };
+            write(&mut body).with_context(|| format!("failed to write xlsx part {name}"))?;
+            let written = body
+                .encoder
+                .finish()
+                .with_context(|| format!("failed to compress xlsx part {name}"))?
+                .count;
+            (body.crc.finalize(), body.size, written)
+        };
+        let uncompressed = zip32(uncompressed, name)?;
+        self.offset += written;
+        let compressed = zip32(written, name)?;
+
+        let mut descriptor = Vec::with_capacity(16);
+        descriptor.extend_from_slice(&0x0807_4b50u32.to_le_bytes());
+        descriptor.extend_from_slice(&crc.to_le_bytes());
+        descriptor.extend_from_slice(&compressed.to_le_bytes());
+        descriptor.extend_from_slice(&uncompressed.to_le_bytes());
+        self.write_raw(&descriptor)?;
+
+        self.entries.push(ZipEntry {
+            name,
+            crc,
+            compressed,
+            uncompressed,
+            offset,
+        });
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<()> {
+        let directory_offset = zip32(self.offset, "archive")?;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            directory.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+            directory.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+            directory.extend_from_slice(&FLAG_DATA_DESCRIPTOR.to_le_bytes());
+            directory.extend_from_slice(&METHOD_DEFLATE.to_le_bytes());
+            directory.extend_from_slice(&0u16.to_le_bytes());
+            directory.extend_from_slice(&DOS_DATE.to_le_bytes());
+            directory.extend_from_slice(&entry.crc.to_le_bytes());
+            directory.extend_from_slice(&entry.compressed.to_le_bytes());
+            directory.extend_from_slice(&entry.uncompressed.to_le_bytes());
+            directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            // Extra field, comment, disk number, internal and external attributes.
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = directory.len() as u32;
+        let count = self.entries.len() as u16;
+        directory.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&[0; 4]);
+        directory.extend_from_slice(&count.to_le_bytes());
+        directory.extend_from_slice(&count.to_le_bytes());
+        directory.extend_from_slice(&directory_size.to_le_bytes());
+        directory.extend_from_slice(&directory_offset.to_le_bytes());
+        directory.extend_from_slice(&0u16.to_le_bytes());
+        self.write_raw(&directory)?;
+        self.out.flush().context("failed to flush xlsx file")
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> Result<()> {
+        self.out
+            .write_all(bytes)
+            .context("failed to write xlsx file")?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+fn zip32(value: u64, what: &str) -> Result<u32> {
+    u32::try_from(value)
+        .map_err(|_| anyhow::anyhow!("{what} exceeds the 4 GiB XLSX limit; narrow the time range"))
+}
+
+struct EntryBody<'a, W: Write> {
+    encoder: DeflateEncoder<CountingWriter<&'a mut W>>,
+    crc: crc32fast::Hasher,
+    size: u64,
+}
+
+impl<W: Write> Write for EntryBody<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.encoder.write(buf)?;
+        self.crc.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
+
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+const CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+<Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+<Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>\
+</Types>";
+
+const ROOT_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>\
+</Relationships>";
+
+const WORKBOOK: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+<sheets><sheet name=\"History\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>";
+
+const WORKBOOK_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>\
+<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>\
+</Relationships>";
+
+/// Cell formats: 0 default, 1 bold header, 2 date and time with milliseconds.
+const STYLES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+<numFmts count=\"1\"><numFmt numFmtId=\"164\" formatCode=\"yyyy-mm-dd hh:mm:ss.000\"/></numFmts>\
+<fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font>\
+<font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>\
+<fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill>\
+<fill><patternFill patternType=\"gray125\"/></fill></fills>\
+<borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+<cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>\
+<cellXfs count=\"3\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>\
+<xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>\
+<xf numFmtId=\"164\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/></cellXfs>\
+<cellStyles count=\"1\"><cellStyle name=\"Normal\" xfId=\"0\" builtinId=\"0\"/></cellStyles>\
+</styleSheet>";
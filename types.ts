@@ -14,6 +14,67 @@ export interface AuthIdentity {
   username?: string;
   password?: string;
   clientId?: string;
+  clientCertPath?: string;
+  tokenExpiresAt?: number;
+  expiryWarningDays?: number;
+}
+
+export interface CredentialExpiry {
+  identityId: string;
+  identityName: string;
+  kind: 'clientCertificate' | 'token';
+  expiresAt?: number | null;
+  daysLeft?: number | null;
+  expired: boolean;
+  warning: boolean;
+  error?: string | null;
+}
+
+export interface CredentialFinding {
+  kind: 'plaintextSecret' | 'weakPassword' | 'duplicateSecret' | 'unusedIdentity';
+  severity: 'low' | 'medium' | 'high';
+  locations: string[];
+  detail: string;
+}
+
+export interface CredentialAuditReport {
+  findings: CredentialFinding[];
+  secretsScanned: number;
+  auditedAt: number;
+}
+
+export interface InventoryMapping {
+  delimiter?: string;
+  hostColumn: string;
+  nameColumn?: string;
+  groupColumn?: string;
+  portColumn?: string;
+  protocolColumn?: string;
+  pathColumn?: string;
+  protocolVersionColumn?: string;
+  usernameColumn?: string;
+  passwordColumn?: string;
+  clientIdColumn?: string;
+  brokerNameColumn?: string;
+  identityNameColumn?: string;
+  labelColumns?: string[];
+  defaultGroup?: string;
+  defaultPort?: number;
+  defaultProtocol?: 'mqtt' | 'mqtts' | 'ws' | 'wss';
+  defaultProtocolVersion?: 4 | 5;
+}
+
+export interface InventoryRowError {
+  line: number;
+  message: string;
+}
+
+export interface InventoryImportResult {
+  rows: number;
+  connections: ConnectionProfile[];
+  brokers: BrokerConfig[];
+  identities: AuthIdentity[];
+  errors: InventoryRowError[];
 }
 
 export interface ConnectionProfile {
@@ -36,9 +97,290 @@ export interface ConnectionProfile {
   password?: string;
   clientId: string;
   clean: boolean;
+  followServerRedirects?: boolean;
+  uniqueClientId?: boolean;
+  replayUnackedPublishes?: boolean;
+  defaultSubscriptions?: SubscriptionPreset[];
+  labels?: Record<string, string>;
+  caCertPath?: string;
+  clientCertPath?: string;
+  clientKeyPath?: string;
+  environment?: ConnectionEnvironment;
+  idleDisconnect?: IdleDisconnect;
+}
+
+export interface IdleDisconnect {
+  /** Minutes without activity before disconnecting; 0 disables it. */
+  minutes: number;
+  /** Only user actions count as activity, not incoming messages. */
+  ignoreInbound: boolean;
+}
+
+export type ConnectionEnvironment = 'dev' | 'staging' | 'prod';
+
+export interface ConnectionDuplicateOverrides {
+  name?: string;
+  clientId?: string;
+  host?: string;
+  port?: number;
+}
+
+export interface ConnectionDuplicate {
+  profile: ConnectionProfile;
+  topicDocument?: ConnectionTopicDocument | null;
+}
+
+export interface SubscribeOptions {
+  noLocal?: boolean;
+  retainAsPublished?: boolean;
+  retainHandling?: 'onSubscribe' | 'onNewSubscribe' | 'never';
+}
+
+export interface SubscriptionPreset {
+  topic: string;
+  qos: 0 | 1 | 2;
+  options?: SubscribeOptions;
+}
+
+export interface SubscriptionPresetsPayload {
+  connectionId: string;
+  subscriptions: SubscriptionPreset[];
+}
+
+export type ConnectionStatus = 'disconnected' | 'connecting' | 'connected' | 'error' | 'takenOver';
+
+export interface ServerRedirect {
+  reference: string;
+  host: string;
+  port?: number;
+  permanent: boolean;
+  following: boolean;
+}
+
+export interface MqttPingEvent {
+  connectionId: string;
+  kind: 'sent' | 'response' | 'missed';
+  rttMs?: number;
+  timestamp: number;
+}
+
+export interface KeepAliveStats {
+  connectionId: string;
+  debug: boolean;
+  pingsSent: number;
+  responses: number;
+  missedPingresps: number;
+  lastRttMs?: number;
+  maxRttMs?: number;
+  lastPingAt?: number;
+  intervalSecs?: number;
+}
+
+export type HealthGrade = 'healthy' | 'degraded' | 'unhealthy';
+
+export interface ConnectionHealth {
+  connectionId: string;
+  score: number;
+  grade: HealthGrade;
+  reconnects: number;
+  errors: number;
+  missedPings: number;
+  rttAvgMs?: number;
+  rttJitterMs?: number;
+  ackLatencyMs?: number;
+  computedAt: number;
+}
+
+export interface ConnectionInfo {
+  connectionId: string;
+  transport: 'mqtt' | 'mqtts' | 'ws' | 'wss';
+  protocolVersion: number;
+  host: string;
+  port: number;
+  websocketUrl?: string;
+  resolvedAddresses: string[];
+  remoteAddress?: string;
+  localAddress?: string;
+  tlsVersion?: string;
+  tlsCipher?: string;
+  alpn?: string;
+  probeError?: string;
+  measuredAt: number;
+  keepAliveSecs?: number;
+}
+
+export interface DataWipeReport {
+  disconnected: string[];
+  historyDatabases: number;
+  exportFiles: number;
+  configEntries: string[];
+  bytesRemoved: number;
+  errors: string[];
+}
+
+export interface ConnectionDiskUsage {
+  connectionId: string;
+  name?: string | null;
+  dbBytes: number;
+  walBytes: number;
+  shmBytes: number;
+  totalBytes: number;
+  paused: boolean;
+}
+
+export interface PeekEndedPayload {
+  connectionId: string;
+  topic: string;
+  received: number;
+  reason: 'count' | 'timeout';
+}
+
+export interface PayloadSizeBucket {
+  maxBytes?: number | null;
+  count: number;
+}
+
+export interface PayloadSizeDistribution {
+  count: number;
+  totalBytes: number;
+  largestBytes: number;
+  buckets: PayloadSizeBucket[];
+}
+
+export interface TopicPayloadSizes extends PayloadSizeDistribution {
+  topic: string;
+}
+
+export interface PayloadSizeHistogram {
+  overall: PayloadSizeDistribution;
+  topics: TopicPayloadSizes[];
+  truncated: boolean;
+}
+
+export interface HistoryRepair {
+  fileName: string;
+  movedTo: string;
+  reason: string;
+  repairedAt: number;
+}
+
+export interface HistoryPauseStatus {
+  connectionId: string;
+  paused: boolean;
+  pausedAt?: number | null;
+  skippedMessages: number;
+}
+
+export interface HistoryDiskUsage {
+  historyDir: string;
+  connections: ConnectionDiskUsage[];
+  exportsBytes: number;
+  pendingDeleteBytes: number;
+  totalBytes: number;
+  availableBytes?: number | null;
+}
+
+export interface MultiPublishResult {
+  connectionId: string;
+  publishId?: number | null;
+  error?: string | null;
+}
+
+export interface PublishAckPayload {
+  connectionId: string;
+  publishId: number;
+  topic: string;
+  packetId: number;
+  qos: number;
+  packet: 'puback' | 'pubrec' | 'pubcomp';
+  latencyMs: number;
+  success: boolean;
+  reasonCode: number;
+  reason: string;
+  reasonString?: string | null;
+}
+
+export interface SubscriptionResultPayload {
+  connectionId: string;
+  kind: 'subscribe' | 'unsubscribe';
+  topic: string;
+  packetId: number;
+  success: boolean;
+  grantedQos?: number | null;
+  reasonCode: number;
+  reason: string;
+  reasonString?: string | null;
+}
+
+export type LowDiskAction = 'pauseWrites' | 'pruneOldest';
+
+export interface StorageGuardConfig {
+  enabled: boolean;
+  minFreeBytes: number;
+  action: LowDiskAction;
+  prunePercent: number;
+  checkIntervalSecs: number;
+}
+
+export interface StorageStatus {
+  low: boolean;
+  writesPaused: boolean;
+  availableBytes?: number | null;
+  minFreeBytes: number;
+  action: LowDiskAction;
+  skippedMessages: number;
+  prunedMessages: number;
+  lastError?: string | null;
+  checkedAt: number;
 }
 
-export type ConnectionStatus = 'disconnected' | 'connecting' | 'connected' | 'error';
+export type Qos2StepKind = 'publish' | 'retransmit' | 'pubRec' | 'pubRel' | 'pubComp';
+
+export interface Qos2Step {
+  kind: Qos2StepKind;
+  timestamp: number;
+  failure?: string;
+}
+
+export interface Qos2Flow {
+  connectionId: string;
+  packetId: number;
+  topic: string;
+  queuedAt: number;
+  steps: Qos2Step[];
+  complete: boolean;
+  totalMs?: number;
+}
+
+export interface EchoLatencyBucket {
+  start: number;
+  samples: number;
+  minMs: number;
+  maxMs: number;
+  avgMs: number;
+  /** Counts per `binUpperMs` bound, plus one slower bin. */
+  bins: number[];
+}
+
+export interface EchoLatencyStats {
+  topic: string;
+  samples: number;
+  minMs: number;
+  maxMs: number;
+  avgMs: number;
+  lastMs: number;
+  awaitingEcho: number;
+  bucketMs: number;
+  binUpperMs: number[];
+  buckets: EchoLatencyBucket[];
+}
+
+export interface SessionParameters {
+  clientId: string;
+  clientIdAssigned: boolean;
+  keepAliveSecs: number;
+  keepAliveOverridden: boolean;
+}
 
 export interface Message {
   id: string;
@@ -49,6 +391,14 @@ export interface Message {
   retain: boolean;
   direction: 'in' | 'out';
   timestamp: number;
+  userProperties?: UserProperty[];
+  /** The payload is base64 of bytes that were not UTF-8. */
+  isBinary?: boolean;
+}
+
+export interface UserProperty {
+  key: string;
+  value: string;
 }
 
 export interface Subscription {
@@ -56,6 +406,7 @@ export interface Subscription {
   qos: 0 | 1 | 2;
   color: string;
   muted?: boolean;
+  options?: SubscribeOptions;
 }
 
 export interface ConnectionState {
@@ -64,6 +415,9 @@ export interface ConnectionState {
   messages: Message[];
   subscriptions: Subscription[];
   lastError?: string;
+  session?: SessionParameters;
+  redirect?: ServerRedirect;
+  historyPaused?: HistoryPauseStatus;
 }
 
 export interface AiConfig {
@@ -85,6 +439,15 @@ export interface PayloadTemplate {
   name: string;
   topic: string;
   payload: string;
+  connectionId?: string;
+  updatedAt?: number;
+}
+
+export interface PayloadTemplateDraft {
+  name: string;
+  topic: string;
+  payload: string;
+  connectionId?: string;
 }
 
 export type TopicDirection = 'publish' | 'subscribe' | 'both';
@@ -144,9 +507,18 @@ export interface NativeAppConfig {
   activeConnectionId?: string;
   publisherTemplates?: PayloadTemplate[];
   connectionTopicDocs?: Record<string, ConnectionTopicDocument>;
+  exportDirectory?: string;
+  exportFilenameTemplate?: string;
+  exportTimeZone?: string;
   updatedAt?: number;
 }
 
+export interface ExportSettings {
+  exportDirectory?: string;
+  exportFilenameTemplate?: string;
+  exportTimeZone?: string;
+}
+
 export interface AppConfigPaths {
   configDir: string;
   configFile: string;
@@ -160,9 +532,200 @@ export interface HistoryMessageRecord {
   qos: 0 | 1 | 2;
   retain: boolean;
   direction: 'in' | 'out';
+  payloadTimestamp?: number | null;
+  userProperties?: UserProperty[];
+  isBinary?: boolean;
+}
+
+export type HistoryTimeSource = 'arrival' | 'payload';
+
+export type HistorySort = 'timestampAsc' | 'timestampDesc' | 'topic' | 'payloadSize';
+
+export type PayloadTimestampFormat = 'auto' | 'epochSeconds' | 'epochMillis' | 'rfc3339' | 'pattern';
+
+export interface PayloadTimestampRule {
+  topicFilter: string;
+  path: string;
+  format?: PayloadTimestampFormat;
+  pattern?: string | null;
+}
+
+export interface PayloadTimestampsConfig {
+  connections: Record<string, PayloadTimestampRule[]>;
+}
+
+export interface PinnedMessageRecord extends HistoryMessageRecord {
+  pinnedAt: number;
+  note?: string;
+}
+
+export interface MqttRequestResult {
+  correlationId: string;
+  responseTopic: string;
+  elapsedMs: number;
+  /** `null` when no reply arrived before the timeout. */
+  reply: {
+    topic: string;
+    payload: string;
+    qos: 0 | 1 | 2;
+    retain: boolean;
+    timestamp: number;
+    correlationId?: string | null;
+    responseTopic?: string | null;
+    userProperties?: UserProperty[];
+    isBinary?: boolean;
+  } | null;
+}
+
+export interface TopicUnreadCount {
+  topic: string;
+  unread: number;
+  lastMessageAt: number;
+  lastReadAt?: number | null;
 }
 
 export interface HistoryExportResult {
   path: string;
   count: number;
 }
+
+export type JobState = 'running' | 'completed' | 'failed' | 'cancelled';
+
+export interface RetainedSnapshotImportResult {
+  published: number;
+  skipped: number;
+}
+
+export interface PcapImportOptions {
+  brokerPorts?: number[];
+  protocolVersion?: 4 | 5;
+  topicFilter?: string;
+}
+
+export interface ReplayExportOptions {
+  format?: 'ndjson' | 'shell';
+  includeOutgoing?: boolean;
+  topicFilter?: string;
+  maxGapMs?: number;
+  speed?: number;
+  retain?: 'preserve' | 'strip' | 'skip';
+}
+
+export interface TopicListExportOptions {
+  format?: 'json' | 'csv';
+  includeSamples?: boolean;
+  includeSeen?: boolean;
+  topicFilter?: string;
+}
+
+export interface PcapImportResult {
+  packets: number;
+  streams: number;
+  imported: number;
+  filtered: number;
+  malformed: number;
+}
+
+export type ExportRequest =
+  | { type: 'history'; connectionId: string; format?: string; timeZone?: string; payloadRegex?: string }
+  | { type: 'historyAll'; connectionIds: string[]; format?: string; timeZone?: string }
+  | { type: 'influx'; connectionId: string; mapping: Record<string, unknown> }
+  | { type: 'elasticsearch'; connectionId: string; options: Record<string, unknown> }
+  | { type: 'mosquitto'; connectionId: string; outputFormat: string }
+  | { type: 'replay'; connectionId: string; options: ReplayExportOptions }
+  | { type: 'topics'; connectionId: string; options: TopicListExportOptions };
+
+export interface ExportRecord {
+  id: string;
+  label: string;
+  request: ExportRequest;
+  fromTs?: number | null;
+  toTs?: number | null;
+  path: string;
+  count: number;
+  startedAt: number;
+  durationMs: number;
+}
+
+export interface JobStatus<T = unknown> {
+  jobId: string;
+  kind: string;
+  label: string;
+  state: JobState;
+  done: number;
+  total?: number;
+  startedAt: number;
+  finishedAt?: number;
+  result?: T;
+  error?: string;
+}
+
+export type JsonPreviewKind = 'object' | 'array' | 'string' | 'number' | 'bool' | 'null';
+
+export interface JsonPreviewNode {
+  key?: string | null;
+  path: string;
+  kind: JsonPreviewKind;
+  size?: number | null;
+  value?: string | null;
+  valueTruncated: boolean;
+  children?: JsonPreviewNode[] | null;
+  offset: number;
+  remaining: number;
+}
+
+export interface BenchmarkWorkload {
+  rateSteps: number[];
+  stepSecs: number;
+  fanOutSubscribers: number;
+  fanOutRate: number;
+  payloadBytes: number;
+  qos: 0 | 1 | 2;
+}
+
+export type BenchmarkStepKind = 'rateLadder' | 'fanOut';
+
+export interface BenchmarkStep {
+  kind: BenchmarkStepKind;
+  targetRate: number;
+  subscribers: number;
+  sent: number;
+  expected: number;
+  received: number;
+  publishErrors: number;
+  connectionErrors: number;
+  sentPerSec: number;
+  receivedPerSec: number;
+  lossRate: number;
+  latencyP50Ms?: number | null;
+  latencyP95Ms?: number | null;
+  latencyP99Ms?: number | null;
+  latencyMaxMs?: number | null;
+  error?: string | null;
+}
+
+export interface BenchmarkBrokerResult {
+  connectionId: string;
+  name: string;
+  host: string;
+  port: number;
+  steps: BenchmarkStep[];
+  error?: string | null;
+}
+
+export interface BenchmarkComparison {
+  step: number;
+  kind: BenchmarkStepKind;
+  targetRate: number;
+  subscribers: number;
+  bestThroughput?: string | null;
+  bestLatency?: string | null;
+}
+
+export interface BenchmarkReport {
+  workload: BenchmarkWorkload;
+  startedAt: number;
+  finishedAt: number;
+  brokers: BenchmarkBrokerResult[];
+  comparisons: BenchmarkComparison[];
+}